@@ -31,15 +31,18 @@ pub fn apply_effects(
   {
     OverlayToggle::Toggle =>
     {
-      app.overlay = match app.overlay
+      if let crate::app::Overlay::Messages = app.overlay
       {
-        crate::app::Overlay::Messages => crate::app::Overlay::None,
-        _ => crate::app::Overlay::Messages,
-      };
+        app.overlay = crate::app::Overlay::None;
+      }
+      else
+      {
+        app.set_text_overlay(crate::app::Overlay::Messages);
+      }
     }
     OverlayToggle::Show =>
     {
-      app.overlay = crate::app::Overlay::Messages;
+      app.set_text_overlay(crate::app::Overlay::Messages);
     }
     OverlayToggle::Hide | OverlayToggle::None =>
     {}
@@ -49,21 +52,18 @@ pub fn apply_effects(
   {
     OverlayToggle::Toggle =>
     {
-      app.overlay = match app.overlay
+      if let crate::app::Overlay::Output { .. } = app.overlay
+      {
+        app.overlay = crate::app::Overlay::None;
+      }
+      else
       {
-        crate::app::Overlay::Output { .. } => crate::app::Overlay::None,
-        _ => crate::app::Overlay::Output {
-          title: String::from("Output"),
-          lines: Vec::new(),
-        },
-      };
+        app.display_output("Output", "");
+      }
     }
     OverlayToggle::Show =>
     {
-      app.overlay = crate::app::Overlay::Output {
-        title: String::from("Output"),
-        lines: Vec::new(),
-      };
+      app.display_output("Output", "");
     }
     OverlayToggle::Hide | OverlayToggle::None =>
     {}
@@ -77,10 +77,24 @@ pub fn apply_effects(
   {
     app.add_message(msg);
   }
+  if let Some(msg) = fx.warning_text.as_ref()
+  {
+    app.add_warning(msg);
+  }
   if let Some(err) = fx.error_text.as_ref()
   {
-    app.add_message(&format!("Error: {}", err));
-    app.overlay = crate::app::Overlay::Messages;
+    app.add_error(err);
+    app.set_text_overlay(crate::app::Overlay::Messages);
+  }
+  if let Some((level, text)) = fx.notify.as_ref()
+  {
+    let level = match level.to_ascii_lowercase().as_str()
+    {
+      "warn" | "warning" => crate::app::MessageLevel::Warn,
+      "error" => crate::app::MessageLevel::Error,
+      _ => crate::app::MessageLevel::Info,
+    };
+    app.notify(level, text);
   }
   if fx.clear_messages
   {
@@ -89,12 +103,36 @@ pub fn apply_effects(
   if let Some(name) = fx.theme_set_name.as_ref()
     && !app.set_theme_by_name(name)
   {
-    app.add_message(&format!("Theme '{}' not found", name));
+    app.add_error(&format!("Theme '{}' not found", name));
   }
   if let Some(cmd) = fx.preview_run_cmd.as_ref()
   {
     app.start_preview_process(cmd);
   }
+  if let Some(names) = fx.columns_reset.as_ref()
+  {
+    app.invalidate_columns(names);
+  }
+  if let Some(cmd) = fx.interactive.as_deref()
+  {
+    app.run_interactive(cmd);
+  }
+  if let Some(cmd) = fx.run_async.as_deref()
+  {
+    app.start_command_run(cmd);
+  }
+  for err in &fx.fs_errors
+  {
+    app.add_error(err);
+  }
+  for job in fx.fs_jobs
+  {
+    app.enqueue_fs_job(job);
+  }
+  if let Some(dialog) = fx.dialog
+  {
+    app.open_dialog(dialog);
+  }
 
   match fx.theme_picker
   {
@@ -141,6 +179,11 @@ pub fn apply_effects(
       );
       app.request_delete_selected();
     }
+    crate::actions::effects::ConfirmCommand::TrashSelected =>
+    {
+      crate::trace::log("[apply] confirm=trash_selected");
+      app.request_trash_selected();
+    }
     crate::actions::effects::ConfirmCommand::None =>
     {}
   }
@@ -166,6 +209,14 @@ pub fn apply_effects(
     crate::actions::effects::ClipboardCommand::CopyArm => app.copy_selection(),
     crate::actions::effects::ClipboardCommand::MoveArm => app.move_selection(),
     crate::actions::effects::ClipboardCommand::Paste => app.paste_clipboard(),
+    crate::actions::effects::ClipboardCommand::PasteSymlink =>
+    {
+      app.paste_links(crate::core::fs_ops::LinkKind::Symlink, None)
+    }
+    crate::actions::effects::ClipboardCommand::PasteHardlink =>
+    {
+      app.paste_links(crate::core::fs_ops::LinkKind::Hardlink, None)
+    }
     crate::actions::effects::ClipboardCommand::Clear => app.clear_clipboard(),
     crate::actions::effects::ClipboardCommand::None =>
     {}
@@ -246,6 +297,15 @@ pub fn apply_config_overlay(
     });
   }
 
+  // Pane visibility: affects layout
+  if app.config.ui.show_parent != data.ui.show_parent
+    || app.config.ui.show_preview != data.ui.show_preview
+  {
+    app.config.ui.show_parent = data.ui.show_parent;
+    app.config.ui.show_preview = data.ui.show_preview;
+    layout_change = true;
+  }
+
   // Hidden files: change listing
   if app.config.ui.show_hidden != data.ui.show_hidden
   {
@@ -342,6 +402,8 @@ pub fn apply_config_overlay(
       selection_bar_fg:      th.selection_bar_fg.clone(),
       selection_bar_copy_fg: th.selection_bar_copy_fg.clone(),
       selection_bar_move_fg: th.selection_bar_move_fg.clone(),
+      // Not exposed to Lua actions; keep the loaded rules
+      entry_colors:          cur_theme.entry_colors.clone(),
     };
     Some(t)
   }
@@ -14,6 +14,7 @@ use super::{
     apply_effects,
   },
   internal::{
+    InternalAction,
     execute_internal_action,
     parse_internal_action,
   },
@@ -26,8 +27,9 @@ use crate::{
 /// Parse and execute an action string.
 ///
 /// Returns `Ok(true)` when at least one action ran successfully. Supports
-/// multiple actions separated by `;`, Lua actions via `run_lua:<idx>`, and
-/// internal actions parsed by [`internal`](super::internal).
+/// multiple actions separated by `;`, Lua actions via `run_lua:<idx>`, shell
+/// commands bound by `lsv.map_command` via `shell:<idx>`, and internal
+/// actions parsed by [`internal`](super::internal).
 pub fn dispatch_action(
   app: &mut App,
   action: &str,
@@ -79,6 +81,14 @@ pub fn dispatch_action(
     }
   }
 
+  // Shell command bound by lsv.map_command
+  if let Some(rest) = action.strip_prefix("shell:")
+    && let Ok(idx) = rest.parse::<usize>()
+  {
+    trace::log(format!("[dispatch] action='{}'", action));
+    return Ok(app.run_mapped_command(idx));
+  }
+
   // Internal action
   if let Some(int) = parse_internal_action(action)
   {
@@ -95,3 +105,65 @@ pub fn dispatch_action(
   }
   Ok(false)
 }
+
+/// Commands that a count repeats, like `3n` for the third search match.
+const REPEATABLE_COMMANDS: &[&str] = &["next", "prev", "tab_next", "tab_prev"];
+
+/// Execute an action typed after a count, as in `3gt`.
+///
+/// `gg`/`G` go to row `count`, moves, scrolls and tab switches repeat
+/// `count` times and `select_toggle` toggles `count` rows from the cursor
+/// down. Other actions run once; Lua actions see the count as
+/// `config.context.count`.
+pub fn dispatch_action_with_count(
+  app: &mut App,
+  action: &str,
+  count: usize,
+) -> io::Result<bool>
+{
+  let repeat = match parse_internal_action(action)
+  {
+    Some(InternalAction::GoTop | InternalAction::GoBottom) =>
+    {
+      app.go_to_row(count);
+      return Ok(true);
+    }
+    Some(InternalAction::Move(n)) =>
+    {
+      app.move_cursor(n.saturating_mul(count as isize));
+      return Ok(true);
+    }
+    Some(InternalAction::RunCommand(cmd))
+      if cmd.trim().eq_ignore_ascii_case("select_toggle") =>
+    {
+      app.toggle_select_rows(count);
+      return Ok(true);
+    }
+    Some(
+      InternalAction::TabNext
+      | InternalAction::TabPrev
+      | InternalAction::PreviewScroll(_)
+      | InternalAction::PreviewPage(_)
+      | InternalAction::PreviewScrollX(_),
+    ) => true,
+    Some(InternalAction::RunCommand(cmd)) =>
+    {
+      REPEATABLE_COMMANDS.iter().any(|c| cmd.trim().eq_ignore_ascii_case(c))
+    }
+    _ => false,
+  };
+  if repeat
+  {
+    let mut any = false;
+    for _ in 0..count
+    {
+      any |= dispatch_action(app, action)?;
+    }
+    return Ok(any);
+  }
+  trace::log(format!("[dispatch] count={} action='{}'", count, action));
+  app.action_count = Some(count);
+  let ran = dispatch_action(app, action);
+  app.action_count = None;
+  ran
+}
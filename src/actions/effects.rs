@@ -20,7 +20,9 @@ pub struct ActionEffects
   pub output_overlay:  OverlayToggle,
   pub output:          Option<(String, String)>, // (title, text)
   pub message_text:    Option<String>,
+  pub warning_text:    Option<String>,
   pub error_text:      Option<String>,
+  pub notify:          Option<(String, String)>, // (level, text)
   pub theme_picker:    ThemePickerCommand,
   pub theme_set_name:  Option<String>,
   pub prompt:          PromptCommand,
@@ -32,7 +34,21 @@ pub struct ActionEffects
   pub select_paths:    Option<Vec<String>>,
   pub clear_messages:  bool,
   pub preview_run_cmd: Option<String>,
+  /// Lua columns to recompute; `*` stands for all of them.
+  pub columns_reset:   Option<Vec<String>>,
+  /// Command to run attached to the terminal; empty starts `$SHELL`.
+  pub interactive:     Option<String>,
+  /// Command whose output streams into the Output panel.
+  pub run_async:       Option<String>,
+  /// File operations `lsv.fs` checked, to queue as jobs.
+  pub fs_jobs:         Vec<FsJob>,
+  /// Operations `lsv.fs` refused or that failed outright.
+  pub fs_errors:       Vec<String>,
+  /// Dialog to open for an `lsv.ui` callback.
+  pub dialog:          Option<DialogRequest>,
 }
+use std::path::PathBuf;
+
 use mlua::Table;
 
 impl From<&str> for OverlayToggle
@@ -79,10 +95,19 @@ pub fn parse_effects_from_lua(tbl: &Table) -> ActionEffects
   {
     fx.message_text = Some(m);
   }
+  if let Ok(w) = tbl.get::<String>("warning_text")
+  {
+    fx.warning_text = Some(w);
+  }
   if let Ok(e) = tbl.get::<String>("error_text")
   {
     fx.error_text = Some(e);
   }
+  if let Ok(text) = tbl.get::<String>("notify_text")
+  {
+    let level = tbl.get::<String>("notify_level").unwrap_or_default();
+    fx.notify = Some((level, text));
+  }
   fx.clear_messages = tbl.get::<bool>("clear_messages").unwrap_or(false);
   // redraw/quit
   fx.redraw = tbl.get::<bool>("redraw").unwrap_or(false);
@@ -102,6 +127,23 @@ pub fn parse_effects_from_lua(tbl: &Table) -> ActionEffects
   {
     fx.preview_run_cmd = Some(cmd);
   }
+  if let Ok(list_tbl) = tbl.get::<Table>("columns_reset")
+  {
+    fx.columns_reset =
+      Some(list_tbl.sequence_values::<String>().flatten().collect());
+  }
+  if let Ok(cmd) = tbl.get::<String>("interactive")
+  {
+    fx.interactive = Some(cmd);
+  }
+  if let Ok(cmd) = tbl.get::<String>("run_async")
+  {
+    fx.run_async = Some(cmd);
+  }
+  if let Ok(t) = tbl.get::<Table>("dialog")
+  {
+    fx.dialog = dialog_request(&t);
+  }
   if let Ok(s) = tbl.get::<String>("find")
   {
     fx.find = match s.as_str()
@@ -150,6 +192,11 @@ pub fn parse_effects_from_lua(tbl: &Table) -> ActionEffects
         crate::trace::log("[effects] confirm request 'delete_selected'");
         fx.confirm = ConfirmCommand::DeleteSelected;
       }
+      "trash" | "trash_selected" =>
+      {
+        crate::trace::log("[effects] confirm request 'trash_selected'");
+        fx.confirm = ConfirmCommand::TrashSelected;
+      }
       _ =>
       {}
     }
@@ -191,6 +238,8 @@ pub fn parse_effects_from_lua(tbl: &Table) -> ActionEffects
       "copy_arm" => ClipboardCommand::CopyArm,
       "move_arm" => ClipboardCommand::MoveArm,
       "paste" => ClipboardCommand::Paste,
+      "paste_symlink" => ClipboardCommand::PasteSymlink,
+      "paste_hardlink" => ClipboardCommand::PasteHardlink,
       "clear" => ClipboardCommand::Clear,
       _ => ClipboardCommand::None,
     };
@@ -199,6 +248,74 @@ pub fn parse_effects_from_lua(tbl: &Table) -> ActionEffects
   fx
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsOp
+{
+  Copy,
+  Move,
+  Remove,
+}
+
+/// A checked `lsv.fs` operation: copies and moves take `items[i]` to
+/// `dests[i]`, removals have no destinations.
+#[derive(Debug, Clone)]
+pub struct FsJob
+{
+  pub op:    FsOp,
+  pub items: Vec<PathBuf>,
+  pub dests: Vec<PathBuf>,
+}
+
+/// What `lsv.fs` checked and refused during a Lua call. The helpers fill it
+/// on the Rust side, where scripts cannot reach, so only operations that
+/// passed the checks become jobs.
+#[derive(Debug, Default)]
+pub struct FsRequests
+{
+  pub jobs:   Vec<FsJob>,
+  pub errors: Vec<String>,
+}
+
+/// The `{ kind, title, text, items }` table `lsv.ui` leaves in `dialog`.
+fn dialog_request(t: &Table) -> Option<DialogRequest>
+{
+  let title = t.get::<String>("title").unwrap_or_default();
+  match t.get::<String>("kind").ok()?.as_str()
+  {
+    "prompt" => Some(DialogRequest::Prompt {
+      title,
+      text: t.get::<String>("text").unwrap_or_default(),
+    }),
+    "confirm" => Some(DialogRequest::Confirm { question: title }),
+    "select" => Some(DialogRequest::Select {
+      title,
+      items: t
+        .get::<Table>("items")
+        .map(|l| l.sequence_values::<String>().flatten().collect())
+        .unwrap_or_default(),
+    }),
+    _ => None,
+  }
+}
+
+/// A dialog opened by `lsv.ui`, whose answer goes to a Lua callback.
+#[derive(Debug, Clone)]
+pub enum DialogRequest
+{
+  Prompt
+  {
+    title: String, text: String
+  },
+  Confirm
+  {
+    question: String
+  },
+  Select
+  {
+    title: String, items: Vec<String>
+  },
+}
+
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ThemePickerCommand
 {
@@ -222,6 +339,7 @@ pub enum ConfirmCommand
   #[default]
   None,
   DeleteSelected,
+  TrashSelected,
 }
 
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
@@ -241,6 +359,8 @@ pub enum ClipboardCommand
   CopyArm,
   MoveArm,
   Paste,
+  PasteSymlink,
+  PasteHardlink,
   Clear,
 }
 
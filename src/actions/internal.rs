@@ -7,7 +7,11 @@ pub enum SortKey
   Name,
   Size,
   MTime,
-  CTime,
+  Created,
+  /// Name with digit runs compared as numbers ("file2" before "file10").
+  Natural,
+  /// Extension, then name.
+  Extension,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -20,14 +24,97 @@ pub(crate) enum InternalAction
   SetDisplayMode(crate::app::DisplayMode),
   GoTop,
   GoBottom,
+  /// Move the cursor by this many rows.
+  Move(isize),
+  NavRight,
+  NavOpen,
+  NavLeft,
+  NavParent,
+  SetMark,
+  GotoMark,
+  RecordMacro,
+  PlayMacro,
+  SelectRegister,
   RunCommand(String),
   ClipboardCopy,
   ClipboardMove,
   ClipboardPaste,
+  ClipboardPasteLink(crate::core::fs_ops::LinkKind),
   ClipboardClear,
   CloseOverlays,
+  TabNew,
+  TabNext,
+  TabPrev,
+  TabClose,
+  PreviewScroll(isize),
+  PreviewPage(isize),
+  /// Scroll the unwrapped preview sideways by this many columns.
+  PreviewScrollX(isize),
+  PreviewToggleWrap,
+  PreviewFocus,
 }
 
+/// Built-in actions for keymaps, each with a short description, as listed
+/// by `:help`. Aliases accepted by [`parse_internal_action`] are left out.
+pub const ACTIONS: &[(&str, &str)] = &[
+  ("nav:up", "Move the cursor up a row"),
+  ("nav:down", "Move the cursor down a row"),
+  ("nav:top", "Go to the first row"),
+  ("nav:bottom", "Go to the last row"),
+  ("nav:left", "Go to the parent directory, or collapse in the tree view"),
+  ("nav:right", "Enter the directory, or expand in the tree view"),
+  ("nav:open", "Open the file or enter the directory"),
+  ("nav:parent", "Go to the parent directory"),
+  ("mark:set", "Mark the current directory under the next key"),
+  ("mark:goto", "Go to the directory marked under the next key"),
+  ("macro:record", "Record a macro into the next key's register, or stop"),
+  ("macro:play", "Play the macro in the next key's register"),
+  (
+    "register:select",
+    "Use the next key's register for the next copy, move or paste",
+  ),
+  ("sort:name", "Sort by name"),
+  ("sort:natural", "Sort by name, numbers by value"),
+  ("sort:size", "Sort by size"),
+  ("sort:mtime", "Sort by modified time"),
+  ("sort:created", "Sort by created time"),
+  ("sort:extension", "Sort by extension"),
+  ("sort:reverse:toggle", "Reverse the sort order"),
+  ("show:none", "Hide the info column"),
+  ("show:size", "Show sizes in the info column"),
+  ("show:modified", "Show modified times in the info column"),
+  ("show:created", "Show created times in the info column"),
+  ("display:absolute", "Show absolute sizes and dates"),
+  ("display:friendly", "Show friendly sizes and dates"),
+  ("clipboard:copy", "Copy the selection to the clipboard"),
+  ("clipboard:move", "Cut the selection to the clipboard"),
+  ("clipboard:paste", "Paste the clipboard here"),
+  ("clipboard:paste_symlink", "Paste the clipboard as symlinks"),
+  ("clipboard:paste_hardlink", "Paste the clipboard as hard links"),
+  ("clipboard:clear", "Clear the selection"),
+  ("overlay:close", "Close the open overlay"),
+  ("tab:new", "Open a tab on the current directory"),
+  ("tab:next", "Switch to the next tab"),
+  ("tab:prev", "Switch to the previous tab"),
+  ("tab:close", "Close the current tab"),
+  ("preview:scroll_down", "Scroll the preview down a line"),
+  ("preview:scroll_up", "Scroll the preview up a line"),
+  ("preview:page_down", "Scroll the preview down a page"),
+  ("preview:page_up", "Scroll the preview up a page"),
+  ("preview:scroll_left", "Scroll the unwrapped preview left"),
+  ("preview:scroll_right", "Scroll the unwrapped preview right"),
+  ("preview:toggle_wrap", "Wrap or cut long preview lines"),
+  (
+    "preview:focus",
+    "Enter or leave preview focus, where h/j/k/l scroll the preview",
+  ),
+  ("quit", "Quit lsv"),
+  ("cmd:<command>", "Run a : command line"),
+];
+
+/// Columns moved by one horizontal preview scroll.
+pub(crate) const PREVIEW_HSCROLL_STEP: isize = 4;
+
 pub(crate) fn parse_internal_action(s: &str) -> Option<InternalAction>
 {
   let low = s.trim().to_ascii_lowercase();
@@ -81,8 +168,26 @@ pub(crate) fn parse_internal_action(s: &str) -> Option<InternalAction>
   {
     return Some(InternalAction::GoBottom);
   }
-  if let Some(cmd) = low.strip_prefix("cmd:")
+  match low.as_str()
   {
+    "nav:down" => return Some(InternalAction::Move(1)),
+    "nav:up" => return Some(InternalAction::Move(-1)),
+    "nav:right" => return Some(InternalAction::NavRight),
+    "nav:open" => return Some(InternalAction::NavOpen),
+    "nav:left" => return Some(InternalAction::NavLeft),
+    "nav:parent" => return Some(InternalAction::NavParent),
+    "mark:set" => return Some(InternalAction::SetMark),
+    "mark:goto" => return Some(InternalAction::GotoMark),
+    "macro:record" => return Some(InternalAction::RecordMacro),
+    "macro:play" => return Some(InternalAction::PlayMacro),
+    "register:select" => return Some(InternalAction::SelectRegister),
+    _ =>
+    {}
+  }
+  if low.starts_with("cmd:")
+  {
+    // Arguments keep their case (grep patterns, shell commands)
+    let cmd = &s.trim()["cmd:".len()..];
     return Some(InternalAction::RunCommand(cmd.to_string()));
   }
   if low == "clipboard:copy"
@@ -97,6 +202,18 @@ pub(crate) fn parse_internal_action(s: &str) -> Option<InternalAction>
   {
     return Some(InternalAction::ClipboardPaste);
   }
+  if low == "clipboard:paste_symlink"
+  {
+    return Some(InternalAction::ClipboardPasteLink(
+      crate::core::fs_ops::LinkKind::Symlink,
+    ));
+  }
+  if low == "clipboard:paste_hardlink"
+  {
+    return Some(InternalAction::ClipboardPasteLink(
+      crate::core::fs_ops::LinkKind::Hardlink,
+    ));
+  }
   if low == "clipboard:clear"
   {
     return Some(InternalAction::ClipboardClear);
@@ -105,6 +222,47 @@ pub(crate) fn parse_internal_action(s: &str) -> Option<InternalAction>
   {
     return Some(InternalAction::CloseOverlays);
   }
+  match low.as_str()
+  {
+    "tab:new" | "tab_new" => return Some(InternalAction::TabNew),
+    "tab:next" | "tab_next" => return Some(InternalAction::TabNext),
+    "tab:prev" | "tab_prev" => return Some(InternalAction::TabPrev),
+    "tab:close" | "tab_close" => return Some(InternalAction::TabClose),
+    "preview:scroll_up" | "preview_scroll_up" =>
+    {
+      return Some(InternalAction::PreviewScroll(-1));
+    }
+    "preview:scroll_down" | "preview_scroll_down" =>
+    {
+      return Some(InternalAction::PreviewScroll(1));
+    }
+    "preview:page_up" | "preview_scroll_page_up" =>
+    {
+      return Some(InternalAction::PreviewPage(-1));
+    }
+    "preview:page_down" | "preview_scroll_page_down" =>
+    {
+      return Some(InternalAction::PreviewPage(1));
+    }
+    "preview:scroll_left" | "preview_scroll_left" =>
+    {
+      return Some(InternalAction::PreviewScrollX(-PREVIEW_HSCROLL_STEP));
+    }
+    "preview:scroll_right" | "preview_scroll_right" =>
+    {
+      return Some(InternalAction::PreviewScrollX(PREVIEW_HSCROLL_STEP));
+    }
+    "preview:toggle_wrap" | "preview_toggle_wrap" =>
+    {
+      return Some(InternalAction::PreviewToggleWrap);
+    }
+    "preview:focus" | "preview_focus" =>
+    {
+      return Some(InternalAction::PreviewFocus);
+    }
+    _ =>
+    {}
+  }
   None
 }
 
@@ -174,6 +332,27 @@ pub(crate) fn execute_internal_action(
         app.refresh_preview();
       }
     }
+    InternalAction::Move(n) => app.move_cursor(n),
+    InternalAction::NavRight => app.nav_right(),
+    InternalAction::NavOpen => app.nav_open(),
+    InternalAction::NavLeft => app.nav_left(),
+    InternalAction::NavParent => app.go_to_parent(),
+    InternalAction::SetMark =>
+    {
+      app.pending_mark = true;
+      app.add_message("Mark: type a letter to save this directory");
+    }
+    InternalAction::GotoMark =>
+    {
+      app.pending_goto = true;
+      app.add_message("Goto: type a letter to jump to its mark");
+    }
+    InternalAction::RecordMacro => app.macro_record_key(),
+    InternalAction::PlayMacro =>
+    {
+      app.macro_play_key(app.action_count.unwrap_or(1))
+    }
+    InternalAction::SelectRegister => app.register_key(),
     InternalAction::RunCommand(cmd) =>
     {
       app.execute_command_line(&cmd);
@@ -190,6 +369,10 @@ pub(crate) fn execute_internal_action(
     {
       app.paste_clipboard();
     }
+    InternalAction::ClipboardPasteLink(kind) =>
+    {
+      app.paste_links(kind, None);
+    }
     InternalAction::ClipboardClear =>
     {
       app.clear_all_selected();
@@ -199,6 +382,15 @@ pub(crate) fn execute_internal_action(
       app.overlay = crate::app::Overlay::None;
       app.force_full_redraw = true;
     }
+    InternalAction::TabNew => app.tab_new(),
+    InternalAction::TabNext => app.tab_next(),
+    InternalAction::TabPrev => app.tab_prev(),
+    InternalAction::TabClose => app.tab_close(),
+    InternalAction::PreviewScroll(n) => app.preview_scroll(n),
+    InternalAction::PreviewPage(n) => app.preview_scroll_page(n),
+    InternalAction::PreviewScrollX(n) => app.preview_scroll_x(n),
+    InternalAction::PreviewToggleWrap => app.toggle_preview_wrap(),
+    InternalAction::PreviewFocus => app.toggle_preview_focus(),
   }
 }
 
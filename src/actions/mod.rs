@@ -5,5 +5,8 @@ mod dispatcher;
 pub mod effects;
 pub mod internal;
 
-pub use dispatcher::dispatch_action;
+pub use dispatcher::{
+  dispatch_action,
+  dispatch_action_with_count,
+};
 pub(crate) use internal::SortKey;
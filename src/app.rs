@@ -23,27 +23,111 @@ pub use state::{
     CommandPaneState,
     ConfirmKind,
     ConfirmState,
+    DirBatch,
     DirEntryInfo,
+    DirLoad,
     DisplayMode,
+    FinderBatch,
+    FinderIndex,
+    FinderKind,
+    FinderState,
+    GrepMatch,
+    GrepState,
+    HelpState,
     InfoMode,
     KeyState,
+    LuaDialog,
     LuaRuntime,
+    MountsState,
     Overlay,
+    PaletteEntry,
+    PaletteState,
+    PaneAreas,
+    PasteConflicts,
+    PendingArchive,
+    PendingPreview,
+    PreviewContent,
     PreviewState,
     PromptKind,
     PromptState,
+    RunningGrep,
     RunningPreview,
+    SelectState,
+    TabState,
+    TextView,
     ThemePickerEntry,
     ThemePickerState,
+    TrashBrowserState,
 };
 
+pub(crate) mod archive;
+pub(crate) mod checksums;
+pub(crate) mod columns;
 pub(crate) mod commands;
+pub(crate) mod confirm;
+pub(crate) mod dir_sizes;
+pub(crate) mod disk_space;
+pub(crate) mod drives;
+pub(crate) mod events;
+pub(crate) mod file_info;
+pub(crate) mod filter;
+pub use events::AppEvent;
+pub(crate) mod finder;
+pub(crate) mod flatten;
+pub(crate) mod grep;
+pub(crate) mod help;
+pub(crate) mod history;
+pub(crate) mod interactive;
+pub(crate) mod jobs;
+pub use jobs::{
+    Job,
+    JobKind,
+    JobStatus,
+    PasteTarget,
+};
+pub(crate) mod jump;
 pub(crate) mod keys;
+pub(crate) mod lua_commands;
+pub(crate) mod lua_ui;
+pub(crate) mod macros;
 pub(crate) mod marks;
+pub(crate) mod messages;
+pub(crate) mod meta_cache;
+pub use meta_cache::EntryMeta;
+pub use messages::{
+    Message,
+    MessageLevel,
+};
+pub(crate) mod mounts;
 pub(crate) mod nav;
+pub(crate) mod notify;
+pub use notify::Notification;
+pub(crate) mod open;
 pub(crate) mod overlays_api;
+pub(crate) mod palette;
+pub(crate) mod paste;
+pub(crate) mod permissions;
+pub(crate) mod picker;
+pub use picker::{
+    PickMode,
+    write_picked,
+};
 pub(crate) mod preview_ctrl;
+pub(crate) mod registers;
+pub(crate) mod reload;
+pub(crate) mod remote;
+pub(crate) mod rename;
+pub(crate) mod run;
+pub(crate) mod search;
 pub(crate) mod selection;
+pub(crate) mod session;
+pub(crate) mod tabs;
+pub(crate) mod template_fns;
+pub(crate) mod text_view;
+pub(crate) mod trash;
+pub(crate) mod tree;
+pub(crate) mod watch;
+pub(crate) mod yank;
 
 // Re-exported types live in state.rs
 
@@ -54,88 +138,29 @@ impl App
     pub fn new() -> io::Result<Self>
     {
         let cwd = env::current_dir()?;
-        // Temporary initial read with default sort (Name asc)
-        let current_entries = {
-            // Build a temporary App-like context for sorting
-            let mut tmp = Vec::new();
-            for de in (fs::read_dir(&cwd)?).flatten()
-            {
-                let path = de.path();
-                let name = de.file_name().to_string_lossy().to_string();
-                if let Ok(ft) = de.file_type()
-                {
-                    let meta = fs::metadata(&path).ok();
-                    let size = meta.as_ref().map(|m| m.len()).unwrap_or(0);
-                    let mtime = meta.as_ref().and_then(|m| m.modified().ok());
-                    let ctime = meta.as_ref().and_then(|m| m.created().ok());
-                    tmp.push(DirEntryInfo {
-                        name,
-                        path,
-                        is_dir: ft.is_dir(),
-                        size,
-                        mtime,
-                        ctime,
-                    });
-                }
-            }
-            tmp.sort_by(|a, b| match (a.is_dir, b.is_dir)
-                {
-                    (true, false) => std::cmp::Ordering::Less,
-                    (false, true) => std::cmp::Ordering::Greater,
-                    _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
-                });
-            tmp
-        };
-        let parent_entries = if let Some(p) = cwd.parent()
-        {
-            // Same initial read for parent
-            let mut tmp = Vec::new();
-            for de in (fs::read_dir(p)?).flatten()
-            {
-                let path = de.path();
-                let name = de.file_name().to_string_lossy().to_string();
-                if let Ok(ft) = de.file_type()
-                {
-                    let meta = fs::metadata(&path).ok();
-                    let size = meta.as_ref().map(|m| m.len()).unwrap_or(0);
-                    let mtime = meta.as_ref().and_then(|m| m.modified().ok());
-                    let ctime = meta.as_ref().and_then(|m| m.created().ok());
-                    tmp.push(DirEntryInfo {
-                        name,
-                        path,
-                        is_dir: ft.is_dir(),
-                        size,
-                        mtime,
-                        ctime,
-                    });
-                }
-            }
-            tmp.sort_by(|a, b| match (a.is_dir, b.is_dir)
-                {
-                    (true, false) => std::cmp::Ordering::Less,
-                    (false, true) => std::cmp::Ordering::Greater,
-                    _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
-                });
-            tmp
-        }
-        else
-        {
-            Vec::new()
-        };
+        // Surface unreadable start directories early; entries themselves are
+        // streamed in by refresh_lists once configuration is applied.
+        fs::read_dir(&cwd)?;
 
-        let mut list_state = ListState::default();
-        if !current_entries.is_empty()
-        {
-            list_state.select(Some(0));
-        }
+        let list_state = ListState::default();
+        let first_tab = TabState {
+            cwd:           cwd.clone(),
+            list_state:    ListState::default(),
+            selected_name: None,
+            selected:      std::collections::HashSet::new(),
+            sort_key:      SortKey::Name,
+            sort_reverse:  false,
+        };
         let mut app = Self {
             cwd,
-            current_entries,
-            parent_entries,
+            current_entries: Vec::new(),
+            parent_entries: Vec::new(),
             list_state,
             preview: PreviewState::default(),
             recent_messages: Vec::new(),
             overlay: Overlay::None,
+            text_view: TextView::default(),
+            notifications: Vec::new(),
             config: crate::config::Config::default(),
             keys: KeyState::default(),
             force_full_redraw: false,
@@ -149,10 +174,47 @@ impl App
             should_quit: false,
             search_query: None,
             _search_locked: false,
+            filters: std::collections::HashMap::new(),
+            cursor_memory: std::collections::HashMap::new(),
             marks: std::collections::HashMap::new(),
             pending_mark: false,
             pending_goto: false,
+            pending_count: None,
+            action_count: None,
+            macros: macros::Macros::default(),
+            registers: registers::Registers::default(),
             running_preview: None,
+            pending_preview: None,
+            pending_archive: None,
+            running_grep: None,
+            command_run: None,
+            finder_index: None,
+            current_load: None,
+            parent_load: None,
+            tabs: vec![first_tab],
+            active_tab: 0,
+            last_tab: None,
+            jobs: jobs::JobQueue::default(),
+            watch: watch::WatchState::default(),
+            checksums: checksums::ChecksumState::default(),
+            dir_sizes: dir_sizes::DirSizeState::default(),
+            disk_space: disk_space::DiskSpaceState::default(),
+            pane_areas: PaneAreas::default(),
+            last_click: None,
+            flatten: None,
+            tree: tree::TreeState::default(),
+            pick_mode: None,
+            picked: Vec::new(),
+            history: history::CommandHistory::default(),
+            frecency: jump::Frecency::default(),
+            events: events::EventState::default(),
+            lua_command: None,
+            columns: columns::ColumnCache::default(),
+            template_fns: template_fns::TemplateFnCache::default(),
+            meta_cache: meta_cache::MetaCache::default(),
+            config_paths: None,
+            config_error: None,
+            image_protocol: Default::default(),
         };
         // Load marks from config root
         if let Some(root) = app.theme_root_dir()
@@ -160,143 +222,28 @@ impl App
             let path = root.join("marks");
             app.marks = crate::core::marks::load_marks(&path);
         }
+        app.load_history();
+        app.load_frecency();
         // Discover configuration paths (entry not executed yet)
         if let Ok(paths) = crate::config::discover_config_paths()
         {
             match crate::config::load_config(&paths)
             {
-                Ok((cfg, maps, engine_opt)) =>
-                {
-                    app.config = cfg;
-                    app.keys.maps = maps;
-                    app.rebuild_keymap_lookup();
-                    if let Some((eng, key, action_keys)) = engine_opt
-                    {
-                        app.lua = Some(LuaRuntime {
-                            engine:    eng,
-                            previewer: Some(key),
-                            actions:   action_keys,
-                        });
-                    }
-                    else
-                    {
-                        app.lua = None;
-                    }
-                    // Re-apply lists to honor config (e.g., show_hidden)
-                    // Also apply optional initial sort/show from config.ui
-                    if let Some(ref srt) = app.config.ui.sort
-                    && let Some(k) = crate::enums::sort_key_from_str(srt)
-                    {
-                        app.sort_key = k;
-                    }
-                    if let Some(b) = app.config.ui.sort_reverse
-                    {
-                        app.sort_reverse = b;
-                    }
-                    if let Some(ref sh) = app.config.ui.show
-                    {
-                        if sh.eq_ignore_ascii_case("none")
-                        {
-                            app.info_mode = crate::app::InfoMode::None;
-                        }
-                        else if let Some(m) = crate::enums::info_mode_from_str(sh)
-                        {
-                            app.info_mode = m;
-                        }
-                    }
-                    app.refresh_lists();
-                    // Apply display_mode from config if present
-                    if let Some(dm) = app.config.ui.display_mode.as_deref()
-                    && let Some(mode) = crate::enums::display_mode_from_str(dm)
-                    {
-                        app.display_mode = mode;
-                    }
-                }
+                Ok(artifacts) => app.apply_config_artifacts(artifacts),
                 Err(e) =>
                 {
-                    eprintln!("lsv: config load error: {}", e);
+                    app.use_default_config();
+                    app.show_config_error(&e.to_string(), reload::DEFAULT_CONFIG);
                 }
             }
+            app.config_paths = Some(paths);
         }
+        app.init_image_protocol();
+        app.refresh_lists();
         app.refresh_preview();
         Ok(app)
     }
 
-    fn find_match_from(
-        &self,
-        start: usize,
-        pat: &str,
-        backwards: bool,
-    ) -> Option<usize>
-    {
-        if self.current_entries.is_empty() || pat.is_empty()
-        {
-            return None;
-        }
-        let pat_l = pat.to_lowercase();
-        let len = self.current_entries.len();
-        if backwards
-        {
-            let mut idx = start;
-            for _ in 0..len
-            {
-                if let Some(e) = self.current_entries.get(idx)
-                && e.name.to_lowercase().contains(&pat_l)
-                {
-                    return Some(idx);
-                }
-                if idx == 0
-                {
-                    idx = len - 1;
-                }
-                else
-                {
-                    idx -= 1;
-                }
-            }
-        }
-        else
-        {
-            let mut idx = start;
-            for _ in 0..len
-            {
-                if let Some(e) = self.current_entries.get(idx)
-                && e.name.to_lowercase().contains(&pat_l)
-                {
-                    return Some(idx);
-                }
-                idx = (idx + 1) % len;
-            }
-        }
-        None
-    }
-
-
-    #[allow(dead_code)]
-    pub(crate) fn update_search_live(
-        &mut self,
-        q: &str,
-    )
-    {
-        if q.is_empty()
-        {
-            return;
-        }
-        let start = self.list_state.selected().unwrap_or(0);
-        let len = self.current_entries.len();
-        if len == 0
-        {
-            return;
-        }
-        // Try from current to include current when first typing
-        if let Some(i) = self.find_match_from(start, q, false)
-        {
-            self.list_state.select(Some(i));
-            self.refresh_preview();
-            // regular draw is enough
-        }
-    }
-
     /// Test helper: inject a prepared Lua engine and registered action keys.
     ///
     /// This lets integration tests execute Lua callbacks without loading files
@@ -307,8 +254,26 @@ impl App
         action_keys: Vec<mlua::RegistryKey>,
     )
     {
-        self.lua =
-            Some(LuaRuntime { engine, previewer: None, actions: action_keys });
+        self.lua = Some(LuaRuntime {
+            engine,
+            previewers: Vec::new(),
+            actions: action_keys,
+            dialog: None,
+        });
+    }
+
+    pub fn inject_lua_previewer_for_tests(
+        &mut self,
+        engine: crate::config::LuaEngine,
+        previewers: Vec<mlua::RegistryKey>,
+    )
+    {
+        self.lua = Some(LuaRuntime {
+            engine,
+            previewers,
+            actions: Vec::new(),
+            dialog: None,
+        });
     }
 
     pub fn show_hidden(&self) -> bool
@@ -370,6 +335,13 @@ impl App
     {
         self.list_state.selected()
     }
+    /// Selected paths, sorted.
+    pub fn get_selected_paths(&self) -> Vec<std::path::PathBuf>
+    {
+        let mut paths: Vec<_> = self.selected.iter().cloned().collect();
+        paths.sort();
+        paths
+    }
     pub fn get_quit(&self) -> bool
     {
         self.should_quit
@@ -412,6 +384,29 @@ impl App
     )
     {
         self.config = cfg;
+        self.init_image_protocol();
+        // Previewers may have changed
+        self.reset_preview_cache();
+    }
+
+    /// Pick the image protocol for previewers from `ui.image_protocol`, the
+    /// per-terminal overrides or the terminal's environment.
+    pub(crate) fn init_image_protocol(&mut self)
+    {
+        use crate::core::image_protocol;
+        let env = |k: &str| std::env::var(k).ok();
+        let (protocol, source) = image_protocol::resolve(
+            self.config.ui.image_protocol.as_deref(),
+            &self.config.ui.image_protocols,
+            &env,
+        );
+        crate::trace::log(format!(
+            "[image] protocol={} source={:?} term={:?}",
+            protocol.as_str(),
+            source,
+            image_protocol::terminal_name(&env)
+        ));
+        self.image_protocol = protocol;
     }
     pub fn get_config(&mut self) -> crate::config::Config
     {
@@ -427,38 +422,6 @@ impl App
         self.preview.static_lines.len()
     }
 
-    pub fn recent_messages_len(&self) -> usize
-    {
-        self.recent_messages.len()
-    }
-
-    pub fn add_message(
-        &mut self,
-        msg: &str,
-    )
-    {
-        let m = msg.trim().to_string();
-        if m.is_empty()
-        {
-            return;
-        }
-        self.recent_messages.push(m);
-        if self.recent_messages.len() > 100
-        {
-            let _ = self.recent_messages.drain(0..self.recent_messages.len() - 100);
-        }
-        self.force_full_redraw = true;
-    }
-
-    pub fn clear_recent_messages(&mut self)
-    {
-        if !self.recent_messages.is_empty()
-        {
-            self.recent_messages.clear();
-            self.force_full_redraw = true;
-        }
-    }
-
     pub fn set_theme_by_name(
         &mut self,
         name: &str,
@@ -485,13 +448,8 @@ impl App
                 root.join("themes")
             }
         };
-        let rd = match std::fs::read_dir(&themes_dir)
-        {
-            Ok(v) => v,
-            Err(_) => return false,
-        };
         let target_lower = name.to_lowercase();
-        for ent in rd.flatten()
+        for ent in std::fs::read_dir(&themes_dir).into_iter().flatten().flatten()
         {
             let path = ent.path();
             if !path.is_file()
@@ -533,7 +491,17 @@ impl App
                 }
             }
         }
-        false
+        match crate::config::builtin_themes::load(name)
+        {
+            Ok(theme) =>
+            {
+                self.config.ui.theme = Some(theme);
+                self.config.ui.theme_path = None;
+                self.force_full_redraw = true;
+                true
+            }
+            Err(_) => false,
+        }
     }
 
     pub(crate) fn theme_root_dir(&self) -> Option<PathBuf>
@@ -579,8 +547,7 @@ impl App
     {
         let lines: Vec<String> =
         text.replace('\r', "").lines().map(|s| s.to_string()).collect();
-        self.overlay = Overlay::Output { title: title.to_string(), lines };
-        self.force_full_redraw = true;
+        self.set_text_overlay(Overlay::Output { title: title.to_string(), lines });
     }
 }
 
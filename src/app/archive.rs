@@ -0,0 +1,276 @@
+//! Archive browsing actions for App.
+//!
+//! Archives are entered like directories through [`crate::core::vfs`]; their
+//! listings are read-only, and members are copied out with `:extract_here`.
+//! `:extract` unpacks a whole archive file into a directory of its own.
+//! `:compress` packs the selected entries into a new archive (see
+//! [`crate::core::pack`]).
+
+use std::path::PathBuf;
+
+use crate::{
+  app::{
+    App,
+    JobKind,
+    Overlay,
+    PasteTarget,
+    PromptKind,
+    PromptState,
+  },
+  core::{
+    archive,
+    fs_ops,
+    pack::{
+      PackFormat,
+      PackSpec,
+    },
+    vfs,
+  },
+};
+
+/// Extension the compress prompt suggests.
+const DEFAULT_EXTENSION: &str = ".tar.gz";
+const FORMATS: &str = ".zip, .tar, .tar.gz, .tar.zst";
+
+/// `name` without its archive extension: `site-1.0` for `site-1.0.tar.gz`.
+fn archive_stem(name: &str) -> &str
+{
+  let lower = name.to_ascii_lowercase();
+  [".tar.gz", ".tgz", ".tar", ".zip", ".jar", ".whl"]
+    .iter()
+    .find(|ext| lower.ends_with(*ext))
+    .map(|ext| &name[..name.len() - ext.len()])
+    .filter(|stem| !stem.is_empty())
+    .unwrap_or(name)
+}
+
+impl App
+{
+  /// Whether the current directory is inside an archive.
+  pub fn in_archive(&self) -> bool
+  {
+    vfs::resolve(&self.cwd).is_some()
+  }
+
+  /// Refuse a modifying action while browsing an archive or the drive list.
+  /// Returns true (and posts a message) when the action must not run.
+  pub(crate) fn refuse_in_archive(
+    &mut self,
+    what: &str,
+  ) -> bool
+  {
+    if self.in_drives()
+    {
+      self.add_message(&format!("{}: the drive list is read-only", what));
+      return true;
+    }
+    if !self.in_archive()
+    {
+      return false;
+    }
+    self.add_message(&format!("{}: archives are read-only", what));
+    true
+  }
+
+  /// Extract the selected archive members (or the one under the cursor) into
+  /// the directory holding the archive, as a background job.
+  pub(crate) fn extract_here(&mut self)
+  {
+    let Some(ap) = vfs::resolve(&self.cwd)
+    else
+    {
+      self.add_message("extract_here: not inside an archive");
+      return;
+    };
+    let mut items: Vec<PathBuf> =
+      self.selected.iter().filter(|p| vfs::is_member(p)).cloned().collect();
+    if items.is_empty()
+    {
+      match self.selected_entry()
+      {
+        Some(e) => items.push(e.path.clone()),
+        None =>
+        {
+          self.add_message("extract_here: nothing selected");
+          return;
+        }
+      }
+    }
+    items.sort();
+    let dest = ap.archive.parent().map(|p| p.to_path_buf());
+    for p in &items
+    {
+      self.selected.remove(p);
+    }
+    self.enqueue_job(JobKind::Extract, items, dest);
+  }
+
+  /// Extract the highlighted archive file as a background job. Archives
+  /// holding a single top-level directory are extracted as that directory;
+  /// anything else goes into a new directory named after the archive, so
+  /// loose members never spill into the current one. A name that is taken
+  /// gets a `_N` suffix. The result is highlighted when the job is done.
+  /// Inside an archive this is `:extract_here`.
+  pub(crate) fn extract_archive(&mut self)
+  {
+    if self.in_archive()
+    {
+      self.extract_here();
+      return;
+    }
+    if self.refuse_remote("extract")
+    {
+      return;
+    }
+    let Some(path) = self.selected_entry().map(|e| e.path.clone())
+    else
+    {
+      self.add_message("extract: nothing selected");
+      return;
+    };
+    let root = match vfs::resolve(&path)
+    {
+      Some(ap) if ap.inner.is_empty() => ap,
+      _ =>
+      {
+        let msg = match archive::detect(&path)
+        {
+          Some(kind) =>
+          {
+            format!("extract: {} archives are not supported", kind.label())
+          }
+          None => String::from("extract: not an archive"),
+        };
+        self.add_message(&msg);
+        return;
+      }
+    };
+    let top = match vfs::read_dir(&root, true)
+    {
+      Ok(entries) => entries,
+      Err(e) =>
+      {
+        self.add_message(&format!("extract: {}", e));
+        return;
+      }
+    };
+    let (src, name) = match top.as_slice()
+    {
+      [only] if only.is_dir => (path.join(&only.name), only.name.clone()),
+      _ =>
+      {
+        let name =
+          path.file_name().map(|s| s.to_string_lossy()).unwrap_or_default();
+        (path.clone(), archive_stem(&name).to_string())
+      }
+    };
+    let dest = fs_ops::free_name(&self.cwd.join(name));
+    let target = PasteTarget { dest: dest.clone(), overwrite: false };
+    let id = self.enqueue_paste(
+      JobKind::Extract,
+      vec![(src, target)],
+      self.cwd.clone(),
+    );
+    self.select_after_job(id, dest);
+  }
+
+  /// Pack the selected entries (or the one under the cursor) into the
+  /// archive `name` in the current directory, or prompt for the name when it
+  /// is empty.
+  pub(crate) fn compress(
+    &mut self,
+    name: &str,
+  )
+  {
+    if self.refuse_in_archive("Compress") || self.refuse_remote("Compress")
+    {
+      return;
+    }
+    let mut items: Vec<PathBuf> = self.selected.iter().cloned().collect();
+    if items.is_empty()
+    {
+      items.extend(self.selected_entry().map(|e| e.path.clone()));
+    }
+    items.sort();
+    if items.is_empty()
+    {
+      self.add_message("Compress: nothing selected");
+      return;
+    }
+    if !name.trim().is_empty()
+    {
+      self.compress_items(items, name);
+      return;
+    }
+    // Named after the only item, or else the directory holding them
+    let stem = match items.as_slice()
+    {
+      [one] if one.is_dir() => one.file_name(),
+      [one] => one.file_stem(),
+      _ => self.cwd.file_name(),
+    }
+    .map(|s| s.to_string_lossy().to_string())
+    .unwrap_or_else(|| String::from("archive"));
+    let input = format!("{}{}", stem, DEFAULT_EXTENSION);
+    let title = match items.len()
+    {
+      1 => format!("Compress into ({}):", FORMATS),
+      n => format!("Compress {} items into ({}):", n, FORMATS),
+    };
+    self.keys.pending.clear();
+    self.keys.last_at = None;
+    self.overlay = Overlay::Prompt(Box::new(PromptState {
+      title,
+      cursor: input.len(),
+      input,
+      kind: PromptKind::Compress { items },
+    }));
+    self.force_full_redraw = true;
+  }
+
+  /// Queue packing `items` into the archive `name`, whose extension picks
+  /// the format.
+  pub(crate) fn compress_items(
+    &mut self,
+    items: Vec<PathBuf>,
+    name: &str,
+  )
+  {
+    let name = name.trim();
+    if name.is_empty()
+    {
+      return;
+    }
+    let Some(format) = PackFormat::detect(name)
+    else
+    {
+      self.add_message(&format!(
+        "Compress: unknown archive type '{}' ({})",
+        name, FORMATS
+      ));
+      return;
+    };
+    let archive = self.cwd.join(crate::core::complete::expand_tilde(name));
+    if archive.symlink_metadata().is_ok()
+    {
+      self.add_message(&format!(
+        "Compress: {} already exists",
+        archive.display()
+      ));
+      return;
+    }
+    for p in &items
+    {
+      self.selected.remove(p);
+    }
+    let spec = PackSpec {
+      command: format.command(&self.config.ui.compress),
+      archive,
+      format,
+      base: self.cwd.clone(),
+      shell: self.config.command_shell.clone(),
+    };
+    self.enqueue_compress(items, spec);
+    self.force_full_redraw = true;
+  }
+}
@@ -0,0 +1,320 @@
+//! File checksums (`:checksum`, `:verify`) for App.
+//!
+//! `:checksum [md5|sha1|sha256]` hashes the selected files (or the one under
+//! the cursor) on a background thread. The results are shown in the output
+//! overlay in the format of `sha256sum` and friends, and copied to the
+//! clipboard: just the digest for one file, every line for several.
+//! `:verify <sumfile>` checks the files listed in a manifest, relative to the
+//! manifest's directory, and reports each as OK, FAILED or unreadable.
+
+use std::{
+  path::{
+    Path,
+    PathBuf,
+  },
+  sync::mpsc,
+};
+
+use crate::{
+  app::{
+    App,
+    yank::copy_to_clipboard,
+  },
+  core::checksum::{
+    self,
+    Algorithm,
+  },
+};
+
+/// What a finished worker hands back for display.
+struct ChecksumReport
+{
+  title:   String,
+  text:    String,
+  /// Text to put on the clipboard, if any.
+  copy:    Option<String>,
+  summary: String,
+}
+
+/// The running checksum worker, if any.
+#[derive(Debug, Default)]
+pub struct ChecksumState
+{
+  rx: Option<mpsc::Receiver<ChecksumReport>>,
+}
+
+impl App
+{
+  /// Hash the selected files (or the current one) with `algo`.
+  pub(crate) fn compute_checksums(
+    &mut self,
+    algo: Algorithm,
+  )
+  {
+    if self.in_archive()
+    {
+      self.add_message("checksum: not available inside archives");
+      return;
+    }
+    if self.refuse_remote("checksum")
+    {
+      return;
+    }
+    let mut items: Vec<PathBuf> = self.selected.iter().cloned().collect();
+    if items.is_empty()
+    {
+      items.extend(self.selected_entry().map(|e| e.path.clone()));
+    }
+    items.sort();
+    if items.is_empty()
+    {
+      self.add_message("checksum: nothing selected");
+      return;
+    }
+    self.add_message(&format!(
+      "Computing {} of {} file(s)...",
+      algo.tool(),
+      items.len()
+    ));
+    let cwd = self.cwd.clone();
+    self.spawn_checksums(move || hash_files(algo, &items, &cwd));
+  }
+
+  /// Check the files listed in the manifest at `sumfile` (relative to the
+  /// current directory), or in the current entry when `sumfile` is empty.
+  pub(crate) fn verify_checksums(
+    &mut self,
+    sumfile: &str,
+  )
+  {
+    if self.in_archive()
+    {
+      self.add_message("verify: not available inside archives");
+      return;
+    }
+    if self.refuse_remote("verify")
+    {
+      return;
+    }
+    let manifest = if sumfile.trim().is_empty()
+    {
+      match self.selected_entry()
+      {
+        Some(e) if !e.is_dir => e.path.clone(),
+        _ =>
+        {
+          self.add_message("verify: usage: verify <sumfile>");
+          return;
+        }
+      }
+    }
+    else
+    {
+      self.cwd.join(crate::core::complete::expand_tilde(sumfile.trim()))
+    };
+    let text = match std::fs::read_to_string(&manifest)
+    {
+      Ok(t) => t,
+      Err(e) =>
+      {
+        self.add_message(&format!("verify: {}: {}", manifest.display(), e));
+        return;
+      }
+    };
+    self.spawn_checksums(move || verify_manifest(&manifest, &text));
+  }
+
+  fn spawn_checksums(
+    &mut self,
+    work: impl FnOnce() -> ChecksumReport + Send + 'static,
+  )
+  {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+      let _ = tx.send(work());
+    });
+    // A newer request replaces (and silences) one still running
+    self.checksums.rx = Some(rx);
+  }
+
+  /// Show the worker's report once it is done. Returns true when it was.
+  pub fn poll_checksums(&mut self) -> bool
+  {
+    let Some(rx) = self.checksums.rx.as_ref()
+    else
+    {
+      return false;
+    };
+    let report = match rx.try_recv()
+    {
+      Ok(r) => r,
+      Err(mpsc::TryRecvError::Empty) => return false,
+      Err(mpsc::TryRecvError::Disconnected) =>
+      {
+        self.checksums.rx = None;
+        return true;
+      }
+    };
+    self.checksums.rx = None;
+    self.display_output(&report.title, &report.text);
+    let mut summary = report.summary;
+    if let Some(copy) = report.copy
+    {
+      let (osc52, tool) = copy_to_clipboard(&copy);
+      if osc52 || tool.is_some()
+      {
+        summary.push_str(" (copied)");
+      }
+    }
+    self.add_message(&summary);
+    true
+  }
+
+  /// Whether checksums are still being computed.
+  pub fn is_computing_checksums(&self) -> bool
+  {
+    self.checksums.rx.is_some()
+  }
+
+  /// Block until the checksum worker finishes (tests and scripted use).
+  pub fn finish_checksums(&mut self)
+  {
+    while self.is_computing_checksums()
+    {
+      if !self.poll_checksums()
+      {
+        std::thread::sleep(std::time::Duration::from_millis(5));
+      }
+    }
+  }
+}
+
+/// `path` relative to `base` when it lies below it.
+fn display_name(
+  path: &Path,
+  base: &Path,
+) -> String
+{
+  path.strip_prefix(base).unwrap_or(path).to_string_lossy().to_string()
+}
+
+fn hash_files(
+  algo: Algorithm,
+  items: &[PathBuf],
+  cwd: &Path,
+) -> ChecksumReport
+{
+  let mut lines = Vec::new();
+  let mut digests = Vec::new();
+  let mut failed = 0;
+  for path in items
+  {
+    let name = display_name(path, cwd);
+    if path.is_dir()
+    {
+      lines.push(format!("{}: is a directory", name));
+      failed += 1;
+      continue;
+    }
+    match checksum::file_digest(algo, path)
+    {
+      Ok(hash) =>
+      {
+        let line = format!("{}  {}", hash, name);
+        lines.push(line.clone());
+        digests.push((hash, line));
+      }
+      Err(e) =>
+      {
+        lines.push(format!("{}: {}", name, e));
+        failed += 1;
+      }
+    }
+  }
+  let copy = match digests.as_slice()
+  {
+    [] => None,
+    [(hash, _)] if failed == 0 => Some(hash.clone()),
+    _ => Some(
+      digests
+        .iter()
+        .map(|(_, line)| line.as_str())
+        .collect::<Vec<_>>()
+        .join("\n"),
+    ),
+  };
+  let mut summary =
+    format!("{}: {} file(s) hashed", algo.tool(), digests.len());
+  if failed > 0
+  {
+    summary.push_str(&format!(", {} failed", failed));
+  }
+  ChecksumReport {
+    title: algo.tool().to_string(),
+    text: lines.join("\n"),
+    copy,
+    summary,
+  }
+}
+
+fn verify_manifest(
+  manifest: &Path,
+  text: &str,
+) -> ChecksumReport
+{
+  let dir = manifest.parent().unwrap_or(Path::new("."));
+  let (entries, bad) = checksum::parse_manifest(text);
+  let mut lines = Vec::new();
+  let (mut ok, mut failed, mut unreadable) = (0, 0, 0);
+  for entry in &entries
+  {
+    let path = dir.join(&entry.name);
+    match checksum::file_digest(entry.algo, &path)
+    {
+      Ok(hash) if hash == entry.hash =>
+      {
+        ok += 1;
+        lines.push(format!("{}: OK", entry.name));
+      }
+      Ok(_) =>
+      {
+        failed += 1;
+        lines.push(format!("{}: FAILED", entry.name));
+      }
+      Err(e) =>
+      {
+        unreadable += 1;
+        lines.push(format!("{}: FAILED open or read ({})", entry.name, e));
+      }
+    }
+  }
+  if !bad.is_empty()
+  {
+    lines.push(format!(
+      "warning: {} line(s) improperly formatted (line {})",
+      bad.len(),
+      bad.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(", ")
+    ));
+  }
+  let name = manifest
+    .file_name()
+    .map(|s| s.to_string_lossy().to_string())
+    .unwrap_or_default();
+  let summary = if entries.is_empty()
+  {
+    format!("verify: no checksums found in {}", name)
+  }
+  else
+  {
+    format!(
+      "verify {}: {} OK, {} FAILED, {} unreadable",
+      name, ok, failed, unreadable
+    )
+  };
+  ChecksumReport {
+    title: format!("Verify: {}", name),
+    text: lines.join("\n"),
+    copy: None,
+    summary,
+  }
+}
@@ -0,0 +1,192 @@
+//! Row template fields computed by Lua.
+//!
+//! `lsv.define_column(name, fn)` makes `{name}` usable in `ui.row`
+//! templates. `fn` gets a table describing the entry (`path`, `name`, `dir`,
+//! `extension`, `is_dir`, `size` and `mtime` in seconds since the epoch) and
+//! returns the text to show; `nil` shows nothing.
+//!
+//! Values are cached per column and path while the entry's modification time
+//! stays the same, since rows are redrawn on every tick. Actions call
+//! `lsv.invalidate_column(name)` (or `lsv.invalidate_column()` for all
+//! columns) when a value may change without the file changing, e.g. after a
+//! `git add`.
+//!
+//! The built-in `{mime}` field is cached the same way, since detecting it
+//! reads the start of the file.
+
+use std::{
+  cell::RefCell,
+  collections::HashMap,
+  path::PathBuf,
+  time::SystemTime,
+};
+
+use mlua::Value;
+
+use crate::{
+  app::{
+    App,
+    DirEntryInfo,
+  },
+  core::mime,
+};
+
+/// Computed values, filled in while rows are drawn.
+#[derive(Debug, Default)]
+pub struct ColumnCache
+{
+  values: RefCell<HashMap<(String, PathBuf), CachedValue>>,
+  mimes:  RefCell<HashMap<PathBuf, CachedValue>>,
+}
+
+#[derive(Debug)]
+struct CachedValue
+{
+  mtime: Option<SystemTime>,
+  text:  String,
+}
+
+pub(crate) fn entry_table(
+  lua: &mlua::Lua,
+  e: &DirEntryInfo,
+) -> mlua::Result<mlua::Table>
+{
+  let t = lua.create_table()?;
+  t.set("path", e.path.to_string_lossy().to_string())?;
+  t.set(
+    "name",
+    e.path
+      .file_name()
+      .map(|s| s.to_string_lossy().to_string())
+      .unwrap_or_else(|| e.name.clone()),
+  )?;
+  if let Some(dir) = e.path.parent()
+  {
+    t.set("dir", dir.to_string_lossy().to_string())?;
+  }
+  t.set(
+    "extension",
+    e.path.extension().and_then(|s| s.to_str()).unwrap_or(""),
+  )?;
+  t.set("is_dir", e.is_dir)?;
+  t.set("size", e.size)?;
+  if let Some(secs) = e
+    .mtime
+    .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+    .map(|d| d.as_secs())
+  {
+    t.set("mtime", secs)?;
+  }
+  if let Some(meta) = e.meta
+  {
+    t.set("mode", meta.mode)?;
+    t.set("uid", meta.uid)?;
+    t.set("gid", meta.gid)?;
+    t.set("nlink", meta.nlink)?;
+  }
+  Ok(t)
+}
+
+impl App
+{
+  /// Value of the Lua column `name` for `e`, or `None` when no such column
+  /// is defined.
+  pub(crate) fn column_value(
+    &self,
+    name: &str,
+    e: &DirEntryInfo,
+  ) -> Option<String>
+  {
+    let col = self.config.columns.iter().find(|c| c.name == name)?;
+    let key = (col.name.clone(), e.path.clone());
+    if let Some(v) = self.columns.values.borrow().get(&key)
+      && v.mtime == e.mtime
+    {
+      return Some(v.text.clone());
+    }
+    let text = self.compute_column(col.action, e).unwrap_or_else(|err| {
+      // Cached like a value so a failing column doesn't rerun every frame
+      crate::trace::log(format!("[column] {} error: {}", name, err));
+      String::new()
+    });
+    self
+      .columns
+      .values
+      .borrow_mut()
+      .insert(key, CachedValue { mtime: e.mtime, text: text.clone() });
+    Some(text)
+  }
+
+  /// MIME type of `e` as [`crate::core::mime`] detects it. Entries in an
+  /// archive or on a remote host are only judged by their extension.
+  pub(crate) fn entry_mime(
+    &self,
+    e: &DirEntryInfo,
+  ) -> String
+  {
+    if e.is_dir
+    {
+      return String::from("inode/directory");
+    }
+    if self.in_archive() || self.in_remote()
+    {
+      let ext = e.path.extension().and_then(|s| s.to_str()).unwrap_or("");
+      return mime::from_extension(ext).unwrap_or_default().to_string();
+    }
+    if let Some(v) = self.columns.mimes.borrow().get(&e.path)
+      && v.mtime == e.mtime
+    {
+      return v.text.clone();
+    }
+    let text = mime::guess(&e.path);
+    self.columns.mimes.borrow_mut().insert(
+      e.path.clone(),
+      CachedValue { mtime: e.mtime, text: text.clone() },
+    );
+    text
+  }
+
+  fn compute_column(
+    &self,
+    idx: usize,
+    e: &DirEntryInfo,
+  ) -> mlua::Result<String>
+  {
+    let Some(rt) = self.lua.as_ref()
+    else
+    {
+      return Ok(String::new());
+    };
+    let Some(key) = rt.actions.get(idx)
+    else
+    {
+      return Ok(String::new());
+    };
+    let lua = rt.engine.lua();
+    let func = lua.registry_value::<mlua::Function>(key)?;
+    Ok(match func.call::<Value>(entry_table(lua, e)?)?
+    {
+      Value::Nil => String::new(),
+      Value::String(s) => s.to_str()?.to_string(),
+      other => other.to_string()?,
+    })
+  }
+
+  /// Drop cached values of the named columns; `*` drops them all.
+  pub(crate) fn invalidate_columns(
+    &mut self,
+    names: &[String],
+  )
+  {
+    let values = self.columns.values.get_mut();
+    if names.iter().any(|n| n == "*")
+    {
+      values.clear();
+    }
+    else
+    {
+      values.retain(|(col, _), _| !names.contains(col));
+    }
+    self.force_full_redraw = true;
+  }
+}
@@ -1,10 +1,23 @@
 //! Command pane verbs and routing for App.
 
-use crate::app::{
-  App,
-  CommandPaneState,
-  InfoMode,
-  Overlay,
+use std::path::{
+  Path,
+  PathBuf,
+};
+
+use crate::{
+  app::{
+    App,
+    ClipboardOp,
+    CommandPaneState,
+    InfoMode,
+    Overlay,
+    yank::YankKind,
+  },
+  core::{
+    checksum::Algorithm,
+    fs_ops::LinkKind,
+  },
 };
 
 impl App
@@ -16,21 +29,44 @@ impl App
       input:            String::new(),
       cursor:           0,
       show_suggestions: false,
+      history:          Default::default(),
+      candidates:       Vec::new(),
     }));
     self.force_full_redraw = true;
   }
 
   pub(crate) fn open_command(&mut self)
+  {
+    self.open_command_with("");
+  }
+
+  /// Open the command pane with `input` already typed.
+  pub(crate) fn open_command_with(
+    &mut self,
+    input: &str,
+  )
   {
     self.overlay = Overlay::CommandPane(Box::new(CommandPaneState {
       prompt:           ":".to_string(),
-      input:            String::new(),
-      cursor:           0,
+      input:            input.to_string(),
+      cursor:           input.len(),
       show_suggestions: false,
+      history:          Default::default(),
+      candidates:       Vec::new(),
     }));
     self.force_full_redraw = true;
   }
 
+  /// Text in the command pane, while it is open.
+  pub fn command_input(&self) -> Option<&str>
+  {
+    match self.overlay
+    {
+      Overlay::CommandPane(ref st) => Some(st.input.as_str()),
+      _ => None,
+    }
+  }
+
   pub(crate) fn execute_command_line(
     &mut self,
     line: &str,
@@ -45,58 +81,206 @@ impl App
     {
       "" =>
       {}
-      "marks" =>
+      "marks" => self.open_bookmarks(None),
+      "delmark" =>
       {
-        let text = self.list_marks_text();
-        self.display_output("Marks", &text);
+        // Mark names keep their case
+        let removed = self.delete_marks(cmd.split_whitespace().skip(1));
+        self.add_message(&format!("Deleted {} mark(s)", removed));
       }
-      "delmark" =>
+      "rename_mark" =>
+      {
+        let args: Vec<&str> = cmd.split_whitespace().skip(1).collect();
+        match args.as_slice()
+        {
+          [from, to] => self.rename_mark(from, to),
+          _ => self.add_message("Usage: rename_mark <name> <new-name>"),
+        }
+      }
+      "find" => self.open_search(),
+      "find_files" => self.open_finder(),
+      "extract_here" => self.extract_here(),
+      "paste_symlink" =>
       {
-        let mut removed = 0usize;
-        for tok in parts
+        let relative = match parts.next()
         {
-          if let Some(ch) = tok.chars().next()
-            && self.marks.remove(&ch).is_some()
+          Some("relative") => Some(true),
+          Some("absolute") => Some(false),
+          Some(other) =>
           {
-            removed += 1;
+            self.add_message(&format!(
+              "paste_symlink: expected relative or absolute, got '{}'",
+              other
+            ));
+            return;
           }
-        }
-        if removed > 0
+          None => None,
+        };
+        self.paste_links(LinkKind::Symlink, relative);
+      }
+      "paste_hardlink" => self.paste_links(LinkKind::Hardlink, None),
+      "set_permissions" =>
+      {
+        // Owner names keep their case
+        let rest =
+          cmd.chars().skip("set_permissions".len()).collect::<String>();
+        self.set_permissions(&rest);
+      }
+      "file_info" | "show_file_info" => self.show_file_info(),
+      "compute_dir_sizes" => self.compute_dir_sizes(),
+      "extract" => self.extract_archive(),
+      "compress" =>
+      {
+        // Archive names keep their case
+        let rest = cmd.chars().skip("compress".len()).collect::<String>();
+        self.compress(&rest);
+      }
+      "checksum" =>
+      {
+        let algo = parts.next().unwrap_or("sha256");
+        match Algorithm::from_name(algo)
         {
-          self.save_marks();
+          Some(algo) => self.compute_checksums(algo),
+          None => self.add_message(&format!(
+            "checksum: unknown algorithm '{}' (md5, sha1, sha256)",
+            algo
+          )),
         }
-        self.add_message(&format!("Deleted {} mark(s)", removed));
       }
-      "find" => self.open_search(),
+      "verify" =>
+      {
+        // File names keep their case
+        let rest = cmd.chars().skip("verify".len()).collect::<String>();
+        self.verify_checksums(&rest);
+      }
+      "toggle_tree" => self.toggle_tree(),
+      "toggle_parent_pane" =>
+      {
+        self.config.ui.show_parent = !self.config.ui.show_parent;
+        self.force_full_redraw = true;
+      }
+      "toggle_preview_pane" =>
+      {
+        self.config.ui.show_preview = !self.config.ui.show_preview;
+        self.force_full_redraw = true;
+      }
+      "toggle_flatten" =>
+      {
+        let depth = parts.next().and_then(|s| s.parse::<usize>().ok());
+        self.toggle_flatten(depth);
+      }
       "next" => self.search_next(),
       "prev" => self.search_prev(),
       "messages" =>
       {
-        self.overlay = match self.overlay
-        {
-          Overlay::Messages => Overlay::None,
-          _ => Overlay::Messages,
-        };
-        self.force_full_redraw = true;
+        // File names keep their case
+        let rest = cmd.chars().skip(name.len()).collect::<String>();
+        self.messages_command(&rest);
       }
       "output" =>
       {
-        self.overlay = match self.overlay
+        if let Overlay::Output { .. } = self.overlay
         {
-          Overlay::Output { .. } => Overlay::None,
-          _ =>
-          {
-            Overlay::Output { title: String::from("Output"), lines: Vec::new() }
-          }
-        };
-        self.force_full_redraw = true;
+          self.overlay = Overlay::None;
+          self.force_full_redraw = true;
+        }
+        else if !self.show_command_run()
+        {
+          self.display_output("Output", "");
+        }
       }
       "theme" => self.open_theme_picker(),
+      "reload_config" =>
+      {
+        self.reload_config();
+      }
       "add" => self.open_add_entry_prompt(),
       "rename" => self.open_rename_entry_prompt(),
+      "rename_editor" => self.open_rename_editor(),
+      "open" =>
+      {
+        self.open_selected();
+      }
+      "open_at_line" => match parts.next()
+      {
+        None =>
+        {
+          self.open_current_at_line(None);
+        }
+        Some(arg) => match arg.parse::<usize>()
+        {
+          Ok(line) =>
+          {
+            self.open_current_at_line(Some(line));
+          }
+          Err(_) => self.add_message(&format!(
+            "open_at_line: '{}' is not a line number",
+            arg
+          )),
+        },
+      },
+      "yank_path" => self.yank(YankKind::Path),
+      "yank_name" => self.yank(YankKind::Name),
+      "yank_dir" => self.yank(YankKind::Dir),
+      "yank_contents" => self.yank(YankKind::Contents),
+      "open_shell" =>
+      {
+        self.open_shell();
+      }
+      "run_interactive" =>
+      {
+        // Keep the command's original case
+        let rest = cmd.chars().skip(name.len()).collect::<String>();
+        self.run_interactive(&rest);
+      }
+      "run" =>
+      {
+        // Keep the command's original case
+        let rest = cmd.chars().skip(name.len()).collect::<String>();
+        self.start_command_run(&rest);
+      }
+      "run_background" =>
+      {
+        let rest = cmd.chars().skip(name.len()).collect::<String>();
+        self.start_background_run(&rest);
+      }
+      "run_preview" =>
+      {
+        let rest = cmd.chars().skip(name.len()).collect::<String>();
+        self.start_preview_run(&rest);
+      }
+      "run_cancel" => self.cancel_command_run(),
       "delete" => self.request_delete_selected(),
+      "delete_to_trash" => self.request_trash_selected(),
+      "restore_from_trash" => self.restore_from_trash(),
+      "trash" => self.open_trash_browser(),
+      "mounts" => self.open_mounts(),
+      "drives" => self.open_drives(),
+      "jobs" => self.toggle_jobs_overlay(),
+      "selections" => self.open_selections(),
+      "registers" => self.open_registers(),
+      "job_cancel" => self.cancel_running_job(),
       "select_toggle" => self.toggle_select_current(),
       "select_clear" => self.clear_all_selected(),
+      "filter" =>
+      {
+        let rest = cmd.chars().skip(name.len()).collect::<String>();
+        self.set_filter(&rest);
+      }
+      "filter_clear" => self.set_filter(""),
+      "filter_prompt" => self.open_filter_prompt(),
+      "select_all" => self.select_all(),
+      "invert_selection" => self.invert_selection(),
+      "select" | "select!" | "unselect" | "unselect!" =>
+      {
+        // Keep the pattern's original case
+        let rest = cmd.chars().skip(name.len()).collect::<String>();
+        self.select_matching(
+          &rest,
+          name.ends_with('!'),
+          !name.starts_with('u'),
+        );
+      }
       "show_hidden_toggle" =>
       {
         self.config.ui.show_hidden = !self.config.ui.show_hidden;
@@ -149,10 +333,26 @@ impl App
         let path = rest.trim();
         if !path.is_empty()
         {
-          let p = std::path::Path::new(path);
-          if p.is_dir()
+          let p = if crate::core::remote::is_remote(Path::new(path))
+          {
+            PathBuf::from(path)
+          }
+          else
+          {
+            crate::core::complete::resolve_typed_path(path, &self.cwd)
+          };
+          // Remote directories show an error in the listing if missing
+          if let Some(rp) = crate::core::remote::RemotePath::parse(&p)
+          {
+            self.set_cwd(&rp.to_path());
+          }
+          else if crate::core::vfs::is_drives(Path::new(path))
+          {
+            self.open_drives();
+          }
+          else if p.is_dir()
           {
-            self.set_cwd(p);
+            self.set_cwd(&p);
           }
           else
           {
@@ -160,62 +360,47 @@ impl App
           }
         }
       }
+      "z" =>
+      {
+        let rest = cmd.chars().skip(1).collect::<String>();
+        self.jump(&rest);
+      }
+      "grep" =>
+      {
+        // Keep the pattern's original case
+        let rest = cmd.chars().skip(4).collect::<String>();
+        self.start_grep(&rest);
+      }
       "mark" =>
       {
-        if let Some(arg) = parts.next()
-          && let Some(ch) = arg.chars().next()
+        if let Some(name) = cmd.split_whitespace().nth(1)
         {
-          self.add_mark(ch);
+          self.add_mark(name);
         }
       }
       "goto" =>
       {
-        if let Some(arg) = parts.next()
-          && let Some(ch) = arg.chars().next()
+        if let Some(name) = cmd.split_whitespace().nth(1)
         {
-          self.goto_mark(ch);
+          self.goto_mark(name);
         }
       }
+      "tab_new" => self.tab_new(),
+      "tab_next" => self.tab_next(),
+      "tab_prev" => self.tab_prev(),
+      "tab_close" => self.tab_close(),
+      "copy_to_other" => self.transfer_to_other_tab(ClipboardOp::Copy),
+      "move_to_other" => self.transfer_to_other_tab(ClipboardOp::Move),
+      "palette" => self.open_palette(),
+      "help" => self.open_help(),
       other =>
       {
-        self.add_message(&format!("Unknown command: :{}", other));
-      }
-    }
-  }
-
-  pub(crate) fn search_next(&mut self)
-  {
-    if let Some(ref q) = self.search_query
-    {
-      let start = self.list_state.selected().unwrap_or(0);
-      let next = if self.current_entries.is_empty()
-      {
-        None
-      }
-      else
-      {
-        self.find_match_from((start + 1) % self.current_entries.len(), q, false)
-      };
-      if let Some(i) = next
-      {
-        self.list_state.select(Some(i));
-        self.refresh_preview();
-      }
-    }
-  }
-
-  pub(crate) fn search_prev(&mut self)
-  {
-    if let Some(ref q) = self.search_query
-    {
-      let start = self.list_state.selected().unwrap_or(0);
-      let len = self.current_entries.len();
-      let prev_start = if len == 0 { 0 } else { (start + len - 1) % len };
-      let prev = self.find_match_from(prev_start, q, true);
-      if let Some(i) = prev
-      {
-        self.list_state.select(Some(i));
-        self.refresh_preview();
+        // Lowercasing keeps byte offsets, so the arguments keep their case
+        let rest = cmd[other.len()..].trim_start();
+        if !self.run_lua_command(other, rest)
+        {
+          self.add_warning(&format!("Unknown command: :{}", other));
+        }
       }
     }
   }
@@ -0,0 +1,157 @@
+//! Confirmation of batch operations for App.
+//!
+//! Deleting or trashing, pastes that replace more than one existing item and
+//! bulk renames of more than one item ask first in a dialog that lists every
+//! path involved, scrollable with `j`/`k` and `PageUp`/`PageDown`, above a
+//! summary of how many directories and files it affects and their total
+//! size. `y` goes ahead; `n` or `Esc` cancels.
+
+use std::path::{
+  Path,
+  PathBuf,
+};
+
+use crate::{
+  app::{
+    App,
+    ConfirmKind,
+    ConfirmState,
+    Overlay,
+  },
+  core::fs_ops,
+  ui::format::human_size,
+};
+
+/// "2 directories and 3 files, 41 files and 1.2 MiB in total" for `items`.
+pub(crate) fn batch_summary(items: &[PathBuf]) -> String
+{
+  let dirs = items
+    .iter()
+    .filter(|p| p.symlink_metadata().is_ok_and(|m| m.is_dir()))
+    .count();
+  let files = items.len() - dirs;
+  let (bytes, total_files) = items
+    .iter()
+    .map(|p| fs_ops::path_totals(p))
+    .fold((0, 0), |acc, t| (acc.0 + t.0, acc.1 + t.1));
+  let plural = |n: usize, one: &str, many: &str| {
+    format!("{} {}", n, if n == 1 { one } else { many })
+  };
+  let mut parts = Vec::new();
+  if dirs > 0
+  {
+    parts.push(plural(dirs, "directory", "directories"));
+  }
+  if files > 0 || dirs == 0
+  {
+    parts.push(plural(files, "file", "files"));
+  }
+  let mut out = parts.join(" and ");
+  if dirs > 0
+  {
+    out.push_str(&format!(
+      ", {} and {} in total",
+      plural(total_files as usize, "file", "files"),
+      human_size(bytes)
+    ));
+  }
+  else
+  {
+    out.push_str(&format!(", {}", human_size(bytes)));
+  }
+  out
+}
+
+/// `from -> to`, by name when the item stays in its directory.
+pub(crate) fn rename_line(
+  from: &Path,
+  to: &Path,
+) -> String
+{
+  let name = |p: &Path| {
+    p.file_name().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default()
+  };
+  if from.parent() == to.parent()
+  {
+    format!("{} -> {}", name(from), name(to))
+  }
+  else
+  {
+    format!("{} -> {}", from.display(), to.display())
+  }
+}
+
+impl App
+{
+  /// Ask `question` about the paths (or other lines) in `details`.
+  pub(crate) fn open_batch_confirm(
+    &mut self,
+    title: &str,
+    question: String,
+    details: Vec<String>,
+    kind: ConfirmKind,
+  )
+  {
+    self.keys.pending.clear();
+    self.keys.last_at = None;
+    self.overlay = Overlay::Confirm(Box::new(ConfirmState {
+      title: title.to_string(),
+      question,
+      default_yes: false,
+      kind,
+      details,
+      scroll: 0,
+      page_height: 1,
+    }));
+    self.force_full_redraw = true;
+  }
+
+  /// Lines listed by the open confirmation dialog.
+  pub fn confirm_details(&self) -> Option<&[String]>
+  {
+    match self.overlay
+    {
+      Overlay::Confirm(ref st) => Some(&st.details),
+      _ => None,
+    }
+  }
+
+  /// Question asked by the open confirmation dialog.
+  pub fn confirm_question(&self) -> Option<&str>
+  {
+    match self.overlay
+    {
+      Overlay::Confirm(ref st) => Some(&st.question),
+      _ => None,
+    }
+  }
+
+  /// Scroll the list of the confirmation dialog by `delta` lines.
+  pub(crate) fn confirm_scroll(
+    &mut self,
+    delta: isize,
+  )
+  {
+    if let Overlay::Confirm(ref mut st) = self.overlay
+    {
+      let max = st.details.len().saturating_sub(st.page_height);
+      let next = (st.scroll as isize).saturating_add(delta).max(0) as usize;
+      st.scroll = next.min(max);
+      self.force_full_redraw = true;
+    }
+  }
+
+  /// Scroll the list of the confirmation dialog by `pages` visible heights.
+  pub(crate) fn confirm_scroll_page(
+    &mut self,
+    pages: isize,
+  )
+  {
+    let page = match self.overlay
+    {
+      Overlay::Confirm(ref st) => st.page_height.max(1) as isize,
+      _ => return,
+    };
+    self.confirm_scroll(pages * page);
+  }
+}
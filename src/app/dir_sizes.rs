@@ -0,0 +1,214 @@
+//! Cumulative directory sizes for App.
+//!
+//! `:compute_dir_sizes` walks directories on a background thread and reports
+//! running totals, which the Size column shows for directories (with a
+//! spinner until each walk finishes). Results are kept per path for the rest
+//! of the session; running the command again recomputes them.
+
+use std::{
+  collections::HashMap,
+  path::{
+    Path,
+    PathBuf,
+  },
+  sync::mpsc,
+};
+
+use crate::{
+  app::{
+    App,
+    InfoMode,
+  },
+  core::vfs,
+};
+
+/// Send a running total after this many entries have been visited.
+const PROGRESS_EVERY: u64 = 2048;
+
+/// Size of one directory, possibly still being summed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DirSize
+{
+  pub bytes: u64,
+  pub done:  bool,
+}
+
+struct DirSizeUpdate
+{
+  path: PathBuf,
+  size: DirSize,
+}
+
+/// Computed directory sizes and the walker feeding them, if any.
+#[derive(Debug, Default)]
+pub struct DirSizeState
+{
+  sizes: HashMap<PathBuf, DirSize>,
+  rx:    Option<mpsc::Receiver<DirSizeUpdate>>,
+}
+
+/// Sum file sizes below `root` without following symlinks, reporting running
+/// totals. Returns `None` when the receiver went away.
+fn walk_dir_size(
+  root: &Path,
+  tx: &mpsc::Sender<DirSizeUpdate>,
+) -> Option<u64>
+{
+  let report = |bytes: u64, done: bool| {
+    tx.send(DirSizeUpdate {
+      path: root.to_path_buf(),
+      size: DirSize { bytes, done },
+    })
+    .ok()
+  };
+  if let Some(ap) = vfs::resolve(root)
+  {
+    let (bytes, _) = vfs::totals(&ap);
+    report(bytes, true)?;
+    return Some(bytes);
+  }
+  let mut stack = vec![root.to_path_buf()];
+  let (mut bytes, mut seen) = (0u64, 0u64);
+  while let Some(dir) = stack.pop()
+  {
+    let Ok(rd) = std::fs::read_dir(&dir)
+    else
+    {
+      continue;
+    };
+    for de in rd.flatten()
+    {
+      let Ok(meta) = de.metadata()
+      else
+      {
+        continue;
+      };
+      if meta.is_dir()
+      {
+        stack.push(de.path());
+      }
+      else
+      {
+        bytes += meta.len();
+      }
+      seen += 1;
+      if seen % PROGRESS_EVERY == 0
+      {
+        report(bytes, false)?;
+      }
+    }
+  }
+  report(bytes, true)?;
+  Some(bytes)
+}
+
+impl App
+{
+  /// Compute cumulative sizes of the selected directories (or of every
+  /// directory in the current listing) in the background.
+  pub(crate) fn compute_dir_sizes(&mut self)
+  {
+    if self.refuse_remote("compute_dir_sizes")
+    {
+      return;
+    }
+    let selected: Vec<PathBuf> = self
+      .current_entries
+      .iter()
+      .filter(|e| e.is_dir && self.selected.contains(&e.path))
+      .map(|e| e.path.clone())
+      .collect();
+    let targets = if selected.is_empty()
+    {
+      self
+        .current_entries
+        .iter()
+        .filter(|e| e.is_dir)
+        .map(|e| e.path.clone())
+        .collect()
+    }
+    else
+    {
+      selected
+    };
+    if targets.is_empty()
+    {
+      self.add_message("compute_dir_sizes: no directories here");
+      return;
+    }
+    // Replacing the receiver below stops a previous walk; drop its partial
+    // sums so they don't show a spinner forever
+    self.dir_sizes.sizes.retain(|_, s| s.done);
+    for p in &targets
+    {
+      self.dir_sizes.sizes.insert(p.clone(), DirSize { bytes: 0, done: false });
+    }
+    let (tx, rx) = mpsc::channel::<DirSizeUpdate>();
+    std::thread::spawn(move || {
+      for dir in targets
+      {
+        if walk_dir_size(&dir, &tx).is_none()
+        {
+          return;
+        }
+      }
+    });
+    self.dir_sizes.rx = Some(rx);
+    self.info_mode = InfoMode::Size;
+  }
+
+  /// Merge running totals from the size walker. Returns true on changes.
+  pub fn poll_dir_sizes(&mut self) -> bool
+  {
+    let Some(rx) = self.dir_sizes.rx.as_ref()
+    else
+    {
+      return false;
+    };
+    let mut changed = false;
+    loop
+    {
+      match rx.try_recv()
+      {
+        Ok(u) =>
+        {
+          self.dir_sizes.sizes.insert(u.path, u.size);
+          changed = true;
+        }
+        Err(mpsc::TryRecvError::Empty) => break,
+        Err(mpsc::TryRecvError::Disconnected) =>
+        {
+          self.dir_sizes.rx = None;
+          changed = true;
+          break;
+        }
+      }
+    }
+    changed
+  }
+
+  /// Whether directory sizes are still being computed.
+  pub fn is_computing_dir_sizes(&self) -> bool
+  {
+    self.dir_sizes.rx.is_some()
+  }
+
+  /// Block until the size walker finishes (tests and scripted use).
+  pub fn finish_dir_sizes(&mut self)
+  {
+    while self.is_computing_dir_sizes()
+    {
+      self.poll_dir_sizes();
+      std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+  }
+
+  /// Computed (or in-progress) cumulative size of the directory at `path`.
+  pub fn dir_size(
+    &self,
+    path: &Path,
+  ) -> Option<DirSize>
+  {
+    self.dir_sizes.sizes.get(path).copied()
+  }
+}
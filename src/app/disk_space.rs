@@ -0,0 +1,137 @@
+//! Free and total space of the current filesystem for App.
+//!
+//! The `{free_space}` and `{total_space}` header placeholders read figures
+//! that `df` reports on a background thread. They are fetched only while a
+//! template (or a Lua footer) might show them, again after changing
+//! directory, and at most every few seconds otherwise.
+
+use std::{
+  path::PathBuf,
+  sync::mpsc,
+  time::{
+    Duration,
+    Instant,
+  },
+};
+
+use crate::{
+  app::App,
+  config::FooterSegment,
+  core::{
+    mounts,
+    vfs,
+  },
+};
+
+/// How long figures for the same directory are reused.
+const REFRESH_EVERY: Duration = Duration::from_secs(10);
+
+/// Last figures read and the lookup in flight, if any.
+#[derive(Debug, Default)]
+pub struct DiskSpaceState
+{
+  /// Directory the figures belong to and when they were asked for.
+  path:  Option<PathBuf>,
+  at:    Option<Instant>,
+  /// Total and available bytes.
+  space: Option<(u64, u64)>,
+  rx:    Option<mpsc::Receiver<Option<(u64, u64)>>>,
+}
+
+impl App
+{
+  /// Whether a header or footer might show the free or total space.
+  fn wants_disk_space(&self) -> bool
+  {
+    let uses =
+      |t: &str| t.contains("{free_space") || t.contains("{total_space");
+    let ui = &self.config.ui;
+    let header = [&ui.header_left, &ui.header_right]
+      .iter()
+      .any(|t| t.as_deref().is_some_and(uses));
+    let footer = ui.footer.as_ref().is_some_and(|f| {
+      [&f.left, &f.right].iter().any(|seg| match seg
+      {
+        Some(FooterSegment::Template(t)) => uses(t),
+        // Lua footers get every value; there is no telling what they show
+        Some(FooterSegment::Lua(_)) => true,
+        None => false,
+      })
+    });
+    header || footer
+  }
+
+  /// Pick up figures from the `df` lookup and start a new one when they are
+  /// missing or stale. Returns true when the figures changed.
+  pub fn poll_disk_space(&mut self) -> bool
+  {
+    let mut changed = false;
+    if let Some(rx) = self.disk_space.rx.as_ref()
+    {
+      match rx.try_recv()
+      {
+        Ok(space) =>
+        {
+          changed = self.disk_space.space != space;
+          self.disk_space.space = space;
+          self.disk_space.rx = None;
+        }
+        Err(mpsc::TryRecvError::Empty) => return false,
+        Err(mpsc::TryRecvError::Disconnected) => self.disk_space.rx = None,
+      }
+    }
+    if !self.wants_disk_space()
+    {
+      return changed;
+    }
+    // Archives count against the filesystem holding the archive file
+    let dir = match vfs::locate(&self.cwd)
+    {
+      vfs::Location::Local => self.cwd.clone(),
+      vfs::Location::Archive(ap) => ap.archive,
+      vfs::Location::Remote(_) | vfs::Location::Drives =>
+      {
+        changed |= self.disk_space.space.take().is_some();
+        self.disk_space.path = None;
+        return changed;
+      }
+    };
+    let same_dir = self.disk_space.path.as_ref() == Some(&dir);
+    let fresh = self.disk_space.at.is_some_and(|t| t.elapsed() < REFRESH_EVERY);
+    if same_dir && fresh
+    {
+      return changed;
+    }
+    if !same_dir
+    {
+      // Don't show another filesystem's figures while waiting
+      changed |= self.disk_space.space.take().is_some();
+    }
+    let (tx, rx) = mpsc::channel();
+    let target = dir.clone();
+    std::thread::spawn(move || {
+      let _ = tx.send(mounts::usage(&target).map(|r| (r.total, r.avail)));
+    });
+    self.disk_space.path = Some(dir);
+    self.disk_space.at = Some(Instant::now());
+    self.disk_space.rx = Some(rx);
+    changed
+  }
+
+  /// Block until a pending `df` lookup answers (tests and scripted use).
+  pub fn finish_disk_space(&mut self)
+  {
+    self.poll_disk_space();
+    while self.disk_space.rx.is_some()
+    {
+      std::thread::sleep(Duration::from_millis(5));
+      self.poll_disk_space();
+    }
+  }
+
+  /// Total and available bytes of the current filesystem, once known.
+  pub fn disk_space(&self) -> Option<(u64, u64)>
+  {
+    self.disk_space.space
+  }
+}
@@ -0,0 +1,34 @@
+//! The drive list (`drives://`) for App.
+//!
+//! `:drives` shows every mounted filesystem as a directory; on Windows the
+//! list also sits above each drive root, so ascending from `C:\` lands on it
+//! with `C:\` under the cursor. The list itself is read-only.
+
+use std::path::Path;
+
+use crate::{
+  app::App,
+  core::vfs,
+};
+
+impl App
+{
+  /// Whether the current directory is the drive list.
+  pub fn in_drives(&self) -> bool
+  {
+    vfs::is_drives(&self.cwd)
+  }
+
+  /// Show the drive list, with the cursor on the drive holding the current
+  /// directory on Windows.
+  pub(crate) fn open_drives(&mut self)
+  {
+    if self.in_drives()
+    {
+      return;
+    }
+    let focus =
+      self.cwd.ancestors().find(|p| vfs::is_drive_root(p)).map(vfs::drive_name);
+    self.change_dir(Path::new(vfs::DRIVES), focus);
+  }
+}
@@ -0,0 +1,134 @@
+//! Lua event hooks for App.
+//!
+//! `lsv.on_event(name, fn)` registers `fn` as a Lua action that runs when the
+//! event happens: `startup` once the first listing is requested, `cd` when
+//! the current directory changes, `select` when the highlighted entry
+//! changes, and `quit` as lsv exits. Hooks get the same `(lsv, config)`
+//! arguments and effects as mapped actions, with `config.context.event` set
+//! to the event name and `config.context.previous` to the directory or file
+//! that was current before a `cd` or `select`.
+//!
+//! `cd` and `select` are detected by [`App::poll_event_hooks`] comparing the
+//! state against the last one seen, so several moves between two polls fire
+//! once.
+
+use std::path::PathBuf;
+
+use crate::app::App;
+
+/// Events Lua code can hook with `lsv.on_event`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppEvent
+{
+  Startup,
+  Cd,
+  Select,
+  Quit,
+}
+
+impl AppEvent
+{
+  pub const ALL: [AppEvent; 4] =
+    [AppEvent::Startup, AppEvent::Cd, AppEvent::Select, AppEvent::Quit];
+
+  pub fn name(self) -> &'static str
+  {
+    match self
+    {
+      AppEvent::Startup => "startup",
+      AppEvent::Cd => "cd",
+      AppEvent::Select => "select",
+      AppEvent::Quit => "quit",
+    }
+  }
+
+  /// Parse an event name, with or without the `on_` prefix.
+  pub fn from_name(name: &str) -> Option<AppEvent>
+  {
+    let name = name.strip_prefix("on_").unwrap_or(name);
+    AppEvent::ALL.into_iter().find(|e| e.name() == name)
+  }
+}
+
+/// Last directory and entry seen by the hooks, and the event being run.
+#[derive(Debug, Default)]
+pub struct EventState
+{
+  last_cwd:           Option<PathBuf>,
+  last_selected:      Option<PathBuf>,
+  /// Event whose hooks are running, with the previous directory or file.
+  pub(crate) current: Option<(AppEvent, Option<PathBuf>)>,
+}
+
+impl App
+{
+  fn has_event_hooks(
+    &self,
+    ev: AppEvent,
+  ) -> bool
+  {
+    self.config.events.get(ev.name()).is_some_and(|v| !v.is_empty())
+  }
+
+  /// Run the Lua hooks registered for `ev`.
+  pub fn emit_event(
+    &mut self,
+    ev: AppEvent,
+  )
+  {
+    self.emit_event_from(ev, None);
+  }
+
+  fn emit_event_from(
+    &mut self,
+    ev: AppEvent,
+    previous: Option<PathBuf>,
+  )
+  {
+    let Some(hooks) = self.config.events.get(ev.name()).cloned()
+    else
+    {
+      return;
+    };
+    self.events.current = Some((ev, previous));
+    for idx in hooks
+    {
+      let action = format!("run_lua:{}", idx);
+      if let Err(e) = crate::actions::dispatch_action(self, &action)
+      {
+        self.add_message(&format!("on_{} hook: {}", ev.name(), e));
+      }
+    }
+    self.events.current = None;
+  }
+
+  /// Fire `cd` and `select` hooks for changes since the last poll.
+  pub fn poll_event_hooks(&mut self)
+  {
+    if self.events.last_cwd.as_ref() != Some(&self.cwd)
+    {
+      let previous = self.events.last_cwd.replace(self.cwd.clone());
+      // The starting directory is reported by `startup`, not `cd`
+      if previous.is_some() && self.has_event_hooks(AppEvent::Cd)
+      {
+        self.emit_event_from(AppEvent::Cd, previous);
+      }
+    }
+    // Wait for listings to settle so a reselect doesn't fire twice
+    if self.current_load.is_some()
+    {
+      return;
+    }
+    let selected = self.selected_entry().map(|e| e.path.clone());
+    if selected.is_some() && selected != self.events.last_selected
+    {
+      let previous =
+        std::mem::replace(&mut self.events.last_selected, selected);
+      // Likewise the entry first highlighted at startup
+      if previous.is_some() && self.has_event_hooks(AppEvent::Select)
+      {
+        self.emit_event_from(AppEvent::Select, previous);
+      }
+    }
+  }
+}
@@ -0,0 +1,163 @@
+//! File properties overlay (`:file_info`) for App.
+//!
+//! Shows everything `stat` knows about the highlighted entry (absolute path,
+//! type and symlink target, MIME type, size, mode, owner, inode, link count,
+//! device and timestamps) followed by EXIF for images or stream details for
+//! audio and video (see [`crate::core::media_info`]), in the output overlay.
+
+use std::time::SystemTime;
+
+use crate::{
+  app::App,
+  core::{
+    media_info,
+    mime,
+  },
+  ui::format::{
+    format_time_abs,
+    human_size,
+  },
+};
+
+/// Timestamps are shown in full rather than with `ui.date_format`.
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S %z";
+
+impl App
+{
+  pub(crate) fn show_file_info(&mut self)
+  {
+    let Some((path, name)) =
+      self.selected_entry().map(|e| (e.path.clone(), e.name.clone()))
+    else
+    {
+      self.add_message("file_info: nothing selected");
+      return;
+    };
+    let meta = match std::fs::symlink_metadata(&path)
+    {
+      Ok(m) => m,
+      Err(e) =>
+      {
+        self.add_message(&format!("file_info: {}: {}", name, e));
+        return;
+      }
+    };
+    // Sizes, MIME type and media details describe what a symlink points to
+    let target_meta = std::fs::metadata(&path).ok();
+    let is_dir = target_meta.as_ref().is_some_and(|m| m.is_dir());
+    let is_file = target_meta.as_ref().is_some_and(|m| m.is_file());
+
+    let mut fields = vec![(
+      String::from("Path"),
+      std::path::absolute(&path).unwrap_or(path.clone()).display().to_string(),
+    )];
+    let kind = if is_dir { "directory" } else { "file" };
+    let kind = if meta.is_symlink()
+    {
+      let target = std::fs::read_link(&path)
+        .map(|t| t.display().to_string())
+        .unwrap_or_default();
+      match target_meta
+      {
+        Some(_) => format!("symlink to {} -> {}", kind, target),
+        None => format!("broken symlink -> {}", target),
+      }
+    }
+    else
+    {
+      kind.to_string()
+    };
+    fields.push((String::from("Type"), kind));
+    if is_dir
+    {
+      fields.push((String::from("MIME"), String::from("inode/directory")));
+    }
+    else if is_file
+    {
+      // A link's own name may lack the target's extension
+      let real = std::fs::canonicalize(&path).unwrap_or(path.clone());
+      fields.push((String::from("MIME"), mime::guess(&real)));
+      let len = target_meta.as_ref().map(|m| m.len()).unwrap_or(0);
+      fields.push((
+        String::from("Size"),
+        format!("{} ({} bytes)", human_size(len), len),
+      ));
+    }
+    #[cfg(unix)]
+    {
+      use std::os::unix::fs::MetadataExt;
+      let user = crate::ui::lookup_user_name(meta.uid());
+      let group = crate::ui::lookup_group_name(meta.gid());
+      let named = |name: Option<String>, id: u32| match name
+      {
+        Some(n) => format!("{} ({})", n, id),
+        None => id.to_string(),
+      };
+      fields.extend([
+        (
+          String::from("Mode"),
+          format!(
+            "{:04o} ({})",
+            meta.mode() & 0o7777,
+            crate::ui::row::mode_string(meta.mode())
+          ),
+        ),
+        (String::from("Owner"), named(user, meta.uid())),
+        (String::from("Group"), named(group, meta.gid())),
+        (String::from("Inode"), meta.ino().to_string()),
+        (String::from("Links"), meta.nlink().to_string()),
+        (String::from("Device"), format!("{:#x}", meta.dev())),
+      ]);
+    }
+    #[cfg(windows)]
+    {
+      use std::os::windows::fs::MetadataExt;
+      fields.push((
+        String::from("Attributes"),
+        crate::ui::row::attributes_string(meta.file_attributes()),
+      ));
+      if let Some(owner) = crate::core::winsec::owner_name(&path)
+      {
+        fields.push((String::from("Owner"), owner));
+      }
+    }
+    #[cfg(not(unix))]
+    fields.push((
+      String::from("Read-only"),
+      meta.permissions().readonly().to_string(),
+    ));
+
+    let time = |t: std::io::Result<SystemTime>| {
+      t.ok().map(|t| format_time_abs(t, TIME_FORMAT))
+    };
+    let mut times = vec![
+      ("Accessed", time(meta.accessed())),
+      ("Modified", time(meta.modified())),
+    ];
+    #[cfg(unix)]
+    {
+      use std::os::unix::fs::MetadataExt;
+      let changed = u64::try_from(meta.ctime()).ok().map(|secs| {
+        SystemTime::UNIX_EPOCH
+          + std::time::Duration::new(secs, meta.ctime_nsec() as u32)
+      });
+      times.push(("Changed", changed.map(|t| format_time_abs(t, TIME_FORMAT))));
+    }
+    times.push(("Created", time(meta.created())));
+    fields
+      .extend(times.into_iter().filter_map(|(k, v)| Some((k.to_string(), v?))));
+
+    if is_file
+    {
+      fields.extend(media_info::describe(&path));
+    }
+
+    let width =
+      fields.iter().map(|(k, _)| k.chars().count()).max().unwrap_or(0);
+    let text: Vec<String> = fields
+      .iter()
+      .map(|(k, v)| format!("{:<w$}  {}", format!("{}:", k), v, w = width + 1))
+      .collect();
+    self.display_output(&format!("Info: {}", name), &text.join("\n"));
+  }
+}
@@ -0,0 +1,182 @@
+//! Per-directory filters for the current pane.
+//!
+//! `:filter <pattern>` hides the entries of the current directory whose
+//! names don't match (see [`crate::core::listing::filter_matches`]) until
+//! the filter is cleared with `:filter` or `:filter_clear`. Filters are
+//! remembered per directory for the session and applied as listings load,
+//! so they hold across refreshes, sorting and coming back to the directory.
+//!
+//! Config filters registered with `lsv.filter(fn)` apply everywhere: an
+//! entry is hidden when `fn(entry, dir)` returns `false`, where `entry` has
+//! the fields of a Lua column and `dir` is the directory being listed.
+
+use std::{
+  collections::HashMap,
+  path::{
+    Path,
+    PathBuf,
+  },
+};
+
+use crate::app::{
+  App,
+  CommandPaneState,
+  DirEntryInfo,
+  Overlay,
+};
+
+impl App
+{
+  /// Filter pattern of the current directory, if any.
+  pub fn active_filter(&self) -> Option<&str>
+  {
+    self.filters.get(&self.cwd).map(|s| s.as_str())
+  }
+
+  /// Set the current directory's filter; an empty pattern clears it.
+  pub(crate) fn set_filter(
+    &mut self,
+    pattern: &str,
+  )
+  {
+    let pattern = pattern.trim();
+    if pattern.is_empty()
+    {
+      if self.filters.remove(&self.cwd).is_none()
+      {
+        return;
+      }
+    }
+    else
+    {
+      self.filters.insert(self.cwd.clone(), pattern.to_string());
+    }
+    self.refresh_lists();
+    self.refresh_preview();
+    self.force_full_redraw = true;
+  }
+
+  /// Open the `:` prompt with the current filter ready to edit.
+  pub(crate) fn open_filter_prompt(&mut self)
+  {
+    let input = format!("filter {}", self.active_filter().unwrap_or(""));
+    self.overlay = Overlay::CommandPane(Box::new(CommandPaneState {
+      prompt: ":".to_string(),
+      cursor: input.len(),
+      input,
+      show_suggestions: false,
+      history: Default::default(),
+      candidates: Vec::new(),
+    }));
+    self.force_full_redraw = true;
+  }
+
+  /// Drop the entries of `dir` from `from` on that `lsv.filter` functions
+  /// hide, and in tree and flat listings everything below a hidden
+  /// directory. `dirs` remembers which directories were kept, so a listing
+  /// that arrives in batches can be filtered a batch at a time.
+  pub(crate) fn apply_lua_filters(
+    &self,
+    dir: &Path,
+    entries: &mut Vec<DirEntryInfo>,
+    from: usize,
+    dirs: &mut HashMap<PathBuf, bool>,
+  )
+  {
+    if self.config.entry_filters.is_empty() || from >= entries.len()
+    {
+      return;
+    }
+    let new = entries.split_off(from);
+    entries.extend(new.into_iter().filter(|e| {
+      if !self.lua_ancestors_kept(dir, &e.path, dirs)
+      {
+        return false;
+      }
+      let keep = self.lua_filters_keep(dir, e);
+      if e.is_dir
+      {
+        dirs.insert(e.path.clone(), keep);
+      }
+      keep
+    }));
+  }
+
+  /// Whether the filters keep every directory between `dir` and `path`.
+  fn lua_ancestors_kept(
+    &self,
+    dir: &Path,
+    path: &Path,
+    dirs: &mut HashMap<PathBuf, bool>,
+  ) -> bool
+  {
+    let Some(rel) = path.parent().and_then(|p| p.strip_prefix(dir).ok())
+    else
+    {
+      return true;
+    };
+    let mut cur = dir.to_path_buf();
+    for part in rel.components()
+    {
+      cur.push(part);
+      let keep = match dirs.get(&cur)
+      {
+        Some(keep) => *keep,
+        None =>
+        {
+          let info = DirEntryInfo {
+            name:   part.as_os_str().to_string_lossy().into_owned(),
+            path:   cur.clone(),
+            is_dir: true,
+            size:   0,
+            mtime:  None,
+            ctime:  None,
+            meta:   None,
+          };
+          let keep = self.lua_filters_keep(dir, &info);
+          dirs.insert(cur.clone(), keep);
+          keep
+        }
+      };
+      if !keep
+      {
+        return false;
+      }
+    }
+    true
+  }
+
+  /// Whether every `lsv.filter` function keeps `e`. A function that fails
+  /// keeps it.
+  fn lua_filters_keep(
+    &self,
+    dir: &Path,
+    e: &DirEntryInfo,
+  ) -> bool
+  {
+    let Some(rt) = self.lua.as_ref()
+    else
+    {
+      return true;
+    };
+    let lua = rt.engine.lua();
+    self.config.entry_filters.iter().all(|idx| {
+      let call = || -> mlua::Result<bool> {
+        let Some(key) = rt.actions.get(*idx)
+        else
+        {
+          return Ok(true);
+        };
+        let func = lua.registry_value::<mlua::Function>(key)?;
+        let entry = crate::app::columns::entry_table(lua, e)?;
+        let keep = func
+          .call::<mlua::Value>((entry, dir.to_string_lossy().to_string()))?;
+        Ok(!matches!(keep, mlua::Value::Boolean(false)))
+      };
+      call().unwrap_or_else(|err| {
+        crate::trace::log(format!("[filter] lua filter error: {}", err));
+        true
+      })
+    })
+  }
+}
@@ -0,0 +1,309 @@
+//! Fuzzy file finder for App.
+//!
+//! Files below the current directory are indexed on a background thread and
+//! streamed into the finder overlay, which re-ranks them with
+//! [`crate::core::fuzzy`] as the query changes. Accepting a result jumps to its
+//! directory and selects the file.
+
+use std::{
+  path::Path,
+  sync::mpsc,
+};
+
+use crate::{
+  app::{
+    App,
+    FinderBatch,
+    FinderIndex,
+    FinderKind,
+    FinderState,
+    Overlay,
+  },
+  core::frecency,
+};
+
+/// Stop indexing after this many files.
+const MAX_INDEXED_FILES: usize = 100_000;
+/// Files per batch sent from the indexer.
+const INDEX_BATCH_SIZE: usize = 512;
+/// Results kept (and shown) per query.
+const MAX_RESULTS: usize = 500;
+
+/// Walk `root` depth-first without following symlinks, sending relative file
+/// paths in batches. Stops early when the receiver goes away.
+fn index_files(
+  root: &Path,
+  show_hidden: bool,
+  tx: &mpsc::Sender<FinderBatch>,
+)
+{
+  let mut stack = vec![root.to_path_buf()];
+  let mut batch = Vec::with_capacity(INDEX_BATCH_SIZE);
+  let mut total = 0usize;
+  while let Some(dir) = stack.pop()
+  {
+    let Ok(rd) = std::fs::read_dir(&dir)
+    else
+    {
+      continue;
+    };
+    for de in rd.flatten()
+    {
+      let name = de.file_name();
+      let name = name.to_string_lossy();
+      if name == ".git" || (!show_hidden && name.starts_with('.'))
+      {
+        continue;
+      }
+      let Ok(ft) = de.file_type()
+      else
+      {
+        continue;
+      };
+      let path = de.path();
+      if ft.is_dir()
+      {
+        stack.push(path);
+        continue;
+      }
+      let Ok(rel) = path.strip_prefix(root)
+      else
+      {
+        continue;
+      };
+      batch.push(rel.to_string_lossy().replace('\\', "/"));
+      total += 1;
+      if total >= MAX_INDEXED_FILES
+      {
+        let _ = tx.send(FinderBatch::Files(batch));
+        let _ = tx.send(FinderBatch::Done { truncated: true });
+        return;
+      }
+      if batch.len() >= INDEX_BATCH_SIZE
+        && tx.send(FinderBatch::Files(std::mem::take(&mut batch))).is_err()
+      {
+        return;
+      }
+    }
+  }
+  let _ = tx.send(FinderBatch::Files(batch));
+  let _ = tx.send(FinderBatch::Done { truncated: false });
+}
+
+pub(crate) fn rerank(st: &mut FinderState)
+{
+  st.results = if st.kind == FinderKind::Dirs
+  {
+    // Already in frecency order; only filter
+    st.files
+      .iter()
+      .enumerate()
+      .filter(|(_, d)| frecency::matches(&st.input, Path::new(d)))
+      .map(|(i, _)| i)
+      .take(MAX_RESULTS)
+      .collect()
+  }
+  else if st.input.trim().is_empty()
+  {
+    (0..st.files.len().min(MAX_RESULTS)).collect()
+  }
+  else
+  {
+    crate::core::fuzzy::rank(&st.input, &st.files, MAX_RESULTS)
+      .into_iter()
+      .map(|(i, _)| i)
+      .collect()
+  };
+  st.selected = st.selected.min(st.results.len().saturating_sub(1));
+}
+
+impl App
+{
+  /// Open the fuzzy finder over all files below the current directory.
+  pub(crate) fn open_finder(&mut self)
+  {
+    if self.refuse_remote("Finder")
+    {
+      return;
+    }
+    let root = self.cwd.clone();
+    let (tx, rx) = mpsc::channel::<FinderBatch>();
+    let walk_root = root.clone();
+    let show_hidden = self.config.ui.show_hidden;
+    std::thread::spawn(move || index_files(&walk_root, show_hidden, &tx));
+    self.finder_index = Some(FinderIndex { rx });
+    self.keys.pending.clear();
+    self.keys.last_at = None;
+    self.overlay = Overlay::Finder(Box::new(FinderState {
+      kind: FinderKind::Files,
+      root,
+      input: String::new(),
+      files: Vec::new(),
+      results: Vec::new(),
+      selected: 0,
+      indexing: true,
+      truncated: false,
+    }));
+    self.force_full_redraw = true;
+  }
+
+  /// Merge indexed files into the finder overlay. Returns true on changes.
+  pub fn poll_finder(&mut self) -> bool
+  {
+    let Some(index) = self.finder_index.as_ref()
+    else
+    {
+      return false;
+    };
+    let Overlay::Finder(ref mut st) = self.overlay
+    else
+    {
+      // Overlay closed; dropping the receiver stops the indexer
+      self.finder_index = None;
+      return false;
+    };
+    let mut changed = false;
+    let mut done = false;
+    for msg in index.rx.try_iter()
+    {
+      changed = true;
+      match msg
+      {
+        FinderBatch::Files(files) => st.files.extend(files),
+        FinderBatch::Done { truncated } =>
+        {
+          st.truncated = truncated;
+          done = true;
+          break;
+        }
+      }
+    }
+    if changed
+    {
+      rerank(st);
+    }
+    if done
+    {
+      st.indexing = false;
+      self.finder_index = None;
+    }
+    changed
+  }
+
+  /// Whether the finder is still indexing files.
+  pub fn is_finder_indexing(&self) -> bool
+  {
+    self.finder_index.is_some()
+  }
+
+  /// Current finder results as paths relative to the finder root.
+  pub fn finder_results(&self) -> Vec<String>
+  {
+    match self.overlay
+    {
+      Overlay::Finder(ref st) =>
+      {
+        st.results.iter().filter_map(|&i| st.files.get(i).cloned()).collect()
+      }
+      _ => Vec::new(),
+    }
+  }
+
+  /// Replace the finder query and re-rank results.
+  pub fn set_finder_query(
+    &mut self,
+    query: &str,
+  )
+  {
+    if let Overlay::Finder(ref mut st) = self.overlay
+    {
+      st.input = query.to_string();
+      st.selected = 0;
+      rerank(st);
+    }
+  }
+
+  pub(crate) fn finder_edit(
+    &mut self,
+    ch: Option<char>,
+  )
+  {
+    let query = match self.overlay
+    {
+      Overlay::Finder(ref st) =>
+      {
+        let mut q = st.input.clone();
+        match ch
+        {
+          Some(c) => q.push(c),
+          None =>
+          {
+            q.pop();
+          }
+        }
+        q
+      }
+      _ => return,
+    };
+    self.set_finder_query(&query);
+  }
+
+  pub(crate) fn finder_move(
+    &mut self,
+    delta: isize,
+  )
+  {
+    if let Overlay::Finder(ref mut st) = self.overlay
+      && !st.results.is_empty()
+    {
+      let last = st.results.len() as isize - 1;
+      st.selected = (st.selected as isize + delta).clamp(0, last) as usize;
+    }
+  }
+
+  pub(crate) fn close_finder(&mut self)
+  {
+    self.overlay = Overlay::None;
+    self.finder_index = None;
+    self.force_full_redraw = true;
+  }
+
+  /// Jump to the selected result: enter its directory and select the file.
+  pub fn finder_accept(&mut self)
+  {
+    let target = match self.overlay
+    {
+      Overlay::Finder(ref st) => st
+        .results
+        .get(st.selected)
+        .and_then(|&i| st.files.get(i))
+        .map(|rel| st.root.join(rel)),
+      _ => None,
+    };
+    let kind = match self.overlay
+    {
+      Overlay::Finder(ref st) => st.kind,
+      _ => FinderKind::Files,
+    };
+    self.close_finder();
+    let Some(path) = target
+    else
+    {
+      return;
+    };
+    if kind == FinderKind::Dirs
+    {
+      self.set_cwd(&path);
+      return;
+    }
+    let (Some(dir), Some(name)) =
+      (path.parent(), path.file_name().and_then(|s| s.to_str()))
+    else
+    {
+      return;
+    };
+    self.set_cwd(dir);
+    self.reselect_when_loaded(name);
+    self.refresh_preview();
+  }
+}
@@ -0,0 +1,141 @@
+//! Flat (recursive) view of the current directory for App.
+//!
+//! While flattened, the current pane lists every file below the directory
+//! down to a depth limit, named by their path relative to it, so a whole
+//! subtree can be sorted by size or date. The view applies to the directory
+//! it was turned on in and ends when the current directory changes.
+
+use std::{
+  path::{
+    Path,
+    PathBuf,
+  },
+  sync::mpsc,
+};
+
+use crate::app::{
+  App,
+  DirBatch,
+};
+
+/// Directory the flat view was enabled for, and how deep it lists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlattenView
+{
+  pub root:  PathBuf,
+  pub depth: usize,
+}
+
+/// Walk `root` down to `depth` levels without following symlinks, sending
+/// files (not directories) named by their relative path. Stops after
+/// `max_items` entries or when the receiver goes away.
+pub(crate) fn stream_flat_entries(
+  root: &Path,
+  depth: usize,
+  show_hidden: bool,
+  need_meta: bool,
+  max_items: usize,
+  batch_size: usize,
+  tx: &mpsc::Sender<DirBatch>,
+)
+{
+  let mut stack = vec![(root.to_path_buf(), 1usize)];
+  let mut batch = Vec::with_capacity(batch_size);
+  let mut total = 0usize;
+  'walk: while let Some((dir, level)) = stack.pop()
+  {
+    let rd = match std::fs::read_dir(&dir)
+    {
+      Ok(rd) => rd,
+      Err(e) if dir == root =>
+      {
+        let _ = tx.send(DirBatch::Error(e.to_string()));
+        return;
+      }
+      Err(_) => continue,
+    };
+    for de in rd.flatten()
+    {
+      let Some(mut info) =
+        crate::core::listing::entry_info(&de, show_hidden, need_meta)
+      else
+      {
+        continue;
+      };
+      if info.is_dir
+      {
+        if level < depth
+        {
+          stack.push((info.path, level + 1));
+        }
+        continue;
+      }
+      if let Ok(rel) = info.path.strip_prefix(root)
+      {
+        info.name = rel.to_string_lossy().replace('\\', "/");
+      }
+      batch.push(info);
+      total += 1;
+      if total >= max_items
+      {
+        break 'walk;
+      }
+      if batch.len() >= batch_size
+        && tx.send(DirBatch::Entries(std::mem::take(&mut batch))).is_err()
+      {
+        return;
+      }
+    }
+  }
+  if !batch.is_empty()
+  {
+    let _ = tx.send(DirBatch::Entries(batch));
+  }
+  let _ = tx.send(DirBatch::Done);
+}
+
+impl App
+{
+  /// Toggle the flat view of the current directory. `depth` overrides
+  /// `ui.flatten_depth`; giving a depth while flattened changes it instead of
+  /// turning the view off.
+  pub(crate) fn toggle_flatten(
+    &mut self,
+    depth: Option<usize>,
+  )
+  {
+    let active = self.flatten_depth().is_some();
+    if active && depth.is_none()
+    {
+      self.flatten = None;
+      self.add_message("Flat view off");
+    }
+    else
+    {
+      if crate::core::vfs::resolve(&self.cwd).is_some()
+      {
+        self.add_message("Flat view: not available inside archives");
+        return;
+      }
+      if self.refuse_remote("Flat view")
+      {
+        return;
+      }
+      let depth = depth.unwrap_or(self.config.ui.flatten_depth).max(1);
+      self.flatten = Some(FlattenView { root: self.cwd.clone(), depth });
+      self.add_message(&format!("Flat view: depth {}", depth));
+    }
+    self.refresh_lists();
+    if self.list_state.selected().is_none() && !self.current_entries.is_empty()
+    {
+      self.list_state.select(Some(0));
+    }
+    self.refresh_preview();
+  }
+
+  /// Depth of the flat view when the current directory is flattened.
+  pub fn flatten_depth(&self) -> Option<usize>
+  {
+    self.flatten.as_ref().filter(|f| f.root == self.cwd).map(|f| f.depth)
+  }
+}
@@ -0,0 +1,283 @@
+//! Content search (`:grep`) for App.
+//!
+//! Runs ripgrep (or `grep -r` when `rg` is not installed) below the current
+//! directory on a background thread and streams matches into the grep overlay.
+//! Closing the overlay drops the receiver, which stops the search.
+
+use std::{
+  io::BufRead,
+  path::{
+    Path,
+    PathBuf,
+  },
+  process::{
+    Command,
+    Stdio,
+  },
+  sync::mpsc,
+};
+
+use crate::app::{
+  App,
+  GrepMatch,
+  GrepState,
+  Overlay,
+  RunningGrep,
+};
+
+/// Stop collecting after this many matches.
+const MAX_GREP_MATCHES: usize = 10_000;
+
+/// Parse one `path\0line:text` record as printed by `rg --null` and
+/// `grep --null`.
+fn parse_match(
+  root: &Path,
+  raw: &[u8],
+) -> Option<GrepMatch>
+{
+  let nul = raw.iter().position(|&b| b == 0)?;
+  let path = String::from_utf8_lossy(&raw[..nul]).to_string();
+  let rest = String::from_utf8_lossy(&raw[nul + 1..]).to_string();
+  let (line, text) = rest.split_once(':')?;
+  let rel = path.strip_prefix("./").unwrap_or(&path);
+  Some(GrepMatch {
+    path: root.join(rel),
+    line: line.trim().parse().ok()?,
+    text: text.trim_end_matches(['\r', '\n']).to_string(),
+  })
+}
+
+fn spawn_search(
+  root: &Path,
+  pattern: &str,
+) -> std::io::Result<std::process::Child>
+{
+  let mut rg = Command::new("rg");
+  rg.args([
+    "--line-number",
+    "--no-heading",
+    "--color=never",
+    "--null",
+    "--",
+    pattern,
+    ".",
+  ]);
+  let mut grep = Command::new("grep");
+  grep.args(["-rnI", "--null", "-e", pattern, "."]);
+  for mut cmd in [rg, grep]
+  {
+    cmd
+      .current_dir(root)
+      .stdin(Stdio::null())
+      .stdout(Stdio::piped())
+      .stderr(Stdio::null());
+    match cmd.spawn()
+    {
+      Ok(child) => return Ok(child),
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+      Err(e) => return Err(e),
+    }
+  }
+  Err(std::io::Error::new(
+    std::io::ErrorKind::NotFound,
+    "neither rg nor grep found in PATH",
+  ))
+}
+
+impl App
+{
+  /// Start searching file contents below the current directory.
+  pub(crate) fn start_grep(
+    &mut self,
+    pattern: &str,
+  )
+  {
+    let pattern = pattern.trim();
+    if pattern.is_empty()
+    {
+      self.add_message("grep: missing pattern");
+      return;
+    }
+    if self.refuse_remote("grep")
+    {
+      return;
+    }
+    let root = self.cwd.clone();
+    let mut child = match spawn_search(&root, pattern)
+    {
+      Ok(c) => c,
+      Err(e) =>
+      {
+        self.add_message(&format!("grep: {}", e));
+        return;
+      }
+    };
+    let (tx, rx) = mpsc::channel::<Option<GrepMatch>>();
+    let stdout = child.stdout.take();
+    let worker_root = root.clone();
+    std::thread::spawn(move || {
+      if let Some(out) = stdout
+      {
+        let mut reader = std::io::BufReader::new(out);
+        let mut buf = Vec::new();
+        loop
+        {
+          buf.clear();
+          match reader.read_until(b'\n', &mut buf)
+          {
+            Ok(0) | Err(_) => break,
+            Ok(_) =>
+            {}
+          }
+          if let Some(m) = parse_match(&worker_root, &buf)
+            && tx.send(Some(m)).is_err()
+          {
+            // Overlay closed: stop the search
+            let _ = child.kill();
+            break;
+          }
+        }
+      }
+      let _ = child.wait();
+      let _ = tx.send(None);
+    });
+    self.running_grep = Some(RunningGrep { rx });
+    self.keys.pending.clear();
+    self.keys.last_at = None;
+    self.overlay = Overlay::Grep(Box::new(GrepState {
+      pattern: pattern.to_string(),
+      root,
+      matches: Vec::new(),
+      selected: 0,
+      running: true,
+      truncated: false,
+    }));
+    self.force_full_redraw = true;
+  }
+
+  /// Move streamed matches into the grep overlay. Returns true when new
+  /// results arrived.
+  pub fn poll_grep(&mut self) -> bool
+  {
+    let Some(run) = self.running_grep.as_ref()
+    else
+    {
+      return false;
+    };
+    let Overlay::Grep(ref mut st) = self.overlay
+    else
+    {
+      // Overlay was closed; dropping the receiver stops the worker
+      self.running_grep = None;
+      return false;
+    };
+    let mut changed = false;
+    let mut done = false;
+    for msg in run.rx.try_iter()
+    {
+      changed = true;
+      match msg
+      {
+        Some(m) if st.matches.len() < MAX_GREP_MATCHES => st.matches.push(m),
+        Some(_) =>
+        {
+          st.truncated = true;
+          done = true;
+          break;
+        }
+        None =>
+        {
+          done = true;
+          break;
+        }
+      }
+    }
+    if done
+    {
+      st.running = false;
+      self.running_grep = None;
+    }
+    changed
+  }
+
+  /// Whether a grep is still producing results.
+  pub fn is_grep_running(&self) -> bool
+  {
+    self.running_grep.is_some()
+  }
+
+  /// Number of matches collected by the open grep overlay.
+  pub fn grep_match_count(&self) -> usize
+  {
+    match self.overlay
+    {
+      Overlay::Grep(ref st) => st.matches.len(),
+      _ => 0,
+    }
+  }
+
+  pub(crate) fn grep_move(
+    &mut self,
+    delta: isize,
+  )
+  {
+    if let Overlay::Grep(ref mut st) = self.overlay
+      && !st.matches.is_empty()
+    {
+      let last = st.matches.len() as isize - 1;
+      st.selected = (st.selected as isize + delta).clamp(0, last) as usize;
+    }
+  }
+
+  pub(crate) fn close_grep(&mut self)
+  {
+    self.overlay = Overlay::None;
+    self.running_grep = None;
+    self.force_full_redraw = true;
+  }
+
+  /// Open the selected match's file at its line, leaving the results open.
+  pub(crate) fn grep_open_at_line(&mut self)
+  {
+    let target = match self.overlay
+    {
+      Overlay::Grep(ref st) =>
+      {
+        st.matches.get(st.selected).map(|m| (m.path.clone(), m.line))
+      }
+      _ => None,
+    };
+    if let Some((path, line)) = target
+    {
+      self.open_at_line(&path, line as usize);
+    }
+  }
+
+  /// Navigate to the directory holding the selected match and select it.
+  pub(crate) fn grep_jump(&mut self)
+  {
+    let target: Option<PathBuf> = match self.overlay
+    {
+      Overlay::Grep(ref st) =>
+      {
+        st.matches.get(st.selected).map(|m| m.path.clone())
+      }
+      _ => None,
+    };
+    let Some(path) = target
+    else
+    {
+      return;
+    };
+    self.close_grep();
+    let (Some(dir), Some(name)) =
+      (path.parent(), path.file_name().and_then(|s| s.to_str()))
+    else
+    {
+      return;
+    };
+    self.set_cwd(dir);
+    self.reselect_when_loaded(name);
+    self.refresh_preview();
+  }
+}
@@ -0,0 +1,202 @@
+//! `:help` overlay for App.
+//!
+//! The text is generated when the overlay opens, so it lists the key
+//! bindings in effect (those from `init.lua` included), the built-in actions
+//! they can run and every `:` command, Lua ones too.
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+  app::{
+    App,
+    HelpState,
+    Overlay,
+  },
+  keymap::normalize_sequence,
+  ui::overlays::whichkey::{
+    action_label,
+    format_seq_for_display,
+  },
+};
+
+/// Widest key column before descriptions stop lining up.
+const MAX_KEY_WIDTH: usize = 16;
+
+/// `name` with its arguments: `[a|b]` when it also runs without one,
+/// `<a|b>` when one is required.
+fn command_usage(
+  name: &str,
+  bare: bool,
+  args: &[&str],
+) -> String
+{
+  match (args.is_empty(), bare)
+  {
+    (true, _) => name.to_string(),
+    (false, true) => format!("{} [{}]", name, args.join("|")),
+    (false, false) => format!("{} <{}>", name, args.join("|")),
+  }
+}
+
+impl App
+{
+  fn help_text(&self) -> Vec<String>
+  {
+    let mut out = vec![String::from("Keys")];
+    // Later bindings of a sequence replace earlier ones
+    let mut seen = std::collections::HashSet::new();
+    let mut maps: Vec<&crate::config::KeyMapping> = self
+      .keys
+      .maps
+      .iter()
+      .rev()
+      .filter(|km| seen.insert(normalize_sequence(&km.sequence)))
+      .collect();
+    maps.sort_by_key(|km| (km.sequence.to_lowercase(), km.sequence.clone()));
+    let mut rows: Vec<(String, String)> = vec![
+      (":".into(), "Open the command pane".into()),
+      ("?".into(), "Show the keys that can follow".into()),
+      ("Esc".into(), "Cancel a key sequence or close the overlay".into()),
+    ];
+    for km in maps
+    {
+      let label =
+        km.description.clone().unwrap_or_else(|| action_label(&km.action));
+      let action = if km.action.starts_with("run_lua:")
+      {
+        String::from("Lua")
+      }
+      else if km.action.starts_with("shell:")
+      {
+        String::from("shell")
+      }
+      else
+      {
+        km.action.clone()
+      };
+      rows.push((
+        format_seq_for_display(&km.sequence),
+        format!("{} ({})", label, action),
+      ));
+    }
+    let width = rows
+      .iter()
+      .map(|(k, _)| k.width())
+      .filter(|w| *w <= MAX_KEY_WIDTH)
+      .max()
+      .unwrap_or(0);
+    for (keys, text) in rows
+    {
+      let pad = width.saturating_sub(keys.width());
+      out.push(format!("  {}{}  {}", keys, " ".repeat(pad), text));
+    }
+
+    out.push(String::new());
+    out.push(String::from("Actions"));
+    let width = crate::actions::internal::ACTIONS
+      .iter()
+      .map(|(name, _)| name.len())
+      .max()
+      .unwrap_or(0);
+    for (name, desc) in crate::actions::internal::ACTIONS
+    {
+      out.push(format!("  {:<width$}  {}", name, desc, width = width));
+    }
+
+    out.push(String::new());
+    out.push(String::from("Commands"));
+    // One line per command with its argument choices
+    let mut commands: Vec<(&str, bool, Vec<&str>)> = Vec::new();
+    for entry in crate::commands::all()
+    {
+      let (name, arg) = match entry.split_once(' ')
+      {
+        Some((name, arg)) => (name, Some(arg)),
+        None => (*entry, None),
+      };
+      let idx = match commands.iter().position(|(n, _, _)| *n == name)
+      {
+        Some(i) => i,
+        None =>
+        {
+          commands.push((name, false, Vec::new()));
+          commands.len() - 1
+        }
+      };
+      match arg
+      {
+        Some(arg) => commands[idx].2.push(arg),
+        None => commands[idx].1 = true,
+      }
+    }
+    for (name, bare, args) in &commands
+    {
+      out.push(format!("  :{}", command_usage(name, *bare, args)));
+    }
+    for cmd in &self.config.commands
+    {
+      let args: Vec<&str> = cmd.complete.iter().map(String::as_str).collect();
+      out.push(format!("  :{} (Lua)", command_usage(&cmd.name, true, &args)));
+    }
+    out
+  }
+
+  /// Open the help overlay, generated from the current keymap and commands.
+  pub(crate) fn open_help(&mut self)
+  {
+    let lines = self.help_text();
+    self.keys.pending.clear();
+    self.keys.last_at = None;
+    self.overlay =
+      Overlay::Help(Box::new(HelpState { lines, scroll: 0, page_height: 1 }));
+    self.force_full_redraw = true;
+  }
+
+  /// Lines of the help overlay, while it is open.
+  pub fn help_lines(&self) -> Option<&[String]>
+  {
+    match self.overlay
+    {
+      Overlay::Help(ref st) => Some(&st.lines),
+      _ => None,
+    }
+  }
+
+  /// Scroll the help text by `delta` lines (negative scrolls up).
+  pub fn help_scroll(
+    &mut self,
+    delta: isize,
+  )
+  {
+    if let Overlay::Help(ref mut st) = self.overlay
+    {
+      let max = st.lines.len().saturating_sub(st.page_height);
+      let next = (st.scroll as isize).saturating_add(delta).max(0) as usize;
+      st.scroll = next.min(max);
+    }
+  }
+
+  /// Scroll the help text by `pages` visible heights.
+  pub(crate) fn help_scroll_page(
+    &mut self,
+    pages: isize,
+  )
+  {
+    let page = match self.overlay
+    {
+      Overlay::Help(ref st) => st.page_height.max(1) as isize,
+      _ => return,
+    };
+    self.help_scroll(pages * page);
+  }
+
+  /// First help line shown.
+  pub fn help_offset(&self) -> Option<usize>
+  {
+    match self.overlay
+    {
+      Overlay::Help(ref st) => Some(st.scroll),
+      _ => None,
+    }
+  }
+}
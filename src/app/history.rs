@@ -0,0 +1,240 @@
+//! Command pane history for App.
+//!
+//! Command lines run from `:` and searches entered at `/` are recorded, and
+//! `Up`/`Down` in the command pane step through earlier entries for the same
+//! prompt (only those starting with the text already typed, if any).
+//! `Ctrl-r` searches the history incrementally. New entries are appended to
+//! `history` under the config root on exit.
+
+use std::path::PathBuf;
+
+use crate::{
+  app::{
+    App,
+    Overlay,
+  },
+  core::history,
+};
+
+/// Loaded entries plus the ones added this session.
+#[derive(Debug, Default)]
+pub struct CommandHistory
+{
+  entries: Vec<String>,
+  added:   Vec<String>,
+}
+
+/// Where a command pane is in the history while browsing or searching.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryNav
+{
+  /// Position among the matching entries, newest first.
+  pub index:  Option<usize>,
+  /// Input typed before browsing started, restored when stepping back.
+  pub draft:  String,
+  /// Query of an active `Ctrl-r` search.
+  pub search: Option<String>,
+  /// Matches skipped by repeated `Ctrl-r`.
+  pub skip:   usize,
+}
+
+impl CommandHistory
+{
+  /// Entries entered at `prompt`, newest first, without the prompt.
+  fn for_prompt<'a>(
+    &'a self,
+    prompt: &'a str,
+  ) -> impl Iterator<Item = &'a str> + 'a
+  {
+    self.entries.iter().rev().filter_map(move |e| e.strip_prefix(prompt))
+  }
+}
+
+impl App
+{
+  fn history_path(&self) -> Option<PathBuf>
+  {
+    self.theme_root_dir().map(|root| root.join("history"))
+  }
+
+  /// Read the history saved by earlier runs.
+  pub(crate) fn load_history(&mut self)
+  {
+    if let Some(path) = self.history_path()
+    {
+      self.history.entries = history::load_history(&path);
+    }
+  }
+
+  /// Append the entries added this session to the history file.
+  pub fn save_history(&self)
+  {
+    if let Some(path) = self.history_path()
+    {
+      let _ = history::append_history(&path, &self.history.added);
+    }
+  }
+
+  /// Remember `line` as entered at `prompt`.
+  pub(crate) fn record_history(
+    &mut self,
+    prompt: &str,
+    line: &str,
+  )
+  {
+    let line = line.trim();
+    if line.is_empty()
+    {
+      return;
+    }
+    let entry = format!("{}{}", prompt, line);
+    history::push_entry(
+      &mut self.history.entries,
+      &entry,
+      history::MAX_ENTRIES,
+    );
+    self.history.added.retain(|e| *e != entry);
+    self.history.added.push(entry);
+  }
+
+  /// Earlier (`older`) or later history entry into the command pane.
+  pub(crate) fn history_step(
+    &mut self,
+    older: bool,
+  )
+  {
+    let Overlay::CommandPane(ref mut st) = self.overlay
+    else
+    {
+      return;
+    };
+    let nav = &mut st.history;
+    nav.search = None;
+    if nav.index.is_none()
+    {
+      if !older
+      {
+        return;
+      }
+      nav.draft = st.input.clone();
+    }
+    let matches: Vec<&str> = self
+      .history
+      .for_prompt(&st.prompt)
+      .filter(|e| e.starts_with(nav.draft.as_str()))
+      .collect();
+    let next = match (nav.index, older)
+    {
+      (None, _) => Some(0),
+      (Some(i), true) => Some((i + 1).min(matches.len().saturating_sub(1))),
+      (Some(0), false) => None,
+      (Some(i), false) => Some(i - 1),
+    };
+    match next.and_then(|i| matches.get(i).map(|e| (i, e.to_string())))
+    {
+      Some((i, entry)) =>
+      {
+        nav.index = Some(i);
+        st.input = entry;
+      }
+      None =>
+      {
+        nav.index = None;
+        st.input = nav.draft.clone();
+      }
+    }
+    st.cursor = st.input.len();
+    self.force_full_redraw = true;
+  }
+
+  /// `Ctrl-r`: start an incremental history search, or move on to the next
+  /// older match of the current one.
+  pub(crate) fn history_search_next(&mut self)
+  {
+    if let Overlay::CommandPane(ref mut st) = self.overlay
+    {
+      match st.history.search
+      {
+        Some(_) => st.history.skip += 1,
+        None =>
+        {
+          st.history.draft = st.input.clone();
+          st.history.search = Some(String::new());
+          st.history.skip = 0;
+        }
+      }
+    }
+    self.history_search_update();
+  }
+
+  /// Edit the query of the active history search.
+  pub(crate) fn history_search_edit(
+    &mut self,
+    ch: Option<char>,
+  )
+  {
+    if let Overlay::CommandPane(ref mut st) = self.overlay
+      && let Some(q) = st.history.search.as_mut()
+    {
+      match ch
+      {
+        Some(c) => q.push(c),
+        None =>
+        {
+          q.pop();
+        }
+      }
+      st.history.skip = 0;
+    }
+    self.history_search_update();
+  }
+
+  /// End the history search, keeping the match (`keep`) or the input typed
+  /// before it started.
+  pub(crate) fn history_search_end(
+    &mut self,
+    keep: bool,
+  )
+  {
+    if let Overlay::CommandPane(ref mut st) = self.overlay
+      && st.history.search.take().is_some()
+      && !keep
+    {
+      st.input = st.history.draft.clone();
+      st.cursor = st.input.len();
+    }
+    self.force_full_redraw = true;
+  }
+
+  fn history_search_update(&mut self)
+  {
+    let Overlay::CommandPane(ref mut st) = self.overlay
+    else
+    {
+      return;
+    };
+    let Some(query) = st.history.search.as_deref()
+    else
+    {
+      return;
+    };
+    let matches: Vec<&str> = self
+      .history
+      .for_prompt(&st.prompt)
+      .filter(|e| e.contains(query))
+      .collect();
+    // Stay on the oldest match once there are no more
+    if st.history.skip >= matches.len()
+    {
+      st.history.skip = matches.len().saturating_sub(1);
+    }
+    st.input = match matches.get(st.history.skip)
+    {
+      Some(m) => m.to_string(),
+      None if query.is_empty() => st.history.draft.clone(),
+      None => String::new(),
+    };
+    st.cursor = st.input.len();
+    self.force_full_redraw = true;
+  }
+}
@@ -0,0 +1,172 @@
+//! Running commands attached to the terminal for App.
+//!
+//! The TUI gives up the terminal while the command runs: raw mode is turned
+//! off and the alternate screen and mouse capture are left, then restored
+//! once it exits and the whole screen is redrawn. `:run_interactive <cmd>`
+//! runs `cmd` through the shell with the selected files (or the current one)
+//! as its arguments; with no command, or with `:open_shell`, it starts
+//! `$SHELL` in the current directory with `LSV_LEVEL` set one above lsv's
+//! own, so prompts can show the shell is nested.
+
+use std::{
+  path::PathBuf,
+  process::Command,
+};
+
+use crossterm::{
+  event::{
+    DisableMouseCapture,
+    EnableMouseCapture,
+  },
+  execute,
+  terminal::{
+    EnterAlternateScreen,
+    LeaveAlternateScreen,
+    disable_raw_mode,
+    enable_raw_mode,
+  },
+};
+
+use crate::{
+  app::App,
+  core::shell::{
+    self,
+    Placeholders,
+  },
+};
+
+/// Run `f` with the terminal handed back to the user. Nothing is changed
+/// when there is no terminal.
+pub(crate) fn with_terminal<T>(f: impl FnOnce() -> T) -> T
+{
+  let mut tty = crate::util::terminal_output().ok();
+  if let Some(tty) = tty.as_mut()
+  {
+    disable_raw_mode().ok();
+    let _ = execute!(tty, DisableMouseCapture, LeaveAlternateScreen);
+  }
+  let out = f();
+  if let Some(tty) = tty.as_mut()
+  {
+    enable_raw_mode().ok();
+    let _ = execute!(tty, EnterAlternateScreen, EnableMouseCapture);
+  }
+  out
+}
+
+/// The user's shell, or the platform default.
+fn shell_command() -> String
+{
+  let default = if cfg!(windows) { "cmd" } else { "sh" };
+  std::env::var("SHELL")
+    .ok()
+    .filter(|s| !s.trim().is_empty())
+    .unwrap_or_else(|| default.to_string())
+}
+
+impl App
+{
+  /// The files a command works on: the selection, or the current entry.
+  pub(crate) fn command_files(&self) -> Vec<PathBuf>
+  {
+    let mut files: Vec<PathBuf> = self.selected.iter().cloned().collect();
+    if files.is_empty()
+    {
+      files.extend(self.selected_entry().map(|e| e.path.clone()));
+    }
+    files.sort();
+    files
+  }
+
+  /// What the placeholders of a command run here stand for, with `files`
+  /// as `{files}`.
+  pub(crate) fn placeholders(
+    &self,
+    files: &[PathBuf],
+  ) -> Placeholders
+  {
+    Placeholders {
+      file:      self.selected_entry().map(|e| e.path.clone()),
+      files:     files.to_vec(),
+      dir:       self.cwd.clone(),
+      clipboard: self
+        .clipboard
+        .as_ref()
+        .map(|c| c.items.clone())
+        .unwrap_or_default(),
+      extra:     Vec::new(),
+    }
+  }
+
+  /// Start `$SHELL` in the current directory, with `LSV_LEVEL` telling it
+  /// how many lsv instances it is nested in. Lists are refreshed once it
+  /// exits.
+  pub(crate) fn open_shell(&mut self) -> bool
+  {
+    self.run_interactive("")
+  }
+
+  /// Warn when lsv itself runs in a shell an lsv instance started.
+  pub fn warn_if_nested(&mut self)
+  {
+    if let Ok(level) = std::env::var("LSV_LEVEL")
+      && level.trim().parse::<u32>().is_ok_and(|n| n > 0)
+    {
+      self.add_warning(&format!(
+        "lsv is nested in a shell started by lsv (LSV_LEVEL={}); exit that \
+         shell to return",
+        level.trim()
+      ));
+    }
+  }
+
+  /// Run `cmd` attached to the terminal, or `$SHELL` when `cmd` is empty.
+  /// Returns whether it exited successfully.
+  pub(crate) fn run_interactive(
+    &mut self,
+    cmd: &str,
+  ) -> bool
+  {
+    let cmd = cmd.trim();
+    let mut command = if cmd.is_empty()
+    {
+      let mut c = Command::new(shell_command());
+      let level = shell::next_level(std::env::var("LSV_LEVEL").ok().as_deref());
+      c.env("LSV_LEVEL", level.to_string());
+      c
+    }
+    else
+    {
+      let files = self.command_files();
+      let shell = &self.config.command_shell;
+      let expanded = shell::expand(shell, cmd, &self.placeholders(&files));
+      shell::with_args(shell, &expanded, &files)
+    };
+    command.current_dir(&self.cwd);
+    crate::trace::log(format!(
+      "[interactive] cwd='{}' cmd='{}'",
+      self.cwd.display(),
+      cmd
+    ));
+    let status = with_terminal(|| command.status());
+    self.force_full_redraw = true;
+    // The command may have changed the directory's contents
+    self.refresh_lists();
+    self.refresh_preview();
+    let what = if cmd.is_empty() { "shell" } else { cmd };
+    match status
+    {
+      Ok(s) if s.success() => true,
+      Ok(s) =>
+      {
+        self.add_message(&format!("{} exited with {}", what, s));
+        false
+      }
+      Err(e) =>
+      {
+        self.add_error(&format!("Cannot run {}: {}", what, e));
+        false
+      }
+    }
+  }
+}
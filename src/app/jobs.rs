@@ -0,0 +1,922 @@
+//! Background job queue for file operations.
+//!
+//! Copy, move, delete, trash, archive extraction, compression and recursive
+//! permission changes run on a worker thread, one job at a time; further jobs
+//! wait in FIFO order. Workers report progress over a channel that the runtime
+//! loop drains via [`App::poll_jobs`], and can be cancelled through a shared
+//! flag checked between chunks.
+
+use std::{
+  path::{
+    Path,
+    PathBuf,
+  },
+  sync::{
+    Arc,
+    atomic::{
+      AtomicBool,
+      Ordering,
+    },
+    mpsc,
+  },
+  time::{
+    Duration,
+    Instant,
+  },
+};
+
+use crate::{
+  actions::effects::{
+    FsJob,
+    FsOp,
+  },
+  app::{
+    App,
+    MessageLevel,
+    Overlay,
+  },
+  core::{
+    fs_ops::{
+      self,
+      CopyProgress,
+    },
+    pack::{
+      self,
+      PackSpec,
+    },
+    permissions::PermissionRequest,
+    remote,
+    trash::{
+      self,
+      TrashLocation,
+    },
+    vfs,
+  },
+};
+
+/// Finished jobs kept around for the jobs overlay.
+const MAX_FINISHED_JOBS: usize = 20;
+/// Minimum interval between progress updates sent by a worker.
+const PROGRESS_INTERVAL_MS: u64 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobKind
+{
+  Copy,
+  Move,
+  Delete,
+  Trash,
+  Extract,
+  Compress,
+  #[cfg_attr(not(unix), allow(dead_code))]
+  Permissions,
+}
+
+impl JobKind
+{
+  pub fn label(self) -> &'static str
+  {
+    match self
+    {
+      JobKind::Copy => "Copy",
+      JobKind::Move => "Move",
+      JobKind::Delete => "Delete",
+      JobKind::Trash => "Trash",
+      JobKind::Extract => "Extract",
+      JobKind::Compress => "Compress",
+      JobKind::Permissions => "Permissions",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct JobProgress
+{
+  pub files_done:  u64,
+  pub files_total: u64,
+  pub bytes_done:  u64,
+  pub bytes_total: u64,
+  pub current:     Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus
+{
+  Queued,
+  Running,
+  Done,
+  Failed,
+  Cancelled,
+}
+
+/// Where one pasted item goes once its name conflict is settled.
+#[derive(Debug, Clone)]
+pub struct PasteTarget
+{
+  pub dest:      PathBuf,
+  /// Replace whatever is at `dest` instead of skipping the item.
+  pub overwrite: bool,
+}
+
+#[derive(Debug)]
+pub struct Job
+{
+  pub id:       u64,
+  pub kind:     JobKind,
+  pub items:    Vec<PathBuf>,
+  pub dest_dir: Option<PathBuf>,
+  /// One per item for pastes that settled name conflicts; empty means each
+  /// item goes to `dest_dir` under its own name.
+  pub targets:  Vec<PasteTarget>,
+  /// The archive a compress job makes.
+  pub pack:     Option<PackSpec>,
+  /// What a permissions job changes.
+  pub perms:    Option<PermissionRequest>,
+  /// Entry to highlight once the job has succeeded.
+  pub select:   Option<PathBuf>,
+  pub status:   JobStatus,
+  pub progress: JobProgress,
+  pub errors:   Vec<String>,
+  cancel:       Arc<AtomicBool>,
+}
+
+enum JobEvent
+{
+  Progress(JobProgress),
+  Error(String),
+  Finished
+  {
+    cancelled: bool,
+  },
+}
+
+/// All known jobs (queued, running and recently finished) plus the channel of
+/// the running one.
+#[derive(Default)]
+pub struct JobQueue
+{
+  pub jobs: Vec<Job>,
+  next_id:  u64,
+  running:  Option<(u64, mpsc::Receiver<JobEvent>)>,
+}
+
+impl JobQueue
+{
+  fn job_mut(
+    &mut self,
+    id: u64,
+  ) -> Option<&mut Job>
+  {
+    self.jobs.iter_mut().find(|j| j.id == id)
+  }
+}
+
+impl App
+{
+  /// Queue a file operation; it starts immediately when no job is running.
+  pub(crate) fn enqueue_job(
+    &mut self,
+    kind: JobKind,
+    items: Vec<PathBuf>,
+    dest_dir: Option<PathBuf>,
+  ) -> u64
+  {
+    self.push_job(kind, items, Vec::new(), dest_dir, None, None)
+  }
+
+  /// Queue a copy or move whose destinations were chosen item by item.
+  pub(crate) fn enqueue_paste(
+    &mut self,
+    kind: JobKind,
+    plan: Vec<(PathBuf, PasteTarget)>,
+    dest_dir: PathBuf,
+  ) -> u64
+  {
+    let (items, targets) = plan.into_iter().unzip();
+    self.push_job(kind, items, targets, Some(dest_dir), None, None)
+  }
+
+  /// Queue packing `items` into the archive `spec` describes.
+  pub(crate) fn enqueue_compress(
+    &mut self,
+    items: Vec<PathBuf>,
+    spec: PackSpec,
+  ) -> u64
+  {
+    self.push_job(JobKind::Compress, items, Vec::new(), None, Some(spec), None)
+  }
+
+  /// Queue a recursive mode and owner change of `items`.
+  #[cfg_attr(not(unix), allow(dead_code))]
+  pub(crate) fn enqueue_permissions(
+    &mut self,
+    items: Vec<PathBuf>,
+    req: PermissionRequest,
+  ) -> u64
+  {
+    let kind = JobKind::Permissions;
+    self.push_job(kind, items, Vec::new(), None, None, Some(req))
+  }
+
+  /// Queue a copy, move or removal a Lua action asked for through `lsv.fs`.
+  pub(crate) fn enqueue_fs_job(
+    &mut self,
+    job: FsJob,
+  )
+  {
+    let kind = match job.op
+    {
+      FsOp::Copy => JobKind::Copy,
+      FsOp::Move => JobKind::Move,
+      FsOp::Remove =>
+      {
+        self.enqueue_job(JobKind::Delete, job.items, None);
+        return;
+      }
+    };
+    let dest_dir = job
+      .dests
+      .first()
+      .and_then(|d| d.parent())
+      .map_or_else(|| self.cwd.clone(), Path::to_path_buf);
+    let targets =
+      job.dests.into_iter().map(|dest| PasteTarget { dest, overwrite: false });
+    self.enqueue_paste(
+      kind,
+      job.items.into_iter().zip(targets).collect(),
+      dest_dir,
+    );
+  }
+
+  fn push_job(
+    &mut self,
+    kind: JobKind,
+    items: Vec<PathBuf>,
+    targets: Vec<PasteTarget>,
+    dest_dir: Option<PathBuf>,
+    pack: Option<PackSpec>,
+    perms: Option<PermissionRequest>,
+  ) -> u64
+  {
+    self.jobs.next_id += 1;
+    let id = self.jobs.next_id;
+    self.jobs.jobs.push(Job {
+      id,
+      kind,
+      items,
+      dest_dir,
+      targets,
+      pack,
+      perms,
+      select: None,
+      status: JobStatus::Queued,
+      progress: JobProgress::default(),
+      errors: Vec::new(),
+      cancel: Arc::new(AtomicBool::new(false)),
+    });
+    if self.jobs.running.is_some()
+    {
+      self.add_message(&format!("{} queued (job {})", kind.label(), id));
+    }
+    self.start_next_job();
+    id
+  }
+
+  fn start_next_job(&mut self)
+  {
+    if self.jobs.running.is_some()
+    {
+      return;
+    }
+    let Some(job) =
+      self.jobs.jobs.iter_mut().find(|j| j.status == JobStatus::Queued)
+    else
+    {
+      return;
+    };
+    job.status = JobStatus::Running;
+    let (tx, rx) = mpsc::channel();
+    let kind = job.kind;
+    let items = job.items.clone();
+    let dest_dir = job.dest_dir.clone();
+    let targets = job.targets.clone();
+    let pack = job.pack.clone();
+    let perms = job.perms.clone();
+    let cancel = job.cancel.clone();
+    self.jobs.running = Some((job.id, rx));
+    std::thread::spawn(move || match (pack, perms)
+    {
+      (Some(spec), _) => run_compress(&spec, &items, &cancel, &tx),
+      (_, Some(req)) => run_permissions(&req, &items, &cancel, &tx),
+      _ => run_job(kind, &items, &targets, dest_dir.as_deref(), &cancel, &tx),
+    });
+  }
+
+  /// Whether any job is running or queued.
+  pub fn has_active_jobs(&self) -> bool
+  {
+    self
+      .jobs
+      .jobs
+      .iter()
+      .any(|j| matches!(j.status, JobStatus::Queued | JobStatus::Running))
+  }
+
+  /// Apply progress from the running job and start the next one when it
+  /// finishes. Returns true when anything changed.
+  pub fn poll_jobs(&mut self) -> bool
+  {
+    let mut changed = false;
+    let mut finished = None;
+    if let Some((id, rx)) = self.jobs.running.as_ref()
+    {
+      let id = *id;
+      let events: Vec<JobEvent> = rx.try_iter().collect();
+      let disconnected = events.is_empty()
+        && matches!(rx.try_recv(), Err(mpsc::TryRecvError::Disconnected));
+      for ev in events
+      {
+        changed = true;
+        let Some(job) = self.jobs.job_mut(id)
+        else
+        {
+          continue;
+        };
+        match ev
+        {
+          JobEvent::Progress(p) => job.progress = p,
+          JobEvent::Error(e) => job.errors.push(e),
+          JobEvent::Finished { cancelled } => finished = Some((id, cancelled)),
+        }
+      }
+      if disconnected && finished.is_none()
+      {
+        // Worker died without reporting; treat as failed
+        finished = Some((id, false));
+        if let Some(job) = self.jobs.job_mut(id)
+        {
+          job.errors.push("worker exited unexpectedly".to_string());
+        }
+        changed = true;
+      }
+    }
+    if let Some((id, cancelled)) = finished
+    {
+      self.jobs.running = None;
+      self.finish_job(id, cancelled);
+      self.start_next_job();
+    }
+    changed
+  }
+
+  fn finish_job(
+    &mut self,
+    id: u64,
+    cancelled: bool,
+  )
+  {
+    let Some(job) = self.jobs.job_mut(id)
+    else
+    {
+      return;
+    };
+    job.status = if cancelled
+    {
+      JobStatus::Cancelled
+    }
+    else if job.errors.is_empty()
+    {
+      JobStatus::Done
+    }
+    else
+    {
+      JobStatus::Failed
+    };
+    let summary = format!(
+      "{} {}: {}/{} file(s), {} error(s)",
+      job.kind.label(),
+      match job.status
+      {
+        JobStatus::Cancelled => "cancelled",
+        JobStatus::Failed => "finished with errors",
+        _ => "done",
+      },
+      job.progress.files_done,
+      job.progress.files_total,
+      job.errors.len()
+    );
+    let errors = job.errors.clone();
+    let select = job.select.clone().filter(|_| job.status == JobStatus::Done);
+    let job_status = job.status;
+    for e in errors
+    {
+      self.add_message(&e);
+    }
+    let level = match job_status
+    {
+      JobStatus::Failed => MessageLevel::Error,
+      JobStatus::Cancelled => MessageLevel::Warn,
+      _ => MessageLevel::Info,
+    };
+    self.notify(level, &summary);
+    // Keep only a bounded history of finished jobs
+    let mut finished = self
+      .jobs
+      .jobs
+      .iter()
+      .filter(|j| !matches!(j.status, JobStatus::Queued | JobStatus::Running))
+      .count();
+    self.jobs.jobs.retain(|j| {
+      let done = !matches!(j.status, JobStatus::Queued | JobStatus::Running);
+      if done && finished > MAX_FINISHED_JOBS
+      {
+        finished -= 1;
+        return false;
+      }
+      true
+    });
+    self.refresh_lists();
+    if let Some(path) = select
+      && path.parent() == Some(self.cwd.as_path())
+      && let Some(name) = path.file_name()
+    {
+      self.reselect_when_loaded(&name.to_string_lossy());
+    }
+    self.refresh_preview();
+    self.force_full_redraw = true;
+  }
+
+  /// Highlight `path` when job `id` succeeds, if its directory is still the
+  /// current one.
+  pub(crate) fn select_after_job(
+    &mut self,
+    id: u64,
+    path: PathBuf,
+  )
+  {
+    if let Some(job) = self.jobs.job_mut(id)
+    {
+      job.select = Some(path);
+    }
+  }
+
+  /// Block until every queued job has finished.
+  pub fn finish_jobs(&mut self)
+  {
+    while self.has_active_jobs()
+    {
+      if !self.poll_jobs()
+      {
+        std::thread::sleep(Duration::from_millis(10));
+      }
+    }
+  }
+
+  /// Cancel the job at `idx` in the jobs list: queued jobs are dropped,
+  /// the running job stops at its next progress check.
+  pub(crate) fn cancel_job(
+    &mut self,
+    idx: usize,
+  )
+  {
+    let Some(job) = self.jobs.jobs.get_mut(idx)
+    else
+    {
+      return;
+    };
+    match job.status
+    {
+      JobStatus::Queued =>
+      {
+        job.status = JobStatus::Cancelled;
+        let msg = format!("{} cancelled (job {})", job.kind.label(), job.id);
+        self.add_message(&msg);
+      }
+      JobStatus::Running =>
+      {
+        job.cancel.store(true, Ordering::Relaxed);
+      }
+      _ =>
+      {}
+    }
+  }
+
+  /// Cancel the running job, if any.
+  pub(crate) fn cancel_running_job(&mut self)
+  {
+    match self.jobs.jobs.iter().position(|j| j.status == JobStatus::Running)
+    {
+      Some(idx) => self.cancel_job(idx),
+      None => self.add_message("No running job"),
+    }
+  }
+
+  pub(crate) fn toggle_jobs_overlay(&mut self)
+  {
+    self.overlay = match self.overlay
+    {
+      Overlay::Jobs { .. } => Overlay::None,
+      _ => Overlay::Jobs { selected: 0 },
+    };
+    self.force_full_redraw = true;
+  }
+
+  pub(crate) fn jobs_overlay_move(
+    &mut self,
+    delta: isize,
+  )
+  {
+    let len = self.jobs.jobs.len() as isize;
+    if let Overlay::Jobs { ref mut selected } = self.overlay
+      && len > 0
+    {
+      *selected = (*selected as isize + delta).clamp(0, len - 1) as usize;
+    }
+  }
+}
+
+/// Worker body: process `items` and report through `tx`.
+fn run_job(
+  kind: JobKind,
+  items: &[PathBuf],
+  targets: &[PasteTarget],
+  dest_dir: Option<&Path>,
+  cancel: &AtomicBool,
+  tx: &mpsc::Sender<JobEvent>,
+)
+{
+  let mut p = JobProgress::default();
+  for (i, src) in items.iter().enumerate()
+  {
+    // Transfers to or from a host report each item once it is done
+    let remote = remote::is_remote(src)
+      || targets.get(i).is_some_and(|t| remote::is_remote(&t.dest));
+    if kind == JobKind::Delete || remote
+    {
+      p.files_total += 1;
+    }
+    else if kind == JobKind::Trash
+    {
+      // Counted as one file each, plus the bytes a copy would move
+      p.bytes_total += fs_ops::path_totals(src).0;
+      p.files_total += 1;
+    }
+    else
+    {
+      let (bytes, files) = match vfs::resolve(src)
+      {
+        Some(ap) if kind == JobKind::Extract => vfs::totals(&ap),
+        _ => fs_ops::path_totals(src),
+      };
+      p.bytes_total += bytes;
+      p.files_total += files;
+    }
+  }
+  let _ = tx.send(JobEvent::Progress(p.clone()));
+  let mut last_sent = Instant::now();
+  let interval = Duration::from_millis(PROGRESS_INTERVAL_MS);
+
+  for (i, src) in items.iter().enumerate()
+  {
+    if cancel.load(Ordering::Relaxed)
+    {
+      break;
+    }
+    p.current = Some(src.clone());
+    let _ = tx.send(JobEvent::Progress(p.clone()));
+    if kind == JobKind::Delete
+    {
+      match vfs::remove_all(src)
+      {
+        Ok(()) => p.files_done += 1,
+        Err(e) =>
+        {
+          let _ = tx.send(JobEvent::Error(format!(
+            "Delete error: {}: {}",
+            src.display(),
+            e
+          )));
+        }
+      }
+      continue;
+    }
+    if kind == JobKind::Trash
+    {
+      let bytes = fs_ops::path_totals(src).0;
+      let before = p.bytes_done;
+      let res = TrashLocation::detect()
+        .ok_or_else(|| std::io::Error::other("no trash directory"))
+        .and_then(|loc| {
+          trash::trash_path(&loc, src, &mut |step| {
+            if let CopyProgress::Bytes(n) = step
+            {
+              p.bytes_done += n;
+            }
+            if last_sent.elapsed() >= interval
+            {
+              last_sent = Instant::now();
+              let _ = tx.send(JobEvent::Progress(p.clone()));
+            }
+            if cancel.load(Ordering::Relaxed)
+            {
+              return Err(std::io::Error::from(
+                std::io::ErrorKind::Interrupted,
+              ));
+            }
+            Ok(())
+          })
+        });
+      match res
+      {
+        // A rename within the volume reports no bytes along the way
+        Ok(_) =>
+        {
+          p.bytes_done = before + bytes;
+          p.files_done += 1;
+        }
+        Err(_) if cancel.load(Ordering::Relaxed) => break,
+        Err(e) =>
+        {
+          let _ = tx.send(JobEvent::Error(format!(
+            "Trash error: {}: {}",
+            src.display(),
+            e
+          )));
+        }
+      }
+      continue;
+    }
+    let Some(name) = src.file_name()
+    else
+    {
+      continue;
+    };
+    let (dest, overwrite) = match targets.get(i)
+    {
+      Some(t) => (t.dest.clone(), t.overwrite),
+      None => (dest_dir.unwrap_or(Path::new(".")).join(name), false),
+    };
+    if overwrite
+      && (remote::is_remote(&dest) || dest.symlink_metadata().is_ok())
+    {
+      if let Err(e) = vfs::remove_all(&dest)
+      {
+        let _ = tx.send(JobEvent::Error(format!(
+          "Overwrite error: {}: {}",
+          dest.display(),
+          e
+        )));
+        continue;
+      }
+    }
+    else if dest.exists()
+    {
+      let _ =
+        tx.send(JobEvent::Error(format!("Skip (exists): {}", dest.display())));
+      continue;
+    }
+    // Same-device moves are a cheap rename
+    if kind == JobKind::Move
+      && !remote::is_remote(src)
+      && !remote::is_remote(&dest)
+    {
+      let (bytes, files) = fs_ops::path_totals(src);
+      if std::fs::rename(src, &dest).is_ok()
+      {
+        p.bytes_done += bytes;
+        p.files_done += files;
+        continue;
+      }
+    }
+    let mut on_progress = |step: CopyProgress| -> std::io::Result<()> {
+      match step
+      {
+        CopyProgress::Bytes(n) => p.bytes_done += n,
+        CopyProgress::FileDone => p.files_done += 1,
+      }
+      if last_sent.elapsed() >= interval
+      {
+        last_sent = Instant::now();
+        let _ = tx.send(JobEvent::Progress(p.clone()));
+      }
+      if cancel.load(Ordering::Relaxed)
+      {
+        return Err(std::io::Error::from(std::io::ErrorKind::Interrupted));
+      }
+      Ok(())
+    };
+    let res = if remote::is_remote(src) || remote::is_remote(&dest)
+    {
+      transfer_remote(kind, src, &dest, &mut on_progress)
+    }
+    else if kind == JobKind::Extract
+    {
+      match vfs::resolve(src)
+      {
+        Some(ap) => crate::core::archive::extract(
+          &ap.archive,
+          ap.kind,
+          &ap.inner,
+          &dest,
+          &mut on_progress,
+        ),
+        None => Err(std::io::Error::new(
+          std::io::ErrorKind::NotFound,
+          "not an archive member",
+        )),
+      }
+    }
+    else
+    {
+      fs_ops::copy_path_with_progress(src, &dest, &mut on_progress).and_then(
+        |_| {
+          if kind == JobKind::Move
+          {
+            fs_ops::remove_path_all(src)
+          }
+          else
+          {
+            Ok(())
+          }
+        },
+      )
+    };
+    if let Err(e) = res
+    {
+      if cancel.load(Ordering::Relaxed)
+      {
+        // Do not leave a half-copied item behind
+        let _ = vfs::remove_all(&dest);
+        break;
+      }
+      let _ = tx.send(JobEvent::Error(format!(
+        "Error: {} -> {}: {}",
+        src.display(),
+        dest.display(),
+        e
+      )));
+    }
+  }
+  p.current = None;
+  let _ = tx.send(JobEvent::Progress(p));
+  let _ =
+    tx.send(JobEvent::Finished { cancelled: cancel.load(Ordering::Relaxed) });
+}
+
+/// Copy or move `src` to `dest` when either of them is on a remote host.
+fn transfer_remote(
+  kind: JobKind,
+  src: &Path,
+  dest: &Path,
+  on_progress: &mut dyn FnMut(CopyProgress) -> std::io::Result<()>,
+) -> std::io::Result<()>
+{
+  if kind == JobKind::Move && remote::is_remote(src) && remote::is_remote(dest)
+  {
+    vfs::rename(src, dest)?;
+  }
+  else
+  {
+    remote::transfer(src, dest, on_progress)?;
+    if kind == JobKind::Move
+    {
+      vfs::remove_all(src)?;
+    }
+  }
+  on_progress(CopyProgress::FileDone)
+}
+
+/// Worker body of a compress job: one archive from all `items`.
+fn run_compress(
+  spec: &PackSpec,
+  items: &[PathBuf],
+  cancel: &AtomicBool,
+  tx: &mpsc::Sender<JobEvent>,
+)
+{
+  let mut p = JobProgress::default();
+  for src in items
+  {
+    let (bytes, files) = fs_ops::path_totals(src);
+    p.bytes_total += bytes;
+    p.files_total += files;
+  }
+  p.current = Some(spec.archive.clone());
+  let _ = tx.send(JobEvent::Progress(p.clone()));
+  let mut last_sent = Instant::now();
+  let interval = Duration::from_millis(PROGRESS_INTERVAL_MS);
+  let res = pack::create(spec, items, &mut |step| {
+    match step
+    {
+      CopyProgress::Bytes(n) => p.bytes_done += n,
+      CopyProgress::FileDone => p.files_done += 1,
+    }
+    if last_sent.elapsed() >= interval
+    {
+      last_sent = Instant::now();
+      let _ = tx.send(JobEvent::Progress(p.clone()));
+    }
+    if cancel.load(Ordering::Relaxed)
+    {
+      return Err(std::io::Error::from(std::io::ErrorKind::Interrupted));
+    }
+    Ok(())
+  });
+  match res
+  {
+    // Commands like zip report nothing along the way
+    Ok(()) =>
+    {
+      p.files_done = p.files_total;
+      p.bytes_done = p.bytes_total;
+    }
+    Err(e) =>
+    {
+      // Do not leave a partial archive behind
+      let _ = std::fs::remove_file(&spec.archive);
+      if !cancel.load(Ordering::Relaxed)
+      {
+        let _ = tx.send(JobEvent::Error(format!(
+          "Compress error: {}: {}",
+          spec.archive.display(),
+          e
+        )));
+      }
+    }
+  }
+  p.current = None;
+  let _ = tx.send(JobEvent::Progress(p));
+  let _ =
+    tx.send(JobEvent::Finished { cancelled: cancel.load(Ordering::Relaxed) });
+}
+
+/// Worker body of a permissions job: `chown -R` for the owner, then the mode
+/// of every path found below `items`.
+fn run_permissions(
+  req: &PermissionRequest,
+  items: &[PathBuf],
+  cancel: &AtomicBool,
+  tx: &mpsc::Sender<JobEvent>,
+)
+{
+  let mut p = JobProgress::default();
+  #[cfg(unix)]
+  {
+    use crate::core::permissions;
+    let paths = match req.mode
+    {
+      Some(_) => permissions::expand(items, req.recursive),
+      None => items.to_vec(),
+    };
+    p.files_total = paths.len() as u64;
+    let _ = tx.send(JobEvent::Progress(p.clone()));
+    if let Some(owner) = req.owner.as_deref()
+      && let Err(e) = permissions::chown(items, owner, req.recursive)
+    {
+      let _ = tx.send(JobEvent::Error(format!("set_permissions: {}", e)));
+    }
+    let mut last_sent = Instant::now();
+    let interval = Duration::from_millis(PROGRESS_INTERVAL_MS);
+    for path in &paths
+    {
+      if cancel.load(Ordering::Relaxed)
+      {
+        break;
+      }
+      let res = match req.mode.as_ref()
+      {
+        Some(spec) => permissions::chmod_path(path, spec),
+        None => Ok(()),
+      };
+      match res
+      {
+        Ok(()) => p.files_done += 1,
+        Err(e) =>
+        {
+          let _ = tx.send(JobEvent::Error(format!(
+            "set_permissions: {}: {}",
+            path.display(),
+            e
+          )));
+        }
+      }
+      if last_sent.elapsed() >= interval
+      {
+        last_sent = Instant::now();
+        p.current = Some(path.clone());
+        let _ = tx.send(JobEvent::Progress(p.clone()));
+      }
+    }
+  }
+  #[cfg(not(unix))]
+  {
+    let _ = (req, items);
+    let _ = tx.send(JobEvent::Error(String::from(
+      "set_permissions: not supported on this platform",
+    )));
+  }
+  p.current = None;
+  let _ = tx.send(JobEvent::Progress(p));
+  let _ =
+    tx.send(JobEvent::Finished { cancelled: cancel.load(Ordering::Relaxed) });
+}
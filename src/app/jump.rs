@@ -0,0 +1,167 @@
+//! Frecency jumps (`:z`) for App.
+//!
+//! Every directory lsv changes to is recorded in `frecency` under the config
+//! root (see [`crate::core::frecency`]). `:z <query>` jumps straight to the
+//! best scoring directory matching the query; `:z` alone opens the finder
+//! overlay over the visited directories. With `ui.zoxide = true` visits are
+//! also passed on to zoxide, and its database is ranked alongside lsv's.
+
+use std::path::{
+  Path,
+  PathBuf,
+};
+
+use crate::{
+  app::{
+    App,
+    FinderKind,
+    FinderState,
+    Overlay,
+  },
+  core::frecency,
+};
+
+/// Loaded visits plus the ones made this session.
+#[derive(Debug, Default)]
+pub struct Frecency
+{
+  db:    Vec<frecency::Visit>,
+  added: Vec<(PathBuf, u64)>,
+}
+
+impl App
+{
+  fn frecency_path(&self) -> Option<PathBuf>
+  {
+    self.theme_root_dir().map(|root| root.join("frecency"))
+  }
+
+  /// Read the visits recorded by earlier runs.
+  pub(crate) fn load_frecency(&mut self)
+  {
+    if let Some(path) = self.frecency_path()
+    {
+      self.frecency.db = frecency::load_db(&path);
+    }
+  }
+
+  /// Add this session's visits to the frecency database. The file is re-read
+  /// first so several instances don't overwrite each other's visits.
+  pub fn save_frecency(&self)
+  {
+    if self.frecency.added.is_empty()
+    {
+      return;
+    }
+    if let Some(path) = self.frecency_path()
+    {
+      let mut db = frecency::load_db(&path);
+      for (dir, at) in &self.frecency.added
+      {
+        frecency::add_visit(&mut db, dir, *at);
+      }
+      let _ = frecency::save_db(&path, &db);
+    }
+  }
+
+  /// Record a visit to `dir`. Archives and other virtual directories are
+  /// left out.
+  pub(crate) fn record_visit(
+    &mut self,
+    dir: &Path,
+  )
+  {
+    if !dir.is_dir()
+    {
+      return;
+    }
+    let now = frecency::now();
+    frecency::add_visit(&mut self.frecency.db, dir, now);
+    self.frecency.added.push((dir.to_path_buf(), now));
+    if self.config.ui.zoxide
+    {
+      frecency::zoxide_add(dir);
+    }
+  }
+
+  /// Existing directories matching `query`, best first, without the
+  /// current one.
+  pub(crate) fn jump_candidates(
+    &self,
+    query: &str,
+  ) -> Vec<PathBuf>
+  {
+    let mut scored =
+      frecency::scored_matches(&self.frecency.db, query, frecency::now());
+    if self.config.ui.zoxide
+    {
+      for (score, path) in frecency::zoxide_scores()
+      {
+        if !frecency::matches(query, &path)
+        {
+          continue;
+        }
+        match scored.iter_mut().find(|(_, p)| *p == path)
+        {
+          Some(hit) => hit.0 = hit.0.max(score),
+          None => scored.push((score, path)),
+        }
+      }
+    }
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    scored
+      .into_iter()
+      .map(|(_, p)| p)
+      .filter(|p| *p != self.cwd && p.is_dir())
+      .collect()
+  }
+
+  /// Jump to the best directory matching `query`, or open the jump overlay
+  /// when `query` is empty.
+  pub(crate) fn jump(
+    &mut self,
+    query: &str,
+  )
+  {
+    let query = query.trim();
+    if query.is_empty()
+    {
+      self.open_jump();
+      return;
+    }
+    match self.jump_candidates(query).into_iter().next()
+    {
+      Some(dir) =>
+      {
+        crate::trace::log(format!("[z] '{}' -> '{}'", query, dir.display()));
+        self.set_cwd(&dir);
+      }
+      None => self.add_message(&format!("z: no match for '{}'", query)),
+    }
+  }
+
+  /// Open the finder overlay over the visited directories.
+  pub(crate) fn open_jump(&mut self)
+  {
+    let dirs: Vec<String> = self
+      .jump_candidates("")
+      .iter()
+      .map(|p| p.to_string_lossy().to_string())
+      .collect();
+    self.keys.pending.clear();
+    self.keys.last_at = None;
+    let mut st = FinderState {
+      kind:      FinderKind::Dirs,
+      root:      PathBuf::new(),
+      input:     String::new(),
+      files:     dirs,
+      results:   Vec::new(),
+      selected:  0,
+      indexing:  false,
+      truncated: false,
+    };
+    crate::app::finder::rerank(&mut st);
+    self.overlay = Overlay::Finder(Box::new(st));
+    self.force_full_redraw = true;
+  }
+}
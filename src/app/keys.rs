@@ -2,7 +2,10 @@
 
 use crate::app::App;
 
-use crate::keymap::tokenize_sequence;
+use crate::keymap::{
+  normalize_sequence,
+  tokenize_sequence,
+};
 
 impl App
 {
@@ -12,9 +15,12 @@ impl App
     self.keys.prefixes.clear();
     for m in &self.keys.maps
     {
-      self.keys.lookup.insert(m.sequence.clone(), m.action.clone());
+      // Written as pressed keys are built: `<Space>` as " ", `<Enter>` as
+      // `<CR>`
+      let sequence = normalize_sequence(&m.sequence);
+      self.keys.lookup.insert(sequence.clone(), m.action.clone());
       // collect token-based prefixes for sequence matching
-      let tokens = tokenize_sequence(&m.sequence);
+      let tokens = tokenize_sequence(&sequence);
       let mut acc = String::new();
       for (idx, t) in tokens.iter().enumerate()
       {
@@ -41,7 +47,7 @@ impl App
     seq: &str,
   ) -> Option<String>
   {
-    self.keys.lookup.get(seq).cloned()
+    self.keys.lookup.get(normalize_sequence(seq).as_str()).cloned()
   }
 
   pub fn has_prefix(
@@ -49,6 +55,6 @@ impl App
     seq: &str,
   ) -> bool
   {
-    self.keys.prefixes.contains(seq)
+    self.keys.prefixes.contains(&normalize_sequence(seq))
   }
 }
@@ -0,0 +1,50 @@
+//! `:` commands registered from Lua.
+//!
+//! `lsv.register_command(name, fn, { complete = { ... } })` adds `name` to
+//! the command pane: it is offered by Tab completion alongside the built-in
+//! commands (followed by each `complete` value as an argument), and running
+//! it calls `fn` like a mapped action with `config.context.command` set to
+//! the name, `config.context.args` to the arguments split shell-style, and
+//! `config.context.args_line` to the text after the name as typed.
+
+use crate::app::App;
+
+/// A Lua command being run, as seen from `config.context`.
+#[derive(Debug, Clone)]
+pub struct LuaCommandCall
+{
+  pub name: String,
+  pub args: Vec<String>,
+  pub line: String,
+}
+
+impl App
+{
+  /// Run the Lua command `name` with the rest of the command line. Returns
+  /// false when no such command is registered.
+  pub(crate) fn run_lua_command(
+    &mut self,
+    name: &str,
+    rest: &str,
+  ) -> bool
+  {
+    let Some(idx) =
+      self.config.commands.iter().find(|c| c.name == name).map(|c| c.action)
+    else
+    {
+      return false;
+    };
+    self.lua_command = Some(LuaCommandCall {
+      name: name.to_string(),
+      args: crate::commands::split_args(rest),
+      line: rest.to_string(),
+    });
+    let action = format!("run_lua:{}", idx);
+    if let Err(e) = crate::actions::dispatch_action(self, &action)
+    {
+      self.add_message(&format!(":{}: {}", name, e));
+    }
+    self.lua_command = None;
+    true
+  }
+}
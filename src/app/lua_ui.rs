@@ -0,0 +1,187 @@
+//! Dialogs opened from Lua with `lsv.ui`.
+//!
+//! `lsv.ui.prompt`, `lsv.ui.confirm` and `lsv.ui.select` open the prompt,
+//! the confirmation dialog or a searchable list once the action returns.
+//! The callback passed with them is kept in [`LuaRuntime::dialog`] and
+//! called with the answer when the dialog closes: the text typed, whether
+//! `y` was pressed, or the chosen item and its index. Cancelling answers
+//! `nil` (`false` for a confirmation). The callback runs like the action
+//! that opened it and may open another dialog.
+//!
+//! [`LuaRuntime::dialog`]: crate::app::LuaRuntime::dialog
+
+use crate::{
+  actions::{
+    apply::{
+      apply_config_overlay,
+      apply_effects,
+    },
+    effects::DialogRequest,
+  },
+  app::{
+    App,
+    ConfirmKind,
+    Overlay,
+    PromptKind,
+    PromptState,
+    SelectState,
+  },
+};
+
+/// What a dialog hands back to its Lua callback.
+#[derive(Debug, Clone)]
+pub enum DialogAnswer
+{
+  /// The text entered at the prompt; `None` when cancelled.
+  Text(Option<String>),
+  /// Whether the question was answered with yes.
+  Yes(bool),
+  /// The 0-based index and text of the chosen item; `None` when cancelled.
+  Choice(Option<(usize, String)>),
+}
+
+fn rerank(st: &mut SelectState)
+{
+  st.results = if st.input.trim().is_empty()
+  {
+    (0..st.items.len()).collect()
+  }
+  else
+  {
+    crate::core::fuzzy::rank(&st.input, &st.items, st.items.len())
+      .into_iter()
+      .map(|(i, _)| i)
+      .collect()
+  };
+  st.selected = st.selected.min(st.results.len().saturating_sub(1));
+}
+
+impl App
+{
+  /// Open the dialog a Lua action asked for.
+  pub(crate) fn open_dialog(
+    &mut self,
+    dialog: DialogRequest,
+  )
+  {
+    self.keys.pending.clear();
+    self.keys.last_at = None;
+    match dialog
+    {
+      DialogRequest::Prompt { title, text } =>
+      {
+        self.overlay = Overlay::Prompt(Box::new(PromptState {
+          title,
+          cursor: text.len(),
+          input: text,
+          kind: PromptKind::Lua,
+        }));
+        self.force_full_redraw = true;
+      }
+      DialogRequest::Confirm { question } => self.open_batch_confirm(
+        "Confirm",
+        question,
+        Vec::new(),
+        ConfirmKind::Lua,
+      ),
+      DialogRequest::Select { title, items } =>
+      {
+        let mut st = SelectState {
+          title,
+          input: String::new(),
+          items,
+          results: Vec::new(),
+          selected: 0,
+        };
+        rerank(&mut st);
+        self.overlay = Overlay::Select(Box::new(st));
+        self.force_full_redraw = true;
+      }
+    }
+  }
+
+  /// Close the dialog and call its Lua callback with `answer`.
+  pub(crate) fn answer_dialog(
+    &mut self,
+    answer: DialogAnswer,
+  )
+  {
+    self.overlay = Overlay::None;
+    self.force_full_redraw = true;
+    match crate::config::runtime::glue::call_lua_dialog(self, answer)
+    {
+      Ok(Some((fx, overlay))) =>
+      {
+        apply_effects(self, fx);
+        if let Some(data) = overlay
+        {
+          apply_config_overlay(self, &data);
+        }
+      }
+      Ok(None) =>
+      {}
+      Err(e) => self.add_error(&format!("Lua dialog: {}", e)),
+    }
+  }
+
+  /// Items of the open `lsv.ui.select` list matching its query, best first.
+  pub fn select_results(&self) -> Vec<String>
+  {
+    match self.overlay
+    {
+      Overlay::Select(ref st) =>
+      {
+        st.results.iter().filter_map(|&i| st.items.get(i)).cloned().collect()
+      }
+      _ => Vec::new(),
+    }
+  }
+
+  pub(crate) fn select_edit(
+    &mut self,
+    ch: Option<char>,
+  )
+  {
+    if let Overlay::Select(ref mut st) = self.overlay
+    {
+      match ch
+      {
+        Some(c) => st.input.push(c),
+        None =>
+        {
+          st.input.pop();
+        }
+      }
+      st.selected = 0;
+      rerank(st);
+      self.force_full_redraw = true;
+    }
+  }
+
+  pub(crate) fn select_move(
+    &mut self,
+    delta: isize,
+  )
+  {
+    if let Overlay::Select(ref mut st) = self.overlay
+      && !st.results.is_empty()
+    {
+      let last = st.results.len() as isize - 1;
+      st.selected = (st.selected as isize + delta).clamp(0, last) as usize;
+    }
+  }
+
+  /// Answer the `lsv.ui.select` list with the highlighted item.
+  pub(crate) fn select_accept(&mut self)
+  {
+    let choice = match self.overlay
+    {
+      Overlay::Select(ref st) => st
+        .results
+        .get(st.selected)
+        .and_then(|&i| Some((i, st.items.get(i)?.clone()))),
+      _ => return,
+    };
+    self.answer_dialog(DialogAnswer::Choice(choice));
+  }
+}
@@ -0,0 +1,171 @@
+//! Keyboard macros: record the actions run from keys into a register and
+//! replay them.
+//!
+//! `Q` followed by a register character starts recording; `Q` again stops.
+//! Every action dispatched from the keymap is stored with its count, as is
+//! each `:` command line, so `:rename` and paste workflows replay as typed.
+//! `@` followed by a register plays it back, `@@` repeats the last register
+//! played, and a count (`5@a`) plays it that many times.
+
+use std::collections::HashMap;
+
+use crate::app::App;
+
+/// One recorded action and the count typed before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct MacroStep
+{
+  pub(crate) action: String,
+  pub(crate) count:  Option<usize>,
+}
+
+/// What the next key names a register for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MacroPrompt
+{
+  Record,
+  /// Play the register this many times.
+  Play(usize),
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Macros
+{
+  registers:          HashMap<char, Vec<MacroStep>>,
+  /// Register being recorded and the steps so far.
+  recording:          Option<(char, Vec<MacroStep>)>,
+  pub(crate) pending: Option<MacroPrompt>,
+  last_played:        Option<char>,
+  playing:            bool,
+}
+
+/// Upper bound on steps run by one playback, so a macro that plays itself
+/// through a Lua action cannot run forever.
+const MAX_STEPS: usize = 100_000;
+
+impl App
+{
+  /// `Q`: stop the recording in progress, or ask for a register to record.
+  pub(crate) fn macro_record_key(&mut self)
+  {
+    if let Some((reg, steps)) = self.macros.recording.take()
+    {
+      self.add_message(&format!(
+        "Recorded @{} ({} action{})",
+        reg,
+        steps.len(),
+        if steps.len() == 1 { "" } else { "s" }
+      ));
+      self.macros.registers.insert(reg, steps);
+      return;
+    }
+    self.macros.pending = Some(MacroPrompt::Record);
+    self.add_message("Record: type a register letter");
+  }
+
+  /// `@`: ask for the register to play, `count` times.
+  pub(crate) fn macro_play_key(
+    &mut self,
+    count: usize,
+  )
+  {
+    if self.macros.playing
+    {
+      return;
+    }
+    self.macros.pending = Some(MacroPrompt::Play(count.max(1)));
+  }
+
+  /// The key typed after `Q` or `@`.
+  pub(crate) fn macro_register_key(
+    &mut self,
+    prompt: MacroPrompt,
+    ch: char,
+  )
+  {
+    match prompt
+    {
+      MacroPrompt::Record if ch.is_ascii_alphanumeric() =>
+      {
+        self.macros.recording = Some((ch, Vec::new()));
+        self.add_message(&format!("Recording @{}", ch));
+      }
+      MacroPrompt::Play(times) =>
+      {
+        let reg = if ch == '@' { self.macros.last_played } else { Some(ch) };
+        match reg
+        {
+          Some(reg) => self.play_macro(reg, times),
+          None => self.add_message("No macro played yet"),
+        }
+      }
+      MacroPrompt::Record =>
+      {
+        self.add_warning(&format!("Invalid register '{}'", ch));
+      }
+    }
+  }
+
+  /// Add an action to the recording in progress, if any. Opening the
+  /// command palette is skipped; the action picked from it is recorded.
+  pub(crate) fn record_macro_step(
+    &mut self,
+    action: &str,
+    count: Option<usize>,
+  )
+  {
+    if self.macros.playing
+      || action.trim().eq_ignore_ascii_case(crate::app::palette::PALETTE_ACTION)
+    {
+      return;
+    }
+    if let Some((_, steps)) = self.macros.recording.as_mut()
+    {
+      steps.push(MacroStep { action: action.to_string(), count });
+    }
+  }
+
+  /// Register being recorded, for the header.
+  pub(crate) fn recording_macro(&self) -> Option<char>
+  {
+    self.macros.recording.as_ref().map(|(reg, _)| *reg)
+  }
+
+  /// Run the actions stored in `reg`, `times` times over.
+  pub fn play_macro(
+    &mut self,
+    reg: char,
+    times: usize,
+  )
+  {
+    let Some(steps) = self.macros.registers.get(&reg).cloned()
+    else
+    {
+      self.add_message(&format!("Register @{} is empty", reg));
+      return;
+    };
+    self.macros.last_played = Some(reg);
+    self.macros.playing = true;
+    let mut ran = 0usize;
+    'outer: for _ in 0..times
+    {
+      for step in &steps
+      {
+        let _ = match step.count
+        {
+          Some(n) =>
+          {
+            crate::actions::dispatch_action_with_count(self, &step.action, n)
+          }
+          None => crate::actions::dispatch_action(self, &step.action),
+        };
+        ran += 1;
+        if self.should_quit || ran >= MAX_STEPS
+        {
+          break 'outer;
+        }
+      }
+    }
+    self.macros.playing = false;
+  }
+}
@@ -1,6 +1,20 @@
 //! Marks persistence and navigation for App.
+//!
+//! Marks are named directories saved to `marks` under the config root. `m`
+//! followed by a character marks the current directory under that
+//! character; `:mark <name>` accepts longer names. `:marks` opens the
+//! bookmarks overlay listing every mark, where they can be visited, added,
+//! renamed and deleted.
 
-use crate::app::App;
+use crate::{
+  app::{
+    App,
+    Overlay,
+    PromptKind,
+    PromptState,
+  },
+  core::marks,
+};
 
 impl App
 {
@@ -9,27 +23,32 @@ impl App
     if let Some(root) = self.theme_root_dir()
     {
       let path = root.join("marks");
-      let _ = crate::core::marks::save_marks(&path, &self.marks);
+      let _ = marks::save_marks(&path, &self.marks);
     }
   }
 
   pub(crate) fn add_mark(
     &mut self,
-    ch: char,
+    name: &str,
   )
   {
+    if !marks::valid_name(name)
+    {
+      self.add_warning(&format!("Invalid mark name '{}'", name));
+      return;
+    }
     let dir = self.cwd.clone();
-    self.marks.insert(ch, dir.clone());
+    self.marks.insert(name.to_string(), dir.clone());
     self.save_marks();
-    self.add_message(&format!("Mark '{}' set: {}", ch, dir.display()));
+    self.add_message(&format!("Mark '{}' set: {}", name, dir.display()));
   }
 
   pub(crate) fn goto_mark(
     &mut self,
-    ch: char,
+    name: &str,
   )
   {
-    if let Some(path) = self.marks.get(&ch).cloned()
+    if let Some(path) = self.marks.get(name).cloned()
     {
       if path.is_dir()
       {
@@ -40,33 +59,159 @@ impl App
       {
         self.add_message(&format!(
           "Mark '{}' not a directory: {}",
-          ch,
+          name,
           path.display()
         ));
       }
     }
     else
     {
-      self.add_message(&format!("No mark '{}'", ch));
+      self.add_message(&format!("No mark '{}'", name));
+    }
+  }
+
+  /// Remove the marks in `names`, returning how many existed.
+  pub(crate) fn delete_marks<'a>(
+    &mut self,
+    names: impl IntoIterator<Item = &'a str>,
+  ) -> usize
+  {
+    let removed =
+      names.into_iter().filter(|n| self.marks.remove(*n).is_some()).count();
+    if removed > 0
+    {
+      self.save_marks();
+    }
+    removed
+  }
+
+  /// Give mark `from` the name `to`, replacing any mark already called so.
+  pub(crate) fn rename_mark(
+    &mut self,
+    from: &str,
+    to: &str,
+  )
+  {
+    if !marks::valid_name(to)
+    {
+      self.add_warning(&format!("Invalid mark name '{}'", to));
+      return;
     }
+    match self.marks.remove(from)
+    {
+      Some(path) =>
+      {
+        self.marks.insert(to.to_string(), path);
+        self.save_marks();
+        self.add_message(&format!("Mark '{}' renamed to '{}'", from, to));
+      }
+      None => self.add_message(&format!("No mark '{}'", from)),
+    }
+  }
+
+  /// Mark names, sorted.
+  pub(crate) fn mark_names(&self) -> Vec<String>
+  {
+    let mut names: Vec<String> = self.marks.keys().cloned().collect();
+    names.sort();
+    names
+  }
+
+  /// Open the bookmarks overlay, keeping the cursor on `selected` when it
+  /// is still a mark.
+  pub(crate) fn open_bookmarks(
+    &mut self,
+    selected: Option<&str>,
+  )
+  {
+    let names = self.mark_names();
+    let selected =
+      selected.and_then(|s| names.iter().position(|n| n == s)).unwrap_or(0);
+    self.keys.pending.clear();
+    self.keys.last_at = None;
+    self.overlay = Overlay::Bookmarks { selected };
+    self.force_full_redraw = true;
   }
 
-  pub(crate) fn list_marks_text(&self) -> String
+  /// Name of the mark under the cursor in the bookmarks overlay.
+  pub(crate) fn bookmarks_current(&self) -> Option<String>
   {
-    let mut keys: Vec<char> = self.marks.keys().copied().collect();
-    keys.sort();
-    let mut out = String::new();
-    for k in keys
+    match self.overlay
     {
-      if let Some(p) = self.marks.get(&k)
+      Overlay::Bookmarks { selected } =>
       {
-        out.push_str(&format!("{}  {}\n", k, p.display()));
+        self.mark_names().get(selected).cloned()
       }
+      _ => None,
+    }
+  }
+
+  pub(crate) fn bookmarks_move(
+    &mut self,
+    delta: isize,
+  )
+  {
+    let len = self.marks.len();
+    if let Overlay::Bookmarks { ref mut selected } = self.overlay
+      && len > 0
+    {
+      *selected =
+        (*selected as isize + delta).clamp(0, len as isize - 1) as usize;
+    }
+  }
+
+  /// Close the overlay and change to the mark under the cursor.
+  pub(crate) fn bookmarks_jump(&mut self)
+  {
+    let name = self.bookmarks_current();
+    self.overlay = Overlay::None;
+    self.force_full_redraw = true;
+    if let Some(name) = name
+    {
+      self.goto_mark(&name);
     }
-    if out.is_empty()
+  }
+
+  pub(crate) fn bookmarks_delete(&mut self)
+  {
+    if let Some(name) = self.bookmarks_current()
     {
-      out.push_str("<no marks>\n");
+      self.delete_marks([name.as_str()]);
+      self.add_message(&format!("Deleted mark '{}'", name));
+      let len = self.marks.len();
+      if let Overlay::Bookmarks { ref mut selected } = self.overlay
+      {
+        *selected = (*selected).min(len.saturating_sub(1));
+      }
     }
-    out
+  }
+
+  /// Prompt for a name to mark the current directory under.
+  pub(crate) fn bookmarks_add_prompt(&mut self)
+  {
+    self.overlay = Overlay::Prompt(Box::new(PromptState {
+      title:  format!("Mark {} as:", self.cwd.display()),
+      input:  String::new(),
+      cursor: 0,
+      kind:   PromptKind::AddMark,
+    }));
+    self.force_full_redraw = true;
+  }
+
+  /// Prompt for a new name for the mark under the cursor.
+  pub(crate) fn bookmarks_rename_prompt(&mut self)
+  {
+    let Some(name) = self.bookmarks_current()
+    else
+    {
+      return;
+    };
+    self.overlay = Overlay::Prompt(Box::new(PromptState {
+      title:  format!("Rename mark '{}' to:", name),
+      input:  name.clone(),
+      cursor: name.len(),
+      kind:   PromptKind::RenameMark { from: name },
+    }));
+    self.force_full_redraw = true;
   }
 }
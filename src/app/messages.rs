@@ -0,0 +1,196 @@
+//! Message log for App.
+//!
+//! Each message keeps the time it was added and a level: information,
+//! warnings and errors are colored apart in the messages panel (`zm`). The
+//! log holds the newest `ui.max_messages` entries for the session;
+//! `:messages clear` empties it and `:messages save FILE` writes it out.
+
+use std::path::Path;
+
+use chrono::{
+  DateTime,
+  Local,
+};
+
+use crate::app::App;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageLevel
+{
+  Info,
+  Warn,
+  Error,
+}
+
+impl MessageLevel
+{
+  pub fn as_str(self) -> &'static str
+  {
+    match self
+    {
+      MessageLevel::Info => "INFO",
+      MessageLevel::Warn => "WARN",
+      MessageLevel::Error => "ERROR",
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct Message
+{
+  pub time:  DateTime<Local>,
+  pub level: MessageLevel,
+  pub text:  String,
+}
+
+impl Message
+{
+  /// The message as shown in the panel: time, level and text.
+  pub fn line(&self) -> String
+  {
+    format!(
+      "{} {:<5} {}",
+      self.time.format("%H:%M:%S"),
+      self.level.as_str(),
+      self.text
+    )
+  }
+}
+
+impl App
+{
+  /// Add a message at `level`, dropping the oldest beyond
+  /// `ui.max_messages`.
+  pub fn add_message_at(
+    &mut self,
+    level: MessageLevel,
+    msg: &str,
+  )
+  {
+    let text = msg.trim();
+    if text.is_empty()
+    {
+      return;
+    }
+    self.recent_messages.push(Message {
+      time: Local::now(),
+      level,
+      text: text.to_string(),
+    });
+    let max = self.config.ui.max_messages.max(1);
+    if self.recent_messages.len() > max
+    {
+      let excess = self.recent_messages.len() - max;
+      self.recent_messages.drain(0..excess);
+    }
+    self.force_full_redraw = true;
+  }
+
+  pub fn add_message(
+    &mut self,
+    msg: &str,
+  )
+  {
+    self.add_message_at(MessageLevel::Info, msg);
+  }
+
+  pub fn add_warning(
+    &mut self,
+    msg: &str,
+  )
+  {
+    self.add_message_at(MessageLevel::Warn, msg);
+  }
+
+  pub fn add_error(
+    &mut self,
+    msg: &str,
+  )
+  {
+    self.add_message_at(MessageLevel::Error, msg);
+  }
+
+  pub fn recent_messages_len(&self) -> usize
+  {
+    self.recent_messages.len()
+  }
+
+  /// Messages in the log, oldest first.
+  pub fn messages(&self) -> &[Message]
+  {
+    &self.recent_messages
+  }
+
+  pub fn clear_recent_messages(&mut self)
+  {
+    if !self.recent_messages.is_empty()
+    {
+      self.recent_messages.clear();
+      self.force_full_redraw = true;
+    }
+  }
+
+  /// `:messages [clear | save FILE]`; without arguments, toggle the panel.
+  pub(crate) fn messages_command(
+    &mut self,
+    args: &str,
+  )
+  {
+    let args = args.trim();
+    let (sub, rest) =
+      args.split_once(char::is_whitespace).unwrap_or((args, ""));
+    match sub.to_ascii_lowercase().as_str()
+    {
+      "" =>
+      {
+        if let crate::app::Overlay::Messages = self.overlay
+        {
+          self.overlay = crate::app::Overlay::None;
+          self.force_full_redraw = true;
+        }
+        else
+        {
+          self.set_text_overlay(crate::app::Overlay::Messages);
+        }
+      }
+      "clear" => self.clear_recent_messages(),
+      "save" if !rest.trim().is_empty() =>
+      {
+        let path =
+          crate::core::complete::resolve_typed_path(rest.trim(), &self.cwd);
+        self.save_messages(&path);
+      }
+      _ => self.add_warning("Usage: messages [clear | save <file>]"),
+    }
+  }
+
+  /// Write the log to `path` with full dates, one message per line.
+  fn save_messages(
+    &mut self,
+    path: &Path,
+  )
+  {
+    let text: String = self
+      .recent_messages
+      .iter()
+      .map(|m| {
+        format!(
+          "{} {:<5} {}\n",
+          m.time.format("%Y-%m-%d %H:%M:%S"),
+          m.level.as_str(),
+          m.text
+        )
+      })
+      .collect();
+    let count = self.recent_messages.len();
+    match std::fs::write(path, text)
+    {
+      Ok(()) => self.add_message(&format!(
+        "Saved {} message(s) to {}",
+        count,
+        path.display()
+      )),
+      Err(e) => self.add_error(&format!("messages save: {}", e)),
+    }
+  }
+}
@@ -0,0 +1,141 @@
+//! Metadata shared by the header, rows and templates.
+//!
+//! Listings that read metadata keep each entry's mode, owner and link count
+//! with it (see [`crate::core::listing::entry_info`]). For entries listed
+//! without metadata, they are read with one `stat` per path and
+//! modification time and then kept until the listing is refreshed, which
+//! file operations and the watcher do, instead of being read again for
+//! every row on every redraw.
+
+use std::{
+  cell::RefCell,
+  collections::HashMap,
+  path::{
+    Path,
+    PathBuf,
+  },
+  time::SystemTime,
+};
+
+use crate::app::{
+  App,
+  DirEntryInfo,
+};
+
+/// Most paths kept; the cache starts over beyond this.
+const MAX_CACHED: usize = 4096;
+
+/// The parts of a file's metadata that are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntryMeta
+{
+  pub is_dir:     bool,
+  /// Permission and file type bits (`st_mode`); 0 where unknown.
+  pub mode:       u32,
+  pub uid:        u32,
+  pub gid:        u32,
+  pub nlink:      u64,
+  /// Windows file attributes (read-only, hidden, system, ...); 0 elsewhere.
+  pub attributes: u32,
+}
+
+impl EntryMeta
+{
+  #[cfg(unix)]
+  pub(crate) fn from_metadata(meta: &std::fs::Metadata) -> Self
+  {
+    use std::os::unix::fs::MetadataExt;
+    EntryMeta {
+      is_dir:     meta.is_dir(),
+      mode:       meta.mode(),
+      uid:        meta.uid(),
+      gid:        meta.gid(),
+      nlink:      meta.nlink(),
+      attributes: 0,
+    }
+  }
+
+  #[cfg(windows)]
+  pub(crate) fn from_metadata(meta: &std::fs::Metadata) -> Self
+  {
+    use std::os::windows::fs::MetadataExt;
+    EntryMeta {
+      is_dir:     meta.is_dir(),
+      mode:       0,
+      uid:        0,
+      gid:        0,
+      nlink:      1,
+      attributes: meta.file_attributes(),
+    }
+  }
+
+  #[cfg(not(any(unix, windows)))]
+  pub(crate) fn from_metadata(meta: &std::fs::Metadata) -> Self
+  {
+    EntryMeta {
+      is_dir:     meta.is_dir(),
+      mode:       0,
+      uid:        0,
+      gid:        0,
+      nlink:      1,
+      attributes: 0,
+    }
+  }
+
+  fn read(path: &Path) -> Option<Self>
+  {
+    std::fs::metadata(path).ok().as_ref().map(Self::from_metadata)
+  }
+}
+
+/// Path and the modification time it was listed with.
+type MetaKey = (PathBuf, Option<SystemTime>);
+
+/// Metadata read since the last refresh; `None` for paths that could not be
+/// read.
+#[derive(Debug, Default)]
+pub struct MetaCache
+{
+  entries: RefCell<HashMap<MetaKey, Option<EntryMeta>>>,
+}
+
+impl App
+{
+  /// Metadata of `e`: as listed, or read at most once per path and mtime
+  /// until the next refresh.
+  pub(crate) fn entry_meta(
+    &self,
+    e: &DirEntryInfo,
+  ) -> Option<EntryMeta>
+  {
+    if e.meta.is_some()
+    {
+      return e.meta;
+    }
+    let key = (e.path.clone(), e.mtime);
+    if let Some(meta) = self.meta_cache.entries.borrow().get(&key)
+    {
+      return *meta;
+    }
+    let meta = EntryMeta::read(&e.path);
+    let mut entries = self.meta_cache.entries.borrow_mut();
+    if entries.len() >= MAX_CACHED
+    {
+      entries.clear();
+    }
+    entries.insert(key, meta);
+    meta
+  }
+
+  /// Forget cached metadata, e.g. when the listing is read again.
+  pub(crate) fn clear_meta_cache(&mut self)
+  {
+    self.meta_cache.entries.get_mut().clear();
+  }
+
+  /// Number of paths with cached metadata.
+  pub fn meta_cache_len(&self) -> usize
+  {
+    self.meta_cache.entries.borrow().len()
+  }
+}
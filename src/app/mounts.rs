@@ -0,0 +1,67 @@
+//! The `:mounts` picker for App.
+//!
+//! Lists mounted filesystems (drive letters on Windows) with how full they
+//! are, starting on the one holding the current directory. Enter jumps to
+//! the chosen mount point.
+
+use crate::{
+  app::{
+    App,
+    MountsState,
+    Overlay,
+  },
+  core::mounts,
+};
+
+impl App
+{
+  pub(crate) fn open_mounts(&mut self)
+  {
+    let entries = mounts::list();
+    if entries.is_empty()
+    {
+      self.add_message("Mounts: no filesystems found");
+      return;
+    }
+    let selected = mounts::containing(&entries, &self.cwd).unwrap_or(0);
+    self.keys.pending.clear();
+    self.keys.last_at = None;
+    self.overlay = Overlay::Mounts(Box::new(MountsState { entries, selected }));
+    self.force_full_redraw = true;
+  }
+
+  pub(crate) fn mounts_move(
+    &mut self,
+    delta: isize,
+  )
+  {
+    if let Overlay::Mounts(ref mut st) = self.overlay
+      && !st.entries.is_empty()
+    {
+      let last = st.entries.len() as isize - 1;
+      st.selected = (st.selected as isize + delta).clamp(0, last) as usize;
+    }
+  }
+
+  /// Close the picker and change to the highlighted mount point.
+  pub(crate) fn mounts_accept(&mut self)
+  {
+    let path = match self.overlay
+    {
+      Overlay::Mounts(ref st) => match st.entries.get(st.selected)
+      {
+        Some(m) => m.path.clone(),
+        None => return,
+      },
+      _ => return,
+    };
+    self.overlay = Overlay::None;
+    self.force_full_redraw = true;
+    if !path.is_dir()
+    {
+      self.add_error(&format!("Mounts: cannot open {}", path.display()));
+      return;
+    }
+    self.set_cwd(&path);
+  }
+}
@@ -1,21 +1,96 @@
 //! Navigation and list refresh for App.
+//!
+//! Directory listings are read on a background thread and streamed back in
+//! batches so huge directories do not freeze the UI; the runtime loop merges
+//! them via [`App::poll_dir_loads`].
 
 use std::{
   cmp::min,
   io,
   path::Path,
+  sync::mpsc,
+  time::{
+    Duration,
+    Instant,
+  },
 };
 
 use crate::{
   actions::SortKey,
   app::{
     App,
+    DirBatch,
     DirEntryInfo,
+    DirLoad,
     InfoMode,
     Overlay,
+    tree::TreeListing,
   },
 };
 
+/// How the current pane's directory is listed.
+enum ListingMode
+{
+  Plain,
+  Flat(usize),
+  Tree(Box<TreeListing>),
+}
+
+/// Entries per batch sent from the background reader.
+const LOAD_BATCH_SIZE: usize = 1024;
+/// How long a refresh waits for a listing before returning partial results.
+const SYNC_LOAD_BUDGET_MS: u64 = 150;
+
+/// Receive batches into `entries`. Returns `(changed, done)`.
+fn drain_batches(
+  load: &DirLoad,
+  entries: &mut Vec<DirEntryInfo>,
+  deadline: Option<Instant>,
+) -> (bool, bool)
+{
+  let mut changed = false;
+  loop
+  {
+    let msg = match deadline
+    {
+      Some(d) =>
+      {
+        let left = d.saturating_duration_since(Instant::now());
+        match load.rx.recv_timeout(left)
+        {
+          Ok(m) => m,
+          Err(mpsc::RecvTimeoutError::Timeout) => return (changed, false),
+          Err(mpsc::RecvTimeoutError::Disconnected) => return (changed, true),
+        }
+      }
+      None => match load.rx.try_recv()
+      {
+        Ok(m) => m,
+        Err(mpsc::TryRecvError::Empty) => return (changed, false),
+        Err(mpsc::TryRecvError::Disconnected) => return (changed, true),
+      },
+    };
+    match msg
+    {
+      DirBatch::Entries(batch) =>
+      {
+        entries.extend(batch);
+        changed = true;
+      }
+      DirBatch::Error(e) =>
+      {
+        crate::trace::log(format!(
+          "[nav] read_dir '{}' failed: {}",
+          load.path.display(),
+          e
+        ));
+        return (changed, true);
+      }
+      DirBatch::Done => return (changed, true),
+    }
+  }
+}
+
 impl App
 {
   pub(crate) fn selected_entry(&self) -> Option<&DirEntryInfo>
@@ -40,26 +115,396 @@ impl App
     self.refresh_preview();
   }
 
+  /// Move the cursor `delta` rows, stopping at either end. Without a cursor,
+  /// moving down selects the first row.
+  pub(crate) fn move_cursor(
+    &mut self,
+    delta: isize,
+  )
+  {
+    let Some(last) = self.current_entries.len().checked_sub(1)
+    else
+    {
+      return;
+    };
+    let target = match self.list_state.selected()
+    {
+      Some(sel) => sel.saturating_add_signed(delta).min(last),
+      None if delta > 0 => 0,
+      None => return,
+    };
+    if Some(target) != self.list_state.selected()
+    {
+      self.select_index(target);
+    }
+  }
+
+  /// `l`/Right: expand the entry in the tree view, else enter the directory
+  /// or browsable archive under the cursor.
+  pub(crate) fn nav_right(&mut self)
+  {
+    if self.tree_expand()
+    {
+      return;
+    }
+    if let Some(entry) = self.selected_entry()
+      && (entry.is_dir || crate::core::vfs::is_browsable_archive(&entry.path))
+    {
+      let path = entry.path.clone();
+      self.set_cwd(&path);
+    }
+  }
+
+  /// Enter: as a file picker choose the file and quit, open a file with a
+  /// matching opener rule, else enter the directory or archive.
+  pub(crate) fn nav_open(&mut self)
+  {
+    if self.picker_accept()
+    {
+      self.should_quit = true;
+      return;
+    }
+    if self.has_opener_for_current()
+    {
+      self.open_selected();
+      return;
+    }
+    if let Some(entry) = self.selected_entry()
+      && (entry.is_dir || crate::core::vfs::is_browsable_archive(&entry.path))
+    {
+      let path = entry.path.clone();
+      self.set_cwd(&path);
+    }
+  }
+
+  /// `h`/Left: collapse the entry in the tree view, else go to the parent.
+  pub(crate) fn nav_left(&mut self)
+  {
+    if !self.tree_collapse()
+    {
+      self.go_to_parent();
+    }
+  }
+
+  /// Select row `n` of the listing, counting from 1; past the end selects
+  /// the last row.
+  pub(crate) fn go_to_row(
+    &mut self,
+    n: usize,
+  )
+  {
+    if let Some(last) = self.current_entries.len().checked_sub(1)
+    {
+      self.select_index(n.saturating_sub(1).min(last));
+    }
+  }
+
   pub(crate) fn refresh_lists(&mut self)
   {
-    self.current_entries = self.read_dir_sorted(&self.cwd).unwrap_or_default();
-    if self.current_entries.len() > self.config.ui.max_list_items
+    // The flat view ends once the current directory changes
+    if self.flatten.as_ref().is_some_and(|f| f.root != self.cwd)
+    {
+      self.flatten = None;
+    }
+    // Only carry the selection over when relisting the same directory (names
+    // are paths relative to it, which covers the flat view too)
+    let reselect = self
+      .selected_entry()
+      .filter(|e| {
+        e.path
+          .strip_prefix(&self.cwd)
+          .is_ok_and(|rel| rel.to_string_lossy().replace('\\', "/") == e.name)
+      })
+      .map(|e| e.name.clone());
+    self.current_entries.clear();
+    self.parent_entries.clear();
+    self.clear_meta_cache();
+    let mode = self.current_listing_mode();
+    self.current_load = Some(self.spawn_dir_load(&self.cwd, reselect, mode));
+    self.parent_load = crate::core::vfs::parent(&self.cwd)
+      .map(|p| self.spawn_dir_load(&p, None, ListingMode::Plain));
+    // Small directories finish well within the budget, so most refreshes
+    // still look synchronous; huge ones keep streaming in the background.
+    self.wait_dir_loads(Duration::from_millis(SYNC_LOAD_BUDGET_MS));
+    self.clamp_selection();
+    // Invalidate dynamic preview cache on list refresh
+    self.preview.cache_key = None;
+    self.preview.cache_lines = None;
+  }
+
+  /// Select the entry named `name`, now or once it streams in.
+  pub(crate) fn reselect_when_loaded(
+    &mut self,
+    name: &str,
+  )
+  {
+    crate::core::selection::reselect_by_name(self, name);
+    if let Some(load) = self.current_load.as_mut()
+    {
+      load.reselect = Some(name.to_string());
+    }
+  }
+
+  /// Whether a background directory read is still streaming entries.
+  pub fn is_loading(&self) -> bool
+  {
+    self.current_load.is_some() || self.parent_load.is_some()
+  }
+
+  /// Block until all background directory reads have completed.
+  pub fn finish_loading(&mut self)
+  {
+    while self.is_loading()
+    {
+      self.wait_dir_loads(Duration::from_millis(50));
+    }
+    self.clamp_selection();
+  }
+
+  /// Drain batches from background directory reads without blocking.
+  ///
+  /// Returns true when any listing changed and the UI should redraw.
+  pub fn poll_dir_loads(&mut self) -> bool
+  {
+    let had_selection = self.list_state.selected().is_some();
+    let changed = self.drain_current_load(None) | self.drain_parent_load(None);
+    if changed
+    {
+      self.clamp_selection();
+      if !had_selection && self.list_state.selected().is_some()
+      {
+        self.refresh_preview();
+      }
+    }
+    changed
+  }
+
+  fn wait_dir_loads(
+    &mut self,
+    budget: Duration,
+  )
+  {
+    let deadline = Instant::now() + budget;
+    self.drain_current_load(Some(deadline));
+    self.drain_parent_load(Some(deadline));
+  }
+
+  fn current_listing_mode(&self) -> ListingMode
+  {
+    if let Some(depth) = self.flatten_depth()
+    {
+      ListingMode::Flat(depth)
+    }
+    else if self.tree_active()
+    {
+      ListingMode::Tree(Box::new(TreeListing {
+        expanded:     self.tree.expanded.clone(),
+        show_hidden:  self.config.ui.show_hidden,
+        need_meta:    self.listing_needs_meta(),
+        sort_key:     self.sort_key,
+        sort_reverse: self.sort_reverse,
+        max_items:    self.config.ui.max_list_items,
+      }))
+    }
+    else
+    {
+      ListingMode::Plain
+    }
+  }
+
+  /// Read `path` on a background thread, as a single level or per `mode`.
+  fn spawn_dir_load(
+    &self,
+    path: &Path,
+    reselect: Option<String>,
+    mode: ListingMode,
+  ) -> DirLoad
+  {
+    let (tx, rx) = mpsc::channel::<DirBatch>();
+    let dir = path.to_path_buf();
+    let show_hidden = self.config.ui.show_hidden;
+    let need_meta = self.listing_needs_meta();
+    let max_items = self.config.ui.max_list_items;
+    // Tree listings arrive in display order and must not be re-sorted
+    let presorted = matches!(mode, ListingMode::Tree(_));
+    std::thread::spawn(move || {
+      match mode
+      {
+        ListingMode::Flat(depth) =>
+        {
+          crate::app::flatten::stream_flat_entries(
+            &dir,
+            depth,
+            show_hidden,
+            need_meta,
+            max_items,
+            LOAD_BATCH_SIZE,
+            &tx,
+          );
+          return;
+        }
+        ListingMode::Tree(opts) =>
+        {
+          crate::app::tree::stream_tree_entries(&dir, &opts, &tx);
+          return;
+        }
+        ListingMode::Plain =>
+        {}
+      }
+      // Archives and remote directories are listed in one batch
+      if let Some(listing) = crate::core::vfs::list(&dir, show_hidden)
+      {
+        match listing
+        {
+          Ok(mut entries) =>
+          {
+            entries.truncate(max_items);
+            let _ = tx.send(DirBatch::Entries(entries));
+            let _ = tx.send(DirBatch::Done);
+          }
+          Err(e) =>
+          {
+            let _ = tx.send(DirBatch::Error(e.to_string()));
+          }
+        }
+        return;
+      }
+      let rd = match std::fs::read_dir(&dir)
+      {
+        Ok(rd) => rd,
+        Err(e) =>
+        {
+          let _ = tx.send(DirBatch::Error(e.to_string()));
+          return;
+        }
+      };
+      let mut batch = Vec::with_capacity(LOAD_BATCH_SIZE);
+      let mut total = 0usize;
+      for de in rd.flatten()
+      {
+        if total >= max_items
+        {
+          break;
+        }
+        if let Some(info) =
+          crate::core::listing::entry_info(&de, show_hidden, need_meta)
+        {
+          batch.push(info);
+          total += 1;
+        }
+        if batch.len() >= LOAD_BATCH_SIZE
+          && tx.send(DirBatch::Entries(std::mem::take(&mut batch))).is_err()
+        {
+          // Receiver dropped: the listing was superseded
+          return;
+        }
+      }
+      if !batch.is_empty()
+      {
+        let _ = tx.send(DirBatch::Entries(batch));
+      }
+      let _ = tx.send(DirBatch::Done);
+    });
+    DirLoad {
+      path: path.to_path_buf(),
+      rx,
+      reselect,
+      presorted,
+      lua_dirs: Default::default(),
+    }
+  }
+
+  /// Merge pending batches for the current pane. With a deadline, waits for
+  /// more batches until the load completes or the deadline passes.
+  fn drain_current_load(
+    &mut self,
+    deadline: Option<Instant>,
+  ) -> bool
+  {
+    let Some(mut load) = self.current_load.take()
+    else
+    {
+      return false;
+    };
+    let mut entries = std::mem::take(&mut self.current_entries);
+    let before = entries.len();
+    let (changed, done) = drain_batches(&load, &mut entries, deadline);
+    if changed
     {
-      self.current_entries.truncate(self.config.ui.max_list_items);
+      self.apply_lua_filters(
+        &load.path,
+        &mut entries,
+        before,
+        &mut load.lua_dirs,
+      );
+    }
+    if changed && let Some(pattern) = self.filters.get(&load.path)
+    {
+      // Tree rows keep their directories so matches stay reachable
+      entries.retain(|e| {
+        (load.presorted && e.is_dir)
+          || e.path.file_name().is_some_and(|n| {
+            crate::core::listing::filter_matches(pattern, &n.to_string_lossy())
+          })
+      });
+    }
+    if changed && !load.presorted
+    {
+      crate::core::listing::sort_entries(
+        &mut entries,
+        self.sort_key,
+        self.sort_reverse,
+      );
     }
-    self.parent_entries = if let Some(p) = self.cwd.parent()
+    self.current_entries = entries;
+    if changed && let Some(name) = load.reselect.as_ref()
     {
-      self.read_dir_sorted(p).unwrap_or_default()
+      crate::core::selection::reselect_by_name(self, name);
     }
+    if !done
+    {
+      self.current_load = Some(load);
+    }
+    changed || done
+  }
+
+  fn drain_parent_load(
+    &mut self,
+    deadline: Option<Instant>,
+  ) -> bool
+  {
+    let Some(mut load) = self.parent_load.take()
     else
     {
-      Vec::new()
+      return false;
     };
-    if self.parent_entries.len() > self.config.ui.max_list_items
+    let mut entries = std::mem::take(&mut self.parent_entries);
+    let before = entries.len();
+    let (changed, done) = drain_batches(&load, &mut entries, deadline);
+    if changed
     {
-      self.parent_entries.truncate(self.config.ui.max_list_items);
+      self.apply_lua_filters(
+        &load.path,
+        &mut entries,
+        before,
+        &mut load.lua_dirs,
+      );
+      crate::core::listing::sort_entries(
+        &mut entries,
+        self.sort_key,
+        self.sort_reverse,
+      );
     }
-    // Clamp selection
+    self.parent_entries = entries;
+    if !done
+    {
+      self.parent_load = Some(load);
+    }
+    changed || done
+  }
+
+  fn clamp_selection(&mut self)
+  {
     let max_idx = self.current_entries.len().saturating_sub(1);
     if let Some(sel) = self.list_state.selected()
     {
@@ -78,9 +523,17 @@ impl App
     {
       self.list_state.select(Some(0));
     }
-    // Invalidate dynamic preview cache on list refresh
-    self.preview.cache_key = None;
-    self.preview.cache_lines = None;
+  }
+
+  pub(crate) fn listing_needs_meta(&self) -> bool
+  {
+    !matches!(self.info_mode, InfoMode::None)
+      || self.config.ui.row.as_ref().is_some_and(crate::ui::row::row_uses_meta)
+      || crate::ui::template::status_uses_meta(&self.config.ui)
+      || !matches!(
+        self.sort_key,
+        SortKey::Name | SortKey::Natural | SortKey::Extension
+      )
   }
 
   pub(crate) fn read_dir_sorted(
@@ -88,29 +541,111 @@ impl App
     path: &Path,
   ) -> io::Result<Vec<DirEntryInfo>>
   {
-    let need_meta = !matches!(self.info_mode, InfoMode::None)
-      || !matches!(self.sort_key, SortKey::Name);
-    crate::core::listing::read_dir_sorted(
+    let mut entries = crate::core::listing::read_dir_sorted(
       path,
       self.config.ui.show_hidden,
       self.sort_key,
       self.sort_reverse,
-      need_meta,
+      self.listing_needs_meta(),
       self.config.ui.max_list_items,
-    )
+    )?;
+    self.apply_lua_filters(path, &mut entries, 0, &mut Default::default());
+    Ok(entries)
   }
 
+  /// The first `limit` entries of `path` as [`Self::read_dir_sorted`] lists
+  /// them, for directory previews. When the order only depends on names,
+  /// metadata is read for those entries alone.
+  pub(crate) fn read_dir_head(
+    &self,
+    path: &Path,
+    limit: usize,
+  ) -> io::Result<Vec<DirEntryInfo>>
+  {
+    let by_name = matches!(
+      self.sort_key,
+      SortKey::Name | SortKey::Natural | SortKey::Extension
+    );
+    // Lua filters may look at sizes and times, so they get full entries
+    if !self.listing_needs_meta()
+      || !by_name
+      || !self.config.entry_filters.is_empty()
+    {
+      let mut entries = self.read_dir_sorted(path)?;
+      entries.truncate(limit);
+      return Ok(entries);
+    }
+    let mut entries = crate::core::listing::read_dir_sorted(
+      path,
+      self.config.ui.show_hidden,
+      self.sort_key,
+      self.sort_reverse,
+      false,
+      self.config.ui.max_list_items,
+    )?;
+    entries.truncate(limit);
+    entries.iter_mut().for_each(crate::core::listing::fill_meta);
+    Ok(entries)
+  }
+
+  /// Change to `path`, putting the cursor back on the entry that was focused
+  /// when it was last left (or on the first entry).
   pub fn set_cwd(
     &mut self,
     path: &Path,
   )
   {
+    self.change_dir(path, None);
+  }
+
+  /// Change to the parent directory with the cursor on the one just left.
+  pub(crate) fn go_to_parent(&mut self)
+  {
+    let Some(parent) = crate::core::vfs::parent(&self.cwd)
+    else
+    {
+      return;
+    };
+    let just_left = if crate::core::vfs::is_drives(&parent)
+    {
+      Some(crate::core::vfs::drive_name(&self.cwd))
+    }
+    else
+    {
+      self.cwd.file_name().map(|s| s.to_string_lossy().to_string())
+    };
+    self.change_dir(&parent, just_left);
+  }
+
+  /// Change to `path` and focus `focus`, falling back to the remembered
+  /// entry and then the first one.
+  pub(crate) fn change_dir(
+    &mut self,
+    path: &Path,
+    focus: Option<String>,
+  )
+  {
+    self.remember_cursor();
     self.cwd = path.to_path_buf();
+    self.record_visit(path);
     self.refresh_lists();
-    if !self.current_entries.is_empty()
+    self
+      .list_state
+      .select(if self.current_entries.is_empty() { None } else { Some(0) });
+    if let Some(name) =
+      focus.or_else(|| self.cursor_memory.get(&self.cwd).cloned())
     {
-      self.list_state.select(Some(0));
-      self.refresh_preview();
+      self.reselect_when_loaded(&name);
+    }
+    self.refresh_preview();
+  }
+
+  /// Record the focused entry of the current directory for [`App::set_cwd`].
+  fn remember_cursor(&mut self)
+  {
+    if let Some(name) = self.selected_entry().map(|e| e.name.clone())
+    {
+      self.cursor_memory.insert(self.cwd.clone(), name);
     }
   }
 
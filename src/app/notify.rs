@@ -0,0 +1,107 @@
+//! Notifications shown in the bottom-right corner.
+//!
+//! `lsv.notify` and finished background jobs post a short note that fades
+//! after `ui.notify_ms` milliseconds; running jobs keep a note with their
+//! progress until they finish. Every posted note also goes to the message
+//! log, so it can be read again with `zm`.
+
+use std::time::{
+  Duration,
+  Instant,
+};
+
+use crate::app::{
+  App,
+  JobStatus,
+  MessageLevel,
+};
+
+/// Most notes on screen; older ones make room for new ones.
+const MAX_NOTIFICATIONS: usize = 5;
+
+#[derive(Debug, Clone)]
+pub struct Notification
+{
+  pub level:   MessageLevel,
+  pub text:    String,
+  /// When the note goes away; progress notes stay while their job runs.
+  pub expires: Option<Instant>,
+}
+
+impl App
+{
+  /// Post a note at `level` and record it in the message log.
+  pub fn notify(
+    &mut self,
+    level: MessageLevel,
+    text: &str,
+  )
+  {
+    let text = text.trim();
+    if text.is_empty()
+    {
+      return;
+    }
+    self.add_message_at(level, text);
+    let ms = self.config.ui.notify_ms;
+    if ms == 0
+    {
+      return;
+    }
+    self.notifications.push(Notification {
+      level,
+      text: text.to_string(),
+      expires: Some(Instant::now() + Duration::from_millis(ms)),
+    });
+    if self.notifications.len() > MAX_NOTIFICATIONS
+    {
+      let excess = self.notifications.len() - MAX_NOTIFICATIONS;
+      self.notifications.drain(0..excess);
+    }
+  }
+
+  /// Drop expired notes. Returns true when any went away.
+  pub fn poll_notifications(&mut self) -> bool
+  {
+    let now = Instant::now();
+    let before = self.notifications.len();
+    self.notifications.retain(|n| n.expires.is_none_or(|t| t > now));
+    self.notifications.len() != before
+  }
+
+  /// Notes to show, oldest first, followed by the progress of running jobs.
+  pub fn notifications(&self) -> Vec<Notification>
+  {
+    let mut out = self.notifications.clone();
+    if self.config.ui.notify_ms == 0
+    {
+      return out;
+    }
+    for job in &self.jobs.jobs
+    {
+      if job.status != JobStatus::Running
+      {
+        continue;
+      }
+      let p = &job.progress;
+      let percent = p
+        .bytes_done
+        .saturating_mul(100)
+        .checked_div(p.bytes_total)
+        .or_else(|| p.files_done.saturating_mul(100).checked_div(p.files_total))
+        .unwrap_or(0);
+      out.push(Notification {
+        level:   MessageLevel::Info,
+        text:    format!(
+          "{} {}%  {}/{} files",
+          job.kind.label(),
+          percent.min(100),
+          p.files_done,
+          p.files_total
+        ),
+        expires: None,
+      });
+    }
+    out
+  }
+}
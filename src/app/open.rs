@@ -0,0 +1,272 @@
+//! Opening files with the `openers` rules for App.
+//!
+//! The first rule matching the first item decides the command, which gets
+//! every item as a positional argument (`$1`, `$@` in the shell). Terminal
+//! openers get the terminal with the TUI suspended, detached ones are left
+//! running in the background, and the rest run to completion with their
+//! output shown when there is any.
+//!
+//! Opening at a line (from a grep match or the preview) uses the matching
+//! rule when it has a `line` template, and `$EDITOR` otherwise.
+
+use std::{
+  path::{
+    Path,
+    PathBuf,
+  },
+  process::Stdio,
+};
+
+use crate::{
+  app::{
+    App,
+    interactive::with_terminal,
+    rename::editor_command,
+  },
+  config::OpenerRule,
+  core::{
+    opener,
+    shell,
+  },
+};
+
+/// How `editor` is told to open at a line: `file:line` for helix, `+line`
+/// for vi, nano, emacs and most others.
+fn editor_line_template(editor: &str) -> &'static str
+{
+  let program = editor.split_whitespace().next().unwrap_or_default();
+  let name = Path::new(program)
+    .file_name()
+    .map(|s| s.to_string_lossy().to_string())
+    .unwrap_or_default();
+  match name.as_str()
+  {
+    "hx" | "helix" => "{file}:{line}",
+    _ => "+{line}",
+  }
+}
+
+/// Arguments for opening `file` at `line`: the words of `template` with
+/// `{file}` and `{line}` filled in, then the file unless it was named.
+fn line_args(
+  template: &str,
+  file: &Path,
+  line: usize,
+) -> Vec<PathBuf>
+{
+  let file_str = file.to_string_lossy();
+  let mut args: Vec<PathBuf> = template
+    .split_whitespace()
+    .map(|w| {
+      PathBuf::from(
+        w.replace("{file}", &file_str).replace("{line}", &line.to_string()),
+      )
+    })
+    .collect();
+  if !template.contains("{file}")
+  {
+    args.push(file.to_path_buf());
+  }
+  args
+}
+
+impl App
+{
+  /// Files the open action applies to: the selection, or the current entry.
+  /// Directories are left out.
+  fn open_targets(&self) -> Vec<PathBuf>
+  {
+    let mut items: Vec<PathBuf> = self.selected.iter().cloned().collect();
+    if items.is_empty()
+    {
+      items.extend(self.selected_entry().map(|e| e.path.clone()));
+    }
+    items.retain(|p| !p.is_dir());
+    items.sort();
+    items
+  }
+
+  /// Whether an opener rule applies to the entry under the cursor, so Enter
+  /// should open it.
+  pub(crate) fn has_opener_for_current(&self) -> bool
+  {
+    if self.config.openers.is_empty() || self.in_archive() || self.in_remote()
+    {
+      return false;
+    }
+    self.selected_entry().is_some_and(|e| {
+      !e.is_dir && opener::find_opener(&self.config.openers, &e.path).is_some()
+    })
+  }
+
+  /// Open the selected files (or the current one) with the first matching
+  /// opener rule. Returns whether a command was run.
+  pub(crate) fn open_selected(&mut self) -> bool
+  {
+    if self.refuse_in_archive("Open") || self.refuse_remote("Open")
+    {
+      return false;
+    }
+    let items = self.open_targets();
+    let Some(first) = items.first()
+    else
+    {
+      self.add_message("Open: no file selected");
+      return false;
+    };
+    let Some(rule) = opener::find_opener(&self.config.openers, first).cloned()
+    else
+    {
+      let name = first
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+      self.add_message(&format!("Open: no opener for {}", name));
+      return false;
+    };
+    crate::trace::log(format!(
+      "[open] '{}' on {} item(s)",
+      rule.cmd,
+      items.len()
+    ));
+    self.run_opener(&rule, &items, &items);
+    true
+  }
+
+  /// Open `path` at `line` (1-based) with its opener rule when that has a
+  /// `line` template, or in the editor otherwise. Returns whether a command
+  /// was run.
+  pub(crate) fn open_at_line(
+    &mut self,
+    path: &Path,
+    line: usize,
+  ) -> bool
+  {
+    if self.refuse_in_archive("Open") || self.refuse_remote("Open")
+    {
+      return false;
+    }
+    let found = opener::find_opener(&self.config.openers, path)
+      .filter(|r| r.line.is_some())
+      .cloned();
+    let (rule, template) = match found
+    {
+      Some(rule) =>
+      {
+        let template = rule.line.clone().unwrap_or_default();
+        (rule, template)
+      }
+      None =>
+      {
+        let editor = editor_command();
+        let template = editor_line_template(&editor).to_string();
+        let cmd =
+          if cfg!(windows) { editor } else { format!("{} \"$@\"", editor) };
+        (OpenerRule { cmd, terminal: true, ..Default::default() }, template)
+      }
+    };
+    let line = line.max(1);
+    crate::trace::log(format!(
+      "[open] '{}' on {} at line {}",
+      rule.cmd,
+      path.display(),
+      line
+    ));
+    let args = line_args(&template, path, line);
+    self.run_opener(&rule, &args, &[path.to_path_buf()]);
+    true
+  }
+
+  /// Open the current file at `line`, or at the first line shown in the
+  /// preview.
+  pub(crate) fn open_current_at_line(
+    &mut self,
+    line: Option<usize>,
+  ) -> bool
+  {
+    let Some(path) =
+      self.selected_entry().filter(|e| !e.is_dir).map(|e| e.path.clone())
+    else
+    {
+      self.add_message("Open: no file selected");
+      return false;
+    };
+    let line = line.unwrap_or(self.preview.offset + 1);
+    self.open_at_line(&path, line)
+  }
+
+  /// Run `rule` with `args` as its positional arguments and `files`, the
+  /// first as `{file}`, filling in its placeholders.
+  fn run_opener(
+    &mut self,
+    rule: &OpenerRule,
+    args: &[PathBuf],
+    files: &[PathBuf],
+  )
+  {
+    let mut ph = self.placeholders(files);
+    ph.file = files.first().cloned();
+    let shell = &self.config.command_shell;
+    let cmd = shell::expand(shell, &rule.cmd, &ph);
+    let mut command = shell::with_args(shell, &cmd, args);
+    command.current_dir(&self.cwd);
+    if rule.terminal
+    {
+      let status = with_terminal(|| command.status());
+      self.force_full_redraw = true;
+      match status
+      {
+        Ok(s) if s.success() =>
+        {}
+        Ok(s) =>
+        {
+          self.add_message(&format!("Open: {} exited with {}", rule.cmd, s))
+        }
+        Err(e) =>
+        {
+          self.add_error(&format!("Open: cannot run {}: {}", rule.cmd, e))
+        }
+      }
+    }
+    else if rule.detach
+    {
+      let spawned = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+      if let Err(e) = spawned
+      {
+        self.add_error(&format!("Open: cannot run {}: {}", rule.cmd, e));
+      }
+    }
+    else
+    {
+      match command.stdin(Stdio::null()).output()
+      {
+        Ok(out) =>
+        {
+          let mut text = String::from_utf8_lossy(&out.stdout).to_string();
+          text.push_str(&String::from_utf8_lossy(&out.stderr));
+          if !out.status.success()
+          {
+            text.push_str(&format!(
+              "\n[{} exited with {}]",
+              rule.cmd, out.status
+            ));
+          }
+          if !text.trim().is_empty()
+          {
+            self.display_output("Open", &text);
+          }
+        }
+        Err(e) =>
+        {
+          self.add_error(&format!("Open: cannot run {}: {}", rule.cmd, e))
+        }
+      }
+    }
+    self.refresh_lists();
+    self.refresh_preview();
+  }
+}
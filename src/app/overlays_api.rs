@@ -33,21 +33,9 @@ impl App
         root.join("themes")
       }
     };
-    let read_dir = match fs::read_dir(&themes_dir)
-    {
-      Ok(rd) => rd,
-      Err(_) =>
-      {
-        self.add_message(&format!(
-          "Theme picker: no themes directory at {}",
-          themes_dir.display()
-        ));
-        return;
-      }
-    };
-
     let mut entries: Vec<ThemePickerEntry> = Vec::new();
-    for entry in read_dir
+    // A missing themes directory leaves just the built-in themes
+    for entry in fs::read_dir(&themes_dir).into_iter().flatten()
     {
       match entry
       {
@@ -78,7 +66,7 @@ impl App
                 .and_then(|s| s.to_str())
                 .map(|s| s.to_string())
                 .unwrap_or_else(|| path.display().to_string());
-              entries.push(ThemePickerEntry { name, path, theme });
+              entries.push(ThemePickerEntry { name, path: Some(path), theme });
             }
             Err(e) =>
             {
@@ -99,21 +87,43 @@ impl App
         }
       }
     }
+    // Built-in themes, unless a user theme has the same name
+    for (name, src) in crate::config::builtin_themes::THEMES
+    {
+      if entries.iter().any(|e| e.name.eq_ignore_ascii_case(name))
+      {
+        continue;
+      }
+      match crate::config::load_theme_from_source(name, src)
+      {
+        Ok(theme) => entries.push(ThemePickerEntry {
+          name: name.to_string(),
+          path: None,
+          theme,
+        }),
+        Err(e) =>
+        {
+          crate::trace::log(format!("[theme] built-in {}: {}", name, e))
+        }
+      }
+    }
 
     if entries.is_empty()
     {
-      self.add_message(&format!(
-        "Theme picker: no .lua themes found in {}",
-        themes_dir.display()
-      ));
+      self.add_message("Theme picker: no themes found");
       return;
     }
 
-    entries.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    entries.sort_by_key(|e| e.name.to_lowercase());
     let current_path = self.config.ui.theme_path.clone();
     let mut selected = 0usize;
-    if let Some(cur) = current_path.as_ref()
-      && let Some(idx) = entries.iter().position(|e| e.path == *cur)
+    let current_theme = self.config.ui.theme.as_ref();
+    if let Some(idx) = entries.iter().position(|e| match e.path
+    {
+      Some(ref p) => current_path.as_ref() == Some(p),
+      // Built-in themes have no file; recognise them by their colours
+      None => current_path.is_none() && current_theme == Some(&e.theme),
+    })
     {
       selected = idx;
     }
@@ -131,41 +141,42 @@ impl App
 
   pub(crate) fn open_add_entry_prompt(&mut self)
   {
+    if self.refuse_in_archive("Add") || self.refuse_remote("Add")
+    {
+      return;
+    }
     crate::core::overlays::open_add_entry_prompt(self)
   }
 
   pub(crate) fn open_rename_entry_prompt(&mut self)
   {
+    if self.refuse_in_archive("Rename")
+    {
+      return;
+    }
     crate::core::overlays::open_rename_entry_prompt(self)
   }
 
   pub(crate) fn request_delete_selected(&mut self)
   {
+    if self.refuse_in_archive("Delete")
+    {
+      return;
+    }
     crate::core::overlays::request_delete_selected(self)
   }
 
-  pub(crate) fn perform_delete_path(
+  /// Queue a background job deleting `paths` permanently.
+  pub(crate) fn perform_delete_paths(
     &mut self,
-    path: &std::path::Path,
+    paths: &[std::path::PathBuf],
   )
   {
-    crate::trace::log(format!("[delete] perform path='{}'", path.display()));
-    let res = crate::core::fs_ops::remove_path_all(path);
-    match res
+    crate::trace::log(format!("[delete] queue {} path(s)", paths.len()));
+    for p in paths
     {
-      Ok(_) =>
-      {
-        crate::trace::log("[delete] success");
-        self.add_message("Deleted");
-        self.selected.remove(path);
-      }
-      Err(e) =>
-      {
-        crate::trace::log(format!("[delete] error: {}", e));
-        self.add_message(&format!("Delete error: {}", e));
-      }
+      self.selected.remove(p);
     }
-    self.refresh_lists();
-    self.refresh_preview();
+    self.enqueue_job(crate::app::JobKind::Delete, paths.to_vec(), None);
   }
 }
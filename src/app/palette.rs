@@ -0,0 +1,253 @@
+//! Command palette for App.
+//!
+//! `:palette` (`<C-p>` by default) lists every mapped action, built-in `:`
+//! command and command registered from Lua in one searchable overlay.
+//! Typing filters the list with [`crate::core::fuzzy`]; accepting runs the
+//! entry as if its key had been pressed. Commands that need an argument,
+//! such as `cd` or `grep`, open the command pane with the name filled in.
+
+use std::collections::HashMap;
+
+use crate::app::{
+  App,
+  Overlay,
+  PaletteEntry,
+  PaletteState,
+};
+
+/// Action that opens the palette; it is left out of the list.
+pub(crate) const PALETTE_ACTION: &str = "cmd:palette";
+
+/// Spelling-independent name of an action, so `sort:name` and `cmd:sort
+/// name` or `tab:new` and `cmd:tab_new` count as the same entry.
+fn action_key(action: &str) -> String
+{
+  let low = action.trim().to_ascii_lowercase();
+  let name = low
+    .strip_prefix("cmd:")
+    .or_else(|| low.strip_prefix("clipboard:"))
+    .unwrap_or(&low);
+  name
+    .split([':', '_', ' '])
+    .filter(|w| !w.is_empty())
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+fn rerank(st: &mut PaletteState)
+{
+  st.results = if st.input.trim().is_empty()
+  {
+    (0..st.entries.len()).collect()
+  }
+  else
+  {
+    let haystacks: Vec<String> = st
+      .entries
+      .iter()
+      .map(|e| {
+        let name = e.action.strip_prefix("cmd:").unwrap_or(&e.action);
+        format!("{} {}", e.label, name)
+      })
+      .collect();
+    crate::core::fuzzy::rank(&st.input, &haystacks, haystacks.len())
+      .into_iter()
+      .map(|(i, _)| i)
+      .collect()
+  };
+  st.selected = st.selected.min(st.results.len().saturating_sub(1));
+}
+
+impl App
+{
+  /// Entries for the palette: mapped actions with their keys first merged
+  /// into one entry per action, then the commands no key runs, sorted by
+  /// label.
+  fn palette_entries(&self) -> Vec<PaletteEntry>
+  {
+    let mut entries: Vec<PaletteEntry> = Vec::new();
+    let mut by_action: HashMap<String, usize> = HashMap::new();
+    for km in &self.keys.maps
+    {
+      let action = km.action.trim();
+      if action.eq_ignore_ascii_case(PALETTE_ACTION)
+      {
+        continue;
+      }
+      if let Some(&i) = by_action.get(&action_key(action))
+      {
+        entries[i].keys.push(km.sequence.clone());
+        if let Some(desc) = km.description.as_ref()
+        {
+          entries[i].label = desc.clone();
+        }
+        continue;
+      }
+      by_action.insert(action_key(action), entries.len());
+      entries.push(PaletteEntry {
+        label:  km.description.clone().unwrap_or_else(|| {
+          crate::ui::overlays::whichkey::action_label(action)
+        }),
+        keys:   vec![km.sequence.clone()],
+        action: action.to_string(),
+      });
+    }
+    let commands = crate::commands::all()
+      .iter()
+      .map(|c| c.to_string())
+      .chain(self.config.commands.iter().map(|c| c.name.clone()));
+    for cmd in commands
+    {
+      let action = format!("cmd:{}", cmd);
+      let key = action_key(&action);
+      if action == PALETTE_ACTION || by_action.contains_key(&key)
+      {
+        continue;
+      }
+      by_action.insert(key, entries.len());
+      entries.push(PaletteEntry {
+        label: crate::ui::overlays::whichkey::action_label(&action),
+        keys: Vec::new(),
+        action,
+      });
+    }
+    entries.sort_by_key(|e| e.label.to_lowercase());
+    entries
+  }
+
+  /// Open the command palette.
+  pub(crate) fn open_palette(&mut self)
+  {
+    let mut st = PaletteState {
+      input:    String::new(),
+      entries:  self.palette_entries(),
+      results:  Vec::new(),
+      selected: 0,
+    };
+    rerank(&mut st);
+    self.keys.pending.clear();
+    self.keys.last_at = None;
+    self.overlay = Overlay::Palette(Box::new(st));
+    self.force_full_redraw = true;
+  }
+
+  /// Labels of the entries matching the palette query, best first.
+  pub fn palette_results(&self) -> Vec<String>
+  {
+    match self.overlay
+    {
+      Overlay::Palette(ref st) => st
+        .results
+        .iter()
+        .filter_map(|&i| st.entries.get(i))
+        .map(|e| e.label.clone())
+        .collect(),
+      _ => Vec::new(),
+    }
+  }
+
+  /// Replace the palette query and re-rank entries.
+  pub fn set_palette_query(
+    &mut self,
+    query: &str,
+  )
+  {
+    if let Overlay::Palette(ref mut st) = self.overlay
+    {
+      st.input = query.to_string();
+      st.selected = 0;
+      rerank(st);
+    }
+  }
+
+  pub(crate) fn palette_edit(
+    &mut self,
+    ch: Option<char>,
+  )
+  {
+    let query = match self.overlay
+    {
+      Overlay::Palette(ref st) =>
+      {
+        let mut q = st.input.clone();
+        match ch
+        {
+          Some(c) => q.push(c),
+          None =>
+          {
+            q.pop();
+          }
+        }
+        q
+      }
+      _ => return,
+    };
+    self.set_palette_query(&query);
+  }
+
+  pub(crate) fn palette_move(
+    &mut self,
+    delta: isize,
+  )
+  {
+    if let Overlay::Palette(ref mut st) = self.overlay
+      && !st.results.is_empty()
+    {
+      let last = st.results.len() as isize - 1;
+      st.selected = (st.selected as isize + delta).clamp(0, last) as usize;
+    }
+  }
+
+  pub(crate) fn close_palette(&mut self)
+  {
+    self.overlay = Overlay::None;
+    self.force_full_redraw = true;
+  }
+
+  /// Run the selected entry, or open the command pane for a command that
+  /// needs an argument.
+  pub fn palette_accept(&mut self)
+  {
+    let action = match self.overlay
+    {
+      Overlay::Palette(ref st) => st
+        .results
+        .get(st.selected)
+        .and_then(|&i| st.entries.get(i))
+        .map(|e| e.action.clone()),
+      _ => None,
+    };
+    self.close_palette();
+    let Some(action) = action
+    else
+    {
+      return;
+    };
+    if let Some(cmd) = action.strip_prefix("cmd:")
+      && self.command_takes_argument(cmd)
+    {
+      self.open_command_with(&format!("{} ", cmd));
+      return;
+    }
+    self.record_macro_step(&action, None);
+    if let Err(e) = crate::actions::dispatch_action(self, &action)
+    {
+      self.add_error(&format!("Palette: {}", e));
+    }
+  }
+
+  /// Whether `cmd` does nothing useful without an argument: built-ins such
+  /// as `cd` and Lua commands that offer completion values.
+  fn command_takes_argument(
+    &self,
+    cmd: &str,
+  ) -> bool
+  {
+    crate::commands::takes_argument(cmd)
+      || self
+        .config
+        .commands
+        .iter()
+        .any(|c| c.name == cmd && !c.complete.is_empty())
+  }
+}
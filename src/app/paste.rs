@@ -0,0 +1,333 @@
+//! Name conflicts when pasting, for App.
+//!
+//! Pasted items whose names already exist in the destination are asked about
+//! one at a time in a confirm overlay: overwrite, skip or rename to a free
+//! `name_N` (`o`/`s`/`r`), or overwrite or skip every remaining clash
+//! (`O`/`S`). `Esc` cancels the whole paste. Once every clash is settled the
+//! paste runs as a single job, after a confirmation listing what gets
+//! replaced when that is more than one item.
+
+use std::path::{
+  Path,
+  PathBuf,
+};
+
+use crate::{
+  app::{
+    App,
+    ClipboardOp,
+    ConfirmKind,
+    ConfirmState,
+    JobKind,
+    Overlay,
+    PasteConflicts,
+    PasteTarget,
+  },
+  core::{
+    fs_ops,
+    vfs::{
+      self,
+      Location,
+    },
+  },
+};
+
+const CHOICES: &str = "[o]verwrite [s]kip [r]ename  [O]verwrite all [S]kip all";
+
+/// An answer to the paste conflict overlay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ConflictChoice
+{
+  Overwrite,
+  Skip,
+  Rename,
+  OverwriteAll,
+  SkipAll,
+  Cancel,
+}
+
+impl App
+{
+  /// Paste `items` into `dest_dir`, asking first about names already taken
+  /// there.
+  pub(crate) fn plan_paste(
+    &mut self,
+    op: ClipboardOp,
+    items: Vec<PathBuf>,
+    dest_dir: PathBuf,
+  )
+  {
+    // A remote directory is listed once rather than asked about each name
+    let remote_names = match vfs::locate(&dest_dir)
+    {
+      Location::Remote(_) => Some(
+        vfs::list(&dest_dir, true)
+          .and_then(Result::ok)
+          .unwrap_or_default()
+          .into_iter()
+          .map(|e| e.name)
+          .collect(),
+      ),
+      _ => None,
+    };
+    let mut paste = PasteConflicts {
+      op,
+      dest_dir,
+      planned: Vec::new(),
+      pending: Vec::new(),
+      total: 0,
+      remote_names,
+      claimed: Default::default(),
+    };
+    for src in items
+    {
+      let Some(name) = src.file_name()
+      else
+      {
+        continue;
+      };
+      let dest = paste.dest_dir.join(name);
+      if paste.taken(&dest)
+      {
+        paste.pending.push(src);
+      }
+      else
+      {
+        paste.plan(src, dest, false);
+      }
+    }
+    // Selections are unordered; ask in a predictable order
+    paste.pending.sort();
+    paste.total = paste.pending.len();
+    self.continue_paste(paste);
+  }
+
+  /// Ask about the next clash, or queue the paste when none are left.
+  fn continue_paste(
+    &mut self,
+    paste: PasteConflicts,
+  )
+  {
+    if let Some(src) = paste.pending.first()
+    {
+      let name =
+        src.file_name().map(|s| s.to_string_lossy()).unwrap_or_default();
+      let title = format!(
+        "Paste conflict ({} of {})",
+        paste.total - paste.pending.len() + 1,
+        paste.total
+      );
+      let question = format!("'{}' already exists.\n{}", name, CHOICES);
+      self.keys.pending.clear();
+      self.keys.last_at = None;
+      self.overlay = Overlay::Confirm(Box::new(ConfirmState {
+        title,
+        question,
+        default_yes: false,
+        kind: ConfirmKind::PasteConflict(Box::new(paste)),
+        details: Vec::new(),
+        scroll: 0,
+        page_height: 1,
+      }));
+      self.force_full_redraw = true;
+      return;
+    }
+    if paste.planned.is_empty()
+    {
+      self.add_message("Paste: nothing to do");
+      return;
+    }
+    let replaced: Vec<PathBuf> = paste
+      .planned
+      .iter()
+      .filter(|(_, t)| t.overwrite)
+      .map(|(_, t)| t.dest.clone())
+      .collect();
+    if replaced.len() > 1 && self.config.ui.confirm_delete
+    {
+      let question = format!(
+        "Paste {} item(s) into {}, replacing {} existing ones? \
+         (y/n)\nReplaced: {}",
+        paste.planned.len(),
+        paste.dest_dir.display(),
+        replaced.len(),
+        crate::app::confirm::batch_summary(&replaced)
+      );
+      let details = replaced.iter().map(|p| p.display().to_string()).collect();
+      self.open_batch_confirm(
+        "Confirm Overwrite",
+        question,
+        details,
+        ConfirmKind::PasteOverwrite(Box::new(paste)),
+      );
+      return;
+    }
+    self.queue_paste(paste);
+  }
+
+  /// Run a paste whose destinations are all settled.
+  pub(crate) fn queue_paste(
+    &mut self,
+    paste: PasteConflicts,
+  )
+  {
+    let kind = match paste.op
+    {
+      ClipboardOp::Copy => JobKind::Copy,
+      ClipboardOp::Move => JobKind::Move,
+    };
+    self.enqueue_paste(kind, paste.planned, paste.dest_dir);
+    self.force_full_redraw = true;
+  }
+
+  /// Settle the clash the overlay is asking about (or all of them).
+  pub(crate) fn resolve_paste_conflict(
+    &mut self,
+    choice: ConflictChoice,
+  )
+  {
+    let mut paste = match std::mem::replace(&mut self.overlay, Overlay::None)
+    {
+      Overlay::Confirm(st) => match st.kind
+      {
+        ConfirmKind::PasteConflict(paste) => *paste,
+        _ =>
+        {
+          self.overlay = Overlay::Confirm(st);
+          return;
+        }
+      },
+      other =>
+      {
+        self.overlay = other;
+        return;
+      }
+    };
+    self.force_full_redraw = true;
+    match choice
+    {
+      ConflictChoice::Cancel =>
+      {
+        self.add_message("Paste cancelled");
+        return;
+      }
+      ConflictChoice::OverwriteAll | ConflictChoice::SkipAll =>
+      {
+        for src in std::mem::take(&mut paste.pending)
+        {
+          self.settle_conflict(&mut paste, src, choice);
+        }
+      }
+      _ if !paste.pending.is_empty() =>
+      {
+        let src = paste.pending.remove(0);
+        self.settle_conflict(&mut paste, src, choice);
+      }
+      _ =>
+      {}
+    }
+    self.continue_paste(paste);
+  }
+
+  fn settle_conflict(
+    &mut self,
+    paste: &mut PasteConflicts,
+    src: PathBuf,
+    choice: ConflictChoice,
+  )
+  {
+    let Some(name) = src.file_name()
+    else
+    {
+      return;
+    };
+    let dest = paste.dest_dir.join(name);
+    match choice
+    {
+      ConflictChoice::Rename =>
+      {
+        let dest = fs_ops::free_name_where(&dest, |p| paste.taken(p));
+        paste.plan(src, dest, false);
+      }
+      // Another item of this paste already goes there
+      ConflictChoice::Overwrite | ConflictChoice::OverwriteAll
+        if paste.claimed.contains(&dest) =>
+      {
+        self.add_message(&format!(
+          "Skip (pasted there already): {}",
+          dest.display()
+        ));
+      }
+      ConflictChoice::Overwrite | ConflictChoice::OverwriteAll
+        if !replaces_itself(&src, &dest) =>
+      {
+        paste.plan(src, dest, true);
+      }
+      ConflictChoice::Overwrite | ConflictChoice::OverwriteAll =>
+      {
+        self.add_message(&format!(
+          "Skip (would overwrite itself): {}",
+          dest.display()
+        ));
+      }
+      _ =>
+      {
+        self.add_message(&format!("Skip (exists): {}", dest.display()));
+      }
+    }
+  }
+}
+
+impl PasteConflicts
+{
+  /// Whether something already goes by the name of `dest`, in the
+  /// destination or among the items planned so far.
+  fn taken(
+    &self,
+    dest: &Path,
+  ) -> bool
+  {
+    if self.claimed.contains(dest)
+    {
+      return true;
+    }
+    match &self.remote_names
+    {
+      Some(names) => dest
+        .file_name()
+        .is_some_and(|n| names.contains(n.to_string_lossy().as_ref())),
+      None => dest.symlink_metadata().is_ok(),
+    }
+  }
+
+  /// Settle `src` on `dest`, keeping the name from later items.
+  fn plan(
+    &mut self,
+    src: PathBuf,
+    dest: PathBuf,
+    overwrite: bool,
+  )
+  {
+    self.claimed.insert(dest.clone());
+    self.planned.push((src, PasteTarget { dest, overwrite }));
+  }
+}
+
+/// Whether removing `dest` would remove `src` too: pasting an item onto
+/// itself or onto a directory holding it.
+fn replaces_itself(
+  src: &Path,
+  dest: &Path,
+) -> bool
+{
+  let src = std::fs::canonicalize(src).unwrap_or(src.to_path_buf());
+  // Resolve only the parent, so a symlink at `dest` is judged as itself
+  let dest = match (dest.parent(), dest.file_name())
+  {
+    (Some(dir), Some(name)) => std::fs::canonicalize(dir)
+      .map(|d| d.join(name))
+      .unwrap_or(dest.to_path_buf()),
+    _ => dest.to_path_buf(),
+  };
+  src.starts_with(&dest)
+}
@@ -0,0 +1,157 @@
+//! Mode and owner changes (`:set_permissions`) for App.
+//!
+//! `:set_permissions` alone opens a prompt, pre-filled with the current
+//! mode, for the selected entries (or the one under the cursor);
+//! `:set_permissions <request>` applies a request straight away. Recursive
+//! requests run as a background job. See [`crate::core::permissions`] for
+//! the request syntax.
+
+use std::path::PathBuf;
+
+use crate::{
+  app::{
+    App,
+    Overlay,
+    PromptKind,
+    PromptState,
+  },
+  core::permissions,
+};
+
+impl App
+{
+  /// The selected entries, or the one under the cursor.
+  fn permission_targets(&self) -> Vec<PathBuf>
+  {
+    if self.selected.is_empty()
+    {
+      self.selected_entry().map(|e| vec![e.path.clone()]).unwrap_or_default()
+    }
+    else
+    {
+      let mut items: Vec<PathBuf> = self.selected.iter().cloned().collect();
+      items.sort();
+      items
+    }
+  }
+
+  /// Change the targets' permissions as `request` asks, or prompt for the
+  /// request when it is empty.
+  pub(crate) fn set_permissions(
+    &mut self,
+    request: &str,
+  )
+  {
+    if self.refuse_in_archive("set_permissions")
+      || self.refuse_remote("set_permissions")
+    {
+      return;
+    }
+    let items = self.permission_targets();
+    if items.is_empty()
+    {
+      self.add_message("set_permissions: nothing selected");
+      return;
+    }
+    if request.trim().is_empty()
+    {
+      self.open_permissions_prompt(items);
+    }
+    else
+    {
+      self.apply_permissions(&items, request);
+    }
+  }
+
+  fn open_permissions_prompt(
+    &mut self,
+    items: Vec<PathBuf>,
+  )
+  {
+    #[cfg(unix)]
+    let input = permissions::current_mode(&items[0]).unwrap_or_default();
+    #[cfg(not(unix))]
+    let input = String::new();
+    let title = match items.as_slice()
+    {
+      [one] => format!(
+        "Permissions of '{}' ([-R] MODE [OWNER[:GROUP]]):",
+        one.file_name().map(|s| s.to_string_lossy()).unwrap_or_default()
+      ),
+      many => format!(
+        "Permissions of {} items ([-R] MODE [OWNER[:GROUP]]):",
+        many.len()
+      ),
+    };
+    self.keys.pending.clear();
+    self.keys.last_at = None;
+    self.overlay = Overlay::Prompt(Box::new(PromptState {
+      title,
+      cursor: input.len(),
+      input,
+      kind: PromptKind::SetPermissions { items },
+    }));
+    self.force_full_redraw = true;
+  }
+
+  /// Apply a `[-R] [MODE] [OWNER[:GROUP]]` request to `items`.
+  pub(crate) fn apply_permissions(
+    &mut self,
+    items: &[PathBuf],
+    request: &str,
+  )
+  {
+    let req = match permissions::parse_request(request)
+    {
+      Ok(r) => r,
+      Err(e) =>
+      {
+        self.add_message(&format!("set_permissions: {}", e));
+        return;
+      }
+    };
+    #[cfg(not(unix))]
+    {
+      let _ = (items, req);
+      self.add_message("set_permissions: not supported on this platform");
+    }
+    #[cfg(unix)]
+    {
+      crate::trace::log(format!(
+        "[permissions] '{}' on {} item(s)",
+        request.trim(),
+        items.len()
+      ));
+      // Walking whole trees can take a while
+      if req.recursive
+      {
+        self.enqueue_permissions(items.to_vec(), req);
+        return;
+      }
+      let mut errors = Vec::new();
+      if let Some(owner) = req.owner.as_deref()
+        && let Err(e) = permissions::chown(items, owner, false)
+      {
+        errors.push(e);
+      }
+      let mut changed = 0;
+      if let Some(spec) = req.mode.as_ref()
+      {
+        let (done, failed) = permissions::chmod(items, spec);
+        changed = done;
+        errors.extend(failed);
+      }
+      for e in errors.iter().take(5)
+      {
+        self.add_message(&format!("set_permissions: {}", e));
+      }
+      if errors.is_empty()
+      {
+        let what = if req.mode.is_some() { changed } else { items.len() };
+        self.add_message(&format!("Permissions set on {} item(s)", what));
+      }
+      self.refresh_lists();
+      self.refresh_preview();
+    }
+  }
+}
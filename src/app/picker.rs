@@ -0,0 +1,126 @@
+//! File picker mode for App.
+//!
+//! Started with `--choosefile` or `--choosefiles`, lsv behaves as a file
+//! picker: `Enter` on a file records the choice and quits, and the caller
+//! writes the chosen paths out (one per line) once the terminal is restored.
+//! Directories are still entered with `Enter`; quitting with `q` picks
+//! nothing, and lsv exits with status 1. With `-` as the target the paths go
+//! to stdout, so the TUI draws on the terminal itself
+//! ([`crate::util::terminal_output`]).
+
+use std::{
+  io::{
+    self,
+    Write,
+  },
+  path::{
+    Path,
+    PathBuf,
+  },
+};
+
+use crate::{
+  app::App,
+  core::vfs,
+};
+
+/// How many paths a picker session may return.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickMode
+{
+  /// The file under the cursor.
+  One,
+  /// The selected items, or the file under the cursor when none are.
+  Many,
+}
+
+/// Write `paths` one per line to `target`, or to stdout when it is `-`.
+pub fn write_picked(
+  target: &str,
+  paths: &[PathBuf],
+) -> io::Result<()>
+{
+  let mut text = String::new();
+  for p in paths
+  {
+    text.push_str(&p.to_string_lossy());
+    text.push('\n');
+  }
+  if target == "-"
+  {
+    let mut out = io::stdout();
+    out.write_all(text.as_bytes())?;
+    out.flush()
+  }
+  else
+  {
+    std::fs::write(Path::new(target), text)
+  }
+}
+
+impl App
+{
+  /// Turn on picker mode.
+  pub fn set_pick_mode(
+    &mut self,
+    mode: PickMode,
+  )
+  {
+    self.pick_mode = Some(mode);
+    self.add_message(match mode
+    {
+      PickMode::One => "Pick a file: Enter chooses it, q cancels",
+      PickMode::Many =>
+      {
+        "Pick files: select with Space, Enter chooses them, q cancels"
+      }
+    });
+  }
+
+  /// Paths chosen in picker mode; empty until a pick is made.
+  pub fn picked(&self) -> &[PathBuf]
+  {
+    &self.picked
+  }
+
+  /// Handle `Enter` in picker mode. Returns true when paths were picked and
+  /// lsv should quit; false lets the key enter directories as usual.
+  pub(crate) fn picker_accept(&mut self) -> bool
+  {
+    let Some(mode) = self.pick_mode
+    else
+    {
+      return false;
+    };
+    let Some(entry) = self.selected_entry()
+    else
+    {
+      return false;
+    };
+    if entry.is_dir || vfs::is_browsable_archive(&entry.path)
+    {
+      return false;
+    }
+    if vfs::resolve(&entry.path).is_some()
+    {
+      self.add_message("Pick: files inside archives cannot be chosen");
+      return false;
+    }
+    let mut paths: Vec<PathBuf> = if mode == PickMode::Many
+    {
+      self.selected.iter().cloned().collect()
+    }
+    else
+    {
+      Vec::new()
+    };
+    if paths.is_empty()
+    {
+      paths.push(entry.path.clone());
+    }
+    paths.sort();
+    self.picked = paths;
+    self.should_quit = true;
+    true
+  }
+}
@@ -1,15 +1,50 @@
 //! Preview lifecycle control for App.
 
-use crate::app::App;
+use std::process::Command;
+
+use crate::{
+  app::{
+    App,
+    PendingArchive,
+    PreviewContent,
+  },
+  core::shell,
+};
+
+/// Lines kept from a command streaming into the preview.
+const RUNNING_PREVIEW_LINES: usize = 2000;
+
+/// List the archive at `path` on a background thread; the listing is
+/// picked up by [`App::poll_preview`].
+fn spawn_archive_listing(
+  path: std::path::PathBuf,
+  kind: crate::core::archive::ArchiveKind,
+  limit: usize,
+) -> PendingArchive
+{
+  let (tx, rx) = std::sync::mpsc::channel();
+  let job = path.clone();
+  std::thread::spawn(move || {
+    // The receiver is gone when the selection moved on; nothing to do
+    let _ = tx.send(crate::core::archive::list(&job, kind, limit));
+  });
+  PendingArchive { path, rx }
+}
 
 impl App
 {
   pub(crate) fn refresh_preview(&mut self)
   {
-    if self.running_preview.is_some()
+    if let Some(rp) = self.running_preview.as_ref()
     {
-      // Live process is writing into preview
-      return;
+      let path = self.selected_entry().map(|e| e.path.clone());
+      if rp.path == path && rp.cwd == self.cwd
+      {
+        // Live process is writing into preview
+        return;
+      }
+      // Its output belongs to the previous selection
+      self.stop_preview_process();
     }
     // Avoid borrowing self while mutating by cloning the needed fields first
     let (is_dir, path) = match self.selected_entry()
@@ -18,23 +53,55 @@ impl App
       None =>
       {
         self.preview.static_lines.clear();
+        self.pending_archive = None;
+        self.preview.offset = 0;
+        self.preview.hscroll = 0;
+        self.preview.offset_path = None;
         // Invalidate dynamic preview cache when nothing selected
         self.preview.cache_key = None;
         self.preview.cache_lines = None;
         return;
       }
     };
+    let previous = std::mem::take(&mut self.preview.content);
+    let same_path = self.preview.offset_path.as_ref() == Some(&path);
+    // A listing still running for another file is no longer wanted
+    if self.pending_archive.as_ref().is_some_and(|p| p.path != path)
+    {
+      self.pending_archive = None;
+    }
+    if !same_path
+    {
+      self.preview.offset = 0;
+      self.preview.hscroll = 0;
+      self.preview.offset_path = Some(path.clone());
+    }
 
     const PREVIEW_LINES_LIMIT: usize = 200;
     let preview_limit = PREVIEW_LINES_LIMIT;
+    if crate::core::remote::is_remote(&path)
+    {
+      // Listing or reading it takes a round trip; files are previewed in
+      // the background by the preview pane
+      let size = self.selected_entry().map(|e| e.size).unwrap_or(0);
+      self.preview.static_lines = if is_dir
+      {
+        vec![String::from("<remote directory>")]
+      }
+      else
+      {
+        vec![String::from("<remote file>"), format!("size: {} bytes", size)]
+      };
+      return;
+    }
     if is_dir
     {
-      match self.read_dir_sorted(&path)
+      match self.read_dir_head(&path, preview_limit)
       {
         Ok(list) =>
         {
           let mut lines = Vec::new();
-          for e in list.into_iter().take(preview_limit)
+          for e in list
           {
             let marker = if e.is_dir { "/" } else { "" };
             let formatted = format!("{}{}", e.name, marker);
@@ -51,16 +118,58 @@ impl App
     }
     else
     {
-      // Detect binary early to avoid rendering junk or huge wrapped lines
-      if crate::util::is_binary(&path)
+      if let Some(ap) =
+        crate::core::vfs::resolve(&path).filter(|ap| !ap.inner.is_empty())
       {
-        let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        let size = crate::core::vfs::member_size(&ap).unwrap_or(0);
         self.preview.static_lines = vec![
-          String::from("<binary file>"),
+          format!("<archive member of {}>", ap.archive.display()),
           format!("size: {} bytes", size),
-          String::from("tip: configure a previewer for this type"),
+          String::from("tip: :extract_here extracts it next to the archive"),
         ];
       }
+      else if let Some(kind) = crate::core::archive::detect(&path)
+      {
+        // Listing reads the archive (or runs 7z) on a worker; the listing
+        // already shown for this file stays up until the new one arrives
+        if same_path && matches!(previous, PreviewContent::ArchiveListing(_))
+        {
+          self.preview.content = previous;
+        }
+        else
+        {
+          self.preview.static_lines =
+            vec![format!("<reading {} archive…>", kind.label())];
+        }
+        if self.pending_archive.is_none()
+        {
+          self.pending_archive =
+            Some(spawn_archive_listing(path.clone(), kind, preview_limit));
+        }
+      }
+      // Detect binary early to avoid rendering junk or huge wrapped lines
+      else if crate::util::is_binary(&path)
+      {
+        let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        self.preview.static_lines = if let Some(kind) =
+          self.thumbnail_kind(&path)
+        {
+          self.preview.content = PreviewContent::Thumbnail(kind);
+          vec![
+            String::from("<no thumbnail>"),
+            format!("size: {} bytes", size),
+            String::from("tip: check the ui.thumbnails commands are installed"),
+          ]
+        }
+        else
+        {
+          vec![
+            String::from("<binary file>"),
+            format!("size: {} bytes", size),
+            String::from("tip: configure a previewer for this type"),
+          ]
+        };
+      }
       else
       {
         // Cap bytes and lines to avoid runaway previews for huge files
@@ -74,6 +183,11 @@ impl App
           v.into_iter().map(|s| crate::util::sanitize_line(&s)).collect()
         })
         .unwrap_or_else(|e| vec![format!("<error reading file: {}>", e)]);
+        // Text images (SVG) show their source when no thumbnail can be made
+        if let Some(kind) = self.thumbnail_kind(&path)
+        {
+          self.preview.content = PreviewContent::Thumbnail(kind);
+        }
       }
       // Invalidate dynamic preview cache when selection changes
       self.preview.cache_key = None;
@@ -81,38 +195,296 @@ impl App
     }
   }
 
+  /// Forget kept previewer outputs and size the cache from the config.
+  pub(crate) fn reset_preview_cache(&mut self)
+  {
+    let ui = &self.config.ui;
+    let ttl = (ui.preview_ttl > 0)
+      .then(|| std::time::Duration::from_secs(ui.preview_ttl));
+    self.preview.recent.clear();
+    self.preview.recent.set_limits(ui.preview_cache, ttl);
+  }
+
+  fn thumbnail_kind(
+    &self,
+    path: &std::path::Path,
+  ) -> Option<crate::core::thumbnail::ThumbnailKind>
+  {
+    crate::core::thumbnail::kind_for(path, &self.config.ui.thumbnails)
+  }
+
+  /// Move finished background previewer results and archive listings into
+  /// the preview. Returns true when a result arrived.
+  pub fn poll_preview(&mut self) -> bool
+  {
+    let listed = self.poll_archive_listing();
+    self.poll_previewer() || listed
+  }
+
+  fn poll_archive_listing(&mut self) -> bool
+  {
+    use std::sync::mpsc::TryRecvError;
+    let Some(pending) = self.pending_archive.as_ref()
+    else
+    {
+      return false;
+    };
+    let res = match pending.rx.try_recv()
+    {
+      Ok(res) => res,
+      Err(TryRecvError::Empty) => return false,
+      Err(TryRecvError::Disconnected) =>
+      {
+        Err(std::io::Error::other("archive listing stopped"))
+      }
+    };
+    let Some(done) = self.pending_archive.take()
+    else
+    {
+      return false;
+    };
+    if self.selected_entry().map(|e| &e.path) != Some(&done.path)
+    {
+      return false;
+    }
+    match res
+    {
+      Ok(listing) =>
+      {
+        self.preview.static_lines.clear();
+        self.preview.content =
+          PreviewContent::ArchiveListing(Box::new(listing));
+      }
+      Err(e) =>
+      {
+        let label = crate::core::archive::detect(&done.path)
+          .map_or("", |kind| kind.label());
+        self.preview.static_lines =
+          vec![format!("<error reading {} archive: {}>", label, e)];
+        self.preview.content = PreviewContent::Text;
+      }
+    }
+    true
+  }
+
+  fn poll_previewer(&mut self) -> bool
+  {
+    use std::sync::mpsc::TryRecvError;
+    let Some(pending) = self.pending_preview.as_ref()
+    else
+    {
+      return false;
+    };
+    let lines = match pending.rx.try_recv()
+    {
+      Ok(lines) => lines,
+      Err(TryRecvError::Empty) => return false,
+      // Worker died without answering; fall back to the built-in preview
+      Err(TryRecvError::Disconnected) => None,
+    };
+    if let Some(done) = self.pending_preview.take()
+    {
+      let stamp = (done.key.clone(), done.mtime, done.size);
+      self.preview.recent.insert(stamp, lines.clone());
+      self.preview.cache_key = Some(done.key);
+      self.preview.cache_lines = lines;
+    }
+    true
+  }
+
+  /// Whether a previewer command or archive listing is still running in
+  /// the background.
+  pub fn is_preview_loading(&self) -> bool
+  {
+    self.pending_preview.is_some() || self.pending_archive.is_some()
+  }
+
+  /// Scroll the preview by `delta` lines, clamped to its content.
+  pub fn preview_scroll(
+    &mut self,
+    delta: isize,
+  )
+  {
+    let max = self.preview.total_lines.saturating_sub(1);
+    let next = (self.preview.offset as isize + delta).max(0) as usize;
+    self.preview.offset =
+      if self.preview.total_lines > 0 { next.min(max) } else { next };
+    self.force_full_redraw = true;
+  }
+
+  /// Scroll the preview by `pages` visible heights (negative scrolls up).
+  pub fn preview_scroll_page(
+    &mut self,
+    pages: isize,
+  )
+  {
+    let page = self.preview.page_height.max(1) as isize;
+    self.preview_scroll(pages * page);
+  }
+
+  /// Scroll the unwrapped preview sideways by `delta` columns, clamped so
+  /// the widest line's end stays in view.
+  pub fn preview_scroll_x(
+    &mut self,
+    delta: isize,
+  )
+  {
+    let max = self.preview.line_width.saturating_sub(self.preview.page_width);
+    let next = (self.preview.hscroll as isize + delta).max(0) as usize;
+    self.preview.hscroll =
+      if self.preview.line_width > 0 { next.min(max) } else { next };
+    self.force_full_redraw = true;
+  }
+
+  /// Switch the preview between wrapping long lines and cutting them.
+  pub fn toggle_preview_wrap(&mut self)
+  {
+    self.preview.nowrap = !self.preview.nowrap;
+    self.preview.hscroll = 0;
+    self.force_full_redraw = true;
+  }
+
+  /// Enter or leave preview focus mode.
+  pub fn toggle_preview_focus(&mut self)
+  {
+    self.preview.focused = !self.preview.focused;
+    self.force_full_redraw = true;
+  }
+
+  /// Whether preview focus mode is active.
+  pub fn is_preview_focused(&self) -> bool
+  {
+    self.preview.focused
+  }
+
+  /// Whether the preview wraps long lines.
+  pub fn preview_wraps(&self) -> bool
+  {
+    !self.preview.nowrap
+  }
+
+  /// First visible column of the unwrapped preview.
+  pub fn preview_hscroll(&self) -> usize
+  {
+    self.preview.hscroll
+  }
+
+  /// First visible line of the preview.
+  pub fn preview_offset(&self) -> usize
+  {
+    self.preview.offset
+  }
+
+  /// Whether a command started with `preview_run_cmd` is still streaming
+  /// into the preview.
+  pub fn is_preview_process_running(&self) -> bool
+  {
+    self.running_preview.as_ref().is_some_and(|rp| !rp.done)
+  }
+
+  /// Kill the command streaming into the preview, if any.
+  pub(crate) fn stop_preview_process(&mut self)
+  {
+    // Dropping it kills the child
+    if self.running_preview.take().is_some()
+    {
+      crate::trace::log("[preview] stopped the running preview command");
+    }
+  }
+
+  /// Append output of the command streaming into the preview; once it has
+  /// finished, show the normal preview again unless its output is kept.
+  /// Returns true when anything changed.
+  pub fn poll_running_preview(&mut self) -> bool
+  {
+    use std::sync::mpsc::TryRecvError;
+    let Some(rp) = self.running_preview.as_mut().filter(|rp| !rp.done)
+    else
+    {
+      return false;
+    };
+    let mut lines = Vec::new();
+    let finished = loop
+    {
+      match rp.rx.try_recv()
+      {
+        Ok(Some(line)) => lines.push(crate::util::sanitize_line(&line)),
+        Ok(None) | Err(TryRecvError::Disconnected) => break true,
+        Err(TryRecvError::Empty) => break false,
+      }
+    };
+    let changed = finished || !lines.is_empty();
+    let keep = rp.keep;
+    rp.done = finished && keep;
+    let static_lines = &mut self.preview.static_lines;
+    static_lines.extend(lines);
+    // Trim to a reasonable bound to avoid unbounded growth
+    let excess = static_lines.len().saturating_sub(RUNNING_PREVIEW_LINES);
+    static_lines.drain(..excess);
+    if finished && !keep
+    {
+      self.running_preview = None;
+      // Recompute normal preview for current selection
+      self.refresh_preview();
+    }
+    changed
+  }
+
   pub fn start_preview_process(
     &mut self,
     cmd: &str,
   )
+  {
+    let command = shell::command(&self.config.command_shell, cmd);
+    self.stream_into_preview(command, false);
+  }
+
+  /// Run `cmd` with the selected files (or the current one) as its
+  /// arguments and placeholders filled in, streaming its output into the
+  /// preview, as `:run` does into the Output panel. The output stays until
+  /// the selection changes.
+  pub(crate) fn start_preview_run(
+    &mut self,
+    cmd: &str,
+  )
+  {
+    let cmd = cmd.trim();
+    if cmd.is_empty()
+    {
+      self.add_warning("Usage: run_preview <command>");
+      return;
+    }
+    let files = self.command_files();
+    let shell = &self.config.command_shell;
+    let expanded = shell::expand(shell, cmd, &self.placeholders(&files));
+    let command = shell::with_args(shell, &expanded, &files);
+    self.stream_into_preview(command, true);
+  }
+
+  fn stream_into_preview(
+    &mut self,
+    mut command: Command,
+    keep: bool,
+  )
   {
     use std::{
-      process::{
-        Command,
-        Stdio,
-      },
+      process::Stdio,
       sync::mpsc,
     };
+    // A command started earlier stops writing into the preview
+    self.stop_preview_process();
     // Reset preview buffer and caches
     self.preview.static_lines.clear();
+    self.preview.content = PreviewContent::Text;
     self.preview.cache_key = None;
     self.preview.cache_lines = None;
     // Channel to stream lines
     let (tx, rx) = mpsc::channel::<Option<String>>();
-    // Build platform shell
-    #[cfg(windows)]
-    let mut command = {
-      let mut c = Command::new("cmd");
-      c.arg("/C").arg(cmd);
-      c
-    };
-    #[cfg(not(windows))]
-    let mut command = {
-      let mut c = Command::new("sh");
-      c.arg("-lc").arg(cmd);
-      c
-    };
-    command.stdout(Stdio::piped()).stderr(Stdio::piped());
+    command
+      .current_dir(&self.cwd)
+      .stdin(Stdio::null())
+      .stdout(Stdio::piped())
+      .stderr(Stdio::piped());
     match command.spawn()
     {
       Ok(mut child) =>
@@ -187,7 +559,14 @@ impl App
           }
           let _ = tx.send(None);
         });
-        self.running_preview = Some(crate::app::RunningPreview { rx });
+        self.running_preview = Some(crate::app::RunningPreview {
+          rx,
+          path: self.selected_entry().map(|e| e.path.clone()),
+          cwd: self.cwd.clone(),
+          child: Some(child),
+          keep,
+          done: false,
+        });
         self.force_full_redraw = true;
       }
       Err(e) =>
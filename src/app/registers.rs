@@ -0,0 +1,198 @@
+//! Named clipboard registers, like vim's `"a`–`"z`.
+//!
+//! `"` followed by a letter picks the register the next copy (`c`), move
+//! (`x`), paste (`v`, `pl`, `ph`) or clear uses, so several sets of files
+//! can be staged at once. Without a register these use the unnamed
+//! clipboard, as before. A copied register is kept after pasting, so it can
+//! be pasted again elsewhere; a move register is emptied once its items
+//! have moved. `zr` (`:registers`) lists the registers and their contents.
+
+use std::collections::BTreeMap;
+
+use crate::app::{
+  App,
+  Clipboard,
+  ClipboardOp,
+  Overlay,
+};
+
+/// Name of the unnamed register in messages and the registers overlay.
+pub const UNNAMED: char = '"';
+
+#[derive(Debug, Default)]
+pub(crate) struct Registers
+{
+  named:              BTreeMap<char, Clipboard>,
+  /// Waiting for the register name typed after `"`.
+  pub(crate) pending: bool,
+  /// Register the next clipboard action uses.
+  active:             Option<char>,
+}
+
+impl App
+{
+  /// `"`: ask for the register the next clipboard action uses.
+  pub(crate) fn register_key(&mut self)
+  {
+    self.registers.pending = true;
+    self.add_message("Register: type a letter (a-z)");
+  }
+
+  /// The key typed after `"`.
+  pub(crate) fn register_chosen(
+    &mut self,
+    ch: char,
+  )
+  {
+    self.registers.pending = false;
+    if ch == UNNAMED
+    {
+      self.registers.active = None;
+    }
+    else if ch.is_ascii_lowercase()
+    {
+      self.registers.active = Some(ch);
+      self.add_message(&format!("Register \"{}", ch));
+    }
+    else
+    {
+      self.add_warning(&format!("Invalid register '{}'", ch));
+    }
+  }
+
+  /// Register picked with `"` for the next clipboard action, if any.
+  pub fn active_register(&self) -> Option<char>
+  {
+    self.registers.active
+  }
+
+  /// Use up the register picked for this clipboard action.
+  pub(crate) fn take_register(&mut self) -> Option<char>
+  {
+    self.registers.active.take()
+  }
+
+  /// Contents of `reg`; `None` (or `"`) is the unnamed clipboard.
+  pub fn register(
+    &self,
+    reg: Option<char>,
+  ) -> Option<&Clipboard>
+  {
+    match reg
+    {
+      None | Some(UNNAMED) => self.clipboard.as_ref(),
+      Some(r) => self.registers.named.get(&r),
+    }
+  }
+
+  pub(crate) fn set_register(
+    &mut self,
+    reg: Option<char>,
+    cb: Option<Clipboard>,
+  )
+  {
+    match (reg, cb)
+    {
+      (None | Some(UNNAMED), cb) => self.clipboard = cb,
+      (Some(r), Some(cb)) =>
+      {
+        self.registers.named.insert(r, cb);
+      }
+      (Some(r), None) =>
+      {
+        self.registers.named.remove(&r);
+      }
+    }
+    self.force_full_redraw = true;
+  }
+
+  /// Forget the register's contents after a paste: the unnamed clipboard
+  /// always, a named register only when its items were moved.
+  pub(crate) fn release_register(
+    &mut self,
+    reg: Option<char>,
+  )
+  {
+    let moved = self.register(reg).is_some_and(|cb| cb.op == ClipboardOp::Move);
+    if reg.is_none() || moved
+    {
+      self.set_register(reg, None);
+    }
+  }
+
+  /// Non-empty registers, the unnamed one first.
+  pub fn register_list(&self) -> Vec<(char, Clipboard)>
+  {
+    let mut out = Vec::new();
+    if let Some(cb) = self.clipboard.as_ref()
+    {
+      out.push((UNNAMED, cb.clone()));
+    }
+    out.extend(self.registers.named.iter().map(|(r, cb)| (*r, cb.clone())));
+    out
+  }
+
+  pub(crate) fn open_registers(&mut self)
+  {
+    self.keys.pending.clear();
+    self.keys.last_at = None;
+    self.overlay = Overlay::Registers { selected: 0 };
+    self.force_full_redraw = true;
+  }
+
+  /// Register under the cursor in the registers overlay.
+  pub fn registers_current(&self) -> Option<char>
+  {
+    match self.overlay
+    {
+      Overlay::Registers { selected } =>
+      {
+        self.register_list().get(selected).map(|(r, _)| *r)
+      }
+      _ => None,
+    }
+  }
+
+  pub(crate) fn registers_move(
+    &mut self,
+    delta: isize,
+  )
+  {
+    let len = self.register_list().len();
+    if let Overlay::Registers { ref mut selected } = self.overlay
+      && len > 0
+    {
+      *selected =
+        (*selected as isize + delta).clamp(0, len as isize - 1) as usize;
+    }
+  }
+
+  /// Close the overlay and paste the register under the cursor here.
+  pub(crate) fn registers_paste(&mut self)
+  {
+    let reg = self.registers_current();
+    self.overlay = Overlay::None;
+    self.force_full_redraw = true;
+    if let Some(reg) = reg
+    {
+      self.registers.active = (reg != UNNAMED).then_some(reg);
+      self.paste_clipboard();
+    }
+  }
+
+  /// Empty the register under the cursor.
+  pub(crate) fn registers_clear(&mut self)
+  {
+    let Some(reg) = self.registers_current()
+    else
+    {
+      return;
+    };
+    self.set_register(Some(reg), None);
+    let len = self.register_list().len();
+    if let Overlay::Registers { ref mut selected } = self.overlay
+    {
+      *selected = (*selected).min(len.saturating_sub(1));
+    }
+  }
+}
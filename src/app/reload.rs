@@ -0,0 +1,185 @@
+//! Reloading the configuration without restarting lsv.
+//!
+//! `:reload_config` runs `init.lua` again in a fresh Lua engine and swaps in
+//! the resulting config, keymaps, previewer and actions. When the new config
+//! fails to load, the running one is kept and the error is shown in the
+//! output panel. With `ui.auto_reload = true` the same happens
+//! whenever `init.lua` is saved (see `watch.rs`).
+//!
+//! A config that fails at startup is reported in the same panel, and lsv
+//! runs with the built-in defaults until it is fixed and reloaded.
+
+use crate::{
+  app::{
+    App,
+    LuaRuntime,
+  },
+  config::{
+    ConfigArtifacts,
+    ConfigError,
+    ConfigPaths,
+  },
+};
+
+/// Title of the output panel showing a config that failed to load.
+const ERROR_TITLE: &str = "Config error";
+/// Closing lines of the error panel.
+pub(crate) const DEFAULT_CONFIG: &str =
+  "Running with the default config. Fix the file and run :reload_config.";
+const KEPT_CONFIG: &str = "The previous config is still in use.";
+
+impl App
+{
+  /// Report a config that failed to load in the error panel and messages.
+  /// `outcome` tells which config lsv goes on with.
+  pub(crate) fn show_config_error(
+    &mut self,
+    error: &str,
+    outcome: &str,
+  )
+  {
+    crate::trace::log(format!("[config] load failed: {}", error));
+    let report = ConfigError::parse(error);
+    self.add_message(&match report.location()
+    {
+      Some(loc) => format!("Config error at {}: {}", loc, report.message),
+      None => format!("Config error: {}", report.message),
+    });
+    let mut lines = report.lines();
+    lines.push(String::new());
+    lines.push(outcome.to_string());
+    self.display_output(ERROR_TITLE, &lines.join("\n"));
+    self.config_error = Some(report);
+  }
+
+  /// The last config load failure, until a config loads cleanly.
+  pub fn config_error(&self) -> Option<&ConfigError>
+  {
+    self.config_error.as_ref()
+  }
+
+  /// Fall back to the built-in config and keymaps, without Lua.
+  pub(crate) fn use_default_config(&mut self)
+  {
+    let mut cfg = crate::config::Config::default();
+    crate::config::defaults::apply_config_defaults(&mut cfg);
+    self.keys.maps = crate::config::defaults::finish_keymaps(
+      crate::config::defaults::rust_default_keymaps(),
+      &mut cfg.keys,
+    );
+    self.config = cfg;
+    self.rebuild_keymap_lookup();
+    self.lua = None;
+  }
+
+  /// Install a freshly loaded config, its keymaps and Lua runtime.
+  pub(crate) fn apply_config_artifacts(
+    &mut self,
+    (cfg, maps, engine_opt): ConfigArtifacts,
+  )
+  {
+    self.config = cfg;
+    // Previewers may have changed
+    self.reset_preview_cache();
+    for p in self.config.plugins.clone()
+    {
+      if let Some(e) = p.error
+      {
+        self.add_message(&format!("Plugin {}: {}", p.name, e));
+      }
+    }
+    self.keys.maps = maps;
+    self.rebuild_keymap_lookup();
+    self.lua = engine_opt.map(|(engine, previewers, actions)| LuaRuntime {
+      engine,
+      previewers,
+      actions,
+      dialog: None,
+    });
+    // Apply optional initial sort/show from config.ui before the next
+    // listing so it honors them (e.g., show_hidden)
+    if let Some(ref srt) = self.config.ui.sort
+      && let Some(k) = crate::enums::sort_key_from_str(srt)
+    {
+      self.sort_key = k;
+    }
+    if let Some(b) = self.config.ui.sort_reverse
+    {
+      self.sort_reverse = b;
+    }
+    if let Some(ref sh) = self.config.ui.show
+    {
+      if sh.eq_ignore_ascii_case("none")
+      {
+        self.info_mode = crate::app::InfoMode::None;
+      }
+      else if let Some(m) = crate::enums::info_mode_from_str(sh)
+      {
+        self.info_mode = m;
+      }
+    }
+    if let Some(dm) = self.config.ui.display_mode.as_deref()
+      && let Some(mode) = crate::enums::display_mode_from_str(dm)
+    {
+      self.display_mode = mode;
+    }
+  }
+
+  /// Load the configuration again from where it was found at startup.
+  pub fn reload_config(&mut self) -> bool
+  {
+    let paths = match self.config_paths.clone()
+    {
+      Some(p) => Ok(p),
+      None => crate::config::discover_config_paths(),
+    };
+    match paths
+    {
+      Ok(paths) => self.reload_config_from(paths),
+      Err(e) =>
+      {
+        self.show_config_error(&e.to_string(), KEPT_CONFIG);
+        false
+      }
+    }
+  }
+
+  /// Load the configuration from `paths` and apply it. Returns false and
+  /// shows the error, keeping the current config, when loading fails.
+  pub fn reload_config_from(
+    &mut self,
+    paths: ConfigPaths,
+  ) -> bool
+  {
+    let loaded = crate::config::load_config(&paths);
+    self.config_paths = Some(paths);
+    match loaded
+    {
+      Ok(artifacts) =>
+      {
+        self.apply_config_artifacts(artifacts);
+        self.config_error = None;
+        self.init_image_protocol();
+        // Column values and previews came from the old Lua functions
+        self.invalidate_columns(&[String::from("*")]);
+        self.preview.cache_key = None;
+        self.preview.cache_lines = None;
+        if let crate::app::Overlay::Output { ref title, .. } = self.overlay
+          && title == ERROR_TITLE
+        {
+          self.overlay = crate::app::Overlay::None;
+        }
+        self.refresh_lists();
+        self.refresh_preview();
+        self.force_full_redraw = true;
+        self.add_message("Config reloaded");
+        true
+      }
+      Err(e) =>
+      {
+        self.show_config_error(&e.to_string(), KEPT_CONFIG);
+        false
+      }
+    }
+  }
+}
@@ -0,0 +1,35 @@
+//! Remote (`sftp://`) locations for App.
+//!
+//! `:cd sftp://[user@]host[:port]/path` browses a directory on another
+//! host. Listing, previews, renaming, deleting and pasting to or from local
+//! directories work there; actions that need the files on disk (opening,
+//! trashing, checksums, archives, searches) are refused.
+
+use crate::{
+  app::App,
+  core::remote,
+};
+
+impl App
+{
+  /// Whether the current directory is on a remote host.
+  pub fn in_remote(&self) -> bool
+  {
+    remote::is_remote(&self.cwd)
+  }
+
+  /// Refuse an action that only works on local files. Returns true (and
+  /// posts a message) when the action must not run.
+  pub(crate) fn refuse_remote(
+    &mut self,
+    what: &str,
+  ) -> bool
+  {
+    if !self.in_remote()
+    {
+      return false;
+    }
+    self.add_message(&format!("{}: not available on remote hosts", what));
+    true
+  }
+}
@@ -0,0 +1,228 @@
+//! Bulk rename through an external editor for App.
+//!
+//! The names of the items are written to a temporary file, one per line, and
+//! the user's editor is run on it with the TUI suspended. When the editor
+//! exits successfully the edited lines are applied as renames via
+//! [`crate::core::rename`], after a confirmation listing them when there is
+//! more than one.
+
+use std::{
+  io,
+  path::PathBuf,
+};
+
+use crate::{
+  app::{
+    App,
+    interactive::with_terminal,
+  },
+  config::CommandShell,
+  core::{
+    rename,
+    shell,
+  },
+};
+
+/// `$VISUAL`, then `$EDITOR`, then a platform default.
+pub(crate) fn editor_command() -> String
+{
+  ["VISUAL", "EDITOR"]
+    .iter()
+    .filter_map(|v| std::env::var(v).ok())
+    .find(|s| !s.trim().is_empty())
+    .unwrap_or_else(|| {
+      if cfg!(windows) { "notepad".to_string() } else { "vi".to_string() }
+    })
+}
+
+/// Run `editor` on `file` through `sh`, giving it the terminal for the
+/// duration.
+fn run_editor(
+  sh: &CommandShell,
+  editor: &str,
+  file: &std::path::Path,
+) -> io::Result<std::process::ExitStatus>
+{
+  // The editor setting may carry arguments, so let the shell split it
+  let line =
+    format!("{} {}", editor, shell::quote(sh, &file.to_string_lossy()));
+  with_terminal(|| shell::command(sh, &line).status())
+}
+
+impl App
+{
+  /// Items a bulk rename applies to: the selection, or the current entry.
+  fn rename_targets(&self) -> Vec<PathBuf>
+  {
+    let mut items: Vec<PathBuf> = self.selected.iter().cloned().collect();
+    if items.is_empty()
+    {
+      items.extend(self.selected_entry().map(|e| e.path.clone()));
+    }
+    items.sort();
+    items
+  }
+
+  /// Rename the selected items (or the current one) by editing their names
+  /// in `$EDITOR`.
+  pub(crate) fn open_rename_editor(&mut self)
+  {
+    let items = self.rename_targets();
+    self.rename_in_editor(items);
+  }
+
+  /// Write the names of `items` to a temporary file, open it in the editor
+  /// and apply the edited names once it exits.
+  pub(crate) fn rename_in_editor(
+    &mut self,
+    items: Vec<PathBuf>,
+  )
+  {
+    if self.refuse_in_archive("Rename") || self.refuse_remote("Rename")
+    {
+      return;
+    }
+    if items.is_empty()
+    {
+      self.add_message("Rename: no selection");
+      return;
+    }
+    let dir = std::env::temp_dir();
+    let file = match rename::create_list_file(&dir, &rename::names_text(&items))
+    {
+      Ok(file) => file,
+      Err(e) =>
+      {
+        self.add_message(&format!(
+          "Rename: cannot write a list in {}: {}",
+          dir.display(),
+          e
+        ));
+        return;
+      }
+    };
+    let editor = editor_command();
+    let status = run_editor(&self.config.command_shell, &editor, &file);
+    self.force_full_redraw = true;
+    let text = std::fs::read_to_string(&file);
+    let _ = std::fs::remove_file(&file);
+    match status
+    {
+      Ok(s) if s.success() =>
+      {}
+      Ok(s) =>
+      {
+        self.add_message(&format!(
+          "Rename: {} exited with {}; nothing renamed",
+          editor, s
+        ));
+        return;
+      }
+      Err(e) =>
+      {
+        self.add_error(&format!("Rename: cannot run {}: {}", editor, e));
+        return;
+      }
+    }
+    match text
+    {
+      Ok(text) => self.confirm_rename_list(&items, &text),
+      Err(e) => self.add_error(&format!("Rename: {}", e)),
+    }
+  }
+
+  /// Rename `items` to the names on the matching lines of `text`, as edited
+  /// in the editor. Nothing is renamed when the list has collisions.
+  pub fn rename_from_list(
+    &mut self,
+    items: &[PathBuf],
+    text: &str,
+  )
+  {
+    if let Some(plan) = self.rename_plan(items, text)
+    {
+      self.apply_rename_plan(&plan);
+    }
+  }
+
+  /// Like [`App::rename_from_list`], but several renames are listed for
+  /// confirmation before any is made.
+  pub fn confirm_rename_list(
+    &mut self,
+    items: &[PathBuf],
+    text: &str,
+  )
+  {
+    let Some(plan) = self.rename_plan(items, text)
+    else
+    {
+      return;
+    };
+    if plan.len() == 1
+    {
+      self.apply_rename_plan(&plan);
+      return;
+    }
+    let question = format!("Rename {} items? (y/n)", plan.len());
+    let details = plan
+      .iter()
+      .map(|(from, to)| crate::app::confirm::rename_line(from, to))
+      .collect();
+    self.open_batch_confirm(
+      "Confirm Rename",
+      question,
+      details,
+      crate::app::ConfirmKind::RenameBatch(plan),
+    );
+  }
+
+  /// Renames the edited `text` asks for, or `None` (with a message) when
+  /// there are none or the list has collisions.
+  fn rename_plan(
+    &mut self,
+    items: &[PathBuf],
+    text: &str,
+  ) -> Option<Vec<(PathBuf, PathBuf)>>
+  {
+    match rename::plan_renames(items, text)
+    {
+      Ok(plan) if plan.is_empty() =>
+      {
+        self.add_message("Rename: no names changed");
+        None
+      }
+      Ok(plan) => Some(plan),
+      Err(e) =>
+      {
+        self.add_error(&format!("Rename: {}; nothing renamed", e));
+        None
+      }
+    }
+  }
+
+  pub(crate) fn apply_rename_plan(
+    &mut self,
+    plan: &[(PathBuf, PathBuf)],
+  )
+  {
+    let (done, errors) = rename::apply_renames(plan);
+    // Drop all old paths before adding new ones so swaps stay selected
+    let moved: Vec<PathBuf> = done
+      .iter()
+      .filter(|(from, _)| self.selected.remove(from))
+      .map(|(_, to)| to.clone())
+      .collect();
+    self.selected.extend(moved);
+    for e in &errors
+    {
+      self.add_error(&format!("Rename: {}", e));
+    }
+    self.add_message(&format!(
+      "Renamed {} of {} items",
+      done.len(),
+      plan.len()
+    ));
+    self.refresh_lists();
+    self.refresh_preview();
+  }
+}
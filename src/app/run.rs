@@ -0,0 +1,358 @@
+//! Commands whose output streams into the Output panel.
+//!
+//! `:run <cmd>` runs `cmd` through the shell with the selected files (or the
+//! current one) as its arguments, like `:run_interactive`, but lsv keeps the
+//! terminal: stdout and stderr are read on background threads and show up in
+//! the Output panel as lines arrive. The panel title says whether the
+//! command is still running and how it ended; `Ctrl-c` in the panel, or
+//! `:run_cancel`, kills it. One command runs at a time, and `:output` brings
+//! back the last one's output.
+
+use std::{
+  io::{
+    BufRead,
+    BufReader,
+    Read,
+  },
+  process::{
+    Child,
+    Stdio,
+  },
+  sync::mpsc,
+  time::Duration,
+};
+
+use crate::{
+  app::{
+    App,
+    MessageLevel,
+    Overlay,
+  },
+  config::CommandMode,
+  core::shell,
+};
+
+/// Lines kept from one command; older ones are dropped.
+const MAX_RUN_LINES: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunStatus
+{
+  Running,
+  /// Exit code, if the command was not ended by a signal.
+  Exited(Option<i32>),
+  Cancelled,
+}
+
+/// A command started with `:run` and the output it has produced so far.
+pub struct CommandRun
+{
+  pub title:  String,
+  pub lines:  Vec<String>,
+  pub status: RunStatus,
+  child:      Option<Child>,
+  rx:         Option<mpsc::Receiver<String>>,
+}
+
+impl Drop for CommandRun
+{
+  fn drop(&mut self)
+  {
+    if let Some(mut child) = self.child.take()
+    {
+      let _ = child.kill();
+      let _ = child.wait();
+    }
+  }
+}
+
+/// Send each line read from `pipe` until it closes.
+fn forward_lines(
+  pipe: impl Read + Send + 'static,
+  tx: mpsc::Sender<String>,
+)
+{
+  std::thread::spawn(move || {
+    let mut reader = BufReader::new(pipe);
+    let mut buf = Vec::new();
+    while let Ok(n) = reader.read_until(b'\n', &mut buf)
+    {
+      if n == 0
+      {
+        break;
+      }
+      let line = String::from_utf8_lossy(&buf);
+      let line = line.trim_end_matches(['\n', '\r']).replace('\r', "");
+      if tx.send(line).is_err()
+      {
+        break;
+      }
+      buf.clear();
+    }
+  });
+}
+
+impl App
+{
+  /// Run the shell command `lsv.map_command` bound as number `idx`, where
+  /// its mode asks. Returns false when there is no such command.
+  pub(crate) fn run_mapped_command(
+    &mut self,
+    idx: usize,
+  ) -> bool
+  {
+    let Some(mc) = self.config.shell_commands.get(idx).cloned()
+    else
+    {
+      return false;
+    };
+    match mc.mode
+    {
+      CommandMode::Background => self.start_background_run(&mc.cmd),
+      CommandMode::Output => self.start_command_run(&mc.cmd),
+      CommandMode::Preview => self.start_preview_run(&mc.cmd),
+      CommandMode::Terminal =>
+      {
+        self.run_interactive(&mc.cmd);
+      }
+    }
+    true
+  }
+
+  /// Start `cmd` detached from lsv with the selected files (or the current
+  /// one) as its arguments, discarding its output. Only a failure to start
+  /// it is reported.
+  pub(crate) fn start_background_run(
+    &mut self,
+    cmd: &str,
+  )
+  {
+    let cmd = cmd.trim();
+    if cmd.is_empty()
+    {
+      self.add_warning("Usage: run_background <command>");
+      return;
+    }
+    let files = self.command_files();
+    let shell = &self.config.command_shell;
+    let expanded = shell::expand(shell, cmd, &self.placeholders(&files));
+    let spawned = shell::with_args(shell, &expanded, &files)
+      .current_dir(&self.cwd)
+      .stdin(Stdio::null())
+      .stdout(Stdio::null())
+      .stderr(Stdio::null())
+      .spawn();
+    crate::trace::log(format!(
+      "[run] background cwd='{}' cmd='{}'",
+      self.cwd.display(),
+      expanded
+    ));
+    match spawned
+    {
+      Ok(mut child) =>
+      {
+        // Reap it once it exits
+        std::thread::spawn(move || child.wait());
+      }
+      Err(e) => self.add_error(&format!("Cannot run {}: {}", cmd, e)),
+    }
+  }
+
+  /// Start `cmd` with its output streamed into the Output panel, replacing
+  /// the output of the previous command.
+  pub(crate) fn start_command_run(
+    &mut self,
+    cmd: &str,
+  )
+  {
+    let cmd = cmd.trim();
+    if cmd.is_empty()
+    {
+      self.add_warning("Usage: run <command>");
+      return;
+    }
+    if self.is_command_running()
+    {
+      self.add_warning("A command is already running; :run_cancel stops it");
+      return;
+    }
+    let files = self.command_files();
+    let shell = &self.config.command_shell;
+    let expanded = shell::expand(shell, cmd, &self.placeholders(&files));
+    let mut command = shell::with_args(shell, &expanded, &files);
+    command
+      .current_dir(&self.cwd)
+      .stdin(Stdio::null())
+      .stdout(Stdio::piped())
+      .stderr(Stdio::piped());
+    crate::trace::log(format!(
+      "[run] cwd='{}' cmd='{}'",
+      self.cwd.display(),
+      cmd
+    ));
+    let mut child = match command.spawn()
+    {
+      Ok(child) => child,
+      Err(e) =>
+      {
+        self.add_error(&format!("Cannot run {}: {}", cmd, e));
+        return;
+      }
+    };
+    let (tx, rx) = mpsc::channel();
+    if let Some(out) = child.stdout.take()
+    {
+      forward_lines(out, tx.clone());
+    }
+    if let Some(err) = child.stderr.take()
+    {
+      forward_lines(err, tx);
+    }
+    let title = format!("$ {}", cmd);
+    self.command_run = Some(CommandRun {
+      title:  title.clone(),
+      lines:  Vec::new(),
+      status: RunStatus::Running,
+      child:  Some(child),
+      rx:     Some(rx),
+    });
+    self.display_output(&title, "");
+  }
+
+  pub fn is_command_running(&self) -> bool
+  {
+    self.command_run.as_ref().is_some_and(|r| r.status == RunStatus::Running)
+  }
+
+  /// Whether the Output panel shows the output of the last `:run`.
+  fn shows_command_run(&self) -> bool
+  {
+    match (&self.overlay, &self.command_run)
+    {
+      (Overlay::Output { title, .. }, Some(run)) => *title == run.title,
+      _ => false,
+    }
+  }
+
+  /// Append new output of the running command and notice when it exits.
+  /// Returns true when anything changed.
+  pub fn poll_command_run(&mut self) -> bool
+  {
+    let Some(run) = self.command_run.as_mut()
+    else
+    {
+      return false;
+    };
+    let Some(rx) = run.rx.as_ref()
+    else
+    {
+      return false;
+    };
+    let new: Vec<String> = rx.try_iter().collect();
+    let closed = new.is_empty()
+      && matches!(rx.try_recv(), Err(mpsc::TryRecvError::Disconnected));
+    let mut changed = !new.is_empty();
+    run.lines.extend(new.iter().cloned());
+    let excess = run.lines.len().saturating_sub(MAX_RUN_LINES);
+    run.lines.drain(..excess);
+    let mut finished = None;
+    if closed && let Some(child) = run.child.as_mut()
+    {
+      // Output ends when the command exits; wait for its status
+      if let Ok(Some(status)) = child.try_wait()
+      {
+        run.status = RunStatus::Exited(status.code());
+        run.child = None;
+        run.rx = None;
+        finished = Some((run.title.clone(), status.success(), status.code()));
+        changed = true;
+      }
+    }
+    if !new.is_empty()
+      && self.shows_command_run()
+      && let Overlay::Output { ref mut lines, .. } = self.overlay
+    {
+      lines.extend(new);
+      let excess = lines.len().saturating_sub(MAX_RUN_LINES);
+      lines.drain(..excess);
+    }
+    if let Some((title, success, code)) = finished
+    {
+      let (level, what) = match code
+      {
+        _ if success => (MessageLevel::Info, String::from("done")),
+        Some(c) => (MessageLevel::Warn, format!("exited with {}", c)),
+        None => (MessageLevel::Warn, String::from("killed")),
+      };
+      self.notify(level, &format!("{}: {}", title, what));
+      // The command may have changed the directory's contents
+      self.refresh_lists();
+      self.refresh_preview();
+    }
+    changed
+  }
+
+  /// Kill the running command.
+  pub fn cancel_command_run(&mut self)
+  {
+    let Some(run) = self.command_run.as_mut()
+    else
+    {
+      return;
+    };
+    let Some(mut child) = run.child.take()
+    else
+    {
+      return;
+    };
+    let _ = child.kill();
+    let _ = child.wait();
+    run.status = RunStatus::Cancelled;
+    run.rx = None;
+    let title = run.title.clone();
+    self.notify(MessageLevel::Warn, &format!("{}: cancelled", title));
+  }
+
+  /// Show the output of the last `:run` again.
+  pub(crate) fn show_command_run(&mut self) -> bool
+  {
+    let Some(run) = self.command_run.as_ref()
+    else
+    {
+      return false;
+    };
+    let (title, text) = (run.title.clone(), run.lines.join("\n"));
+    self.display_output(&title, &text);
+    true
+  }
+
+  /// Status of the command whose output the Output panel shows.
+  pub fn command_run_status(&self) -> Option<String>
+  {
+    if !self.shows_command_run()
+    {
+      return None;
+    }
+    let run = self.command_run.as_ref()?;
+    Some(match run.status
+    {
+      RunStatus::Running => String::from("running, Ctrl-c cancels"),
+      RunStatus::Exited(Some(0)) => String::from("done"),
+      RunStatus::Exited(Some(c)) => format!("exit {}", c),
+      RunStatus::Exited(None) => String::from("killed"),
+      RunStatus::Cancelled => String::from("cancelled"),
+    })
+  }
+
+  /// Block until the running command has exited.
+  pub fn finish_command_run(&mut self)
+  {
+    while self.is_command_running()
+    {
+      if !self.poll_command_run()
+      {
+        std::thread::sleep(Duration::from_millis(10));
+      }
+    }
+  }
+}
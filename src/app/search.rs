@@ -0,0 +1,108 @@
+//! `/` search over the current pane for App.
+//!
+//! Entry names are matched fuzzily with [`crate::core::fuzzy`]: typing jumps
+//! to the best-scoring entry, and `n`/`N` step through the matches from best
+//! to worst using the last query. Matched characters are highlighted in the
+//! rows while a query is active.
+
+use crate::{
+  app::{
+    App,
+    Overlay,
+  },
+  core::fuzzy::fuzzy_score,
+};
+
+impl App
+{
+  /// Indices of the current entries matching `query`, best first; equal
+  /// scores keep list order.
+  fn search_ranking(
+    &self,
+    query: &str,
+  ) -> Vec<usize>
+  {
+    if query.trim().is_empty()
+    {
+      return Vec::new();
+    }
+    let mut ranked: Vec<(usize, i64)> = self
+      .current_entries
+      .iter()
+      .enumerate()
+      .filter_map(|(i, e)| fuzzy_score(query, &e.name).map(|s| (i, s)))
+      .collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    ranked.into_iter().map(|(i, _)| i).collect()
+  }
+
+  /// Query to highlight: the one being typed, else the last search.
+  pub(crate) fn search_highlight_query(&self) -> Option<&str>
+  {
+    match self.overlay
+    {
+      Overlay::CommandPane(ref st) if st.prompt == "/" =>
+      {
+        Some(st.input.as_str())
+      }
+      _ => self.search_query.as_deref(),
+    }
+    .filter(|q| !q.trim().is_empty())
+  }
+
+  /// Jump to the best match while the query is typed.
+  pub(crate) fn update_search_live(
+    &mut self,
+    q: &str,
+  )
+  {
+    if let Some(&best) = self.search_ranking(q).first()
+      && self.list_state.selected() != Some(best)
+    {
+      self.list_state.select(Some(best));
+      self.refresh_preview();
+    }
+  }
+
+  /// Step through the ranking of the last query, wrapping around.
+  fn search_step(
+    &mut self,
+    forward: bool,
+  )
+  {
+    let Some(q) = self.search_query.clone()
+    else
+    {
+      return;
+    };
+    let ranking = self.search_ranking(&q);
+    if ranking.is_empty()
+    {
+      self.add_message(&format!("Search: no match for {}", q));
+      return;
+    }
+    let len = ranking.len();
+    let here = self
+      .list_state
+      .selected()
+      .and_then(|sel| ranking.iter().position(|&i| i == sel));
+    let next = match (here, forward)
+    {
+      (Some(p), true) => (p + 1) % len,
+      (Some(p), false) => (p + len - 1) % len,
+      (None, _) => 0,
+    };
+    self.list_state.select(Some(ranking[next]));
+    self.refresh_preview();
+  }
+
+  pub(crate) fn search_next(&mut self)
+  {
+    self.search_step(true);
+  }
+
+  pub(crate) fn search_prev(&mut self)
+  {
+    self.search_step(false);
+  }
+}
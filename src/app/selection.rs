@@ -1,11 +1,38 @@
 //! Selection and clipboard operations for App.
+//!
+//! The selection outlives directory changes; the selections overlay (`gv`,
+//! `:selections`) lists every selected path grouped by directory.
 
-use crate::app::{
-  App,
-  Clipboard,
-  ClipboardOp,
+use std::path::PathBuf;
+
+use crate::{
+  app::{
+    App,
+    Clipboard,
+    ClipboardOp,
+    Overlay,
+  },
+  core::{
+    fs_ops::{
+      self,
+      LinkKind,
+    },
+    opener::glob_match,
+    regex::Regex,
+    vfs,
+  },
 };
 
+/// "Paste: clipboard empty", or the same for a named register.
+fn empty_register_message(reg: Option<char>) -> String
+{
+  match reg
+  {
+    Some(r) => format!("Paste: register \"{} is empty", r),
+    None => String::from("Paste: clipboard empty"),
+  }
+}
+
 impl App
 {
   pub(crate) fn toggle_select_current(&mut self)
@@ -23,6 +50,29 @@ impl App
     }
   }
 
+  /// Toggle `n` rows from the cursor down, as `select_toggle` with a count.
+  pub(crate) fn toggle_select_rows(
+    &mut self,
+    n: usize,
+  )
+  {
+    let start = self.list_state.selected().unwrap_or(0);
+    let paths: Vec<_> = self
+      .current_entries
+      .iter()
+      .skip(start)
+      .take(n)
+      .map(|e| e.path.clone())
+      .collect();
+    for p in paths
+    {
+      if !self.selected.remove(&p)
+      {
+        self.selected.insert(p);
+      }
+    }
+  }
+
   pub(crate) fn clear_all_selected(&mut self)
   {
     if !self.selected.is_empty()
@@ -31,115 +81,444 @@ impl App
     }
   }
 
+  /// Select (or with `select = false`, unselect) the entries of the current
+  /// pane whose file name matches `pattern`: a glob, or a regex when
+  /// `regex` is set.
+  pub(crate) fn select_matching(
+    &mut self,
+    pattern: &str,
+    regex: bool,
+    select: bool,
+  )
+  {
+    let verb = if select { "Select" } else { "Unselect" };
+    let pattern = pattern.trim();
+    if pattern.is_empty()
+    {
+      self.add_message(&format!("{}: missing pattern", verb));
+      return;
+    }
+    let re = if regex
+    {
+      match Regex::new(pattern)
+      {
+        Ok(re) => Some(re),
+        Err(e) =>
+        {
+          self.add_message(&format!("{}: bad regex: {}", verb, e));
+          return;
+        }
+      }
+    }
+    else
+    {
+      None
+    };
+    let matcher = |name: &str| match &re
+    {
+      Some(re) => re.try_match(name),
+      None => Some(glob_match(pattern, name)),
+    };
+    let mut matching: Vec<PathBuf> = Vec::new();
+    let mut too_costly = false;
+    for e in &self.current_entries
+    {
+      let name = e.path.file_name().map(|s| s.to_string_lossy());
+      match matcher(name.as_deref().unwrap_or(&e.name))
+      {
+        Some(true) => matching.push(e.path.clone()),
+        Some(false) =>
+        {}
+        None =>
+        {
+          too_costly = true;
+          break;
+        }
+      }
+    }
+    // Give up on the whole pattern rather than select a partial set
+    if too_costly
+    {
+      self.add_message(&format!(
+        "{}: regex takes too long to match, nothing changed",
+        verb
+      ));
+      return;
+    }
+    let mut changed = 0usize;
+    for path in matching
+    {
+      let did = if select
+      {
+        self.selected.insert(path)
+      }
+      else
+      {
+        self.selected.remove(&path)
+      };
+      changed += did as usize;
+    }
+    let done = if select { "Selected" } else { "Unselected" };
+    self.add_message(&format!("{} {} item(s)", done, changed));
+  }
+
+  /// Select every entry of the current pane.
+  pub(crate) fn select_all(&mut self)
+  {
+    let paths: Vec<PathBuf> =
+      self.current_entries.iter().map(|e| e.path.clone()).collect();
+    self.selected.extend(paths);
+  }
+
+  /// Flip the selection state of every entry of the current pane.
+  pub(crate) fn invert_selection(&mut self)
+  {
+    let paths: Vec<PathBuf> =
+      self.current_entries.iter().map(|e| e.path.clone()).collect();
+    for path in paths
+    {
+      if !self.selected.remove(&path)
+      {
+        self.selected.insert(path);
+      }
+    }
+  }
+
   pub(crate) fn copy_selection(&mut self)
   {
-    let items: Vec<std::path::PathBuf> =
+    let mut items: Vec<std::path::PathBuf> =
       self.selected.iter().cloned().collect();
+    items.sort();
+    let reg = self.take_register();
     if items.is_empty()
     {
       self.add_message("Copy: no items selected");
       return;
     }
-    self.clipboard = Some(Clipboard { op: ClipboardOp::Copy, items });
-    self.add_message("Copied selection to clipboard");
-    self.force_full_redraw = true;
+    let count = items.len();
+    self.set_register(reg, Some(Clipboard { op: ClipboardOp::Copy, items }));
+    match reg
+    {
+      Some(r) => self
+        .add_message(&format!("Copied {} item(s) to register \"{}", count, r)),
+      None => self.add_message("Copied selection to clipboard"),
+    }
   }
 
   pub(crate) fn move_selection(&mut self)
   {
-    let items: Vec<std::path::PathBuf> =
+    let mut items: Vec<std::path::PathBuf> =
       self.selected.iter().cloned().collect();
+    items.sort();
+    let reg = self.take_register();
     if items.is_empty()
     {
       self.add_message("Move: no items selected");
       return;
     }
-    self.clipboard = Some(Clipboard { op: ClipboardOp::Move, items });
-    self.add_message("Move selection armed");
-    self.force_full_redraw = true;
+    let count = items.len();
+    self.set_register(reg, Some(Clipboard { op: ClipboardOp::Move, items }));
+    match reg
+    {
+      Some(r) => self.add_message(&format!(
+        "Move of {} item(s) armed in register \"{}",
+        count, r
+      )),
+      None => self.add_message("Move selection armed"),
+    }
   }
 
   pub(crate) fn clear_clipboard(&mut self)
   {
-    self.clipboard = None;
-    self.add_message("Clipboard cleared");
-    self.force_full_redraw = true;
+    let reg = self.take_register();
+    self.set_register(reg, None);
+    match reg
+    {
+      Some(r) => self.add_message(&format!("Register \"{} cleared", r)),
+      None => self.add_message("Clipboard cleared"),
+    }
   }
 
   pub(crate) fn paste_clipboard(&mut self)
   {
-    let Some(cb) = self.clipboard.clone()
+    let reg = self.take_register();
+    if self.refuse_in_archive("Paste")
+    {
+      return;
+    }
+    let Some(cb) = self.register(reg).cloned()
     else
     {
-      self.add_message("Paste: clipboard empty");
+      self.add_message(&empty_register_message(reg));
       return;
     };
     let dest_dir = self.cwd.clone();
-    let mut ok = 0usize;
-    let mut skipped = 0usize;
-    let mut errs = 0usize;
+    let mut items = Vec::new();
     for src in cb.items.iter()
     {
       if matches!(cb.op, ClipboardOp::Move) && dest_dir.starts_with(src)
       {
         self
           .add_message(&format!("Skip (move into subdir): {}", src.display()));
-        skipped += 1;
         continue;
       }
+      items.push(src.clone());
+    }
+    if matches!(cb.op, ClipboardOp::Move)
+    {
+      for p in cb.items.iter()
+      {
+        self.selected.remove(p);
+      }
+    }
+    self.release_register(reg);
+    if items.is_empty()
+    {
+      self.add_message("Paste: nothing to do");
+      return;
+    }
+    self.plan_paste(cb.op, items, dest_dir);
+  }
+
+  /// Copy or move the selected entries (or the current one) into the other
+  /// tab's directory, as a paste there would.
+  pub(crate) fn transfer_to_other_tab(
+    &mut self,
+    op: ClipboardOp,
+  )
+  {
+    let what = match op
+    {
+      ClipboardOp::Copy => "Copy to other tab",
+      ClipboardOp::Move => "Move to other tab",
+    };
+    if self.refuse_in_archive(what)
+    {
+      return;
+    }
+    let Some(dest_dir) = self.other_tab_dir()
+    else
+    {
+      self.add_message(&format!("{}: only one tab is open", what));
+      return;
+    };
+    if vfs::resolve(&dest_dir).is_some()
+    {
+      self.add_message(&format!("{}: archives are read-only", what));
+      return;
+    }
+    if dest_dir == self.cwd
+    {
+      self.add_message(&format!("{}: both tabs show this directory", what));
+      return;
+    }
+    let mut items: Vec<PathBuf> = self.selected.iter().cloned().collect();
+    if items.is_empty()
+    {
+      items.extend(self.selected_entry().map(|e| e.path.clone()));
+    }
+    items.sort();
+    let mut planned = Vec::new();
+    for src in items
+    {
+      if op == ClipboardOp::Move && dest_dir.starts_with(&src)
+      {
+        self
+          .add_message(&format!("Skip (move into subdir): {}", src.display()));
+        continue;
+      }
+      if op == ClipboardOp::Move
+      {
+        self.selected.remove(&src);
+      }
+      planned.push(src);
+    }
+    if planned.is_empty()
+    {
+      self.add_message(&format!("{}: nothing to do", what));
+      return;
+    }
+    self.plan_paste(op, planned, dest_dir);
+  }
+
+  /// Create links in the current directory to the copied items instead of
+  /// copying them. Symlinks are relative when `relative` says so, otherwise
+  /// as `ui.symlinks` is set.
+  pub(crate) fn paste_links(
+    &mut self,
+    kind: LinkKind,
+    relative: Option<bool>,
+  )
+  {
+    let reg = self.take_register();
+    if self.refuse_in_archive("Paste") || self.refuse_remote("Paste link")
+    {
+      return;
+    }
+    let items = match self.register(reg)
+    {
+      Some(cb) if cb.op == ClipboardOp::Copy => cb.items.clone(),
+      Some(_) =>
+      {
+        self.add_message("Paste link: copy items (not move) to link them");
+        return;
+      }
+      None =>
+      {
+        self.add_message(&empty_register_message(reg));
+        return;
+      }
+    };
+    let relative = relative.unwrap_or(self.config.ui.relative_links);
+    let dest_dir = self.cwd.clone();
+    let mut made = 0;
+    for src in &items
+    {
       let Some(name) = src.file_name()
       else
       {
-        skipped += 1;
         continue;
       };
-      let dest_path = dest_dir.join(name);
-      if dest_path.exists()
+      let link = dest_dir.join(name);
+      if link.symlink_metadata().is_ok()
       {
-        self.add_message(&format!("Skip (exists): {}", dest_path.display()));
-        skipped += 1;
+        self.add_message(&format!("Skip (exists): {}", link.display()));
         continue;
       }
-      let res = match cb.op
+      let result = match kind
       {
-        ClipboardOp::Copy =>
-        {
-          crate::core::fs_ops::copy_path_recursive(src, &dest_path)
-        }
-        ClipboardOp::Move =>
+        LinkKind::Symlink =>
         {
-          crate::core::fs_ops::move_path_with_fallback(src, &dest_path)
+          let target = if relative
+          {
+            fs_ops::relative_path(&dest_dir, src)
+          }
+          else
+          {
+            src.clone()
+          };
+          fs_ops::symlink(&target, &link, src.is_dir())
         }
-      };
-      match res
-      {
-        Ok(()) => ok += 1,
-        Err(e) =>
+        LinkKind::Hardlink if src.is_dir() =>
         {
-          errs += 1;
           self.add_message(&format!(
-            "Error: {} -> {}: {}",
-            src.display(),
-            dest_path.display(),
-            e
+            "Skip (directories cannot be hard linked): {}",
+            src.display()
           ));
+          continue;
         }
+        LinkKind::Hardlink => std::fs::hard_link(src, &link),
+      };
+      match result
+      {
+        Ok(()) => made += 1,
+        Err(e) => self.add_message(&format!("Link {}: {}", link.display(), e)),
       }
     }
-    if matches!(cb.op, ClipboardOp::Move)
+    self.release_register(reg);
+    self.add_message(&format!(
+      "Created {} {}",
+      made,
+      match kind
+      {
+        LinkKind::Symlink => "symlink(s)",
+        LinkKind::Hardlink => "hard link(s)",
+      }
+    ));
+    self.refresh_lists();
+    self.force_full_redraw = true;
+  }
+}
+
+impl App
+{
+  pub(crate) fn open_selections(&mut self)
+  {
+    if self.selected.is_empty()
     {
-      for p in cb.items.iter()
+      self.add_message("Selections: nothing selected");
+      return;
+    }
+    self.keys.pending.clear();
+    self.keys.last_at = None;
+    self.overlay = Overlay::Selections { selected: 0 };
+    self.force_full_redraw = true;
+  }
+
+  /// Selected path under the cursor in the selections overlay.
+  pub fn selections_current(&self) -> Option<PathBuf>
+  {
+    match self.overlay
+    {
+      Overlay::Selections { selected } =>
       {
-        self.selected.remove(p);
+        self.get_selected_paths().get(selected).cloned()
       }
+      _ => None,
     }
-    self.clipboard = None;
-    self.refresh_lists();
+  }
+
+  pub(crate) fn selections_move(
+    &mut self,
+    delta: isize,
+  )
+  {
+    let len = self.selected.len();
+    if let Overlay::Selections { ref mut selected } = self.overlay
+      && len > 0
+    {
+      *selected =
+        (*selected as isize + delta).clamp(0, len as isize - 1) as usize;
+    }
+  }
+
+  /// Close the overlay and go to the path under the cursor.
+  pub(crate) fn selections_jump(&mut self)
+  {
+    let path = self.selections_current();
+    self.overlay = Overlay::None;
+    self.force_full_redraw = true;
+    let Some(path) = path
+    else
+    {
+      return;
+    };
+    let (Some(dir), Some(name)) =
+      (path.parent(), path.file_name().and_then(|s| s.to_str()))
+    else
+    {
+      return;
+    };
+    self.set_cwd(dir);
+    self.reselect_when_loaded(name);
     self.refresh_preview();
-    self.add_message(&format!(
-      "Paste: ok={} skipped={} errors={}",
-      ok, skipped, errs
-    ));
+  }
+
+  /// Unselect the path under the cursor, closing the overlay with the last.
+  pub(crate) fn selections_unselect(&mut self)
+  {
+    let Some(path) = self.selections_current()
+    else
+    {
+      return;
+    };
+    self.selected.remove(&path);
+    let len = self.selected.len();
+    if len == 0
+    {
+      self.overlay = Overlay::None;
+    }
+    else if let Overlay::Selections { ref mut selected } = self.overlay
+    {
+      *selected = (*selected).min(len - 1);
+    }
     self.force_full_redraw = true;
   }
+
+  /// Unselect everything and close the overlay.
+  pub(crate) fn selections_clear(&mut self)
+  {
+    self.overlay = Overlay::None;
+    self.clear_all_selected();
+  }
 }
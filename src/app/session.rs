@@ -0,0 +1,110 @@
+//! Session persistence for App.
+//!
+//! On exit the current directory, highlighted entry and view settings are
+//! written to `session` under the config root; the next start restores them
+//! over the configured defaults. `ui.session = false` turns both off.
+
+use std::path::PathBuf;
+
+use crate::{
+  app::App,
+  core::session::{
+    self,
+    Session,
+  },
+};
+
+impl App
+{
+  fn session_path(&self) -> Option<PathBuf>
+  {
+    if !self.config.ui.session
+    {
+      return None;
+    }
+    self.theme_root_dir().map(|root| root.join("session"))
+  }
+
+  /// Snapshot of the state saved between runs.
+  pub fn session_state(&self) -> Session
+  {
+    Session {
+      cwd:          Some(self.cwd.clone()),
+      selected:     self.selected_entry().map(|e| e.name.clone()),
+      sort_key:     Some(self.sort_key),
+      sort_reverse: Some(self.sort_reverse),
+      info_mode:    Some(self.info_mode),
+      display_mode: Some(self.display_mode),
+      show_hidden:  Some(self.config.ui.show_hidden),
+    }
+  }
+
+  /// Apply a saved session. The directory (and the entry highlighted in it)
+  /// is only restored when `restore_cwd` is set and it still exists.
+  pub fn apply_session(
+    &mut self,
+    s: &Session,
+    restore_cwd: bool,
+  )
+  {
+    if let Some(k) = s.sort_key
+    {
+      self.sort_key = k;
+    }
+    if let Some(b) = s.sort_reverse
+    {
+      self.sort_reverse = b;
+    }
+    if let Some(m) = s.info_mode
+    {
+      self.info_mode = m;
+    }
+    if let Some(d) = s.display_mode
+    {
+      self.display_mode = d;
+    }
+    if let Some(b) = s.show_hidden
+    {
+      self.config.ui.show_hidden = b;
+    }
+    match s.cwd.as_ref().filter(|p| restore_cwd && p.is_dir())
+    {
+      Some(dir) =>
+      {
+        let dir = dir.clone();
+        self.set_cwd(&dir);
+        if let Some(name) = s.selected.as_deref()
+        {
+          self.reselect_when_loaded(name);
+        }
+      }
+      None => self.refresh_lists(),
+    }
+    self.refresh_preview();
+  }
+
+  /// Restore the session saved by the previous run, if any.
+  pub fn restore_session(
+    &mut self,
+    restore_cwd: bool,
+  )
+  {
+    if let Some(path) = self.session_path()
+    {
+      let s = session::load_session(&path);
+      if s != Session::default()
+      {
+        self.apply_session(&s, restore_cwd);
+      }
+    }
+  }
+
+  /// Save the session for the next run.
+  pub fn save_session(&self)
+  {
+    if let Some(path) = self.session_path()
+    {
+      let _ = session::save_session(&path, &self.session_state());
+    }
+  }
+}
@@ -1,5 +1,7 @@
 use std::{
+  cell::RefCell,
   path::PathBuf,
+  rc::Rc,
   time::SystemTime,
 };
 
@@ -17,13 +19,16 @@ pub struct DirEntryInfo
   pub(crate) size:   u64,
   pub(crate) mtime:  Option<SystemTime>,
   pub(crate) ctime:  Option<SystemTime>,
+  /// Mode, owner and link count, read with the size and times.
+  pub(crate) meta:   Option<crate::app::meta_cache::EntryMeta>,
 }
 
 #[derive(Debug, Clone)]
 pub struct ThemePickerEntry
 {
   pub name:  String,
-  pub path:  PathBuf,
+  /// Theme file, or `None` for a built-in theme.
+  pub path:  Option<PathBuf>,
   pub theme: crate::config::UiTheme,
 }
 
@@ -36,6 +41,116 @@ pub struct ThemePickerState
   pub original_theme_path: Option<PathBuf>,
 }
 
+#[derive(Debug, Clone)]
+pub struct TrashBrowserState
+{
+  pub location: crate::core::trash::TrashLocation,
+  pub entries:  Vec<crate::core::trash::TrashedItem>,
+  pub selected: usize,
+}
+
+/// The `:mounts` picker.
+#[derive(Debug, Clone)]
+pub struct MountsState
+{
+  pub entries:  Vec<crate::core::mounts::MountPoint>,
+  pub selected: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct GrepMatch
+{
+  pub path: PathBuf,
+  pub line: u64,
+  pub text: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct GrepState
+{
+  pub pattern:   String,
+  pub root:      PathBuf,
+  pub matches:   Vec<GrepMatch>,
+  pub selected:  usize,
+  pub running:   bool,
+  pub truncated: bool,
+}
+
+/// What the finder overlay lists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FinderKind
+{
+  /// Files below `root`, ranked by fuzzy score.
+  Files,
+  /// Visited directories (`:z`), ranked by frecency.
+  Dirs,
+}
+
+#[derive(Debug, Clone)]
+pub struct FinderState
+{
+  pub kind:      FinderKind,
+  pub root:      PathBuf,
+  pub input:     String,
+  /// Indexed files relative to `root`, using `/` separators; for
+  /// [`FinderKind::Dirs`], absolute directories best first.
+  pub files:     Vec<String>,
+  /// Indices into `files` matching `input`, best first.
+  pub results:   Vec<usize>,
+  pub selected:  usize,
+  pub indexing:  bool,
+  pub truncated: bool,
+}
+
+/// One action offered by the command palette.
+#[derive(Debug, Clone)]
+pub struct PaletteEntry
+{
+  pub label:  String,
+  /// Keys bound to the action, as typed in `lsv.mapkey`.
+  pub keys:   Vec<String>,
+  /// Action dispatched on accept, e.g. `cmd:find_files`.
+  pub action: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct PaletteState
+{
+  pub input:    String,
+  pub entries:  Vec<PaletteEntry>,
+  /// Indices into `entries` matching `input`, best first.
+  pub results:  Vec<usize>,
+  pub selected: usize,
+}
+
+/// Scroll and search state shared by the messages and output overlays.
+#[derive(Debug, Clone, Default)]
+pub struct TextView
+{
+  /// First line shown; `None` keeps the last lines in view as more arrive.
+  pub scroll:      Option<usize>,
+  /// Rows shown at the last draw, for paging.
+  pub page_height: usize,
+  /// Query being typed after `/`.
+  pub input:       Option<String>,
+  /// Last query searched for.
+  pub query:       String,
+  /// Line of the match last jumped to.
+  pub current:     Option<usize>,
+  /// `g` typed, waiting for a second `g`.
+  pub pending_g:   bool,
+}
+
+/// Generated `:help` text and how far it is scrolled.
+#[derive(Debug, Clone)]
+pub struct HelpState
+{
+  pub lines:       Vec<String>,
+  pub scroll:      usize,
+  /// Rows shown at the last draw, for paging.
+  pub page_height: usize,
+}
+
 #[derive(Debug, Clone)]
 pub enum Overlay
 {
@@ -51,17 +166,80 @@ pub enum Overlay
     lines: Vec<String>,
   },
   ThemePicker(Box<ThemePickerState>),
+  Trash(Box<TrashBrowserState>),
+  Mounts(Box<MountsState>),
+  Grep(Box<GrepState>),
+  Finder(Box<FinderState>),
+  Palette(Box<PaletteState>),
+  Help(Box<HelpState>),
+  Jobs
+  {
+    selected: usize,
+  },
+  Bookmarks
+  {
+    selected: usize,
+  },
+  /// Every selected path, by directory; `selected` indexes the sorted paths.
+  Selections
+  {
+    selected: usize,
+  },
+  /// Clipboard registers; `selected` indexes [`App::register_list`].
+  Registers
+  {
+    selected: usize,
+  },
   Prompt(Box<PromptState>),
   Confirm(Box<ConfirmState>),
+  /// A list to choose from, opened by `lsv.ui.select`.
+  Select(Box<SelectState>),
   CommandPane(Box<CommandPaneState>),
 }
 
+/// What the built-in (non-previewer) preview shows for a file.
+#[derive(Debug, Clone, Default)]
+pub enum PreviewContent
+{
+  /// Plain text held in `PreviewState::static_lines`.
+  #[default]
+  Text,
+  /// Members of a zip/tar/7z archive.
+  ArchiveListing(Box<crate::core::archive::ArchiveListing>),
+  /// A video or PDF drawn from its cached thumbnail in the background;
+  /// `static_lines` hold the notice shown when that fails.
+  Thumbnail(crate::core::thumbnail::ThumbnailKind),
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct PreviewState
 {
   pub static_lines: Vec<String>,
+  pub content:      PreviewContent,
   pub cache_key:    Option<(std::path::PathBuf, u16, u16)>,
   pub cache_lines:  Option<Vec<String>>,
+  /// Recent previewer outputs, so returning to a file (an image previewer
+  /// in particular) doesn't run the command again; sized by
+  /// `ui.preview_cache` and `ui.preview_ttl`.
+  pub recent:       crate::core::lru::Lru<PreviewStamp, Option<Vec<String>>>,
+  /// First preview line shown; reset when the previewed path changes.
+  pub offset:       usize,
+  pub offset_path:  Option<std::path::PathBuf>,
+  /// Line count and visible height from the last draw, used to clamp
+  /// scrolling and size page steps.
+  pub total_lines:  usize,
+  pub page_height:  usize,
+  /// Long lines are cut instead of wrapped, and scrolled sideways by
+  /// `hscroll` columns; the offset is reset with `offset`.
+  pub nowrap:       bool,
+  pub hscroll:      usize,
+  /// Widest line and visible width from the last draw, used to clamp
+  /// horizontal scrolling.
+  pub line_width:   usize,
+  pub page_width:   usize,
+  /// Preview focus mode: h/j/k/l scroll the preview instead of moving
+  /// the cursor.
+  pub focused:      bool,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -76,9 +254,23 @@ pub struct KeyState
 
 pub struct LuaRuntime
 {
-  pub engine:    crate::config::LuaEngine,
-  pub previewer: Option<RegistryKey>,
-  pub actions:   Vec<RegistryKey>,
+  pub engine:     crate::config::LuaEngine,
+  /// Previewers in the order they are asked for a command.
+  pub previewers: Vec<RegistryKey>,
+  pub actions:    Vec<RegistryKey>,
+  /// Callback of the `lsv.ui` dialog that is open, if any.
+  pub dialog:     Option<LuaDialog>,
+}
+
+/// A Lua callback waiting for the answer to an `lsv.ui` dialog, with the
+/// `lsv` and `config` tables of the action that opened it and what its
+/// `lsv.fs` helpers queue.
+pub struct LuaDialog
+{
+  pub callback: RegistryKey,
+  pub lsv:      RegistryKey,
+  pub config:   RegistryKey,
+  pub fs:       Rc<RefCell<crate::actions::effects::FsRequests>>,
 }
 
 #[derive(Debug, Clone)]
@@ -95,6 +287,21 @@ pub enum PromptKind
     pre:   String,
     suf:   String,
   },
+  AddMark,
+  RenameMark
+  {
+    from: String,
+  },
+  SetPermissions
+  {
+    items: Vec<std::path::PathBuf>,
+  },
+  Compress
+  {
+    items: Vec<std::path::PathBuf>,
+  },
+  /// Answered to the Lua callback of `lsv.ui.prompt`.
+  Lua,
 }
 
 #[derive(Debug, Clone)]
@@ -124,6 +331,32 @@ pub struct Clipboard
 pub enum ConfirmKind
 {
   DeleteSelected(Vec<std::path::PathBuf>),
+  TrashSelected(Vec<std::path::PathBuf>),
+  PasteConflict(Box<PasteConflicts>),
+  /// A settled paste that replaces several existing items.
+  PasteOverwrite(Box<PasteConflicts>),
+  /// Bulk renames, as (from, to).
+  RenameBatch(Vec<(std::path::PathBuf, std::path::PathBuf)>),
+  /// Answered to the Lua callback of `lsv.ui.confirm`.
+  Lua,
+}
+
+/// A paste waiting on answers about names that already exist.
+#[derive(Debug, Clone)]
+pub struct PasteConflicts
+{
+  pub op:           ClipboardOp,
+  pub dest_dir:     std::path::PathBuf,
+  /// Items whose destination is settled.
+  pub planned:      Vec<(std::path::PathBuf, crate::app::PasteTarget)>,
+  /// Clashing items still to ask about, the first being asked now.
+  pub pending:      Vec<std::path::PathBuf>,
+  /// How many clashes there were, for the "1 of 3" title.
+  pub total:        usize,
+  /// Names in a remote `dest_dir`, which cannot be looked up on disk.
+  pub remote_names: Option<std::collections::HashSet<String>>,
+  /// Destinations already given to items of this paste.
+  pub claimed:      std::collections::HashSet<std::path::PathBuf>,
 }
 
 #[derive(Debug, Clone)]
@@ -133,6 +366,23 @@ pub struct ConfirmState
   pub question:    String,
   pub default_yes: bool,
   pub kind:        ConfirmKind,
+  /// Paths (or renames) the question is about, listed below it.
+  pub details:     Vec<String>,
+  /// First line of `details` shown.
+  pub scroll:      usize,
+  /// Rows of `details` that fit, recorded when drawn.
+  pub page_height: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct SelectState
+{
+  pub title:    String,
+  pub input:    String,
+  pub items:    Vec<String>,
+  /// Indices into `items` matching `input`, best first.
+  pub results:  Vec<usize>,
+  pub selected: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -142,6 +392,9 @@ pub struct CommandPaneState
   pub input:            String,
   pub cursor:           usize,
   pub show_suggestions: bool,
+  pub history:          crate::app::history::HistoryNav,
+  /// Path completions listed in a popup after an ambiguous `Tab`.
+  pub candidates:       Vec<String>,
 }
 
 /// Mutable application state driving the three-pane UI.
@@ -152,8 +405,12 @@ pub struct App
   pub(crate) parent_entries:    Vec<DirEntryInfo>,
   pub(crate) list_state:        ListState,
   pub(crate) preview:           PreviewState,
-  pub(crate) recent_messages:   Vec<String>,
+  pub(crate) recent_messages:   Vec<crate::app::Message>,
   pub(crate) overlay:           Overlay,
+  /// Scroll and search state of the messages or output overlay.
+  pub(crate) text_view:         TextView,
+  /// Notes in the bottom-right corner, oldest first.
+  pub(crate) notifications:     Vec<crate::app::Notification>,
   pub(crate) config:            crate::config::Config,
   pub(crate) keys:              KeyState,
   pub(crate) force_full_redraw: bool,
@@ -167,15 +424,176 @@ pub struct App
   pub(crate) should_quit:       bool,
   pub(crate) search_query:      Option<String>,
   pub(crate) _search_locked:    bool,
-  pub(crate) marks: std::collections::HashMap<char, std::path::PathBuf>,
+  /// `:filter` patterns, per directory.
+  pub(crate) filters:           std::collections::HashMap<PathBuf, String>,
+  /// Entry focused in each directory when it was last left, per session.
+  pub(crate) cursor_memory:     std::collections::HashMap<PathBuf, String>,
+  pub(crate) marks: std::collections::HashMap<String, std::path::PathBuf>,
   pub(crate) pending_mark:      bool,
   pub(crate) pending_goto:      bool,
+  /// Digits typed before a motion, as in `5j`.
+  pub(crate) pending_count:     Option<usize>,
+  /// Count of the key action running now, passed to Lua as `context.count`.
+  pub(crate) action_count:      Option<usize>,
+  pub(crate) macros:            crate::app::macros::Macros,
+  pub(crate) registers:         crate::app::registers::Registers,
   pub(crate) running_preview:   Option<RunningPreview>,
+  pub(crate) pending_preview:   Option<PendingPreview>,
+  pub(crate) pending_archive:   Option<PendingArchive>,
+  pub(crate) running_grep:      Option<RunningGrep>,
+  /// Last command started with `:run`, running or finished.
+  pub(crate) command_run:       Option<crate::app::run::CommandRun>,
+  pub(crate) finder_index:      Option<FinderIndex>,
+  pub(crate) current_load:      Option<DirLoad>,
+  pub(crate) parent_load:       Option<DirLoad>,
+  pub(crate) tabs:              Vec<TabState>,
+  pub(crate) active_tab:        usize,
+  /// The tab switched away from last, the target of `copy_to_other`.
+  pub(crate) last_tab:          Option<usize>,
+  pub(crate) jobs:              crate::app::jobs::JobQueue,
+  pub(crate) watch:             crate::app::watch::WatchState,
+  pub(crate) checksums:         crate::app::checksums::ChecksumState,
+  pub(crate) dir_sizes:         crate::app::dir_sizes::DirSizeState,
+  pub(crate) disk_space:        crate::app::disk_space::DiskSpaceState,
+  pub(crate) pane_areas:        PaneAreas,
+  /// Row and time of the last left click, for double-click detection.
+  pub(crate) last_click:        Option<(std::time::Instant, usize)>,
+  pub(crate) flatten:           Option<crate::app::flatten::FlattenView>,
+  pub(crate) tree:              crate::app::tree::TreeState,
+  /// Set when lsv runs as a file picker.
+  pub(crate) pick_mode:         Option<crate::app::PickMode>,
+  pub(crate) picked:            Vec<PathBuf>,
+  pub(crate) history:           crate::app::history::CommandHistory,
+  pub(crate) frecency:          crate::app::jump::Frecency,
+  pub(crate) events:            crate::app::events::EventState,
+  /// Lua `:` command being run, for `config.context`.
+  pub(crate) lua_command: Option<crate::app::lua_commands::LuaCommandCall>,
+  pub(crate) columns:           crate::app::columns::ColumnCache,
+  pub(crate) template_fns:      crate::app::template_fns::TemplateFnCache,
+  pub(crate) meta_cache:        crate::app::meta_cache::MetaCache,
+  /// Graphics protocol handed to previewers as `ctx.image_protocol`.
+  pub(crate) image_protocol:    crate::core::image_protocol::ImageProtocol,
+  /// Where the configuration was loaded from, for `:reload_config`.
+  pub(crate) config_paths:      Option<crate::config::ConfigPaths>,
+  pub(crate) config_error:      Option<crate::config::ConfigError>,
+}
+
+/// Screen areas of the three panes as last drawn, for mouse hit testing.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PaneAreas
+{
+  pub parent:  ratatui::layout::Rect,
+  pub current: ratatui::layout::Rect,
+  pub preview: ratatui::layout::Rect,
 }
 
+/// Saved view of a tab. The active tab's live state is held on [`App`]
+/// itself; its slot here is refreshed whenever another tab is activated.
+#[derive(Debug, Clone)]
+pub struct TabState
+{
+  pub cwd:           PathBuf,
+  pub list_state:    ListState,
+  pub selected_name: Option<String>,
+  pub selected:      std::collections::HashSet<std::path::PathBuf>,
+  pub sort_key:      crate::actions::SortKey,
+  pub sort_reverse:  bool,
+}
+
+/// Command whose output streams into the preview; killed once the selection
+/// or directory it was started for changes.
 pub struct RunningPreview
 {
-  pub rx: std::sync::mpsc::Receiver<Option<String>>,
+  pub rx:           std::sync::mpsc::Receiver<Option<String>>,
+  /// Selected entry and directory when the command started.
+  pub path:         Option<std::path::PathBuf>,
+  pub cwd:          std::path::PathBuf,
+  pub(crate) child: Option<std::process::Child>,
+  /// Leave the output up once the command ends, until the selection
+  /// changes, rather than going back to the normal preview.
+  pub(crate) keep:  bool,
+  /// The command has ended and its output is kept.
+  pub(crate) done:  bool,
+}
+
+impl Drop for RunningPreview
+{
+  fn drop(&mut self)
+  {
+    if let Some(mut child) = self.child.take()
+    {
+      let _ = child.kill();
+      let _ = child.wait();
+    }
+  }
+}
+
+/// Previewer command running in the background for the preview cache `key`;
+/// the result arrives once on `rx` (`None` when the command failed).
+pub struct PendingPreview
+{
+  pub key:   (std::path::PathBuf, u16, u16),
+  /// Modification time and size of the file when the previewer started.
+  pub mtime: Option<std::time::SystemTime>,
+  pub size:  u64,
+  pub rx:    std::sync::mpsc::Receiver<Option<Vec<String>>>,
+}
+
+/// Archive being listed in the background for the preview of `path`.
+pub struct PendingArchive
+{
+  pub path: std::path::PathBuf,
+  pub rx: std::sync::mpsc::Receiver<
+    std::io::Result<crate::core::archive::ArchiveListing>,
+  >,
+}
+
+/// Preview cache key: path and preview area size, plus the file's
+/// modification time and size so edited files are previewed afresh.
+pub type PreviewStamp =
+  ((std::path::PathBuf, u16, u16), Option<std::time::SystemTime>, u64);
+
+/// Matches streamed from a background grep; `None` marks the end.
+pub struct RunningGrep
+{
+  pub rx: std::sync::mpsc::Receiver<Option<GrepMatch>>,
+}
+
+/// Message streamed from the file finder's background indexer.
+pub enum FinderBatch
+{
+  Files(Vec<String>),
+  Done
+  {
+    truncated: bool,
+  },
+}
+
+pub struct FinderIndex
+{
+  pub rx: std::sync::mpsc::Receiver<FinderBatch>,
+}
+
+/// Message streamed from a background directory reader.
+pub enum DirBatch
+{
+  Entries(Vec<DirEntryInfo>),
+  Error(String),
+  Done,
+}
+
+/// In-flight background read of a directory listing.
+pub struct DirLoad
+{
+  pub path:      PathBuf,
+  pub rx:        std::sync::mpsc::Receiver<DirBatch>,
+  /// Entry name to keep selected while batches arrive and get re-sorted.
+  pub reselect:  Option<String>,
+  /// Entries arrive in display order (tree view); skip sorting them.
+  pub presorted: bool,
+  /// Directories below `path` that `lsv.filter` kept (true) or hid, with
+  /// everything in them.
+  pub lua_dirs:  std::collections::HashMap<PathBuf, bool>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
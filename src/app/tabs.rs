@@ -0,0 +1,158 @@
+//! Tabs: independent working directories within one App.
+//!
+//! The "other" tab is the one last switched away from (the next one when
+//! that is unknown); `copy_to_other` and `move_to_other` paste into its
+//! directory.
+
+use std::path::PathBuf;
+
+use crate::app::{
+  App,
+  TabState,
+};
+
+impl App
+{
+  fn snapshot_tab(&self) -> TabState
+  {
+    TabState {
+      cwd:           self.cwd.clone(),
+      list_state:    self.list_state.clone(),
+      selected_name: self.selected_entry().map(|e| e.name.clone()),
+      selected:      self.selected.clone(),
+      sort_key:      self.sort_key,
+      sort_reverse:  self.sort_reverse,
+    }
+  }
+
+  fn activate_tab(
+    &mut self,
+    idx: usize,
+  )
+  {
+    let Some(tab) = self.tabs.get(idx).cloned()
+    else
+    {
+      return;
+    };
+    self.active_tab = idx;
+    self.cwd = tab.cwd;
+    self.sort_key = tab.sort_key;
+    self.sort_reverse = tab.sort_reverse;
+    self.selected = tab.selected;
+    // Clear the selection first so refresh_lists does not carry the
+    // previous tab's entry over; restore the saved index afterwards.
+    let saved = tab.list_state.selected();
+    self.list_state = tab.list_state;
+    self.list_state.select(None);
+    self.refresh_lists();
+    if let Some(i) = saved
+      && !self.current_entries.is_empty()
+    {
+      self.list_state.select(Some(i.min(self.current_entries.len() - 1)));
+    }
+    if let Some(name) = tab.selected_name
+    {
+      self.reselect_when_loaded(&name);
+    }
+    self.refresh_preview();
+    self.force_full_redraw = true;
+  }
+
+  /// Open a new tab on the current directory and switch to it.
+  pub fn tab_new(&mut self)
+  {
+    let snap = self.snapshot_tab();
+    self.tabs[self.active_tab] = snap.clone();
+    self.tabs.insert(self.active_tab + 1, snap);
+    self.last_tab = Some(self.active_tab);
+    self.active_tab += 1;
+    self.force_full_redraw = true;
+  }
+
+  /// Close the active tab. The last remaining tab cannot be closed.
+  pub fn tab_close(&mut self)
+  {
+    if self.tabs.len() <= 1
+    {
+      self.add_message("tab: cannot close the last tab");
+      return;
+    }
+    self.tabs.remove(self.active_tab);
+    self.last_tab = None;
+    let next = self.active_tab.min(self.tabs.len() - 1);
+    self.activate_tab(next);
+  }
+
+  /// Switch to the next tab, wrapping around.
+  pub fn tab_next(&mut self)
+  {
+    let len = self.tabs.len();
+    if len > 1
+    {
+      self.tabs[self.active_tab] = self.snapshot_tab();
+      self.last_tab = Some(self.active_tab);
+      self.activate_tab((self.active_tab + 1) % len);
+    }
+  }
+
+  /// Switch to the previous tab, wrapping around.
+  pub fn tab_prev(&mut self)
+  {
+    let len = self.tabs.len();
+    if len > 1
+    {
+      self.tabs[self.active_tab] = self.snapshot_tab();
+      self.last_tab = Some(self.active_tab);
+      self.activate_tab((self.active_tab + len - 1) % len);
+    }
+  }
+
+  /// Working directory of the other tab, if more than one is open.
+  pub(crate) fn other_tab_dir(&self) -> Option<PathBuf>
+  {
+    if self.tabs.len() <= 1
+    {
+      return None;
+    }
+    let idx = self
+      .last_tab
+      .filter(|&i| i != self.active_tab && i < self.tabs.len())
+      .unwrap_or((self.active_tab + 1) % self.tabs.len());
+    Some(self.tabs[idx].cwd.clone())
+  }
+
+  pub fn tab_count(&self) -> usize
+  {
+    self.tabs.len()
+  }
+
+  /// Zero-based index of the active tab.
+  pub fn active_tab_index(&self) -> usize
+  {
+    self.active_tab
+  }
+
+  /// Compact tab indicator for the header, e.g. `[1] 2 3`. Empty when only
+  /// one tab is open.
+  pub(crate) fn tabs_label(&self) -> String
+  {
+    if self.tabs.len() <= 1
+    {
+      return String::new();
+    }
+    (0..self.tabs.len())
+      .map(|i| {
+        if i == self.active_tab
+        {
+          format!("[{}]", i + 1)
+        }
+        else
+        {
+          format!("{}", i + 1)
+        }
+      })
+      .collect::<Vec<_>>()
+      .join(" ")
+  }
+}
@@ -0,0 +1,123 @@
+//! `{lua:name}` segments in header, footer and row templates.
+//!
+//! `lsv.define_template_fn(name, fn)` registers `fn`, which is called as
+//! `fn(entry, status)`: `entry` describes the row's entry (or, in the header
+//! and footer, the entry under the cursor; `nil` in an empty directory) with
+//! the same fields as Lua columns, and `status` holds the footer values. The
+//! returned text replaces the segment; `nil` shows nothing.
+//!
+//! Values are computed at most once per redraw for each function and entry,
+//! so a function used on several rows or in both header and footer costs one
+//! call per frame.
+
+use std::{
+  cell::RefCell,
+  collections::HashMap,
+  path::PathBuf,
+};
+
+use mlua::Value;
+
+use crate::app::{
+  App,
+  DirEntryInfo,
+};
+
+/// Values computed during the current redraw.
+#[derive(Debug, Default)]
+pub struct TemplateFnCache
+{
+  values: RefCell<HashMap<(String, Option<PathBuf>), String>>,
+  status: RefCell<Option<mlua::Table>>,
+}
+
+impl App
+{
+  /// Forget the values of the previous redraw.
+  pub(crate) fn clear_template_fn_values(&mut self)
+  {
+    self.template_fns.values.get_mut().clear();
+    *self.template_fns.status.get_mut() = None;
+  }
+
+  /// Value of the template function `name` for `entry`, or `None` when no
+  /// such function is defined.
+  pub(crate) fn template_fn_value(
+    &self,
+    name: &str,
+    entry: Option<&DirEntryInfo>,
+  ) -> Option<String>
+  {
+    let def = self.config.template_fns.iter().find(|t| t.name == name)?;
+    let key = (def.name.clone(), entry.map(|e| e.path.clone()));
+    if let Some(v) = self.template_fns.values.borrow().get(&key)
+    {
+      return Some(v.clone());
+    }
+    let text = self.call_template_fn(def.action, entry).unwrap_or_else(|err| {
+      crate::trace::log(format!("[template] lua:{} error: {}", name, err));
+      String::new()
+    });
+    self.template_fns.values.borrow_mut().insert(key, text.clone());
+    Some(text)
+  }
+
+  fn call_template_fn(
+    &self,
+    idx: usize,
+    entry: Option<&DirEntryInfo>,
+  ) -> mlua::Result<String>
+  {
+    let Some(rt) = self.lua.as_ref()
+    else
+    {
+      return Ok(String::new());
+    };
+    let Some(key) = rt.actions.get(idx)
+    else
+    {
+      return Ok(String::new());
+    };
+    let lua = rt.engine.lua();
+    let func = lua.registry_value::<mlua::Function>(key)?;
+    let entry = match entry
+    {
+      Some(e) => Value::Table(crate::app::columns::entry_table(lua, e)?),
+      None => Value::Nil,
+    };
+    let status = match self.template_fns.status.borrow().clone()
+    {
+      Some(t) => t,
+      None => self.status_table(lua)?,
+    };
+    *self.template_fns.status.borrow_mut() = Some(status.clone());
+    Ok(match func.call::<Value>((entry, status))?
+    {
+      Value::Nil => String::new(),
+      Value::String(s) => s.to_str()?.to_string(),
+      other => other.to_string()?,
+    })
+  }
+
+  /// The table of status values handed to Lua footers and template
+  /// functions.
+  pub(crate) fn status_table(
+    &self,
+    lua: &mlua::Lua,
+  ) -> mlua::Result<mlua::Table>
+  {
+    let ctx = lua.create_table()?;
+    for (name, value) in crate::ui::template::status_values(self)
+    {
+      ctx.set(name, value)?;
+    }
+    ctx.set("cwd", self.cwd.to_string_lossy().to_string())?;
+    if let Some(sel) = self.selected_entry()
+    {
+      ctx.set("current_file", sel.path.to_string_lossy().to_string())?;
+      ctx.set("current_file_name", sel.name.clone())?;
+    }
+    ctx.set("current_len", self.current_entries.len() as u64)?;
+    Ok(ctx)
+  }
+}
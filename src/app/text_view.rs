@@ -0,0 +1,192 @@
+//! Scrolling, search and yank in the messages and output overlays.
+//!
+//! Both overlays open scrolled to their last lines and stay there as lines
+//! arrive. `j`/`k`, `PageUp`/`PageDown` (or `Ctrl-u`/`Ctrl-d`), `gg` and `G`
+//! move through the text; `/` searches it (case-insensitively), `n`/`N` step
+//! through the matching lines and `y` copies the whole text to the clipboard.
+//! Other keys fall through to the keymap.
+
+use crate::app::{
+  App,
+  Overlay,
+  TextView,
+};
+
+impl App
+{
+  /// Show the messages or output overlay, scrolled to its end.
+  pub(crate) fn set_text_overlay(
+    &mut self,
+    overlay: Overlay,
+  )
+  {
+    self.overlay = overlay;
+    self.text_view = TextView::default();
+    self.force_full_redraw = true;
+  }
+
+  /// Lines of the open messages or output overlay.
+  fn text_view_lines(&self) -> Option<Vec<String>>
+  {
+    match self.overlay
+    {
+      Overlay::Messages =>
+      {
+        Some(self.recent_messages.iter().map(|m| m.line()).collect())
+      }
+      Overlay::Output { ref lines, .. } => Some(lines.clone()),
+      _ => None,
+    }
+  }
+
+  /// Largest first line that still fills the panel.
+  fn text_view_max_scroll(&self) -> usize
+  {
+    let len = self.text_view_lines().map_or(0, |l| l.len());
+    len.saturating_sub(self.text_view.page_height.max(1))
+  }
+
+  /// First line shown in the messages or output overlay.
+  pub fn text_view_offset(&self) -> usize
+  {
+    let max = self.text_view_max_scroll();
+    self.text_view.scroll.map_or(max, |s| s.min(max))
+  }
+
+  /// Scroll by `delta` lines (negative scrolls up). Reaching the end
+  /// follows new lines again.
+  pub fn text_view_scroll(
+    &mut self,
+    delta: isize,
+  )
+  {
+    let max = self.text_view_max_scroll();
+    let next = (self.text_view_offset() as isize).saturating_add(delta);
+    let next = next.clamp(0, max as isize) as usize;
+    self.text_view.scroll = if next >= max { None } else { Some(next) };
+    self.force_full_redraw = true;
+  }
+
+  /// Scroll by `pages` visible heights.
+  pub(crate) fn text_view_page(
+    &mut self,
+    pages: isize,
+  )
+  {
+    let page = self.text_view.page_height.max(1) as isize;
+    self.text_view_scroll(pages.saturating_mul(page));
+  }
+
+  /// Search the text for `query` and jump to the first match at or below
+  /// the first line shown, wrapping around.
+  pub fn text_view_search(
+    &mut self,
+    query: &str,
+  )
+  {
+    self.text_view.query = query.to_string();
+    self.text_view.current = None;
+    let from = self.text_view_offset();
+    self.text_view_jump(from, true);
+  }
+
+  /// Jump to the next (or previous) line matching the last query.
+  pub fn text_view_next(
+    &mut self,
+    forward: bool,
+  )
+  {
+    let from = match self.text_view.current
+    {
+      Some(i) if forward => i + 1,
+      Some(i) => i.wrapping_sub(1),
+      None => self.text_view_offset(),
+    };
+    self.text_view_jump(from, forward);
+  }
+
+  /// Line of the match last jumped to.
+  pub fn text_view_match(&self) -> Option<usize>
+  {
+    self.text_view.current
+  }
+
+  fn text_view_jump(
+    &mut self,
+    from: usize,
+    forward: bool,
+  )
+  {
+    let needle = self.text_view.query.to_lowercase();
+    if needle.is_empty()
+    {
+      return;
+    }
+    let Some(lines) = self.text_view_lines()
+    else
+    {
+      return;
+    };
+    let len = lines.len();
+    let found = (0..len)
+      .map(|i| {
+        if forward
+        {
+          from.wrapping_add(i) % len
+        }
+        else
+        {
+          from.wrapping_add(len).wrapping_sub(i) % len
+        }
+      })
+      .find(|&i| {
+        crate::ui::ansi::strip_ansi(&lines[i]).to_lowercase().contains(&needle)
+      });
+    match found
+    {
+      Some(i) =>
+      {
+        self.text_view.current = Some(i);
+        // Keep the match in view, a few lines from the top
+        let offset = self.text_view_offset();
+        let page = self.text_view.page_height.max(1);
+        if i < offset || i >= offset + page
+        {
+          let max = self.text_view_max_scroll();
+          let top = i.saturating_sub(2).min(max);
+          self.text_view.scroll = if top >= max { None } else { Some(top) };
+        }
+      }
+      None =>
+      {
+        self.text_view.current = None;
+        let query = self.text_view.query.clone();
+        self.add_message(&format!("Pattern not found: {}", query));
+      }
+    }
+    self.force_full_redraw = true;
+  }
+
+  /// Copy the whole text to the clipboard.
+  pub(crate) fn text_view_yank(&mut self)
+  {
+    let Some(lines) = self.text_view_lines()
+    else
+    {
+      return;
+    };
+    let text = lines
+      .iter()
+      .map(|l| crate::ui::ansi::strip_ansi(l))
+      .collect::<Vec<_>>()
+      .join("\n");
+    let count = lines.len();
+    let (osc52, tool) = crate::app::yank::copy_to_clipboard(&text);
+    if !osc52 && tool.is_none()
+    {
+      self.add_message("Yank: no clipboard available");
+      return;
+    }
+    self.add_message(&format!("Yanked {} line(s)", count));
+  }
+}
@@ -0,0 +1,177 @@
+//! Trash actions and the trash browser overlay for App.
+
+use std::path::PathBuf;
+
+use crate::{
+  app::{
+    App,
+    JobKind,
+    Overlay,
+    TrashBrowserState,
+  },
+  core::trash::{
+    self,
+    TrashLocation,
+    TrashedItem,
+  },
+};
+
+impl App
+{
+  fn trash_location(&mut self) -> Option<TrashLocation>
+  {
+    let loc = TrashLocation::detect();
+    if loc.is_none()
+    {
+      self.add_message("Trash: unable to determine trash directory");
+    }
+    loc
+  }
+
+  pub(crate) fn request_trash_selected(&mut self)
+  {
+    if self.refuse_in_archive("Trash") || self.refuse_remote("Trash")
+    {
+      return;
+    }
+    crate::core::overlays::request_trash_selected(self)
+  }
+
+  /// Queue a background job moving `paths` to the trash.
+  pub(crate) fn perform_trash_paths(
+    &mut self,
+    paths: &[PathBuf],
+  )
+  {
+    if self.trash_location().is_none()
+    {
+      return;
+    }
+    crate::trace::log(format!("[trash] queue {} path(s)", paths.len()));
+    for p in paths
+    {
+      self.selected.remove(p);
+    }
+    self.enqueue_job(JobKind::Trash, paths.to_vec(), None);
+  }
+
+  /// Restore the most recently trashed item.
+  pub(crate) fn restore_from_trash(&mut self)
+  {
+    let Some(loc) = self.trash_location()
+    else
+    {
+      return;
+    };
+    match trash::list_items(&loc)
+    {
+      Ok(items) => match items.into_iter().next()
+      {
+        Some(item) => self.restore_trashed(&loc, &item),
+        None => self.add_message("Trash is empty"),
+      },
+      Err(e) => self.add_error(&format!("Trash error: {}", e)),
+    }
+  }
+
+  fn restore_trashed(
+    &mut self,
+    loc: &TrashLocation,
+    item: &TrashedItem,
+  )
+  {
+    match trash::restore_item(loc, item)
+    {
+      Ok(dest) =>
+      {
+        self.add_message(&format!("Restored {}", dest.display()));
+        self.refresh_lists();
+        if dest.parent() == Some(self.cwd.as_path())
+          && let Some(name) = dest.file_name().and_then(|s| s.to_str())
+        {
+          self.reselect_when_loaded(name);
+        }
+        self.refresh_preview();
+      }
+      Err(e) => self.add_error(&format!("Restore error: {}", e)),
+    }
+  }
+
+  pub(crate) fn open_trash_browser(&mut self)
+  {
+    let Some(location) = self.trash_location()
+    else
+    {
+      return;
+    };
+    let entries = match trash::list_items(&location)
+    {
+      Ok(v) => v,
+      Err(e) =>
+      {
+        self.add_error(&format!("Trash error: {}", e));
+        return;
+      }
+    };
+    self.keys.pending.clear();
+    self.keys.last_at = None;
+    self.overlay = Overlay::Trash(Box::new(TrashBrowserState {
+      location,
+      entries,
+      selected: 0,
+    }));
+    self.force_full_redraw = true;
+  }
+
+  pub(crate) fn trash_browser_move(
+    &mut self,
+    delta: isize,
+  )
+  {
+    if let Overlay::Trash(ref mut st) = self.overlay
+      && !st.entries.is_empty()
+    {
+      let last = st.entries.len() as isize - 1;
+      st.selected = (st.selected as isize + delta).clamp(0, last) as usize;
+    }
+  }
+
+  /// Restore (`purge == false`) or permanently delete the highlighted entry.
+  pub(crate) fn trash_browser_apply(
+    &mut self,
+    purge: bool,
+  )
+  {
+    let (loc, item) = match self.overlay
+    {
+      Overlay::Trash(ref st) => match st.entries.get(st.selected)
+      {
+        Some(item) => (st.location.clone(), item.clone()),
+        None => return,
+      },
+      _ => return,
+    };
+    if purge
+    {
+      match trash::purge_item(&loc, &item)
+      {
+        Ok(()) => self.add_message(&format!(
+          "Deleted {} permanently",
+          item.original_path.display()
+        )),
+        Err(e) => self.add_error(&format!("Trash error: {}", e)),
+      }
+    }
+    else
+    {
+      self.restore_trashed(&loc, &item);
+    }
+    // Re-read so the list reflects what actually happened on disk
+    if let Overlay::Trash(ref mut st) = self.overlay
+    {
+      st.entries = trash::list_items(&loc).unwrap_or_default();
+      st.selected = st.selected.min(st.entries.len().saturating_sub(1));
+    }
+    self.force_full_redraw = true;
+  }
+}
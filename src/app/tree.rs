@@ -0,0 +1,215 @@
+//! Tree view of the current pane for App.
+//!
+//! In tree mode the current pane lists the directory as an expandable tree
+//! instead of a single level, and the parent pane is hidden. `l` expands a
+//! directory in place and `h` collapses it (or the one containing the
+//! cursor). Expanded directories are remembered by path for the session, so
+//! they stay open while navigating and across toggles. Entry names are paths
+//! relative to the current directory, as in the flat view.
+
+use std::{
+  collections::HashSet,
+  path::{
+    Path,
+    PathBuf,
+  },
+  sync::mpsc,
+};
+
+use crate::{
+  actions::SortKey,
+  app::{
+    App,
+    DirBatch,
+  },
+};
+
+/// Tree mode switch and the directories expanded in it.
+#[derive(Debug, Default)]
+pub struct TreeState
+{
+  pub enabled:  bool,
+  pub expanded: HashSet<PathBuf>,
+}
+
+/// Options for listing a tree, captured for the loader thread.
+pub(crate) struct TreeListing
+{
+  pub expanded:     HashSet<PathBuf>,
+  pub show_hidden:  bool,
+  pub need_meta:    bool,
+  pub sort_key:     SortKey,
+  pub sort_reverse: bool,
+  pub max_items:    usize,
+}
+
+/// List `root` with its expanded directories inlined below them, each level
+/// sorted on its own, and send the result as one ordered batch.
+pub(crate) fn stream_tree_entries(
+  root: &Path,
+  opts: &TreeListing,
+  tx: &mpsc::Sender<DirBatch>,
+)
+{
+  let read = |dir: &Path| {
+    crate::core::listing::read_dir_sorted(
+      dir,
+      opts.show_hidden,
+      opts.sort_key,
+      opts.sort_reverse,
+      opts.need_meta,
+      opts.max_items,
+    )
+  };
+  let top = match read(root)
+  {
+    Ok(entries) => entries,
+    Err(e) =>
+    {
+      let _ = tx.send(DirBatch::Error(e.to_string()));
+      return;
+    }
+  };
+  let mut out = Vec::new();
+  // Depth-first, keeping each directory's sorted order
+  let mut stack = vec![top.into_iter()];
+  while let Some(level) = stack.last_mut()
+  {
+    let Some(mut info) = level.next()
+    else
+    {
+      stack.pop();
+      continue;
+    };
+    if out.len() >= opts.max_items
+    {
+      break;
+    }
+    let expand = info.is_dir && opts.expanded.contains(&info.path);
+    let children = if expand { read(&info.path).ok() } else { None };
+    if let Ok(rel) = info.path.strip_prefix(root)
+    {
+      info.name = rel.to_string_lossy().replace('\\', "/");
+    }
+    out.push(info);
+    if let Some(children) = children
+    {
+      stack.push(children.into_iter());
+    }
+  }
+  let _ = tx.send(DirBatch::Entries(out));
+  let _ = tx.send(DirBatch::Done);
+}
+
+impl App
+{
+  /// Switch the current pane between the column view and the tree view.
+  pub(crate) fn toggle_tree(&mut self)
+  {
+    self.tree.enabled = !self.tree.enabled;
+    // The tree lists nested names; the flat view would fight over them
+    if self.tree.enabled
+    {
+      self.flatten = None;
+    }
+    self.relist_keeping_selection();
+    self.force_full_redraw = true;
+  }
+
+  /// Whether the current pane is shown as a tree.
+  pub fn tree_active(&self) -> bool
+  {
+    // Remote directories are listed one level at a time
+    self.tree.enabled && self.flatten_depth().is_none() && !self.in_remote()
+  }
+
+  /// Whether the directory at `path` is expanded in the tree view.
+  pub fn is_tree_expanded(
+    &self,
+    path: &Path,
+  ) -> bool
+  {
+    self.tree.expanded.contains(path)
+  }
+
+  /// Expand the directory under the cursor in place. Returns false when the
+  /// key should fall back to its usual action.
+  pub(crate) fn tree_expand(&mut self) -> bool
+  {
+    if !self.tree_active()
+    {
+      return false;
+    }
+    let Some(entry) = self.selected_entry()
+    else
+    {
+      return false;
+    };
+    if !entry.is_dir
+    {
+      return false;
+    }
+    let path = entry.path.clone();
+    if self.tree.expanded.insert(path)
+    {
+      self.relist_keeping_selection();
+    }
+    else if let Some(sel) = self.list_state.selected()
+      && self.current_entries.get(sel + 1).is_some_and(|next| {
+        next.path.parent() == Some(&self.current_entries[sel].path)
+      })
+    {
+      // Already open: step onto its first child
+      self.select_index(sel + 1);
+    }
+    true
+  }
+
+  /// Collapse the directory under the cursor, or else the one containing it
+  /// (moving the cursor there). Returns false at the top level, where the
+  /// key should go to the parent directory as usual.
+  pub(crate) fn tree_collapse(&mut self) -> bool
+  {
+    if !self.tree_active()
+    {
+      return false;
+    }
+    let Some(entry) = self.selected_entry()
+    else
+    {
+      return false;
+    };
+    if entry.is_dir && self.tree.expanded.contains(&entry.path)
+    {
+      let path = entry.path.clone();
+      self.tree.expanded.remove(&path);
+      self.relist_keeping_selection();
+      return true;
+    }
+    let Some((parent_name, _)) = entry.name.rsplit_once('/')
+    else
+    {
+      return false;
+    };
+    let parent_name = parent_name.to_string();
+    if let Some(parent) = entry.path.parent().map(Path::to_path_buf)
+    {
+      self.tree.expanded.remove(&parent);
+    }
+    self.refresh_lists();
+    self.reselect_when_loaded(&parent_name);
+    self.refresh_preview();
+    true
+  }
+
+  fn relist_keeping_selection(&mut self)
+  {
+    let name = self.selected_entry().map(|e| e.name.clone());
+    self.refresh_lists();
+    if let Some(name) = name
+    {
+      self.reselect_when_loaded(&name);
+    }
+    self.refresh_preview();
+  }
+}
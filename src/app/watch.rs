@@ -0,0 +1,214 @@
+//! Filesystem watching for App.
+//!
+//! Rather than depending on platform notification APIs, the watcher polls the
+//! modification time of the current, parent and previewed directories. A
+//! directory's mtime changes whenever entries are created, removed or renamed
+//! in it, which is exactly what the listings show.
+//!
+//! The active theme file (`ui.theme_path`) is checked the same way and
+//! reloaded when it is saved, so colours can be tuned without reopening the
+//! theme picker. With `ui.auto_reload` on, saving `init.lua` reloads the
+//! whole config.
+
+use std::{
+  path::PathBuf,
+  time::{
+    Duration,
+    Instant,
+    SystemTime,
+  },
+};
+
+use crate::app::App;
+
+/// How often watched directories are checked for changes.
+const WATCH_INTERVAL_MS: u64 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WatchRole
+{
+  Current,
+  Parent,
+  Preview,
+}
+
+/// Last seen modification times for the watched directories.
+#[derive(Debug, Default)]
+pub struct WatchState
+{
+  targets:     Vec<(WatchRole, PathBuf, Option<SystemTime>)>,
+  last_check:  Option<Instant>,
+  /// The theme file and `init.lua` with their modification times when last
+  /// loaded.
+  theme_file:  Option<(PathBuf, Option<SystemTime>)>,
+  config_file: Option<(PathBuf, Option<SystemTime>)>,
+}
+
+fn dir_mtime(path: &std::path::Path) -> Option<SystemTime>
+{
+  std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Record `path`'s modification time in `seen` and tell whether the file
+/// was saved since the last call. A file seen for the first time counts as
+/// unchanged since whatever it holds is already applied.
+fn file_changed(
+  seen: &mut Option<(PathBuf, Option<SystemTime>)>,
+  path: &std::path::Path,
+) -> bool
+{
+  let mtime = dir_mtime(path);
+  let changed = match seen
+  {
+    Some((p, prev)) if p == path => *prev != mtime,
+    _ => false,
+  };
+  *seen = Some((path.to_path_buf(), mtime));
+  changed && mtime.is_some()
+}
+
+impl App
+{
+  fn watch_targets(&self) -> Vec<(WatchRole, PathBuf)>
+  {
+    let mut out = vec![(WatchRole::Current, self.cwd.clone())];
+    if let Some(p) = self.cwd.parent()
+    {
+      out.push((WatchRole::Parent, p.to_path_buf()));
+    }
+    if let Some(e) = self.selected_entry()
+      && e.is_dir
+    {
+      out.push((WatchRole::Preview, e.path.clone()));
+    }
+    out
+  }
+
+  /// Check watched directories and refresh listings or the preview when they
+  /// changed on disk. Checks are rate limited; returns true when anything was
+  /// refreshed.
+  pub fn poll_fs_changes(&mut self) -> bool
+  {
+    if let Some(at) = self.watch.last_check
+      && at.elapsed() < Duration::from_millis(WATCH_INTERVAL_MS)
+    {
+      return false;
+    }
+    self.check_fs_changes()
+  }
+
+  /// Reload the theme file when it changed since it was last seen. Returns
+  /// true when the theme was reapplied.
+  fn check_theme_file(&mut self) -> bool
+  {
+    let Some(path) = self.config.ui.theme_path.clone()
+    else
+    {
+      self.watch.theme_file = None;
+      return false;
+    };
+    if !file_changed(&mut self.watch.theme_file, &path)
+    {
+      return false;
+    }
+    match crate::config::load_theme_from_file(&path)
+    {
+      Ok(theme) =>
+      {
+        crate::trace::log(format!(
+          "[watch] theme reloaded: {}",
+          path.display()
+        ));
+        self.config.ui.theme = Some(theme);
+        self.force_full_redraw = true;
+        true
+      }
+      Err(e) =>
+      {
+        // Keep the last good theme while the file is being edited
+        self.add_error(&format!("Theme: {}", e));
+        false
+      }
+    }
+  }
+
+  /// Reload the whole config when `ui.auto_reload` is on and `init.lua`
+  /// changed since it was last seen.
+  fn check_config_file(&mut self) -> bool
+  {
+    let entry = self
+      .config_paths
+      .as_ref()
+      .filter(|_| self.config.ui.auto_reload)
+      .map(|p| p.entry.clone());
+    let Some(path) = entry
+    else
+    {
+      self.watch.config_file = None;
+      return false;
+    };
+    file_changed(&mut self.watch.config_file, &path) && self.reload_config()
+  }
+
+  /// Unthrottled variant of [`App::poll_fs_changes`].
+  pub fn check_fs_changes(&mut self) -> bool
+  {
+    self.watch.last_check = Some(Instant::now());
+    let config = self.check_config_file();
+    let theme = config || self.check_theme_file();
+    // Our own listing is still streaming in; compare once it settles
+    if self.is_loading()
+    {
+      return theme;
+    }
+    let targets = self.watch_targets();
+    let mut relist = false;
+    let mut repreview = false;
+    let mut next = Vec::with_capacity(targets.len());
+    for (role, path) in targets
+    {
+      let mtime = dir_mtime(&path);
+      let prev = self
+        .watch
+        .targets
+        .iter()
+        .find(|(r, p, _)| *r == role && *p == path)
+        .map(|(_, _, t)| *t);
+      // Only a directory seen before can have changed; new targets (after
+      // navigating) are just recorded.
+      if let Some(prev) = prev
+        && prev != mtime
+      {
+        match role
+        {
+          WatchRole::Current | WatchRole::Parent => relist = true,
+          WatchRole::Preview => repreview = true,
+        }
+      }
+      next.push((role, path, mtime));
+    }
+    self.watch.targets = next;
+    if relist
+    {
+      crate::trace::log("[watch] listing changed on disk");
+      self.refresh_lists();
+      // The selection may now point at another directory; rebase targets
+      self.watch.targets = self
+        .watch_targets()
+        .into_iter()
+        .map(|(role, path)| {
+          let t = dir_mtime(&path);
+          (role, path, t)
+        })
+        .collect();
+    }
+    if relist || repreview
+    {
+      self.preview.cache_key = None;
+      self.preview.cache_lines = None;
+      self.refresh_preview();
+      self.force_full_redraw = true;
+    }
+    relist || repreview || theme
+  }
+}
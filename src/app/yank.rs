@@ -0,0 +1,150 @@
+//! Yanking paths, names and file contents to the system clipboard for App.
+//!
+//! `yank_path`, `yank_name` and `yank_dir` copy the full paths, the file
+//! names or the current directory; with several items selected, one per
+//! line. `yank_contents` copies the current text file. The text is sent
+//! with OSC 52 when lsv runs in a terminal and through the platform's
+//! clipboard tool when one is available (see [`crate::core::yank`]).
+
+use std::{
+  io::IsTerminal,
+  path::PathBuf,
+};
+
+use crate::{
+  app::App,
+  core::yank,
+};
+
+/// Largest file `yank_contents` copies; terminals cap OSC 52 payloads.
+const MAX_CONTENTS: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YankKind
+{
+  Path,
+  Name,
+  Dir,
+  Contents,
+}
+
+impl App
+{
+  fn yank_items(&self) -> Vec<PathBuf>
+  {
+    let mut items: Vec<PathBuf> = self.selected.iter().cloned().collect();
+    if items.is_empty()
+    {
+      items.extend(self.selected_entry().map(|e| e.path.clone()));
+    }
+    items.sort();
+    items
+  }
+
+  /// Text to copy for `kind`, or the reason there is none.
+  fn yank_text(
+    &self,
+    kind: YankKind,
+  ) -> Result<String, String>
+  {
+    let items = self.yank_items();
+    let lines: Vec<String> = match kind
+    {
+      YankKind::Dir => vec![self.cwd.to_string_lossy().to_string()],
+      YankKind::Path =>
+      {
+        items.iter().map(|p| p.to_string_lossy().to_string()).collect()
+      }
+      YankKind::Name => items
+        .iter()
+        .filter_map(|p| p.file_name())
+        .map(|s| s.to_string_lossy().to_string())
+        .collect(),
+      YankKind::Contents =>
+      {
+        let path = self
+          .selected_entry()
+          .map(|e| e.path.clone())
+          .ok_or_else(|| String::from("no file selected"))?;
+        return read_text(&path);
+      }
+    };
+    if lines.is_empty()
+    {
+      return Err(String::from("nothing selected"));
+    }
+    Ok(lines.join("\n"))
+  }
+
+  /// Copy the paths, names, directory or contents to the system clipboard.
+  pub(crate) fn yank(
+    &mut self,
+    kind: YankKind,
+  )
+  {
+    if kind == YankKind::Contents
+      && (self.refuse_in_archive("Yank") || self.refuse_remote("Yank"))
+    {
+      return;
+    }
+    let text = match self.yank_text(kind)
+    {
+      Ok(text) => text,
+      Err(e) =>
+      {
+        self.add_message(&format!("Yank: {}", e));
+        return;
+      }
+    };
+    let (osc52, tool) = copy_to_clipboard(&text);
+    crate::trace::log(format!(
+      "[yank] {:?} bytes={} osc52={} tool={:?}",
+      kind,
+      text.len(),
+      osc52,
+      tool
+    ));
+    if !osc52 && tool.is_none()
+    {
+      self.add_message("Yank: no clipboard available");
+      return;
+    }
+    let lines = text.lines().count();
+    let summary = match kind
+    {
+      YankKind::Contents => format!("{} line(s)", lines),
+      _ if lines > 1 => format!("{} items", lines),
+      _ => text.clone(),
+    };
+    self.add_message(&format!("Yanked {}", summary));
+  }
+}
+
+/// Send `text` to the clipboard over OSC 52 (in a terminal) and through the
+/// platform's tool. Returns whether OSC 52 was written and which tool ran.
+pub(crate) fn copy_to_clipboard(text: &str) -> (bool, Option<&'static str>)
+{
+  let osc52 =
+    std::io::stdout().is_terminal() && yank::write_osc52(text).is_ok();
+  (osc52, yank::copy_with_tool(text))
+}
+
+/// Contents of `path` if it is a reasonably small text file.
+fn read_text(path: &std::path::Path) -> Result<String, String>
+{
+  let meta = std::fs::metadata(path).map_err(|e| e.to_string())?;
+  if meta.is_dir()
+  {
+    return Err(String::from("not a file"));
+  }
+  if meta.len() > MAX_CONTENTS
+  {
+    return Err(String::from("file is too large"));
+  }
+  let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+  if bytes.contains(&0)
+  {
+    return Err(String::from("binary file"));
+  }
+  Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
@@ -8,27 +8,190 @@ pub fn all() -> &'static [&'static str]
   &[
     "marks",
     "delmark",
+    "rename_mark",
     "find",
+    "find_files",
+    "extract",
+    "extract_here",
+    "compress",
+    "file_info",
+    "show_file_info",
+    "set_permissions",
+    "compute_dir_sizes",
+    "checksum",
+    "checksum md5",
+    "checksum sha1",
+    "checksum sha256",
+    "verify",
+    "toggle_flatten",
+    "toggle_tree",
+    "toggle_parent_pane",
+    "toggle_preview_pane",
     "next",
     "prev",
     "messages",
+    "messages clear",
+    "messages save",
     "output",
     "theme",
+    "reload_config",
     "add",
     "rename",
+    "rename_editor",
+    "open",
+    "open_at_line",
+    "paste_symlink",
+    "paste_symlink relative",
+    "paste_symlink absolute",
+    "paste_hardlink",
+    "yank_path",
+    "yank_name",
+    "yank_dir",
+    "yank_contents",
+    "run_interactive",
+    "open_shell",
+    "run",
+    "run_background",
+    "run_preview",
+    "run_cancel",
     "delete",
+    "delete_to_trash",
+    "restore_from_trash",
+    "trash",
+    "jobs",
+    "job_cancel",
+    "registers",
     "select_toggle",
+    "selections",
     "select_clear",
+    "select_all",
+    "filter",
+    "filter_clear",
+    "filter_prompt",
+    "invert_selection",
+    "select",
+    "select!",
+    "unselect",
+    "unselect!",
     "show_hidden_toggle",
     "sort name",
     "sort size",
     "sort mtime",
     "sort created",
+    "sort natural",
+    "sort extension",
     "sort_reverse_toggle",
     "display friendly",
     "display absolute",
     "cd",
+    "z",
+    "mounts",
+    "drives",
+    "grep",
     "mark",
     "goto",
+    "tab_new",
+    "tab_next",
+    "tab_prev",
+    "tab_close",
+    "copy_to_other",
+    "move_to_other",
+    "palette",
+    "help",
   ]
 }
+
+/// Whether the built-in command `cmd` needs an argument to do anything, so
+/// the palette opens the command pane for it instead of running it.
+pub fn takes_argument(cmd: &str) -> bool
+{
+  matches!(
+    cmd,
+    "delmark"
+      | "rename_mark"
+      | "run_interactive"
+      | "run"
+      | "run_background"
+      | "run_preview"
+      | "filter"
+      | "select"
+      | "select!"
+      | "unselect"
+      | "unselect!"
+      | "cd"
+      | "grep"
+      | "mark"
+      | "goto"
+  )
+}
+
+/// Command names without their arguments (`sort name` gives `sort`).
+pub fn builtin_names() -> impl Iterator<Item = &'static str>
+{
+  all().iter().filter_map(|c| c.split_whitespace().next())
+}
+
+/// Palette entries including commands registered from Lua, each followed by
+/// its completion values (`name value`).
+pub fn with_lua(lua: &[crate::config::LuaCommand]) -> Vec<String>
+{
+  let mut out: Vec<String> = all().iter().map(|c| c.to_string()).collect();
+  for cmd in lua
+  {
+    out.push(cmd.name.clone());
+    out.extend(cmd.complete.iter().map(|v| format!("{} {}", cmd.name, v)));
+  }
+  out
+}
+
+/// Split a command line into arguments like a shell would: whitespace
+/// separates words, single quotes keep their contents as is, and inside
+/// double quotes or unquoted a backslash escapes the next character.
+pub fn split_args(line: &str) -> Vec<String>
+{
+  let mut out = Vec::new();
+  let mut cur = String::new();
+  let mut in_word = false;
+  let mut quote: Option<char> = None;
+  let mut chars = line.chars();
+  while let Some(c) = chars.next()
+  {
+    match (quote, c)
+    {
+      (Some('\''), '\'') | (Some('"'), '"') => quote = None,
+      (Some('\''), _) => cur.push(c),
+      (_, '\\') =>
+      {
+        in_word = true;
+        if let Some(next) = chars.next()
+        {
+          cur.push(next);
+        }
+      }
+      (Some(_), _) => cur.push(c),
+      (None, '\'' | '"') =>
+      {
+        in_word = true;
+        quote = Some(c);
+      }
+      (None, c) if c.is_whitespace() =>
+      {
+        if in_word
+        {
+          out.push(std::mem::take(&mut cur));
+          in_word = false;
+        }
+      }
+      (None, _) =>
+      {
+        in_word = true;
+        cur.push(c);
+      }
+    }
+  }
+  if in_word
+  {
+    out.push(cur);
+  }
+  out
+}
@@ -23,34 +23,53 @@ mod lsv_api;
 pub(crate) use lsv_api::install_lsv_api;
 mod theme;
 pub(crate) use theme::{
+  eval_theme_table,
   load_theme_table_from_path,
   merge_theme_table,
   resolve_theme_path,
 };
+mod plugins;
+#[allow(unused_imports)]
+pub use plugins::{
+  discover_plugins,
+  order_plugins,
+};
+mod report;
+pub use report::ConfigError;
 mod require;
 pub(crate) use require::install_require;
 mod lua_engine;
 pub use lua_engine::LuaEngine;
 mod loader;
 pub mod runtime;
-pub use loader::load_config;
 #[allow(unused_imports)]
 pub use loader::load_config_from_code;
+pub use loader::{
+  ConfigArtifacts,
+  load_config,
+};
 
+pub mod builtin_themes;
 pub mod defaults;
 
 // Theme helpers moved to config/theme.rs
 
+/// Lua state for evaluating a theme on its own.
+fn theme_lua() -> std::io::Result<Lua>
+{
+  Lua::new_with(
+    StdLib::STRING | StdLib::TABLE | StdLib::MATH,
+    LuaOptions::default(),
+  )
+  .map_err(|e| std::io::Error::other(format!("lua init failed: {e}")))
+}
+
 /// Load a standalone theme file from disk.
 ///
 /// The theme is returned as a [`UiTheme`] without mutating any global state.
 pub fn load_theme_from_file(path: &Path) -> std::io::Result<UiTheme>
 {
-  let lua = Lua::new_with(
-    StdLib::STRING | StdLib::TABLE | StdLib::MATH,
-    LuaOptions::default(),
-  )
-  .map_err(|e| std::io::Error::other(format!("lua init failed: {e}")))?;
+  let lua = theme_lua()?;
   let tbl = load_theme_table_from_path(&lua, path).map_err(|e| {
     std::io::Error::other(format!("load theme '{}': {e}", path.display()))
   })?;
@@ -59,6 +78,21 @@ pub fn load_theme_from_file(path: &Path) -> std::io::Result<UiTheme>
   Ok(theme)
 }
 
+/// Load a theme from Lua source, such as a built-in theme.
+pub fn load_theme_from_source(
+  name: &str,
+  code: &str,
+) -> std::io::Result<UiTheme>
+{
+  let lua = theme_lua()?;
+  let tbl = eval_theme_table(&lua, code, name).map_err(|e| {
+    std::io::Error::other(format!("load theme '{}': {e}", name))
+  })?;
+  let mut theme = UiTheme::default();
+  merge_theme_table(&tbl, &mut theme);
+  Ok(theme)
+}
+
 // LuaEngine moved to config/lua_engine.rs
 // Paths moved to config/paths.rs
 // lsv_api moved to config/lsv_api.rs
@@ -0,0 +1,65 @@
+//! Themes compiled into the binary.
+//!
+//! The example themes under `examples/config/lua/themes` are embedded so the
+//! theme picker and `ui.theme = "themes.<name>"` work without a themes
+//! directory. A file of the same name in the user's config takes precedence.
+
+use std::io;
+
+use super::UiTheme;
+
+macro_rules! theme {
+  ($name:literal) => {
+    (
+      $name,
+      include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/examples/config/lua/themes/",
+        $name,
+        ".lua"
+      )),
+    )
+  };
+}
+
+/// Name and Lua source of each built-in theme, sorted by name.
+pub const THEMES: &[(&str, &str)] = &[
+  theme!("catppuccin"),
+  theme!("dark"),
+  theme!("dracula"),
+  theme!("everforest"),
+  theme!("gruvbox"),
+  theme!("horizon"),
+  theme!("kanagawa"),
+  theme!("light"),
+  theme!("material_palenight"),
+  theme!("monokai_pro"),
+  theme!("nightfox"),
+  theme!("nord"),
+  theme!("oceanic_next"),
+  theme!("one_light"),
+  theme!("onedark"),
+  theme!("rose_pine_moon"),
+  theme!("solarized"),
+  theme!("solarized_light"),
+  theme!("tokyonight"),
+  theme!("tokyonight_day"),
+];
+
+/// Lua source of the built-in theme `name`, ignoring case.
+pub fn source(name: &str) -> Option<&'static str>
+{
+  THEMES.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, src)| *src)
+}
+
+/// Load the built-in theme `name`.
+pub fn load(name: &str) -> io::Result<UiTheme>
+{
+  let src = source(name).ok_or_else(|| {
+    io::Error::new(
+      io::ErrorKind::NotFound,
+      format!("no built-in theme '{}'", name),
+    )
+  })?;
+  super::load_theme_from_source(name, src)
+}
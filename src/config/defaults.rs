@@ -1,6 +1,7 @@
 use super::{
   Config,
   KeyMapping,
+  KeysConfig,
   UiModalConfig,
   UiModals,
   UiPanes,
@@ -9,6 +10,60 @@ use super::{
   UiTheme,
 };
 
+/// Names shown in the which-key overlay for the default key prefixes.
+const DEFAULT_GROUPS: &[(&str, &str)] = &[
+  ("g", "Go to"),
+  ("p", "Paste links"),
+  ("s", "Sort"),
+  ("t", "Tabs"),
+  ("y", "Yank"),
+  ("z", "View"),
+];
+
+/// Drop the built-in keymaps seeded in front of `maps` when `keys.defaults`
+/// is false, and expand `<leader>` in the rest and in the group names.
+pub(crate) fn finish_keymaps(
+  mut maps: Vec<KeyMapping>,
+  keys: &mut KeysConfig,
+) -> Vec<KeyMapping>
+{
+  if !keys.defaults
+  {
+    let seeded = rust_default_keymaps().len().min(maps.len());
+    maps.drain(..seeded);
+  }
+  for m in &mut maps
+  {
+    m.sequence = expand_leader(&m.sequence, &keys.leader);
+  }
+  keys.groups = std::mem::take(&mut keys.groups)
+    .into_iter()
+    .map(|(prefix, name)| (expand_leader(&prefix, &keys.leader), name))
+    .collect();
+  if keys.defaults
+  {
+    for (prefix, name) in DEFAULT_GROUPS
+    {
+      keys.groups.entry(prefix.to_string()).or_insert_with(|| name.to_string());
+    }
+  }
+  maps
+}
+
+/// Replace `<leader>`, in any case, with the configured leader key.
+fn expand_leader(
+  seq: &str,
+  leader: &str,
+) -> String
+{
+  crate::keymap::tokenize_sequence(seq)
+    .into_iter()
+    .map(|t| {
+      if t.eq_ignore_ascii_case("<leader>") { leader.to_string() } else { t }
+    })
+    .collect()
+}
+
 /// Built-in default keymaps defined in Rust.
 /// These mirror the previous Lua defaults and are applied before user config.
 pub fn rust_default_keymaps() -> Vec<KeyMapping>
@@ -45,7 +100,87 @@ pub fn rust_default_keymaps() -> Vec<KeyMapping>
       action:      "sort:created".into(),
       description: Some("Sort by created time".into()),
     },
+    KeyMapping {
+      sequence:    "sv".into(),
+      action:      "sort:natural".into(),
+      description: Some("Sort by name, numbers by value".into()),
+    },
+    KeyMapping {
+      sequence:    "se".into(),
+      action:      "sort:extension".into(),
+      description: Some("Sort by extension".into()),
+    },
     // Navigation
+    KeyMapping {
+      sequence:    "j".into(),
+      action:      "nav:down".into(),
+      description: Some("Move down".into()),
+    },
+    KeyMapping {
+      sequence:    "<Down>".into(),
+      action:      "nav:down".into(),
+      description: Some("Move down".into()),
+    },
+    KeyMapping {
+      sequence:    "k".into(),
+      action:      "nav:up".into(),
+      description: Some("Move up".into()),
+    },
+    KeyMapping {
+      sequence:    "<Up>".into(),
+      action:      "nav:up".into(),
+      description: Some("Move up".into()),
+    },
+    KeyMapping {
+      sequence:    "l".into(),
+      action:      "nav:right".into(),
+      description: Some("Enter directory or expand".into()),
+    },
+    KeyMapping {
+      sequence:    "<Right>".into(),
+      action:      "nav:right".into(),
+      description: Some("Enter directory or expand".into()),
+    },
+    KeyMapping {
+      sequence:    "<CR>".into(),
+      action:      "nav:open".into(),
+      description: Some("Open or enter".into()),
+    },
+    KeyMapping {
+      sequence:    "h".into(),
+      action:      "nav:left".into(),
+      description: Some("Parent directory or collapse".into()),
+    },
+    KeyMapping {
+      sequence:    "<Left>".into(),
+      action:      "nav:left".into(),
+      description: Some("Parent directory or collapse".into()),
+    },
+    KeyMapping {
+      sequence:    "<BS>".into(),
+      action:      "nav:parent".into(),
+      description: Some("Parent directory".into()),
+    },
+    KeyMapping {
+      sequence:    "m".into(),
+      action:      "mark:set".into(),
+      description: Some("Mark this directory".into()),
+    },
+    KeyMapping {
+      sequence:    "`".into(),
+      action:      "mark:goto".into(),
+      description: Some("Go to a mark".into()),
+    },
+    KeyMapping {
+      sequence:    "Q".into(),
+      action:      "macro:record".into(),
+      description: Some("Record a macro / stop recording".into()),
+    },
+    KeyMapping {
+      sequence:    "@".into(),
+      action:      "macro:play".into(),
+      description: Some("Play a macro".into()),
+    },
     KeyMapping {
       sequence:    "gg".into(),
       action:      "nav:top".into(),
@@ -56,6 +191,37 @@ pub fn rust_default_keymaps() -> Vec<KeyMapping>
       action:      "nav:bottom".into(),
       description: Some("Go to bottom".into()),
     },
+    // Tabs
+    KeyMapping {
+      sequence:    "tn".into(),
+      action:      "tab:new".into(),
+      description: Some("New tab".into()),
+    },
+    KeyMapping {
+      sequence:    "tc".into(),
+      action:      "tab:close".into(),
+      description: Some("Close tab".into()),
+    },
+    KeyMapping {
+      sequence:    "tC".into(),
+      action:      "cmd:copy_to_other".into(),
+      description: Some("Copy selection to other tab".into()),
+    },
+    KeyMapping {
+      sequence:    "tM".into(),
+      action:      "cmd:move_to_other".into(),
+      description: Some("Move selection to other tab".into()),
+    },
+    KeyMapping {
+      sequence:    "gt".into(),
+      action:      "tab:next".into(),
+      description: Some("Next tab".into()),
+    },
+    KeyMapping {
+      sequence:    "gT".into(),
+      action:      "tab:prev".into(),
+      description: Some("Previous tab".into()),
+    },
     // Info/Display
     KeyMapping {
       sequence:    "zn".into(),
@@ -67,6 +233,31 @@ pub fn rust_default_keymaps() -> Vec<KeyMapping>
       action:      "show:size".into(),
       description: Some("Info: size".into()),
     },
+    KeyMapping {
+      sequence:    "zp".into(),
+      action:      "cmd:toggle_parent_pane".into(),
+      description: Some("Toggle parent pane".into()),
+    },
+    KeyMapping {
+      sequence:    "zv".into(),
+      action:      "cmd:toggle_preview_pane".into(),
+      description: Some("Toggle preview pane".into()),
+    },
+    KeyMapping {
+      sequence:    "zT".into(),
+      action:      "cmd:toggle_tree".into(),
+      description: Some("Toggle tree view".into()),
+    },
+    KeyMapping {
+      sequence:    "zS".into(),
+      action:      "cmd:compute_dir_sizes".into(),
+      description: Some("Compute directory sizes".into()),
+    },
+    KeyMapping {
+      sequence:    "zF".into(),
+      action:      "cmd:toggle_flatten".into(),
+      description: Some("Toggle flat view".into()),
+    },
     KeyMapping {
       sequence:    "zc".into(),
       action:      "show:created".into(),
@@ -99,11 +290,96 @@ pub fn rust_default_keymaps() -> Vec<KeyMapping>
       description: Some("Show Output".into()),
     },
     // Find
+    KeyMapping {
+      sequence:    "<C-e>".into(),
+      action:      "preview:scroll_down".into(),
+      description: Some("Scroll preview down".into()),
+    },
+    KeyMapping {
+      sequence:    "<C-y>".into(),
+      action:      "preview:scroll_up".into(),
+      description: Some("Scroll preview up".into()),
+    },
+    KeyMapping {
+      sequence:    "<C-d>".into(),
+      action:      "preview:page_down".into(),
+      description: Some("Preview page down".into()),
+    },
+    KeyMapping {
+      sequence:    "<C-u>".into(),
+      action:      "preview:page_up".into(),
+      description: Some("Preview page up".into()),
+    },
+    KeyMapping {
+      sequence:    "zw".into(),
+      action:      "preview:toggle_wrap".into(),
+      description: Some("Toggle preview wrap".into()),
+    },
+    KeyMapping {
+      sequence:    "gp".into(),
+      action:      "preview:focus".into(),
+      description: Some("Focus preview".into()),
+    },
     KeyMapping {
       sequence:    "/".into(),
       action:      "cmd:find".into(),
       description: Some("Find in current".into()),
     },
+    KeyMapping {
+      sequence:    "F".into(),
+      action:      "cmd:filter_prompt".into(),
+      description: Some("Filter current pane".into()),
+    },
+    KeyMapping {
+      sequence:    "gf".into(),
+      action:      "cmd:find_files".into(),
+      description: Some("Find file in tree".into()),
+    },
+    KeyMapping {
+      sequence:    "<C-p>".into(),
+      action:      "cmd:palette".into(),
+      description: Some("Command palette".into()),
+    },
+    KeyMapping {
+      sequence:    "<F1>".into(),
+      action:      "cmd:help".into(),
+      description: Some("Help".into()),
+    },
+    KeyMapping {
+      sequence:    "gz".into(),
+      action:      "cmd:z".into(),
+      description: Some("Jump to a visited directory".into()),
+    },
+    KeyMapping {
+      sequence:    "gm".into(),
+      action:      "cmd:marks".into(),
+      description: Some("Bookmarks".into()),
+    },
+    KeyMapping {
+      sequence:    "gM".into(),
+      action:      "cmd:mounts".into(),
+      description: Some("Mounted filesystems".into()),
+    },
+    KeyMapping {
+      sequence:    "gD".into(),
+      action:      "cmd:drives".into(),
+      description: Some("Drive list".into()),
+    },
+    KeyMapping {
+      sequence:    "=".into(),
+      action:      "cmd:set_permissions".into(),
+      description: Some("Change permissions".into()),
+    },
+    KeyMapping {
+      sequence:    "gi".into(),
+      action:      "cmd:file_info".into(),
+      description: Some("File info".into()),
+    },
+    KeyMapping {
+      sequence:    "gh".into(),
+      action:      "cmd:checksum".into(),
+      description: Some("SHA-256 checksum".into()),
+    },
     KeyMapping {
       sequence:    "n".into(),
       action:      "cmd:next".into(),
@@ -114,6 +390,11 @@ pub fn rust_default_keymaps() -> Vec<KeyMapping>
       action:      "cmd:prev".into(),
       description: Some("Find previous".into()),
     },
+    KeyMapping {
+      sequence:    "N".into(),
+      action:      "cmd:prev".into(),
+      description: Some("Find previous".into()),
+    },
     // Theme picker
     KeyMapping {
       sequence:    "ut".into(),
@@ -136,10 +417,55 @@ pub fn rust_default_keymaps() -> Vec<KeyMapping>
       action:      "cmd:rename".into(),
       description: Some("Rename selected".into()),
     },
+    KeyMapping {
+      sequence:    "R".into(),
+      action:      "cmd:rename_editor".into(),
+      description: Some("Rename selected in $EDITOR".into()),
+    },
+    KeyMapping {
+      sequence:    "o".into(),
+      action:      "cmd:open".into(),
+      description: Some("Open with configured opener".into()),
+    },
+    KeyMapping {
+      sequence:    "gl".into(),
+      action:      "cmd:open_at_line".into(),
+      description: Some("Open at the preview's line".into()),
+    },
+    KeyMapping {
+      sequence:    "S".into(),
+      action:      "cmd:open_shell".into(),
+      description: Some("Open a shell in the current directory".into()),
+    },
     KeyMapping {
       sequence:    "D".into(),
-      action:      "cmd:delete".into(),
-      description: Some("Delete selected".into()),
+      action:      "cmd:delete_to_trash".into(),
+      description: Some("Move selected to trash".into()),
+    },
+    KeyMapping {
+      sequence:    "zj".into(),
+      action:      "cmd:jobs".into(),
+      description: Some("Show Jobs".into()),
+    },
+    KeyMapping {
+      sequence:    "ze".into(),
+      action:      "cmd:extract_here".into(),
+      description: Some("Extract archive member here".into()),
+    },
+    KeyMapping {
+      sequence:    "zx".into(),
+      action:      "cmd:extract".into(),
+      description: Some("Extract archive into a folder".into()),
+    },
+    KeyMapping {
+      sequence:    "zA".into(),
+      action:      "cmd:compress".into(),
+      description: Some("Compress into an archive".into()),
+    },
+    KeyMapping {
+      sequence:    "zt".into(),
+      action:      "cmd:trash".into(),
+      description: Some("Show Trash".into()),
     },
     // Selection
     KeyMapping {
@@ -152,7 +478,32 @@ pub fn rust_default_keymaps() -> Vec<KeyMapping>
       action:      "cmd:select_clear".into(),
       description: Some("Clear selected".into()),
     },
+    KeyMapping {
+      sequence:    "V".into(),
+      action:      "cmd:select_all".into(),
+      description: Some("Select all".into()),
+    },
+    KeyMapping {
+      sequence:    "I".into(),
+      action:      "cmd:invert_selection".into(),
+      description: Some("Invert selection".into()),
+    },
+    KeyMapping {
+      sequence:    "gv".into(),
+      action:      "cmd:selections".into(),
+      description: Some("Show selections".into()),
+    },
     // Clipboard
+    KeyMapping {
+      sequence:    "\"".into(),
+      action:      "register:select".into(),
+      description: Some("Use a register for the next copy/move/paste".into()),
+    },
+    KeyMapping {
+      sequence:    "zr".into(),
+      action:      "cmd:registers".into(),
+      description: Some("Show registers".into()),
+    },
     KeyMapping {
       sequence:    "c".into(),
       action:      "clipboard:copy".into(),
@@ -168,6 +519,37 @@ pub fn rust_default_keymaps() -> Vec<KeyMapping>
       action:      "clipboard:paste".into(),
       description: Some("Paste clipboard".into()),
     },
+    KeyMapping {
+      sequence:    "pl".into(),
+      action:      "clipboard:paste_symlink".into(),
+      description: Some("Paste as symlinks".into()),
+    },
+    KeyMapping {
+      sequence:    "ph".into(),
+      action:      "clipboard:paste_hardlink".into(),
+      description: Some("Paste as hard links".into()),
+    },
+    // Yank to the system clipboard
+    KeyMapping {
+      sequence:    "yp".into(),
+      action:      "cmd:yank_path".into(),
+      description: Some("Yank path".into()),
+    },
+    KeyMapping {
+      sequence:    "yn".into(),
+      action:      "cmd:yank_name".into(),
+      description: Some("Yank name".into()),
+    },
+    KeyMapping {
+      sequence:    "yd".into(),
+      action:      "cmd:yank_dir".into(),
+      description: Some("Yank current directory".into()),
+    },
+    KeyMapping {
+      sequence:    "yc".into(),
+      action:      "cmd:yank_contents".into(),
+      description: Some("Yank file contents".into()),
+    },
     // Overlays
     KeyMapping {
       sequence:    "<Esc>".into(),
@@ -228,6 +610,7 @@ pub fn default_theme() -> UiTheme
     selection_bar_fg:      Some("cyan".into()),
     selection_bar_copy_fg: Some("green".into()),
     selection_bar_move_fg: Some("yellow".into()),
+    entry_colors:          Vec::new(),
   }
 }
 
@@ -12,10 +12,17 @@ use super::{
   ConfigPaths,
   KeyMapping,
   LuaEngine,
+  plugins::Previewers,
 };
 
-type ConfigArtifacts =
-  (Config, Vec<KeyMapping>, Option<(LuaEngine, RegistryKey, Vec<RegistryKey>)>);
+/// A loaded config, its keymaps and, when Lua defined previewers or
+/// actions, the engine with their registry keys (previewers in the order
+/// they are asked).
+pub type ConfigArtifacts = (
+  Config,
+  Vec<KeyMapping>,
+  Option<(LuaEngine, Vec<RegistryKey>, Vec<RegistryKey>)>,
+);
 
 pub fn load_config(paths: &ConfigPaths) -> io::Result<ConfigArtifacts>
 {
@@ -26,8 +33,7 @@ pub fn load_config(paths: &ConfigPaths) -> io::Result<ConfigArtifacts>
   let config_acc = Rc::new(RefCell::new(Config::default()));
   let keymaps_acc: Rc<RefCell<Vec<KeyMapping>>> =
     Rc::new(RefCell::new(Vec::new()));
-  let previewer_key_acc: Rc<RefCell<Option<RegistryKey>>> =
-    Rc::new(RefCell::new(None));
+  let previewers_acc = Rc::new(RefCell::new(Previewers::default()));
   let lua_action_keys_acc: Rc<RefCell<Vec<RegistryKey>>> =
     Rc::new(RefCell::new(Vec::new()));
 
@@ -35,7 +41,7 @@ pub fn load_config(paths: &ConfigPaths) -> io::Result<ConfigArtifacts>
     lua,
     Rc::clone(&config_acc),
     Rc::clone(&keymaps_acc),
-    Rc::clone(&previewer_key_acc),
+    Rc::clone(&previewers_acc),
     Rc::clone(&lua_action_keys_acc),
     Some(paths.root.clone()),
   )
@@ -48,6 +54,7 @@ pub fn load_config(paths: &ConfigPaths) -> io::Result<ConfigArtifacts>
     let mut maps = keymaps_acc.borrow_mut();
     maps.extend(super::defaults::rust_default_keymaps());
   }
+  let plugins = super::plugins::load_plugins(lua, &paths.root, &previewers_acc);
 
   if paths.exists
   {
@@ -72,25 +79,16 @@ pub fn load_config(paths: &ConfigPaths) -> io::Result<ConfigArtifacts>
   let cfg = config_acc.borrow().clone();
   let mut cfg = cfg;
   super::defaults::apply_config_defaults(&mut cfg);
-  let maps = keymaps_acc.borrow().clone();
-  let key_opt = previewer_key_acc.borrow_mut().take();
+  cfg.plugins = plugins;
+  let maps = super::defaults::finish_keymaps(
+    keymaps_acc.borrow().clone(),
+    &mut cfg.keys,
+  );
+  let previewers = super::plugins::chain_previewers(previewers_acc.take());
   let action_keys = std::mem::take(&mut *lua_action_keys_acc.borrow_mut());
-  let engine_opt = if key_opt.is_some() || !action_keys.is_empty()
+  let engine_opt = if !previewers.is_empty() || !action_keys.is_empty()
   {
-    let key = match key_opt
-    {
-      Some(k) => k,
-      None =>
-      {
-        let f: mlua::Function = lua
-          .create_function(|_, _ctx: mlua::Value| Ok(mlua::Value::Nil))
-          .map_err(|e| io_err(format!("create noop previewer failed: {e}")))?;
-        lua
-          .create_registry_value(f)
-          .map_err(|e| io_err(format!("registry noop previewer failed: {e}")))?
-      }
-    };
-    Some((engine, key, action_keys))
+    Some((engine, previewers, action_keys))
   }
   else
   {
@@ -112,8 +110,7 @@ pub fn load_config_from_code(
   let config_acc = Rc::new(RefCell::new(Config::default()));
   let keymaps_acc: Rc<RefCell<Vec<KeyMapping>>> =
     Rc::new(RefCell::new(Vec::new()));
-  let previewer_key_acc: Rc<RefCell<Option<RegistryKey>>> =
-    Rc::new(RefCell::new(None));
+  let previewers_acc = Rc::new(RefCell::new(Previewers::default()));
   let lua_action_keys_acc: Rc<RefCell<Vec<RegistryKey>>> =
     Rc::new(RefCell::new(Vec::new()));
   let config_root = root.map(|p| p.to_path_buf());
@@ -122,7 +119,7 @@ pub fn load_config_from_code(
     lua,
     Rc::clone(&config_acc),
     Rc::clone(&keymaps_acc),
-    Rc::clone(&previewer_key_acc),
+    Rc::clone(&previewers_acc),
     Rc::clone(&lua_action_keys_acc),
     config_root.clone(),
   )
@@ -143,6 +140,11 @@ pub fn load_config_from_code(
     let mut maps = keymaps_acc.borrow_mut();
     maps.extend(super::defaults::rust_default_keymaps());
   }
+  let plugins = match config_root.as_ref()
+  {
+    Some(root) => super::plugins::load_plugins(lua, root, &previewers_acc),
+    None => Vec::new(),
+  };
 
   crate::trace::log("[lua] exec inline init.lua");
   lua.load(code).set_name("inline init.lua").exec().map_err(|e| {
@@ -152,25 +154,16 @@ pub fn load_config_from_code(
 
   let mut cfg = config_acc.borrow().clone();
   super::defaults::apply_config_defaults(&mut cfg);
-  let maps = keymaps_acc.borrow().clone();
-  let key_opt = previewer_key_acc.borrow_mut().take();
+  cfg.plugins = plugins;
+  let maps = super::defaults::finish_keymaps(
+    keymaps_acc.borrow().clone(),
+    &mut cfg.keys,
+  );
+  let previewers = super::plugins::chain_previewers(previewers_acc.take());
   let action_keys = std::mem::take(&mut *lua_action_keys_acc.borrow_mut());
-  let engine_opt = if key_opt.is_some() || !action_keys.is_empty()
+  let engine_opt = if !previewers.is_empty() || !action_keys.is_empty()
   {
-    let key = match key_opt
-    {
-      Some(k) => k,
-      None =>
-      {
-        let f: mlua::Function = lua
-          .create_function(|_, _ctx: mlua::Value| Ok(mlua::Value::Nil))
-          .map_err(|e| io_err(format!("create noop previewer failed: {e}")))?;
-        lua
-          .create_registry_value(f)
-          .map_err(|e| io_err(format!("registry noop previewer failed: {e}")))?
-      }
-    };
-    Some((engine, key, action_keys))
+    Some((engine, previewers, action_keys))
   }
   else
   {
@@ -12,6 +12,8 @@ use std::{
 
 use super::{
   Config,
+  FooterSegment,
+  IconsConfig,
   UiPanes,
   load_theme_table_from_path,
   merge_theme_table,
@@ -22,7 +24,7 @@ pub(crate) fn install_lsv_api(
   lua: &Lua,
   config_acc: Rc<RefCell<Config>>,
   maps: Rc<RefCell<Vec<super::KeyMapping>>>,
-  previewer_key_out: Rc<RefCell<Option<mlua::RegistryKey>>>,
+  previewers_out: Rc<RefCell<super::plugins::Previewers>>,
   lua_action_keys_out: Rc<RefCell<Vec<mlua::RegistryKey>>>,
   config_root: Option<std::path::PathBuf>,
 ) -> io::Result<()>
@@ -284,6 +286,20 @@ pub(crate) fn install_lsv_api(
               }
             }
           }
+          if let Ok(colors_tbl) = icons_tbl.get::<Table>("colors")
+          {
+            for (k, v) in colors_tbl.pairs::<String, String>().flatten()
+            {
+              for name in k.split([',', '|', ';', '/'])
+              {
+                let n = name.trim();
+                if !n.is_empty()
+                {
+                  icons.colors.insert(n.to_lowercase(), v.clone());
+                }
+              }
+            }
+          }
           cfg_mut.icons = icons;
         }
         if let Ok(keys_tbl) = t.get::<Table>("keys")
@@ -293,11 +309,41 @@ pub(crate) fn install_lsv_api(
           {
             keys.sequence_timeout_ms = ms;
           }
+          if let Some(b) = keys_tbl.get::<Option<bool>>("defaults")?
+          {
+            keys.defaults = b;
+          }
+          if let Ok(leader) = keys_tbl.get::<String>("leader")
+          {
+            keys.leader = leader;
+          }
+          if let Ok(groups) = keys_tbl.get::<Table>("groups")
+          {
+            for pair in groups.pairs::<String, String>()
+            {
+              let (prefix, name) = pair?;
+              keys.groups.insert(prefix, name);
+            }
+          }
           cfg_mut.keys = keys;
         }
         if let Ok(ui_tbl) = t.get::<Table>("ui")
         {
           merge_ui_table(lua, theme_root.as_deref(), &ui_tbl, &mut cfg_mut)?;
+          merge_footer_table(lua, &ui_tbl, &mut cfg_mut, &actions_in_cfg)?;
+        }
+
+        if let Ok(openers_tbl) = t.get::<Table>("openers")
+        {
+          cfg_mut.openers = parse_openers(&openers_tbl)?;
+        }
+        if let Ok(previewers_tbl) = t.get::<Table>("previewers")
+        {
+          cfg_mut.previewers = parse_previewers(&previewers_tbl)?;
+        }
+        if let Some(shell) = parse_command_shell(&t)?
+        {
+          cfg_mut.command_shell = shell;
         }
 
         // Top-level actions table (collect both Lua fn and string actions)
@@ -327,6 +373,23 @@ pub(crate) fn install_lsv_api(
                 });
                 continue;
               }
+              // Shell command: cmd = "...", mode = "output" | ...
+              if let Ok(cmd) = t.get::<String>("cmd")
+              {
+                let keymap = t
+                  .get::<String>("keymap")
+                  .map_err(|e| LuaError::RuntimeError(e.to_string()))?;
+                let mode = command_mode(t.get("mode")?)?;
+                let desc = t.get::<String>("description").ok();
+                let idx = cfg_mut.shell_commands.len();
+                maps_in_cfg.borrow_mut().push(super::KeyMapping {
+                  sequence:    keymap,
+                  action:      format!("shell:{}", idx),
+                  description: desc.or_else(|| Some(cmd.clone())),
+                });
+                cfg_mut.shell_commands.push(super::MappedCommand { cmd, mode });
+                continue;
+              }
               // String action
               if let (Ok(kseq), Ok(action_str)) =
                 (t.get::<String>("keymap"), t.get::<String>("action"))
@@ -362,16 +425,206 @@ pub(crate) fn install_lsv_api(
     .map_err(|e| io::Error::other(e.to_string()))?;
 
   // set_previewer(function)
-  let prev_out = Rc::clone(&previewer_key_out);
+  let prev_out = Rc::clone(&previewers_out);
   let set_previewer_fn = lua
     .create_function(move |lua, func: mlua::Function| {
       let key = lua.create_registry_value(func)?;
-      *prev_out.borrow_mut() = Some(key);
+      prev_out.borrow_mut().main = Some(key);
+      Ok(true)
+    })
+    .map_err(|e| io::Error::other(e.to_string()))?;
+
+  // lsv.add_previewer(fn, { priority = n }): one more previewer in the chain
+  let added_out = Rc::clone(&previewers_out);
+  let add_previewer_fn = lua
+    .create_function(
+      move |lua, (func, opts): (mlua::Function, Option<Table>)| {
+        let priority = super::plugins::previewer_priority(opts)?;
+        let key = lua.create_registry_value(func)?;
+        added_out.borrow_mut().added.push((priority, key));
+        Ok(true)
+      },
+    )
+    .map_err(|e| io::Error::other(e.to_string()))?;
+
+  // lsv.on_event(name, fn): run fn as an action when the event happens
+  let actions_for_events = Rc::clone(&lua_action_keys_out);
+  let config_for_events = Rc::clone(&config_acc);
+  let on_event_fn = lua
+    .create_function(move |lua, (name, func): (String, mlua::Function)| {
+      let Some(ev) = crate::app::AppEvent::from_name(&name)
+      else
+      {
+        return Err(LuaError::RuntimeError(format!(
+          "lsv.on_event: unknown event '{}' (expected one of: {})",
+          name,
+          crate::app::AppEvent::ALL.map(|e| e.name()).join(", ")
+        )));
+      };
+      let reg = lua.create_registry_value(func)?;
+      let idx = actions_for_events.borrow().len();
+      actions_for_events.borrow_mut().push(reg);
+      config_for_events
+        .borrow_mut()
+        .events
+        .entry(ev.name().to_string())
+        .or_default()
+        .push(idx);
+      Ok(true)
+    })
+    .map_err(|e| io::Error::other(e.to_string()))?;
+
+  // lsv.register_command(name, fn, opts): run fn from the ':' prompt
+  let actions_for_commands = Rc::clone(&lua_action_keys_out);
+  let config_for_commands = Rc::clone(&config_acc);
+  let register_command_fn = lua
+    .create_function(
+      move |lua,
+            (name, func, opts): (String, mlua::Function, Option<Table>)| {
+        let valid = !name.is_empty()
+          && name.chars().all(|c| {
+            c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-'
+          });
+        if !valid
+        {
+          return Err(LuaError::RuntimeError(format!(
+            "lsv.register_command: invalid name '{}' (use a-z, 0-9, '_' and \
+             '-')",
+            name
+          )));
+        }
+        if crate::commands::builtin_names().any(|b| b == name)
+        {
+          return Err(LuaError::RuntimeError(format!(
+            "lsv.register_command: '{}' is a built-in command",
+            name
+          )));
+        }
+        let complete = match opts
+        {
+          Some(t) =>
+          {
+            t.get::<Option<Vec<String>>>("complete")?.unwrap_or_default()
+          }
+          None => Vec::new(),
+        };
+        let reg = lua.create_registry_value(func)?;
+        let idx = actions_for_commands.borrow().len();
+        actions_for_commands.borrow_mut().push(reg);
+        let mut cfg = config_for_commands.borrow_mut();
+        // Registering a name again replaces the earlier command
+        cfg.commands.retain(|c| c.name != name);
+        cfg.commands.push(super::LuaCommand { name, action: idx, complete });
+        Ok(true)
+      },
+    )
+    .map_err(|e| io::Error::other(e.to_string()))?;
+
+  // lsv.define_column(name, fn): {name} in row templates shows fn(entry)
+  let actions_for_columns = Rc::clone(&lua_action_keys_out);
+  let config_for_columns = Rc::clone(&config_acc);
+  let define_column_fn = lua
+    .create_function(move |lua, (name, func): (String, mlua::Function)| {
+      let valid = name
+        .starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+        && name
+          .chars()
+          .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+      if !valid
+      {
+        return Err(LuaError::RuntimeError(format!(
+          "lsv.define_column: invalid name '{}' (use a-z, 0-9 and '_')",
+          name
+        )));
+      }
+      if crate::ui::row::ROW_PLACEHOLDERS.contains(&name.as_str())
+      {
+        return Err(LuaError::RuntimeError(format!(
+          "lsv.define_column: '{}' is a built-in row field",
+          name
+        )));
+      }
+      let reg = lua.create_registry_value(func)?;
+      let idx = actions_for_columns.borrow().len();
+      actions_for_columns.borrow_mut().push(reg);
+      let mut cfg = config_for_columns.borrow_mut();
+      cfg.columns.retain(|c| c.name != name);
+      cfg.columns.push(super::LuaColumn { name, action: idx });
       Ok(true)
     })
     .map_err(|e| io::Error::other(e.to_string()))?;
 
-  // lsv.map_action(keymap_or_list, description, fn)
+  // lsv.icons(map): per-extension icon and colour overrides
+  let config_for_icons = Rc::clone(&config_acc);
+  let icons_fn = lua
+    .create_function(move |_, tbl: Table| {
+      let mut cfg = config_for_icons
+        .try_borrow_mut()
+        .map_err(|e| LuaError::RuntimeError(e.to_string()))?;
+      merge_icon_overrides(&tbl, &mut cfg.icons)?;
+      Ok(true)
+    })
+    .map_err(|e| io::Error::other(e.to_string()))?;
+
+  // lsv.define_template_fn(name, fn): {lua:name} in header and row templates
+  let actions_for_templates = Rc::clone(&lua_action_keys_out);
+  let config_for_templates = Rc::clone(&config_acc);
+  let define_template_fn = lua
+    .create_function(move |lua, (name, func): (String, mlua::Function)| {
+      let valid = !name.is_empty()
+        && name
+          .chars()
+          .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+      if !valid
+      {
+        return Err(LuaError::RuntimeError(format!(
+          "lsv.define_template_fn: invalid name '{}'",
+          name
+        )));
+      }
+      let reg = lua.create_registry_value(func)?;
+      let idx = actions_for_templates.borrow().len();
+      actions_for_templates.borrow_mut().push(reg);
+      let mut cfg = config_for_templates.borrow_mut();
+      cfg.template_fns.retain(|t| t.name != name);
+      cfg.template_fns.push(super::LuaTemplateFn { name, action: idx });
+      Ok(true)
+    })
+    .map_err(|e| io::Error::other(e.to_string()))?;
+
+  // lsv.filter(fn): hide the entries for which fn(entry, dir) is false
+  let actions_for_filters = Rc::clone(&lua_action_keys_out);
+  let config_for_filters = Rc::clone(&config_acc);
+  let filter_fn = lua
+    .create_function(move |lua, func: mlua::Function| {
+      let reg = lua.create_registry_value(func)?;
+      let idx = actions_for_filters.borrow().len();
+      actions_for_filters.borrow_mut().push(reg);
+      config_for_filters.borrow_mut().entry_filters.push(idx);
+      Ok(true)
+    })
+    .map_err(|e| io::Error::other(e.to_string()))?;
+
+  // lsv.add_dir_previewer(fn, { priority = n }): preview directories
+  let actions_for_dirs = Rc::clone(&lua_action_keys_out);
+  let config_for_dirs = Rc::clone(&config_acc);
+  let add_dir_previewer_fn = lua
+    .create_function(
+      move |lua, (func, opts): (mlua::Function, Option<Table>)| {
+        let priority = super::plugins::previewer_priority(opts)?;
+        let reg = lua.create_registry_value(func)?;
+        let idx = actions_for_dirs.borrow().len();
+        actions_for_dirs.borrow_mut().push(reg);
+        let mut cfg = config_for_dirs.borrow_mut();
+        // After those of the same priority, so they keep their order
+        let at = cfg.dir_previewers.iter().take_while(|(p, _)| *p >= priority);
+        let at = at.count();
+        cfg.dir_previewers.insert(at, (priority, idx));
+        Ok(true)
+      },
+    )
+    .map_err(|e| io::Error::other(e.to_string()))?;
+
   let actions_acc_outer = Rc::clone(&lua_action_keys_out);
   let maps_for_actions_outer = Rc::clone(&maps);
   let map_action_fn = lua
@@ -415,28 +668,85 @@ pub(crate) fn install_lsv_api(
     )
     .map_err(|e| io::Error::other(e.to_string()))?;
 
+  // lsv.map_command(keys, cmd, { mode = "...", description = "..." })
+  let config_for_shell = Rc::clone(&config_acc);
+  let maps_for_shell = Rc::clone(&maps);
+  let map_command_fn = lua
+    .create_function(
+      move |_, (keymaps_val, cmd, opts): (Value, String, Option<Table>)| {
+        let (mode, desc) = match opts
+        {
+          Some(t) => (command_mode(t.get("mode")?)?, t.get("description")?),
+          None => (super::CommandMode::default(), None),
+        };
+        let seqs: Vec<String> = match keymaps_val
+        {
+          Value::String(s) => vec![s.to_str()?.to_string()],
+          Value::Table(t) =>
+          {
+            t.sequence_values::<String>().collect::<Result<Vec<_>, _>>()?
+          }
+          other =>
+          {
+            return Err(LuaError::RuntimeError(format!(
+              "map_command: expected a key sequence or list, got {}",
+              other.type_name()
+            )));
+          }
+        };
+        let mut cfg = config_for_shell.borrow_mut();
+        let action = format!("shell:{}", cfg.shell_commands.len());
+        let description = desc.unwrap_or_else(|| cmd.clone());
+        for sequence in seqs
+        {
+          maps_for_shell.borrow_mut().push(super::KeyMapping {
+            sequence,
+            action: action.clone(),
+            description: Some(description.clone()),
+          });
+        }
+        cfg.shell_commands.push(super::MappedCommand { cmd, mode });
+        Ok(true)
+      },
+    )
+    .map_err(|e| io::Error::other(e.to_string()))?;
+
   // Wire helpers
   lsv.set("config", config_fn).map_err(|e| io::Error::other(e.to_string()))?;
   lsv.set("mapkey", mapkey_fn).map_err(|e| io::Error::other(e.to_string()))?;
   lsv
     .set("set_previewer", set_previewer_fn)
     .map_err(|e| io::Error::other(e.to_string()))?;
+  lsv
+    .set("add_previewer", add_previewer_fn)
+    .map_err(|e| io::Error::other(e.to_string()))?;
+  lsv
+    .set("add_dir_previewer", add_dir_previewer_fn)
+    .map_err(|e| io::Error::other(e.to_string()))?;
   lsv
     .set("map_action", map_action_fn)
     .map_err(|e| io::Error::other(e.to_string()))?;
+  lsv
+    .set("map_command", map_command_fn)
+    .map_err(|e| io::Error::other(e.to_string()))?;
+  lsv
+    .set("on_event", on_event_fn)
+    .map_err(|e| io::Error::other(e.to_string()))?;
+  lsv
+    .set("register_command", register_command_fn)
+    .map_err(|e| io::Error::other(e.to_string()))?;
+  lsv
+    .set("define_column", define_column_fn)
+    .map_err(|e| io::Error::other(e.to_string()))?;
+  lsv
+    .set("define_template_fn", define_template_fn)
+    .map_err(|e| io::Error::other(e.to_string()))?;
+  lsv.set("filter", filter_fn).map_err(|e| io::Error::other(e.to_string()))?;
+  lsv.set("icons", icons_fn).map_err(|e| io::Error::other(e.to_string()))?;
 
   // lsv.quote
   let quote_fn = lua
-    .create_function(|_, s: String| {
-      #[cfg(windows)]
-      {
-        Ok(format!("\"{}\"", s.replace('"', "\"\"")))
-      }
-      #[cfg(not(windows))]
-      {
-        Ok(format!("'{}'", s.replace('\'', "'\\''")))
-      }
-    })
+    .create_function(|_, s: String| Ok(crate::util::shell_quote(&s)))
     .map_err(|e| io::Error::other(e.to_string()))?;
   lsv.set("quote", quote_fn).map_err(|e| io::Error::other(e.to_string()))?;
 
@@ -486,7 +796,258 @@ pub(crate) fn install_lsv_api(
   Ok(())
 }
 
+/// A rule's `cmd`, required and non-empty.
+fn rule_cmd(
+  t: &Table,
+  what: &str,
+  i: usize,
+) -> Result<String, LuaError>
+{
+  let cmd = t.get::<Option<String>>("cmd")?.filter(|c| !c.trim().is_empty());
+  cmd.ok_or_else(|| {
+    LuaError::RuntimeError(format!("{}[{}]: missing cmd", what, i + 1))
+  })
+}
+
+/// A rule's `ext`: one extension or a list, lowercased without the dot.
+fn rule_ext(
+  t: &Table,
+  what: &str,
+  i: usize,
+) -> Result<Vec<String>, LuaError>
+{
+  let ext = match t.get::<Value>("ext")?
+  {
+    Value::Nil => Vec::new(),
+    Value::String(s) => vec![s.to_str()?.to_string()],
+    Value::Table(list) =>
+    {
+      list.sequence_values::<String>().collect::<Result<Vec<_>, _>>()?
+    }
+    other =>
+    {
+      return Err(LuaError::RuntimeError(format!(
+        "{}[{}].ext: expected a string or list, got {}",
+        what,
+        i + 1,
+        other.type_name()
+      )));
+    }
+  };
+  Ok(ext.iter().map(|e| e.trim_start_matches('.').to_lowercase()).collect())
+}
+
+// openers = { { ext = {...}, mime = "...", name = "...", cmd = "...",
+// terminal = bool, detach = bool }, ... }
+fn parse_openers(tbl: &Table) -> Result<Vec<super::OpenerRule>, LuaError>
+{
+  let mut rules = Vec::new();
+  for (i, rule_tbl) in tbl.sequence_values::<Table>().enumerate()
+  {
+    let t = rule_tbl?;
+    let cmd = rule_cmd(&t, "openers", i)?;
+    rules.push(super::OpenerRule {
+      ext: rule_ext(&t, "openers", i)?,
+      mime: t.get("mime")?,
+      name: t.get("name")?,
+      cmd,
+      terminal: t.get::<Option<bool>>("terminal")?.unwrap_or(false),
+      detach: t.get::<Option<bool>>("detach")?.unwrap_or(false),
+      line: t.get("line")?,
+    });
+  }
+  Ok(rules)
+}
+
+// previewers = { { ext = {...}, mime = "...", name = "...", cmd = "..." }, ...
+// }
+fn parse_previewers(tbl: &Table)
+-> Result<Vec<super::PreviewerRule>, LuaError>
+{
+  let mut rules = Vec::new();
+  for (i, rule_tbl) in tbl.sequence_values::<Table>().enumerate()
+  {
+    let t = rule_tbl?;
+    let cmd = rule_cmd(&t, "previewers", i)?;
+    rules.push(super::PreviewerRule {
+      ext: rule_ext(&t, "previewers", i)?,
+      mime: t.get("mime")?,
+      name: t.get("name")?,
+      cmd,
+    });
+  }
+  Ok(rules)
+}
+
+// mode = "background" | "output" | "preview" | "terminal"; output when nil
+fn command_mode(mode: Option<String>) -> Result<super::CommandMode, LuaError>
+{
+  let Some(mode) = mode
+  else
+  {
+    return Ok(super::CommandMode::default());
+  };
+  super::CommandMode::parse(&mode).ok_or_else(|| {
+    LuaError::RuntimeError(format!(
+      "mode: expected background, output, preview or terminal, got '{}'",
+      mode
+    ))
+  })
+}
+
+// command_shell = "bash" or { program = "bash", login = bool, env = {...} }
+fn parse_command_shell(
+  t: &Table
+) -> Result<Option<super::CommandShell>, LuaError>
+{
+  let tbl = match t.get::<Value>("command_shell")?
+  {
+    Value::Nil => return Ok(None),
+    Value::String(s) =>
+    {
+      return Ok(Some(super::CommandShell {
+        program: Some(s.to_str()?.to_string()),
+        ..Default::default()
+      }));
+    }
+    Value::Table(tbl) => tbl,
+    other =>
+    {
+      return Err(LuaError::RuntimeError(format!(
+        "command_shell: expected a string or table, got {}",
+        other.type_name()
+      )));
+    }
+  };
+  let env = match tbl.get::<Option<Table>>("env")?
+  {
+    Some(list) =>
+    {
+      Some(list.sequence_values::<String>().collect::<Result<Vec<_>, _>>()?)
+    }
+    None => None,
+  };
+  Ok(Some(super::CommandShell {
+    program: tbl
+      .get::<Option<String>>("program")?
+      .filter(|p| !p.trim().is_empty()),
+    login: tbl.get::<Option<bool>>("login")?.unwrap_or(false),
+    env,
+  }))
+}
+
+// ui.footer: left/right are templates or functions returning one
+fn merge_footer_table(
+  lua: &Lua,
+  ui_tbl: &Table,
+  cfg_mut: &mut Config,
+  actions: &RefCell<Vec<mlua::RegistryKey>>,
+) -> Result<(), LuaError>
+{
+  let footer_tbl = match ui_tbl.get::<Value>("footer")?
+  {
+    Value::Nil => return Ok(()),
+    Value::Boolean(false) =>
+    {
+      cfg_mut.ui.footer = None;
+      return Ok(());
+    }
+    Value::Table(t) => t,
+    other =>
+    {
+      return Err(LuaError::RuntimeError(format!(
+        "ui.footer: expected a table, got {}",
+        other.type_name()
+      )));
+    }
+  };
+  let mut footer = cfg_mut.ui.footer.clone().unwrap_or_default();
+  for side in ["left", "right"]
+  {
+    let seg = match footer_tbl.get::<Value>(side)?
+    {
+      Value::Nil => continue,
+      Value::String(s) => FooterSegment::Template(s.to_str()?.to_string()),
+      Value::Function(func) =>
+      {
+        let reg = lua.create_registry_value(func)?;
+        let idx = actions.borrow().len();
+        actions.borrow_mut().push(reg);
+        FooterSegment::Lua(idx)
+      }
+      other =>
+      {
+        return Err(LuaError::RuntimeError(format!(
+          "ui.footer.{}: expected a string or function, got {}",
+          side,
+          other.type_name()
+        )));
+      }
+    };
+    if side == "left"
+    {
+      footer.left = Some(seg);
+    }
+    else
+    {
+      footer.right = Some(seg);
+    }
+  }
+  if let Ok(s) = footer_tbl.get::<String>("bg")
+  {
+    footer.bg = Some(s);
+  }
+  if let Ok(s) = footer_tbl.get::<String>("fg")
+  {
+    footer.fg = Some(s);
+  }
+  cfg_mut.ui.footer = Some(footer);
+  Ok(())
+}
+
 // Small helper to merge UI table (moved from config.rs; kept private here)
+/// Merge `lsv.icons { ext = "glyph", ["a,b"] = { icon = ..., color = ... } }`
+/// into the icon overrides.
+fn merge_icon_overrides(
+  tbl: &Table,
+  icons: &mut IconsConfig,
+) -> Result<(), LuaError>
+{
+  for pair in tbl.pairs::<String, Value>()
+  {
+    let (k, v) = pair?;
+    let (icon, color) = match v
+    {
+      Value::String(s) => (Some(s.to_str()?.to_string()), None),
+      Value::Table(t) =>
+      {
+        (t.get::<String>("icon").ok(), t.get::<String>("color").ok())
+      }
+      _ =>
+      {
+        return Err(LuaError::RuntimeError(format!(
+          "lsv.icons: '{}' must be a string or {{ icon, color }}",
+          k
+        )));
+      }
+    };
+    for name in
+      k.split([',', '|', ';', '/']).map(str::trim).filter(|n| !n.is_empty())
+    {
+      let name = name.to_lowercase();
+      if let Some(icon) = &icon
+      {
+        icons.extensions.insert(name.clone(), icon.clone());
+      }
+      if let Some(color) = &color
+      {
+        icons.colors.insert(name, color.clone());
+      }
+    }
+  }
+  Ok(())
+}
+
 fn merge_ui_table(
   lua: &Lua,
   theme_root: Option<&std::path::Path>,
@@ -515,6 +1076,43 @@ fn merge_ui_table(
     }
     cfg_mut.ui.panes = Some(panes);
   }
+  // Shorthand for icons.enabled, picking the built-in Nerd Font icons
+  if let Ok(Value::Boolean(b)) = ui_tbl.get::<Value>("icons")
+  {
+    cfg_mut.icons.enabled = b;
+    if b && cfg_mut.icons.preset.is_none()
+    {
+      cfg_mut.icons.preset = Some(String::from("nerdfont"));
+    }
+  }
+  // Shorthand for `panes`: { parent, current, preview }
+  if let Ok(ratios) = ui_tbl.get::<Table>("pane_ratios")
+  {
+    let values = ratios
+      .sequence_values::<u16>()
+      .collect::<Result<Vec<u16>, _>>()
+      .ok()
+      .filter(|v| v.len() == 3)
+      .ok_or_else(|| {
+        LuaError::RuntimeError(
+          "ui.pane_ratios must list three numbers: {parent, current, preview}"
+            .to_string(),
+        )
+      })?;
+    cfg_mut.ui.panes = Some(UiPanes {
+      parent:  values[0],
+      current: values[1],
+      preview: values[2],
+    });
+  }
+  if let Ok(Some(b)) = ui_tbl.get::<Option<bool>>("show_parent")
+  {
+    cfg_mut.ui.show_parent = b;
+  }
+  if let Ok(Some(b)) = ui_tbl.get::<Option<bool>>("show_preview")
+  {
+    cfg_mut.ui.show_preview = b;
+  }
   if let Ok(b) = ui_tbl.get::<bool>("show_hidden")
   {
     cfg_mut.ui.show_hidden = b;
@@ -523,10 +1121,174 @@ fn merge_ui_table(
   {
     cfg_mut.ui.max_list_items = n as usize;
   }
+  if let Ok(Some(b)) = ui_tbl.get::<Option<bool>>("session")
+  {
+    cfg_mut.ui.session = b;
+  }
+  if let Ok(Some(b)) = ui_tbl.get::<Option<bool>>("zoxide")
+  {
+    cfg_mut.ui.zoxide = b;
+  }
+  if let Ok(Some(b)) = ui_tbl.get::<Option<bool>>("auto_reload")
+  {
+    cfg_mut.ui.auto_reload = b;
+  }
+  if let Ok(s) = ui_tbl.get::<String>("image_protocol")
+  {
+    if !crate::core::image_protocol::is_valid_setting(&s)
+    {
+      return Err(LuaError::RuntimeError(format!(
+        "ui.image_protocol: unknown protocol '{}' (expected auto, kitty, \
+         iterm2, sixel or halfblocks)",
+        s
+      )));
+    }
+    cfg_mut.ui.image_protocol = Some(s);
+  }
+  if let Ok(tbl) = ui_tbl.get::<Table>("image_protocols")
+  {
+    for pair in tbl.pairs::<String, String>()
+    {
+      let (term, protocol) = pair?;
+      if crate::core::image_protocol::ImageProtocol::parse(&protocol).is_none()
+      {
+        return Err(LuaError::RuntimeError(format!(
+          "ui.image_protocols.{}: unknown protocol '{}'",
+          term, protocol
+        )));
+      }
+      cfg_mut.ui.image_protocols.insert(term.to_ascii_lowercase(), protocol);
+    }
+  }
+  match ui_tbl.get::<Value>("thumbnails")?
+  {
+    Value::Nil =>
+    {}
+    Value::Boolean(b) => cfg_mut.ui.thumbnails.enabled = b,
+    Value::Table(tbl) =>
+    {
+      cfg_mut.ui.thumbnails.enabled = true;
+      for (key, slot) in [
+        ("video", &mut cfg_mut.ui.thumbnails.video),
+        ("pdf", &mut cfg_mut.ui.thumbnails.pdf),
+        ("svg", &mut cfg_mut.ui.thumbnails.svg),
+        ("heif", &mut cfg_mut.ui.thumbnails.heif),
+      ]
+      {
+        match tbl.get::<Value>(key)?
+        {
+          Value::Nil =>
+          {}
+          Value::Boolean(false) => *slot = None,
+          Value::String(s) => *slot = Some(s.to_str()?.to_string()),
+          _ =>
+          {
+            return Err(LuaError::RuntimeError(format!(
+              "ui.thumbnails.{}: expected a command string or false",
+              key
+            )));
+          }
+        }
+      }
+      if let Some(view) = tbl.get::<Option<String>>("view")?
+      {
+        cfg_mut.ui.thumbnails.view = view;
+      }
+    }
+    _ =>
+    {
+      return Err(LuaError::RuntimeError(
+        "ui.thumbnails: expected a table or a boolean".to_string(),
+      ));
+    }
+  }
+  match ui_tbl.get::<Value>("compress")?
+  {
+    Value::Nil =>
+    {}
+    Value::Table(tbl) =>
+    {
+      for (key, slot) in [
+        ("gzip", &mut cfg_mut.ui.compress.gzip),
+        ("zstd", &mut cfg_mut.ui.compress.zstd),
+        ("zip", &mut cfg_mut.ui.compress.zip),
+      ]
+      {
+        match tbl.get::<Value>(key)?
+        {
+          Value::Nil =>
+          {}
+          Value::String(s) => *slot = s.to_str()?.to_string(),
+          _ =>
+          {
+            return Err(LuaError::RuntimeError(format!(
+              "ui.compress.{}: expected a command string",
+              key
+            )));
+          }
+        }
+      }
+    }
+    _ =>
+    {
+      return Err(LuaError::RuntimeError(
+        "ui.compress: expected a table".to_string(),
+      ));
+    }
+  }
+  if let Ok(n) = ui_tbl.get::<u64>("flatten_depth")
+  {
+    cfg_mut.ui.flatten_depth = (n as usize).max(1);
+  }
+  if let Ok(n) = ui_tbl.get::<u64>("max_messages")
+  {
+    cfg_mut.ui.max_messages = (n as usize).max(1);
+  }
+  if let Ok(n) = ui_tbl.get::<u64>("notify_ms")
+  {
+    cfg_mut.ui.notify_ms = n;
+  }
+  if let Ok(n) = ui_tbl.get::<u64>("preview_cache")
+  {
+    cfg_mut.ui.preview_cache = n as usize;
+  }
+  if let Ok(n) = ui_tbl.get::<u64>("preview_ttl")
+  {
+    cfg_mut.ui.preview_ttl = n;
+  }
+  if let Ok(n) = ui_tbl.get::<u64>("preview_timeout_ms")
+  {
+    cfg_mut.ui.preview_timeout_ms = n.max(1);
+  }
+  if let Ok(n) = ui_tbl.get::<u64>("preview_max_bytes")
+  {
+    cfg_mut.ui.preview_max_bytes = (n as usize).max(1);
+  }
   if let Ok(s) = ui_tbl.get::<String>("date_format")
   {
     cfg_mut.ui.date_format = Some(s);
   }
+  match ui_tbl.get::<Value>("relative_dates")?
+  {
+    Value::Nil =>
+    {}
+    Value::Boolean(b) => cfg_mut.ui.relative_dates = b.then_some(u64::MAX),
+    Value::Integer(n) if n >= 0 =>
+    {
+      cfg_mut.ui.relative_dates = Some((n as u64).saturating_mul(86_400))
+    }
+    Value::Number(n) if n >= 0.0 =>
+    {
+      cfg_mut.ui.relative_dates = Some((n * 86_400.0) as u64)
+    }
+    other =>
+    {
+      return Err(LuaError::RuntimeError(format!(
+        "ui.relative_dates must be true, false or a number of days (got {})",
+        other.type_name()
+      )));
+    }
+  }
   if let Ok(h_tbl) = ui_tbl.get::<Table>("header")
   {
     if let Ok(s) = h_tbl.get::<String>("left")
@@ -677,6 +1439,38 @@ fn merge_ui_table(
   {
     cfg_mut.ui.show = Some(s);
   }
+  if let Ok(s) = ui_tbl.get::<String>("line_numbers")
+  {
+    cfg_mut.ui.line_numbers = match s.as_str()
+    {
+      "none" => crate::config::LineNumbers::None,
+      "absolute" => crate::config::LineNumbers::Absolute,
+      "relative" => crate::config::LineNumbers::Relative,
+      other =>
+      {
+        return Err(LuaError::RuntimeError(format!(
+          "ui.line_numbers: expected \"none\", \"absolute\" or \"relative\", \
+           got '{}'",
+          other
+        )));
+      }
+    };
+  }
+  if let Ok(style) = ui_tbl.get::<String>("symlinks")
+  {
+    cfg_mut.ui.relative_links = match style.as_str()
+    {
+      "relative" => true,
+      "absolute" => false,
+      other =>
+      {
+        return Err(LuaError::RuntimeError(format!(
+          "ui.symlinks: expected \"absolute\" or \"relative\", got '{}'",
+          other
+        )));
+      }
+    };
+  }
   if let Ok(b) = ui_tbl.get::<bool>("confirm_delete")
   {
     cfg_mut.ui.confirm_delete = b;
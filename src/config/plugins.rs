@@ -0,0 +1,313 @@
+//! Plugins under `<config_root>/plugins/<name>/init.lua`.
+//!
+//! Each plugin's `init.lua` runs in its own global environment (reads fall
+//! back to the shared globals, writes stay in the plugin), with `require`
+//! resolving modules inside the plugin directory and `lsv.plugin` set to a
+//! handle for registering keymaps, commands, columns, event hooks and
+//! previewers for files and directories. It may return a table
+//! `{ depends = { ... }, setup = fn }`: plugins are set up after the ones
+//! they depend on, and `setup` is called with the handle. A plugin whose
+//! dependencies are missing or form a cycle is skipped. Plugins load before the
+//! user's `init.lua`, so its settings and keymaps take precedence.
+
+use std::{
+  cell::RefCell,
+  collections::HashMap,
+  path::{
+    Path,
+    PathBuf,
+  },
+  rc::Rc,
+};
+
+use mlua::{
+  Function,
+  Lua,
+  RegistryKey,
+  Table,
+  Value,
+};
+
+use super::PluginStatus;
+
+/// Previewers registered while a config loads; see [`chain_previewers`].
+#[derive(Default)]
+pub(crate) struct Previewers
+{
+  /// Set by `lsv.set_previewer`; a later call replaces it.
+  pub(crate) main:    Option<RegistryKey>,
+  /// Added by `lsv.add_previewer`, with their priorities.
+  pub(crate) added:   Vec<(i64, RegistryKey)>,
+  /// Added by plugins' `add_previewer`, in setup order.
+  pub(crate) plugins: Vec<(i64, RegistryKey)>,
+}
+
+/// `opts.priority` of `add_previewer(fn, opts)`, 0 when not given.
+pub(crate) fn previewer_priority(opts: Option<Table>) -> mlua::Result<i64>
+{
+  match opts
+  {
+    Some(t) => Ok(t.get::<Option<i64>>("priority")?.unwrap_or(0)),
+    None => Ok(0),
+  }
+}
+
+/// A plugin whose `init.lua` ran.
+struct LoadedPlugin
+{
+  name:    String,
+  depends: Vec<String>,
+  setup:   Option<Function>,
+  handle:  Table,
+}
+
+/// Plugin directories containing an `init.lua`, sorted by name.
+pub fn discover_plugins(root: &Path) -> Vec<(String, PathBuf)>
+{
+  let mut out: Vec<(String, PathBuf)> = std::fs::read_dir(root.join("plugins"))
+    .map(|rd| {
+      rd.flatten()
+        .map(|de| de.path())
+        .filter(|p| p.join("init.lua").is_file())
+        .filter_map(|p| {
+          let name = p.file_name()?.to_string_lossy().to_string();
+          Some((name, p))
+        })
+        .collect()
+    })
+    .unwrap_or_default();
+  out.sort();
+  out
+}
+
+/// Order plugins so each comes after its dependencies. Returns the indices
+/// to set up, in order, and the plugins that can't be with the reason.
+pub fn order_plugins(
+  plugins: &[(String, Vec<String>)]
+) -> (Vec<usize>, Vec<(usize, String)>)
+{
+  #[derive(Clone, Copy, PartialEq)]
+  enum Mark
+  {
+    Visiting,
+    Done,
+    Failed,
+  }
+
+  fn visit(
+    i: usize,
+    plugins: &[(String, Vec<String>)],
+    index: &HashMap<&str, usize>,
+    marks: &mut [Option<Mark>],
+    order: &mut Vec<usize>,
+    failed: &mut Vec<(usize, String)>,
+  ) -> bool
+  {
+    match marks[i]
+    {
+      Some(Mark::Done) => return true,
+      Some(Mark::Failed) => return false,
+      Some(Mark::Visiting) =>
+      {
+        marks[i] = Some(Mark::Failed);
+        failed.push((i, String::from("dependency cycle")));
+        return false;
+      }
+      None =>
+      {}
+    }
+    marks[i] = Some(Mark::Visiting);
+    for dep in &plugins[i].1
+    {
+      let reason = match index.get(dep.as_str())
+      {
+        None => Some(format!("missing dependency '{}'", dep)),
+        Some(&j) if !visit(j, plugins, index, marks, order, failed) =>
+        {
+          Some(format!("dependency '{}' failed", dep))
+        }
+        Some(_) => None,
+      };
+      if let Some(reason) = reason
+      {
+        // A cycle back to this plugin has already marked it
+        if marks[i] != Some(Mark::Failed)
+        {
+          marks[i] = Some(Mark::Failed);
+          failed.push((i, reason));
+        }
+        return false;
+      }
+    }
+    if marks[i] == Some(Mark::Failed)
+    {
+      return false;
+    }
+    marks[i] = Some(Mark::Done);
+    order.push(i);
+    true
+  }
+
+  let index: HashMap<&str, usize> =
+    plugins.iter().enumerate().map(|(i, (n, _))| (n.as_str(), i)).collect();
+  let mut marks = vec![None; plugins.len()];
+  let mut order = Vec::new();
+  let mut failed = Vec::new();
+  for i in 0..plugins.len()
+  {
+    visit(i, plugins, &index, &mut marks, &mut order, &mut failed);
+  }
+  (order, failed)
+}
+
+/// Handle passed to a plugin: its name and directory plus the `lsv`
+/// registration functions, and `add_previewer`.
+fn plugin_handle(
+  lua: &Lua,
+  lsv: &Table,
+  name: &str,
+  dir: &Path,
+  previewers: &Rc<RefCell<Previewers>>,
+) -> mlua::Result<Table>
+{
+  let handle = lua.create_table()?;
+  handle.set("name", name)?;
+  handle.set("dir", dir.to_string_lossy().to_string())?;
+  for f in [
+    "map_action",
+    "register_command",
+    "define_column",
+    "define_template_fn",
+    "filter",
+    "on_event",
+    "add_dir_previewer",
+  ]
+  {
+    handle.set(f, lsv.get::<Function>(f)?)?;
+  }
+  let previewers = Rc::clone(previewers);
+  let add_previewer = lua.create_function(
+    move |lua, (func, opts): (Function, Option<Table>)| {
+      let priority = previewer_priority(opts)?;
+      let key = lua.create_registry_value(func)?;
+      previewers.borrow_mut().plugins.push((priority, key));
+      Ok(true)
+    },
+  )?;
+  handle.set("add_previewer", add_previewer)?;
+  Ok(handle)
+}
+
+/// Run a plugin's `init.lua` in its own environment.
+fn run_plugin(
+  lua: &Lua,
+  name: &str,
+  dir: &Path,
+  previewers: &Rc<RefCell<Previewers>>,
+) -> mlua::Result<LoadedPlugin>
+{
+  let globals = lua.globals();
+  let lsv: Table = globals.get("lsv")?;
+  let handle = plugin_handle(lua, &lsv, name, dir, previewers)?;
+
+  // lsv.plugin is the handle; everything else comes from the shared lsv
+  let plugin_lsv = lua.create_table()?;
+  plugin_lsv.set("plugin", handle.clone())?;
+  let lsv_mt = lua.create_table()?;
+  lsv_mt.set("__index", lsv)?;
+  plugin_lsv.set_metatable(Some(lsv_mt))?;
+
+  let env = lua.create_table()?;
+  env.set("lsv", plugin_lsv)?;
+  env.set("require", super::require::make_require(lua, dir)?)?;
+  let env_mt = lua.create_table()?;
+  env_mt.set("__index", globals)?;
+  env.set_metatable(Some(env_mt))?;
+
+  let entry = dir.join("init.lua");
+  let code = std::fs::read_to_string(&entry).map_err(mlua::Error::external)?;
+  let ret: Value = lua
+    .load(&code)
+    .set_name(entry.to_string_lossy())
+    .set_environment(env)
+    .eval()?;
+  let (depends, setup) = match ret
+  {
+    Value::Table(t) => (
+      t.get::<Option<Vec<String>>>("depends")?.unwrap_or_default(),
+      t.get::<Option<Function>>("setup")?,
+    ),
+    _ => (Vec::new(), None),
+  };
+  Ok(LoadedPlugin { name: name.to_string(), depends, setup, handle })
+}
+
+/// Load every plugin under `root`. Previewers added by plugins are pushed to
+/// `previewers` in setup order.
+pub(crate) fn load_plugins(
+  lua: &Lua,
+  root: &Path,
+  previewers: &Rc<RefCell<Previewers>>,
+) -> Vec<PluginStatus>
+{
+  let mut statuses = Vec::new();
+  let mut loaded = Vec::new();
+  for (name, dir) in discover_plugins(root)
+  {
+    crate::trace::log(format!("[plugin] loading {}", dir.display()));
+    match run_plugin(lua, &name, &dir, previewers)
+    {
+      Ok(p) => loaded.push(p),
+      Err(e) =>
+      {
+        statuses.push(PluginStatus { name, error: Some(e.to_string()) })
+      }
+    }
+  }
+  let deps: Vec<(String, Vec<String>)> =
+    loaded.iter().map(|p| (p.name.clone(), p.depends.clone())).collect();
+  let (order, failed) = order_plugins(&deps);
+  for (i, reason) in failed
+  {
+    statuses
+      .push(PluginStatus { name: deps[i].0.clone(), error: Some(reason) });
+  }
+  for i in order
+  {
+    let p = &loaded[i];
+    let error = match p.setup.as_ref()
+    {
+      Some(setup) => setup.call::<Value>(p.handle.clone()).err(),
+      None => None,
+    };
+    statuses.push(PluginStatus {
+      name:  p.name.clone(),
+      error: error.map(|e| e.to_string()),
+    });
+  }
+  for s in &statuses
+  {
+    if let Some(e) = s.error.as_ref()
+    {
+      crate::trace::log(format!("[plugin] {} failed: {}", s.name, e));
+    }
+  }
+  statuses
+}
+
+/// Previewers in the order they are asked for a command: higher priority
+/// first, and among equal priorities the one from `lsv.set_previewer`, then
+/// `lsv.add_previewer` ones and then plugins', each in the order added.
+pub(crate) fn chain_previewers(acc: Previewers) -> Vec<RegistryKey>
+{
+  let mut chain: Vec<(i64, RegistryKey)> = acc
+    .main
+    .map(|key| (0, key))
+    .into_iter()
+    .chain(acc.added)
+    .chain(acc.plugins)
+    .collect();
+  // Stable, so equal priorities keep their order
+  chain.sort_by_key(|(priority, _)| std::cmp::Reverse(*priority));
+  chain.into_iter().map(|(_, key)| key).collect()
+}
@@ -0,0 +1,113 @@
+//! Readable reports for configs that fail to load.
+//!
+//! Lua errors arrive as one string such as `init.lua execution failed:
+//! runtime error: [string "/home/u/.config/lsv/init.lua"]:3: attempt to ...`
+//! followed by a stack traceback. [`ConfigError::parse`] pulls out the file
+//! and line the error points at so the error panel can lead with them.
+
+use std::path::PathBuf;
+
+/// A config load failure split into where it happened and what went wrong.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError
+{
+  /// Lua file the error points at, if the message names one.
+  pub file:    Option<PathBuf>,
+  pub line:    Option<usize>,
+  /// First line of the error without the location.
+  pub message: String,
+  /// Remaining lines, usually the Lua stack traceback.
+  pub details: Vec<String>,
+}
+
+/// File, line and byte range of a location within an error message.
+type Location = (PathBuf, usize, std::ops::Range<usize>);
+
+/// Find the first `[string "<file>"]:<line>:` location in `text`.
+fn find_location(text: &str) -> Option<Location>
+{
+  const OPEN: &str = "[string \"";
+  let mut from = 0;
+  while let Some(rel) = text[from..].find(OPEN)
+  {
+    let start = from + rel;
+    let name_start = start + OPEN.len();
+    from = name_start;
+    let Some(name_len) = text[name_start..].find("\"]:")
+    else
+    {
+      break;
+    };
+    let digits_start = name_start + name_len + 3;
+    let digits =
+      text[digits_start..].bytes().take_while(|b| b.is_ascii_digit()).count();
+    let Ok(line) = text[digits_start..digits_start + digits].parse::<usize>()
+    else
+    {
+      continue;
+    };
+    let mut end = digits_start + digits;
+    if text[end..].starts_with(": ")
+    {
+      end += 2;
+    }
+    let file = PathBuf::from(&text[name_start..name_start + name_len]);
+    return Some((file, line, start..end));
+  }
+  None
+}
+
+impl ConfigError
+{
+  /// Split an error message from loading the config.
+  pub fn parse(text: &str) -> Self
+  {
+    let mut lines = text.lines();
+    let first = lines.next().unwrap_or_default();
+    let details: Vec<String> = lines.map(|l| l.replace('\t', "  ")).collect();
+    let location = find_location(text);
+    let mut message = first
+      .strip_prefix("init.lua execution failed: ")
+      .unwrap_or(first)
+      .to_string();
+    if let Some((_, _, ref range)) = location
+      && let Some(loc) = first.get(range.clone())
+    {
+      message = message.replacen(loc, "", 1).trim().to_string();
+    }
+    let (file, line) = match location
+    {
+      Some((file, line, _)) => (Some(file), Some(line)),
+      None => (None, None),
+    };
+    Self { file, line, message, details }
+  }
+
+  /// `file:line` of the error, or the file alone without a line.
+  pub fn location(&self) -> Option<String>
+  {
+    let file = self.file.as_ref()?.display().to_string();
+    Some(match self.line
+    {
+      Some(n) => format!("{}:{}", file, n),
+      None => file,
+    })
+  }
+
+  /// Lines for the error panel: the location, the message, then details.
+  pub fn lines(&self) -> Vec<String>
+  {
+    let mut out = Vec::new();
+    if let Some(loc) = self.location()
+    {
+      out.push(loc);
+    }
+    out.push(self.message.clone());
+    if !self.details.is_empty()
+    {
+      out.push(String::new());
+      out.extend(self.details.iter().cloned());
+    }
+    out
+  }
+}
@@ -9,15 +9,35 @@ pub(crate) fn install_require(
   lua: &Lua,
   lua_root: &Path,
 ) -> mlua::Result<()>
+{
+  let require_fn = make_require(lua, lua_root)?;
+  let globals = lua.globals();
+  globals.set("require", require_fn)?;
+  Ok(())
+}
+
+/// A `require` loading `name` from `<lua_root>/name.lua` (dots as
+/// directory separators), refusing paths outside `lua_root`. `themes.<name>`
+/// falls back to the built-in theme when no such file exists.
+pub(crate) fn make_require(
+  lua: &Lua,
+  lua_root: &Path,
+) -> mlua::Result<mlua::Function>
 {
   let root = lua_root.to_path_buf();
-  let require_fn = lua.create_function(move |lua, name: String| {
+  lua.create_function(move |lua, name: String| {
     if name.contains("..") || name.starts_with('/')
     {
       return Err(LuaError::external("invalid module name"));
     }
     let rel_path = name.replace('.', "/");
     let path = root.join(format!("{}.lua", rel_path));
+    if !path.exists()
+      && let Some(src) =
+        name.strip_prefix("themes.").and_then(super::builtin_themes::source)
+    {
+      return lua.load(src).set_name(name).eval::<Value>();
+    }
     // Canonicalize and ensure under root
     let canon = std::fs::canonicalize(&path)
       .map_err(|e| LuaError::external(format!("{e}")))?;
@@ -31,8 +51,5 @@ pub(crate) fn install_require(
       .map_err(|e| LuaError::external(format!("{e}")))?;
     let chunk = lua.load(&code).set_name(name);
     chunk.eval::<Value>()
-  })?;
-  let globals = lua.globals();
-  globals.set("require", require_fn)?;
-  Ok(())
+  })
 }
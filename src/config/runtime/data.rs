@@ -72,6 +72,8 @@ pub struct UiData
 {
   pub panes:          UiPanesData,
   pub show_hidden:    bool,
+  pub show_parent:    bool,
+  pub show_preview:   bool,
   pub date_format:    Option<String>,
   pub display_mode:   crate::app::DisplayMode,
   pub max_list_items: usize,
@@ -123,6 +125,8 @@ pub fn to_lua_config_table(
   panes.set("preview", r)?;
   ui.set("panes", panes)?;
   ui.set("show_hidden", app.config.ui.show_hidden)?;
+  ui.set("show_parent", app.config.ui.show_parent)?;
+  ui.set("show_preview", app.config.ui.show_preview)?;
   if let Some(fmt) = app.config.ui.date_format.as_ref()
   {
     ui.set("date_format", fmt.as_str())?;
@@ -137,6 +141,24 @@ pub fn to_lua_config_table(
   let sel_idx = app.list_state.selected().map(|i| i as u64).unwrap_or(u64::MAX);
   ctx.set("selected_index", sel_idx)?;
   ctx.set("current_len", app.current_entries.len() as u64)?;
+  if let Some(n) = app.action_count
+  {
+    ctx.set("count", n as u64)?;
+  }
+  if let Some((ev, previous)) = app.events.current.as_ref()
+  {
+    ctx.set("event", ev.name())?;
+    if let Some(p) = previous
+    {
+      ctx.set("previous", p.to_string_lossy().to_string())?;
+    }
+  }
+  if let Some(call) = app.lua_command.as_ref()
+  {
+    ctx.set("command", call.name.as_str())?;
+    ctx.set("args", call.args.clone())?;
+    ctx.set("args_line", call.line.as_str())?;
+  }
   // Include commonly used path fields for convenience in actions
   use chrono::{
     DateTime,
@@ -321,6 +343,8 @@ pub fn from_lua_config_table(tbl: Table) -> Result<ConfigData, String>
     ui: UiData {
       panes:          UiPanesData { parent: 30, current: 40, preview: 30 },
       show_hidden:    false,
+      show_parent:    true,
+      show_preview:   true,
       date_format:    None,
       display_mode:   crate::app::DisplayMode::Friendly,
       max_list_items: 5000,
@@ -369,6 +393,14 @@ pub fn from_lua_config_table(tbl: Table) -> Result<ConfigData, String>
     {
       data.ui.show_hidden = b;
     }
+    if let Ok(Some(b)) = ui.get::<Option<bool>>("show_parent")
+    {
+      data.ui.show_parent = b;
+    }
+    if let Ok(Some(b)) = ui.get::<Option<bool>>("show_preview")
+    {
+      data.ui.show_preview = b;
+    }
     if let Ok(s) = ui.get::<String>("date_format")
     {
       data.ui.date_format = Some(s);
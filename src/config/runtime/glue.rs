@@ -5,29 +5,53 @@
 //! Lua VM.
 
 use std::{
+  cell::RefCell,
   io,
-  io::stdout,
-  time::Instant,
+  path::{
+    Path,
+    PathBuf,
+  },
+  rc::Rc,
+  time::{
+    Instant,
+    UNIX_EPOCH,
+  },
 };
 
-use crossterm::terminal::{
-  EnterAlternateScreen,
-  LeaveAlternateScreen,
-  disable_raw_mode,
-  enable_raw_mode,
-};
 use mlua::{
+  Function,
+  IntoLuaMulti,
   Lua,
   Table,
   Value,
 };
 
 use crate::{
-  actions::effects::{
-    ActionEffects,
-    parse_effects_from_lua,
+  actions::{
+    effects::{
+      ActionEffects,
+      FsJob,
+      FsOp,
+      FsRequests,
+      parse_effects_from_lua,
+    },
+    internal::SortKey,
+  },
+  app::{
+    App,
+    DirEntryInfo,
+    LuaDialog,
+    interactive::with_terminal,
+    lua_ui::DialogAnswer,
+  },
+  core::{
+    complete,
+    fs_ops,
+    listing,
+    remote,
+    shell,
+    vfs,
   },
-  app::App,
   trace,
 };
 
@@ -42,62 +66,166 @@ pub fn call_lua_action(
   idx: usize,
 ) -> io::Result<(ActionEffects, Option<crate::config::runtime::data::ConfigData>)>
 {
-  let (engine, funcs) = match app.lua.as_ref()
+  let (lua, func) = match app.lua.as_ref()
   {
-    Some(lua) => (&lua.engine, &lua.actions),
-    None => return Ok((ActionEffects::default(), None)),
+    Some(rt) if idx < rt.actions.len() =>
+    {
+      let lua = rt.engine.lua().clone();
+      let func = lua
+        .registry_value::<Function>(&rt.actions[idx])
+        .map_err(|e| io::Error::other(format!("lua fn lookup: {e}")))?;
+      (lua, func)
+    }
+    _ => return Ok((ActionEffects::default(), None)),
   };
-  if idx >= funcs.len()
-  {
-    return Ok((ActionEffects::default(), None));
-  }
-
-  let lua = engine.lua();
-  let func = lua
-    .registry_value::<mlua::Function>(&funcs[idx])
-    .map_err(|e| io::Error::other(format!("lua fn lookup: {e}")))?;
 
   // Build config snapshot (mutable by Lua)
-  let cfg_tbl = crate::config::runtime::data::to_lua_config_table(lua, app)
+  let cfg_tbl = crate::config::runtime::data::to_lua_config_table(&lua, app)
     .map_err(|e| io::Error::other(format!("build config tbl: {e}")))?;
-  let cfg_tbl_copy = cfg_tbl.clone();
 
   // Build lsv helpers table
-  let lsv_tbl = build_lsv_helpers(lua, &cfg_tbl, app)?;
+  let fs = Rc::new(RefCell::new(FsRequests::default()));
+  let lsv_tbl = build_lsv_helpers(&lua, &cfg_tbl, &fs, app)?;
 
   trace::log(format!("[lua] calling action idx={}...", idx));
   let started = Instant::now();
-  let ret_val: Value = func.call((lsv_tbl, cfg_tbl.clone())).map_err(|e| {
-    let bt = std::backtrace::Backtrace::force_capture();
-    trace::log(format!("[lua] action idx={} error: {}", idx, e));
-    trace::log(format!("[lua] backtrace:\n{}", bt));
-    io::Error::other(format!("lua fn: {e}"))
-  })?;
+  let ret_val: Value =
+    func.call((lsv_tbl.clone(), cfg_tbl.clone())).map_err(|e| {
+      let bt = std::backtrace::Backtrace::force_capture();
+      trace::log(format!("[lua] action idx={} error: {}", idx, e));
+      trace::log(format!("[lua] backtrace:\n{}", bt));
+      io::Error::other(format!("lua fn: {e}"))
+    })?;
   trace::log(format!(
     "[lua] action idx={} ok in {}ms",
     idx,
     started.elapsed().as_millis()
   ));
+  finish_call(app, &lua, &lsv_tbl, &cfg_tbl, fs, ret_val)
+}
+
+/// Call the callback of the open `lsv.ui` dialog with `answer`. Returns
+/// `None` when no callback is waiting.
+///
+/// The callback gets the `lsv` and `config` tables of the action that opened
+/// the dialog, so helpers it closed over still take effect; `config` is
+/// refreshed first to describe the app as it is now.
+pub fn call_lua_dialog(
+  app: &mut App,
+  answer: DialogAnswer,
+) -> io::Result<
+  Option<(ActionEffects, Option<crate::config::runtime::data::ConfigData>)>,
+>
+{
+  let Some(rt) = app.lua.as_mut()
+  else
+  {
+    return Ok(None);
+  };
+  let Some(dialog) = rt.dialog.take()
+  else
+  {
+    return Ok(None);
+  };
+  let lua = rt.engine.lua().clone();
+  let lookup = || -> mlua::Result<(Function, Table, Table)> {
+    let found = (
+      lua.registry_value(&dialog.callback)?,
+      lua.registry_value(&dialog.lsv)?,
+      lua.registry_value(&dialog.config)?,
+    );
+    lua.remove_registry_value(dialog.callback)?;
+    lua.remove_registry_value(dialog.lsv)?;
+    lua.remove_registry_value(dialog.config)?;
+    Ok(found)
+  };
+  let (func, lsv_tbl, cfg_tbl) =
+    lookup().map_err(|e| io::Error::other(format!("lua fn lookup: {e}")))?;
+
+  let fresh = crate::config::runtime::data::to_lua_config_table(&lua, app)
+    .map_err(|e| io::Error::other(format!("build config tbl: {e}")))?;
+  let refresh = || -> mlua::Result<()> {
+    let keys: Vec<Value> = cfg_tbl
+      .pairs::<Value, Value>()
+      .map(|kv| kv.map(|(k, _)| k))
+      .collect::<mlua::Result<_>>()?;
+    for k in keys
+    {
+      cfg_tbl.raw_set(k, Value::Nil)?;
+    }
+    for kv in fresh.pairs::<Value, Value>()
+    {
+      let (k, v) = kv?;
+      cfg_tbl.raw_set(k, v)?;
+    }
+    Ok(())
+  };
+  refresh().map_err(|e| io::Error::other(format!("build config tbl: {e}")))?;
+
+  trace::log(format!("[lua] answering dialog with {:?}", answer));
+  // Text or nil, true or false, or the item and its 1-based index
+  let args = match answer
+  {
+    DialogAnswer::Text(text) => text.into_lua_multi(&lua),
+    DialogAnswer::Yes(yes) => yes.into_lua_multi(&lua),
+    DialogAnswer::Choice(Some((i, item))) => (item, i + 1).into_lua_multi(&lua),
+    DialogAnswer::Choice(None) => Value::Nil.into_lua_multi(&lua),
+  };
+  let ret_val: Value = args.and_then(|a| func.call(a)).map_err(|e| {
+    trace::log(format!("[lua] dialog callback error: {}", e));
+    io::Error::other(format!("lua fn: {e}"))
+  })?;
+  finish_call(app, &lua, &lsv_tbl, &cfg_tbl, dialog.fs, ret_val).map(Some)
+}
+
+/// Turn what Lua returned and left in `cfg_tbl`, and the operations `fs`
+/// collected, into effects and a config overlay, and keep the callback of a
+/// dialog the call opened.
+fn finish_call(
+  app: &mut App,
+  lua: &Lua,
+  lsv_tbl: &Table,
+  cfg_tbl: &Table,
+  fs: Rc<RefCell<FsRequests>>,
+  ret_val: Value,
+) -> io::Result<(ActionEffects, Option<crate::config::runtime::data::ConfigData>)>
+{
+  if let Ok(callback) = cfg_tbl.get::<Function>("dialog_callback")
+  {
+    let keys = (
+      lua.create_registry_value(callback),
+      lua.create_registry_value(lsv_tbl.clone()),
+      lua.create_registry_value(cfg_tbl.clone()),
+    );
+    if let (Ok(callback), Ok(lsv), Ok(config)) = keys
+      && let Some(rt) = app.lua.as_mut()
+    {
+      rt.dialog = Some(LuaDialog { callback, lsv, config, fs: fs.clone() });
+    }
+  }
 
   // Prefer merging any returned partial table into the full snapshot
   let candidate_tbl = match ret_val
   {
-    Value::Table(t) => merge_tables(lua, &cfg_tbl, &t)
+    Value::Table(t) => merge_tables(lua, cfg_tbl, &t)
       .map_err(|e| io::Error::other(format!("merge: {}", e)))?,
-    _ => cfg_tbl,
+    _ => cfg_tbl.clone(),
   };
 
   // Parse lightweight effects first
   let mut fx = parse_effects_from_lua(&candidate_tbl);
   // Fallback: read from original cfg table if helper mutated it
   if fx.output.is_none()
-    && let Ok(text) = cfg_tbl_copy.get::<String>("output_text")
+    && let Ok(text) = cfg_tbl.get::<String>("output_text")
   {
-    let title = cfg_tbl_copy
+    let title = cfg_tbl
       .get::<String>("output_title")
       .unwrap_or_else(|_| String::from("Output"));
     fx.output = Some((title, text));
   }
+  let checked = fs.take();
+  fx.fs_jobs = checked.jobs;
+  fx.fs_errors = checked.errors;
 
   // Optionally parse a full Config overlay (ui changes, etc.)
   let overlay =
@@ -108,6 +236,7 @@ pub fn call_lua_action(
 fn build_lsv_helpers(
   lua: &Lua,
   cfg_tbl: &Table,
+  fs: &Rc<RefCell<FsRequests>>,
   app: &App,
 ) -> io::Result<Table>
 {
@@ -139,16 +268,7 @@ fn build_lsv_helpers(
 
   // lsv.quote(s)
   let quote_fn = lua
-    .create_function(|_, s: String| {
-      #[cfg(windows)]
-      {
-        Ok(format!("\"{}\"", s.replace('"', "\"\"")))
-      }
-      #[cfg(not(windows))]
-      {
-        Ok(format!("'{}'", s.replace('\'', "'\\''")))
-      }
-    })
+    .create_function(|_, s: String| Ok(crate::util::shell_quote(&s)))
     .map_err(|e| io::Error::other(e.to_string()))?;
   tbl.set("quote", quote_fn).map_err(|e| io::Error::other(e.to_string()))?;
 
@@ -218,6 +338,29 @@ fn build_lsv_helpers(
     .set("show_message", show_message_fn)
     .map_err(|e| io::Error::other(e.to_string()))?;
 
+  // show_warning(text)
+  let cfg_ref_warn = cfg_tbl.clone();
+  let show_warning_fn = lua
+    .create_function(move |_, text: String| {
+      let _ = cfg_ref_warn.set("warning_text", text);
+      Ok(true)
+    })
+    .map_err(|e| io::Error::other(e.to_string()))?;
+  tbl
+    .set("show_warning", show_warning_fn)
+    .map_err(|e| io::Error::other(e.to_string()))?;
+
+  // notify(text, level?)
+  let cfg_ref_notify = cfg_tbl.clone();
+  let notify_fn = lua
+    .create_function(move |_, (text, level): (String, Option<String>)| {
+      let _ = cfg_ref_notify.set("notify_text", text);
+      let _ = cfg_ref_notify.set("notify_level", level.unwrap_or_default());
+      Ok(true)
+    })
+    .map_err(|e| io::Error::other(e.to_string()))?;
+  tbl.set("notify", notify_fn).map_err(|e| io::Error::other(e.to_string()))?;
+
   // show_error(text)
   let cfg_ref_err = cfg_tbl.clone();
   let show_error_fn = lua
@@ -312,27 +455,42 @@ fn build_lsv_helpers(
     .set("clear_clipboard", clear_clipboard_fn)
     .map_err(|e| io::Error::other(e.to_string()))?;
 
+  // invalidate_column(name?): recompute a Lua column (all when nil)
+  let cfg_ref_cols = cfg_tbl.clone();
+  let invalidate_column_fn = lua
+    .create_function(move |lua, name: Option<String>| {
+      let list: Table = match cfg_ref_cols.get("columns_reset")
+      {
+        Ok(t) => t,
+        Err(_) =>
+        {
+          let t = lua.create_table()?;
+          cfg_ref_cols.set("columns_reset", t.clone())?;
+          t
+        }
+      };
+      list.push(name.unwrap_or_else(|| String::from("*")))?;
+      Ok(true)
+    })
+    .map_err(|e| io::Error::other(e.to_string()))?;
+  tbl
+    .set("invalidate_column", invalidate_column_fn)
+    .map_err(|e| io::Error::other(e.to_string()))?;
+
   // os_run (captured)
   let cfg_ref5 = cfg_tbl.clone();
   let cwd_str = app.cwd.to_string_lossy().to_string();
   let cwd_capture = cwd_str.clone();
+  let shell_run = app.config.command_shell.clone();
+  let ph_run = app.placeholders(&app.command_files());
+  let ph_i = ph_run.clone();
 
   let os_run_fn = lua
     .create_function(move |_, cmd: String| {
+      let cmd = shell::expand(&shell_run, &cmd, &ph_run);
       trace::log(format!("[os_run] cwd='{}' cmd='{}'", cwd_capture, cmd));
-      #[cfg(windows)]
-      let mut command = {
-        let mut c = std::process::Command::new("cmd");
-        c.arg("/C").arg(&cmd);
-        c
-      };
-      #[cfg(not(windows))]
-      let mut command = {
-        let mut c = std::process::Command::new("sh");
-        c.arg("-lc").arg(&cmd);
-        c
-      };
-      let out = command.current_dir(&cwd_capture).output();
+      let out =
+        shell::command(&shell_run, &cmd).current_dir(&cwd_capture).output();
       match out
       {
         Ok(output) =>
@@ -381,29 +539,16 @@ fn build_lsv_helpers(
   // os_run_interactive
   let cfg_ref_i = cfg_tbl.clone();
   let cwd_str_i = cwd_str.clone();
+  let shell_i = app.config.command_shell.clone();
   let os_run_interactive_fn = lua
     .create_function(move |_, cmd: String| {
       let title = format!("$ {}", cmd);
       let _ = cfg_ref_i.set("output_title", title);
-      #[cfg(windows)]
-      let program = "cmd";
-      #[cfg(windows)]
-      let args: &[&str] = &["/C", &cmd];
-      #[cfg(not(windows))]
-      let program = "sh";
-      #[cfg(not(windows))]
-      let args: &[&str] = &["-lc", &cmd];
-      // leave tui
-      disable_raw_mode().ok();
-      let _ = crossterm::execute!(stdout(), LeaveAlternateScreen);
-      // run
-      let status = std::process::Command::new(program)
-        .args(args)
-        .current_dir(&cwd_str_i)
-        .status();
-      // re-enter tui
-      enable_raw_mode().ok();
-      let _ = crossterm::execute!(stdout(), EnterAlternateScreen);
+      let cmd = shell::expand(&shell_i, &cmd, &ph_i);
+      let status = with_terminal(|| {
+        shell::command(&shell_i, &cmd).current_dir(&cwd_str_i).status()
+      });
+      let _ = cfg_ref_i.set("redraw", true);
       let text = match status
       {
         Ok(s) => format!("exit status: {:?}", s.code()),
@@ -417,8 +562,36 @@ fn build_lsv_helpers(
     .set("os_run_interactive", os_run_interactive_fn)
     .map_err(|e| io::Error::other(e.to_string()))?;
 
+  // spawn_interactive: run after the action returns, like :run_interactive
+  let cfg_ref_spawn = cfg_tbl.clone();
+  let spawn_interactive_fn = lua
+    .create_function(move |_, cmd: Option<String>| {
+      cfg_ref_spawn.set("interactive", cmd.unwrap_or_default())?;
+      Ok(true)
+    })
+    .map_err(|e| io::Error::other(e.to_string()))?;
+  tbl
+    .set("spawn_interactive", spawn_interactive_fn)
+    .map_err(|e| io::Error::other(e.to_string()))?;
+
+  // os_run_async: stream the output into the Output panel, like :run
+  let cfg_ref_async = cfg_tbl.clone();
+  let os_run_async_fn = lua
+    .create_function(move |_, cmd: String| {
+      cfg_ref_async.set("run_async", cmd)?;
+      Ok(true)
+    })
+    .map_err(|e| io::Error::other(e.to_string()))?;
+  tbl
+    .set("os_run_async", os_run_async_fn)
+    .map_err(|e| io::Error::other(e.to_string()))?;
+
   // preview helpers
   build_preview_helpers(lua, &tbl)?;
+  // lsv.fs
+  build_fs_helpers(lua, &tbl, fs, app)?;
+  // lsv.ui
+  build_ui_helpers(lua, &tbl, cfg_tbl)?;
   Ok(tbl)
 }
 
@@ -453,9 +626,409 @@ fn build_clipboard_helpers(
   out
     .set("delete_selected", delete_selected_fn)
     .map_err(|e| io::Error::other(e.to_string()))?;
+
+  // trash_selected(): move selection to trash (with confirmation)
+  let cfg_ref_trash = cfg_tbl.clone();
+  let trash_selected_fn = lua
+    .create_function(move |_, ()| {
+      let _ = cfg_ref_trash.set("confirm", "trash_selected");
+      Ok(true)
+    })
+    .map_err(|e| io::Error::other(e.to_string()))?;
+  out
+    .set("trash_selected", trash_selected_fn)
+    .map_err(|e| io::Error::other(e.to_string()))?;
   Ok(())
 }
 
+/// `lsv.fs`: reads answer at once; copies, moves and removals are checked at
+/// once, kept in `requests` and queued as jobs when the action returns.
+/// Every helper returns `nil, err` on failure, and refused changes are also
+/// logged as errors.
+fn build_fs_helpers(
+  lua: &Lua,
+  out: &Table,
+  requests: &Rc<RefCell<FsRequests>>,
+  app: &App,
+) -> io::Result<()>
+{
+  let fs = lua.create_table().map_err(|e| io::Error::other(e.to_string()))?;
+  let cwd = app.cwd.clone();
+
+  // read_dir(path?): the entries of a directory, hidden ones included
+  let cwd_rd = cwd.clone();
+  let read_dir_fn = lua
+    .create_function(move |lua, path: Option<String>| {
+      let dir = path.map_or_else(|| cwd_rd.clone(), |p| fs_path(&cwd_rd, &p));
+      let listed = listing::read_dir_sorted(
+        &dir,
+        true,
+        SortKey::Name,
+        false,
+        true,
+        usize::MAX,
+      );
+      match listed
+      {
+        Ok(entries) =>
+        {
+          let t = lua.create_table()?;
+          for e in &entries
+          {
+            t.push(entry_table(lua, e)?)?;
+          }
+          Ok((Value::Table(t), Value::Nil))
+        }
+        Err(e) => failure(lua, format!("{}: {}", dir.display(), e)),
+      }
+    })
+    .map_err(|e| io::Error::other(e.to_string()))?;
+  fs.set("read_dir", read_dir_fn)
+    .map_err(|e| io::Error::other(e.to_string()))?;
+
+  // stat(path)
+  let cwd_st = cwd.clone();
+  let stat_fn = lua
+    .create_function(move |lua, path: String| {
+      let path = fs_path(&cwd_st, &path);
+      match stat(&path)
+      {
+        Ok(st) => Ok((Value::Table(stat_table(lua, &st)?), Value::Nil)),
+        Err(e) => failure(lua, format!("{}: {}", path.display(), e)),
+      }
+    })
+    .map_err(|e| io::Error::other(e.to_string()))?;
+  fs.set("stat", stat_fn).map_err(|e| io::Error::other(e.to_string()))?;
+
+  // mkdir(path): create it and any missing parents right away
+  let req_mkdir = requests.clone();
+  let cwd_mk = cwd.clone();
+  let mkdir_fn = lua
+    .create_function(move |lua, path: String| {
+      let path = fs_path(&cwd_mk, &path);
+      let res = local_only(&path).and_then(|()| {
+        std::fs::create_dir_all(&path)
+          .map_err(|e| format!("{}: {}", path.display(), e))
+      });
+      match res
+      {
+        Ok(()) => Ok((Value::Boolean(true), Value::Nil)),
+        Err(e) => refuse(lua, &req_mkdir, "mkdir", e),
+      }
+    })
+    .map_err(|e| io::Error::other(e.to_string()))?;
+  fs.set("mkdir", mkdir_fn).map_err(|e| io::Error::other(e.to_string()))?;
+
+  // copy(src, dest) and move(src, dest): `src` is a path or a list of them
+  for (name, op) in [("copy", FsOp::Copy), ("move", FsOp::Move)]
+  {
+    let req_tr = requests.clone();
+    let cwd_tr = cwd.clone();
+    let transfer_fn = lua
+      .create_function(move |lua, (src, dest): (Value, String)| {
+        let plan =
+          path_list(src).and_then(|src| plan_transfer(&cwd_tr, &src, &dest));
+        match plan
+        {
+          Ok((items, dests)) =>
+          {
+            req_tr.borrow_mut().jobs.push(FsJob { op, items, dests });
+            Ok((Value::Boolean(true), Value::Nil))
+          }
+          Err(e) => refuse(lua, &req_tr, name, e),
+        }
+      })
+      .map_err(|e| io::Error::other(e.to_string()))?;
+    fs.set(name, transfer_fn).map_err(|e| io::Error::other(e.to_string()))?;
+  }
+
+  // remove(paths): permanently delete, like :delete
+  let req_rm = requests.clone();
+  let cwd_rm = cwd;
+  let remove_fn = lua
+    .create_function(move |lua, paths: Value| {
+      let home = complete::home_dir();
+      let checked = path_list(paths).and_then(|paths| {
+        let items: Vec<PathBuf> =
+          paths.iter().map(|p| fs_path(&cwd_rm, p)).collect();
+        for p in &items
+        {
+          local_only(p)?;
+          fs_ops::check_remove(p, &cwd_rm, home.as_deref())?;
+        }
+        Ok(items)
+      });
+      match checked
+      {
+        Ok(items) =>
+        {
+          let job = FsJob { op: FsOp::Remove, items, dests: Vec::new() };
+          req_rm.borrow_mut().jobs.push(job);
+          Ok((Value::Boolean(true), Value::Nil))
+        }
+        Err(e) => refuse(lua, &req_rm, "remove", e),
+      }
+    })
+    .map_err(|e| io::Error::other(e.to_string()))?;
+  fs.set("remove", remove_fn).map_err(|e| io::Error::other(e.to_string()))?;
+
+  out.set("fs", fs).map_err(|e| io::Error::other(e.to_string()))?;
+  Ok(())
+}
+
+/// `lsv.ui`: dialogs that open once the action returns and hand their
+/// answer to a callback; see [`crate::app::lua_ui`].
+fn build_ui_helpers(
+  lua: &Lua,
+  out: &Table,
+  cfg_tbl: &Table,
+) -> io::Result<()>
+{
+  let ui = lua.create_table().map_err(|e| io::Error::other(e.to_string()))?;
+
+  // prompt(title, default, cb): cb(text), or cb(nil) when cancelled
+  let cfg_ref_prompt = cfg_tbl.clone();
+  let prompt_fn = lua
+    .create_function(
+      move |lua, (title, text, cb): (String, Option<String>, Function)| {
+        let dialog = lua.create_table()?;
+        dialog.set("kind", "prompt")?;
+        dialog.set("title", title)?;
+        dialog.set("text", text.unwrap_or_default())?;
+        request_dialog(&cfg_ref_prompt, dialog, cb)
+      },
+    )
+    .map_err(|e| io::Error::other(e.to_string()))?;
+  ui.set("prompt", prompt_fn).map_err(|e| io::Error::other(e.to_string()))?;
+
+  // confirm(question, cb): cb(true) for yes, cb(false) otherwise
+  let cfg_ref_confirm = cfg_tbl.clone();
+  let confirm_fn = lua
+    .create_function(move |lua, (question, cb): (String, Function)| {
+      let dialog = lua.create_table()?;
+      dialog.set("kind", "confirm")?;
+      dialog.set("title", question)?;
+      request_dialog(&cfg_ref_confirm, dialog, cb)
+    })
+    .map_err(|e| io::Error::other(e.to_string()))?;
+  ui.set("confirm", confirm_fn).map_err(|e| io::Error::other(e.to_string()))?;
+
+  // select(items, cb, { title }): cb(item, index), or cb(nil) when cancelled
+  let cfg_ref_select = cfg_tbl.clone();
+  let select_fn = lua
+    .create_function(
+      move |lua, (items, cb, opts): (Vec<String>, Function, Option<Table>)| {
+        let title = opts
+          .and_then(|o| o.get::<Option<String>>("title").ok().flatten())
+          .unwrap_or_else(|| String::from("Select"));
+        let dialog = lua.create_table()?;
+        dialog.set("kind", "select")?;
+        dialog.set("title", title)?;
+        dialog.set("items", items)?;
+        request_dialog(&cfg_ref_select, dialog, cb)
+      },
+    )
+    .map_err(|e| io::Error::other(e.to_string()))?;
+  ui.set("select", select_fn).map_err(|e| io::Error::other(e.to_string()))?;
+
+  out.set("ui", ui).map_err(|e| io::Error::other(e.to_string()))?;
+  Ok(())
+}
+
+/// Leave `dialog` and its callback for [`finish_call`]; a later request in
+/// the same action replaces an earlier one.
+fn request_dialog(
+  cfg_tbl: &Table,
+  dialog: Table,
+  callback: Function,
+) -> mlua::Result<bool>
+{
+  cfg_tbl.set("dialog", dialog)?;
+  cfg_tbl.set("dialog_callback", callback)?;
+  Ok(true)
+}
+
+/// A path handed to `lsv.fs`: `~` expanded, relative to `cwd`, and with `.`
+/// and `..` resolved.
+fn fs_path(
+  cwd: &Path,
+  s: &str,
+) -> PathBuf
+{
+  let p = cwd.join(complete::expand_tilde(s));
+  if remote::is_remote(&p) || vfs::is_drives(&p)
+  {
+    p
+  }
+  else
+  {
+    fs_ops::normalize(&p)
+  }
+}
+
+/// One path or a list of them.
+fn path_list(v: Value) -> Result<Vec<String>, String>
+{
+  let list = match v
+  {
+    Value::String(s) => vec![s.to_string_lossy()],
+    Value::Table(t) => t
+      .sequence_values::<String>()
+      .collect::<mlua::Result<_>>()
+      .map_err(|e| e.to_string())?,
+    other => return Err(format!("expected a path, got {}", other.type_name())),
+  };
+  if list.is_empty()
+  {
+    return Err(String::from("no paths given"));
+  }
+  Ok(list)
+}
+
+/// Changes stay on this machine; archives, hosts and the drive list have
+/// their own commands.
+fn local_only(path: &Path) -> Result<(), String>
+{
+  if remote::is_remote(path) || vfs::is_drives(path) || vfs::is_member(path)
+  {
+    return Err(format!("not a local path: {}", path.display()));
+  }
+  Ok(())
+}
+
+/// Where each of `src` goes: into `dest` when it is a directory, otherwise
+/// to `dest` itself, which then takes a single source.
+fn plan_transfer(
+  cwd: &Path,
+  src: &[String],
+  dest: &str,
+) -> Result<(Vec<PathBuf>, Vec<PathBuf>), String>
+{
+  let dest = fs_path(cwd, dest);
+  let items: Vec<PathBuf> = src.iter().map(|s| fs_path(cwd, s)).collect();
+  let into = dest.is_dir();
+  if !into && items.len() > 1
+  {
+    return Err(format!("not a directory: {}", dest.display()));
+  }
+  let mut dests = Vec::with_capacity(items.len());
+  for item in &items
+  {
+    let target = if into
+    {
+      let name = item
+        .file_name()
+        .ok_or_else(|| format!("no file name: {}", item.display()))?;
+      dest.join(name)
+    }
+    else
+    {
+      dest.clone()
+    };
+    local_only(item)?;
+    local_only(&target)?;
+    fs_ops::check_transfer(item, &target)?;
+    dests.push(target);
+  }
+  Ok((items, dests))
+}
+
+/// Log a refused change and hand `nil, err` back to the script.
+fn refuse(
+  lua: &Lua,
+  requests: &RefCell<FsRequests>,
+  op: &str,
+  err: String,
+) -> mlua::Result<(Value, Value)>
+{
+  let err = format!("lsv.fs.{}: {}", op, err);
+  requests.borrow_mut().errors.push(err.clone());
+  failure(lua, err)
+}
+
+fn failure(
+  lua: &Lua,
+  err: String,
+) -> mlua::Result<(Value, Value)>
+{
+  Ok((Value::Nil, Value::String(lua.create_string(err)?)))
+}
+
+/// `{ name, path, is_dir, size, mtime }` for a listed entry; `mtime` is in
+/// seconds since the epoch.
+fn entry_table(
+  lua: &Lua,
+  e: &DirEntryInfo,
+) -> mlua::Result<Table>
+{
+  let t = lua.create_table()?;
+  t.set("name", e.name.as_str())?;
+  t.set("path", e.path.to_string_lossy().to_string())?;
+  t.set("is_dir", e.is_dir)?;
+  t.set("size", e.size)?;
+  let mtime = e.mtime.and_then(|m| m.duration_since(UNIX_EPOCH).ok());
+  t.set("mtime", mtime.map(|d| d.as_secs()))?;
+  Ok(t)
+}
+
+/// What `lsv.fs.stat` knows about `path`: the listed entry, plus `is_file`,
+/// `is_symlink` and `readonly` for local files. Entries of archives and hosts
+/// are looked up in their directory's listing.
+struct Stat
+{
+  entry:      DirEntryInfo,
+  is_file:    bool,
+  is_symlink: bool,
+  readonly:   bool,
+}
+
+fn stat(path: &Path) -> io::Result<Stat>
+{
+  let listed = vfs::parent(path).and_then(|dir| vfs::list(&dir, true));
+  if let Some(listing) = listed
+  {
+    let entry = listing?
+      .into_iter()
+      .find(|e| e.path == path)
+      .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))?;
+    let is_file = !entry.is_dir;
+    return Ok(Stat { entry, is_file, is_symlink: false, readonly: false });
+  }
+  let link = std::fs::symlink_metadata(path)?;
+  // Broken links describe themselves
+  let meta = std::fs::metadata(path).unwrap_or_else(|_| link.clone());
+  let entry = DirEntryInfo {
+    name:   path
+      .file_name()
+      .map(|n| n.to_string_lossy().to_string())
+      .unwrap_or_else(|| path.to_string_lossy().to_string()),
+    path:   path.to_path_buf(),
+    is_dir: meta.is_dir(),
+    size:   meta.len(),
+    mtime:  meta.modified().ok(),
+    ctime:  meta.created().ok(),
+    meta:   None,
+  };
+  Ok(Stat {
+    entry,
+    is_file: meta.is_file(),
+    is_symlink: link.file_type().is_symlink(),
+    readonly: meta.permissions().readonly(),
+  })
+}
+
+fn stat_table(
+  lua: &Lua,
+  st: &Stat,
+) -> mlua::Result<Table>
+{
+  let t = entry_table(lua, &st.entry)?;
+  t.set("is_file", st.is_file)?;
+  t.set("is_symlink", st.is_symlink)?;
+  t.set("readonly", st.readonly)?;
+  Ok(t)
+}
+
 fn build_selection_helpers(
   lua: &Lua,
   out: &Table,
@@ -13,7 +13,10 @@ use mlua::{
   Value,
 };
 
-use super::UiTheme;
+use super::{
+  EntryColorRule,
+  UiTheme,
+};
 
 pub(crate) fn merge_theme_table(
   theme_tbl: &Table,
@@ -100,6 +103,76 @@ pub(crate) fn merge_theme_table(
   {
     theme.selection_bar_move_fg = Some(s);
   }
+  if let Ok(rules_tbl) = theme_tbl.get::<Table>("entry_colors")
+  {
+    for (pattern, value) in rules_tbl.pairs::<String, Value>().flatten()
+    {
+      let Some(rule) = parse_entry_color(&pattern, &value)
+      else
+      {
+        crate::trace::log(format!(
+          "[theme] ignoring entry_colors['{}']: expected a string or table",
+          pattern
+        ));
+        continue;
+      };
+      theme.entry_colors.retain(|r| r.pattern != rule.pattern);
+      theme.entry_colors.push(rule);
+    }
+    sort_entry_colors(&mut theme.entry_colors);
+  }
+}
+
+/// Parse one `entry_colors` value: a string such as `"yellow bold"` or
+/// `"white on red"`, or a table `{ fg = ..., bg = ..., bold = true }`.
+fn parse_entry_color(
+  pattern: &str,
+  value: &Value,
+) -> Option<EntryColorRule>
+{
+  let mut rule =
+    EntryColorRule { pattern: pattern.to_string(), ..Default::default() };
+  match value
+  {
+    Value::String(s) =>
+    {
+      let text = s.to_str().ok()?.to_string();
+      let mut words = text.split_whitespace();
+      while let Some(word) = words.next()
+      {
+        match word.to_ascii_lowercase().as_str()
+        {
+          "bold" => rule.bold = true,
+          "italic" => rule.italic = true,
+          "underline" | "underlined" => rule.underline = true,
+          "on" => rule.bg = words.next().map(str::to_string),
+          _ => rule.fg = Some(word.to_string()),
+        }
+      }
+    }
+    Value::Table(t) =>
+    {
+      rule.fg = t.get::<String>("fg").ok();
+      rule.bg = t.get::<String>("bg").ok();
+      rule.bold = t.get::<bool>("bold").unwrap_or(false);
+      rule.italic = t.get::<bool>("italic").unwrap_or(false);
+      rule.underline = t.get::<bool>("underline").unwrap_or(false);
+    }
+    _ => return None,
+  }
+  Some(rule)
+}
+
+/// Order rules so the first match is the most specific: exact names before
+/// globs, then globs with more literal characters first.
+fn sort_entry_colors(rules: &mut [EntryColorRule])
+{
+  let key = |r: &EntryColorRule| {
+    let wild = r.pattern.contains(['*', '?']);
+    let literal = r.pattern.chars().filter(|c| !matches!(c, '*' | '?')).count();
+    (wild, std::cmp::Reverse(literal), r.pattern.clone())
+  };
+  rules.sort_by_key(key);
 }
 
 pub(crate) fn resolve_theme_path(
@@ -135,18 +208,25 @@ pub(crate) fn load_theme_table_from_path(
       e
     ))
   })?;
-  crate::trace::log(format!("[lua] eval theme: {}", path.display()));
-  let chunk = lua.load(&code).set_name(path.to_string_lossy());
+  eval_theme_table(lua, &code, &path.to_string_lossy())
+}
+
+/// Run theme source `code` and return the table it evaluates to. `name`
+/// labels the chunk in errors.
+pub(crate) fn eval_theme_table(
+  lua: &Lua,
+  code: &str,
+  name: &str,
+) -> mlua::Result<Table>
+{
+  crate::trace::log(format!("[lua] eval theme: {}", name));
+  let chunk = lua.load(code).set_name(name);
   let value = match chunk.eval::<Value>()
   {
     Ok(v) => v,
     Err(e) =>
     {
-      crate::trace::log(format!(
-        "[lua] theme eval error ({}): {}",
-        path.display(),
-        e
-      ));
+      crate::trace::log(format!("[lua] theme eval error ({}): {}", name, e));
       return Err(e);
     }
   };
@@ -155,7 +235,7 @@ pub(crate) fn load_theme_table_from_path(
     Value::Table(t) => Ok(t),
     other => Err(LuaError::RuntimeError(format!(
       "theme '{}' returned {} (table expected)",
-      path.display(),
+      name,
       other.type_name()
     ))),
   }
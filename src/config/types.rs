@@ -13,13 +13,34 @@ pub struct IconsConfig
   pub extensions:   std::collections::HashMap<String, String>,
   // Optional per-folder-name icon overrides (lowercased keys)
   pub folders:      std::collections::HashMap<String, String>,
+  /// Icon colours by lowercase extension, overriding the preset's.
+  pub colors:       std::collections::HashMap<String, String>,
 }
 
-#[derive(Debug, Clone, Default)]
-/// Key-handling configuration (currently only sequence timeout).
+#[derive(Debug, Clone)]
+/// Key-handling configuration.
 pub struct KeysConfig
 {
   pub sequence_timeout_ms: u64,
+  /// Install the built-in keymaps, navigation included.
+  pub defaults:            bool,
+  /// What `<leader>` in a key sequence stands for.
+  pub leader:              String,
+  /// Names of key prefixes for the which-key overlay, e.g. "z" = "View".
+  pub groups:              std::collections::HashMap<String, String>,
+}
+
+impl Default for KeysConfig
+{
+  fn default() -> Self
+  {
+    Self {
+      sequence_timeout_ms: 0,
+      defaults:            true,
+      leader:              String::from("\\"),
+      groups:              std::collections::HashMap::new(),
+    }
+  }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -30,6 +51,152 @@ pub struct Config
   pub icons:          IconsConfig,
   pub keys:           KeysConfig,
   pub ui:             UiConfig,
+  /// Lua action indices registered per event name by `lsv.on_event`.
+  pub events:         std::collections::HashMap<String, Vec<usize>>,
+  /// `:` commands registered by `lsv.register_command`.
+  pub commands:       Vec<LuaCommand>,
+  /// Row template fields registered by `lsv.define_column`.
+  pub columns:        Vec<LuaColumn>,
+  /// `{lua:name}` template functions registered by `lsv.define_template_fn`.
+  pub template_fns:   Vec<LuaTemplateFn>,
+  /// Indices of the entry predicates registered by `lsv.filter`, among the
+  /// Lua actions.
+  pub entry_filters:  Vec<usize>,
+  /// Plugins found under the config root, in setup order.
+  pub plugins:        Vec<PluginStatus>,
+  /// Rules picking the program `:open` runs, first match wins.
+  pub openers:        Vec<OpenerRule>,
+  /// Rules picking the preview command, tried before the Lua previewer.
+  pub previewers:     Vec<PreviewerRule>,
+  /// Lua action indices of the directory previewers added by
+  /// `lsv.add_dir_previewer`, with their priorities, highest first.
+  pub dir_previewers: Vec<(i64, usize)>,
+  /// The shell previewers, openers, `os_run` and the command pane use.
+  pub command_shell:  CommandShell,
+  /// Shell commands bound by `lsv.map_command`, run by `shell:<index>`.
+  pub shell_commands: Vec<MappedCommand>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// Where a mapped shell command runs and where its output goes.
+pub enum CommandMode
+{
+  /// Detached, with its output thrown away.
+  Background,
+  /// Streamed into the Output panel, like `:run`.
+  #[default]
+  Output,
+  /// Streamed into the preview pane.
+  Preview,
+  /// Given the terminal until it exits, like `:run_interactive`.
+  Terminal,
+}
+
+impl CommandMode
+{
+  pub fn parse(s: &str) -> Option<Self>
+  {
+    match s.trim().to_ascii_lowercase().as_str()
+    {
+      "background" => Some(Self::Background),
+      "output" => Some(Self::Output),
+      "preview" => Some(Self::Preview),
+      "terminal" => Some(Self::Terminal),
+      _ => None,
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A shell command bound to keys with `lsv.map_command`.
+pub struct MappedCommand
+{
+  /// Command line; placeholders are filled in when it runs.
+  pub cmd:  String,
+  pub mode: CommandMode,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// `command_shell`: how shell commands are spawned.
+pub struct CommandShell
+{
+  /// Shell program; `sh` (`cmd` on Windows) when unset.
+  pub program: Option<String>,
+  /// Start it as a login shell, reading the user's profile first.
+  pub login:   bool,
+  /// Environment variables passed on; every one when unset.
+  pub env:     Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default)]
+/// An `openers` rule: which files it applies to and how to run `cmd`.
+pub struct OpenerRule
+{
+  /// Lowercase extensions without the dot; any when empty.
+  pub ext:      Vec<String>,
+  /// MIME type, optionally ending in `/*` (e.g. `image/*`).
+  pub mime:     Option<String>,
+  /// Glob (`*`, `?`) matched against the file name.
+  pub name:     Option<String>,
+  /// Shell command; the files are `"$@"` (`"$1"` for the first).
+  pub cmd:      String,
+  /// Give the program the terminal, suspending the UI until it exits.
+  pub terminal: bool,
+  /// Start the program in the background and don't wait for it.
+  pub detach:   bool,
+  /// Arguments pointing `cmd` at a line when opening at one, such as
+  /// `+{line}`; the file follows unless they name it with `{file}`.
+  pub line:     Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+/// A `previewers` rule: which files it applies to, matched like an
+/// [`OpenerRule`], and the command showing them.
+pub struct PreviewerRule
+{
+  pub ext:  Vec<String>,
+  pub mime: Option<String>,
+  pub name: Option<String>,
+  /// Shell command with `{file}`, `{width}` and `{height}` filled in.
+  pub cmd:  String,
+}
+
+#[derive(Debug, Clone)]
+/// Outcome of loading one plugin.
+pub struct PluginStatus
+{
+  pub name:  String,
+  /// Why the plugin failed to load or set up, if it did.
+  pub error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+/// A row template field computed per entry by a Lua function.
+pub struct LuaColumn
+{
+  pub name:   String,
+  /// Index of the function among the Lua actions.
+  pub action: usize,
+}
+
+#[derive(Debug, Clone)]
+/// A `{lua:name}` header or row template segment computed by a Lua function.
+pub struct LuaTemplateFn
+{
+  pub name:   String,
+  /// Index of the function among the Lua actions.
+  pub action: usize,
+}
+
+#[derive(Debug, Clone)]
+/// A `:` command implemented by a Lua function.
+pub struct LuaCommand
+{
+  pub name:     String,
+  /// Index of the function among the Lua actions.
+  pub action:   usize,
+  /// Argument values offered by Tab completion.
+  pub complete: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -54,24 +221,64 @@ pub struct UiPanes
 /// User interface configuration block replicated from Lua.
 pub struct UiConfig
 {
-  pub panes:          Option<UiPanes>,
-  pub show_hidden:    bool,
-  pub max_list_items: usize,
-  pub date_format:    Option<String>,
-  pub header_left:    Option<String>,
-  pub header_right:   Option<String>,
-  pub header_bg:      Option<String>,
-  pub header_fg:      Option<String>,
-  pub row:            Option<UiRowFormat>,
-  pub row_widths:     Option<UiRowWidths>,
-  pub display_mode:   Option<String>,
-  pub sort:           Option<String>,
-  pub sort_reverse:   Option<bool>,
-  pub show:           Option<String>,
-  pub theme_path:     Option<PathBuf>,
-  pub theme:          Option<UiTheme>,
-  pub confirm_delete: bool,
-  pub modals:         Option<UiModals>,
+  pub panes:              Option<UiPanes>,
+  pub show_hidden:        bool,
+  pub show_parent:        bool,
+  pub show_preview:       bool,
+  pub max_list_items:     usize,
+  /// Directory levels listed by the flat view.
+  pub flatten_depth:      usize,
+  /// Messages kept in the log; older ones are dropped.
+  pub max_messages:       usize,
+  /// Milliseconds a notification stays up; 0 turns them off.
+  pub notify_ms:          u64,
+  /// Previewer outputs kept for files previewed recently; 0 keeps none.
+  pub preview_cache:      usize,
+  /// Seconds a kept previewer output stays valid; 0 keeps it until evicted.
+  pub preview_ttl:        u64,
+  /// Milliseconds a previewer command may run before it is killed.
+  pub preview_timeout_ms: u64,
+  /// Output bytes read from a previewer command; the rest is cut off.
+  pub preview_max_bytes:  usize,
+  /// Save the session on exit and restore it on start.
+  pub session:            bool,
+  /// Share directory visits with zoxide for `:z`.
+  pub zoxide:             bool,
+  /// Reload the config when `init.lua` is saved.
+  pub auto_reload:        bool,
+  pub date_format:        Option<String>,
+  /// Times at most this many seconds old are shown as "3h ago" in rows and
+  /// the header; older ones use `date_format`.
+  pub relative_dates:     Option<u64>,
+  pub line_numbers:       LineNumbers,
+  pub header_left:        Option<String>,
+  pub header_right:       Option<String>,
+  pub header_bg:          Option<String>,
+  pub header_fg:          Option<String>,
+  /// Status line below the panes; none is drawn when unset.
+  pub footer:             Option<UiFooter>,
+  pub row:                Option<UiRowFormat>,
+  pub row_widths:         Option<UiRowWidths>,
+  pub display_mode:       Option<String>,
+  pub sort:               Option<String>,
+  pub sort_reverse:       Option<bool>,
+  pub show:               Option<String>,
+  pub theme_path:         Option<PathBuf>,
+  pub theme:              Option<UiTheme>,
+  pub confirm_delete:     bool,
+  /// `:paste_symlink` links relative to the link's directory.
+  pub relative_links:     bool,
+  pub modals:             Option<UiModals>,
+  /// Graphics protocol passed to previewers: a protocol name or "auto".
+  pub image_protocol:     Option<String>,
+  /// Protocol per terminal name (`$TERM_PROGRAM` or `$TERM`, lowercased),
+  /// used when `image_protocol` is "auto".
+  pub image_protocols:    std::collections::HashMap<String, String>,
+  /// Thumbnails of videos, PDFs, SVGs and HEIC/AVIF images shown when no
+  /// previewer handles them.
+  pub thumbnails:         UiThumbnails,
+  /// Commands `:compress` runs for compressed tarballs and zip files.
+  pub compress:           UiCompress,
 }
 
 impl Default for UiConfig
@@ -79,24 +286,98 @@ impl Default for UiConfig
   fn default() -> Self
   {
     Self {
-      panes:          None,
-      show_hidden:    false,
-      max_list_items: 5000,
-      date_format:    None,
-      header_left:    None,
-      header_right:   None,
-      header_bg:      None,
-      header_fg:      None,
-      row:            Some(UiRowFormat::default()),
-      row_widths:     None,
-      display_mode:   None,
-      sort:           None,
-      sort_reverse:   None,
-      show:           None,
-      theme_path:     None,
-      theme:          None,
-      confirm_delete: true,
-      modals:         None,
+      panes:              None,
+      show_hidden:        false,
+      show_parent:        true,
+      show_preview:       true,
+      max_list_items:     5000,
+      flatten_depth:      8,
+      max_messages:       100,
+      notify_ms:          3000,
+      preview_cache:      crate::core::lru::DEFAULT_CAPACITY,
+      preview_ttl:        0,
+      preview_timeout_ms: 5000,
+      preview_max_bytes:  1024 * 1024,
+      session:            true,
+      zoxide:             false,
+      auto_reload:        false,
+      date_format:        None,
+      relative_dates:     None,
+      line_numbers:       LineNumbers::None,
+      header_left:        None,
+      header_right:       None,
+      header_bg:          None,
+      header_fg:          None,
+      footer:             None,
+      row:                Some(UiRowFormat::default()),
+      row_widths:         None,
+      display_mode:       None,
+      sort:               None,
+      sort_reverse:       None,
+      show:               None,
+      theme_path:         None,
+      theme:              None,
+      confirm_delete:     true,
+      relative_links:     false,
+      modals:             None,
+      image_protocol:     None,
+      image_protocols:    std::collections::HashMap::new(),
+      thumbnails:         UiThumbnails::default(),
+      compress:           UiCompress::default(),
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+/// Commands making and drawing preview thumbnails; see
+/// [`crate::core::thumbnail`]. A missing command disables that kind.
+pub struct UiThumbnails
+{
+  pub enabled: bool,
+  pub video:   Option<String>,
+  pub pdf:     Option<String>,
+  pub svg:     Option<String>,
+  pub heif:    Option<String>,
+  pub view:    String,
+}
+
+impl Default for UiThumbnails
+{
+  fn default() -> Self
+  {
+    use crate::core::thumbnail;
+    Self {
+      enabled: true,
+      video:   Some(thumbnail::DEFAULT_VIDEO_CMD.to_string()),
+      pdf:     Some(thumbnail::DEFAULT_PDF_CMD.to_string()),
+      svg:     Some(thumbnail::DEFAULT_SVG_CMD.to_string()),
+      heif:    Some(thumbnail::DEFAULT_HEIF_CMD.to_string()),
+      view:    thumbnail::DEFAULT_VIEW_CMD.to_string(),
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+/// Commands making archives; see [`crate::core::pack`].
+pub struct UiCompress
+{
+  /// Filter gzipping a tar stream from stdin to stdout.
+  pub gzip: String,
+  /// Filter compressing a tar stream with zstd.
+  pub zstd: String,
+  /// Makes a zip file: gets the archive path, then the members.
+  pub zip:  String,
+}
+
+impl Default for UiCompress
+{
+  fn default() -> Self
+  {
+    use crate::core::pack;
+    Self {
+      gzip: pack::DEFAULT_GZIP_CMD.to_string(),
+      zstd: pack::DEFAULT_ZSTD_CMD.to_string(),
+      zip:  pack::DEFAULT_ZIP_CMD.to_string(),
     }
   }
 }
@@ -116,6 +397,38 @@ pub struct UiModals
   pub theme:   UiModalConfig,
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// Numbers in front of the rows of the current pane.
+pub enum LineNumbers
+{
+  #[default]
+  None,
+  /// Each row's position in the listing, from 1.
+  Absolute,
+  /// Distance from the cursor row, which shows its own position.
+  Relative,
+}
+
+#[derive(Debug, Clone)]
+/// One side of the footer: a template like the header's, or a Lua function
+/// returning one.
+pub enum FooterSegment
+{
+  Template(String),
+  /// Index of the function among the Lua actions.
+  Lua(usize),
+}
+
+#[derive(Debug, Clone, Default)]
+/// Footer line segments and colours.
+pub struct UiFooter
+{
+  pub left:  Option<FooterSegment>,
+  pub right: Option<FooterSegment>,
+  pub bg:    Option<String>,
+  pub fg:    Option<String>,
+}
+
 #[derive(Debug, Clone)]
 /// Template strings used to render each row in the directory panes.
 pub struct UiRowFormat
@@ -174,4 +487,19 @@ pub struct UiTheme
   pub selection_bar_fg:      Option<String>,
   pub selection_bar_copy_fg: Option<String>,
   pub selection_bar_move_fg: Option<String>,
+  /// Styles for entries whose names match a glob, most specific first.
+  pub entry_colors:          Vec<EntryColorRule>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+/// A theme `entry_colors` rule: entries whose name matches `pattern` (a
+/// case-insensitive glob) are drawn with these colours and attributes.
+pub struct EntryColorRule
+{
+  pub pattern:   String,
+  pub fg:        Option<String>,
+  pub bg:        Option<String>,
+  pub bold:      bool,
+  pub italic:    bool,
+  pub underline: bool,
 }
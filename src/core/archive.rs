@@ -0,0 +1,1003 @@
+//! Archive listings for the preview pane.
+//!
+//! Zip central directories and tar headers (plain or gzip compressed) are
+//! read directly. 7z archives keep their headers LZMA-compressed, so they are
+//! listed through an installed `7z`/`7za`/`7zz` binary instead.
+
+use std::{
+  fs::{
+    self,
+    File,
+  },
+  io::{
+    self,
+    BufReader,
+    Read,
+    Seek,
+    SeekFrom,
+    Write,
+  },
+  path::{
+    Component,
+    Path,
+    PathBuf,
+  },
+  process::{
+    Command,
+    Stdio,
+  },
+};
+
+use chrono::NaiveDateTime;
+
+use crate::core::fs_ops::CopyProgress;
+
+/// Stop scanning a `.tar.gz` after this many decompressed bytes; entry
+/// headers can be spread through the whole stream.
+const MAX_GZIP_SCAN_BYTES: u64 = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind
+{
+  Zip,
+  Tar,
+  TarGz,
+  SevenZip,
+}
+
+impl ArchiveKind
+{
+  pub fn label(self) -> &'static str
+  {
+    match self
+    {
+      ArchiveKind::Zip => "zip",
+      ArchiveKind::Tar => "tar",
+      ArchiveKind::TarGz => "tar.gz",
+      ArchiveKind::SevenZip => "7z",
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveEntry
+{
+  pub name:     String,
+  pub size:     u64,
+  pub modified: Option<NaiveDateTime>,
+  pub is_dir:   bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveListing
+{
+  pub kind:      ArchiveKind,
+  pub entries:   Vec<ArchiveEntry>,
+  /// More entries exist than were listed.
+  pub truncated: bool,
+}
+
+/// Recognise supported archives by file extension.
+pub fn detect(path: &Path) -> Option<ArchiveKind>
+{
+  let name = path.file_name()?.to_string_lossy().to_ascii_lowercase();
+  if name.ends_with(".tar.gz") || name.ends_with(".tgz")
+  {
+    Some(ArchiveKind::TarGz)
+  }
+  else if name.ends_with(".tar")
+  {
+    Some(ArchiveKind::Tar)
+  }
+  else if name.ends_with(".zip")
+    || name.ends_with(".jar")
+    || name.ends_with(".whl")
+  {
+    Some(ArchiveKind::Zip)
+  }
+  else if name.ends_with(".7z")
+  {
+    Some(ArchiveKind::SevenZip)
+  }
+  else
+  {
+    None
+  }
+}
+
+/// List up to `max_entries` members of the archive at `path`.
+pub fn list(
+  path: &Path,
+  kind: ArchiveKind,
+  max_entries: usize,
+) -> io::Result<ArchiveListing>
+{
+  let (entries, truncated) = match kind
+  {
+    ArchiveKind::Zip => list_zip(path, max_entries)?,
+    ArchiveKind::Tar =>
+    {
+      // Read headers a block at a time and seek over member data, so large
+      // archives are not read end to end
+      let mut scan = TarScanner::new(max_entries);
+      let mut f = BufReader::new(File::open(path)?);
+      let mut block = [0u8; 512];
+      loop
+      {
+        match f.read_exact(&mut block)
+        {
+          Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+          res => res?,
+        }
+        if !scan.feed(&block)
+        {
+          break;
+        }
+        let skip = scan.take_skip();
+        if skip > 0
+        {
+          f.seek_relative(skip as i64)?;
+        }
+      }
+      scan.finish()
+    }
+    ArchiveKind::TarGz =>
+    {
+      let mut scan = TarScanner::new(max_entries);
+      let mut seen = 0u64;
+      let mut cut = false;
+      let res = crate::core::inflate::gunzip(File::open(path)?, |chunk| {
+        seen += chunk.len() as u64;
+        if seen > MAX_GZIP_SCAN_BYTES
+        {
+          cut = true;
+          return false;
+        }
+        scan.feed(chunk)
+      });
+      // A damaged tail still leaves the entries read so far worth showing
+      if let Err(e) = res
+        && scan.entries.is_empty()
+      {
+        return Err(e);
+      }
+      let (entries, truncated) = scan.finish();
+      (entries, truncated || cut)
+    }
+    ArchiveKind::SevenZip => list_7z(path, max_entries)?,
+  };
+  Ok(ArchiveListing { kind, entries, truncated })
+}
+
+fn le16(b: &[u8]) -> u16
+{
+  u16::from_le_bytes([b[0], b[1]])
+}
+
+fn le32(b: &[u8]) -> u32
+{
+  u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+}
+
+fn le64(b: &[u8]) -> u64
+{
+  let mut a = [0u8; 8];
+  a.copy_from_slice(&b[..8]);
+  u64::from_le_bytes(a)
+}
+
+fn dos_datetime(
+  date: u16,
+  time: u16,
+) -> Option<NaiveDateTime>
+{
+  chrono::NaiveDate::from_ymd_opt(
+    1980 + (date >> 9) as i32,
+    ((date >> 5) & 0x0f) as u32,
+    (date & 0x1f) as u32,
+  )?
+  .and_hms_opt(
+    (time >> 11) as u32,
+    ((time >> 5) & 0x3f) as u32,
+    ((time & 0x1f) * 2) as u32,
+  )
+}
+
+fn zip_err(msg: &str) -> io::Error
+{
+  io::Error::new(io::ErrorKind::InvalidData, format!("zip: {}", msg))
+}
+
+/// Central directory record of a zip member, with what extraction needs.
+struct ZipMember
+{
+  entry:  ArchiveEntry,
+  method: u16,
+  flags:  u16,
+  csize:  u64,
+  offset: u64,
+}
+
+fn zip_members(
+  path: &Path,
+  max_entries: usize,
+) -> io::Result<(Vec<ZipMember>, bool)>
+{
+  let mut f = File::open(path)?;
+  let len = f.metadata()?.len();
+  // End of central directory record: 22 bytes plus up to 64 KiB comment
+  let tail_len = len.min(22 + 65_535);
+  f.seek(SeekFrom::Start(len - tail_len))?;
+  let mut tail = vec![0u8; tail_len as usize];
+  f.read_exact(&mut tail)?;
+  let eocd = (0..tail.len().saturating_sub(21))
+    .rev()
+    .find(|&i| tail[i..i + 4] == [0x50, 0x4b, 0x05, 0x06])
+    .ok_or_else(|| zip_err("end of central directory not found"))?;
+  let rec = &tail[eocd..];
+  let mut count = le16(&rec[10..]) as u64;
+  let mut cd_size = le32(&rec[12..]) as u64;
+  let mut cd_offset = le32(&rec[16..]) as u64;
+  // Zip64 archives park the real values in a separate record
+  if (count == 0xffff || cd_size == 0xffff_ffff || cd_offset == 0xffff_ffff)
+    && eocd >= 20
+    && tail[eocd - 20..eocd - 16] == [0x50, 0x4b, 0x06, 0x07]
+  {
+    let at = le64(&tail[eocd - 12..]);
+    f.seek(SeekFrom::Start(at))?;
+    let mut z = [0u8; 56];
+    f.read_exact(&mut z)?;
+    if z[..4] != [0x50, 0x4b, 0x06, 0x06]
+    {
+      return Err(zip_err("bad zip64 end record"));
+    }
+    count = le64(&z[32..]);
+    cd_size = le64(&z[40..]);
+    cd_offset = le64(&z[48..]);
+  }
+  if cd_offset.saturating_add(cd_size) > len
+  {
+    return Err(zip_err("central directory out of range"));
+  }
+  f.seek(SeekFrom::Start(cd_offset))?;
+  let mut cd = vec![0u8; cd_size.min(64 * 1024 * 1024) as usize];
+  f.read_exact(&mut cd)?;
+
+  let mut out = Vec::new();
+  let mut pos = 0usize;
+  while pos + 46 <= cd.len() && cd[pos..pos + 4] == [0x50, 0x4b, 0x01, 0x02]
+  {
+    if out.len() >= max_entries
+    {
+      return Ok((out, true));
+    }
+    let h = &cd[pos..];
+    let flags = le16(&h[8..]);
+    let method = le16(&h[10..]);
+    let time = le16(&h[12..]);
+    let date = le16(&h[14..]);
+    let mut csize = le32(&h[20..]) as u64;
+    let mut size = le32(&h[24..]) as u64;
+    let name_len = le16(&h[28..]) as usize;
+    let extra_len = le16(&h[30..]) as usize;
+    let comment_len = le16(&h[32..]) as usize;
+    let mut offset = le32(&h[42..]) as u64;
+    let end = 46 + name_len + extra_len;
+    if h.len() < end
+    {
+      break;
+    }
+    let name = String::from_utf8_lossy(&h[46..46 + name_len]).to_string();
+    // Zip64 extended information holds, in order, whichever of the sizes
+    // and offset overflowed 32 bits
+    let mut extra = &h[46 + name_len..end];
+    while extra.len() >= 4
+    {
+      let id = le16(extra);
+      let n = (le16(&extra[2..]) as usize).min(extra.len() - 4);
+      if id == 0x0001
+      {
+        let mut vals = extra[4..4 + n].chunks_exact(8).map(le64);
+        for field in [&mut size, &mut csize, &mut offset]
+        {
+          if *field == 0xffff_ffff
+          {
+            *field = vals.next().unwrap_or(*field);
+          }
+        }
+        break;
+      }
+      extra = &extra[4 + n..];
+    }
+    out.push(ZipMember {
+      entry: ArchiveEntry {
+        is_dir: name.ends_with('/'),
+        name,
+        size,
+        modified: dos_datetime(date, time),
+      },
+      method,
+      flags,
+      csize,
+      offset,
+    });
+    pos += end + comment_len;
+  }
+  // A central directory cut short still lists what it holds
+  let listed = out.len() as u64;
+  Ok((out, listed < count))
+}
+
+fn list_zip(
+  path: &Path,
+  max_entries: usize,
+) -> io::Result<(Vec<ArchiveEntry>, bool)>
+{
+  let (members, truncated) = zip_members(path, max_entries)?;
+  Ok((members.into_iter().map(|m| m.entry).collect(), truncated))
+}
+
+/// Member name without a leading `./` or trailing `/`.
+fn normalize_name(name: &str) -> &str
+{
+  name.trim_start_matches("./").trim_end_matches('/')
+}
+
+/// Where a member named `name` lands when extracting `inner` to `dest`:
+/// `inner` itself maps to `dest`, members below it keep their relative path.
+/// Returns `None` for unrelated members and for paths escaping `dest`.
+fn member_target(
+  name: &str,
+  inner: &str,
+  dest: &Path,
+) -> Option<PathBuf>
+{
+  let name = normalize_name(name);
+  let rel = if inner.is_empty()
+  {
+    name
+  }
+  else if name == inner
+  {
+    return Some(dest.to_path_buf());
+  }
+  else
+  {
+    name.strip_prefix(inner)?.strip_prefix('/')?
+  };
+  let rel = Path::new(rel);
+  if rel.as_os_str().is_empty()
+    || !rel.components().all(|c| matches!(c, Component::Normal(_)))
+  {
+    return None;
+  }
+  Some(dest.join(rel))
+}
+
+/// Write decoded member data to `out`, reporting progress. Stashes the first
+/// error so it can be returned after the decoder stops.
+fn write_chunk(
+  out: &mut File,
+  chunk: &[u8],
+  on_progress: &mut dyn FnMut(CopyProgress) -> io::Result<()>,
+  error: &mut Option<io::Error>,
+) -> bool
+{
+  match out
+    .write_all(chunk)
+    .and_then(|_| on_progress(CopyProgress::Bytes(chunk.len() as u64)))
+  {
+    Ok(()) => true,
+    Err(e) =>
+    {
+      *error = Some(e);
+      false
+    }
+  }
+}
+
+/// Extract the member `inner` of an archive (a file, or a directory with
+/// everything below it; `""` for the whole archive) to `dest`.
+pub fn extract(
+  path: &Path,
+  kind: ArchiveKind,
+  inner: &str,
+  dest: &Path,
+  on_progress: &mut dyn FnMut(CopyProgress) -> io::Result<()>,
+) -> io::Result<()>
+{
+  let matched = match kind
+  {
+    ArchiveKind::Zip => extract_zip(path, inner, dest, on_progress)?,
+    ArchiveKind::Tar | ArchiveKind::TarGz =>
+    {
+      let mut scan = TarScanner::extracting(
+        inner.to_string(),
+        dest.to_path_buf(),
+        on_progress,
+      );
+      let res = if kind == ArchiveKind::Tar
+      {
+        let mut f = BufReader::new(File::open(path)?);
+        let mut buf = vec![0u8; 64 * 1024];
+        loop
+        {
+          let n = f.read(&mut buf)?;
+          if n == 0 || !scan.feed(&buf[..n])
+          {
+            break Ok(());
+          }
+        }
+      }
+      else
+      {
+        crate::core::inflate::gunzip(File::open(path)?, |chunk| {
+          scan.feed(chunk)
+        })
+      };
+      let ex = scan.extract.take().expect("extracting scanner");
+      if let Some(e) = ex.error
+      {
+        return Err(e);
+      }
+      res?;
+      ex.matched
+    }
+    ArchiveKind::SevenZip =>
+    {
+      return Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "extracting from 7z archives is not supported",
+      ));
+    }
+  };
+  if !matched
+  {
+    return Err(io::Error::new(
+      io::ErrorKind::NotFound,
+      format!("no member '{}' in archive", inner),
+    ));
+  }
+  Ok(())
+}
+
+fn extract_zip(
+  path: &Path,
+  inner: &str,
+  dest: &Path,
+  on_progress: &mut dyn FnMut(CopyProgress) -> io::Result<()>,
+) -> io::Result<bool>
+{
+  let (members, _) = zip_members(path, usize::MAX)?;
+  let mut f = File::open(path)?;
+  let mut matched = false;
+  for m in members
+  {
+    let Some(target) = member_target(&m.entry.name, inner, dest)
+    else
+    {
+      continue;
+    };
+    matched = true;
+    if m.entry.is_dir
+    {
+      fs::create_dir_all(&target)?;
+      continue;
+    }
+    if m.flags & 1 != 0
+    {
+      return Err(zip_err(&format!("{} is encrypted", m.entry.name)));
+    }
+    if let Some(parent) = target.parent()
+    {
+      fs::create_dir_all(parent)?;
+    }
+    // Local header: fixed 30 bytes, then name and extra field
+    f.seek(SeekFrom::Start(m.offset))?;
+    let mut lh = [0u8; 30];
+    f.read_exact(&mut lh)?;
+    if lh[..4] != [0x50, 0x4b, 0x03, 0x04]
+    {
+      return Err(zip_err("bad local header"));
+    }
+    let skip = le16(&lh[26..]) as i64 + le16(&lh[28..]) as i64;
+    f.seek(SeekFrom::Current(skip))?;
+    let data = BufReader::new((&mut f).take(m.csize));
+    let mut out = File::create(&target)?;
+    let mut error = None;
+    match m.method
+    {
+      0 =>
+      {
+        let mut data = data;
+        let mut buf = vec![0u8; 64 * 1024];
+        loop
+        {
+          let n = data.read(&mut buf)?;
+          if n == 0
+            || !write_chunk(&mut out, &buf[..n], on_progress, &mut error)
+          {
+            break;
+          }
+        }
+      }
+      8 => crate::core::inflate::inflate_raw(data, |chunk| {
+        write_chunk(&mut out, chunk, on_progress, &mut error)
+      })?,
+      other =>
+      {
+        return Err(zip_err(&format!(
+          "{} uses unsupported compression method {}",
+          m.entry.name, other
+        )));
+      }
+    }
+    if let Some(e) = error
+    {
+      return Err(e);
+    }
+    on_progress(CopyProgress::FileDone)?;
+  }
+  Ok(matched)
+}
+
+/// Parse a tar numeric field: octal text, or base-256 when the high bit of
+/// the first byte is set (GNU extension for large values).
+fn tar_number(field: &[u8]) -> u64
+{
+  if field.first().is_some_and(|b| b & 0x80 != 0)
+  {
+    let mut v = (field[0] & 0x7f) as u64;
+    for &b in &field[1..]
+    {
+      v = (v << 8) | b as u64;
+    }
+    return v;
+  }
+  let s = String::from_utf8_lossy(field);
+  u64::from_str_radix(s.trim_matches(|c: char| c == '\0' || c == ' '), 8)
+    .unwrap_or(0)
+}
+
+fn tar_string(field: &[u8]) -> String
+{
+  let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+  String::from_utf8_lossy(&field[..end]).to_string()
+}
+
+/// What the bytes after the current header are used for.
+enum TarData
+{
+  Skip,
+  LongName,
+  Pax,
+  Extract,
+}
+
+/// Extraction state for a [`TarScanner`] writing matching members to disk.
+struct TarExtract<'a>
+{
+  inner:       String,
+  dest:        PathBuf,
+  on_progress: &'a mut dyn FnMut(CopyProgress) -> io::Result<()>,
+  file:        Option<File>,
+  matched:     bool,
+  error:       Option<io::Error>,
+}
+
+/// Push parser for a tar stream: collects entries from headers and skips
+/// member data, so it can be fed straight from a decompressor. In extract
+/// mode, matching members are written out instead of collected.
+struct TarScanner<'a>
+{
+  max:       usize,
+  entries:   Vec<ArchiveEntry>,
+  header:    Vec<u8>,
+  remaining: u64,
+  padding:   u64,
+  data:      TarData,
+  captured:  Vec<u8>,
+  long_name: Option<String>,
+  pax_path:  Option<String>,
+  pax_size:  Option<u64>,
+  truncated: bool,
+  done:      bool,
+  extract:   Option<TarExtract<'a>>,
+}
+
+impl<'a> TarScanner<'a>
+{
+  fn new(max: usize) -> Self
+  {
+    TarScanner {
+      max,
+      entries: Vec::new(),
+      header: Vec::with_capacity(512),
+      remaining: 0,
+      padding: 0,
+      data: TarData::Skip,
+      captured: Vec::new(),
+      long_name: None,
+      pax_path: None,
+      pax_size: None,
+      truncated: false,
+      done: false,
+      extract: None,
+    }
+  }
+
+  fn extracting(
+    inner: String,
+    dest: PathBuf,
+    on_progress: &'a mut dyn FnMut(CopyProgress) -> io::Result<()>,
+  ) -> Self
+  {
+    let mut scan = TarScanner::new(usize::MAX);
+    scan.extract = Some(TarExtract {
+      inner,
+      dest,
+      on_progress,
+      file: None,
+      matched: false,
+      error: None,
+    });
+    scan
+  }
+
+  /// Feed more of the stream. Returns false once no more input is needed.
+  fn feed(
+    &mut self,
+    mut buf: &[u8],
+  ) -> bool
+  {
+    while !buf.is_empty() && !self.done
+    {
+      if self.remaining > 0
+      {
+        let n = (self.remaining as usize).min(buf.len());
+        match self.data
+        {
+          TarData::Skip =>
+          {}
+          TarData::Extract => self.write_member(&buf[..n]),
+          _ if self.captured.len() < 1 << 20 =>
+          {
+            self.captured.extend_from_slice(&buf[..n]);
+          }
+          _ =>
+          {}
+        }
+        self.remaining -= n as u64;
+        buf = &buf[n..];
+        if self.remaining == 0
+        {
+          self.data_done();
+        }
+        continue;
+      }
+      if self.padding > 0
+      {
+        let n = (self.padding as usize).min(buf.len());
+        self.padding -= n as u64;
+        buf = &buf[n..];
+        continue;
+      }
+      let n = (512 - self.header.len()).min(buf.len());
+      self.header.extend_from_slice(&buf[..n]);
+      buf = &buf[n..];
+      if self.header.len() == 512
+      {
+        let header = std::mem::take(&mut self.header);
+        self.on_header(&header);
+        self.header = header;
+        self.header.clear();
+      }
+    }
+    !self.done
+  }
+
+  /// Bytes of member data and padding up to the next header that need not
+  /// be fed at all; the caller seeks over them instead.
+  fn take_skip(&mut self) -> u64
+  {
+    if self.extract.is_some() || !matches!(self.data, TarData::Skip)
+    {
+      return 0;
+    }
+    std::mem::take(&mut self.remaining) + std::mem::take(&mut self.padding)
+  }
+
+  fn write_member(
+    &mut self,
+    chunk: &[u8],
+  )
+  {
+    let Some(ex) = self.extract.as_mut()
+    else
+    {
+      return;
+    };
+    if let Some(f) = ex.file.as_mut()
+      && !write_chunk(f, chunk, ex.on_progress, &mut ex.error)
+    {
+      self.done = true;
+    }
+  }
+
+  fn data_done(&mut self)
+  {
+    let data = std::mem::take(&mut self.captured);
+    match self.data
+    {
+      TarData::LongName => self.long_name = Some(tar_string(&data)),
+      TarData::Pax => self.parse_pax(&data),
+      TarData::Extract => self.member_done(),
+      TarData::Skip =>
+      {}
+    }
+    self.data = TarData::Skip;
+  }
+
+  fn member_done(&mut self)
+  {
+    if let Some(ex) = self.extract.as_mut()
+    {
+      ex.file = None;
+      if let Err(e) = (ex.on_progress)(CopyProgress::FileDone)
+      {
+        ex.error = Some(e);
+        self.done = true;
+      }
+    }
+  }
+
+  /// Pax records look like `"<len> <key>=<value>\n"`.
+  fn parse_pax(
+    &mut self,
+    data: &[u8],
+  )
+  {
+    let text = String::from_utf8_lossy(data);
+    for rec in text.split('\n')
+    {
+      let Some((_, kv)) = rec.split_once(' ')
+      else
+      {
+        continue;
+      };
+      match kv.split_once('=')
+      {
+        Some(("path", v)) => self.pax_path = Some(v.to_string()),
+        Some(("size", v)) => self.pax_size = v.parse().ok(),
+        _ =>
+        {}
+      }
+    }
+  }
+
+  fn on_header(
+    &mut self,
+    h: &[u8],
+  )
+  {
+    if h.iter().all(|&b| b == 0)
+    {
+      // End-of-archive marker
+      self.done = true;
+      return;
+    }
+    let typeflag = h[156];
+    let mut size = tar_number(&h[124..136]);
+    match typeflag
+    {
+      b'L' | b'x' | b'g' | b'K' =>
+      {
+        self.remaining = size;
+        self.padding = (512 - size % 512) % 512;
+        self.data = match typeflag
+        {
+          b'L' => TarData::LongName,
+          b'x' => TarData::Pax,
+          // Global pax headers and link names are not needed
+          _ => TarData::Skip,
+        };
+        return;
+      }
+      _ =>
+      {}
+    }
+    let mut name = tar_string(&h[..100]);
+    if &h[257..262] == b"ustar"
+    {
+      let prefix = tar_string(&h[345..500]);
+      if !prefix.is_empty()
+      {
+        name = format!("{}/{}", prefix, name);
+      }
+    }
+    if let Some(long) = self.long_name.take()
+    {
+      name = long;
+    }
+    if let Some(p) = self.pax_path.take()
+    {
+      name = p;
+    }
+    if let Some(s) = self.pax_size.take()
+    {
+      size = s;
+    }
+    // Only regular files carry data; links and directories do not
+    let is_file = matches!(typeflag, b'0' | b'\0' | b'7');
+    if is_file
+    {
+      self.remaining = size;
+      self.padding = (512 - size % 512) % 512;
+    }
+    let is_dir = typeflag == b'5' || name.ends_with('/');
+    let name = name.strip_prefix("./").unwrap_or(&name).to_string();
+    if name.is_empty()
+    {
+      return;
+    }
+    if self.extract.is_some()
+    {
+      self.extract_member(&name, is_dir, is_file);
+      return;
+    }
+    if self.entries.len() >= self.max
+    {
+      self.truncated = true;
+      self.done = true;
+      return;
+    }
+    let modified =
+      chrono::DateTime::from_timestamp(tar_number(&h[136..148]) as i64, 0)
+        .map(|d| d.with_timezone(&chrono::Local).naive_local());
+    self.entries.push(ArchiveEntry {
+      name,
+      size: if is_dir { 0 } else { size },
+      modified,
+      is_dir,
+    });
+  }
+
+  fn extract_member(
+    &mut self,
+    name: &str,
+    is_dir: bool,
+    is_file: bool,
+  )
+  {
+    let Some(ex) = self.extract.as_mut()
+    else
+    {
+      return;
+    };
+    let Some(target) = member_target(name, &ex.inner, &ex.dest)
+    else
+    {
+      return;
+    };
+    ex.matched = true;
+    let res = if is_dir
+    {
+      fs::create_dir_all(&target)
+    }
+    else if is_file
+    {
+      target
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| File::create(&target))
+        .map(|f| ex.file = Some(f))
+    }
+    else
+    {
+      // Links and special files are skipped
+      Ok(())
+    };
+    if let Err(e) = res
+    {
+      ex.error = Some(e);
+      self.done = true;
+      return;
+    }
+    if ex.file.is_some()
+    {
+      self.data = TarData::Extract;
+      if self.remaining == 0
+      {
+        self.member_done();
+      }
+    }
+  }
+
+  fn finish(self) -> (Vec<ArchiveEntry>, bool)
+  {
+    (self.entries, self.truncated)
+  }
+}
+
+/// Parse the `-slt` technical listing printed by 7-Zip.
+fn list_7z(
+  path: &Path,
+  max_entries: usize,
+) -> io::Result<(Vec<ArchiveEntry>, bool)>
+{
+  let mut output = None;
+  for tool in ["7z", "7zz", "7za"]
+  {
+    match Command::new(tool)
+      .args(["l", "-slt", "-ba", "--"])
+      .arg(path)
+      .stdin(Stdio::null())
+      .stderr(Stdio::null())
+      .output()
+    {
+      Ok(out) =>
+      {
+        output = Some(out);
+        break;
+      }
+      Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+      Err(e) => return Err(e),
+    }
+  }
+  let out = output.ok_or_else(|| {
+    io::Error::new(
+      io::ErrorKind::NotFound,
+      "listing 7z archives needs 7z, 7zz or 7za in PATH",
+    )
+  })?;
+  if !out.status.success()
+  {
+    return Err(io::Error::other("7z could not read the archive"));
+  }
+  let text = String::from_utf8_lossy(&out.stdout);
+  let mut entries = Vec::new();
+  let mut cur: Option<ArchiveEntry> = None;
+  for line in text.lines().chain(std::iter::once(""))
+  {
+    let line = line.trim_end();
+    if line.is_empty()
+    {
+      if let Some(e) = cur.take()
+      {
+        if entries.len() >= max_entries
+        {
+          return Ok((entries, true));
+        }
+        entries.push(e);
+      }
+      continue;
+    }
+    let Some((key, value)) = line.split_once(" = ")
+    else
+    {
+      continue;
+    };
+    let e = cur.get_or_insert_with(|| ArchiveEntry {
+      name:     String::new(),
+      size:     0,
+      modified: None,
+      is_dir:   false,
+    });
+    match key
+    {
+      "Path" => e.name = value.to_string(),
+      "Size" => e.size = value.parse().unwrap_or(0),
+      "Folder" => e.is_dir = value == "+",
+      "Attributes" => e.is_dir |= value.starts_with('D'),
+      "Modified" =>
+      {
+        e.modified = value.get(..19).and_then(|v| {
+          NaiveDateTime::parse_from_str(v, "%Y-%m-%d %H:%M:%S").ok()
+        });
+      }
+      _ =>
+      {}
+    }
+  }
+  Ok((entries, false))
+}
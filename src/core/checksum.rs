@@ -0,0 +1,395 @@
+//! MD5, SHA-1 and SHA-256 digests and checksum manifests.
+//!
+//! The three algorithms share 64-byte blocks and the same length padding, so
+//! one streaming [`Hasher`] drives them all. Manifests are read in the
+//! `sha256sum` format (`HASH  name`, or `HASH *name` for binary mode) and in
+//! the BSD format (`SHA256 (name) = HASH`).
+
+use std::{
+  fs::File,
+  io::{
+    self,
+    Read,
+  },
+  path::Path,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm
+{
+  Md5,
+  Sha1,
+  Sha256,
+}
+
+impl Algorithm
+{
+  pub fn from_name(name: &str) -> Option<Algorithm>
+  {
+    match name.to_ascii_lowercase().replace('-', "").as_str()
+    {
+      "md5" => Some(Algorithm::Md5),
+      "sha1" => Some(Algorithm::Sha1),
+      "sha256" => Some(Algorithm::Sha256),
+      _ => None,
+    }
+  }
+
+  /// The algorithm whose hex digests are `len` characters long.
+  pub fn from_hex_len(len: usize) -> Option<Algorithm>
+  {
+    match len
+    {
+      32 => Some(Algorithm::Md5),
+      40 => Some(Algorithm::Sha1),
+      64 => Some(Algorithm::Sha256),
+      _ => None,
+    }
+  }
+
+  /// Name of the matching coreutils tool, e.g. `sha256sum`.
+  pub fn tool(self) -> &'static str
+  {
+    match self
+    {
+      Algorithm::Md5 => "md5sum",
+      Algorithm::Sha1 => "sha1sum",
+      Algorithm::Sha256 => "sha256sum",
+    }
+  }
+}
+
+const MD5_SHIFTS: [u32; 16] =
+  [7, 12, 17, 22, 5, 9, 14, 20, 4, 11, 16, 23, 6, 10, 15, 21];
+
+const MD5_K: [u32; 64] = [
+  0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee, 0xf57c0faf, 0x4787c62a,
+  0xa8304613, 0xfd469501, 0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be,
+  0x6b901122, 0xfd987193, 0xa679438e, 0x49b40821, 0xf61e2562, 0xc040b340,
+  0x265e5a51, 0xe9b6c7aa, 0xd62f105d, 0x02441453, 0xd8a1e681, 0xe7d3fbc8,
+  0x21e1cde6, 0xc33707d6, 0xf4d50d87, 0x455a14ed, 0xa9e3e905, 0xfcefa3f8,
+  0x676f02d9, 0x8d2a4c8a, 0xfffa3942, 0x8771f681, 0x6d9d6122, 0xfde5380c,
+  0xa4beea44, 0x4bdecfa9, 0xf6bb4b60, 0xbebfbc70, 0x289b7ec6, 0xeaa127fa,
+  0xd4ef3085, 0x04881d05, 0xd9d4d039, 0xe6db99e5, 0x1fa27cf8, 0xc4ac5665,
+  0xf4292244, 0x432aff97, 0xab9423a7, 0xfc93a039, 0x655b59c3, 0x8f0ccc92,
+  0xffeff47d, 0x85845dd1, 0x6fa87e4f, 0xfe2ce6e0, 0xa3014314, 0x4e0811a1,
+  0xf7537e82, 0xbd3af235, 0x2ad7d2bb, 0xeb86d391,
+];
+
+const SHA256_K: [u32; 64] = [
+  0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1,
+  0x923f82a4, 0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3,
+  0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786,
+  0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+  0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147,
+  0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13,
+  0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+  0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+  0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a,
+  0x5b9cca4f, 0x682e6ff3, 0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208,
+  0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// Incremental digest of one of the supported algorithms.
+pub struct Hasher
+{
+  algo:  Algorithm,
+  state: [u32; 8],
+  block: [u8; 64],
+  fill:  usize,
+  len:   u64,
+}
+
+impl Hasher
+{
+  pub fn new(algo: Algorithm) -> Hasher
+  {
+    let state = match algo
+    {
+      Algorithm::Md5 =>
+      {
+        [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0, 0, 0, 0]
+      }
+      Algorithm::Sha1 =>
+      {
+        [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0, 0, 0, 0]
+      }
+      Algorithm::Sha256 => [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c,
+        0x1f83d9ab, 0x5be0cd19,
+      ],
+    };
+    Hasher { algo, state, block: [0; 64], fill: 0, len: 0 }
+  }
+
+  pub fn update(
+    &mut self,
+    mut data: &[u8],
+  )
+  {
+    self.len = self.len.wrapping_add(data.len() as u64);
+    while !data.is_empty()
+    {
+      let n = (64 - self.fill).min(data.len());
+      self.block[self.fill..self.fill + n].copy_from_slice(&data[..n]);
+      self.fill += n;
+      data = &data[n..];
+      if self.fill == 64
+      {
+        let block = self.block;
+        self.compress(&block);
+        self.fill = 0;
+      }
+    }
+  }
+
+  /// Pad, finish and return the lowercase hex digest.
+  pub fn finish(mut self) -> String
+  {
+    let bits = self.len.wrapping_mul(8);
+    let len_bytes = match self.algo
+    {
+      Algorithm::Md5 => bits.to_le_bytes(),
+      _ => bits.to_be_bytes(),
+    };
+    let pad = if self.fill < 56 { 56 - self.fill } else { 120 - self.fill };
+    let mut tail = vec![0u8; pad];
+    tail[0] = 0x80;
+    self.update(&tail);
+    self.update(&len_bytes);
+    let words = match self.algo
+    {
+      Algorithm::Md5 => 4,
+      Algorithm::Sha1 => 5,
+      Algorithm::Sha256 => 8,
+    };
+    self.state[..words]
+      .iter()
+      .map(|w| match self.algo
+      {
+        Algorithm::Md5 => format!("{:08x}", w.swap_bytes()),
+        _ => format!("{:08x}", w),
+      })
+      .collect()
+  }
+
+  fn compress(
+    &mut self,
+    block: &[u8; 64],
+  )
+  {
+    match self.algo
+    {
+      Algorithm::Md5 => self.md5_block(block),
+      Algorithm::Sha1 => self.sha1_block(block),
+      Algorithm::Sha256 => self.sha256_block(block),
+    }
+  }
+
+  fn md5_block(
+    &mut self,
+    block: &[u8; 64],
+  )
+  {
+    let m: Vec<u32> = block
+      .chunks_exact(4)
+      .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+      .collect();
+    let [mut a, mut b, mut c, mut d, ..] = self.state;
+    for i in 0..64
+    {
+      let (f, g) = match i / 16
+      {
+        0 => ((b & c) | (!b & d), i),
+        1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+        2 => (b ^ c ^ d, (3 * i + 5) % 16),
+        _ => (c ^ (b | !d), (7 * i) % 16),
+      };
+      let f = f.wrapping_add(a).wrapping_add(MD5_K[i]).wrapping_add(m[g]);
+      a = d;
+      d = c;
+      c = b;
+      b = b.wrapping_add(f.rotate_left(MD5_SHIFTS[(i / 16) * 4 + i % 4]));
+    }
+    for (s, v) in self.state.iter_mut().zip([a, b, c, d])
+    {
+      *s = s.wrapping_add(v);
+    }
+  }
+
+  fn sha1_block(
+    &mut self,
+    block: &[u8; 64],
+  )
+  {
+    let mut w = [0u32; 80];
+    for (i, c) in block.chunks_exact(4).enumerate()
+    {
+      w[i] = u32::from_be_bytes([c[0], c[1], c[2], c[3]]);
+    }
+    for i in 16..80
+    {
+      w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+    }
+    let [mut a, mut b, mut c, mut d, mut e, ..] = self.state;
+    for (i, wi) in w.iter().enumerate()
+    {
+      let (f, k) = match i / 20
+      {
+        0 => ((b & c) | (!b & d), 0x5a827999),
+        1 => (b ^ c ^ d, 0x6ed9eba1),
+        2 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+        _ => (b ^ c ^ d, 0xca62c1d6),
+      };
+      let t = a
+        .rotate_left(5)
+        .wrapping_add(f)
+        .wrapping_add(e)
+        .wrapping_add(k)
+        .wrapping_add(*wi);
+      e = d;
+      d = c;
+      c = b.rotate_left(30);
+      b = a;
+      a = t;
+    }
+    for (s, v) in self.state.iter_mut().zip([a, b, c, d, e])
+    {
+      *s = s.wrapping_add(v);
+    }
+  }
+
+  fn sha256_block(
+    &mut self,
+    block: &[u8; 64],
+  )
+  {
+    let mut w = [0u32; 64];
+    for (i, c) in block.chunks_exact(4).enumerate()
+    {
+      w[i] = u32::from_be_bytes([c[0], c[1], c[2], c[3]]);
+    }
+    for i in 16..64
+    {
+      let s0 = w[i - 15].rotate_right(7)
+        ^ w[i - 15].rotate_right(18)
+        ^ (w[i - 15] >> 3);
+      let s1 = w[i - 2].rotate_right(17)
+        ^ w[i - 2].rotate_right(19)
+        ^ (w[i - 2] >> 10);
+      w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+    }
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.state;
+    for i in 0..64
+    {
+      let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+      let ch = (e & f) ^ (!e & g);
+      let t1 = h
+        .wrapping_add(s1)
+        .wrapping_add(ch)
+        .wrapping_add(SHA256_K[i])
+        .wrapping_add(w[i]);
+      let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+      let maj = (a & b) ^ (a & c) ^ (b & c);
+      let t2 = s0.wrapping_add(maj);
+      h = g;
+      g = f;
+      f = e;
+      e = d.wrapping_add(t1);
+      d = c;
+      c = b;
+      b = a;
+      a = t1.wrapping_add(t2);
+    }
+    for (s, v) in self.state.iter_mut().zip([a, b, c, d, e, f, g, h])
+    {
+      *s = s.wrapping_add(v);
+    }
+  }
+}
+
+/// Hex digest of the file at `path`, read in chunks.
+pub fn file_digest(
+  algo: Algorithm,
+  path: &Path,
+) -> io::Result<String>
+{
+  let mut f = File::open(path)?;
+  let mut h = Hasher::new(algo);
+  let mut buf = vec![0u8; 64 * 1024];
+  loop
+  {
+    let n = f.read(&mut buf)?;
+    if n == 0
+    {
+      break;
+    }
+    h.update(&buf[..n]);
+  }
+  Ok(h.finish())
+}
+
+/// One line of a checksum manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestEntry
+{
+  pub algo: Algorithm,
+  /// Lowercase hex digest.
+  pub hash: String,
+  /// File name as written, relative to the manifest's directory.
+  pub name: String,
+}
+
+/// Entries of a manifest. Blank lines and `#` comments are skipped; other
+/// lines that cannot be read are returned by line number.
+pub fn parse_manifest(text: &str) -> (Vec<ManifestEntry>, Vec<usize>)
+{
+  let mut entries = Vec::new();
+  let mut bad = Vec::new();
+  for (i, line) in text.lines().enumerate()
+  {
+    let line = line.trim_end_matches('\r');
+    if line.trim().is_empty() || line.starts_with('#')
+    {
+      continue;
+    }
+    match parse_line(line)
+    {
+      Some(e) => entries.push(e),
+      None => bad.push(i + 1),
+    }
+  }
+  (entries, bad)
+}
+
+fn parse_line(line: &str) -> Option<ManifestEntry>
+{
+  // BSD style: SHA256 (name) = HASH
+  if let Some((head, hash)) = line.rsplit_once(") = ")
+    && let Some((tag, name)) = head.split_once(" (")
+  {
+    let algo = Algorithm::from_name(tag)?;
+    let hash = hash.trim().to_ascii_lowercase();
+    return (Algorithm::from_hex_len(hash.len()) == Some(algo)
+      && is_hex(&hash))
+    .then(|| ManifestEntry { algo, hash, name: name.to_string() });
+  }
+  // GNU style: HASH  name, or HASH *name for binary mode
+  let (hash, rest) = line.split_once(' ')?;
+  let name = rest.strip_prefix(['*', ' ']).unwrap_or(rest);
+  let algo = Algorithm::from_hex_len(hash.len())?;
+  if !is_hex(hash) || name.is_empty()
+  {
+    return None;
+  }
+  Some(ManifestEntry {
+    algo,
+    hash: hash.to_ascii_lowercase(),
+    name: name.to_string(),
+  })
+}
+
+fn is_hex(s: &str) -> bool
+{
+  s.chars().all(|c| c.is_ascii_hexdigit())
+}
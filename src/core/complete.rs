@@ -0,0 +1,153 @@
+//! Path completion for command pane arguments.
+//!
+//! A partial path is split at its last separator: the part before names the
+//! directory to list (relative to the current directory, with a leading `~`
+//! standing for the home directory) and the part after is matched against
+//! the names in it. The typed text is kept as written, so `~/sr` completes
+//! to `~/src/`.
+
+use std::path::{
+  Path,
+  PathBuf,
+};
+
+/// Result of completing one partial path.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathCompletion
+{
+  /// The word extended as far as all matches agree (unchanged when they
+  /// don't, or nothing matched). A unique directory gets a trailing `/`.
+  pub completed:  String,
+  /// Names of the matches, directories with a trailing `/`, when there is
+  /// more than one.
+  pub candidates: Vec<String>,
+}
+
+/// `$HOME`, or `%USERPROFILE%` on Windows.
+pub fn home_dir() -> Option<PathBuf>
+{
+  ["HOME", "USERPROFILE"]
+    .iter()
+    .filter_map(|v| std::env::var(v).ok())
+    .find(|s| !s.trim().is_empty())
+    .map(PathBuf::from)
+}
+
+/// Expand a leading `~` or `~/` in `s` to the home directory.
+pub fn expand_tilde(s: &str) -> PathBuf
+{
+  let rest = if s == "~"
+  {
+    Some("")
+  }
+  else
+  {
+    s.strip_prefix("~/").or_else(|| s.strip_prefix("~\\"))
+  };
+  match (rest, home_dir())
+  {
+    (Some(rest), Some(home)) => home.join(rest),
+    _ => PathBuf::from(s),
+  }
+}
+
+/// Resolve a path typed at the prompt: `~` expanded, relative paths taken
+/// from `cwd`.
+pub fn resolve_typed_path(
+  s: &str,
+  cwd: &Path,
+) -> PathBuf
+{
+  let p = expand_tilde(s);
+  if p.is_absolute() { p } else { cwd.join(p) }
+}
+
+fn common_prefix<'a>(names: impl Iterator<Item = &'a str>) -> String
+{
+  let mut out: Option<String> = None;
+  for n in names
+  {
+    out = Some(match out
+    {
+      None => n.to_string(),
+      Some(p) => p
+        .chars()
+        .zip(n.chars())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a)
+        .collect(),
+    });
+  }
+  out.unwrap_or_default()
+}
+
+/// Complete `word` against the entries of the directory it names. Hidden
+/// entries are offered when `show_hidden` is set or the name typed so far
+/// starts with a dot.
+pub fn complete_path(
+  word: &str,
+  cwd: &Path,
+  show_hidden: bool,
+) -> PathCompletion
+{
+  let split = word.rfind(['/', std::path::MAIN_SEPARATOR]).map(|i| i + 1);
+  let (dir_part, name_part) = word.split_at(split.unwrap_or(0));
+  let dir = if dir_part.is_empty()
+  {
+    if word == "~"
+    {
+      // Bare `~` completes to the home directory itself
+      return PathCompletion {
+        completed:  "~/".to_string(),
+        candidates: Vec::new(),
+      };
+    }
+    cwd.to_path_buf()
+  }
+  else
+  {
+    resolve_typed_path(dir_part, cwd)
+  };
+  let mut matches: Vec<(String, bool)> = std::fs::read_dir(&dir)
+    .map(|rd| {
+      rd.flatten()
+        .filter_map(|de| {
+          let name = de.file_name().to_string_lossy().to_string();
+          let visible =
+            show_hidden || !name.starts_with('.') || name_part.starts_with('.');
+          (visible && name.starts_with(name_part)).then(|| {
+            // Follow symlinks so links to directories complete as such
+            let is_dir = de.path().is_dir();
+            (name, is_dir)
+          })
+        })
+        .collect()
+    })
+    .unwrap_or_default();
+  matches.sort();
+  let mut out =
+    PathCompletion { completed: word.to_string(), candidates: Vec::new() };
+  match matches.as_slice()
+  {
+    [] =>
+    {}
+    [(name, is_dir)] =>
+    {
+      out.completed =
+        format!("{}{}{}", dir_part, name, if *is_dir { "/" } else { "" });
+    }
+    _ =>
+    {
+      let prefix = common_prefix(matches.iter().map(|(n, _)| n.as_str()));
+      if prefix.len() > name_part.len()
+      {
+        out.completed = format!("{}{}", dir_part, prefix);
+      }
+      out.candidates = matches
+        .into_iter()
+        .map(|(n, is_dir)| if is_dir { format!("{}/", n) } else { n })
+        .collect();
+    }
+  }
+  out
+}
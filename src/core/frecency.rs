@@ -0,0 +1,221 @@
+//! Frecency database of visited directories, for `:z`.
+//!
+//! Each directory has a rank, bumped on every visit, and the time of its
+//! last visit. Its score is the rank weighted by how recent that visit was,
+//! as in zoxide. The database is stored one directory per line as
+//! `path|rank|time`, the format of `z`'s `~/.z`. Ranks are aged once their
+//! total grows past [`MAX_TOTAL_RANK`], so directories no longer visited
+//! eventually drop out.
+
+use std::{
+  fs,
+  io::{
+    self,
+    Write,
+  },
+  path::{
+    Path,
+    PathBuf,
+  },
+  process::Command,
+};
+
+/// Aging starts once the ranks add up to more than this.
+pub const MAX_TOTAL_RANK: f64 = 10_000.0;
+
+/// A visited directory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Visit
+{
+  pub path: PathBuf,
+  pub rank: f64,
+  /// Seconds since the Unix epoch.
+  pub last: u64,
+}
+
+/// Seconds since the Unix epoch.
+pub fn now() -> u64
+{
+  std::time::SystemTime::now()
+    .duration_since(std::time::UNIX_EPOCH)
+    .map(|d| d.as_secs())
+    .unwrap_or(0)
+}
+
+/// Read the database; a missing file or malformed lines are skipped.
+pub fn load_db(path: &Path) -> Vec<Visit>
+{
+  let Ok(text) = fs::read_to_string(path)
+  else
+  {
+    return Vec::new();
+  };
+  text
+    .lines()
+    .filter_map(|line| {
+      // The path may itself contain '|', so split from the right
+      let mut parts = line.rsplitn(3, '|');
+      let last = parts.next()?.trim().parse().ok()?;
+      let rank = parts.next()?.trim().parse().ok()?;
+      let path = parts.next().filter(|p| !p.is_empty())?;
+      Some(Visit { path: PathBuf::from(path), rank, last })
+    })
+    .collect()
+}
+
+/// Write `db` to `path`, replacing the file atomically.
+pub fn save_db(
+  path: &Path,
+  db: &[Visit],
+) -> io::Result<()>
+{
+  if let Some(parent) = path.parent()
+  {
+    let _ = fs::create_dir_all(parent);
+  }
+  let mut tmp = path.to_path_buf();
+  tmp.set_extension("tmp");
+  let mut f = fs::File::create(&tmp)?;
+  for v in db.iter().filter(|v| !v.path.to_string_lossy().contains('\n'))
+  {
+    writeln!(f, "{}|{}|{}", v.path.display(), v.rank, v.last)?;
+  }
+  f.flush()?;
+  fs::rename(tmp, path)?;
+  Ok(())
+}
+
+/// Record a visit to `dir` at `at`, aging the ranks when they grow too
+/// large.
+pub fn add_visit(
+  db: &mut Vec<Visit>,
+  dir: &Path,
+  at: u64,
+)
+{
+  match db.iter_mut().find(|v| v.path == dir)
+  {
+    Some(v) =>
+    {
+      v.rank += 1.0;
+      v.last = v.last.max(at);
+    }
+    None => db.push(Visit { path: dir.to_path_buf(), rank: 1.0, last: at }),
+  }
+  let total: f64 = db.iter().map(|v| v.rank).sum();
+  if total > MAX_TOTAL_RANK
+  {
+    let factor = 0.9 * MAX_TOTAL_RANK / total;
+    for v in db.iter_mut()
+    {
+      v.rank *= factor;
+    }
+    db.retain(|v| v.rank >= 1.0);
+  }
+}
+
+/// Rank weighted by the time since the last visit.
+pub fn score(
+  v: &Visit,
+  now: u64,
+) -> f64
+{
+  let age = now.saturating_sub(v.last);
+  let weight = match age
+  {
+    a if a < 60 * 60 => 4.0,
+    a if a < 24 * 60 * 60 => 2.0,
+    a if a < 7 * 24 * 60 * 60 => 0.5,
+    _ => 0.25,
+  };
+  v.rank * weight
+}
+
+/// Whether `path` matches the space separated keywords of `query`: each
+/// keyword must appear in order (ignoring case), and the last one within the
+/// final path component. An empty query matches everything.
+pub fn matches(
+  query: &str,
+  path: &Path,
+) -> bool
+{
+  let text = path.to_string_lossy().to_lowercase();
+  let keywords: Vec<String> =
+    query.split_whitespace().map(|k| k.to_lowercase()).collect();
+  let mut from = 0;
+  for k in &keywords
+  {
+    match text[from..].find(k.as_str())
+    {
+      Some(i) => from += i + k.len(),
+      None => return false,
+    }
+  }
+  let Some(last) = keywords.last()
+  else
+  {
+    return true;
+  };
+  let name = path
+    .file_name()
+    .map(|s| s.to_string_lossy().to_lowercase())
+    .unwrap_or_default();
+  name.contains(last.as_str())
+}
+
+/// Directories in `db` matching `query` with their scores.
+pub fn scored_matches(
+  db: &[Visit],
+  query: &str,
+  now: u64,
+) -> Vec<(f64, PathBuf)>
+{
+  db.iter()
+    .filter(|v| matches(query, &v.path))
+    .map(|v| (score(v, now), v.path.clone()))
+    .collect()
+}
+
+/// Parse `zoxide query --list --score` output: a score and a path per line.
+pub fn parse_zoxide_scores(text: &str) -> Vec<(f64, PathBuf)>
+{
+  text
+    .lines()
+    .filter_map(|line| {
+      let (score, path) = line.trim_start().split_once(' ')?;
+      let path = path.trim_start();
+      if path.is_empty()
+      {
+        return None;
+      }
+      Some((score.parse().ok()?, PathBuf::from(path)))
+    })
+    .collect()
+}
+
+/// Directories known to zoxide with their scores, when it is installed.
+pub fn zoxide_scores() -> Vec<(f64, PathBuf)>
+{
+  Command::new("zoxide")
+    .args(["query", "--list", "--score"])
+    .output()
+    .ok()
+    .filter(|o| o.status.success())
+    .map(|o| parse_zoxide_scores(&String::from_utf8_lossy(&o.stdout)))
+    .unwrap_or_default()
+}
+
+/// Tell zoxide about a visit to `dir`, without waiting for it.
+pub fn zoxide_add(dir: &Path)
+{
+  let dir = dir.to_path_buf();
+  std::thread::spawn(move || {
+    let _ = Command::new("zoxide")
+      .arg("add")
+      .arg(&dir)
+      .stdin(std::process::Stdio::null())
+      .stdout(std::process::Stdio::null())
+      .stderr(std::process::Stdio::null())
+      .status();
+  });
+}
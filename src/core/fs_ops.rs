@@ -1,8 +1,20 @@
 use std::{
   io,
-  path::Path,
+  path::{
+    Component,
+    Path,
+    PathBuf,
+  },
 };
 
+/// Links `:paste_symlink` and `:paste_hardlink` create.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind
+{
+  Symlink,
+  Hardlink,
+}
+
 /// Recursively copy a file or directory tree from `src` to `dst`.
 pub fn copy_path_recursive(
   src: &Path,
@@ -54,6 +66,54 @@ pub fn move_path_with_fallback(
   }
 }
 
+/// `target` as seen from `dir`, e.g. `../src/main.rs`. Both paths are taken
+/// as they are, without resolving symlinks.
+pub fn relative_path(
+  dir: &Path,
+  target: &Path,
+) -> PathBuf
+{
+  let from: Vec<Component> = dir.components().collect();
+  let to: Vec<Component> = target.components().collect();
+  let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+  let mut out = PathBuf::new();
+  for _ in common..from.len()
+  {
+    out.push("..");
+  }
+  for c in &to[common..]
+  {
+    out.push(c);
+  }
+  if out.as_os_str().is_empty() { PathBuf::from(".") } else { out }
+}
+
+/// Create a symlink at `link` pointing to `target`. `is_dir` picks the kind
+/// of link on Windows, where the two differ.
+pub fn symlink(
+  target: &Path,
+  link: &Path,
+  is_dir: bool,
+) -> io::Result<()>
+{
+  #[cfg(unix)]
+  {
+    let _ = is_dir;
+    std::os::unix::fs::symlink(target, link)
+  }
+  #[cfg(windows)]
+  {
+    if is_dir
+    {
+      std::os::windows::fs::symlink_dir(target, link)
+    }
+    else
+    {
+      std::os::windows::fs::symlink_file(target, link)
+    }
+  }
+}
+
 /// Remove a path (file or directory recursively).
 pub fn remove_path_all(path: &Path) -> io::Result<()>
 {
@@ -66,3 +126,206 @@ pub fn remove_path_all(path: &Path) -> io::Result<()>
     std::fs::remove_file(path)
   }
 }
+
+/// `path` itself when nothing is there, otherwise the first free
+/// `stem_N.ext` beside it (`notes_1.txt`, `notes_2.txt`, ...).
+pub fn free_name(path: &Path) -> PathBuf
+{
+  free_name_where(path, |p| p.symlink_metadata().is_ok())
+}
+
+/// [`free_name`] for places that cannot be checked on disk: `taken` tells
+/// whether a candidate is in use.
+pub fn free_name_where(
+  path: &Path,
+  taken: impl Fn(&Path) -> bool,
+) -> PathBuf
+{
+  if !taken(path)
+  {
+    return path.to_path_buf();
+  }
+  let stem = path.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default();
+  let ext = path
+    .extension()
+    .map(|e| format!(".{}", e.to_string_lossy()))
+    .unwrap_or_default();
+  (1..)
+    .map(|n| path.with_file_name(format!("{}_{}{}", stem, n, ext)))
+    .find(|p| !taken(p))
+    .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Progress notifications emitted by [`copy_path_with_progress`].
+pub enum CopyProgress
+{
+  /// A chunk of this many bytes was written.
+  Bytes(u64),
+  /// A regular file finished copying.
+  FileDone,
+}
+
+/// Count bytes and regular files under `path` without following symlinks.
+pub fn path_totals(path: &Path) -> (u64, u64)
+{
+  let Ok(meta) = std::fs::symlink_metadata(path)
+  else
+  {
+    return (0, 0);
+  };
+  if meta.is_dir()
+  {
+    let mut totals = (0u64, 0u64);
+    if let Ok(rd) = std::fs::read_dir(path)
+    {
+      for de in rd.flatten()
+      {
+        let (b, f) = path_totals(&de.path());
+        totals.0 += b;
+        totals.1 += f;
+      }
+    }
+    totals
+  }
+  else
+  {
+    (meta.len(), 1)
+  }
+}
+
+/// Recursively copy `src` to `dst`, reporting progress to `on_progress`.
+/// An error returned from the callback aborts the copy (used for cancelling).
+/// Symlinks are copied as links, counted like [`path_totals`] does; where
+/// making links needs privileges (Windows) what they point to is copied.
+pub fn copy_path_with_progress(
+  src: &Path,
+  dst: &Path,
+  on_progress: &mut dyn FnMut(CopyProgress) -> io::Result<()>,
+) -> io::Result<()>
+{
+  use std::io::{
+    Read,
+    Write,
+  };
+  let meta = std::fs::symlink_metadata(src)?;
+  #[cfg(unix)]
+  if meta.is_symlink()
+  {
+    std::os::unix::fs::symlink(std::fs::read_link(src)?, dst)?;
+    on_progress(CopyProgress::Bytes(meta.len()))?;
+    return on_progress(CopyProgress::FileDone);
+  }
+  #[cfg(not(unix))]
+  let meta = if meta.is_symlink() { std::fs::metadata(src)? } else { meta };
+  if meta.is_dir()
+  {
+    std::fs::create_dir_all(dst)?;
+    for entry in std::fs::read_dir(src)?
+    {
+      let de = entry?;
+      copy_path_with_progress(
+        &de.path(),
+        &dst.join(de.file_name()),
+        on_progress,
+      )?;
+    }
+    return Ok(());
+  }
+  let mut reader = std::fs::File::open(src)?;
+  let mut writer = std::fs::File::create(dst)?;
+  let mut buf = vec![0u8; 256 * 1024];
+  loop
+  {
+    let n = reader.read(&mut buf)?;
+    if n == 0
+    {
+      break;
+    }
+    writer.write_all(&buf[..n])?;
+    on_progress(CopyProgress::Bytes(n as u64))?;
+  }
+  writer.set_permissions(meta.permissions())?;
+  on_progress(CopyProgress::FileDone)
+}
+
+/// `path` with `.` and `..` components resolved without touching the disk.
+pub fn normalize(path: &Path) -> PathBuf
+{
+  let mut out = PathBuf::new();
+  for c in path.components()
+  {
+    match c
+    {
+      Component::CurDir =>
+      {}
+      Component::ParentDir =>
+      {
+        if !out.pop()
+        {
+          out.push(c);
+        }
+      }
+      _ => out.push(c),
+    }
+  }
+  out
+}
+
+/// Refuse removing a path a script is unlikely to mean: a filesystem root,
+/// the home directory, or `cwd` and the directories holding it.
+pub fn check_remove(
+  path: &Path,
+  cwd: &Path,
+  home: Option<&Path>,
+) -> Result<(), String>
+{
+  if path.parent().is_none()
+  {
+    return Err(format!("refusing to remove the root {}", path.display()));
+  }
+  if home == Some(path)
+  {
+    return Err(format!(
+      "refusing to remove the home directory {}",
+      path.display()
+    ));
+  }
+  if cwd.starts_with(path)
+  {
+    return Err(format!(
+      "refusing to remove {}, which holds the current directory",
+      path.display()
+    ));
+  }
+  if path.symlink_metadata().is_err()
+  {
+    return Err(format!("no such file or directory: {}", path.display()));
+  }
+  Ok(())
+}
+
+/// Refuse copying or moving `src` to `dest` when `src` is missing, `dest`
+/// is taken or has no directory to go in, or `dest` lies inside `src`.
+pub fn check_transfer(
+  src: &Path,
+  dest: &Path,
+) -> Result<(), String>
+{
+  if src.symlink_metadata().is_err()
+  {
+    return Err(format!("no such file or directory: {}", src.display()));
+  }
+  if dest.starts_with(src)
+  {
+    return Err(format!("refusing to put {} inside itself", src.display()));
+  }
+  if dest.symlink_metadata().is_ok()
+  {
+    return Err(format!("already exists: {}", dest.display()));
+  }
+  if !dest.parent().is_some_and(Path::is_dir)
+  {
+    return Err(format!("no directory to hold {}", dest.display()));
+  }
+  Ok(())
+}
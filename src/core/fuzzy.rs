@@ -0,0 +1,111 @@
+//! Fuzzy matching for the file finder and `/` search.
+
+/// Score `candidate` against `query` as a case-insensitive subsequence match.
+///
+/// Returns `None` when the query characters do not all appear in order.
+/// Higher scores are better: consecutive matches, matches at the start of a
+/// path segment or word, and matches inside the file name (after the last
+/// `/`) are rewarded; gaps and long candidates cost a little.
+pub fn fuzzy_score(
+  query: &str,
+  candidate: &str,
+) -> Option<i64>
+{
+  let query: Vec<char> = query
+    .chars()
+    .filter(|c| !c.is_whitespace())
+    .filter_map(|c| c.to_lowercase().next())
+    .collect();
+  if query.is_empty()
+  {
+    return Some(0);
+  }
+  let name_start = candidate.rfind('/').map(|i| i + 1).unwrap_or(0);
+  let mut score = 0i64;
+  let mut qi = 0usize;
+  let mut prev: Option<char> = None;
+  let mut last_match: Option<usize> = None;
+  for (pos, (byte_idx, ch)) in candidate.char_indices().enumerate()
+  {
+    if qi == query.len()
+    {
+      break;
+    }
+    if ch.to_lowercase().next() == Some(query[qi])
+    {
+      let mut s = 1;
+      match last_match
+      {
+        Some(lm) if lm + 1 == pos => s += 15,
+        Some(lm) => s -= ((pos - lm - 1) as i64).min(10),
+        None => s -= (pos as i64).min(10),
+      }
+      if matches!(prev, None | Some('/' | '_' | '-' | '.' | ' '))
+        || (prev.is_some_and(|p| p.is_lowercase()) && ch.is_uppercase())
+      {
+        s += 10;
+      }
+      if byte_idx >= name_start
+      {
+        s += 5;
+      }
+      score += s;
+      last_match = Some(pos);
+      qi += 1;
+    }
+    prev = Some(ch);
+  }
+  if qi < query.len()
+  {
+    return None;
+  }
+  // Prefer shorter paths among otherwise equal matches
+  Some(score * 4 - (candidate.chars().count() as i64).min(200) / 4)
+}
+
+/// Character indices of `candidate` matched by `query`, as picked by
+/// [`fuzzy_score`], or `None` when it doesn't match.
+pub fn fuzzy_positions(
+  query: &str,
+  candidate: &str,
+) -> Option<Vec<usize>>
+{
+  let mut query = query
+    .chars()
+    .filter(|c| !c.is_whitespace())
+    .filter_map(|c| c.to_lowercase().next())
+    .peekable();
+  let mut out = Vec::new();
+  for (pos, ch) in candidate.chars().enumerate()
+  {
+    let Some(&q) = query.peek()
+    else
+    {
+      break;
+    };
+    if ch.to_lowercase().next() == Some(q)
+    {
+      out.push(pos);
+      query.next();
+    }
+  }
+  query.peek().is_none().then_some(out)
+}
+
+/// Rank `candidates` by [`fuzzy_score`], best first, keeping at most `limit`
+/// results. Returns indices into `candidates` with their scores.
+pub fn rank<S: AsRef<str>>(
+  query: &str,
+  candidates: &[S],
+  limit: usize,
+) -> Vec<(usize, i64)>
+{
+  let mut out: Vec<(usize, i64)> = candidates
+    .iter()
+    .enumerate()
+    .filter_map(|(i, c)| fuzzy_score(query, c.as_ref()).map(|s| (i, s)))
+    .collect();
+  out.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+  out.truncate(limit);
+  out
+}
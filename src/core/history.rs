@@ -0,0 +1,75 @@
+//! Persistent history of command pane input.
+//!
+//! One entry per line, oldest first, each starting with the prompt it was
+//! entered at (`:` for commands, `/` for searches), e.g. `:cd ~/src`.
+
+use std::{
+  fs,
+  io::{
+    self,
+    Write,
+  },
+  path::Path,
+};
+
+/// Entries kept in the history file.
+pub const MAX_ENTRIES: usize = 1000;
+
+/// Read the history file; a missing or unreadable file is an empty history.
+pub fn load_history(path: &Path) -> Vec<String>
+{
+  fs::read_to_string(path)
+    .map(|text| {
+      text.lines().filter(|l| !l.trim().is_empty()).map(String::from).collect()
+    })
+    .unwrap_or_default()
+}
+
+/// Append `entry` to `entries`, dropping an earlier copy of it and the
+/// oldest entries beyond `max`.
+pub fn push_entry(
+  entries: &mut Vec<String>,
+  entry: &str,
+  max: usize,
+)
+{
+  entries.retain(|e| e != entry);
+  entries.push(entry.to_string());
+  if entries.len() > max
+  {
+    let excess = entries.len() - max;
+    entries.drain(..excess);
+  }
+}
+
+/// Add `new` entries to the history file at `path`. The file is re-read
+/// first so several instances don't overwrite each other's history.
+pub fn append_history(
+  path: &Path,
+  new: &[String],
+) -> io::Result<()>
+{
+  if new.is_empty()
+  {
+    return Ok(());
+  }
+  let mut entries = load_history(path);
+  for e in new.iter().filter(|e| !e.contains('\n'))
+  {
+    push_entry(&mut entries, e, MAX_ENTRIES);
+  }
+  if let Some(parent) = path.parent()
+  {
+    let _ = fs::create_dir_all(parent);
+  }
+  let mut tmp = path.to_path_buf();
+  tmp.set_extension("tmp");
+  let mut f = fs::File::create(&tmp)?;
+  for e in &entries
+  {
+    writeln!(f, "{}", e)?;
+  }
+  f.flush()?;
+  fs::rename(tmp, path)?;
+  Ok(())
+}
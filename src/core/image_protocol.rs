@@ -0,0 +1,125 @@
+//! Choosing the terminal graphics protocol for image previews.
+//!
+//! Images are drawn by previewer commands (`chafa`, `viu`, `timg`, …), which
+//! are told the protocol through `ctx.image_protocol`. It comes from
+//! `ui.image_protocol` unless that is `"auto"`; then a per-terminal entry in
+//! `ui.image_protocols` wins, and otherwise the terminal is recognised from
+//! its environment variables. Anything unrecognised gets half blocks, which
+//! every terminal can show.
+
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImageProtocol
+{
+  Kitty,
+  Iterm2,
+  Sixel,
+  #[default]
+  Halfblocks,
+}
+
+impl ImageProtocol
+{
+  pub fn as_str(self) -> &'static str
+  {
+    match self
+    {
+      ImageProtocol::Kitty => "kitty",
+      ImageProtocol::Iterm2 => "iterm2",
+      ImageProtocol::Sixel => "sixel",
+      ImageProtocol::Halfblocks => "halfblocks",
+    }
+  }
+
+  /// Parse a protocol name; `"auto"` is not a protocol and gives `None`.
+  pub fn parse(name: &str) -> Option<ImageProtocol>
+  {
+    match name.trim().to_ascii_lowercase().as_str()
+    {
+      "kitty" => Some(ImageProtocol::Kitty),
+      "iterm2" | "iterm" => Some(ImageProtocol::Iterm2),
+      "sixel" | "sixels" => Some(ImageProtocol::Sixel),
+      "halfblocks" | "blocks" | "symbols" => Some(ImageProtocol::Halfblocks),
+      _ => None,
+    }
+  }
+}
+
+/// Whether `name` is accepted by `ui.image_protocol`.
+pub fn is_valid_setting(name: &str) -> bool
+{
+  name.trim().eq_ignore_ascii_case("auto")
+    || ImageProtocol::parse(name).is_some()
+}
+
+/// Name of the terminal lsv runs in, as used for `ui.image_protocols`:
+/// `$TERM_PROGRAM` when set, otherwise `$TERM`. Lowercased.
+pub fn terminal_name(env: &dyn Fn(&str) -> Option<String>) -> Option<String>
+{
+  ["TERM_PROGRAM", "TERM"]
+    .iter()
+    .filter_map(|k| env(k))
+    .map(|v| v.trim().to_ascii_lowercase())
+    .find(|v| !v.is_empty())
+}
+
+/// Protocol the terminal most likely supports, judging by its environment.
+pub fn detect(env: &dyn Fn(&str) -> Option<String>) -> ImageProtocol
+{
+  let var =
+    |k: &str| env(k).map(|v| v.to_ascii_lowercase()).unwrap_or_default();
+  let (program, term) = (var("TERM_PROGRAM"), var("TERM"));
+  if env("KITTY_WINDOW_ID").is_some()
+    || term.contains("kitty")
+    || term.contains("ghostty")
+    || program == "ghostty"
+  {
+    return ImageProtocol::Kitty;
+  }
+  if program == "iterm.app"
+    || program == "wezterm"
+    || env("WEZTERM_EXECUTABLE").is_some()
+  {
+    return ImageProtocol::Iterm2;
+  }
+  if env("KONSOLE_VERSION").is_some()
+  {
+    return ImageProtocol::Kitty;
+  }
+  if ["foot", "mlterm", "contour", "yaft"].iter().any(|t| term.starts_with(t))
+    || program == "mintty"
+  {
+    return ImageProtocol::Sixel;
+  }
+  ImageProtocol::Halfblocks
+}
+
+/// How the protocol was chosen, for logging.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source
+{
+  Config,
+  Override(String),
+  Detected,
+}
+
+/// Protocol for the `setting` (`ui.image_protocol`), the per-terminal
+/// `overrides` (keys lowercased) and the environment.
+pub fn resolve(
+  setting: Option<&str>,
+  overrides: &HashMap<String, String>,
+  env: &dyn Fn(&str) -> Option<String>,
+) -> (ImageProtocol, Source)
+{
+  if let Some(p) = setting.and_then(ImageProtocol::parse)
+  {
+    return (p, Source::Config);
+  }
+  if let Some(term) = terminal_name(env)
+    && let Some(p) = overrides.get(&term).and_then(|s| ImageProtocol::parse(s))
+  {
+    return (p, Source::Override(term));
+  }
+  (detect(env), Source::Detected)
+}
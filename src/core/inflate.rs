@@ -0,0 +1,446 @@
+//! Minimal streaming gzip/DEFLATE decoder (RFC 1951/1952).
+//!
+//! Only decoding is supported, and only as much as the archive preview needs:
+//! output is handed to a sink in chunks so large `.tar.gz` files can be
+//! scanned with bounded memory, and the sink can stop decoding early.
+
+use std::io::{
+  self,
+  Read,
+};
+
+const WINDOW: usize = 32 * 1024;
+const FLUSH_AT: usize = 64 * 1024;
+
+const LEN_BASE: [u16; 29] = [
+  3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67,
+  83, 99, 115, 131, 163, 195, 227, 258,
+];
+const LEN_EXTRA: [u8; 29] = [
+  0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5,
+  5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+  1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513,
+  769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+  0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11,
+  11, 12, 12, 13, 13,
+];
+const CODE_LENGTH_ORDER: [usize; 19] =
+  [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+fn invalid(msg: &str) -> io::Error
+{
+  io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+struct BitReader<R>
+{
+  inner: R,
+  buf:   Box<[u8]>,
+  pos:   usize,
+  len:   usize,
+  bits:  u64,
+  nbits: u32,
+}
+
+impl<R: Read> BitReader<R>
+{
+  fn new(inner: R) -> Self
+  {
+    BitReader {
+      inner,
+      buf: vec![0u8; 16 * 1024].into_boxed_slice(),
+      pos: 0,
+      len: 0,
+      bits: 0,
+      nbits: 0,
+    }
+  }
+
+  fn next_byte(&mut self) -> io::Result<u8>
+  {
+    if self.pos == self.len
+    {
+      self.len = loop
+      {
+        match self.inner.read(&mut self.buf)
+        {
+          Ok(n) => break n,
+          Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+          Err(e) => return Err(e),
+        }
+      };
+      self.pos = 0;
+      if self.len == 0
+      {
+        return Err(io::Error::new(
+          io::ErrorKind::UnexpectedEof,
+          "truncated deflate stream",
+        ));
+      }
+    }
+    let b = self.buf[self.pos];
+    self.pos += 1;
+    Ok(b)
+  }
+
+  /// Read `n` (at most 32) bits, least significant first.
+  fn bits(
+    &mut self,
+    n: u32,
+  ) -> io::Result<u32>
+  {
+    while self.nbits < n
+    {
+      let b = self.next_byte()?;
+      self.bits |= (b as u64) << self.nbits;
+      self.nbits += 8;
+    }
+    let v = (self.bits & ((1u64 << n) - 1)) as u32;
+    self.bits >>= n;
+    self.nbits -= n;
+    Ok(v)
+  }
+
+  /// Drop bits up to the next byte boundary.
+  fn align(&mut self)
+  {
+    let drop = self.nbits % 8;
+    self.bits >>= drop;
+    self.nbits -= drop;
+  }
+
+  fn byte(&mut self) -> io::Result<u8>
+  {
+    Ok(self.bits(8)? as u8)
+  }
+}
+
+/// Canonical Huffman decoding table: code counts per length plus symbols
+/// ordered by code.
+struct Huffman
+{
+  count:  [u16; 16],
+  symbol: Vec<u16>,
+}
+
+impl Huffman
+{
+  fn new(lengths: &[u8]) -> io::Result<Self>
+  {
+    let mut count = [0u16; 16];
+    for &l in lengths
+    {
+      count[l as usize] += 1;
+    }
+    count[0] = 0;
+    let mut offs = [0u16; 16];
+    for len in 1..16
+    {
+      offs[len] = offs[len - 1] + count[len - 1];
+    }
+    let mut symbol = vec![0u16; lengths.len()];
+    for (sym, &l) in lengths.iter().enumerate()
+    {
+      if l != 0
+      {
+        symbol[offs[l as usize] as usize] = sym as u16;
+        offs[l as usize] += 1;
+      }
+    }
+    // Over-subscribed code sets cannot be decoded
+    let mut left = 1i32;
+    for &c in &count[1..]
+    {
+      left = (left << 1) - c as i32;
+      if left < 0
+      {
+        return Err(invalid("over-subscribed huffman code"));
+      }
+    }
+    Ok(Huffman { count, symbol })
+  }
+
+  fn decode<R: Read>(
+    &self,
+    br: &mut BitReader<R>,
+  ) -> io::Result<u16>
+  {
+    let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+    for len in 1..16
+    {
+      code |= br.bits(1)? as i32;
+      let count = self.count[len] as i32;
+      if code - first < count
+      {
+        return Ok(self.symbol[(index + code - first) as usize]);
+      }
+      index += count;
+      first = (first + count) << 1;
+      code <<= 1;
+    }
+    Err(invalid("bad huffman code"))
+  }
+}
+
+/// Decoded output with the trailing 32 KiB kept for back references.
+struct Output<F>
+{
+  data:    Vec<u8>,
+  flushed: usize,
+  sink:    F,
+  stopped: bool,
+}
+
+impl<F: FnMut(&[u8]) -> bool> Output<F>
+{
+  fn flush(&mut self)
+  {
+    if self.flushed < self.data.len() && !self.stopped
+    {
+      self.stopped = !(self.sink)(&self.data[self.flushed..]);
+    }
+    if self.data.len() > WINDOW
+    {
+      self.data.drain(..self.data.len() - WINDOW);
+    }
+    self.flushed = self.data.len();
+  }
+
+  fn maybe_flush(&mut self)
+  {
+    if self.data.len() - self.flushed >= FLUSH_AT
+    {
+      self.flush();
+    }
+  }
+
+  fn copy(
+    &mut self,
+    dist: usize,
+    len: usize,
+  ) -> io::Result<()>
+  {
+    if dist == 0 || dist > self.data.len()
+    {
+      return Err(invalid("distance too far back"));
+    }
+    let start = self.data.len() - dist;
+    for i in 0..len
+    {
+      let b = self.data[start + i];
+      self.data.push(b);
+    }
+    Ok(())
+  }
+}
+
+fn inflate_block<R: Read, F: FnMut(&[u8]) -> bool>(
+  br: &mut BitReader<R>,
+  out: &mut Output<F>,
+  lit: &Huffman,
+  dist: &Huffman,
+) -> io::Result<()>
+{
+  loop
+  {
+    let sym = lit.decode(br)? as usize;
+    if sym < 256
+    {
+      out.data.push(sym as u8);
+    }
+    else if sym == 256
+    {
+      return Ok(());
+    }
+    else
+    {
+      let i = sym - 257;
+      if i >= LEN_BASE.len()
+      {
+        return Err(invalid("bad length symbol"));
+      }
+      let len = LEN_BASE[i] as usize + br.bits(LEN_EXTRA[i] as u32)? as usize;
+      let d = dist.decode(br)? as usize;
+      if d >= DIST_BASE.len()
+      {
+        return Err(invalid("bad distance symbol"));
+      }
+      let d = DIST_BASE[d] as usize + br.bits(DIST_EXTRA[d] as u32)? as usize;
+      out.copy(d, len)?;
+    }
+    out.maybe_flush();
+    if out.stopped
+    {
+      return Ok(());
+    }
+  }
+}
+
+fn dynamic_tables<R: Read>(
+  br: &mut BitReader<R>
+) -> io::Result<(Huffman, Huffman)>
+{
+  let nlen = br.bits(5)? as usize + 257;
+  let ndist = br.bits(5)? as usize + 1;
+  let ncode = br.bits(4)? as usize + 4;
+  let mut cl = [0u8; 19];
+  for &idx in CODE_LENGTH_ORDER.iter().take(ncode)
+  {
+    cl[idx] = br.bits(3)? as u8;
+  }
+  let cl = Huffman::new(&cl)?;
+  let mut lengths = Vec::with_capacity(nlen + ndist);
+  while lengths.len() < nlen + ndist
+  {
+    let sym = cl.decode(br)?;
+    let (value, repeat) = match sym
+    {
+      0..=15 => (sym as u8, 1),
+      16 =>
+      {
+        let prev =
+          *lengths.last().ok_or_else(|| invalid("repeat with no length"))?;
+        (prev, 3 + br.bits(2)? as usize)
+      }
+      17 => (0, 3 + br.bits(3)? as usize),
+      _ => (0, 11 + br.bits(7)? as usize),
+    };
+    if lengths.len() + repeat > nlen + ndist
+    {
+      return Err(invalid("too many code lengths"));
+    }
+    lengths.extend(std::iter::repeat_n(value, repeat));
+  }
+  Ok((Huffman::new(&lengths[..nlen])?, Huffman::new(&lengths[nlen..])?))
+}
+
+fn fixed_tables() -> io::Result<(Huffman, Huffman)>
+{
+  let mut lit = [0u8; 288];
+  lit[..144].fill(8);
+  lit[144..256].fill(9);
+  lit[256..280].fill(7);
+  lit[280..].fill(8);
+  Ok((Huffman::new(&lit)?, Huffman::new(&[5u8; 30])?))
+}
+
+fn inflate<R: Read, F: FnMut(&[u8]) -> bool>(
+  br: &mut BitReader<R>,
+  out: &mut Output<F>,
+) -> io::Result<()>
+{
+  loop
+  {
+    let last = br.bits(1)? == 1;
+    match br.bits(2)?
+    {
+      0 =>
+      {
+        br.align();
+        let len = br.bits(16)?;
+        let nlen = br.bits(16)?;
+        if len != !nlen & 0xffff
+        {
+          return Err(invalid("stored block length mismatch"));
+        }
+        for _ in 0..len
+        {
+          let b = br.byte()?;
+          out.data.push(b);
+          out.maybe_flush();
+        }
+      }
+      1 =>
+      {
+        let (lit, dist) = fixed_tables()?;
+        inflate_block(br, out, &lit, &dist)?;
+      }
+      2 =>
+      {
+        let (lit, dist) = dynamic_tables(br)?;
+        inflate_block(br, out, &lit, &dist)?;
+      }
+      _ => return Err(invalid("reserved block type")),
+    }
+    if last || out.stopped
+    {
+      return Ok(());
+    }
+  }
+}
+
+/// Decode the first member of a gzip stream, passing decompressed data to
+/// `sink` in chunks. Decoding stops early when `sink` returns false.
+pub fn gunzip<R: Read, F: FnMut(&[u8]) -> bool>(
+  reader: R,
+  sink: F,
+) -> io::Result<()>
+{
+  let mut br = BitReader::new(reader);
+  if br.byte()? != 0x1f || br.byte()? != 0x8b
+  {
+    return Err(invalid("not a gzip stream"));
+  }
+  if br.byte()? != 8
+  {
+    return Err(invalid("unsupported gzip compression method"));
+  }
+  let flags = br.byte()?;
+  // mtime, extra flags, OS
+  for _ in 0..6
+  {
+    br.byte()?;
+  }
+  if flags & 0x04 != 0
+  {
+    let xlen = br.bits(16)?;
+    for _ in 0..xlen
+    {
+      br.byte()?;
+    }
+  }
+  // File name and comment are zero terminated
+  for flag in [0x08, 0x10]
+  {
+    if flags & flag != 0
+    {
+      while br.byte()? != 0
+      {}
+    }
+  }
+  if flags & 0x02 != 0
+  {
+    br.bits(16)?;
+  }
+  decode(&mut br, sink)
+}
+
+/// Decode a raw DEFLATE stream (as stored in zip members), passing output to
+/// `sink` in chunks. Decoding stops early when `sink` returns false.
+pub fn inflate_raw<R: Read, F: FnMut(&[u8]) -> bool>(
+  reader: R,
+  sink: F,
+) -> io::Result<()>
+{
+  decode(&mut BitReader::new(reader), sink)
+}
+
+fn decode<R: Read, F: FnMut(&[u8]) -> bool>(
+  br: &mut BitReader<R>,
+  sink: F,
+) -> io::Result<()>
+{
+  let mut out = Output {
+    data: Vec::with_capacity(2 * FLUSH_AT),
+    flushed: 0,
+    sink,
+    stopped: false,
+  };
+  inflate(br, &mut out)?;
+  out.flush();
+  Ok(())
+}
@@ -1,12 +1,44 @@
 use std::{
+  cmp::Ordering,
+  fs,
   io,
   path::Path,
 };
 
 use crate::actions::internal::SortKey;
 
+/// Whether `name` passes a `:filter` pattern: a case-insensitive glob when
+/// the pattern has `*` or `?`, otherwise a case-insensitive substring.
+pub fn filter_matches(
+  pattern: &str,
+  name: &str,
+) -> bool
+{
+  if pattern.contains(['*', '?'])
+  {
+    crate::core::opener::glob_match(pattern, name)
+  }
+  else
+  {
+    name.to_lowercase().contains(&pattern.to_lowercase())
+  }
+}
+
+/// Windows' hidden file attribute.
+pub const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+
+/// Whether an entry is hidden: a dotfile, or one with the hidden attribute
+/// on Windows (`attributes` is 0 elsewhere).
+pub fn is_hidden(
+  name: &str,
+  attributes: u32,
+) -> bool
+{
+  name.starts_with('.') || attributes & FILE_ATTRIBUTE_HIDDEN != 0
+}
+
 /// Read a directory and return entries sorted per key and direction.
-/// Hidden files (dotfiles) are filtered when `show_hidden` is false.
+/// Hidden files (see [`is_hidden`]) are filtered when `show_hidden` is false.
 pub fn read_dir_sorted(
   path: &Path,
   show_hidden: bool,
@@ -16,71 +48,134 @@ pub fn read_dir_sorted(
   max_items: usize,
 ) -> io::Result<Vec<crate::app::DirEntryInfo>>
 {
-  use std::fs;
-  let mut entries: Vec<crate::app::DirEntryInfo> = fs::read_dir(path)?
-    .filter_map(|res| res.ok())
-    .filter_map(|e| {
-      let path = e.path();
-      let name = e.file_name().to_string_lossy().to_string();
-      if !show_hidden && name.starts_with('.')
+  let mut entries: Vec<crate::app::DirEntryInfo> =
+    match crate::core::vfs::list(path, show_hidden)
+    {
+      Some(listing) => listing?.into_iter().take(max_items).collect(),
+      None => fs::read_dir(path)?
+        .filter_map(|res| res.ok())
+        .filter_map(|e| entry_info(&e, show_hidden, need_meta))
+        .take(max_items)
+        .collect(),
+    };
+  sort_entries(&mut entries, sort_key, sort_reverse);
+  Ok(entries)
+}
+
+/// Build a [`DirEntryInfo`](crate::app::DirEntryInfo) from a raw directory
+/// entry. Returns `None` for hidden entries when `show_hidden` is false or
+/// when the file type cannot be determined. Metadata (size, times, mode,
+/// owner and link count) is only fetched when `need_meta` is set.
+pub fn entry_info(
+  e: &fs::DirEntry,
+  show_hidden: bool,
+  need_meta: bool,
+) -> Option<crate::app::DirEntryInfo>
+{
+  let name = e.file_name().to_string_lossy().to_string();
+  // Listing already read the attributes on Windows, so this is cheap
+  #[cfg(windows)]
+  let attributes = {
+    use std::os::windows::fs::MetadataExt;
+    e.metadata().map(|m| m.file_attributes()).unwrap_or(0)
+  };
+  #[cfg(not(windows))]
+  let attributes = 0;
+  if !show_hidden && is_hidden(&name, attributes)
+  {
+    return None;
+  }
+  let ft = e.file_type().ok()?;
+  let mut info = crate::app::DirEntryInfo {
+    name,
+    path: e.path(),
+    is_dir: ft.is_dir(),
+    size: 0,
+    mtime: None,
+    ctime: None,
+    meta: None,
+  };
+  // Fast path: avoid metadata when not needed
+  if need_meta
+  {
+    fill_meta(&mut info);
+  }
+  Some(info)
+}
+
+/// Read the size, times, mode, owner and link count of an entry listed
+/// without them. Entries that cannot be read are left as they are.
+pub fn fill_meta(e: &mut crate::app::DirEntryInfo)
+{
+  if let Ok(meta) = fs::metadata(&e.path)
+  {
+    e.size = meta.len();
+    e.mtime = meta.modified().ok();
+    e.ctime = meta.created().ok();
+    e.meta = Some(crate::app::EntryMeta::from_metadata(&meta));
+  }
+}
+
+/// Compare names case-insensitively with runs of digits compared by value,
+/// so "file2" sorts before "file10". Equal values with different zero
+/// padding put the shorter run first.
+pub fn natural_cmp(
+  a: &str,
+  b: &str,
+) -> Ordering
+{
+  let (mut ai, mut bi) = (a.chars().peekable(), b.chars().peekable());
+  loop
+  {
+    match (ai.peek().copied(), bi.peek().copied())
+    {
+      (None, None) => return a.cmp(b),
+      (None, Some(_)) => return Ordering::Less,
+      (Some(_), None) => return Ordering::Greater,
+      (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() =>
       {
-        return None;
+        let take = |it: &mut std::iter::Peekable<std::str::Chars>| {
+          let mut run = String::new();
+          while let Some(c) = it.peek().copied().filter(char::is_ascii_digit)
+          {
+            run.push(c);
+            it.next();
+          }
+          run
+        };
+        let (ra, rb) = (take(&mut ai), take(&mut bi));
+        let (ta, tb) = (ra.trim_start_matches('0'), rb.trim_start_matches('0'));
+        let ord = ta
+          .len()
+          .cmp(&tb.len())
+          .then_with(|| ta.cmp(tb))
+          .then_with(|| ra.len().cmp(&rb.len()));
+        if ord != Ordering::Equal
+        {
+          return ord;
+        }
       }
-      match e.file_type()
+      (Some(x), Some(y)) =>
       {
-        Ok(ft) =>
+        let ord = x.to_lowercase().cmp(y.to_lowercase());
+        if ord != Ordering::Equal
         {
-          if need_meta && !matches!(sort_key, SortKey::Name)
-          {
-            // Sorting by size/mtime/ctime requires metadata for accuracy
-            let meta = fs::metadata(&path).ok();
-            let size = meta.as_ref().map(|m| m.len()).unwrap_or(0);
-            let mtime = meta.as_ref().and_then(|m| m.modified().ok());
-            let ctime = meta.as_ref().and_then(|m| m.created().ok());
-            Some(crate::app::DirEntryInfo {
-              name,
-              path,
-              is_dir: ft.is_dir(),
-              size,
-              mtime,
-              ctime,
-            })
-          }
-          else if need_meta
-          {
-            // Name sort but meta requested for UI info; fetch once
-            let meta = fs::metadata(&path).ok();
-            let size = meta.as_ref().map(|m| m.len()).unwrap_or(0);
-            let mtime = meta.as_ref().and_then(|m| m.modified().ok());
-            let ctime = meta.as_ref().and_then(|m| m.created().ok());
-            Some(crate::app::DirEntryInfo {
-              name,
-              path,
-              is_dir: ft.is_dir(),
-              size,
-              mtime,
-              ctime,
-            })
-          }
-          else
-          {
-            // Fast path: avoid metadata when not needed
-            Some(crate::app::DirEntryInfo {
-              name,
-              path,
-              is_dir: ft.is_dir(),
-              size: 0,
-              mtime: None,
-              ctime: None,
-            })
-          }
+          return ord;
         }
-        Err(_) => None,
+        ai.next();
+        bi.next();
       }
-    })
-    .take(max_items)
-    .collect();
+    }
+  }
+}
 
+/// Sort entries in place: directories first, then per key and direction.
+pub fn sort_entries(
+  entries: &mut [crate::app::DirEntryInfo],
+  sort_key: SortKey,
+  sort_reverse: bool,
+)
+{
   entries.sort_by(|a, b| {
     // Always keep directories before files
     match (a.is_dir, b.is_dir)
@@ -93,6 +188,25 @@ pub fn read_dir_sorted(
     let ord = match sort_key
     {
       SortKey::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+      SortKey::Natural => natural_cmp(&a.name, &b.name),
+      SortKey::Extension =>
+      {
+        // Directories have no meaningful extension; keep them by name
+        let ext = |e: &crate::app::DirEntryInfo| {
+          if e.is_dir
+          {
+            String::new()
+          }
+          else
+          {
+            Path::new(&e.name)
+              .extension()
+              .map(|x| x.to_string_lossy().to_lowercase())
+              .unwrap_or_default()
+          }
+        };
+        ext(a).cmp(&ext(b)).then_with(|| natural_cmp(&a.name, &b.name))
+      }
       SortKey::Size =>
       {
         // When sorting by size, keep directories ordered by name instead of
@@ -112,7 +226,7 @@ pub fn read_dir_sorted(
         let bt = b.mtime.unwrap_or(std::time::SystemTime::UNIX_EPOCH);
         at.cmp(&bt)
       }
-      SortKey::CTime =>
+      SortKey::Created =>
       {
         let at = a.ctime.unwrap_or(std::time::SystemTime::UNIX_EPOCH);
         let bt = b.ctime.unwrap_or(std::time::SystemTime::UNIX_EPOCH);
@@ -121,8 +235,11 @@ pub fn read_dir_sorted(
     };
     if sort_reverse
     {
-      // For size sort, keep directories ordered by name even when reversed.
-      if matches!(sort_key, SortKey::Size) && a.is_dir && b.is_dir
+      // For size and extension sorts, keep directories ordered by name even
+      // when reversed.
+      if matches!(sort_key, SortKey::Size | SortKey::Extension)
+        && a.is_dir
+        && b.is_dir
       {
         ord
       }
@@ -136,5 +253,4 @@ pub fn read_dir_sorted(
       ord
     }
   });
-  Ok(entries)
 }
@@ -0,0 +1,89 @@
+//! A small least-recently-used cache.
+//!
+//! Entries live in a list ordered from most to least recently used, so
+//! lookups are linear; it is meant for a few dozen entries, such as the
+//! previewer outputs kept by [`crate::app::PreviewState`]. Entries can also
+//! expire a fixed time after they were stored.
+
+use std::{
+  collections::VecDeque,
+  time::{
+    Duration,
+    Instant,
+  },
+};
+
+/// Entries kept by [`Lru::default`].
+pub const DEFAULT_CAPACITY: usize = 32;
+
+#[derive(Debug, Clone)]
+pub struct Lru<K, V>
+{
+  entries:  VecDeque<(K, V, Instant)>,
+  capacity: usize,
+  /// How long an entry stays after it was stored; `None` keeps it until it
+  /// is evicted.
+  ttl:      Option<Duration>,
+}
+
+impl<K, V> Default for Lru<K, V>
+{
+  fn default() -> Self
+  {
+    Self {
+      entries:  VecDeque::new(),
+      capacity: DEFAULT_CAPACITY,
+      ttl:      None,
+    }
+  }
+}
+
+impl<K: PartialEq, V> Lru<K, V>
+{
+  /// Keep at most `capacity` entries (0 keeps none), each for at most `ttl`.
+  pub fn set_limits(
+    &mut self,
+    capacity: usize,
+    ttl: Option<Duration>,
+  )
+  {
+    self.capacity = capacity;
+    self.ttl = ttl;
+    self.entries.truncate(capacity);
+  }
+
+  /// The value for `key`, marking it as the most recently used. Expired
+  /// entries are dropped instead.
+  pub fn get(
+    &mut self,
+    key: &K,
+  ) -> Option<&V>
+  {
+    let idx = self.entries.iter().position(|(k, ..)| k == key)?;
+    let entry = self.entries.remove(idx)?;
+    if self.ttl.is_some_and(|ttl| entry.2.elapsed() >= ttl)
+    {
+      return None;
+    }
+    self.entries.push_front(entry);
+    self.entries.front().map(|(_, v, _)| v)
+  }
+
+  /// Store `value` under `key`, evicting the least recently used entry when
+  /// the cache is full.
+  pub fn insert(
+    &mut self,
+    key: K,
+    value: V,
+  )
+  {
+    self.entries.retain(|(k, ..)| *k != key);
+    self.entries.push_front((key, value, Instant::now()));
+    self.entries.truncate(self.capacity);
+  }
+
+  pub fn clear(&mut self)
+  {
+    self.entries.clear();
+  }
+}
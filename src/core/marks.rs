@@ -11,8 +11,9 @@ use std::{
   },
 };
 
-// Simple line-oriented format: "<key>\t<abs_path>\n"
-pub fn load_marks(path: &Path) -> HashMap<char, PathBuf>
+// Simple line-oriented format: "<name>\t<abs_path>\n"; names set with `m`
+// are a single character
+pub fn load_marks(path: &Path) -> HashMap<String, PathBuf>
 {
   let mut out = HashMap::new();
   let text = match fs::read_to_string(path)
@@ -28,10 +29,10 @@ pub fn load_marks(path: &Path) -> HashMap<char, PathBuf>
       continue;
     }
     if let Some((k, p)) = l.split_once('\t')
-      && let Some(ch) = k.chars().next()
+      && !k.is_empty()
     {
       let pb = PathBuf::from(p);
-      out.insert(ch, pb);
+      out.insert(k.to_string(), pb);
     }
   }
   out
@@ -39,7 +40,7 @@ pub fn load_marks(path: &Path) -> HashMap<char, PathBuf>
 
 pub fn save_marks(
   path: &Path,
-  marks: &HashMap<char, PathBuf>,
+  marks: &HashMap<String, PathBuf>,
 ) -> io::Result<()>
 {
   if let Some(parent) = path.parent()
@@ -50,11 +51,11 @@ pub fn save_marks(
   tmp.set_extension("tmp");
   let mut f = fs::File::create(&tmp)?;
   // stable order
-  let mut keys: Vec<char> = marks.keys().copied().collect();
+  let mut keys: Vec<&String> = marks.keys().collect();
   keys.sort();
   for k in keys
   {
-    if let Some(p) = marks.get(&k)
+    if let Some(p) = marks.get(k)
     {
       let _ = writeln!(f, "{}\t{}", k, p.display());
     }
@@ -63,3 +64,10 @@ pub fn save_marks(
   fs::rename(tmp, path)?;
   Ok(())
 }
+
+/// Whether `name` can be used for a mark: not empty and without whitespace,
+/// which would not survive the file format or the `:` command line.
+pub fn valid_name(name: &str) -> bool
+{
+  !name.is_empty() && !name.chars().any(char::is_whitespace)
+}
@@ -0,0 +1,521 @@
+//! Metadata of images, audio and video for the file info overlay.
+//!
+//! EXIF is read directly from JPEG and TIFF files, and PNG/GIF headers give
+//! the picture size. Audio and video are described by `ffprobe`, when it is
+//! installed: duration, container, bit rate, and codec and resolution per
+//! stream.
+
+use std::{
+  fs::File,
+  io::Read,
+  path::Path,
+  process::{
+    Command,
+    Stdio,
+  },
+};
+
+/// Only the start of an image is read; EXIF lives in its first segments.
+const MAX_HEADER_BYTES: u64 = 512 * 1024;
+
+const IMAGE_EXTENSIONS: &[&str] = &[
+  "jpg", "jpeg", "png", "gif", "webp", "tif", "tiff", "bmp", "heic", "heif",
+  "avif",
+];
+const AUDIO_EXTENSIONS: &[&str] =
+  &["mp3", "flac", "wav", "ogg", "oga", "opus", "m4a", "aac", "wma"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaKind
+{
+  Image,
+  Audio,
+  Video,
+}
+
+/// What sort of media `path` is, judging by its extension.
+pub fn media_kind(path: &Path) -> Option<MediaKind>
+{
+  let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+  if IMAGE_EXTENSIONS.contains(&ext.as_str())
+  {
+    Some(MediaKind::Image)
+  }
+  else if AUDIO_EXTENSIONS.contains(&ext.as_str())
+  {
+    Some(MediaKind::Audio)
+  }
+  else if crate::core::thumbnail::VIDEO_EXTENSIONS.contains(&ext.as_str())
+  {
+    Some(MediaKind::Video)
+  }
+  else
+  {
+    None
+  }
+}
+
+/// Labelled metadata for `path`; empty when there is nothing to tell.
+pub fn describe(path: &Path) -> Vec<(String, String)>
+{
+  match media_kind(path)
+  {
+    Some(MediaKind::Image) =>
+    {
+      let mut head = Vec::new();
+      if let Ok(f) = File::open(path)
+      {
+        let _ = f.take(MAX_HEADER_BYTES).read_to_end(&mut head);
+      }
+      image_info(&head)
+    }
+    Some(MediaKind::Audio | MediaKind::Video) => match ffprobe(path)
+    {
+      Some(out) => parse_ffprobe(&out),
+      None => vec![(
+        String::from("Media"),
+        String::from("install ffprobe (ffmpeg) for duration and codecs"),
+      )],
+    },
+    None => Vec::new(),
+  }
+}
+
+/// Metadata from the first bytes of an image file.
+pub fn image_info(head: &[u8]) -> Vec<(String, String)>
+{
+  if head.starts_with(&[0xFF, 0xD8])
+  {
+    return jpeg_info(head);
+  }
+  if head.starts_with(b"II*\0") || head.starts_with(b"MM\0*")
+  {
+    return Tiff::new(head).map(|t| t.fields()).unwrap_or_default();
+  }
+  let size = if head.starts_with(b"\x89PNG\r\n\x1a\n") && head.len() >= 24
+  {
+    Some((be32(&head[16..20]), be32(&head[20..24])))
+  }
+  else if (head.starts_with(b"GIF87a") || head.starts_with(b"GIF89a"))
+    && head.len() >= 10
+  {
+    Some((
+      u16::from_le_bytes([head[6], head[7]]) as u32,
+      u16::from_le_bytes([head[8], head[9]]) as u32,
+    ))
+  }
+  else
+  {
+    None
+  };
+  size.map(|(w, h)| vec![dimensions(w, h)]).unwrap_or_default()
+}
+
+fn be32(b: &[u8]) -> u32
+{
+  u32::from_be_bytes([b[0], b[1], b[2], b[3]])
+}
+
+fn dimensions(
+  w: u32,
+  h: u32,
+) -> (String, String)
+{
+  (String::from("Dimensions"), format!("{}x{}", w, h))
+}
+
+/// Walk the JPEG segments up to the image data: EXIF comes from APP1, the
+/// picture size from the start-of-frame header.
+fn jpeg_info(data: &[u8]) -> Vec<(String, String)>
+{
+  let mut fields = Vec::new();
+  let mut frame = None;
+  let mut pos = 2;
+  while pos + 4 <= data.len() && data[pos] == 0xFF
+  {
+    let marker = data[pos + 1];
+    if marker == 0xD8 || marker == 0x01 || (0xD0..=0xD7).contains(&marker)
+    {
+      pos += 2;
+      continue;
+    }
+    if marker == 0xDA || marker == 0xD9
+    {
+      break;
+    }
+    let len = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+    let body =
+      &data[(pos + 4).min(data.len())..(pos + 2 + len).min(data.len())];
+    if marker == 0xE1 && body.starts_with(b"Exif\0\0")
+    {
+      if let Some(tiff) = Tiff::new(&body[6..])
+      {
+        fields = tiff.fields();
+      }
+    }
+    else if matches!(marker, 0xC0..=0xC3) && body.len() >= 5
+    {
+      let h = u16::from_be_bytes([body[1], body[2]]) as u32;
+      let w = u16::from_be_bytes([body[3], body[4]]) as u32;
+      frame = Some((w, h));
+    }
+    pos += 2 + len;
+  }
+  if let Some((w, h)) = frame
+    && !fields.iter().any(|(k, _)| k == "Dimensions")
+  {
+    fields.insert(0, dimensions(w, h));
+  }
+  fields
+}
+
+/// One IFD entry: the value's type, count and where its bytes start.
+#[derive(Debug, Clone, Copy)]
+struct Entry
+{
+  tag:   u16,
+  kind:  u16,
+  count: u32,
+  at:    usize,
+}
+
+/// A TIFF structure, as embedded in EXIF.
+struct Tiff<'a>
+{
+  data:   &'a [u8],
+  little: bool,
+}
+
+impl<'a> Tiff<'a>
+{
+  fn new(data: &'a [u8]) -> Option<Tiff<'a>>
+  {
+    let little = match data.get(..2)?
+    {
+      b"II" => true,
+      b"MM" => false,
+      _ => return None,
+    };
+    let t = Tiff { data, little };
+    (t.u16(2)? == 42).then_some(t)
+  }
+
+  fn u16(
+    &self,
+    at: usize,
+  ) -> Option<u16>
+  {
+    let b: [u8; 2] = self.data.get(at..at + 2)?.try_into().ok()?;
+    Some(
+      if self.little { u16::from_le_bytes(b) } else { u16::from_be_bytes(b) },
+    )
+  }
+
+  fn u32(
+    &self,
+    at: usize,
+  ) -> Option<u32>
+  {
+    let b: [u8; 4] = self.data.get(at..at + 4)?.try_into().ok()?;
+    Some(
+      if self.little { u32::from_le_bytes(b) } else { u32::from_be_bytes(b) },
+    )
+  }
+
+  fn ifd(
+    &self,
+    offset: usize,
+  ) -> Vec<Entry>
+  {
+    let Some(n) = self.u16(offset)
+    else
+    {
+      return Vec::new();
+    };
+    (0..n as usize)
+      .filter_map(|i| {
+        let at = offset + 2 + i * 12;
+        let (tag, kind, count) =
+          (self.u16(at)?, self.u16(at + 2)?, self.u32(at + 4)?);
+        let size = match kind
+        {
+          3 => 2,
+          4 | 9 => 4,
+          5 | 10 => 8,
+          _ => 1,
+        } * count as usize;
+        let at = if size <= 4 { at + 8 } else { self.u32(at + 8)? as usize };
+        Some(Entry { tag, kind, count, at })
+      })
+      .collect()
+  }
+
+  fn text(
+    &self,
+    e: &Entry,
+  ) -> Option<String>
+  {
+    let raw = self.data.get(e.at..e.at + e.count as usize)?;
+    let s = String::from_utf8_lossy(raw);
+    let s = s.trim_end_matches('\0').trim();
+    (!s.is_empty()).then(|| crate::util::sanitize_line(s))
+  }
+
+  fn uint(
+    &self,
+    e: &Entry,
+  ) -> Option<u32>
+  {
+    match e.kind
+    {
+      3 => self.u16(e.at).map(u32::from),
+      4 => self.u32(e.at),
+      _ => None,
+    }
+  }
+
+  fn rational(
+    &self,
+    e: &Entry,
+    i: usize,
+  ) -> Option<f64>
+  {
+    if e.kind != 5 || i >= e.count as usize
+    {
+      return None;
+    }
+    let (n, d) = (self.u32(e.at + i * 8)?, self.u32(e.at + i * 8 + 4)?);
+    (d != 0).then(|| n as f64 / d as f64)
+  }
+
+  /// The interesting tags of IFD0 and its EXIF and GPS sub-IFDs, labelled.
+  fn fields(&self) -> Vec<(String, String)>
+  {
+    let Some(first) = self.u32(4)
+    else
+    {
+      return Vec::new();
+    };
+    let mut entries = self.ifd(first as usize);
+    for pointer in [0x8769, 0x8825]
+    {
+      let sub =
+        entries.iter().find(|e| e.tag == pointer).and_then(|e| self.uint(e));
+      if let Some(sub) = sub
+      {
+        // GPS tags reuse small numbers; keep them apart from IFD0's
+        let mut more = self.ifd(sub as usize);
+        if pointer == 0x8825
+        {
+          more.iter_mut().for_each(|e| e.tag |= 0xF000);
+        }
+        entries.extend(more);
+      }
+    }
+    let get = |tag: u16| entries.iter().find(|e| e.tag == tag);
+    let text = |tag: u16| get(tag).and_then(|e| self.text(e));
+    let uint = |tag: u16| get(tag).and_then(|e| self.uint(e));
+    let rational = |tag: u16| get(tag).and_then(|e| self.rational(e, 0));
+
+    let mut out = Vec::new();
+    let mut push = |label: &str, value: Option<String>| {
+      if let Some(v) = value
+      {
+        out.push((label.to_string(), v));
+      }
+    };
+    if let (Some(w), Some(h)) = (uint(0xA002), uint(0xA003))
+    {
+      let (k, v) = dimensions(w, h);
+      push(&k, Some(v));
+    }
+    let camera = match (text(0x010F), text(0x0110))
+    {
+      (Some(make), Some(model)) if model.starts_with(&make) => Some(model),
+      (Some(make), Some(model)) => Some(format!("{} {}", make, model)),
+      (make, model) => make.or(model),
+    };
+    push("Camera", camera);
+    push("Lens", text(0xA434));
+    push("Taken", text(0x9003).or_else(|| text(0x0132)));
+    push("Exposure", rational(0x829A).map(exposure));
+    push("Aperture", rational(0x829D).map(|f| format!("f/{:.1}", f)));
+    push("ISO", uint(0x8827).map(|n| n.to_string()));
+    push("Focal length", rational(0x920A).map(|f| format!("{:.0} mm", f)));
+    push("Orientation", uint(0x0112).and_then(orientation));
+    let coord = |value: u16, reference: u16| {
+      let e = get(0xF000 | value)?;
+      let deg = self.rational(e, 0)?
+        + self.rational(e, 1).unwrap_or(0.0) / 60.0
+        + self.rational(e, 2).unwrap_or(0.0) / 3600.0;
+      let negative =
+        matches!(text(0xF000 | reference).as_deref(), Some("S" | "W"));
+      Some(if negative { -deg } else { deg })
+    };
+    if let (Some(lat), Some(lon)) = (coord(2, 1), coord(4, 3))
+    {
+      push("GPS", Some(format!("{:.5}, {:.5}", lat, lon)));
+    }
+    push("Software", text(0x0131));
+    out
+  }
+}
+
+fn exposure(secs: f64) -> String
+{
+  if secs > 0.0 && secs < 1.0
+  {
+    format!("1/{:.0} s", 1.0 / secs)
+  }
+  else
+  {
+    format!("{} s", secs)
+  }
+}
+
+fn orientation(v: u32) -> Option<String>
+{
+  let s = match v
+  {
+    1 => "normal",
+    3 => "rotated 180°",
+    6 => "rotated 90° clockwise",
+    8 => "rotated 90° counter-clockwise",
+    2 | 4 | 5 | 7 => "mirrored",
+    _ => return None,
+  };
+  Some(s.to_string())
+}
+
+/// Run `ffprobe` on `path`; `None` when it is missing or failed.
+fn ffprobe(path: &Path) -> Option<String>
+{
+  let out = Command::new("ffprobe")
+    .args([
+      "-v",
+      "error",
+      "-show_entries",
+      "format=format_name,duration,bit_rate:stream=codec_type,codec_name,\
+       width,height,r_frame_rate,sample_rate,channels",
+    ])
+    .arg(path)
+    .stdin(Stdio::null())
+    .stderr(Stdio::null())
+    .output()
+    .ok()
+    .filter(|o| o.status.success())?;
+  Some(String::from_utf8_lossy(&out.stdout).into_owned())
+}
+
+/// Summarise ffprobe's default output (`[STREAM]` and `[FORMAT]` sections
+/// of `key=value` lines).
+pub fn parse_ffprobe(text: &str) -> Vec<(String, String)>
+{
+  let mut sections: Vec<(String, Vec<(String, String)>)> = Vec::new();
+  for line in text.lines().map(str::trim)
+  {
+    if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']'))
+    {
+      if !name.starts_with('/')
+      {
+        sections.push((name.to_string(), Vec::new()));
+      }
+    }
+    else if let (Some((k, v)), Some(section)) =
+      (line.split_once('='), sections.last_mut())
+      && v != "N/A"
+      && !v.is_empty()
+    {
+      section.1.push((k.to_string(), v.to_string()));
+    }
+  }
+  let field = |fields: &[(String, String)], key: &str| {
+    fields.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone())
+  };
+
+  let mut out = Vec::new();
+  if let Some((_, format)) = sections.iter().find(|(n, _)| n == "FORMAT")
+  {
+    if let Some(d) = field(format, "duration").and_then(|d| d.parse().ok())
+    {
+      out.push((String::from("Duration"), duration(d)));
+    }
+    if let Some(name) = field(format, "format_name")
+    {
+      out.push((String::from("Container"), name));
+    }
+    if let Some(rate) =
+      field(format, "bit_rate").and_then(|b| b.parse::<u64>().ok())
+    {
+      out.push((String::from("Bit rate"), format!("{} kb/s", rate / 1000)));
+    }
+  }
+  for (_, stream) in sections.iter().filter(|(n, _)| n == "STREAM")
+  {
+    let mut parts: Vec<String> =
+      field(stream, "codec_name").into_iter().collect();
+    let label = match field(stream, "codec_type").as_deref()
+    {
+      Some("video") =>
+      {
+        if let (Some(w), Some(h)) =
+          (field(stream, "width"), field(stream, "height"))
+        {
+          parts.push(format!("{}x{}", w, h));
+        }
+        if let Some(fps) =
+          field(stream, "r_frame_rate").and_then(|r| frame_rate(&r))
+        {
+          parts.push(format!("{} fps", fps));
+        }
+        "Video"
+      }
+      Some("audio") =>
+      {
+        if let Some(rate) = field(stream, "sample_rate")
+        {
+          parts.push(format!("{} Hz", rate));
+        }
+        if let Some(ch) = field(stream, "channels")
+        {
+          parts.push(format!("{} ch", ch));
+        }
+        "Audio"
+      }
+      Some("subtitle") => "Subtitles",
+      _ => continue,
+    };
+    out.push((label.to_string(), parts.join(", ")));
+  }
+  out
+}
+
+/// `h:mm:ss`, or `m:ss` under an hour.
+fn duration(secs: f64) -> String
+{
+  let total = secs.round() as u64;
+  let (h, m, s) = (total / 3600, total / 60 % 60, total % 60);
+  if h > 0
+  {
+    format!("{}:{:02}:{:02}", h, m, s)
+  }
+  else
+  {
+    format!("{}:{:02}", m, s)
+  }
+}
+
+/// ffprobe rates are fractions such as `30000/1001`.
+fn frame_rate(r: &str) -> Option<String>
+{
+  let (n, d) = r.split_once('/')?;
+  let (n, d): (f64, f64) = (n.parse().ok()?, d.parse().ok()?);
+  if d == 0.0 || n == 0.0
+  {
+    return None;
+  }
+  let fps = n / d;
+  Some(
+    if fps.fract() == 0.0 { format!("{}", fps) } else { format!("{:.2}", fps) },
+  )
+}
@@ -0,0 +1,190 @@
+//! MIME type detection from file contents.
+//!
+//! The first bytes are checked against well-known signatures (and a `#!`
+//! line for scripts), then the extension is consulted; anything else is
+//! `text/plain` when it reads as UTF-8 and `application/octet-stream`
+//! otherwise. Used by the file info overlay, `openers` and `previewers`
+//! rules, the Lua previewer's `ctx.mime` and the `{mime}` placeholder.
+
+use std::{
+  fs::File,
+  io::Read,
+  path::Path,
+};
+
+/// Bytes read from the start of a file to detect its type.
+const HEAD_LEN: u64 = 4096;
+
+/// The first bytes of the file at `path`; empty when it can't be read.
+pub fn read_head(path: &Path) -> Vec<u8>
+{
+  let mut head = Vec::new();
+  if let Ok(f) = File::open(path)
+  {
+    let _ = f.take(HEAD_LEN).read_to_end(&mut head);
+  }
+  head
+}
+
+/// Whether a file starting with `head` is binary: it has a NUL byte or
+/// isn't UTF-8. A character cut off at the end of the sample is still text.
+pub fn looks_binary(head: &[u8]) -> bool
+{
+  head.contains(&0)
+    || std::str::from_utf8(head)
+      .map_or_else(|e| e.error_len().is_some(), |_| false)
+}
+
+/// Script type named by a `#!` line, going through `env` to the program.
+fn from_shebang(head: &[u8]) -> Option<&'static str>
+{
+  let rest = head.strip_prefix(b"#!")?;
+  let line = rest.split(|&b| b == b'\n').next().unwrap_or_default();
+  let line = std::str::from_utf8(line).ok()?;
+  let mut words = line.split_whitespace();
+  let mut program = words.next()?.rsplit('/').next().unwrap_or_default();
+  if program == "env"
+  {
+    program = words.find(|w| !w.starts_with('-'))?;
+  }
+  let program =
+    program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+  Some(match program
+  {
+    "sh" | "bash" | "zsh" | "dash" | "ksh" | "fish" => "text/x-shellscript",
+    "python" => "text/x-python",
+    "lua" | "luajit" => "text/x-lua",
+    "perl" => "text/x-perl",
+    "ruby" => "text/x-ruby",
+    "node" | "deno" => "text/javascript",
+    _ => return None,
+  })
+}
+
+/// Types recognised by their leading bytes.
+pub fn from_bytes(head: &[u8]) -> Option<&'static str>
+{
+  const SIGNATURES: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xFF\xD8\xFF", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"II*\0", "image/tiff"),
+    (b"MM\0*", "image/tiff"),
+    (b"BM", "image/bmp"),
+    (b"%PDF-", "application/pdf"),
+    (b"PK\x03\x04", "application/zip"),
+    (b"\x1F\x8B", "application/gzip"),
+    (b"BZh", "application/x-bzip2"),
+    (b"\xFD7zXZ\0", "application/x-xz"),
+    (b"\x28\xB5\x2F\xFD", "application/zstd"),
+    (b"7z\xBC\xAF\x27\x1C", "application/x-7z-compressed"),
+    (b"SQLite format 3\0", "application/vnd.sqlite3"),
+    (b"\0asm", "application/wasm"),
+    (b"\x7FELF", "application/x-executable"),
+    (b"ID3", "audio/mpeg"),
+    (b"fLaC", "audio/flac"),
+    (b"OggS", "audio/ogg"),
+    (b"\x1A\x45\xDF\xA3", "video/x-matroska"),
+  ];
+  if let Some((_, mime)) =
+    SIGNATURES.iter().find(|(sig, _)| head.starts_with(sig))
+  {
+    return Some(mime);
+  }
+  if head.len() >= 12 && &head[..4] == b"RIFF"
+  {
+    return match &head[8..12]
+    {
+      b"WEBP" => Some("image/webp"),
+      b"WAVE" => Some("audio/wav"),
+      b"AVI " => Some("video/x-msvideo"),
+      _ => None,
+    };
+  }
+  if head.len() >= 12 && &head[4..8] == b"ftyp"
+  {
+    return Some(match &head[8..12]
+    {
+      b"heic" | b"heix" | b"mif1" => "image/heic",
+      b"avif" => "image/avif",
+      b"qt  " => "video/quicktime",
+      b"M4A " => "audio/mp4",
+      _ => "video/mp4",
+    });
+  }
+  if head.len() >= 262 && &head[257..262] == b"ustar"
+  {
+    return Some("application/x-tar");
+  }
+  from_shebang(head)
+}
+
+/// Types of text formats and others without a reliable signature.
+pub fn from_extension(ext: &str) -> Option<&'static str>
+{
+  let mime = match ext.to_ascii_lowercase().as_str()
+  {
+    "txt" | "log" => "text/plain",
+    "md" | "markdown" => "text/markdown",
+    "html" | "htm" => "text/html",
+    "css" => "text/css",
+    "csv" => "text/csv",
+    "js" | "mjs" => "text/javascript",
+    "json" => "application/json",
+    "xml" => "application/xml",
+    "svg" => "image/svg+xml",
+    "toml" => "application/toml",
+    "yaml" | "yml" => "application/yaml",
+    "sh" | "bash" | "zsh" => "text/x-shellscript",
+    "rs" => "text/x-rust",
+    "py" => "text/x-python",
+    "lua" => "text/x-lua",
+    "c" | "h" => "text/x-c",
+    "mp3" => "audio/mpeg",
+    "webm" => "video/webm",
+    "mp4" | "m4v" => "video/mp4",
+    "mkv" => "video/x-matroska",
+    "tar" => "application/x-tar",
+    "tgz" | "gz" => "application/gzip",
+    _ => return None,
+  };
+  Some(mime)
+}
+
+/// Best guess at the MIME type of the file at `path`.
+pub fn guess(path: &Path) -> String
+{
+  let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+  from_head(&read_head(path), ext)
+}
+
+/// MIME type of a file starting with `head` and named with extension `ext`.
+pub fn from_head(
+  head: &[u8],
+  ext: &str,
+) -> String
+{
+  // Matroska signatures are shared by WebM; trust the extension there
+  let mime = match from_bytes(head)
+  {
+    Some("video/x-matroska") if ext.eq_ignore_ascii_case("webm") =>
+    {
+      Some("video/webm")
+    }
+    found => found.or_else(|| from_extension(ext)),
+  };
+  match mime
+  {
+    Some(m) => m.to_string(),
+    None if head.is_empty() => String::from("inode/x-empty"),
+    None if looks_binary(head) => String::from("application/octet-stream"),
+    None => String::from("text/plain"),
+  }
+}
+
+/// MIME type of `path`, `inode/directory` for directories.
+pub fn detect(path: &Path) -> String
+{
+  if path.is_dir() { String::from("inode/directory") } else { guess(path) }
+}
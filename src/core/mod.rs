@@ -1,5 +1,33 @@
+pub mod archive;
+pub mod checksum;
+pub mod complete;
+pub mod frecency;
 pub mod fs_ops;
+pub mod fuzzy;
+pub mod history;
+pub mod image_protocol;
+pub mod inflate;
 pub mod listing;
+pub mod lru;
 pub mod marks;
+pub mod media_info;
+pub mod mime;
+pub mod mounts;
+pub mod opener;
 pub mod overlays;
+pub mod pack;
+pub mod permissions;
+#[cfg(windows)]
+pub mod recycle_bin;
+pub mod regex;
+pub mod remote;
+pub mod rename;
 pub mod selection;
+pub mod session;
+pub mod shell;
+pub mod thumbnail;
+pub mod trash;
+pub mod vfs;
+#[cfg(windows)]
+pub mod winsec;
+pub mod yank;
@@ -0,0 +1,354 @@
+//! Mounted filesystems, for the `:mounts` picker.
+//!
+//! Linux mounts come from `/proc/mounts`, with kernel pseudo filesystems
+//! left out. Other Unix systems list what `df` reports, and Windows lists
+//! the drive letters that exist plus network shares connected without one.
+//! Usage figures come from `df -kP`, which is given a few seconds before a
+//! hung network mount is left without them.
+
+use std::{
+  io::Read,
+  path::{
+    Path,
+    PathBuf,
+  },
+  process::{
+    Command,
+    Stdio,
+  },
+  time::{
+    Duration,
+    Instant,
+  },
+};
+
+/// How long `df` (or `net use`) may take before its answer is left out.
+const DF_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Filesystem types that hold no files worth browsing.
+const PSEUDO_FS: &[&str] = &[
+  "autofs",
+  "binfmt_misc",
+  "bpf",
+  "cgroup",
+  "cgroup2",
+  "configfs",
+  "debugfs",
+  "devpts",
+  "fusectl",
+  "hugetlbfs",
+  "mqueue",
+  "nsfs",
+  "proc",
+  "pstore",
+  "rpc_pipefs",
+  "securityfs",
+  "selinuxfs",
+  "sysfs",
+  "tracefs",
+];
+
+/// A mounted filesystem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MountPoint
+{
+  pub device:  String,
+  pub path:    PathBuf,
+  /// Filesystem type, when the platform reports it.
+  pub fs_type: Option<String>,
+  /// Size and free space in bytes, when `df` answered.
+  pub total:   Option<u64>,
+  pub avail:   Option<u64>,
+}
+
+impl MountPoint
+{
+  /// Share of the filesystem in use, from 0.0 to 1.0.
+  pub fn used_fraction(&self) -> Option<f64>
+  {
+    let (total, avail) = (self.total?, self.avail?);
+    if total == 0
+    {
+      return None;
+    }
+    Some(total.saturating_sub(avail) as f64 / total as f64)
+  }
+}
+
+/// One row of `df -kP` output, sizes in bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DfRow
+{
+  pub device: String,
+  pub total:  u64,
+  pub avail:  u64,
+  pub path:   PathBuf,
+}
+
+/// Undo the octal escapes `/proc/mounts` uses for spaces and the like.
+fn unescape_mount_field(field: &str) -> String
+{
+  let bytes = field.as_bytes();
+  let mut out = Vec::with_capacity(bytes.len());
+  let mut i = 0;
+  while i < bytes.len()
+  {
+    if bytes[i] == b'\\'
+      && let Some(oct) = bytes.get(i + 1..i + 4)
+      && oct.iter().all(|b| (b'0'..=b'7').contains(b))
+    {
+      let v = oct.iter().fold(0u32, |acc, b| acc * 8 + (b - b'0') as u32);
+      out.push(v as u8);
+      i += 4;
+      continue;
+    }
+    out.push(bytes[i]);
+    i += 1;
+  }
+  String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Parse `/proc/mounts`, leaving out pseudo filesystems. A path mounted more
+/// than once is listed once, as it was mounted last.
+pub fn parse_proc_mounts(text: &str) -> Vec<MountPoint>
+{
+  let mut out: Vec<MountPoint> = Vec::new();
+  for line in text.lines()
+  {
+    let mut fields = line.split_whitespace();
+    let (Some(device), Some(path), Some(fs_type)) =
+      (fields.next(), fields.next(), fields.next())
+    else
+    {
+      continue;
+    };
+    if PSEUDO_FS.contains(&fs_type)
+    {
+      continue;
+    }
+    let path = PathBuf::from(unescape_mount_field(path));
+    out.retain(|m| m.path != path);
+    out.push(MountPoint {
+      device: unescape_mount_field(device),
+      path,
+      fs_type: Some(fs_type.to_string()),
+      total: None,
+      avail: None,
+    });
+  }
+  out
+}
+
+/// Parse `df -kP` output. Mount points may contain spaces.
+pub fn parse_df(text: &str) -> Vec<DfRow>
+{
+  let mut out = Vec::new();
+  for line in text.lines().skip(1)
+  {
+    // Filesystem, 1024-blocks, Used, Available, Capacity, Mounted on
+    let mut fields = Vec::with_capacity(5);
+    let mut rest = line;
+    for _ in 0..5
+    {
+      let start = rest.trim_start();
+      let end = start.find(char::is_whitespace).unwrap_or(start.len());
+      fields.push(&start[..end]);
+      rest = &start[end..];
+    }
+    let path = rest.trim_start();
+    let (Ok(total), Ok(avail)) =
+      (fields[1].parse::<u64>(), fields[3].parse::<u64>())
+    else
+    {
+      continue;
+    };
+    if path.is_empty()
+    {
+      continue;
+    }
+    out.push(DfRow {
+      device: fields[0].to_string(),
+      total:  total * 1024,
+      avail:  avail * 1024,
+      path:   PathBuf::from(path),
+    });
+  }
+  out
+}
+
+/// Parse `net use` output into the UNC roots of connected shares that have
+/// no drive letter; lettered ones already show up as drives.
+pub fn parse_net_use(text: &str) -> Vec<PathBuf>
+{
+  let mut out: Vec<PathBuf> = Vec::new();
+  for line in text.lines()
+  {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let Some(i) = fields.iter().position(|f| f.starts_with("\\\\"))
+    else
+    {
+      continue;
+    };
+    let lettered = i > 0
+      && fields[i - 1].len() == 2
+      && fields[i - 1].ends_with(':')
+      && fields[i - 1].starts_with(|c: char| c.is_ascii_alphabetic());
+    if lettered
+    {
+      continue;
+    }
+    let root = PathBuf::from(format!("{}\\", fields[i].trim_end_matches('\\')));
+    if !out.contains(&root)
+    {
+      out.push(root);
+    }
+  }
+  out
+}
+
+/// Run `cmd`, giving up after [`DF_TIMEOUT`]. Returns its output even when
+/// it exits non-zero.
+fn run_with_timeout(cmd: &mut Command) -> Option<String>
+{
+  let Ok(mut child) = cmd
+    .stdin(Stdio::null())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::null())
+    .spawn()
+  else
+  {
+    return None;
+  };
+  // Read in the background so a full pipe cannot stall the command
+  let stdout = child.stdout.take();
+  let reader = std::thread::spawn(move || {
+    let mut text = String::new();
+    if let Some(mut out) = stdout
+    {
+      let _ = out.read_to_string(&mut text);
+    }
+    text
+  });
+  let deadline = Instant::now() + DF_TIMEOUT;
+  loop
+  {
+    match child.try_wait()
+    {
+      Ok(Some(_)) => break,
+      Ok(None) if Instant::now() < deadline =>
+      {
+        std::thread::sleep(Duration::from_millis(20));
+      }
+      _ =>
+      {
+        let _ = child.kill();
+        let _ = child.wait();
+        return None;
+      }
+    }
+  }
+  reader.join().ok()
+}
+
+/// Run `df -kP` on `paths` (every filesystem when empty), giving up after
+/// [`DF_TIMEOUT`].
+fn run_df(paths: &[PathBuf]) -> Vec<DfRow>
+{
+  // df exits non-zero when one path fails; the others still count
+  run_with_timeout(Command::new("df").arg("-kP").args(paths))
+    .map(|text| parse_df(&text))
+    .unwrap_or_default()
+}
+
+/// Size and free space of the filesystem holding `path`, from `df`.
+pub fn usage(path: &Path) -> Option<DfRow>
+{
+  run_df(&[path.to_path_buf()]).into_iter().next()
+}
+
+/// The mounted filesystems of this system, sorted by path.
+pub fn list() -> Vec<MountPoint>
+{
+  let mut mounts = if cfg!(windows)
+  {
+    let mut mounts = drive_letters();
+    mounts.extend(network_shares());
+    mounts
+  }
+  else if let Ok(text) = std::fs::read_to_string("/proc/mounts")
+  {
+    let mut mounts = parse_proc_mounts(&text);
+    let paths: Vec<PathBuf> = mounts.iter().map(|m| m.path.clone()).collect();
+    let rows = run_df(&paths);
+    for m in &mut mounts
+    {
+      if let Some(row) = rows.iter().find(|r| r.path == m.path)
+      {
+        m.total = Some(row.total);
+        m.avail = Some(row.avail);
+      }
+    }
+    mounts
+  }
+  else
+  {
+    run_df(&[])
+      .into_iter()
+      .map(|r| MountPoint {
+        device:  r.device,
+        path:    r.path,
+        fs_type: None,
+        total:   Some(r.total),
+        avail:   Some(r.avail),
+      })
+      .collect()
+  };
+  mounts.sort_by(|a, b| a.path.cmp(&b.path));
+  mounts
+}
+
+fn drive_letters() -> Vec<MountPoint>
+{
+  (b'A'..=b'Z')
+    .map(|c| format!("{}:\\", c as char))
+    .filter(|root| Path::new(root).exists())
+    .map(|root| MountPoint {
+      device:  root.trim_end_matches('\\').to_string(),
+      path:    PathBuf::from(root),
+      fs_type: None,
+      total:   None,
+      avail:   None,
+    })
+    .collect()
+}
+
+/// Network shares connected without a drive letter.
+fn network_shares() -> Vec<MountPoint>
+{
+  run_with_timeout(Command::new("net").arg("use"))
+    .map(|text| parse_net_use(&text))
+    .unwrap_or_default()
+    .into_iter()
+    .map(|root| MountPoint {
+      device:  root.to_string_lossy().trim_end_matches('\\').to_string(),
+      path:    root,
+      fs_type: None,
+      total:   None,
+      avail:   None,
+    })
+    .collect()
+}
+
+/// Index of the mount holding `path`: the one with the longest prefix.
+pub fn containing(
+  mounts: &[MountPoint],
+  path: &Path,
+) -> Option<usize>
+{
+  mounts
+    .iter()
+    .enumerate()
+    .filter(|(_, m)| path.starts_with(&m.path))
+    .max_by_key(|(_, m)| m.path.components().count())
+    .map(|(i, _)| i)
+}
@@ -0,0 +1,161 @@
+//! Matching files against `openers` and `previewers` rules.
+//!
+//! A rule applies when all of its conditions hold: the extension is one of
+//! `ext`, the MIME type matches `mime` and the file name matches the `name`
+//! glob. MIME types are only looked up (see [`detect_mime`]) when a rule
+//! asks for one.
+
+use std::{
+  path::Path,
+  process::Command,
+};
+
+use crate::config::{
+  OpenerRule,
+  PreviewerRule,
+};
+
+/// Match `name` against a glob where `*` is any run of characters and `?`
+/// any single one. Case-insensitive.
+pub fn glob_match(
+  pattern: &str,
+  name: &str,
+) -> bool
+{
+  let p: Vec<char> = pattern.to_lowercase().chars().collect();
+  let n: Vec<char> = name.to_lowercase().chars().collect();
+  let (mut pi, mut ni) = (0usize, 0usize);
+  // Position of the last `*` and the name index it was tried at
+  let mut star: Option<(usize, usize)> = None;
+  while ni < n.len()
+  {
+    if pi < p.len() && (p[pi] == '?' || p[pi] == n[ni])
+    {
+      pi += 1;
+      ni += 1;
+    }
+    else if pi < p.len() && p[pi] == '*'
+    {
+      star = Some((pi, ni));
+      pi += 1;
+    }
+    else if let Some((sp, sn)) = star
+    {
+      pi = sp + 1;
+      ni = sn + 1;
+      star = Some((sp, sn + 1));
+    }
+    else
+    {
+      return false;
+    }
+  }
+  p[pi..].iter().all(|c| *c == '*')
+}
+
+/// Whether `mime` matches `pattern`, which may end in `/*`.
+pub fn mime_matches(
+  pattern: &str,
+  mime: &str,
+) -> bool
+{
+  match pattern.strip_suffix("/*")
+  {
+    Some(top) => mime.split('/').next().is_some_and(|t| t == top),
+    None => pattern == mime,
+  }
+}
+
+/// MIME type of `path` from its contents. Types only known to be text or
+/// binary are refined by `file`, when it is installed.
+pub fn detect_mime(path: &Path) -> Option<String>
+{
+  let sniffed = crate::core::mime::detect(path);
+  if sniffed != "text/plain" && sniffed != "application/octet-stream"
+  {
+    return Some(sniffed);
+  }
+  file_mime(path).or(Some(sniffed))
+}
+
+/// MIME type of `path` as reported by `file`, if it is installed.
+fn file_mime(path: &Path) -> Option<String>
+{
+  let out = Command::new("file")
+    .args(["--brief", "--mime-type", "--"])
+    .arg(path)
+    .output()
+    .ok()?;
+  let mime = String::from_utf8_lossy(&out.stdout).trim().to_string();
+  (out.status.success() && mime.contains('/')).then_some(mime)
+}
+
+/// Whether a file at `path` meets the conditions of a rule. `mime` is
+/// called at most once, and only when the rule has a `mime` condition.
+fn conditions_match(
+  (ext, name, mime_pat): (&[String], Option<&str>, Option<&str>),
+  path: &Path,
+  mime: &mut impl FnMut() -> Option<String>,
+) -> bool
+{
+  let file_name =
+    path.file_name().map(|s| s.to_string_lossy()).unwrap_or_default();
+  if !ext.is_empty()
+  {
+    let file_ext = path
+      .extension()
+      .map(|s| s.to_string_lossy().to_lowercase())
+      .unwrap_or_default();
+    if !ext.contains(&file_ext)
+    {
+      return false;
+    }
+  }
+  if let Some(pat) = name
+    && !glob_match(pat, &file_name)
+  {
+    return false;
+  }
+  if let Some(pat) = mime_pat
+  {
+    return mime().is_some_and(|m| mime_matches(pat, &m));
+  }
+  true
+}
+
+/// Whether `rule` applies to `path`. `mime` is called at most once, and
+/// only when the rule has a `mime` condition.
+pub fn rule_matches(
+  rule: &OpenerRule,
+  path: &Path,
+  mime: &mut impl FnMut() -> Option<String>,
+) -> bool
+{
+  let conds = (&rule.ext[..], rule.name.as_deref(), rule.mime.as_deref());
+  conditions_match(conds, path, mime)
+}
+
+/// First rule in `rules` that applies to `path`.
+pub fn find_opener<'a>(
+  rules: &'a [OpenerRule],
+  path: &Path,
+) -> Option<&'a OpenerRule>
+{
+  let mut cached: Option<Option<String>> = None;
+  let mut mime = || cached.get_or_insert_with(|| detect_mime(path)).clone();
+  rules.iter().find(|r| rule_matches(r, path, &mut mime))
+}
+
+/// First `previewers` rule that applies to `path`.
+pub fn find_previewer<'a>(
+  rules: &'a [PreviewerRule],
+  path: &Path,
+) -> Option<&'a PreviewerRule>
+{
+  let mut cached: Option<Option<String>> = None;
+  let mut mime = || cached.get_or_insert_with(|| detect_mime(path)).clone();
+  rules.iter().find(|r| {
+    let conds = (&r.ext[..], r.name.as_deref(), r.mime.as_deref());
+    conditions_match(conds, path, &mut mime)
+  })
+}
@@ -1,7 +1,6 @@
 use crate::app::{
   App,
   ConfirmKind,
-  ConfirmState,
   Overlay,
   PromptKind,
   PromptState,
@@ -15,47 +14,43 @@ pub fn apply_theme_entry(
 )
 {
   app.config.ui.theme = Some(entry.theme);
-  app.config.ui.theme_path = Some(entry.path);
+  app.config.ui.theme_path = entry.path;
   app.force_full_redraw = true;
 }
 
+/// Move the highlight in the theme picker. The overlay previews the
+/// highlighted theme on a sample listing; nothing is applied until confirmed.
 pub fn theme_picker_move(
   app: &mut App,
   delta: isize,
 )
 {
-  let entry = {
-    let state = match app.overlay
-    {
-      Overlay::ThemePicker(ref mut s) => s.as_mut(),
-      _ => return,
-    };
-    if state.entries.is_empty()
-    {
-      return;
-    }
-    let len = state.entries.len() as isize;
-    let mut new_idx = state.selected as isize + delta;
-    new_idx = new_idx.clamp(0, len.saturating_sub(1));
-    if new_idx as usize == state.selected
-    {
-      None
-    }
-    else
-    {
-      state.selected = new_idx as usize;
-      Some(state.entries[state.selected].clone())
-    }
+  let state = match app.overlay
+  {
+    Overlay::ThemePicker(ref mut s) => s.as_mut(),
+    _ => return,
   };
-  if let Some(entry) = entry
+  if state.entries.is_empty()
   {
-    apply_theme_entry(app, entry);
+    return;
+  }
+  let len = state.entries.len() as isize;
+  let new_idx = (state.selected as isize + delta).clamp(0, len - 1) as usize;
+  if new_idx != state.selected
+  {
+    state.selected = new_idx;
+    app.force_full_redraw = true;
   }
 }
 
 pub fn confirm_theme_picker(app: &mut App)
 {
-  app.overlay = Overlay::None;
+  if let Overlay::ThemePicker(state) =
+    std::mem::replace(&mut app.overlay, Overlay::None)
+    && let Some(entry) = state.entries.into_iter().nth(state.selected)
+  {
+    apply_theme_entry(app, entry);
+  }
   app.force_full_redraw = true;
 }
 
@@ -94,7 +89,10 @@ pub fn open_rename_entry_prompt(app: &mut App)
     }
     else
     {
-      format!("Rename {} items (use {{}} for variable part):", names.len())
+      format!(
+        "Rename {} items (use {{}} for variable part, Ctrl-e for $EDITOR):",
+        names.len()
+      )
     };
     app.overlay = Overlay::Prompt(Box::new(PromptState {
       title,
@@ -123,6 +121,37 @@ pub fn open_rename_entry_prompt(app: &mut App)
   app.force_full_redraw = true;
 }
 
+/// Question and path list for deleting or trashing `items`, as in "Move
+/// 'a.txt' to trash?".
+fn removal_question(
+  verb: &str,
+  suffix: &str,
+  items: &[PathBuf],
+) -> (String, Vec<String>)
+{
+  let what = if items.len() == 1
+  {
+    let name = items[0]
+      .file_name()
+      .map(|s| s.to_string_lossy().to_string())
+      .unwrap_or_else(|| items[0].to_string_lossy().to_string());
+    format!("'{}'", name)
+  }
+  else
+  {
+    format!("{} selected items", items.len())
+  };
+  let question = format!(
+    "{} {}{}? (y/n)\n{}",
+    verb,
+    what,
+    suffix,
+    crate::app::confirm::batch_summary(items)
+  );
+  let details = items.iter().map(|p| p.display().to_string()).collect();
+  (question, details)
+}
+
 pub fn request_delete_selected(app: &mut App)
 {
   crate::trace::log("[delete] request_delete_selected()");
@@ -131,34 +160,46 @@ pub fn request_delete_selected(app: &mut App)
     app.add_message("Delete: no items selected");
     return;
   }
-  let items: Vec<PathBuf> = app.selected.iter().cloned().collect();
+  let mut items: Vec<PathBuf> = app.selected.iter().cloned().collect();
+  items.sort();
   if app.config.ui.confirm_delete
   {
-    let question = if items.len() == 1
-    {
-      let name = items[0]
-        .file_name()
-        .map(|s| s.to_string_lossy().to_string())
-        .unwrap_or_else(|| items[0].to_string_lossy().to_string());
-      format!("Delete '{}' ? (y/n)", name)
-    }
-    else
-    {
-      format!("Delete {} selected items? (y/n)", items.len())
-    };
-    app.overlay = Overlay::Confirm(Box::new(ConfirmState {
-      title: "Confirm Delete".to_string(),
+    let (question, details) = removal_question("Delete", "", &items);
+    app.open_batch_confirm(
+      "Confirm Delete",
       question,
-      default_yes: false,
-      kind: ConfirmKind::DeleteSelected(items),
-    }));
-    app.force_full_redraw = true;
+      details,
+      ConfirmKind::DeleteSelected(items),
+    );
   }
   else
   {
-    for p in app.selected.clone().into_iter()
-    {
-      app.perform_delete_path(&p);
-    }
+    app.perform_delete_paths(&items);
+  }
+}
+
+pub fn request_trash_selected(app: &mut App)
+{
+  crate::trace::log("[trash] request_trash_selected()");
+  if app.selected.is_empty()
+  {
+    app.add_message("Trash: no items selected");
+    return;
+  }
+  let mut items: Vec<PathBuf> = app.selected.iter().cloned().collect();
+  items.sort();
+  if app.config.ui.confirm_delete
+  {
+    let (question, details) = removal_question("Move", " to trash", &items);
+    app.open_batch_confirm(
+      "Confirm Trash",
+      question,
+      details,
+      ConfirmKind::TrashSelected(items),
+    );
+  }
+  else
+  {
+    app.perform_trash_paths(&items);
   }
 }
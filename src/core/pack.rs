@@ -0,0 +1,446 @@
+//! Creating archives (`:compress`).
+//!
+//! Tar streams are written directly; `.tar.gz` and `.tar.zst` pipe that
+//! stream through a compressor command (`gzip -c`, `zstd -q -c`) and `.zip`
+//! archives are made by the `zip` tool. All three commands can be replaced
+//! with `ui.compress` and run through the `command_shell`. Members are named
+//! relative to the directory the archive is made from; symlinks are stored as
+//! links, not followed.
+
+use std::{
+  fs::File,
+  io::{
+    self,
+    BufWriter,
+    Read,
+    Write,
+  },
+  path::{
+    Component,
+    Path,
+    PathBuf,
+  },
+  process::Stdio,
+  time::Duration,
+};
+
+use crate::{
+  config::{
+    CommandShell,
+    UiCompress,
+  },
+  core::{
+    fs_ops::CopyProgress,
+    shell,
+  },
+};
+
+pub const DEFAULT_GZIP_CMD: &str = "gzip -c";
+pub const DEFAULT_ZSTD_CMD: &str = "zstd -q -c";
+/// Gets the archive path, then the members, with the working directory set
+/// to where they are named from.
+pub const DEFAULT_ZIP_CMD: &str =
+  r#"a="$1"; shift; exec zip -q -r -y "$a" "$@""#;
+
+const BLOCK: usize = 512;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackFormat
+{
+  Zip,
+  Tar,
+  TarGz,
+  TarZst,
+}
+
+impl PackFormat
+{
+  /// The format an archive file name asks for, by its extension.
+  pub fn detect(name: &str) -> Option<PackFormat>
+  {
+    let name = name.to_ascii_lowercase();
+    if name.ends_with(".tar.gz") || name.ends_with(".tgz")
+    {
+      Some(PackFormat::TarGz)
+    }
+    else if name.ends_with(".tar.zst") || name.ends_with(".tzst")
+    {
+      Some(PackFormat::TarZst)
+    }
+    else if name.ends_with(".tar")
+    {
+      Some(PackFormat::Tar)
+    }
+    else if name.ends_with(".zip")
+    {
+      Some(PackFormat::Zip)
+    }
+    else
+    {
+      None
+    }
+  }
+
+  /// The command this format runs, if any.
+  pub fn command(
+    self,
+    cfg: &UiCompress,
+  ) -> Option<String>
+  {
+    match self
+    {
+      PackFormat::Tar => None,
+      PackFormat::TarGz => Some(cfg.gzip.clone()),
+      PackFormat::TarZst => Some(cfg.zstd.clone()),
+      PackFormat::Zip => Some(cfg.zip.clone()),
+    }
+  }
+}
+
+/// Everything a compress job needs besides its items.
+#[derive(Debug, Clone)]
+pub struct PackSpec
+{
+  pub archive: PathBuf,
+  pub format:  PackFormat,
+  /// Directory members are named relative to.
+  pub base:    PathBuf,
+  pub command: Option<String>,
+  /// How `command` is run.
+  pub shell:   CommandShell,
+}
+
+/// Pack `items` as `spec` says. The archive is left behind on errors; callers
+/// remove it.
+pub fn create(
+  spec: &PackSpec,
+  items: &[PathBuf],
+  on_progress: &mut dyn FnMut(CopyProgress) -> io::Result<()>,
+) -> io::Result<()>
+{
+  match (spec.format, spec.command.as_deref())
+  {
+    (PackFormat::Tar, _) =>
+    {
+      let mut out = BufWriter::new(File::create(&spec.archive)?);
+      write_tar(&mut out, &spec.base, items, &spec.archive, on_progress)?;
+      out.flush()
+    }
+    (PackFormat::Zip, Some(cmd)) => run_zip(cmd, spec, items, on_progress),
+    (_, Some(cmd)) =>
+    {
+      let out = File::create(&spec.archive)?;
+      let mut child = shell::command(&spec.shell, cmd)
+        .stdin(Stdio::piped())
+        .stdout(out)
+        .stderr(Stdio::piped())
+        .spawn()?;
+      let res = {
+        let stdin = child.stdin.take().expect("piped stdin");
+        let mut stdin = BufWriter::new(stdin);
+        write_tar(&mut stdin, &spec.base, items, &spec.archive, on_progress)
+          .and_then(|_| stdin.flush())
+      };
+      if res.as_ref().is_err_and(|e| e.kind() == io::ErrorKind::Interrupted)
+      {
+        let _ = child.kill();
+      }
+      let output = child.wait_with_output()?;
+      match res
+      {
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => Err(e),
+        _ if !output.status.success() =>
+        {
+          Err(command_error(cmd, &output.stderr))
+        }
+        other => other,
+      }
+    }
+    (format, None) => Err(io::Error::new(
+      io::ErrorKind::Unsupported,
+      format!("no command configured for {:?} archives", format),
+    )),
+  }
+}
+
+fn command_error(
+  cmd: &str,
+  stderr: &[u8],
+) -> io::Error
+{
+  let err = String::from_utf8_lossy(stderr);
+  let first = err.lines().next().unwrap_or("").trim();
+  let program = cmd.split_whitespace().next().unwrap_or(cmd);
+  io::Error::other(
+    if first.is_empty()
+    {
+      format!("{} failed", program)
+    }
+    else
+    {
+      first.to_string()
+    },
+  )
+}
+
+fn run_zip(
+  cmd: &str,
+  spec: &PackSpec,
+  items: &[PathBuf],
+  on_progress: &mut dyn FnMut(CopyProgress) -> io::Result<()>,
+) -> io::Result<()>
+{
+  // "./" keeps names starting with '-' from being read as options
+  let names = items.iter().map(|p| match p.strip_prefix(&spec.base)
+  {
+    Ok(rel) => Path::new(".").join(rel),
+    Err(_) => p.clone(),
+  });
+  let args = std::iter::once(spec.archive.clone()).chain(names);
+  let mut child = shell::with_args(&spec.shell, cmd, args)
+    .current_dir(&spec.base)
+    .stdin(Stdio::null())
+    .stdout(Stdio::null())
+    .stderr(Stdio::piped())
+    .spawn()?;
+  let status = loop
+  {
+    if let Some(status) = child.try_wait()?
+    {
+      break status;
+    }
+    // No byte counts to report, but this is where cancelling is noticed
+    if let Err(e) = on_progress(CopyProgress::Bytes(0))
+    {
+      let _ = child.kill();
+      let _ = child.wait();
+      return Err(e);
+    }
+    std::thread::sleep(Duration::from_millis(50));
+  };
+  if status.success()
+  {
+    return Ok(());
+  }
+  let mut stderr = Vec::new();
+  if let Some(mut err) = child.stderr.take()
+  {
+    let _ = err.read_to_end(&mut stderr);
+  }
+  Err(command_error(cmd, &stderr))
+}
+
+/// Write a tar stream of `items` (and everything below directories among
+/// them) to `out`, naming members relative to `base`. `skip` is never
+/// packed, so an archive made inside a packed directory leaves itself out.
+pub fn write_tar(
+  out: &mut dyn Write,
+  base: &Path,
+  items: &[PathBuf],
+  skip: &Path,
+  on_progress: &mut dyn FnMut(CopyProgress) -> io::Result<()>,
+) -> io::Result<()>
+{
+  for item in items
+  {
+    let rel = match item.strip_prefix(base)
+    {
+      Ok(rel) => rel.to_path_buf(),
+      Err(_) => PathBuf::from(item.file_name().unwrap_or_default()),
+    };
+    write_member(out, item, &rel, skip, on_progress)?;
+  }
+  out.write_all(&[0u8; BLOCK * 2])
+}
+
+fn write_member(
+  out: &mut dyn Write,
+  path: &Path,
+  rel: &Path,
+  skip: &Path,
+  on_progress: &mut dyn FnMut(CopyProgress) -> io::Result<()>,
+) -> io::Result<()>
+{
+  if path == skip
+  {
+    return Ok(());
+  }
+  let meta = std::fs::symlink_metadata(path)?;
+  let mut name = member_name(rel);
+  if meta.is_dir()
+  {
+    name.push('/');
+    write_header(out, &name, &meta, b'5', 0, "")?;
+    let mut children: Vec<PathBuf> =
+      std::fs::read_dir(path)?.flatten().map(|e| e.path()).collect();
+    children.sort();
+    for child in children
+    {
+      let child_rel = rel.join(child.file_name().unwrap_or_default());
+      write_member(out, &child, &child_rel, skip, on_progress)?;
+    }
+    Ok(())
+  }
+  else if meta.is_symlink()
+  {
+    let target = std::fs::read_link(path)?;
+    write_header(out, &name, &meta, b'2', 0, &target.to_string_lossy())
+  }
+  else if meta.is_file()
+  {
+    let size = meta.len();
+    write_header(out, &name, &meta, b'0', size, "")?;
+    let mut f = File::open(path)?.take(size);
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut written = 0u64;
+    loop
+    {
+      let n = f.read(&mut buf)?;
+      if n == 0
+      {
+        break;
+      }
+      out.write_all(&buf[..n])?;
+      written += n as u64;
+      on_progress(CopyProgress::Bytes(n as u64))?;
+    }
+    // A file that shrank meanwhile is padded to the size in its header
+    let pad = (size - written) as usize + padding(size);
+    out.write_all(&vec![0u8; pad])?;
+    on_progress(CopyProgress::FileDone)
+  }
+  else
+  {
+    // Sockets, fifos and devices are left out
+    Ok(())
+  }
+}
+
+/// `rel` with `/` separators, as tar wants.
+fn member_name(rel: &Path) -> String
+{
+  rel
+    .components()
+    .filter_map(|c| match c
+    {
+      Component::Normal(s) => Some(s.to_string_lossy().to_string()),
+      _ => None,
+    })
+    .collect::<Vec<_>>()
+    .join("/")
+}
+
+fn padding(size: u64) -> usize
+{
+  (BLOCK - (size % BLOCK as u64) as usize) % BLOCK
+}
+
+/// Write a ustar header, preceded by GNU long name/link records when `name`
+/// or `link` do not fit.
+fn write_header(
+  out: &mut dyn Write,
+  name: &str,
+  meta: &std::fs::Metadata,
+  kind: u8,
+  size: u64,
+  link: &str,
+) -> io::Result<()>
+{
+  for (long, flag) in [(name, b'L'), (link, b'K')]
+  {
+    if long.len() > 100
+    {
+      let data = long.as_bytes();
+      let stat = Stat { mode: 0o644, uid: 0, gid: 0, mtime: 0 };
+      let h = header("././@LongLink", &stat, flag, data.len() as u64 + 1, "");
+      out.write_all(&h)?;
+      out.write_all(data)?;
+      out.write_all(&vec![0u8; 1 + padding(data.len() as u64 + 1)])?;
+    }
+  }
+  #[cfg(unix)]
+  let stat = {
+    use std::os::unix::fs::MetadataExt;
+    Stat {
+      mode:  meta.mode() & 0o7777,
+      uid:   meta.uid(),
+      gid:   meta.gid(),
+      mtime: meta.mtime().max(0) as u64,
+    }
+  };
+  #[cfg(not(unix))]
+  let stat = {
+    let mtime = meta
+      .modified()
+      .ok()
+      .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+      .map(|d| d.as_secs())
+      .unwrap_or(0);
+    let mode = if meta.is_dir() { 0o755 } else { 0o644 };
+    Stat { mode, uid: 0, gid: 0, mtime }
+  };
+  out.write_all(&header(name, &stat, kind, size, link))
+}
+
+/// The header fields taken from a member's metadata.
+struct Stat
+{
+  mode:  u32,
+  uid:   u32,
+  gid:   u32,
+  mtime: u64,
+}
+
+fn header(
+  name: &str,
+  stat: &Stat,
+  kind: u8,
+  size: u64,
+  link: &str,
+) -> [u8; BLOCK]
+{
+  let mut h = [0u8; BLOCK];
+  let put_str = |h: &mut [u8; BLOCK], at: usize, len: usize, s: &str| {
+    let b = s.as_bytes();
+    let n = b.len().min(len);
+    h[at..at + n].copy_from_slice(&b[..n]);
+  };
+  put_str(&mut h, 0, 100, name);
+  put_number(&mut h[100..108], stat.mode as u64);
+  put_number(&mut h[108..116], stat.uid as u64);
+  put_number(&mut h[116..124], stat.gid as u64);
+  put_number(&mut h[124..136], size);
+  put_number(&mut h[136..148], stat.mtime);
+  h[156] = kind;
+  put_str(&mut h, 157, 100, link);
+  h[257..263].copy_from_slice(b"ustar\0");
+  h[263..265].copy_from_slice(b"00");
+  // The checksum is summed with its own field read as spaces
+  h[148..156].fill(b' ');
+  let sum: u32 = h.iter().map(|&b| b as u32).sum();
+  h[148..156].copy_from_slice(format!("{:06o}\0 ", sum).as_bytes());
+  h
+}
+
+/// Octal, or base-256 (GNU) when the value does not fit the field.
+fn put_number(
+  field: &mut [u8],
+  v: u64,
+)
+{
+  let digits = field.len() - 1;
+  if v < 1u64 << (3 * digits)
+  {
+    let s = format!("{:0w$o}", v, w = digits);
+    field[..digits].copy_from_slice(s.as_bytes());
+    field[digits] = 0;
+  }
+  else
+  {
+    for (i, b) in field.iter_mut().rev().enumerate()
+    {
+      *b = if i < 8 { (v >> (8 * i)) as u8 } else { 0 };
+    }
+    field[0] |= 0x80;
+  }
+}
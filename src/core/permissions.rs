@@ -0,0 +1,350 @@
+//! Changing modes and owners (`:set_permissions`).
+//!
+//! A request is `[-R] [MODE] [OWNER[:GROUP]]`. MODE is octal (`755`) or
+//! symbolic like chmod's (`u+x,go-w`, `a=rX`); a symbolic clause without
+//! `u`, `g`, `o` or `a` applies to everyone. A bare number is always a mode,
+//! so numeric owners are written `+1000` (or `+1000:+100`). Numbers that
+//! look more like an id than a mode, such as `1000`, are refused; `01000`
+//! says the mode is meant. Modes are set in-process; owners are changed by
+//! running `chown`, which refuses what the user may not do.
+
+#[cfg(unix)]
+use std::path::{
+  Path,
+  PathBuf,
+};
+
+/// Everything one `:set_permissions` line asks for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissionRequest
+{
+  pub recursive: bool,
+  pub mode:      Option<ModeSpec>,
+  pub owner:     Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModeSpec
+{
+  Octal(u32),
+  Symbolic(Vec<Clause>),
+}
+
+/// One comma-separated part of a symbolic mode, such as `go-w`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Clause
+{
+  who: Vec<char>,
+  ops: Vec<(char, String)>,
+}
+
+/// Parse an octal or symbolic mode.
+pub fn parse_mode(s: &str) -> Option<ModeSpec>
+{
+  if !s.is_empty()
+    && (s.len() <= 4 || (s.len() == 5 && s.starts_with('0')))
+    && s.chars().all(|c| ('0'..='7').contains(&c))
+  {
+    return u32::from_str_radix(s, 8).ok().map(ModeSpec::Octal);
+  }
+  let mut clauses = Vec::new();
+  for part in s.split(',')
+  {
+    let split = part.find(['+', '-', '='])?;
+    let (who, rest) = part.split_at(split);
+    if !who.chars().all(|c| "ugoa".contains(c))
+    {
+      return None;
+    }
+    let mut ops: Vec<(char, String)> = Vec::new();
+    for c in rest.chars()
+    {
+      match c
+      {
+        '+' | '-' | '=' => ops.push((c, String::new())),
+        'r' | 'w' | 'x' | 'X' | 's' | 't' => ops.last_mut()?.1.push(c),
+        _ => return None,
+      }
+    }
+    clauses.push(Clause { who: who.chars().collect(), ops });
+  }
+  Some(ModeSpec::Symbolic(clauses))
+}
+
+/// Parse a request line; the error says what was wrong with it.
+pub fn parse_request(input: &str) -> Result<PermissionRequest, String>
+{
+  let mut req =
+    PermissionRequest { recursive: false, mode: None, owner: None };
+  for token in input.split_whitespace()
+  {
+    if token == "-R" || token == "-r"
+    {
+      req.recursive = true;
+    }
+    else if token.chars().all(|c| c.is_ascii_digit()) && id_like(token)
+    {
+      return Err(format!(
+        "'{}' could be a mode or an owner id; write +{} for the owner or 0{} \
+         for the mode",
+        token, token, token
+      ));
+    }
+    else if let Some(mode) = parse_mode(token)
+    {
+      if req.mode.replace(mode).is_some()
+      {
+        return Err(format!("more than one mode given ('{}')", token));
+      }
+    }
+    else if (token.chars().any(|c| "+=,".contains(c))
+      && !numeric_owner(token))
+      || token.starts_with('-')
+    {
+      return Err(format!("invalid mode '{}'", token));
+    }
+    else if req.owner.replace(owner_arg(token)?).is_some()
+    {
+      return Err(format!("more than one owner given ('{}')", token));
+    }
+  }
+  if req.mode.is_none() && req.owner.is_none()
+  {
+    return Err(String::from("expected a mode and/or OWNER[:GROUP]"));
+  }
+  Ok(req)
+}
+
+/// Whether a bare number reads as a user id rather than a mode: it is not
+/// an octal mode at all (`1009`, `65534`), or sets special bits while
+/// leaving the owner no access (`1000`, `2001`), which no one means.
+fn id_like(token: &str) -> bool
+{
+  let b = token.as_bytes();
+  parse_mode(token).is_none() || (b.len() == 4 && b[0] != b'0' && b[1] == b'0')
+}
+
+/// Whether `token` names a user or group by `+N` id.
+fn numeric_owner(token: &str) -> bool
+{
+  token.split(':').any(|part| {
+    part
+      .strip_prefix('+')
+      .is_some_and(|id| id.starts_with(|c: char| c.is_ascii_digit()))
+  })
+}
+
+/// `chown`'s form of an owner: `+N` ids lose the `+`, which not every chown
+/// accepts.
+fn owner_arg(token: &str) -> Result<String, String>
+{
+  let mut parts = Vec::new();
+  for part in token.split(':')
+  {
+    match part.strip_prefix('+')
+    {
+      Some(id) if !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()) =>
+      {
+        parts.push(id)
+      }
+      Some(_) => return Err(format!("invalid owner '{}'", token)),
+      None => parts.push(part),
+    }
+  }
+  Ok(parts.join(":"))
+}
+
+impl ModeSpec
+{
+  /// The new permission bits for a file currently at `mode`.
+  #[cfg_attr(not(unix), allow(dead_code))]
+  pub fn apply(
+    &self,
+    mode: u32,
+    is_dir: bool,
+  ) -> u32
+  {
+    let clauses = match self
+    {
+      ModeSpec::Octal(m) => return m & 0o7777,
+      ModeSpec::Symbolic(c) => c,
+    };
+    let mut mode = mode & 0o7777;
+    for clause in clauses
+    {
+      let who = if clause.who.is_empty() || clause.who.contains(&'a')
+      {
+        vec!['u', 'g', 'o']
+      }
+      else
+      {
+        clause.who.clone()
+      };
+      for (op, perms) in &clause.ops
+      {
+        let mut bits = 0;
+        let mut all = 0;
+        for class in &who
+        {
+          let shift = match class
+          {
+            'u' => 6,
+            'g' => 3,
+            _ => 0,
+          };
+          let special = match class
+          {
+            'u' => 0o4000,
+            'g' => 0o2000,
+            _ => 0o1000,
+          };
+          all |= (0o7 << shift) | special;
+          for p in perms.chars()
+          {
+            bits |= match p
+            {
+              'r' => 0o4 << shift,
+              'w' => 0o2 << shift,
+              'x' => 0o1 << shift,
+              'X' if is_dir || mode & 0o111 != 0 => 0o1 << shift,
+              's' if *class != 'o' => special,
+              't' if *class == 'o' => special,
+              _ => 0,
+            };
+          }
+        }
+        mode = match op
+        {
+          '+' => mode | bits,
+          '-' => mode & !bits,
+          _ => (mode & !all) | bits,
+        };
+      }
+    }
+    mode
+  }
+}
+
+/// Every path to change: `paths` themselves and, when `recursive`, what
+/// their directories hold. Symlinks met on the way are skipped, as chmod
+/// does.
+#[cfg(unix)]
+pub fn expand(
+  paths: &[PathBuf],
+  recursive: bool,
+) -> Vec<PathBuf>
+{
+  fn walk(
+    dir: &Path,
+    out: &mut Vec<PathBuf>,
+  )
+  {
+    let Ok(rd) = std::fs::read_dir(dir)
+    else
+    {
+      return;
+    };
+    let mut children: Vec<PathBuf> = rd.flatten().map(|e| e.path()).collect();
+    children.sort();
+    for child in children
+    {
+      let Ok(meta) = std::fs::symlink_metadata(&child)
+      else
+      {
+        continue;
+      };
+      if meta.is_symlink()
+      {
+        continue;
+      }
+      out.push(child.clone());
+      if meta.is_dir()
+      {
+        walk(&child, out);
+      }
+    }
+  }
+  let mut out = Vec::new();
+  for p in paths
+  {
+    out.push(p.clone());
+    if recursive && std::fs::symlink_metadata(p).is_ok_and(|m| m.is_dir())
+    {
+      walk(p, &mut out);
+    }
+  }
+  out
+}
+
+/// Apply `spec` to `paths`. Returns how many changed and an error line for
+/// each that could not be.
+#[cfg(unix)]
+pub fn chmod(
+  paths: &[PathBuf],
+  spec: &ModeSpec,
+) -> (usize, Vec<String>)
+{
+  let mut done = 0;
+  let mut errors = Vec::new();
+  for p in paths
+  {
+    match chmod_path(p, spec)
+    {
+      Ok(()) => done += 1,
+      Err(e) => errors.push(format!("{}: {}", p.display(), e)),
+    }
+  }
+  (done, errors)
+}
+
+/// Apply `spec` to one path. Modes of symlinks themselves mean nothing, so
+/// links are followed; [`expand`] leaves out those met while recursing, as
+/// chmod does.
+#[cfg(unix)]
+pub fn chmod_path(
+  path: &Path,
+  spec: &ModeSpec,
+) -> std::io::Result<()>
+{
+  use std::os::unix::fs::PermissionsExt;
+  let m = std::fs::metadata(path)?;
+  let mode = spec.apply(m.permissions().mode(), m.is_dir());
+  std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+}
+
+/// Give `paths` to `owner` (`user`, `user:group` or `:group`) with `chown`.
+#[cfg(unix)]
+pub fn chown(
+  paths: &[PathBuf],
+  owner: &str,
+  recursive: bool,
+) -> Result<(), String>
+{
+  let mut cmd = std::process::Command::new("chown");
+  if recursive
+  {
+    cmd.arg("-R");
+  }
+  let out = cmd
+    .arg("--")
+    .arg(owner)
+    .args(paths)
+    .stdin(std::process::Stdio::null())
+    .output()
+    .map_err(|e| format!("chown: {}", e))?;
+  if out.status.success()
+  {
+    return Ok(());
+  }
+  let err = String::from_utf8_lossy(&out.stderr);
+  Err(err.lines().next().unwrap_or("chown failed").trim().to_string())
+}
+
+/// The octal mode of `path` as shown in the prompt, e.g. `644`.
+#[cfg(unix)]
+pub fn current_mode(path: &Path) -> Option<String>
+{
+  use std::os::unix::fs::PermissionsExt;
+  let mode = std::fs::metadata(path).ok()?.permissions().mode() & 0o7777;
+  Some(format!("{:03o}", mode))
+}
@@ -0,0 +1,285 @@
+//! The Windows Recycle Bin.
+//!
+//! Items are sent there with `SHFileOperationW` and `FOF_ALLOWUNDO`, as
+//! Explorer does, so they show up in (and can be restored from) the Recycle
+//! Bin. Each drive keeps the current user's items under
+//! `<drive>:\$Recycle.Bin\<SID>`: the payload as `$R<id>` and a `$I<id>`
+//! record holding the original path and deletion time, which is what the
+//! trash browser reads.
+
+use std::{
+  ffi::c_void,
+  fs,
+  io,
+  os::windows::ffi::OsStrExt,
+  path::{
+    Path,
+    PathBuf,
+  },
+  ptr,
+};
+
+use crate::core::trash::TrashedItem;
+
+const FO_DELETE: u32 = 3;
+const FOF_SILENT: u16 = 0x4;
+const FOF_NOCONFIRMATION: u16 = 0x10;
+const FOF_ALLOWUNDO: u16 = 0x40;
+const FOF_NOERRORUI: u16 = 0x400;
+/// Ask before deleting outright when an item cannot be recycled (network
+/// shares, oversized items), despite `FOF_NOCONFIRMATION`.
+const FOF_WANTNUKEWARNING: u16 = 0x4000;
+const TOKEN_QUERY: u32 = 0x8;
+const TOKEN_USER: u32 = 1;
+const DRIVE_REMOVABLE: u32 = 2;
+const DRIVE_FIXED: u32 = 3;
+/// Seconds between 1601-01-01 (FILETIME) and 1970-01-01.
+const FILETIME_UNIX_OFFSET: i64 = 11_644_473_600;
+
+#[cfg_attr(target_pointer_width = "32", repr(C, packed(1)))]
+#[cfg_attr(not(target_pointer_width = "32"), repr(C))]
+struct ShFileOpStruct
+{
+  hwnd:           *mut c_void,
+  func:           u32,
+  from:           *const u16,
+  to:             *const u16,
+  flags:          u16,
+  any_aborted:    i32,
+  name_mappings:  *mut c_void,
+  progress_title: *const u16,
+}
+
+#[link(name = "shell32")]
+unsafe extern "system" {
+  fn SHFileOperationW(op: *mut ShFileOpStruct) -> i32;
+}
+
+#[link(name = "advapi32")]
+unsafe extern "system" {
+  fn OpenProcessToken(
+    process: *mut c_void,
+    access: u32,
+    token: *mut *mut c_void,
+  ) -> i32;
+
+  fn GetTokenInformation(
+    token: *mut c_void,
+    class: u32,
+    info: *mut c_void,
+    len: u32,
+    ret_len: *mut u32,
+  ) -> i32;
+
+  fn ConvertSidToStringSidW(
+    sid: *mut c_void,
+    out: *mut *mut u16,
+  ) -> i32;
+}
+
+#[link(name = "kernel32")]
+unsafe extern "system" {
+  fn GetCurrentProcess() -> *mut c_void;
+  fn CloseHandle(handle: *mut c_void) -> i32;
+  fn LocalFree(mem: *mut c_void) -> *mut c_void;
+  fn GetLogicalDrives() -> u32;
+  fn GetDriveTypeW(root: *const u16) -> u32;
+}
+
+fn wide(s: &std::ffi::OsStr) -> Vec<u16>
+{
+  s.encode_wide().chain(std::iter::once(0)).collect()
+}
+
+/// Move `path` (absolute) to the Recycle Bin.
+pub fn send(path: &Path) -> io::Result<()>
+{
+  // The source list is NUL-separated and ends with an empty entry
+  let mut from = wide(path.as_os_str());
+  from.push(0);
+  let mut op = ShFileOpStruct {
+    hwnd:           ptr::null_mut(),
+    func:           FO_DELETE,
+    from:           from.as_ptr(),
+    to:             ptr::null(),
+    flags:          FOF_ALLOWUNDO
+      | FOF_NOCONFIRMATION
+      | FOF_SILENT
+      | FOF_NOERRORUI
+      | FOF_WANTNUKEWARNING,
+    any_aborted:    0,
+    name_mappings:  ptr::null_mut(),
+    progress_title: ptr::null(),
+  };
+  // SAFETY: `op` is fully initialised and `from` is double NUL-terminated
+  // and outlives the call.
+  let status = unsafe { SHFileOperationW(&mut op) };
+  if status != 0
+  {
+    return Err(io::Error::other(format!(
+      "the Recycle Bin refused it (error {:#x})",
+      status
+    )));
+  }
+  if op.any_aborted != 0
+  {
+    return Err(io::Error::new(io::ErrorKind::Interrupted, "cancelled"));
+  }
+  Ok(())
+}
+
+/// String form (`S-1-5-21-…`) of the current user's SID.
+fn user_sid() -> Option<String>
+{
+  let mut token: *mut c_void = ptr::null_mut();
+  // SAFETY: the pseudo handle of the current process needs no closing;
+  // `token` is a valid out pointer.
+  if unsafe { OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) }
+    == 0
+  {
+    return None;
+  }
+  // TOKEN_USER is a SID pointer followed by the SID itself; 256 bytes is
+  // far more than the largest SID needs. u64 keeps it pointer-aligned.
+  let mut buf = [0u64; 32];
+  let mut len = 0u32;
+  // SAFETY: `buf` holds the size passed with it; the token is open.
+  let ok = unsafe {
+    GetTokenInformation(
+      token,
+      TOKEN_USER,
+      buf.as_mut_ptr().cast(),
+      std::mem::size_of_val(&buf) as u32,
+      &mut len,
+    )
+  } != 0;
+  // SAFETY: opened above and not used after this.
+  unsafe {
+    CloseHandle(token);
+  }
+  if !ok
+  {
+    return None;
+  }
+  let sid = buf[0] as usize as *mut c_void;
+  let mut text: *mut u16 = ptr::null_mut();
+  // SAFETY: `sid` points into `buf`, filled in by GetTokenInformation.
+  if unsafe { ConvertSidToStringSidW(sid, &mut text) } == 0 || text.is_null()
+  {
+    return None;
+  }
+  // SAFETY: the API returns a NUL-terminated string for us to free.
+  let out = unsafe {
+    let len = (0..).take_while(|&i| *text.add(i) != 0).count();
+    let s = String::from_utf16_lossy(std::slice::from_raw_parts(text, len));
+    LocalFree(text.cast());
+    s
+  };
+  Some(out)
+}
+
+/// The current user's Recycle Bin folder on each local drive that has one.
+fn bins() -> Vec<PathBuf>
+{
+  let Some(sid) = user_sid()
+  else
+  {
+    return Vec::new();
+  };
+  // SAFETY: no arguments; returns a bit mask of drive letters.
+  let mask = unsafe { GetLogicalDrives() };
+  (0..26u8)
+    .filter(|i| mask & (1 << i) != 0)
+    .map(|i| format!("{}:\\", (b'A' + i) as char))
+    // Network and optical drives have no Recycle Bin, and probing a
+    // disconnected share can hang
+    .filter(|root| {
+      let root = wide(root.as_ref());
+      // SAFETY: `root` is NUL-terminated.
+      let kind = unsafe { GetDriveTypeW(root.as_ptr()) };
+      kind == DRIVE_FIXED || kind == DRIVE_REMOVABLE
+    })
+    .map(|root| Path::new(&root).join("$Recycle.Bin").join(&sid))
+    .filter(|dir| dir.is_dir())
+    .collect()
+}
+
+/// The `$I` record kept next to the payload `$R<id>`.
+pub fn record_of(payload: &Path) -> PathBuf
+{
+  let name = payload.file_name().unwrap_or_default().to_string_lossy();
+  let id = name.strip_prefix("$R").unwrap_or(&name);
+  payload.with_file_name(format!("$I{}", id))
+}
+
+/// Parse a `$I` record: version, size, deletion FILETIME, then the original
+/// path (a length-prefixed string since Windows 10, 260 units before).
+fn parse_record(data: &[u8]) -> Option<(PathBuf, i64)>
+{
+  let u64_at = |at: usize| {
+    Some(u64::from_le_bytes(data.get(at..at + 8)?.try_into().ok()?))
+  };
+  let version = u64_at(0)?;
+  let filetime = u64_at(16)? as i64;
+  let units = match version
+  {
+    1 => data.get(24..24 + 520)?,
+    2 =>
+    {
+      let len = u32::from_le_bytes(data.get(24..28)?.try_into().ok()?) as usize;
+      data.get(28..28 + len * 2)?
+    }
+    _ => return None,
+  };
+  let units: Vec<u16> = units
+    .chunks_exact(2)
+    .map(|c| u16::from_le_bytes([c[0], c[1]]))
+    .take_while(|&u| u != 0)
+    .collect();
+  let secs = filetime / 10_000_000 - FILETIME_UNIX_OFFSET;
+  Some((PathBuf::from(String::from_utf16_lossy(&units)), secs))
+}
+
+/// Items in the current user's Recycle Bins, in no particular order. Their
+/// id is the full path of the payload.
+pub fn list() -> io::Result<Vec<TrashedItem>>
+{
+  let mut out = Vec::new();
+  for bin in bins()
+  {
+    let Ok(rd) = fs::read_dir(&bin)
+    else
+    {
+      continue;
+    };
+    for de in rd.flatten()
+    {
+      let name = de.file_name().to_string_lossy().to_string();
+      let Some(id) = name.strip_prefix("$I")
+      else
+      {
+        continue;
+      };
+      let payload = bin.join(format!("$R{}", id));
+      if fs::symlink_metadata(&payload).is_err()
+      {
+        continue;
+      }
+      let Some((original, secs)) =
+        fs::read(de.path()).ok().as_deref().and_then(parse_record)
+      else
+      {
+        continue;
+      };
+      let deleted_at = chrono::DateTime::from_timestamp(secs, 0).map(|d| {
+        d.with_timezone(&chrono::Local).format("%Y-%m-%dT%H:%M:%S").to_string()
+      });
+      out.push(TrashedItem {
+        id: payload.to_string_lossy().to_string(),
+        original_path: original,
+        deleted_at,
+      });
+    }
+  }
+  Ok(out)
+}
@@ -0,0 +1,414 @@
+//! A small backtracking regular expression matcher for name patterns.
+//!
+//! Supports literals, `.`, character classes (`[a-z]`, `[^0-9]`), the
+//! escapes `\d`, `\w`, `\s` (and their negations), anchors `^` and `$`,
+//! groups with alternation (`(jpe?g|png)`), and the quantifiers `*`, `+`,
+//! `?` and `{m}`, `{m,}`, `{m,n}`. A leading `(?i)` makes the match
+//! case-insensitive. Matching is unanchored unless `^`/`$` say otherwise.
+//!
+//! Backtracking can take exponential time on patterns like `^(a+)+$`, so a
+//! match gives up after [`STEP_LIMIT`] steps rather than hang the caller.
+
+use std::cell::Cell;
+
+/// Steps one match may take before giving up; plenty for file names.
+pub const STEP_LIMIT: usize = 200_000;
+
+#[derive(Debug, Clone)]
+enum ClassItem
+{
+  Char(char),
+  Range(char, char),
+  Digit(bool),
+  Word(bool),
+  Space(bool),
+}
+
+impl ClassItem
+{
+  fn matches(
+    &self,
+    c: char,
+  ) -> bool
+  {
+    match *self
+    {
+      ClassItem::Char(x) => c == x,
+      ClassItem::Range(lo, hi) => lo <= c && c <= hi,
+      ClassItem::Digit(neg) => c.is_ascii_digit() != neg,
+      ClassItem::Word(neg) => (c.is_alphanumeric() || c == '_') != neg,
+      ClassItem::Space(neg) => c.is_whitespace() != neg,
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+enum Node
+{
+  Char(char),
+  Any,
+  Class
+  {
+    items:   Vec<ClassItem>,
+    negated: bool,
+  },
+  Start,
+  End,
+  Group(Vec<Vec<Node>>),
+  Repeat
+  {
+    node: Box<Node>,
+    min:  usize,
+    max:  Option<usize>,
+  },
+}
+
+/// A compiled pattern.
+#[derive(Debug, Clone)]
+pub struct Regex
+{
+  root:        Node,
+  ignore_case: bool,
+}
+
+struct Parser
+{
+  chars:       Vec<char>,
+  pos:         usize,
+  ignore_case: bool,
+}
+
+impl Parser
+{
+  /// A literal character, folded when matching case-insensitively.
+  fn lit(
+    &self,
+    c: char,
+  ) -> char
+  {
+    if self.ignore_case { c.to_lowercase().next().unwrap_or(c) } else { c }
+  }
+
+  fn peek(&self) -> Option<char>
+  {
+    self.chars.get(self.pos).copied()
+  }
+
+  fn next(&mut self) -> Option<char>
+  {
+    let c = self.peek();
+    self.pos += 1;
+    c
+  }
+
+  fn alternatives(&mut self) -> Result<Vec<Vec<Node>>, String>
+  {
+    let mut alts = vec![self.sequence()?];
+    while self.peek() == Some('|')
+    {
+      self.pos += 1;
+      alts.push(self.sequence()?);
+    }
+    Ok(alts)
+  }
+
+  fn sequence(&mut self) -> Result<Vec<Node>, String>
+  {
+    let mut seq = Vec::new();
+    while let Some(c) = self.peek()
+    {
+      if c == '|' || c == ')'
+      {
+        break;
+      }
+      let atom = self.atom()?;
+      seq.push(self.quantified(atom)?);
+    }
+    Ok(seq)
+  }
+
+  fn atom(&mut self) -> Result<Node, String>
+  {
+    let c = self.next().ok_or("unexpected end of pattern")?;
+    Ok(match c
+    {
+      '.' => Node::Any,
+      '^' => Node::Start,
+      '$' => Node::End,
+      '(' =>
+      {
+        let alts = self.alternatives()?;
+        if self.next() != Some(')')
+        {
+          return Err(String::from("missing )"));
+        }
+        Node::Group(alts)
+      }
+      '[' => self.class()?,
+      '\\' => match self.escape()?
+      {
+        ClassItem::Char(c) => Node::Char(c),
+        item => Node::Class { items: vec![item], negated: false },
+      },
+      '*' | '+' | '?' | '{' =>
+      {
+        return Err(format!("nothing to repeat before '{}'", c));
+      }
+      c => Node::Char(self.lit(c)),
+    })
+  }
+
+  fn escape(&mut self) -> Result<ClassItem, String>
+  {
+    let c = self.next().ok_or("pattern ends with \\")?;
+    Ok(match c
+    {
+      'd' => ClassItem::Digit(false),
+      'D' => ClassItem::Digit(true),
+      'w' => ClassItem::Word(false),
+      'W' => ClassItem::Word(true),
+      's' => ClassItem::Space(false),
+      'S' => ClassItem::Space(true),
+      't' => ClassItem::Char('\t'),
+      'n' => ClassItem::Char('\n'),
+      c => ClassItem::Char(self.lit(c)),
+    })
+  }
+
+  fn class(&mut self) -> Result<Node, String>
+  {
+    let negated = self.peek() == Some('^');
+    if negated
+    {
+      self.pos += 1;
+    }
+    let mut items = Vec::new();
+    let mut first = true;
+    loop
+    {
+      let c = self.next().ok_or("missing ]")?;
+      if c == ']' && !first
+      {
+        break;
+      }
+      first = false;
+      let item =
+        if c == '\\' { self.escape()? } else { ClassItem::Char(self.lit(c)) };
+      if let ClassItem::Char(lo) = item
+        && self.peek() == Some('-')
+        && self.chars.get(self.pos + 1).is_some_and(|&n| n != ']')
+      {
+        self.pos += 1;
+        let hi = match self.next()
+        {
+          Some('\\') => match self.escape()?
+          {
+            ClassItem::Char(hi) => hi,
+            _ => return Err(String::from("bad range in []")),
+          },
+          Some(hi) => self.lit(hi),
+          None => return Err(String::from("missing ]")),
+        };
+        if hi < lo
+        {
+          return Err(format!("bad range {}-{}", lo, hi));
+        }
+        items.push(ClassItem::Range(lo, hi));
+      }
+      else
+      {
+        items.push(item);
+      }
+    }
+    Ok(Node::Class { items, negated })
+  }
+
+  fn number(&mut self) -> Option<usize>
+  {
+    let start = self.pos;
+    while self.peek().is_some_and(|c| c.is_ascii_digit())
+    {
+      self.pos += 1;
+    }
+    self.chars[start..self.pos].iter().collect::<String>().parse().ok()
+  }
+
+  fn quantified(
+    &mut self,
+    node: Node,
+  ) -> Result<Node, String>
+  {
+    let (min, max) = match self.peek()
+    {
+      Some('*') => (0, None),
+      Some('+') => (1, None),
+      Some('?') => (0, Some(1)),
+      Some('{') =>
+      {
+        self.pos += 1;
+        let min = self.number().ok_or("expected a number after {")?;
+        let max = if self.peek() == Some(',')
+        {
+          self.pos += 1;
+          self.number()
+        }
+        else
+        {
+          Some(min)
+        };
+        if self.peek() != Some('}')
+        {
+          return Err(String::from("missing }"));
+        }
+        if max.is_some_and(|m| m < min)
+        {
+          return Err(format!("bad repeat {{{},{:?}}}", min, max));
+        }
+        (min, max)
+      }
+      _ => return Ok(node),
+    };
+    self.pos += 1;
+    if matches!(node, Node::Start | Node::End)
+    {
+      return Err(String::from("anchors cannot be repeated"));
+    }
+    Ok(Node::Repeat { node: Box::new(node), min, max })
+  }
+}
+
+impl Regex
+{
+  /// Compile `pattern`, or describe why it is invalid.
+  pub fn new(pattern: &str) -> Result<Regex, String>
+  {
+    let (pattern, ignore_case) = match pattern.strip_prefix("(?i)")
+    {
+      Some(rest) => (rest, true),
+      None => (pattern, false),
+    };
+    let mut parser =
+      Parser { chars: pattern.chars().collect(), pos: 0, ignore_case };
+    let alts = parser.alternatives()?;
+    if parser.pos < parser.chars.len()
+    {
+      return Err(String::from("unmatched )"));
+    }
+    Ok(Regex { root: Node::Group(alts), ignore_case })
+  }
+
+  /// Whether the pattern matches anywhere in `text`, or `None` when that
+  /// takes more than [`STEP_LIMIT`] steps to find out.
+  pub fn try_match(
+    &self,
+    text: &str,
+  ) -> Option<bool>
+  {
+    let text: Vec<char> = if self.ignore_case
+    {
+      text.chars().map(|c| c.to_lowercase().next().unwrap_or(c)).collect()
+    }
+    else
+    {
+      text.chars().collect()
+    };
+    let m = Matcher { text: &text, steps: Cell::new(0) };
+    let root = std::slice::from_ref(&self.root);
+    let found = (0..=text.len()).any(|start| m.seq(root, start, &mut |_| true));
+    if m.steps.get() > STEP_LIMIT { None } else { Some(found) }
+  }
+}
+
+struct Matcher<'a>
+{
+  text:  &'a [char],
+  steps: Cell<usize>,
+}
+
+impl Matcher<'_>
+{
+  /// Match `seq` at `pos`, calling `k` with each end position until it
+  /// accepts one.
+  fn seq(
+    &self,
+    seq: &[Node],
+    pos: usize,
+    k: &mut dyn FnMut(usize) -> bool,
+  ) -> bool
+  {
+    // Past the limit every branch fails, unwinding quickly
+    let steps = self.steps.get() + 1;
+    self.steps.set(steps);
+    if steps > STEP_LIMIT
+    {
+      return false;
+    }
+    let Some((node, rest)) = seq.split_first()
+    else
+    {
+      return k(pos);
+    };
+    match node
+    {
+      Node::Group(alts) =>
+      {
+        alts.iter().any(|alt| self.seq(alt, pos, &mut |p| self.seq(rest, p, k)))
+      }
+      Node::Repeat { node, min, max } =>
+      {
+        self.repeat(node, *min, *max, 0, pos, &mut |p| self.seq(rest, p, k))
+      }
+      Node::Start => pos == 0 && self.seq(rest, pos, k),
+      Node::End => pos == self.text.len() && self.seq(rest, pos, k),
+      single =>
+      {
+        let Some(&c) = self.text.get(pos)
+        else
+        {
+          return false;
+        };
+        let ok = match single
+        {
+          Node::Char(x) => c == *x,
+          Node::Any => true,
+          Node::Class { items, negated } =>
+          {
+            items.iter().any(|i| i.matches(c)) != *negated
+          }
+          _ => false,
+        };
+        ok && self.seq(rest, pos + 1, k)
+      }
+    }
+  }
+
+  /// Greedily match `node` between `min` and `max` times.
+  fn repeat(
+    &self,
+    node: &Node,
+    min: usize,
+    max: Option<usize>,
+    count: usize,
+    pos: usize,
+    k: &mut dyn FnMut(usize) -> bool,
+  ) -> bool
+  {
+    if max.is_none_or(|m| count < m)
+      && self.seq(std::slice::from_ref(node), pos, &mut |p| {
+        // An empty match would repeat forever; further rounds can't
+        // consume anything either, so stop here
+        if p == pos
+        {
+          k(p)
+        }
+        else
+        {
+          self.repeat(node, min, max, count + 1, p, k)
+        }
+      })
+    {
+      return true;
+    }
+    count >= min && k(pos)
+  }
+}
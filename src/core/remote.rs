@@ -0,0 +1,605 @@
+//! Remote directories over SFTP.
+//!
+//! A path such as `sftp://me@example.org:2222/srv/www` names the directory
+//! `/srv/www` on `example.org`. Everything goes through the OpenSSH `sftp`
+//! client in batch mode (and `ssh` for previews), so host aliases, keys and
+//! options come from `~/.ssh/config`. There is no terminal to ask for a
+//! password on, so hosts need key or agent authentication.
+
+use std::{
+  io::{
+    self,
+    Read,
+    Write,
+  },
+  path::{
+    Path,
+    PathBuf,
+  },
+  process::{
+    Command,
+    Stdio,
+  },
+  time::{
+    Duration,
+    SystemTime,
+  },
+};
+
+use chrono::{
+  Datelike,
+  NaiveDate,
+  NaiveDateTime,
+  TimeZone,
+};
+
+use crate::core::fs_ops::CopyProgress;
+
+pub const SCHEME: &str = "sftp://";
+
+/// Seconds to wait for a host to answer before giving up.
+const CONNECT_TIMEOUT_SECS: u32 = 10;
+
+/// A location on a remote host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemotePath
+{
+  pub user: Option<String>,
+  pub host: String,
+  pub port: Option<u16>,
+  /// Absolute, `/`-separated path on the host, without `.` or `..`.
+  pub path: String,
+}
+
+/// An entry of a remote directory listing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteEntry
+{
+  pub name:     String,
+  pub is_dir:   bool,
+  pub size:     u64,
+  pub modified: Option<NaiveDateTime>,
+}
+
+/// Whether `path` names a remote location.
+pub fn is_remote(path: &Path) -> bool
+{
+  path.to_str().is_some_and(|s| s.starts_with(SCHEME))
+}
+
+impl RemotePath
+{
+  /// Parse `sftp://[user@]host[:port][/path]`. IPv6 hosts with a port go
+  /// in brackets: `sftp://[::1]:2222/tmp`.
+  pub fn parse(path: &Path) -> Option<RemotePath>
+  {
+    let rest = path.to_str()?.strip_prefix(SCHEME)?;
+    let (authority, raw) = match rest.find('/')
+    {
+      Some(i) => (&rest[..i], &rest[i..]),
+      None => (rest, "/"),
+    };
+    let (user, hostport) = match authority.rsplit_once('@')
+    {
+      Some((user, hostport)) => (Some(user.to_string()), hostport),
+      None => (None, authority),
+    };
+    let (host, port) = if let Some(bracketed) = hostport.strip_prefix('[')
+    {
+      let (host, tail) = bracketed.split_once(']')?;
+      let port = match tail.strip_prefix(':')
+      {
+        Some(p) => Some(p.parse().ok()?),
+        None if tail.is_empty() => None,
+        None => return None,
+      };
+      (host, port)
+    }
+    else if hostport.matches(':').count() > 1
+    {
+      // A bare IPv6 address has no room for a port
+      (hostport, None)
+    }
+    else
+    {
+      match hostport.split_once(':')
+      {
+        Some((host, port)) => (host, Some(port.parse().ok()?)),
+        None => (hostport, None),
+      }
+    };
+    // A host starting with `-` would reach ssh as an option
+    if host.is_empty() || host.starts_with('-') || user.as_deref() == Some("")
+    {
+      return None;
+    }
+    let mut parts: Vec<&str> = Vec::new();
+    for seg in raw.split('/')
+    {
+      match seg
+      {
+        "" | "." =>
+        {}
+        ".." =>
+        {
+          parts.pop();
+        }
+        seg => parts.push(seg),
+      }
+    }
+    Some(RemotePath {
+      user,
+      host: host.to_string(),
+      port,
+      path: format!("/{}", parts.join("/")),
+    })
+  }
+
+  /// The `sftp://` form of this location.
+  pub fn to_path(&self) -> PathBuf
+  {
+    let mut s = String::from(SCHEME);
+    if let Some(user) = &self.user
+    {
+      s.push_str(user);
+      s.push('@');
+    }
+    s.push_str(&self.bracketed_host());
+    if let Some(port) = self.port
+    {
+      s.push_str(&format!(":{}", port));
+    }
+    s.push_str(&self.path);
+    PathBuf::from(s)
+  }
+
+  /// The directory holding this location; `None` at the host's root.
+  pub fn parent(&self) -> Option<RemotePath>
+  {
+    if self.path == "/"
+    {
+      return None;
+    }
+    let (dir, _) = self.path.rsplit_once('/')?;
+    let path = if dir.is_empty() { "/" } else { dir };
+    Some(RemotePath { path: path.to_string(), ..self.clone() })
+  }
+
+  /// The last path segment; empty at the root.
+  pub fn name(&self) -> &str
+  {
+    self.path.rsplit('/').next().unwrap_or("")
+  }
+
+  fn join(
+    &self,
+    name: &str,
+  ) -> RemotePath
+  {
+    let path = if self.path == "/"
+    {
+      format!("/{}", name)
+    }
+    else
+    {
+      format!("{}/{}", self.path, name)
+    };
+    RemotePath { path, ..self.clone() }
+  }
+
+  /// Whether both locations are reached through the same connection.
+  pub fn same_host(
+    &self,
+    other: &RemotePath,
+  ) -> bool
+  {
+    self.user == other.user
+      && self.host == other.host
+      && self.port == other.port
+  }
+
+  fn bracketed_host(&self) -> String
+  {
+    if self.host.contains(':')
+    {
+      format!("[{}]", self.host)
+    }
+    else
+    {
+      self.host.clone()
+    }
+  }
+
+  /// Options shared by `ssh` and `sftp`.
+  fn connect_args(&self) -> Vec<String>
+  {
+    let mut args = vec![
+      String::from("-o"),
+      String::from("BatchMode=yes"),
+      String::from("-o"),
+      format!("ConnectTimeout={}", CONNECT_TIMEOUT_SECS),
+    ];
+    if let Some(user) = &self.user
+    {
+      args.push(String::from("-o"));
+      args.push(format!("User={}", user));
+    }
+    if let Some(port) = self.port
+    {
+      args.push(String::from("-o"));
+      args.push(format!("Port={}", port));
+    }
+    args
+  }
+}
+
+/// Quote `arg` for a line of an sftp batch file. Commands that expand globs
+/// (`ls`, `rm`, `get`, `put`) see `*`, `?`, `[` and `\` as themselves.
+/// Control characters cannot be quoted (a newline would start another
+/// command), so names holding one are refused.
+pub fn batch_quote(
+  arg: &str,
+  globbed: bool,
+) -> io::Result<String>
+{
+  if arg.chars().any(char::is_control)
+  {
+    return Err(io::Error::new(
+      io::ErrorKind::InvalidInput,
+      format!("cannot pass {:?} to sftp: it holds a control character", arg),
+    ));
+  }
+  let mut out = String::with_capacity(arg.len());
+  for c in arg.chars()
+  {
+    let special = match c
+    {
+      ' ' | '"' | '\'' => true,
+      '*' | '?' | '[' | '\\' => globbed,
+      _ => false,
+    };
+    if special
+    {
+      out.push('\\');
+    }
+    out.push(c);
+  }
+  Ok(out)
+}
+
+/// Quote `arg` for the remote shell `ssh` runs commands with.
+fn shell_quote(arg: &str) -> String
+{
+  format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+fn sftp_command(rp: &RemotePath) -> Command
+{
+  let mut cmd = Command::new("sftp");
+  cmd.arg("-q").args(rp.connect_args()).arg("-b").arg("-");
+  cmd.arg("--").arg(rp.bracketed_host());
+  // Month names in listings are parsed in English
+  cmd.env("LC_ALL", "C");
+  cmd
+}
+
+fn command_error(
+  program: &str,
+  stderr: &[u8],
+) -> io::Error
+{
+  let err = String::from_utf8_lossy(stderr);
+  match err.lines().map(str::trim).rfind(|l| !l.is_empty())
+  {
+    Some(line) => io::Error::other(format!("{}: {}", program, line)),
+    None => io::Error::other(format!("{} failed", program)),
+  }
+}
+
+/// Run `script` against the host of `rp` and return what it printed.
+fn batch(
+  rp: &RemotePath,
+  script: &str,
+) -> io::Result<String>
+{
+  let mut child = sftp_command(rp)
+    .stdin(Stdio::piped())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn()?;
+  if let Some(mut stdin) = child.stdin.take()
+  {
+    stdin.write_all(script.as_bytes())?;
+  }
+  let out = child.wait_with_output()?;
+  if !out.status.success()
+  {
+    return Err(command_error("sftp", &out.stderr));
+  }
+  Ok(String::from_utf8_lossy(&out.stdout).into_owned())
+}
+
+/// Like [`batch`], for transfers: output is dropped and `on_progress` is
+/// polled so the job can be cancelled.
+fn batch_transfer(
+  rp: &RemotePath,
+  script: &str,
+  on_progress: &mut dyn FnMut(CopyProgress) -> io::Result<()>,
+) -> io::Result<()>
+{
+  let mut child = sftp_command(rp)
+    .stdin(Stdio::piped())
+    .stdout(Stdio::null())
+    .stderr(Stdio::piped())
+    .spawn()?;
+  if let Some(mut stdin) = child.stdin.take()
+  {
+    stdin.write_all(script.as_bytes())?;
+  }
+  let status = loop
+  {
+    if let Some(status) = child.try_wait()?
+    {
+      break status;
+    }
+    // No byte counts to report, but this is where cancelling is noticed
+    if let Err(e) = on_progress(CopyProgress::Bytes(0))
+    {
+      let _ = child.kill();
+      let _ = child.wait();
+      return Err(e);
+    }
+    std::thread::sleep(Duration::from_millis(50));
+  };
+  if status.success()
+  {
+    return Ok(());
+  }
+  let mut stderr = Vec::new();
+  if let Some(mut err) = child.stderr.take()
+  {
+    let _ = err.read_to_end(&mut stderr);
+  }
+  Err(command_error("sftp", &stderr))
+}
+
+/// Parse the `ls -ln` output of sftp. Echoed batch commands and the `.` and
+/// `..` entries are skipped. Dates without a year are placed in the year
+/// before `now` when they would otherwise lie in the future.
+pub fn parse_listing(
+  text: &str,
+  now: NaiveDateTime,
+) -> Vec<RemoteEntry>
+{
+  text
+    .lines()
+    .filter(|l| !l.starts_with("sftp>"))
+    .filter_map(|l| parse_long_line(l, now))
+    .filter(|e| e.name != "." && e.name != "..")
+    .collect()
+}
+
+fn parse_long_line(
+  line: &str,
+  now: NaiveDateTime,
+) -> Option<RemoteEntry>
+{
+  // mode, links, owner, group, size, month, day, time or year; then the
+  // name, which may hold spaces of its own
+  let mut fields = Vec::with_capacity(8);
+  let mut rest = line;
+  for _ in 0..8
+  {
+    let start = rest.trim_start_matches(' ');
+    let end = start.find(' ')?;
+    fields.push(&start[..end]);
+    rest = &start[end..];
+  }
+  let name = rest.strip_prefix(' ')?;
+  if name.is_empty()
+  {
+    return None;
+  }
+  let size = fields[4].parse().ok()?;
+  Some(RemoteEntry {
+    name: name.to_string(),
+    is_dir: fields[0].starts_with('d'),
+    size,
+    modified: parse_date(fields[5], fields[6], fields[7], now),
+  })
+}
+
+fn parse_date(
+  month: &str,
+  day: &str,
+  time_or_year: &str,
+  now: NaiveDateTime,
+) -> Option<NaiveDateTime>
+{
+  const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct",
+    "Nov", "Dec",
+  ];
+  let month = MONTHS.iter().position(|m| *m == month)? as u32 + 1;
+  let day: u32 = day.parse().ok()?;
+  match time_or_year.split_once(':')
+  {
+    Some((h, m)) =>
+    {
+      let (h, m) = (h.parse().ok()?, m.parse().ok()?);
+      let at =
+        |year| NaiveDate::from_ymd_opt(year, month, day)?.and_hms_opt(h, m, 0);
+      at(now.year())
+        .filter(|t| *t <= now + chrono::Duration::days(1))
+        .or_else(|| at(now.year() - 1))
+    }
+    None =>
+    {
+      let year = time_or_year.parse().ok()?;
+      NaiveDate::from_ymd_opt(year, month, day)?.and_hms_opt(0, 0, 0)
+    }
+  }
+}
+
+fn list_entries(rp: &RemotePath) -> io::Result<Vec<RemoteEntry>>
+{
+  let out = batch(rp, &format!("ls -lan {}\n", batch_quote(&rp.path, true)?))?;
+  Ok(parse_listing(&out, chrono::Local::now().naive_local()))
+}
+
+/// List a remote directory.
+pub fn read_dir(
+  rp: &RemotePath,
+  show_hidden: bool,
+) -> io::Result<Vec<crate::app::DirEntryInfo>>
+{
+  let now = chrono::Local::now();
+  Ok(
+    list_entries(rp)?
+      .into_iter()
+      .filter(|e| show_hidden || !e.name.starts_with('.'))
+      .map(|e| crate::app::DirEntryInfo {
+        path:   rp.join(&e.name).to_path(),
+        name:   e.name,
+        is_dir: e.is_dir,
+        size:   if e.is_dir { 0 } else { e.size },
+        mtime:  e
+          .modified
+          .and_then(|t| now.timezone().from_local_datetime(&t).earliest())
+          .map(SystemTime::from),
+        ctime:  None,
+        meta:   None,
+      })
+      .collect(),
+  )
+}
+
+/// The first `max_bytes` of a remote file, read with `head` over `ssh`.
+pub fn head(
+  rp: &RemotePath,
+  max_bytes: usize,
+) -> io::Result<Vec<u8>>
+{
+  let out = Command::new("ssh")
+    .args(rp.connect_args())
+    .arg("--")
+    .arg(&rp.host)
+    .arg(format!("head -c {} -- {}", max_bytes, shell_quote(&rp.path)))
+    .stdin(Stdio::null())
+    .output()?;
+  if !out.status.success()
+  {
+    return Err(command_error("ssh", &out.stderr));
+  }
+  Ok(out.stdout)
+}
+
+/// Rename within one host.
+pub fn rename(
+  from: &RemotePath,
+  to: &RemotePath,
+) -> io::Result<()>
+{
+  if !from.same_host(to)
+  {
+    return Err(io::Error::new(
+      io::ErrorKind::Unsupported,
+      "cannot rename across hosts",
+    ));
+  }
+  let script = format!(
+    "rename {} {}\n",
+    batch_quote(&from.path, false)?,
+    batch_quote(&to.path, false)?
+  );
+  batch(from, &script).map(|_| ())
+}
+
+/// Remove a remote file, or a directory and everything below it.
+pub fn remove_all(rp: &RemotePath) -> io::Result<()>
+{
+  let Some(parent) = rp.parent()
+  else
+  {
+    return Err(io::Error::new(
+      io::ErrorKind::PermissionDenied,
+      "refusing to remove the root directory",
+    ));
+  };
+  let is_dir = list_entries(&parent)?
+    .into_iter()
+    .find(|e| e.name == rp.name())
+    .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))?
+    .is_dir;
+  let mut script = String::new();
+  if is_dir
+  {
+    // Files first, then directories deepest first
+    let mut dirs = vec![rp.clone()];
+    let mut i = 0;
+    while let Some(dir) = dirs.get(i).cloned()
+    {
+      for e in list_entries(&dir)?
+      {
+        let child = dir.join(&e.name);
+        if e.is_dir
+        {
+          dirs.push(child);
+        }
+        else
+        {
+          script.push_str(&format!("rm {}\n", batch_quote(&child.path, true)?));
+        }
+      }
+      i += 1;
+    }
+    for dir in dirs.iter().rev()
+    {
+      script.push_str(&format!("rmdir {}\n", batch_quote(&dir.path, false)?));
+    }
+  }
+  else
+  {
+    script.push_str(&format!("rm {}\n", batch_quote(&rp.path, true)?));
+  }
+  batch(rp, &script).map(|_| ())
+}
+
+/// Download `src` to `dest` or upload it, whichever of the two is remote.
+/// Directories are copied with their contents.
+pub fn transfer(
+  src: &Path,
+  dest: &Path,
+  on_progress: &mut dyn FnMut(CopyProgress) -> io::Result<()>,
+) -> io::Result<()>
+{
+  match (RemotePath::parse(src), RemotePath::parse(dest))
+  {
+    (Some(from), None) =>
+    {
+      let script = format!(
+        "get -R {} {}\n",
+        batch_quote(&from.path, true)?,
+        batch_quote(&dest.to_string_lossy(), false)?
+      );
+      batch_transfer(&from, &script, on_progress)
+    }
+    (None, Some(to)) =>
+    {
+      let script = format!(
+        "put -R {} {}\n",
+        batch_quote(&src.to_string_lossy(), true)?,
+        batch_quote(&to.path, false)?
+      );
+      batch_transfer(&to, &script, on_progress)
+    }
+    (Some(_), Some(_)) => Err(io::Error::new(
+      io::ErrorKind::Unsupported,
+      "copying between remote locations is not supported",
+    )),
+    (None, None) =>
+    {
+      Err(io::Error::new(io::ErrorKind::InvalidInput, "neither path is remote"))
+    }
+  }
+}
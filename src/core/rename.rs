@@ -0,0 +1,198 @@
+//! Bulk renames from an edited list of names.
+//!
+//! The list has one line per item, in the order the items were written out.
+//! [`plan_renames`] validates the edited names and rejects collisions before
+//! anything is touched; [`apply_renames`] then performs the renames, staging
+//! items through temporary names so swaps and rotations work.
+
+use std::{
+  collections::HashSet,
+  fs::File,
+  hash::{
+    BuildHasher,
+    RandomState,
+  },
+  io::{
+    self,
+    Write,
+  },
+  path::{
+    Path,
+    PathBuf,
+  },
+};
+
+/// Render `items` as the editable list: one file name per line.
+pub fn names_text(items: &[PathBuf]) -> String
+{
+  let mut out = String::new();
+  for p in items
+  {
+    out.push_str(
+      &p.file_name().map(|s| s.to_string_lossy()).unwrap_or_default(),
+    );
+    out.push('\n');
+  }
+  out
+}
+
+/// Write `text` to a new file in `dir` under an unpredictable name, for the
+/// editor to open. The file is created exclusively, so a name planted in
+/// advance (a symlink, say) is never written through; taken names are
+/// skipped.
+pub fn create_list_file(
+  dir: &Path,
+  text: &str,
+) -> io::Result<PathBuf>
+{
+  let seed = RandomState::new();
+  let mut last = None;
+  for attempt in 0u32..100
+  {
+    let tag = seed.hash_one((std::process::id(), attempt));
+    let path = dir.join(format!("lsv-rename-{:016x}.txt", tag));
+    match File::options().write(true).create_new(true).open(&path)
+    {
+      Ok(mut f) =>
+      {
+        f.write_all(text.as_bytes())?;
+        return Ok(path);
+      }
+      Err(e) if e.kind() == io::ErrorKind::AlreadyExists => last = Some(e),
+      Err(e) => return Err(e),
+    }
+  }
+  Err(last.unwrap_or_else(|| io::Error::from(io::ErrorKind::AlreadyExists)))
+}
+
+fn check_name(name: &str) -> Result<(), String>
+{
+  if name.is_empty()
+  {
+    return Err("empty name".to_string());
+  }
+  if name == "." || name == ".." || name.contains('/') || name.contains('\0')
+  {
+    return Err(format!("{}: invalid file name", name));
+  }
+  #[cfg(windows)]
+  if name.contains('\\')
+  {
+    return Err(format!("{}: invalid file name", name));
+  }
+  Ok(())
+}
+
+/// Pair each of `items` with the name on the matching line of `text`.
+///
+/// Returns only the items whose name changed, as `(from, to)` pairs in the
+/// same directory. Fails without renaming anything when the line count does
+/// not match, a name is invalid, two items would get the same name, or a new
+/// name is already taken by a file that is not itself being renamed.
+pub fn plan_renames(
+  items: &[PathBuf],
+  text: &str,
+) -> Result<Vec<(PathBuf, PathBuf)>, String>
+{
+  let names: Vec<&str> = text
+    .strip_suffix('\n')
+    .unwrap_or(text)
+    .split('\n')
+    .map(|l| l.strip_suffix('\r').unwrap_or(l))
+    .collect();
+  if names.len() != items.len()
+  {
+    return Err(format!(
+      "expected {} lines, found {}; lines must not be added or removed",
+      items.len(),
+      names.len()
+    ));
+  }
+  let mut plan = Vec::new();
+  let mut targets: HashSet<PathBuf> = HashSet::new();
+  for (src, name) in items.iter().zip(names)
+  {
+    check_name(name)?;
+    let dst = src.with_file_name(name);
+    if !targets.insert(dst.clone())
+    {
+      return Err(format!("{}: more than one item renamed to it", name));
+    }
+    if dst != *src
+    {
+      plan.push((src.clone(), dst));
+    }
+  }
+  let moving: HashSet<&Path> = plan.iter().map(|(s, _)| s.as_path()).collect();
+  for (_, dst) in &plan
+  {
+    if std::fs::symlink_metadata(dst).is_ok() && !moving.contains(dst.as_path())
+    {
+      return Err(format!("{}: already exists", dst.display()));
+    }
+  }
+  Ok(plan)
+}
+
+/// Perform the renames from [`plan_renames`]. Every item is first moved
+/// aside to a temporary name, so chains and swaps never overwrite an item
+/// that has not moved yet; a target that exists by the time its item gets
+/// there is left alone and reported.
+///
+/// Returns the renames that succeeded and a message per failure.
+pub fn apply_renames(
+  plan: &[(PathBuf, PathBuf)]
+) -> (Vec<(PathBuf, PathBuf)>, Vec<String>)
+{
+  let mut errors = Vec::new();
+  let mut staged: Vec<(PathBuf, PathBuf, PathBuf)> = Vec::new();
+  for (i, (src, dst)) in plan.iter().enumerate()
+  {
+    let name = src.file_name().map(|s| s.to_string_lossy()).unwrap_or_default();
+    let tmp = (0..)
+      .map(|n| {
+        src.with_file_name(format!(
+          ".{}.lsv-rename-{}-{}-{}",
+          name,
+          std::process::id(),
+          i,
+          n
+        ))
+      })
+      .find(|p| std::fs::symlink_metadata(p).is_err())
+      .unwrap_or_else(|| src.clone());
+    match std::fs::rename(src, &tmp)
+    {
+      Ok(()) => staged.push((src.clone(), tmp, dst.clone())),
+      Err(e) => errors.push(format!("{}: {}", src.display(), e)),
+    }
+  }
+  let mut done = Vec::new();
+  for (src, from, dst) in staged
+  {
+    // std::fs::rename replaces what is there; never let it
+    let res = if std::fs::symlink_metadata(&dst).is_ok()
+    {
+      Err(std::io::Error::from(std::io::ErrorKind::AlreadyExists))
+    }
+    else
+    {
+      std::fs::rename(&from, &dst)
+    };
+    match res
+    {
+      Ok(()) => done.push((src, dst)),
+      Err(e) =>
+      {
+        errors.push(format!("{} -> {}: {}", src.display(), dst.display(), e));
+        // Put the item back under its original name unless another item
+        // has taken it meanwhile
+        if std::fs::symlink_metadata(&src).is_err()
+        {
+          let _ = std::fs::rename(&from, &src);
+        }
+      }
+    }
+  }
+  (done, errors)
+}
@@ -0,0 +1,135 @@
+//! Session state saved on exit and restored on the next start.
+//!
+//! The file is line oriented, `<key>=<value>` per line, so it stays readable
+//! and tolerates keys being added or dropped between versions. Unknown keys
+//! and unparsable values are ignored.
+
+use std::{
+  fs,
+  io::{
+    self,
+    Write,
+  },
+  path::{
+    Path,
+    PathBuf,
+  },
+};
+
+use crate::{
+  actions::SortKey,
+  app::{
+    DisplayMode,
+    InfoMode,
+  },
+  enums,
+};
+
+/// View settings and location of one lsv instance. Fields missing from the
+/// file are `None` and leave the configured defaults alone.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Session
+{
+  pub cwd:          Option<PathBuf>,
+  /// Name of the highlighted entry in `cwd`.
+  pub selected:     Option<String>,
+  pub sort_key:     Option<SortKey>,
+  pub sort_reverse: Option<bool>,
+  pub info_mode:    Option<InfoMode>,
+  pub display_mode: Option<DisplayMode>,
+  pub show_hidden:  Option<bool>,
+}
+
+fn parse_bool(s: &str) -> Option<bool>
+{
+  match s
+  {
+    "true" => Some(true),
+    "false" => Some(false),
+    _ => None,
+  }
+}
+
+/// Read a session file; a missing or unreadable file yields an empty session.
+pub fn load_session(path: &Path) -> Session
+{
+  let mut out = Session::default();
+  let Ok(text) = fs::read_to_string(path)
+  else
+  {
+    return out;
+  };
+  for line in text.lines()
+  {
+    if line.trim().is_empty() || line.starts_with('#')
+    {
+      continue;
+    }
+    let Some((key, value)) = line.split_once('=')
+    else
+    {
+      continue;
+    };
+    match key.trim()
+    {
+      "cwd" if !value.is_empty() => out.cwd = Some(PathBuf::from(value)),
+      "selected" if !value.is_empty() => out.selected = Some(value.to_string()),
+      "sort" => out.sort_key = enums::sort_key_from_str(value),
+      "sort_reverse" => out.sort_reverse = parse_bool(value),
+      "show" => out.info_mode = enums::info_mode_from_str(value),
+      "display" => out.display_mode = enums::display_mode_from_str(value),
+      "show_hidden" => out.show_hidden = parse_bool(value),
+      _ =>
+      {}
+    }
+  }
+  out
+}
+
+/// Write `session` to `path`, replacing the previous file atomically.
+pub fn save_session(
+  path: &Path,
+  session: &Session,
+) -> io::Result<()>
+{
+  if let Some(parent) = path.parent()
+  {
+    let _ = fs::create_dir_all(parent);
+  }
+  let mut tmp = path.to_path_buf();
+  tmp.set_extension("tmp");
+  let mut f = fs::File::create(&tmp)?;
+  writeln!(f, "# lsv session state (rewritten on exit)")?;
+  if let Some(p) = session.cwd.as_ref()
+  {
+    writeln!(f, "cwd={}", p.display())?;
+  }
+  // Names with line breaks cannot be stored in this format
+  if let Some(name) = session.selected.as_ref().filter(|n| !n.contains('\n'))
+  {
+    writeln!(f, "selected={}", name)?;
+  }
+  if let Some(k) = session.sort_key
+  {
+    writeln!(f, "sort={}", enums::sort_key_to_str(k))?;
+  }
+  if let Some(b) = session.sort_reverse
+  {
+    writeln!(f, "sort_reverse={}", b)?;
+  }
+  if let Some(m) = session.info_mode
+  {
+    writeln!(f, "show={}", enums::info_mode_to_str(m).unwrap_or("none"))?;
+  }
+  if let Some(d) = session.display_mode
+  {
+    writeln!(f, "display={}", enums::display_mode_to_str(d))?;
+  }
+  if let Some(b) = session.show_hidden
+  {
+    writeln!(f, "show_hidden={}", b)?;
+  }
+  f.flush()?;
+  fs::rename(tmp, path)?;
+  Ok(())
+}
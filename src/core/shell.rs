@@ -0,0 +1,233 @@
+//! Spawning shell commands as `command_shell` asks.
+//!
+//! Previewers, thumbnailers, openers, compressors, the bulk rename editor,
+//! `os_run` and the command pane run their command lines through
+//! [`command`] or [`with_args`], so one setting decides the shell, whether
+//! it starts as a login shell and which environment variables reach the
+//! command. POSIX shells get `-c` (`-l -c` for a login shell),
+//! `cmd` gets `/C`, and PowerShell gets `-Command`, with `-NoProfile` unless
+//! a login shell is asked for.
+//!
+//! Before that, [`expand`] fills in the placeholders of the command line
+//! (`{file}`, `{files}`, `{dir}`, `{basename}`, `{ext}`, `{clipboard}` and
+//! `{env:NAME}`), each quoted for the shell that will read it.
+
+use std::{
+  ffi::OsStr,
+  path::{
+    Path,
+    PathBuf,
+  },
+  process::Command,
+};
+
+use crate::config::CommandShell;
+
+/// How a shell takes a command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Flavor
+{
+  Posix,
+  Cmd,
+  PowerShell,
+}
+
+fn flavor(program: &str) -> Flavor
+{
+  let stem = Path::new(program)
+    .file_stem()
+    .map(|s| s.to_string_lossy().to_lowercase())
+    .unwrap_or_default();
+  match stem.as_str()
+  {
+    "cmd" => Flavor::Cmd,
+    "powershell" | "pwsh" => Flavor::PowerShell,
+    _ => Flavor::Posix,
+  }
+}
+
+/// What the placeholders of a command stand for.
+#[derive(Debug, Clone, Default)]
+pub struct Placeholders
+{
+  /// `{file}`, `{basename}` and `{ext}`: the entry under the cursor.
+  pub file:      Option<PathBuf>,
+  /// `{files}`: the selection, or the current entry.
+  pub files:     Vec<PathBuf>,
+  /// `{dir}`: the current directory.
+  pub dir:       PathBuf,
+  /// `{clipboard}`: the copied or cut items.
+  pub clipboard: Vec<PathBuf>,
+  /// Further names filled in verbatim, such as `width` for previewers.
+  pub extra:     Vec<(String, String)>,
+}
+
+/// The shell program: the configured one, or `sh` (`cmd` on Windows).
+pub fn program(shell: &CommandShell) -> &str
+{
+  shell.program.as_deref().unwrap_or(if cfg!(windows) { "cmd" } else { "sh" })
+}
+
+/// The shell running `cmd`, without positional arguments.
+pub fn command(
+  shell: &CommandShell,
+  cmd: &str,
+) -> Command
+{
+  with_args(shell, cmd, std::iter::empty::<&OsStr>())
+}
+
+/// The shell running `cmd` with `args` as its positional parameters (`$1`,
+/// `"$@"`); shells without them get the arguments appended to the command.
+pub fn with_args<I, S>(
+  shell: &CommandShell,
+  cmd: &str,
+  args: I,
+) -> Command
+where
+  I: IntoIterator<Item = S>,
+  S: AsRef<OsStr>,
+{
+  let program = program(shell);
+  let mut c = Command::new(program);
+  match flavor(program)
+  {
+    Flavor::Posix =>
+    {
+      if shell.login
+      {
+        c.arg("-l");
+      }
+      // `lsv` fills `$0` so the arguments start at `$1`
+      c.arg("-c").arg(cmd).arg("lsv");
+    }
+    Flavor::Cmd =>
+    {
+      c.arg("/C").arg(cmd);
+    }
+    Flavor::PowerShell =>
+    {
+      if !shell.login
+      {
+        c.arg("-NoProfile");
+      }
+      c.arg("-Command").arg(cmd);
+    }
+  }
+  c.args(args);
+  if let Some(names) = &shell.env
+  {
+    c.env_clear();
+    for name in names
+    {
+      if let Some(value) = std::env::var_os(name)
+      {
+        c.env(name, value);
+      }
+    }
+  }
+  c
+}
+
+/// Quote `s` as one word for the shell `shell` starts.
+pub fn quote(
+  shell: &CommandShell,
+  s: &str,
+) -> String
+{
+  match flavor(program(shell))
+  {
+    Flavor::Posix => format!("'{}'", s.replace('\'', "'\\''")),
+    Flavor::Cmd => format!("\"{}\"", s.replace('"', "\"\"")),
+    Flavor::PowerShell => format!("'{}'", s.replace('\'', "''")),
+  }
+}
+
+fn quote_all(
+  shell: &CommandShell,
+  paths: &[PathBuf],
+) -> String
+{
+  paths
+    .iter()
+    .map(|p| quote(shell, &p.to_string_lossy()))
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+/// The value of placeholder `name`, quoted; `None` for unknown names.
+fn placeholder(
+  shell: &CommandShell,
+  ph: &Placeholders,
+  name: &str,
+) -> Option<String>
+{
+  let file = ph.file.as_deref();
+  let part = |s: Option<&OsStr>| {
+    quote(shell, &s.map(|s| s.to_string_lossy()).unwrap_or_default())
+  };
+  let value = match name
+  {
+    "file" => part(file.map(Path::as_os_str)),
+    "files" => quote_all(shell, &ph.files),
+    "dir" => quote(shell, &ph.dir.to_string_lossy()),
+    "basename" => part(file.and_then(Path::file_name)),
+    "ext" => part(file.and_then(Path::extension)),
+    "clipboard" => quote_all(shell, &ph.clipboard),
+    _ =>
+    {
+      if let Some(var) = name.strip_prefix("env:")
+      {
+        let value = std::env::var_os(var).unwrap_or_default();
+        quote(shell, &value.to_string_lossy())
+      }
+      else
+      {
+        ph.extra.iter().find(|(k, _)| k == name)?.1.clone()
+      }
+    }
+  };
+  Some(value)
+}
+
+/// Fill in the placeholders of `cmd`. Braces that name no placeholder, as in
+/// `awk '{print $1}'`, are left alone, and filled-in values are not scanned
+/// again.
+pub fn expand(
+  shell: &CommandShell,
+  cmd: &str,
+  ph: &Placeholders,
+) -> String
+{
+  let mut out = String::with_capacity(cmd.len());
+  let mut rest = cmd;
+  while let Some(open) = rest.find('{')
+  {
+    out.push_str(&rest[..open]);
+    let after = &rest[open + 1..];
+    let value = after.find('}').and_then(|close| {
+      Some((placeholder(shell, ph, &after[..close])?, close))
+    });
+    match value
+    {
+      Some((value, close)) =>
+      {
+        out.push_str(&value);
+        rest = &after[close + 1..];
+      }
+      None =>
+      {
+        out.push('{');
+        rest = after;
+      }
+    }
+  }
+  out.push_str(rest);
+  out
+}
+
+/// `LSV_LEVEL` for a shell lsv starts, given lsv's own: one more.
+pub fn next_level(current: Option<&str>) -> u32
+{
+  current.and_then(|s| s.trim().parse::<u32>().ok()).unwrap_or(0) + 1
+}
@@ -0,0 +1,193 @@
+//! Thumbnails of videos, PDFs and vector or HEIF images for the preview pane.
+//!
+//! A thumbnailer command (by default `ffmpegthumbnailer` for videos,
+//! `pdftoppm` for PDFs, `rsvg-convert` for SVGs and `heif-convert` for
+//! HEIC/AVIF) renders the file to a PNG under the cache directory,
+//! named after the file's path, size and modification time so edits make a
+//! new one. A viewer command (by default `chafa`) then draws the PNG as text
+//! for the preview. Commands run through the `command_shell` with their
+//! arguments as positional parameters, as openers do: the thumbnailer gets the
+//! file and the PNG to write (`$1`, `$2`), the viewer gets the PNG, the preview
+//! width and height, and the image protocol (`$1` … `$4`).
+
+use std::{
+  path::{
+    Path,
+    PathBuf,
+  },
+  process::Stdio,
+};
+
+use crate::{
+  config::{
+    CommandShell,
+    UiThumbnails,
+  },
+  core::shell,
+};
+
+pub const DEFAULT_VIDEO_CMD: &str =
+  r#"ffmpegthumbnailer -i "$1" -o "$2" -s 512 -q 8"#;
+pub const DEFAULT_PDF_CMD: &str =
+  r#"pdftoppm -png -singlefile -f 1 -l 1 -scale-to 512 "$1" "${2%.png}""#;
+pub const DEFAULT_SVG_CMD: &str = r#"rsvg-convert -w 512 -o "$2" "$1""#;
+pub const DEFAULT_HEIF_CMD: &str =
+  r#"heif-convert "$1" "$2" || magick "$1[0]" "$2""#;
+pub const DEFAULT_VIEW_CMD: &str = r#"chafa -f symbols -s "${2}x${3}" "$1""#;
+
+pub(crate) const VIDEO_EXTENSIONS: &[&str] = &[
+  "mp4", "m4v", "mkv", "webm", "mov", "avi", "wmv", "flv", "mpg", "mpeg", "ogv",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThumbnailKind
+{
+  Video,
+  Pdf,
+  Svg,
+  Heif,
+}
+
+/// The configured thumbnailer for `kind`, if any.
+pub fn command(
+  cfg: &UiThumbnails,
+  kind: ThumbnailKind,
+) -> Option<&str>
+{
+  match kind
+  {
+    ThumbnailKind::Video => cfg.video.as_deref(),
+    ThumbnailKind::Pdf => cfg.pdf.as_deref(),
+    ThumbnailKind::Svg => cfg.svg.as_deref(),
+    ThumbnailKind::Heif => cfg.heif.as_deref(),
+  }
+}
+
+/// Which thumbnailer applies to `path`, if thumbnails are enabled for it.
+pub fn kind_for(
+  path: &Path,
+  cfg: &UiThumbnails,
+) -> Option<ThumbnailKind>
+{
+  if !cfg.enabled
+  {
+    return None;
+  }
+  let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+  let kind = match ext.as_str()
+  {
+    "pdf" => ThumbnailKind::Pdf,
+    "svg" | "svgz" => ThumbnailKind::Svg,
+    "heic" | "heif" | "avif" => ThumbnailKind::Heif,
+    e if VIDEO_EXTENSIONS.contains(&e) => ThumbnailKind::Video,
+    _ => return None,
+  };
+  command(cfg, kind).map(|_| kind)
+}
+
+/// Where thumbnails are kept: `$XDG_CACHE_HOME/lsv/thumbnails`, falling back
+/// to `~/.cache` (or `%LOCALAPPDATA%` on Windows).
+pub fn cache_dir() -> Option<PathBuf>
+{
+  let var = |k: &str| std::env::var_os(k).filter(|v| !v.is_empty());
+  let base = if cfg!(windows)
+  {
+    var("LOCALAPPDATA").map(PathBuf::from)
+  }
+  else
+  {
+    var("XDG_CACHE_HOME")
+      .map(PathBuf::from)
+      .or_else(|| var("HOME").map(|h| PathBuf::from(h).join(".cache")))
+  };
+  base.map(|b| b.join("lsv").join("thumbnails"))
+}
+
+/// FNV-1a, stable across runs and Rust versions unlike `DefaultHasher`.
+fn fnv1a(bytes: &[u8]) -> u64
+{
+  bytes.iter().fold(0xcbf2_9ce4_8422_2325, |h, b| {
+    (h ^ *b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+  })
+}
+
+/// Cached thumbnail for `path` in `dir`; changes when the file does.
+pub fn thumbnail_path(
+  dir: &Path,
+  path: &Path,
+) -> PathBuf
+{
+  let meta = std::fs::metadata(path).ok();
+  let mtime = meta
+    .as_ref()
+    .and_then(|m| m.modified().ok())
+    .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+    .map(|d| d.as_nanos())
+    .unwrap_or(0);
+  let size = meta.map(|m| m.len()).unwrap_or(0);
+  let id = format!("{}\0{}\0{}", path.display(), mtime, size);
+  dir.join(format!("{:016x}.png", fnv1a(id.as_bytes())))
+}
+
+/// Make the thumbnail for `path` with `cmd` unless it is cached already.
+/// Returns the PNG, or `None` when the thumbnailer failed.
+pub fn ensure_thumbnail(
+  sh: &CommandShell,
+  cmd: &str,
+  path: &Path,
+  dir: &Path,
+) -> Option<PathBuf>
+{
+  let out = thumbnail_path(dir, path);
+  if out.is_file()
+  {
+    return Some(out);
+  }
+  std::fs::create_dir_all(dir).ok()?;
+  let status = shell::with_args(sh, cmd, [path, out.as_path()])
+    .stdin(Stdio::null())
+    .stdout(Stdio::null())
+    .stderr(Stdio::null())
+    .status();
+  crate::trace::log(format!(
+    "[thumbnail] '{}' -> '{}' status={:?}",
+    path.display(),
+    out.display(),
+    status.as_ref().map(|s| s.code())
+  ));
+  (status.is_ok_and(|s| s.success()) && out.is_file()).then_some(out)
+}
+
+/// Draw `image` as text lines `width` x `height` cells with the viewer
+/// command. Returns `None` when the viewer failed.
+pub fn render(
+  sh: &CommandShell,
+  view_cmd: &str,
+  image: &Path,
+  width: u16,
+  height: u16,
+  protocol: &str,
+) -> Option<Vec<String>>
+{
+  let out = shell::with_args(
+    sh,
+    view_cmd,
+    [
+      image.as_os_str(),
+      width.to_string().as_ref(),
+      height.to_string().as_ref(),
+      protocol.as_ref(),
+    ],
+  )
+  .stdin(Stdio::null())
+  .output()
+  .ok()
+  .filter(|o| o.status.success())?;
+  Some(
+    String::from_utf8_lossy(&out.stdout)
+      .replace('\r', "")
+      .lines()
+      .map(String::from)
+      .collect(),
+  )
+}
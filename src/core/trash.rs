@@ -0,0 +1,483 @@
+//! Trash can support using each platform's own trash.
+//!
+//! - Linux/BSD: the freedesktop.org Trash specification under
+//!   `$XDG_DATA_HOME/Trash` (default `~/.local/share/Trash`). Trashed files
+//!   live under `<root>/files/<name>` with a matching
+//!   `<root>/info/<name>.trashinfo` recording the original path and deletion
+//!   date. Items on other volumes are copied into the home trash and then
+//!   removed.
+//! - macOS: Finder moves items to the Trash, so its Put Back works. lsv also
+//!   records the original path in `~/.Trash/.lsv-info` for the trash browser;
+//!   items Finder puts in another volume's trash are not listed there.
+//! - Windows: the Recycle Bin (see [`crate::core::recycle_bin`]).
+//!
+//! `LSV_TRASH_DIR` selects a freedesktop-style trash at that root instead, on
+//! all platforms.
+
+use std::{
+  env,
+  fs,
+  io::{
+    self,
+    Write,
+  },
+  path::{
+    Path,
+    PathBuf,
+  },
+};
+
+use crate::core::fs_ops::{
+  self,
+  CopyProgress,
+};
+
+/// How items get into the trash and back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrashBackend
+{
+  /// lsv moves items into `files` and records them in `info`.
+  Files,
+  /// Finder moves items into `files`; lsv records them in `info`.
+  #[cfg(target_os = "macos")]
+  Finder,
+  /// The Recycle Bin; `files` and `info` are unused.
+  #[cfg(windows)]
+  RecycleBin,
+}
+
+/// Directories holding trashed files and their metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashLocation
+{
+  pub files:   PathBuf,
+  pub info:    PathBuf,
+  pub backend: TrashBackend,
+}
+
+/// One entry in the trash can.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashedItem
+{
+  /// Name of the entry inside the trash `files` directory; on Windows, the
+  /// full path of the entry in the Recycle Bin.
+  pub id:            String,
+  /// Absolute path the item was deleted from.
+  pub original_path: PathBuf,
+  /// Deletion date as recorded (`YYYY-MM-DDThh:mm:ss`), if present.
+  pub deleted_at:    Option<String>,
+}
+
+impl TrashLocation
+{
+  /// Use `root/files` and `root/info`, as on freedesktop systems.
+  pub fn at(root: &Path) -> Self
+  {
+    TrashLocation {
+      files:   root.join("files"),
+      info:    root.join("info"),
+      backend: TrashBackend::Files,
+    }
+  }
+
+  /// Resolve the trash location for the current user and platform.
+  pub fn detect() -> Option<Self>
+  {
+    fn non_empty(var: &str) -> Option<PathBuf>
+    {
+      env::var(var).ok().filter(|s| !s.trim().is_empty()).map(PathBuf::from)
+    }
+
+    if let Some(root) = non_empty("LSV_TRASH_DIR")
+    {
+      return Some(Self::at(&root));
+    }
+    #[cfg(windows)]
+    {
+      Some(TrashLocation {
+        files:   PathBuf::new(),
+        info:    PathBuf::new(),
+        backend: TrashBackend::RecycleBin,
+      })
+    }
+    #[cfg(target_os = "macos")]
+    {
+      non_empty("HOME").map(|home| {
+        let files = home.join(".Trash");
+        let info = files.join(".lsv-info");
+        TrashLocation { files, info, backend: TrashBackend::Finder }
+      })
+    }
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+      non_empty("XDG_DATA_HOME")
+        .or_else(|| non_empty("HOME").map(|h| h.join(".local").join("share")))
+        .map(|data| Self::at(&data.join("Trash")))
+    }
+  }
+
+  fn info_path(
+    &self,
+    id: &str,
+  ) -> PathBuf
+  {
+    self.info.join(format!("{}.trashinfo", id))
+  }
+
+  /// The trashed item itself.
+  fn payload(
+    &self,
+    id: &str,
+  ) -> PathBuf
+  {
+    match self.backend
+    {
+      #[cfg(windows)]
+      TrashBackend::RecycleBin => PathBuf::from(id),
+      _ => self.files.join(id),
+    }
+  }
+
+  /// The file recording where the item came from.
+  fn record(
+    &self,
+    id: &str,
+  ) -> PathBuf
+  {
+    match self.backend
+    {
+      #[cfg(windows)]
+      TrashBackend::RecycleBin =>
+      {
+        crate::core::recycle_bin::record_of(Path::new(id))
+      }
+      _ => self.info_path(id),
+    }
+  }
+}
+
+fn info_body(
+  original: &Path,
+  deleted_at: &str,
+) -> String
+{
+  format!(
+    "[Trash Info]\nPath={}\nDeletionDate={}\n",
+    encode_path(original),
+    deleted_at
+  )
+}
+
+/// Move `path` into the trash, returning the recorded item. Copying an item
+/// from another volume is reported through `on_progress`; an error from it
+/// abandons the copy.
+pub fn trash_path(
+  loc: &TrashLocation,
+  path: &Path,
+  on_progress: &mut dyn FnMut(CopyProgress) -> io::Result<()>,
+) -> io::Result<TrashedItem>
+{
+  let original = std::path::absolute(path)?;
+  fs::symlink_metadata(&original)?;
+  match loc.backend
+  {
+    TrashBackend::Files => trash_into_files(loc, original, on_progress),
+    #[cfg(target_os = "macos")]
+    TrashBackend::Finder => trash_with_finder(loc, original),
+    #[cfg(windows)]
+    TrashBackend::RecycleBin => recycle(original),
+  }
+}
+
+fn trash_into_files(
+  loc: &TrashLocation,
+  original: PathBuf,
+  on_progress: &mut dyn FnMut(CopyProgress) -> io::Result<()>,
+) -> io::Result<TrashedItem>
+{
+  let base = original
+    .file_name()
+    .map(|s| s.to_string_lossy().to_string())
+    .ok_or_else(|| io::Error::other("cannot trash a path without a name"))?;
+  fs::create_dir_all(&loc.files)?;
+  fs::create_dir_all(&loc.info)?;
+
+  let deleted_at = chrono::Local::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+  let body = info_body(&original, &deleted_at);
+  // Claim a unique name by creating the info file first
+  let mut n = 1usize;
+  let (id, mut info_file) = loop
+  {
+    let id = if n == 1 { base.clone() } else { format!("{}.{}", base, n) };
+    n += 1;
+    if fs::symlink_metadata(loc.files.join(&id)).is_ok()
+    {
+      continue;
+    }
+    match fs::OpenOptions::new()
+      .write(true)
+      .create_new(true)
+      .open(loc.info_path(&id))
+    {
+      Ok(f) => break (id, f),
+      Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+      Err(e) => return Err(e),
+    }
+  };
+  let moved = info_file.write_all(body.as_bytes()).and_then(|_| {
+    move_into_trash(&original, &loc.files.join(&id), on_progress)
+  });
+  if let Err(e) = moved
+  {
+    let _ = fs::remove_file(loc.info_path(&id));
+    return Err(e);
+  }
+  Ok(TrashedItem { id, original_path: original, deleted_at: Some(deleted_at) })
+}
+
+/// Rename `src` to `dest`, or copy it over and remove it when they are on
+/// different volumes.
+fn move_into_trash(
+  src: &Path,
+  dest: &Path,
+  on_progress: &mut dyn FnMut(CopyProgress) -> io::Result<()>,
+) -> io::Result<()>
+{
+  if fs::rename(src, dest).is_ok()
+  {
+    return Ok(());
+  }
+  if let Err(e) = fs_ops::copy_path_with_progress(src, dest, on_progress)
+  {
+    // Leave no half-copied item in the trash
+    let _ = fs_ops::remove_path_all(dest);
+    return Err(e);
+  }
+  fs_ops::remove_path_all(src)
+}
+
+/// Have Finder trash `original`, as its Delete command does, then record
+/// the item for the trash browser.
+#[cfg(target_os = "macos")]
+fn trash_with_finder(
+  loc: &TrashLocation,
+  original: PathBuf,
+) -> io::Result<TrashedItem>
+{
+  const SCRIPT: [&str; 5] = [
+    "on run argv",
+    "set f to POSIX file (item 1 of argv)",
+    "tell application \"Finder\" to set t to delete f",
+    "return POSIX path of (t as alias)",
+    "end run",
+  ];
+  let mut cmd = std::process::Command::new("osascript");
+  for line in SCRIPT
+  {
+    cmd.arg("-e").arg(line);
+  }
+  let out = cmd
+    .arg(&original)
+    .stdin(std::process::Stdio::null())
+    .stderr(std::process::Stdio::piped())
+    .output()?;
+  if !out.status.success()
+  {
+    return Err(io::Error::other(format!(
+      "Finder could not trash it: {}",
+      String::from_utf8_lossy(&out.stderr).trim()
+    )));
+  }
+  let deleted_at = chrono::Local::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+  let stdout = String::from_utf8_lossy(&out.stdout);
+  let trashed = Path::new(stdout.trim().trim_end_matches('/'));
+  let id = trashed
+    .file_name()
+    .map(|s| s.to_string_lossy().to_string())
+    .ok_or_else(|| io::Error::other("Finder did not say where it went"))?;
+  // Items on other volumes land in that volume's trash instead
+  if trashed.parent() == Some(loc.files.as_path())
+  {
+    fs::create_dir_all(&loc.info)?;
+    // Finder picked a free name, so a record under it is left over from an
+    // item emptied from the Trash since
+    fs::write(loc.info_path(&id), info_body(&original, &deleted_at))?;
+  }
+  Ok(TrashedItem { id, original_path: original, deleted_at: Some(deleted_at) })
+}
+
+#[cfg(windows)]
+fn recycle(original: PathBuf) -> io::Result<TrashedItem>
+{
+  crate::core::recycle_bin::send(&original)?;
+  if fs::symlink_metadata(&original).is_ok()
+  {
+    return Err(io::Error::other("it was not moved to the Recycle Bin"));
+  }
+  let found = crate::core::recycle_bin::list()?
+    .into_iter()
+    .filter(|item| item.original_path == original)
+    .max_by(|a, b| a.deleted_at.cmp(&b.deleted_at));
+  // Not there when it could not be recycled and was deleted after asking
+  Ok(found.unwrap_or(TrashedItem {
+    id:            String::new(),
+    original_path: original,
+    deleted_at:    None,
+  }))
+}
+
+/// List trashed items, most recently deleted first. Info files whose payload
+/// is missing are skipped.
+pub fn list_items(loc: &TrashLocation) -> io::Result<Vec<TrashedItem>>
+{
+  let mut out = match loc.backend
+  {
+    #[cfg(windows)]
+    TrashBackend::RecycleBin => crate::core::recycle_bin::list()?,
+    _ => recorded_items(loc)?,
+  };
+  out.sort_by(|a, b| {
+    b.deleted_at.cmp(&a.deleted_at).then_with(|| a.id.cmp(&b.id))
+  });
+  Ok(out)
+}
+
+fn recorded_items(loc: &TrashLocation) -> io::Result<Vec<TrashedItem>>
+{
+  let rd = match fs::read_dir(&loc.info)
+  {
+    Ok(rd) => rd,
+    Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+    Err(e) => return Err(e),
+  };
+  let mut out = Vec::new();
+  for de in rd.flatten()
+  {
+    let name = de.file_name().to_string_lossy().to_string();
+    let Some(id) = name.strip_suffix(".trashinfo")
+    else
+    {
+      continue;
+    };
+    if fs::symlink_metadata(loc.files.join(id)).is_err()
+    {
+      continue;
+    }
+    if let Ok(text) = fs::read_to_string(de.path())
+      && let Some(item) = parse_info(id, &text)
+    {
+      out.push(item);
+    }
+  }
+  Ok(out)
+}
+
+/// Move a trashed item back to its original path. Fails if something already
+/// exists there.
+pub fn restore_item(
+  loc: &TrashLocation,
+  item: &TrashedItem,
+) -> io::Result<PathBuf>
+{
+  let dest = &item.original_path;
+  if fs::symlink_metadata(dest).is_ok()
+  {
+    return Err(io::Error::new(
+      io::ErrorKind::AlreadyExists,
+      format!("{} already exists", dest.display()),
+    ));
+  }
+  if let Some(parent) = dest.parent()
+  {
+    fs::create_dir_all(parent)?;
+  }
+  fs_ops::move_path_with_fallback(&loc.payload(&item.id), dest)?;
+  let _ = fs::remove_file(loc.record(&item.id));
+  Ok(dest.clone())
+}
+
+/// Permanently delete a trashed item.
+pub fn purge_item(
+  loc: &TrashLocation,
+  item: &TrashedItem,
+) -> io::Result<()>
+{
+  let payload = loc.payload(&item.id);
+  if payload.is_dir() && !payload.is_symlink()
+  {
+    fs::remove_dir_all(&payload)?;
+  }
+  else
+  {
+    fs::remove_file(&payload)?;
+  }
+  fs::remove_file(loc.record(&item.id))
+}
+
+fn parse_info(
+  id: &str,
+  text: &str,
+) -> Option<TrashedItem>
+{
+  let mut lines = text.lines().map(str::trim);
+  if lines.next() != Some("[Trash Info]")
+  {
+    return None;
+  }
+  let mut path = None;
+  let mut deleted_at = None;
+  for line in lines
+  {
+    if let Some(v) = line.strip_prefix("Path=")
+    {
+      path = Some(PathBuf::from(decode_path(v)));
+    }
+    else if let Some(v) = line.strip_prefix("DeletionDate=")
+    {
+      deleted_at = Some(v.to_string());
+    }
+  }
+  Some(TrashedItem { id: id.to_string(), original_path: path?, deleted_at })
+}
+
+// Percent-encode everything except unreserved characters and separators.
+fn encode_path(path: &Path) -> String
+{
+  let s = path.to_string_lossy();
+  let mut out = String::with_capacity(s.len());
+  for b in s.bytes()
+  {
+    if b.is_ascii_alphanumeric() || b"-_.~/".contains(&b)
+    {
+      out.push(b as char);
+    }
+    else
+    {
+      out.push_str(&format!("%{:02X}", b));
+    }
+  }
+  out
+}
+
+fn decode_path(s: &str) -> String
+{
+  let bytes = s.as_bytes();
+  let mut out = Vec::with_capacity(bytes.len());
+  let mut i = 0;
+  while i < bytes.len()
+  {
+    if bytes[i] == b'%'
+      && i + 2 < bytes.len()
+      && bytes[i + 1].is_ascii_hexdigit()
+      && bytes[i + 2].is_ascii_hexdigit()
+      && let Ok(b) = u8::from_str_radix(&s[i + 1..i + 3], 16)
+    {
+      out.push(b);
+      i += 3;
+      continue;
+    }
+    out.push(bytes[i]);
+    i += 1;
+  }
+  String::from_utf8_lossy(&out).to_string()
+}
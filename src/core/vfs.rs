@@ -0,0 +1,397 @@
+//! Virtual directories: read-only archives, remote hosts and the drive list.
+//!
+//! A path such as `/data/site.zip/assets/css` resolves to the archive
+//! `/data/site.zip` and the member prefix `assets/css`. Listings are derived
+//! from the archive's member list, which is cached for the most recently
+//! browsed archive so that moving around inside it stays cheap.
+//!
+//! `sftp://host/path` locations are handled by [`remote`]. `drives://` lists
+//! the mounted filesystems as directories; on Windows it is what lies above
+//! a drive root, so ascending from `C:\` offers the other drives and
+//! network shares. [`locate`] tells the kinds apart, and [`list`],
+//! [`parent`], [`rename`] and [`remove_all`] dispatch on it, so callers need
+//! not care where a directory lives.
+
+use std::{
+  collections::BTreeMap,
+  io,
+  path::{
+    Component,
+    Path,
+    PathBuf,
+  },
+  sync::{
+    Arc,
+    Mutex,
+    OnceLock,
+  },
+  time::SystemTime,
+};
+
+use chrono::TimeZone;
+
+use crate::core::{
+  archive::{
+    self,
+    ArchiveEntry,
+    ArchiveKind,
+  },
+  fs_ops,
+  mounts,
+  remote::{
+    self,
+    RemotePath,
+  },
+};
+
+/// The pseudo-directory listing every drive.
+pub const DRIVES: &str = "drives://";
+
+/// Members read from an archive for browsing.
+const MAX_ARCHIVE_MEMBERS: usize = 100_000;
+
+/// A location inside an archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchivePath
+{
+  pub archive: PathBuf,
+  pub kind:    ArchiveKind,
+  /// Member path relative to the archive root, `/`-separated; empty for the
+  /// root itself.
+  pub inner:   String,
+}
+
+/// Where a path lives.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Location
+{
+  Local,
+  Archive(ArchivePath),
+  Remote(RemotePath),
+  Drives,
+}
+
+type CachedMembers = (PathBuf, Option<SystemTime>, u64, Arc<Vec<ArchiveEntry>>);
+
+static MEMBER_CACHE: OnceLock<Mutex<Option<CachedMembers>>> = OnceLock::new();
+
+/// Whether `path` is the drive list.
+pub fn is_drives(path: &Path) -> bool
+{
+  path.to_str().is_some_and(|s| s.trim_end_matches('/') == "drives:")
+}
+
+/// Whether `path` is the root of a drive or network share (`C:\`,
+/// `\\server\share\`), whose parent is the drive list. Never true off
+/// Windows, where paths carry no prefix.
+pub fn is_drive_root(path: &Path) -> bool
+{
+  let mut comps = path.components();
+  matches!(comps.next(), Some(Component::Prefix(_)))
+    && matches!(comps.next(), Some(Component::RootDir))
+    && comps.next().is_none()
+}
+
+/// Name of the drive-list entry for `root`: the root itself, as in `C:\`.
+pub fn drive_name(root: &Path) -> String
+{
+  root.to_string_lossy().into_owned()
+}
+
+/// Whether `kind` can be browsed and extracted from.
+fn browsable(kind: ArchiveKind) -> bool
+{
+  !matches!(kind, ArchiveKind::SevenZip)
+}
+
+/// Whether `path` is an archive file that can be entered like a directory.
+pub fn is_browsable_archive(path: &Path) -> bool
+{
+  archive::detect(path).is_some_and(browsable) && path.is_file()
+}
+
+/// Resolve `path` to a location inside an archive. Real directories and
+/// files resolve to `None`, except browsable archive files themselves, which
+/// resolve to their root.
+pub fn resolve(path: &Path) -> Option<ArchivePath>
+{
+  if remote::is_remote(path) || is_drives(path)
+  {
+    return None;
+  }
+  for anc in path.ancestors()
+  {
+    let Ok(meta) = std::fs::metadata(anc)
+    else
+    {
+      // Not on disk: may be a member path below an archive
+      continue;
+    };
+    if !meta.is_file()
+    {
+      return None;
+    }
+    let kind = archive::detect(anc).filter(|k| browsable(*k))?;
+    let rel = path.strip_prefix(anc).ok()?;
+    let inner = rel
+      .components()
+      .map(|c| c.as_os_str().to_string_lossy())
+      .collect::<Vec<_>>()
+      .join("/");
+    return Some(ArchivePath { archive: anc.to_path_buf(), kind, inner });
+  }
+  None
+}
+
+/// Tell where `path` lives. Archive files themselves count as archives.
+pub fn locate(path: &Path) -> Location
+{
+  if is_drives(path)
+  {
+    Location::Drives
+  }
+  else if let Some(rp) = RemotePath::parse(path)
+  {
+    Location::Remote(rp)
+  }
+  else if let Some(ap) = resolve(path)
+  {
+    Location::Archive(ap)
+  }
+  else
+  {
+    Location::Local
+  }
+}
+
+/// List a virtual directory; `None` when `path` is an ordinary one.
+pub fn list(
+  path: &Path,
+  show_hidden: bool,
+) -> Option<io::Result<Vec<crate::app::DirEntryInfo>>>
+{
+  match locate(path)
+  {
+    Location::Local => None,
+    Location::Archive(ap) => Some(read_dir(&ap, show_hidden)),
+    Location::Remote(rp) => Some(remote::read_dir(&rp, show_hidden)),
+    Location::Drives => Some(Ok(read_drives())),
+  }
+}
+
+/// The mounted filesystems as directory entries named after their roots.
+fn read_drives() -> Vec<crate::app::DirEntryInfo>
+{
+  mounts::list()
+    .into_iter()
+    .map(|m| crate::app::DirEntryInfo {
+      name:   drive_name(&m.path),
+      path:   m.path,
+      is_dir: true,
+      size:   0,
+      mtime:  None,
+      ctime:  None,
+      meta:   None,
+    })
+    .collect()
+}
+
+/// The directory holding `path`. Remote locations stop at the host's root,
+/// drive roots lead to the drive list and the drive list has no parent.
+pub fn parent(path: &Path) -> Option<PathBuf>
+{
+  if remote::is_remote(path)
+  {
+    return RemotePath::parse(path)?.parent().map(|p| p.to_path());
+  }
+  if is_drives(path)
+  {
+    return None;
+  }
+  if is_drive_root(path)
+  {
+    return Some(PathBuf::from(DRIVES));
+  }
+  path.parent().map(Path::to_path_buf)
+}
+
+fn read_only() -> io::Error
+{
+  io::Error::new(io::ErrorKind::PermissionDenied, "archives are read-only")
+}
+
+fn drives_read_only() -> io::Error
+{
+  io::Error::new(io::ErrorKind::PermissionDenied, "the drive list is read-only")
+}
+
+/// Rename `from` to `to`, both in the same kind of location.
+pub fn rename(
+  from: &Path,
+  to: &Path,
+) -> io::Result<()>
+{
+  match (locate(from), locate(to))
+  {
+    (Location::Local, Location::Local) => std::fs::rename(from, to),
+    (Location::Remote(a), Location::Remote(b)) => remote::rename(&a, &b),
+    (Location::Archive(_), _) | (_, Location::Archive(_)) => Err(read_only()),
+    (Location::Drives, _) | (_, Location::Drives) => Err(drives_read_only()),
+    _ => Err(io::Error::new(
+      io::ErrorKind::Unsupported,
+      "cannot rename between local and remote locations",
+    )),
+  }
+}
+
+/// Remove `path` and everything below it.
+pub fn remove_all(path: &Path) -> io::Result<()>
+{
+  match locate(path)
+  {
+    Location::Remote(rp) => remote::remove_all(&rp),
+    Location::Archive(ap) if !ap.inner.is_empty() => Err(read_only()),
+    Location::Drives => Err(drives_read_only()),
+    _ => fs_ops::remove_path_all(path),
+  }
+}
+
+/// Whether `path` lies inside an archive (not the archive file itself).
+pub fn is_member(path: &Path) -> bool
+{
+  resolve(path).is_some_and(|ap| !ap.inner.is_empty())
+}
+
+fn members(ap: &ArchivePath) -> io::Result<Arc<Vec<ArchiveEntry>>>
+{
+  let meta = std::fs::metadata(&ap.archive)?;
+  let (mtime, len) = (meta.modified().ok(), meta.len());
+  let cache = MEMBER_CACHE.get_or_init(|| Mutex::new(None));
+  if let Ok(guard) = cache.lock()
+    && let Some((p, t, l, entries)) = guard.as_ref()
+    && *p == ap.archive
+    && *t == mtime
+    && *l == len
+  {
+    return Ok(entries.clone());
+  }
+  let listing = archive::list(&ap.archive, ap.kind, MAX_ARCHIVE_MEMBERS)?;
+  let entries = Arc::new(listing.entries);
+  if let Ok(mut guard) = cache.lock()
+  {
+    *guard = Some((ap.archive.clone(), mtime, len, entries.clone()));
+  }
+  Ok(entries)
+}
+
+/// Members at or below `ap.inner`, as `(path relative to inner, entry)`.
+fn members_below(
+  ap: &ArchivePath,
+  entries: &[ArchiveEntry],
+) -> Vec<(String, ArchiveEntry)>
+{
+  entries
+    .iter()
+    .filter_map(|e| {
+      let name = e.name.trim_start_matches("./").trim_end_matches('/');
+      let rel = if ap.inner.is_empty()
+      {
+        name
+      }
+      else if name == ap.inner
+      {
+        ""
+      }
+      else
+      {
+        name.strip_prefix(ap.inner.as_str())?.strip_prefix('/')?
+      };
+      Some((rel.to_string(), e.clone()))
+    })
+    .collect()
+}
+
+fn to_system_time(dt: Option<chrono::NaiveDateTime>) -> Option<SystemTime>
+{
+  let local = chrono::Local.from_local_datetime(&dt?).earliest()?;
+  Some(SystemTime::from(local))
+}
+
+/// List the immediate children of an archive directory. Directories that
+/// only exist implicitly (as prefixes of member paths) are included.
+pub fn read_dir(
+  ap: &ArchivePath,
+  show_hidden: bool,
+) -> io::Result<Vec<crate::app::DirEntryInfo>>
+{
+  let entries = members(ap)?;
+  let below = members_below(ap, &entries);
+  if below.is_empty() && !ap.inner.is_empty()
+  {
+    return Err(io::Error::new(
+      io::ErrorKind::NotFound,
+      format!("{}: no such archive directory", ap.inner),
+    ));
+  }
+  let base = ap.archive.join(&ap.inner);
+  let mut children: BTreeMap<String, crate::app::DirEntryInfo> =
+    BTreeMap::new();
+  for (rel, e) in below
+  {
+    if rel.is_empty()
+    {
+      continue;
+    }
+    let (first, nested) = match rel.split_once('/')
+    {
+      Some((first, _)) => (first, true),
+      None => (rel.as_str(), false),
+    };
+    if !show_hidden && first.starts_with('.')
+    {
+      continue;
+    }
+    let is_dir = nested || e.is_dir;
+    let child =
+      children.entry(first.to_string()).or_insert(crate::app::DirEntryInfo {
+        name: first.to_string(),
+        path: base.join(first),
+        is_dir,
+        size: 0,
+        mtime: None,
+        ctime: None,
+        meta: None,
+      });
+    child.is_dir |= is_dir;
+    if !nested
+    {
+      child.size = if child.is_dir { 0 } else { e.size };
+      child.mtime = to_system_time(e.modified);
+    }
+  }
+  Ok(children.into_values().collect())
+}
+
+/// Total uncompressed bytes and file count at or below `ap.inner`.
+pub fn totals(ap: &ArchivePath) -> (u64, u64)
+{
+  let Ok(entries) = members(ap)
+  else
+  {
+    return (0, 0);
+  };
+  members_below(ap, &entries)
+    .iter()
+    .filter(|(_, e)| !e.is_dir)
+    .fold((0, 0), |(bytes, files), (_, e)| (bytes + e.size, files + 1))
+}
+
+/// Look up a single member's size, for previews of archive members.
+pub fn member_size(ap: &ArchivePath) -> Option<u64>
+{
+  let entries = members(ap).ok()?;
+  entries
+    .iter()
+    .find(|e| e.name.trim_start_matches("./").trim_end_matches('/') == ap.inner)
+    .map(|e| e.size)
+}
@@ -0,0 +1,141 @@
+//! File owners on Windows.
+//!
+//! The owner's SID is read from the file's security descriptor with
+//! `GetNamedSecurityInfoW` and turned into an account name with
+//! `LookupAccountSidW`. Names are cached per path, since rows and the header
+//! ask on every redraw, until [`clear_cache`] is called.
+
+use std::{
+  collections::HashMap,
+  ffi::c_void,
+  os::windows::ffi::OsStrExt,
+  path::{
+    Path,
+    PathBuf,
+  },
+  ptr,
+  sync::{
+    OnceLock,
+    RwLock,
+  },
+};
+
+const SE_FILE_OBJECT: u32 = 1;
+const OWNER_SECURITY_INFORMATION: u32 = 0x1;
+const ERROR_SUCCESS: u32 = 0;
+/// Room for an account or domain name, in UTF-16 units.
+const NAME_LEN: usize = 256;
+
+#[link(name = "advapi32")]
+unsafe extern "system" {
+  fn GetNamedSecurityInfoW(
+    object_name: *const u16,
+    object_type: u32,
+    security_info: u32,
+    owner: *mut *mut c_void,
+    group: *mut *mut c_void,
+    dacl: *mut *mut c_void,
+    sacl: *mut *mut c_void,
+    descriptor: *mut *mut c_void,
+  ) -> u32;
+
+  fn LookupAccountSidW(
+    system_name: *const u16,
+    sid: *mut c_void,
+    name: *mut u16,
+    name_len: *mut u32,
+    domain: *mut u16,
+    domain_len: *mut u32,
+    sid_use: *mut u32,
+  ) -> i32;
+}
+
+#[link(name = "kernel32")]
+unsafe extern "system" {
+  fn LocalFree(mem: *mut c_void) -> *mut c_void;
+}
+
+static OWNER_CACHE: OnceLock<RwLock<HashMap<PathBuf, Option<String>>>> =
+  OnceLock::new();
+
+fn owner_cache() -> &'static RwLock<HashMap<PathBuf, Option<String>>>
+{
+  OWNER_CACHE.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Account name owning `path`, such as `alice` or `Administrators`.
+pub fn owner_name(path: &Path) -> Option<String>
+{
+  if let Ok(map) = owner_cache().read()
+    && let Some(v) = map.get(path)
+  {
+    return v.clone();
+  }
+  let found = lookup_owner(path);
+  if let Ok(mut map) = owner_cache().write()
+  {
+    map.insert(path.to_path_buf(), found.clone());
+  }
+  found
+}
+
+/// Forget the names looked up so far.
+pub fn clear_cache()
+{
+  if let Some(lock) = OWNER_CACHE.get()
+    && let Ok(mut m) = lock.write()
+  {
+    m.clear();
+  }
+}
+
+fn lookup_owner(path: &Path) -> Option<String>
+{
+  let wide: Vec<u16> =
+    path.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+  let mut owner: *mut c_void = ptr::null_mut();
+  let mut descriptor: *mut c_void = ptr::null_mut();
+  // SAFETY: `wide` is NUL-terminated and outlives the call; the out
+  // pointers are valid, and the unused ones are null as the API allows.
+  let status = unsafe {
+    GetNamedSecurityInfoW(
+      wide.as_ptr(),
+      SE_FILE_OBJECT,
+      OWNER_SECURITY_INFORMATION,
+      &mut owner,
+      ptr::null_mut(),
+      ptr::null_mut(),
+      ptr::null_mut(),
+      &mut descriptor,
+    )
+  };
+  if status != ERROR_SUCCESS
+  {
+    return None;
+  }
+  let mut name = [0u16; NAME_LEN];
+  let mut domain = [0u16; NAME_LEN];
+  let mut name_len = NAME_LEN as u32;
+  let mut domain_len = NAME_LEN as u32;
+  let mut sid_use = 0u32;
+  // SAFETY: `owner` points into `descriptor`, which is still allocated; the
+  // buffers hold the lengths passed with them.
+  let ok = !owner.is_null()
+    && unsafe {
+      LookupAccountSidW(
+        ptr::null(),
+        owner,
+        name.as_mut_ptr(),
+        &mut name_len,
+        domain.as_mut_ptr(),
+        &mut domain_len,
+        &mut sid_use,
+      )
+    } != 0;
+  // SAFETY: the descriptor was allocated by GetNamedSecurityInfoW for us to
+  // free, and the owner SID inside it is not used after this.
+  unsafe {
+    LocalFree(descriptor);
+  }
+  ok.then(|| String::from_utf16_lossy(&name[..name_len as usize]))
+}
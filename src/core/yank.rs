@@ -0,0 +1,120 @@
+//! Copying text to the system clipboard.
+//!
+//! The OSC 52 escape sequence asks the terminal itself to set the clipboard,
+//! which also works over SSH. Terminals that ignore it are covered by the
+//! platform's clipboard tool (`wl-copy`, `xclip`, `xsel`, `pbcopy` or
+//! `clip`), when one is available.
+
+use std::{
+  io::{
+    self,
+    Write,
+  },
+  process::{
+    Command,
+    Stdio,
+  },
+};
+
+const BASE64: &[u8; 64] =
+  b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Standard base64 with padding.
+pub fn base64_encode(data: &[u8]) -> String
+{
+  let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+  for chunk in data.chunks(3)
+  {
+    let b =
+      [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+    let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+    for i in 0..4
+    {
+      if i <= chunk.len()
+      {
+        out.push(BASE64[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+      }
+      else
+      {
+        out.push('=');
+      }
+    }
+  }
+  out
+}
+
+/// OSC 52 sequence setting the clipboard to `text`. Inside tmux the
+/// sequence is wrapped so tmux passes it on to the outer terminal.
+pub fn osc52_sequence(
+  text: &str,
+  tmux: bool,
+) -> String
+{
+  let seq = format!("\x1b]52;c;{}\x07", base64_encode(text.as_bytes()));
+  if tmux { format!("\x1bPtmux;\x1b{}\x1b\\", seq) } else { seq }
+}
+
+/// Write the OSC 52 sequence for `text` to the terminal.
+pub fn write_osc52(text: &str) -> io::Result<()>
+{
+  let tmux = std::env::var_os("TMUX").is_some();
+  let mut out = io::stdout();
+  out.write_all(osc52_sequence(text, tmux).as_bytes())?;
+  out.flush()
+}
+
+/// Clipboard tools to try, most specific first.
+fn clipboard_tools() -> Vec<(&'static str, &'static [&'static str])>
+{
+  if cfg!(windows)
+  {
+    return vec![("clip", &[])];
+  }
+  if cfg!(target_os = "macos")
+  {
+    return vec![("pbcopy", &[])];
+  }
+  let mut tools: Vec<(&'static str, &'static [&'static str])> = Vec::new();
+  if std::env::var_os("WAYLAND_DISPLAY").is_some()
+  {
+    tools.push(("wl-copy", &[]));
+  }
+  if std::env::var_os("DISPLAY").is_some()
+  {
+    tools.push(("xclip", &["-selection", "clipboard"]));
+    tools.push(("xsel", &["--clipboard", "--input"]));
+  }
+  tools
+}
+
+/// Copy `text` with the first clipboard tool that works. Returns the tool's
+/// name, or `None` when none is available.
+pub fn copy_with_tool(text: &str) -> Option<&'static str>
+{
+  for (program, args) in clipboard_tools()
+  {
+    // The tools may keep running to serve the selection, so don't hold on
+    // to their output
+    let child = Command::new(program)
+      .args(args)
+      .stdin(Stdio::piped())
+      .stdout(Stdio::null())
+      .stderr(Stdio::null())
+      .spawn();
+    let Ok(mut child) = child
+    else
+    {
+      continue;
+    };
+    let written = child
+      .stdin
+      .take()
+      .map(|mut stdin| stdin.write_all(text.as_bytes()).is_ok())
+      .unwrap_or(false);
+    if written && child.wait().is_ok_and(|s| s.success())
+    {
+      return Some(program);
+    }
+  }
+  None
+}
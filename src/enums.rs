@@ -8,7 +8,9 @@ pub(crate) fn sort_key_to_str(k: crate::actions::SortKey) -> &'static str
     crate::actions::SortKey::Name => "name",
     crate::actions::SortKey::Size => "size",
     crate::actions::SortKey::MTime => "mtime",
-    crate::actions::SortKey::CTime => "created",
+    crate::actions::SortKey::Created => "created",
+    crate::actions::SortKey::Natural => "natural",
+    crate::actions::SortKey::Extension => "extension",
   }
 }
 
@@ -23,7 +25,12 @@ pub(crate) fn sort_key_from_str(s: &str) -> Option<crate::actions::SortKey>
     {
       Some(crate::actions::SortKey::MTime)
     }
-    "created" | "ctime" | "birth" | "c" => Some(crate::actions::SortKey::CTime),
+    "created" | "ctime" | "birth" | "c" =>
+    {
+      Some(crate::actions::SortKey::Created)
+    }
+    "natural" | "version" | "v" => Some(crate::actions::SortKey::Natural),
+    "extension" | "ext" | "e" => Some(crate::actions::SortKey::Extension),
     _ => None,
   }
 }
@@ -1,4 +1,4 @@
-//! Input handling for keyboard events.
+//! Input handling for keyboard and mouse events.
 
 use crate::app::App;
 use std::io;
@@ -8,13 +8,20 @@ use crossterm::event::{
   KeyEvent,
   KeyEventKind,
   KeyModifiers,
+  MouseButton,
+  MouseEvent,
+  MouseEventKind,
 };
+use ratatui::layout::Position;
+
+/// Largest count accepted before a key, as in `5j`.
+const MAX_COUNT: usize = 9999;
 
 /// Accept a terminal key event and mutate the [`App`] accordingly.
 ///
-/// Returns `Ok(true)` when the caller should exit. Multi-key sequences are
-/// resolved via the keymap; unrecognised keys fall back to built-in
-/// navigation behaviour.
+/// Returns `Ok(true)` when the caller should exit. Keys, navigation
+/// included, are resolved via the keymap; only `:`, `?` and `Esc` have
+/// built-in meanings.
 pub fn handle_key(
   app: &mut App,
   key: KeyEvent,
@@ -61,6 +68,262 @@ pub fn handle_key(
     return Ok(false);
   }
 
+  if let crate::app::Overlay::Trash(_) = app.overlay
+  {
+    match key.code
+    {
+      KeyCode::Esc | KeyCode::Char('q') =>
+      {
+        app.overlay = crate::app::Overlay::None;
+        app.force_full_redraw = true;
+      }
+      KeyCode::Up | KeyCode::Char('k') => app.trash_browser_move(-1),
+      KeyCode::Down | KeyCode::Char('j') => app.trash_browser_move(1),
+      KeyCode::PageUp => app.trash_browser_move(-5),
+      KeyCode::PageDown => app.trash_browser_move(5),
+      KeyCode::Enter | KeyCode::Char('r') => app.trash_browser_apply(false),
+      KeyCode::Delete | KeyCode::Char('D') => app.trash_browser_apply(true),
+      _ =>
+      {}
+    }
+    return Ok(false);
+  }
+
+  if let crate::app::Overlay::Mounts(_) = app.overlay
+  {
+    match key.code
+    {
+      KeyCode::Esc | KeyCode::Char('q') =>
+      {
+        app.overlay = crate::app::Overlay::None;
+        app.force_full_redraw = true;
+      }
+      KeyCode::Up | KeyCode::Char('k') => app.mounts_move(-1),
+      KeyCode::Down | KeyCode::Char('j') => app.mounts_move(1),
+      KeyCode::PageUp => app.mounts_move(-5),
+      KeyCode::PageDown => app.mounts_move(5),
+      KeyCode::Enter => app.mounts_accept(),
+      _ =>
+      {}
+    }
+    return Ok(false);
+  }
+
+  if let crate::app::Overlay::Finder(_) = app.overlay
+  {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code
+    {
+      KeyCode::Esc => app.close_finder(),
+      KeyCode::Enter => app.finder_accept(),
+      KeyCode::Up => app.finder_move(-1),
+      KeyCode::Down => app.finder_move(1),
+      KeyCode::PageUp => app.finder_move(-10),
+      KeyCode::PageDown => app.finder_move(10),
+      KeyCode::Char('p') | KeyCode::Char('k') if ctrl => app.finder_move(-1),
+      KeyCode::Char('n') | KeyCode::Char('j') if ctrl => app.finder_move(1),
+      KeyCode::Char('u') if ctrl => app.set_finder_query(""),
+      KeyCode::Backspace => app.finder_edit(None),
+      KeyCode::Char(ch)
+        if !ctrl
+          && !key.modifiers.contains(KeyModifiers::ALT)
+          && !key.modifiers.contains(KeyModifiers::SUPER) =>
+      {
+        app.finder_edit(Some(ch))
+      }
+      _ =>
+      {}
+    }
+    return Ok(false);
+  }
+
+  if let crate::app::Overlay::Palette(_) = app.overlay
+  {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code
+    {
+      KeyCode::Esc => app.close_palette(),
+      KeyCode::Enter => app.palette_accept(),
+      KeyCode::Up => app.palette_move(-1),
+      KeyCode::Down => app.palette_move(1),
+      KeyCode::PageUp => app.palette_move(-10),
+      KeyCode::PageDown => app.palette_move(10),
+      KeyCode::Char('p') | KeyCode::Char('k') if ctrl => app.palette_move(-1),
+      KeyCode::Char('n') | KeyCode::Char('j') if ctrl => app.palette_move(1),
+      KeyCode::Char('u') if ctrl => app.set_palette_query(""),
+      KeyCode::Backspace => app.palette_edit(None),
+      KeyCode::Char(ch)
+        if !ctrl
+          && !key.modifiers.contains(KeyModifiers::ALT)
+          && !key.modifiers.contains(KeyModifiers::SUPER) =>
+      {
+        app.palette_edit(Some(ch))
+      }
+      _ =>
+      {}
+    }
+    return Ok(false);
+  }
+
+  if let crate::app::Overlay::Select(_) = app.overlay
+  {
+    use crate::app::lua_ui::DialogAnswer;
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code
+    {
+      KeyCode::Esc => app.answer_dialog(DialogAnswer::Choice(None)),
+      KeyCode::Enter => app.select_accept(),
+      KeyCode::Up => app.select_move(-1),
+      KeyCode::Down => app.select_move(1),
+      KeyCode::PageUp => app.select_move(-10),
+      KeyCode::PageDown => app.select_move(10),
+      KeyCode::Char('p') | KeyCode::Char('k') if ctrl => app.select_move(-1),
+      KeyCode::Char('n') | KeyCode::Char('j') if ctrl => app.select_move(1),
+      KeyCode::Backspace => app.select_edit(None),
+      KeyCode::Char(ch)
+        if !ctrl
+          && !key.modifiers.contains(KeyModifiers::ALT)
+          && !key.modifiers.contains(KeyModifiers::SUPER) =>
+      {
+        app.select_edit(Some(ch))
+      }
+      _ =>
+      {}
+    }
+    return Ok(false);
+  }
+
+  if let crate::app::Overlay::Help(_) = app.overlay
+  {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code
+    {
+      KeyCode::Esc | KeyCode::Char('q') | KeyCode::F(1) =>
+      {
+        app.overlay = crate::app::Overlay::None;
+        app.force_full_redraw = true;
+      }
+      KeyCode::Char('d') if ctrl => app.help_scroll_page(1),
+      KeyCode::Char('u') if ctrl => app.help_scroll_page(-1),
+      KeyCode::Up | KeyCode::Char('k') => app.help_scroll(-1),
+      KeyCode::Down | KeyCode::Char('j') => app.help_scroll(1),
+      KeyCode::PageUp => app.help_scroll_page(-1),
+      KeyCode::PageDown | KeyCode::Char(' ') => app.help_scroll_page(1),
+      KeyCode::Home | KeyCode::Char('g') => app.help_scroll(isize::MIN / 2),
+      KeyCode::End | KeyCode::Char('G') => app.help_scroll(isize::MAX / 2),
+      _ =>
+      {}
+    }
+    return Ok(false);
+  }
+
+  if let crate::app::Overlay::Grep(_) = app.overlay
+  {
+    match key.code
+    {
+      KeyCode::Esc => app.close_grep(),
+      KeyCode::Enter => app.grep_jump(),
+      KeyCode::Char('o') => app.grep_open_at_line(),
+      KeyCode::Up | KeyCode::Char('k') => app.grep_move(-1),
+      KeyCode::Down | KeyCode::Char('j') => app.grep_move(1),
+      KeyCode::PageUp => app.grep_move(-10),
+      KeyCode::PageDown => app.grep_move(10),
+      KeyCode::Home | KeyCode::Char('g') => app.grep_move(isize::MIN / 2),
+      KeyCode::End | KeyCode::Char('G') => app.grep_move(isize::MAX / 2),
+      _ =>
+      {}
+    }
+    return Ok(false);
+  }
+
+  if let crate::app::Overlay::Bookmarks { .. } = app.overlay
+  {
+    match key.code
+    {
+      KeyCode::Esc | KeyCode::Char('q') =>
+      {
+        app.overlay = crate::app::Overlay::None;
+        app.force_full_redraw = true;
+      }
+      KeyCode::Up | KeyCode::Char('k') => app.bookmarks_move(-1),
+      KeyCode::Down | KeyCode::Char('j') => app.bookmarks_move(1),
+      KeyCode::PageUp => app.bookmarks_move(-5),
+      KeyCode::PageDown => app.bookmarks_move(5),
+      KeyCode::Enter | KeyCode::Char('l') => app.bookmarks_jump(),
+      KeyCode::Char('a') => app.bookmarks_add_prompt(),
+      KeyCode::Char('r') => app.bookmarks_rename_prompt(),
+      KeyCode::Char('d') | KeyCode::Delete => app.bookmarks_delete(),
+      _ =>
+      {}
+    }
+    return Ok(false);
+  }
+
+  if let crate::app::Overlay::Registers { .. } = app.overlay
+  {
+    match key.code
+    {
+      KeyCode::Esc | KeyCode::Char('q') =>
+      {
+        app.overlay = crate::app::Overlay::None;
+        app.force_full_redraw = true;
+      }
+      KeyCode::Up | KeyCode::Char('k') => app.registers_move(-1),
+      KeyCode::Down | KeyCode::Char('j') => app.registers_move(1),
+      KeyCode::Enter | KeyCode::Char('v') => app.registers_paste(),
+      KeyCode::Char('d') | KeyCode::Delete => app.registers_clear(),
+      _ =>
+      {}
+    }
+    return Ok(false);
+  }
+
+  if let crate::app::Overlay::Selections { .. } = app.overlay
+  {
+    match key.code
+    {
+      KeyCode::Esc | KeyCode::Char('q') =>
+      {
+        app.overlay = crate::app::Overlay::None;
+        app.force_full_redraw = true;
+      }
+      KeyCode::Up | KeyCode::Char('k') => app.selections_move(-1),
+      KeyCode::Down | KeyCode::Char('j') => app.selections_move(1),
+      KeyCode::PageUp => app.selections_move(-5),
+      KeyCode::PageDown => app.selections_move(5),
+      KeyCode::Enter | KeyCode::Char('l') => app.selections_jump(),
+      KeyCode::Char('d') | KeyCode::Char(' ') | KeyCode::Delete =>
+      {
+        app.selections_unselect()
+      }
+      KeyCode::Char('c') => app.selections_clear(),
+      _ =>
+      {}
+    }
+    return Ok(false);
+  }
+
+  if let crate::app::Overlay::Jobs { selected } = app.overlay
+  {
+    match key.code
+    {
+      KeyCode::Esc | KeyCode::Char('q') =>
+      {
+        app.overlay = crate::app::Overlay::None;
+        app.force_full_redraw = true;
+      }
+      KeyCode::Up | KeyCode::Char('k') => app.jobs_overlay_move(-1),
+      KeyCode::Down | KeyCode::Char('j') => app.jobs_overlay_move(1),
+      KeyCode::Char('c') | KeyCode::Char('x') | KeyCode::Delete =>
+      {
+        app.cancel_job(selected)
+      }
+      _ =>
+      {}
+    }
+    return Ok(false);
+  }
+
   // Prompt overlay input handling
   if let crate::app::Overlay::Prompt(ref mut st_box) = app.overlay
   {
@@ -74,8 +337,38 @@ pub fn handle_key(
     {
       KeyCode::Esc =>
       {
+        if let crate::app::PromptKind::Lua = st.kind
+        {
+          app.answer_dialog(crate::app::lua_ui::DialogAnswer::Text(None));
+          return Ok(false);
+        }
+        // Mark prompts go back to the bookmarks overlay they came from
+        let back_to = match st.kind
+        {
+          crate::app::PromptKind::AddMark => Some(None),
+          crate::app::PromptKind::RenameMark { ref from } =>
+          {
+            Some(Some(from.clone()))
+          }
+          _ => None,
+        };
         app.overlay = crate::app::Overlay::None;
         app.force_full_redraw = true;
+        if let Some(selected) = back_to
+        {
+          app.open_bookmarks(selected.as_deref());
+        }
+      }
+      // Hand a multi-item rename over to the external editor
+      KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) =>
+      {
+        if let crate::app::PromptKind::RenameMany { ref items, .. } = st.kind
+        {
+          let mut items = items.clone();
+          items.sort();
+          app.overlay = crate::app::Overlay::None;
+          app.rename_in_editor(items);
+        }
       }
       KeyCode::Enter =>
       {
@@ -108,18 +401,67 @@ pub fn handle_key(
             if !new_name.is_empty()
             {
               let dest = app.cwd.join(new_name);
-              if std::fs::rename(from, &dest).is_ok()
+              match crate::core::vfs::rename(from, &dest)
               {
-                // Keep item selected after rename (update selection to new
-                // path)
-                if app.selected.remove(from)
+                Ok(()) =>
                 {
-                  app.selected.insert(dest.clone());
+                  // Keep item selected after rename (update selection to new
+                  // path)
+                  if app.selected.remove(from)
+                  {
+                    app.selected.insert(dest.clone());
+                  }
                 }
+                Err(e) => app.add_error(&format!("Rename: {}", e)),
               }
               app.refresh_lists();
             }
           }
+          crate::app::PromptKind::AddMark =>
+          {
+            let name = st.input.trim().to_string();
+            if !name.is_empty()
+            {
+              app.add_mark(&name);
+            }
+            app.open_bookmarks(Some(&name));
+            return Ok(false);
+          }
+          crate::app::PromptKind::RenameMark { ref from } =>
+          {
+            let (from, to) = (from.clone(), st.input.trim().to_string());
+            if !to.is_empty() && to != from
+            {
+              app.rename_mark(&from, &to);
+            }
+            let selected = if app.marks.contains_key(&to) { to } else { from };
+            app.open_bookmarks(Some(&selected));
+            return Ok(false);
+          }
+          crate::app::PromptKind::SetPermissions { ref items } =>
+          {
+            let (items, request) = (items.clone(), st.input.clone());
+            app.overlay = crate::app::Overlay::None;
+            app.force_full_redraw = true;
+            app.apply_permissions(&items, &request);
+            return Ok(false);
+          }
+          crate::app::PromptKind::Compress { ref items } =>
+          {
+            let (items, name) = (items.clone(), st.input.clone());
+            app.overlay = crate::app::Overlay::None;
+            app.force_full_redraw = true;
+            app.compress_items(items, &name);
+            return Ok(false);
+          }
+          crate::app::PromptKind::Lua =>
+          {
+            let text = st.input.clone();
+            app.answer_dialog(crate::app::lua_ui::DialogAnswer::Text(Some(
+              text,
+            )));
+            return Ok(false);
+          }
           crate::app::PromptKind::RenameMany {
             ref items,
             ref pre,
@@ -146,7 +488,8 @@ pub fn handle_key(
                     .unwrap_or(name);
                   let new_name = format!("{}{}{}", new_pre, var, new_suf);
                   let dst = app.cwd.join(new_name);
-                  if std::fs::rename(p, &dst).is_ok() && app.selected.remove(p)
+                  if crate::core::vfs::rename(p, &dst).is_ok()
+                    && app.selected.remove(p)
                   {
                     app.selected.insert(dst.clone());
                   }
@@ -200,16 +543,14 @@ pub fn handle_key(
         st.cursor = st.input.len();
         app.force_full_redraw = true;
       }
-      KeyCode::Char(ch) =>
-      {
+      KeyCode::Char(ch)
         if !key.modifiers.contains(KeyModifiers::CONTROL)
           && !key.modifiers.contains(KeyModifiers::ALT)
-          && !key.modifiers.contains(KeyModifiers::SUPER)
-        {
-          st.input.insert(st.cursor, ch);
-          st.cursor += ch.len_utf8();
-          app.force_full_redraw = true;
-        }
+          && !key.modifiers.contains(KeyModifiers::SUPER) =>
+      {
+        st.input.insert(st.cursor, ch);
+        st.cursor += ch.len_utf8();
+        app.force_full_redraw = true;
       }
       _ =>
       {}
@@ -217,6 +558,40 @@ pub fn handle_key(
     return Ok(false);
   }
 
+  // Keys while searching the command history with Ctrl-r
+  if let crate::app::Overlay::CommandPane(ref st) = app.overlay
+    && st.history.search.is_some()
+  {
+    let plain = !key.modifiers.intersects(
+      KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SUPER,
+    );
+    match key.code
+    {
+      KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) =>
+      {
+        app.history_search_next();
+        return Ok(false);
+      }
+      KeyCode::Char(ch) if plain =>
+      {
+        app.history_search_edit(Some(ch));
+        return Ok(false);
+      }
+      KeyCode::Backspace =>
+      {
+        app.history_search_edit(None);
+        return Ok(false);
+      }
+      KeyCode::Esc =>
+      {
+        app.history_search_end(false);
+        return Ok(false);
+      }
+      // Any other key takes the match and acts on it as usual
+      _ => app.history_search_end(true),
+    }
+  }
+
   // Command pane (search input)
   if let crate::app::Overlay::CommandPane(ref mut st_box) = app.overlay
   {
@@ -233,19 +608,34 @@ pub fn handle_key(
         if st.prompt == ":"
         {
           // Attempt completion against known commands.
-          let prefix = st.input.trim();
+          let prefix = st.input.trim_start();
           let mut matches: Vec<String> = Vec::new();
           if !prefix.is_empty()
           {
-            for c in crate::commands::all().iter()
+            for c in crate::commands::with_lua(&app.config.commands)
             {
               if c.starts_with(prefix)
               {
-                matches.push((*c).to_string());
+                matches.push(c);
               }
             }
           }
-          if matches.len() == 1
+          st.candidates.clear();
+          // Past the command name, complete the last word as a path
+          if matches.is_empty() && st.input.trim_start().contains(' ')
+          {
+            let split = st.input.rfind(' ').map_or(0, |i| i + 1);
+            let done = crate::core::complete::complete_path(
+              &st.input[split..],
+              &app.cwd,
+              app.config.ui.show_hidden,
+            );
+            st.input.truncate(split);
+            st.input.push_str(&done.completed);
+            st.cursor = st.input.len();
+            st.candidates = done.candidates;
+          }
+          else if matches.len() == 1
           {
             st.input = matches[0].clone();
             st.cursor = st.input.len();
@@ -271,6 +661,7 @@ pub fn handle_key(
           let pat = st.input.trim().to_string();
           if !pat.is_empty()
           {
+            app.record_history("/", &pat);
             app.search_query = Some(pat);
           }
           app.overlay = crate::app::Overlay::None;
@@ -282,6 +673,8 @@ pub fn handle_key(
           // execute_command_line to set a new overlay (e.g., Output)
           // without being overwritten.
           app.overlay = crate::app::Overlay::None;
+          app.record_history(":", &line);
+          app.record_macro_step(&format!("cmd:{}", line), None);
           app.execute_command_line(&line);
         }
         else
@@ -295,6 +688,8 @@ pub fn handle_key(
         {
           st.input.remove(st.cursor - 1);
           st.cursor -= 1;
+          st.history.index = None;
+          st.candidates.clear();
           if st.prompt == "/"
           {
             live_update = Some(st.input.clone());
@@ -319,6 +714,12 @@ pub fn handle_key(
         }
       }
       // (duplicate Tab arm removed; handled earlier)
+      KeyCode::Up => app.history_step(true),
+      KeyCode::Down => app.history_step(false),
+      KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) =>
+      {
+        app.history_search_next();
+      }
       KeyCode::Home =>
       {
         st.cursor = 0;
@@ -329,20 +730,20 @@ pub fn handle_key(
         st.cursor = st.input.len();
         app.force_full_redraw = true;
       }
-      KeyCode::Char(ch) =>
-      {
+      KeyCode::Char(ch)
         if !key.modifiers.contains(KeyModifiers::CONTROL)
           && !key.modifiers.contains(KeyModifiers::ALT)
-          && !key.modifiers.contains(KeyModifiers::SUPER)
+          && !key.modifiers.contains(KeyModifiers::SUPER) =>
+      {
+        st.input.insert(st.cursor, ch);
+        st.cursor += ch.len_utf8();
+        st.history.index = None;
+        st.candidates.clear();
+        if st.prompt == "/"
         {
-          st.input.insert(st.cursor, ch);
-          st.cursor += ch.len_utf8();
-          if st.prompt == "/"
-          {
-            live_update = Some(st.input.clone());
-          }
-          app.force_full_redraw = true;
+          live_update = Some(st.input.clone());
         }
+        app.force_full_redraw = true;
       }
       _ =>
       {}
@@ -354,6 +755,17 @@ pub fn handle_key(
     return Ok(false);
   }
 
+  // The messages and output overlays take their scroll and search keys;
+  // the rest go on to the keymap
+  if matches!(
+    app.overlay,
+    crate::app::Overlay::Messages | crate::app::Overlay::Output { .. }
+  ) && app.keys.pending.is_empty()
+    && handle_text_view_key(app, key)
+  {
+    return Ok(false);
+  }
+
   // Open command pane with ':'
   if let KeyCode::Char(':') = key.code
   {
@@ -369,7 +781,7 @@ pub fn handle_key(
       KeyCode::Char(ch) =>
       {
         app.pending_mark = false;
-        app.add_mark(ch);
+        app.add_mark(&ch.to_string());
       }
       KeyCode::Esc =>
       {
@@ -387,7 +799,7 @@ pub fn handle_key(
       KeyCode::Char(ch) =>
       {
         app.pending_goto = false;
-        app.goto_mark(ch);
+        app.goto_mark(&ch.to_string());
       }
       KeyCode::Esc =>
       {
@@ -399,6 +811,25 @@ pub fn handle_key(
     return Ok(false);
   }
 
+  if app.registers.pending
+  {
+    match key.code
+    {
+      KeyCode::Char(ch) => app.register_chosen(ch),
+      _ => app.registers.pending = false,
+    }
+    return Ok(false);
+  }
+
+  if let Some(prompt) = app.macros.pending.take()
+  {
+    if let KeyCode::Char(ch) = key.code
+    {
+      app.macro_register_key(prompt, ch);
+    }
+    return Ok(false);
+  }
+
   // Confirm overlay input handling (y/n)
   if let crate::app::Overlay::Confirm(ref mut st_box) = app.overlay
   {
@@ -407,6 +838,46 @@ pub fn handle_key(
     {
       return Ok(false);
     }
+    if let crate::app::ConfirmKind::PasteConflict(_) = st_box.kind
+    {
+      use crate::app::paste::ConflictChoice;
+      let choice = match key.code
+      {
+        KeyCode::Char('o') => Some(ConflictChoice::Overwrite),
+        KeyCode::Char('s') | KeyCode::Char('n') => Some(ConflictChoice::Skip),
+        KeyCode::Char('r') => Some(ConflictChoice::Rename),
+        KeyCode::Char('O') => Some(ConflictChoice::OverwriteAll),
+        KeyCode::Char('S') => Some(ConflictChoice::SkipAll),
+        KeyCode::Esc => Some(ConflictChoice::Cancel),
+        _ => None,
+      };
+      if let Some(choice) = choice
+      {
+        app.resolve_paste_conflict(choice);
+      }
+      return Ok(false);
+    }
+    // Scroll the list of paths; other keys answer
+    let scroll = match key.code
+    {
+      KeyCode::Down | KeyCode::Char('j') => Some((1, false)),
+      KeyCode::Up | KeyCode::Char('k') => Some((-1, false)),
+      KeyCode::PageDown => Some((1, true)),
+      KeyCode::PageUp => Some((-1, true)),
+      _ => None,
+    };
+    if let Some((delta, page)) = scroll
+    {
+      if page
+      {
+        app.confirm_scroll_page(delta);
+      }
+      else
+      {
+        app.confirm_scroll(delta);
+      }
+      return Ok(false);
+    }
     let st = st_box.as_ref();
     enum Act
     {
@@ -443,19 +914,108 @@ pub fn handle_key(
     }
     // Drop borrow before mutating app
     let kind = st.kind.clone();
+    if let crate::app::ConfirmKind::Lua = kind
+    {
+      let yes = matches!(act, Act::DeleteAll);
+      app.answer_dialog(crate::app::lua_ui::DialogAnswer::Yes(yes));
+      return Ok(false);
+    }
     app.overlay = crate::app::Overlay::None;
     app.force_full_redraw = true;
-    if let (Act::DeleteAll, crate::app::ConfirmKind::DeleteSelected(list)) =
-      (act, &kind)
+    if let Act::DeleteAll = act
     {
-      for p in list.iter()
+      match kind
       {
-        app.perform_delete_path(p);
+        crate::app::ConfirmKind::DeleteSelected(list) =>
+        {
+          app.perform_delete_paths(&list);
+        }
+        crate::app::ConfirmKind::TrashSelected(list) =>
+        {
+          app.perform_trash_paths(&list);
+        }
+        crate::app::ConfirmKind::PasteOverwrite(paste) =>
+        {
+          app.queue_paste(*paste);
+        }
+        crate::app::ConfirmKind::RenameBatch(plan) =>
+        {
+          app.apply_rename_plan(&plan);
+        }
+        // Answered above
+        crate::app::ConfirmKind::PasteConflict(_)
+        | crate::app::ConfirmKind::Lua =>
+        {}
       }
     }
     return Ok(false);
   }
 
+  // Preview focus: scroll keys move the preview, other keys act as usual
+  if app.preview.focused
+    && matches!(app.overlay, crate::app::Overlay::None)
+    && app.keys.pending.is_empty()
+    && !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+  {
+    // A typed count repeats the scroll
+    let n = app.pending_count.unwrap_or(1).max(1) as isize;
+    let step = crate::actions::internal::PREVIEW_HSCROLL_STEP * n;
+    let handled = match key.code
+    {
+      KeyCode::Char('h') | KeyCode::Left =>
+      {
+        app.preview_scroll_x(-step);
+        true
+      }
+      KeyCode::Char('l') | KeyCode::Right =>
+      {
+        app.preview_scroll_x(step);
+        true
+      }
+      KeyCode::Char('0') if app.pending_count.is_none() =>
+      {
+        app.preview_scroll_x(isize::MIN / 2);
+        true
+      }
+      KeyCode::Char('j') | KeyCode::Down =>
+      {
+        app.preview_scroll(n);
+        true
+      }
+      KeyCode::Char('k') | KeyCode::Up =>
+      {
+        app.preview_scroll(-n);
+        true
+      }
+      KeyCode::PageDown =>
+      {
+        app.preview_scroll_page(n);
+        true
+      }
+      KeyCode::PageUp =>
+      {
+        app.preview_scroll_page(-n);
+        true
+      }
+      KeyCode::Char('w') =>
+      {
+        app.toggle_preview_wrap();
+        true
+      }
+      KeyCode::Esc | KeyCode::Char('q') =>
+      {
+        app.toggle_preview_focus();
+        true
+      }
+      _ => false,
+    };
+    if handled
+    {
+      app.pending_count = None;
+      return Ok(false);
+    }
+  }
+
   // First, try dynamic key mappings with simple sequence support
   // Quick toggle of which-key help
   if let KeyCode::Char('?') = key.code
@@ -468,7 +1028,29 @@ pub fn handle_key(
     return Ok(false);
   }
 
-  if let KeyCode::Char(ch) = key.code
+  // A count before a motion: `5j` moves down five rows, `12G` goes to row 12.
+  // Digits bound in the keymap keep their mapping.
+  if let KeyCode::Char(ch @ '0'..='9') = key.code
+    && key.modifiers == KeyModifiers::NONE
+    && app.keys.pending.is_empty()
+    && (ch != '0' || app.pending_count.is_some())
+    && !app.keys.lookup.contains_key(ch.to_string().as_str())
+  {
+    let digit = ch as usize - '0' as usize;
+    let count = app.pending_count.unwrap_or(0) * 10 + digit;
+    app.pending_count = Some(count.min(MAX_COUNT));
+    return Ok(false);
+  }
+
+  // Named keys such as <F5> or <C-Up> only go through the keymap when bound
+  // or ending a sequence; otherwise they keep their built-in behaviour
+  let token = crate::keymap::key_token(key.code, key.modifiers).filter(|tok| {
+    matches!(key.code, KeyCode::Char(_))
+      || !app.keys.pending.is_empty()
+      || app.keys.lookup.contains_key(tok.as_str())
+      || app.keys.prefixes.contains(tok)
+  });
+  if let Some(tok) = token
   {
     // Allow modifier combinations; build token string for sequence matching
     {
@@ -486,8 +1068,6 @@ pub fn handle_key(
       }
       app.keys.last_at = Some(now);
 
-      // Build token
-      let tok = crate::keymap::build_token(ch, key.modifiers);
       app.keys.pending.push_str(&tok);
       let seq = app.keys.pending.clone();
 
@@ -499,7 +1079,26 @@ pub fn handle_key(
         {
           app.overlay = crate::app::Overlay::None;
         }
-        if crate::actions::dispatch_action(app, &action).unwrap_or(false)
+        let count = app.pending_count.take();
+        if !matches!(
+          crate::actions::internal::parse_internal_action(&action),
+          Some(
+            crate::actions::internal::InternalAction::RecordMacro
+              | crate::actions::internal::InternalAction::PlayMacro
+          )
+        )
+        {
+          app.record_macro_step(&action, count);
+        }
+        let ran = match count
+        {
+          Some(n) =>
+          {
+            crate::actions::dispatch_action_with_count(app, &action, n)
+          }
+          None => crate::actions::dispatch_action(app, &action),
+        };
+        if ran.unwrap_or(false)
         {
           if app.should_quit
           {
@@ -525,97 +1124,192 @@ pub fn handle_key(
       }
     }
   }
-  match (key.code, key.modifiers)
+  // Keys the keymap does not bind; a count typed before them is dropped
+  app.pending_count = None;
+  if key.code == KeyCode::Esc
   {
-    (KeyCode::Char('m'), KeyModifiers::NONE) =>
-    {
-      app.pending_mark = true;
-      app.add_message("Mark: type a letter to save this directory");
-    }
-    (KeyCode::Char('`'), KeyModifiers::NONE) =>
+    // If a mapping exists for <Esc>, dispatch it first
+    let esc_seq = String::from("<Esc>");
+    if let Some(action) = app.keys.lookup.get(esc_seq.as_str()).cloned()
     {
-      app.pending_goto = true;
-      app.add_message("Goto: type a letter to jump to its mark");
+      let _ = crate::actions::dispatch_action(app, &action);
+      if app.should_quit
+      {
+        return Ok(true);
+      }
     }
-    (KeyCode::Char('q'), _) => return Ok(true),
-    (KeyCode::Esc, _mods) =>
+    // cancel pending sequences and which-key
+    app.keys.pending.clear();
+    app.overlay = crate::app::Overlay::None;
+  }
+  Ok(false)
+}
+
+/// Two clicks on the same row within this window count as a double click.
+const DOUBLE_CLICK_MS: u128 = 400;
+/// Preview lines scrolled per wheel step.
+const WHEEL_PREVIEW_LINES: isize = 3;
+
+/// Keys of the messages and output overlays. Returns false for keys they
+/// leave to the keymap.
+fn handle_text_view_key(
+  app: &mut App,
+  key: KeyEvent,
+) -> bool
+{
+  let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+  if let Some(ref mut input) = app.text_view.input
+  {
+    match key.code
     {
-      // If a mapping exists for <Esc>, dispatch it first
-      let esc_seq = String::from("<Esc>");
-      if let Some(action) = app.keys.lookup.get(esc_seq.as_str()).cloned()
+      KeyCode::Esc => app.text_view.input = None,
+      KeyCode::Enter =>
       {
-        let _ = crate::actions::dispatch_action(app, &action);
-        if app.should_quit
-        {
-          return Ok(true);
-        }
+        let query = std::mem::take(input);
+        app.text_view.input = None;
+        app.text_view_search(&query);
       }
-      // cancel pending sequences and which-key
-      app.keys.pending.clear();
+      KeyCode::Backspace =>
+      {
+        input.pop();
+      }
+      KeyCode::Char(ch) if !ctrl => input.push(ch),
+      _ =>
+      {}
+    }
+    app.force_full_redraw = true;
+    return true;
+  }
+  let pending_g = std::mem::take(&mut app.text_view.pending_g);
+  match key.code
+  {
+    KeyCode::Char('g') if pending_g => app.text_view_scroll(isize::MIN / 2),
+    KeyCode::Char('g') if !ctrl => app.text_view.pending_g = true,
+    KeyCode::Home => app.text_view_scroll(isize::MIN / 2),
+    KeyCode::End | KeyCode::Char('G') => app.text_view_scroll(isize::MAX / 2),
+    KeyCode::Char('d') if ctrl => app.text_view_page(1),
+    KeyCode::Char('u') if ctrl => app.text_view_page(-1),
+    KeyCode::PageDown => app.text_view_page(1),
+    KeyCode::PageUp => app.text_view_page(-1),
+    KeyCode::Down | KeyCode::Char('j') => app.text_view_scroll(1),
+    KeyCode::Up | KeyCode::Char('k') => app.text_view_scroll(-1),
+    KeyCode::Char('/') =>
+    {
+      app.text_view.input = Some(String::new());
+      app.force_full_redraw = true;
+    }
+    KeyCode::Char('n') => app.text_view_next(true),
+    KeyCode::Char('N') => app.text_view_next(false),
+    KeyCode::Char('y') => app.text_view_yank(),
+    KeyCode::Char('c') if ctrl && app.is_command_running() =>
+    {
+      app.cancel_command_run()
+    }
+    KeyCode::Char('q') =>
+    {
       app.overlay = crate::app::Overlay::None;
-      return Ok(false);
+      app.force_full_redraw = true;
     }
-    (KeyCode::Up, _) | (KeyCode::Char('k'), _) =>
+    _ => return false,
+  }
+  true
+}
+
+/// Accept a terminal mouse event and mutate the [`App`] accordingly.
+///
+/// Clicking a row in the current pane selects it and a double click opens it
+/// (as `Enter` would); the wheel moves the cursor, or scrolls the preview when
+/// over it. Clicking the parent pane goes up a directory and clicking the
+/// preview pages it up or down depending on which half was clicked. Pane
+/// positions come from the last draw. Returns `Ok(true)` when the caller
+/// should exit.
+pub fn handle_mouse(
+  app: &mut App,
+  ev: MouseEvent,
+) -> io::Result<bool>
+{
+  // Modal overlays own the screen; leave them to the keyboard
+  if !matches!(
+    app.overlay,
+    crate::app::Overlay::None
+      | crate::app::Overlay::Messages
+      | crate::app::Overlay::Output { .. }
+  )
+  {
+    return Ok(false);
+  }
+  let pos = Position::new(ev.column, ev.row);
+  let areas = app.pane_areas;
+  let over_preview = areas.preview.contains(pos);
+  match ev.kind
+  {
+    MouseEventKind::ScrollUp | MouseEventKind::ScrollDown =>
     {
-      if let Some(sel) = app.list_state.selected()
-        && sel > 0
+      let down = matches!(ev.kind, MouseEventKind::ScrollDown);
+      if over_preview
       {
-        app.list_state.select(Some(sel - 1));
-        app.refresh_preview();
+        app.preview_scroll(
+          if down { WHEEL_PREVIEW_LINES } else { -WHEEL_PREVIEW_LINES },
+        );
       }
-    }
-    (KeyCode::Down, _) | (KeyCode::Char('j'), _) =>
-    {
-      if let Some(sel) = app.list_state.selected()
+      else if let Some(sel) = app.list_state.selected()
       {
-        if sel + 1 < app.current_entries.len()
+        let next = if down
         {
-          app.list_state.select(Some(sel + 1));
-          app.refresh_preview();
+          (sel + 1).min(app.current_entries.len().saturating_sub(1))
+        }
+        else
+        {
+          sel.saturating_sub(1)
+        };
+        if next != sel
+        {
+          app.select_index(next);
         }
       }
       else if !app.current_entries.is_empty()
       {
-        app.list_state.select(Some(0));
-        app.refresh_preview();
+        app.select_index(0);
       }
     }
-    (KeyCode::Enter, _) | (KeyCode::Right, _) | (KeyCode::Char('l'), _) =>
+    MouseEventKind::Down(MouseButton::Left) =>
     {
-      if let Some(entry) = app.selected_entry()
-        && entry.is_dir
+      if areas.current.contains(pos)
       {
-        app.cwd = entry.path.clone();
-        app.refresh_lists();
-        if app.current_entries.is_empty()
+        // Rows start below the top border
+        let Some(row) = ev.row.checked_sub(areas.current.y + 1)
+        else
         {
-          app.list_state.select(None);
+          return Ok(false);
+        };
+        let idx = app.list_state.offset() + row as usize;
+        if ev.row + 1 >= areas.current.bottom()
+          || idx >= app.current_entries.len()
+        {
+          return Ok(false);
         }
-        else
+        let now = std::time::Instant::now();
+        let double = app.last_click.is_some_and(|(at, last)| {
+          last == idx && now.duration_since(at).as_millis() <= DOUBLE_CLICK_MS
+        });
+        app.select_index(idx);
+        if double
         {
-          app.list_state.select(Some(0));
+          app.last_click = None;
+          app.nav_open();
+          return Ok(app.should_quit);
         }
-        app.refresh_preview();
+        app.last_click = Some((now, idx));
       }
-    }
-    (KeyCode::Backspace, _)
-    | (KeyCode::Left, _)
-    | (KeyCode::Char('h'), KeyModifiers::NONE) =>
-    {
-      if let Some(parent) = app.cwd.parent()
+      else if areas.parent.contains(pos)
       {
-        // Remember the directory name we are leaving so we can reselect it
-        let just_left =
-          app.cwd.file_name().map(|s| s.to_string_lossy().to_string());
-        app.cwd = parent.to_path_buf();
-        app.refresh_lists();
-        if let Some(name) = just_left
-          && let Some(idx) =
-            app.current_entries.iter().position(|e| e.name == name)
-        {
-          app.list_state.select(Some(idx));
-        }
-        app.refresh_preview();
+        app.nav_left();
+        return Ok(false);
+      }
+      else if over_preview
+      {
+        let mid = areas.preview.y + areas.preview.height / 2;
+        app.preview_scroll_page(if ev.row < mid { -1 } else { 1 });
       }
     }
     _ =>
@@ -1,4 +1,7 @@
-use crossterm::event::KeyModifiers;
+use crossterm::event::{
+  KeyCode,
+  KeyModifiers,
+};
 
 /// Split a key sequence string into tokens, preserving modifier tokens like
 /// "<C-x>" as single units.
@@ -63,3 +66,151 @@ pub fn build_token(
     ch.to_string()
   }
 }
+
+/// Modifier prefix of a `<...>` token, e.g. "C-M-" for Ctrl+Alt.
+fn modifier_prefix(mods: KeyModifiers) -> String
+{
+  let mut out = String::new();
+  for (m, p) in [
+    (KeyModifiers::CONTROL, "C-"),
+    (KeyModifiers::ALT, "M-"),
+    (KeyModifiers::SUPER, "S-"),
+    (KeyModifiers::SHIFT, "Sh-"),
+  ]
+  {
+    if mods.contains(m)
+    {
+      out.push_str(p);
+    }
+  }
+  out
+}
+
+/// Name of a key without a character, as written between `<` and `>`.
+fn key_name(code: KeyCode) -> Option<String>
+{
+  Some(match code
+  {
+    KeyCode::F(n) => format!("F{}", n),
+    KeyCode::Tab | KeyCode::BackTab => "Tab".into(),
+    KeyCode::Up => "Up".into(),
+    KeyCode::Down => "Down".into(),
+    KeyCode::Left => "Left".into(),
+    KeyCode::Right => "Right".into(),
+    KeyCode::Backspace => "BS".into(),
+    KeyCode::Enter => "CR".into(),
+    KeyCode::Home => "Home".into(),
+    KeyCode::End => "End".into(),
+    KeyCode::PageUp => "PageUp".into(),
+    KeyCode::PageDown => "PageDown".into(),
+    KeyCode::Insert => "Insert".into(),
+    KeyCode::Delete => "Del".into(),
+    _ => return None,
+  })
+}
+
+/// Build the key token for any key the keymap can bind: characters as
+/// [`build_token`] does, and named keys like "<F5>", "<Tab>" or "<C-Up>".
+/// `Esc` is left to the caller, which gives `<Esc>` its own handling.
+pub fn key_token(
+  code: KeyCode,
+  mods: KeyModifiers,
+) -> Option<String>
+{
+  if let KeyCode::Char(ch) = code
+  {
+    return Some(build_token(ch, mods));
+  }
+  let mut mods = mods;
+  if code == KeyCode::BackTab
+  {
+    mods |= KeyModifiers::SHIFT;
+  }
+  let name = key_name(code)?;
+  Some(format!("<{}{}>", modifier_prefix(mods), name))
+}
+
+/// Canonical spelling of a named key, accepting common aliases in any case.
+fn canonical_name(name: &str) -> Option<&'static str>
+{
+  const NAMES: &[(&str, &str)] = &[
+    ("tab", "Tab"),
+    ("up", "Up"),
+    ("down", "Down"),
+    ("left", "Left"),
+    ("right", "Right"),
+    ("bs", "BS"),
+    ("backspace", "BS"),
+    ("cr", "CR"),
+    ("enter", "CR"),
+    ("return", "CR"),
+    ("home", "Home"),
+    ("end", "End"),
+    ("pageup", "PageUp"),
+    ("pagedown", "PageDown"),
+    ("insert", "Insert"),
+    ("del", "Del"),
+    ("delete", "Del"),
+    ("esc", "Esc"),
+    ("escape", "Esc"),
+  ];
+  let low = name.to_ascii_lowercase();
+  NAMES.iter().find(|(alias, _)| *alias == low).map(|(_, n)| *n)
+}
+
+/// Rewrite one `<...>` token into the form [`key_token`] produces:
+/// `<space>` becomes " ", `<Enter>` becomes `<CR>`, `<c-f5>` becomes
+/// `<C-F5>`. Other tokens are returned unchanged.
+fn normalize_token(tok: &str) -> String
+{
+  let Some(inner) = tok.strip_prefix('<').and_then(|t| t.strip_suffix('>'))
+  else
+  {
+    return tok.to_string();
+  };
+  // Split off modifier prefixes; the key itself may be a lone '-'
+  let mut mods = KeyModifiers::NONE;
+  let mut rest = inner;
+  while let Some((head, tail)) = rest.split_once('-')
+    && !tail.is_empty()
+  {
+    match head.to_ascii_lowercase().as_str()
+    {
+      "c" => mods |= KeyModifiers::CONTROL,
+      "m" | "a" => mods |= KeyModifiers::ALT,
+      "s" => mods |= KeyModifiers::SUPER,
+      "sh" => mods |= KeyModifiers::SHIFT,
+      _ => break,
+    }
+    rest = tail;
+  }
+  if rest.eq_ignore_ascii_case("space")
+  {
+    return build_token(' ', mods);
+  }
+  if rest.chars().count() == 1
+  {
+    return match rest.chars().next()
+    {
+      Some(ch) if mods != KeyModifiers::NONE => build_token(ch, mods),
+      _ => tok.to_string(),
+    };
+  }
+  let function_key = rest
+    .strip_prefix(['F', 'f'])
+    .and_then(|n| n.parse::<u8>().ok())
+    .map(|n| format!("F{}", n));
+  let Some(name) = canonical_name(rest).map(String::from).or(function_key)
+  else
+  {
+    return tok.to_string();
+  };
+  format!("<{}{}>", modifier_prefix(mods), name)
+}
+
+/// Rewrite a key sequence from the config so it matches the tokens built
+/// for pressed keys, e.g. `<Space>ff` to " ff" and `<c-down>` to `<C-Down>`.
+pub fn normalize_sequence(seq: &str) -> String
+{
+  tokenize_sequence(seq).iter().map(|t| normalize_token(t)).collect()
+}
@@ -29,6 +29,8 @@ fn print_help()
       -V, --version         Show version and exit\n\
           --config-dir DIR  Use DIR as the config root (sets LSV_CONFIG_DIR)\n\
           --init-config     Prompt to create user config from examples\n\
+          --choosefile FILE Pick a file; write its path to FILE (- = stdout)\n\
+          --choosefiles FILE  Pick files; write the selected paths to FILE\n\
           --trace[=FILE]    Enable tracing to FILE (default /tmp/lsv-trace.log)\n\
      Arguments:\n\
       DIR                   Start in directory DIR (default: current dir)\n"
@@ -130,6 +132,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>>
   let mut args = env::args().skip(1);
   let mut dir_arg: Option<String> = None;
   let mut init_config: bool = false;
+  let mut pick: Option<(app::PickMode, String)> = None;
   while let Some(a) = args.next()
   {
     match a.as_str()
@@ -185,6 +188,33 @@ fn main() -> Result<(), Box<dyn std::error::Error>>
           unsafe { env::set_var("LSV_CONFIG_DIR", dir) };
         }
       }
+      s if s.starts_with("--choosefile") =>
+      {
+        let (flag, inline) = match s.split_once('=')
+        {
+          Some((f, v)) => (f, Some(v.to_string())),
+          None => (s, None),
+        };
+        let mode = match flag
+        {
+          "--choosefile" => app::PickMode::One,
+          "--choosefiles" => app::PickMode::Many,
+          _ =>
+          {
+            eprintln!("lsv: unknown option: {}", s);
+            print_help();
+            std::process::exit(2);
+          }
+        };
+        let Some(target) = inline.or_else(|| args.next())
+        else
+        {
+          eprintln!("lsv: {} requires a FILE argument (- for stdout)", flag);
+          print_help();
+          std::process::exit(2);
+        };
+        pick = Some((mode, target));
+      }
       "--" =>
       {
         // Remaining is positional dir (optional); take first if present
@@ -245,6 +275,26 @@ fn main() -> Result<(), Box<dyn std::error::Error>>
     return Ok(());
   }
 
+  // FILE is relative to the launch directory, and is emptied now so a
+  // caller never reads a stale pick after a cancelled session
+  if let Some((_, target)) = pick.as_mut()
+    && target != "-"
+  {
+    if let Ok(abs) = std::path::absolute(&*target)
+      && let Ok(abs) = abs.into_os_string().into_string()
+    {
+      *target = abs;
+    }
+    if let Err(e) = std::fs::write(&*target, "")
+    {
+      eprintln!("lsv: cannot write '{}': {}", target, e);
+      std::process::exit(2);
+    }
+  }
+
+  // An explicit DIR wins over the directory saved in the session, and a
+  // picker starts where it was launched
+  let restore_cwd = dir_arg.is_none() && pick.is_none();
   if let Some(dir) = dir_arg
     && let Err(e) = std::env::set_current_dir(&dir)
   {
@@ -254,10 +304,30 @@ fn main() -> Result<(), Box<dyn std::error::Error>>
 
   trace::log("[main] starting lsv");
   let mut app = App::new()?;
+  app.restore_session(restore_cwd);
+  app.warn_if_nested();
+  if let Some((mode, _)) = pick.as_ref()
+  {
+    app.set_pick_mode(*mode);
+  }
+  app.emit_event(app::AppEvent::Startup);
   if let Err(e) = runtime::run_app(&mut app)
   {
     trace::log(format!("[error] runtime::run_app: {e}"));
     return Err(e);
   }
+  app.emit_event(app::AppEvent::Quit);
+  app.save_session();
+  app.save_history();
+  app.save_frecency();
+  if let Some((_, target)) = pick
+  {
+    if app.picked().is_empty()
+    {
+      // Nothing picked: let `lsv --choosefile f && ...` stop there
+      std::process::exit(1);
+    }
+    app::write_picked(&target, app.picked())?;
+  }
   Ok(())
 }
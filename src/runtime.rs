@@ -1,11 +1,12 @@
-use std::{
-  io,
-  time::Duration,
-};
+use std::time::Duration;
 
 use crossterm::{
   event,
-  event::Event,
+  event::{
+    DisableMouseCapture,
+    EnableMouseCapture,
+    Event,
+  },
   execute,
   terminal::{
     EnterAlternateScreen,
@@ -24,9 +25,9 @@ use crate::app::App;
 pub fn run_app(app: &mut App) -> Result<(), Box<dyn std::error::Error>>
 {
   enable_raw_mode()?;
-  let mut stdout = io::stdout();
-  execute!(stdout, EnterAlternateScreen)?;
-  let backend = CrosstermBackend::new(stdout);
+  let mut out = crate::util::terminal_output()?;
+  execute!(out, EnterAlternateScreen, EnableMouseCapture)?;
+  let backend = CrosstermBackend::new(out);
   let mut terminal = Terminal::new(backend)?;
   terminal.clear()?;
 
@@ -37,34 +38,21 @@ pub fn run_app(app: &mut App) -> Result<(), Box<dyn std::error::Error>>
     loop
     {
       // Drain any running preview process output into the preview buffer
-      if let Some(ref rp) = app.running_preview
-      {
-        while let Ok(opt) = rp.rx.try_recv()
-        {
-          match opt
-          {
-            Some(line) =>
-            {
-              app.preview.static_lines.push(crate::util::sanitize_line(&line));
-              // Trim to a reasonable bound to avoid unbounded growth
-              if app.preview.static_lines.len() > 2000
-              {
-                let _ = app
-                  .preview
-                  .static_lines
-                  .drain(0..app.preview.static_lines.len() - 2000);
-              }
-            }
-            None =>
-            {
-              app.running_preview = None;
-              // Recompute normal preview for current selection
-              app.refresh_preview();
-              break;
-            }
-          }
-        }
-      }
+      app.poll_running_preview();
+      // Merge directory entries streamed in by background loads
+      app.poll_dir_loads();
+      app.poll_preview();
+      app.poll_jobs();
+      app.poll_command_run();
+      app.poll_grep();
+      app.poll_finder();
+      app.poll_dir_sizes();
+      app.poll_checksums();
+      app.poll_disk_space();
+      // Pick up external changes to the watched directories
+      app.poll_fs_changes();
+      app.poll_event_hooks();
+      app.poll_notifications();
       if app.force_full_redraw
       {
         let _ = terminal.clear();
@@ -79,7 +67,17 @@ pub fn run_app(app: &mut App) -> Result<(), Box<dyn std::error::Error>>
         result = Err(e.into());
         break;
       }
-      match crossterm::event::poll(Duration::from_millis(200))
+      // Poll faster while listings stream in or jobs report progress
+      let busy = app.is_loading()
+        || app.has_active_jobs()
+        || app.is_command_running()
+        || app.is_grep_running()
+        || app.is_finder_indexing()
+        || app.is_preview_loading()
+        || app.is_computing_dir_sizes()
+        || app.is_computing_checksums();
+      let tick = if busy { 50 } else { 200 };
+      match crossterm::event::poll(Duration::from_millis(tick))
       {
         Ok(true) => match event::read()
         {
@@ -97,6 +95,18 @@ pub fn run_app(app: &mut App) -> Result<(), Box<dyn std::error::Error>>
               break;
             }
           },
+          Ok(Event::Mouse(ev)) => match crate::input::handle_mouse(app, ev)
+          {
+            Ok(true) => break,
+            Ok(false) =>
+            {}
+            Err(e) =>
+            {
+              crate::trace::log(format!("[runtime] mouse input error: {}", e));
+              result = Err(e.into());
+              break;
+            }
+          },
           Ok(Event::Resize(_, _)) =>
           {}
           Ok(_) =>
@@ -126,7 +136,7 @@ pub fn run_app(app: &mut App) -> Result<(), Box<dyn std::error::Error>>
   };
 
   disable_raw_mode()?;
-  execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+  execute!(terminal.backend_mut(), DisableMouseCapture, LeaveAlternateScreen)?;
   terminal.show_cursor()?;
   // Clear caches tied to this session
   crate::ui::clear_owner_cache();
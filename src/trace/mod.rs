@@ -58,8 +58,16 @@ pub fn install_panic_hook()
     log(format!("[panic] backtrace:\n{bt}"));
     // Best-effort terminal restore so the panic is visible
     let _ = crossterm::terminal::disable_raw_mode();
-    let mut out = std::io::stdout();
-    let _ = crossterm::execute!(out, crossterm::terminal::LeaveAlternateScreen);
+    let Ok(mut out) = crate::util::terminal_output()
+    else
+    {
+      return;
+    };
+    let _ = crossterm::execute!(
+      out,
+      crossterm::event::DisableMouseCapture,
+      crossterm::terminal::LeaveAlternateScreen
+    );
   }));
 }
 
@@ -7,6 +7,12 @@ use ratatui::{
   text::Span,
 };
 
+/// `s` without its escape sequences, as it reads on screen.
+pub fn strip_ansi(s: &str) -> String
+{
+  ansi_spans(s).iter().map(|span| span.content.as_ref()).collect()
+}
+
 pub fn ansi_spans(s: &str) -> Vec<Span<'_>>
 {
   let bytes = s.as_bytes();
@@ -99,22 +105,76 @@ pub fn ansi_spans(s: &str) -> Vec<Span<'_>>
   spans
 }
 
+/// Parameters of an SGR sequence: `;` separates parameters and `:` the
+/// sub-parameters of one (as in `38:2::255:0:0`). Empty fields count as 0.
+fn sgr_params(seq: &str) -> Vec<Vec<u32>>
+{
+  seq
+    .split(';')
+    .map(|p| p.split(':').map(|t| t.parse::<u32>().unwrap_or(0)).collect())
+    .collect()
+}
+
+/// Colour of an extended colour parameter (38, 48 or 58) at `params[i]`,
+/// and how many following parameters it used. Handles `5;n` (256 colours)
+/// and `2;r;g;b` (truecolor), in both the `;` and `:` forms.
+fn extended_color(
+  params: &[Vec<u32>],
+  i: usize,
+) -> (Option<Color>, usize)
+{
+  let byte = |v: u32| v.min(255) as u8;
+  let sub = &params[i];
+  if sub.len() > 1
+  {
+    // Colon form: 38:5:n, 38:2:r:g:b or 38:2:<colour space>:r:g:b
+    let color = match sub[1]
+    {
+      5 => sub.get(2).map(|&n| Color::Indexed(byte(n))),
+      2 =>
+      {
+        let rgb = if sub.len() >= 6 { &sub[3..6] } else { &sub[2..] };
+        (rgb.len() == 3)
+          .then(|| Color::Rgb(byte(rgb[0]), byte(rgb[1]), byte(rgb[2])))
+      }
+      _ => None,
+    };
+    return (color, 0);
+  }
+  let arg = |k: usize| params.get(i + k).and_then(|p| p.first()).copied();
+  match arg(1)
+  {
+    Some(5) => match arg(2)
+    {
+      Some(n) => (Some(Color::Indexed(byte(n))), 2),
+      None => (None, 1),
+    },
+    Some(2) => match (arg(2), arg(3), arg(4))
+    {
+      (Some(r), Some(g), Some(b)) =>
+      {
+        (Some(Color::Rgb(byte(r), byte(g), byte(b))), 4)
+      }
+      _ => (None, params.len() - i - 1),
+    },
+    _ => (None, 0),
+  }
+}
+
 fn apply_sgr_seq(
   seq: &str,
   style: &mut Style,
 )
 {
-  let nums: Vec<i32> =
-    seq.split(';').filter_map(|t| t.parse::<i32>().ok()).collect();
-  if nums.is_empty()
-  {
-    *style = Style::default();
-    return;
-  }
+  let params = sgr_params(seq);
+  // Attributes are switched off in place rather than with remove_modifier,
+  // which would also strip them from the style the preview is drawn on
+  let off = |style: &mut Style, m: Modifier| style.add_modifier.remove(m);
   let mut i = 0;
-  while i < nums.len()
+  while i < params.len()
   {
-    match nums[i]
+    let code = params[i][0];
+    match code
     {
       0 =>
       {
@@ -124,103 +184,83 @@ fn apply_sgr_seq(
       {
         *style = style.add_modifier(Modifier::BOLD);
       }
+      2 =>
+      {
+        *style = style.add_modifier(Modifier::DIM);
+      }
       3 =>
       {
         *style = style.add_modifier(Modifier::ITALIC);
       }
-      4 =>
+      // 4:0 turns underlining off; 4:1 to 4:5 pick a line style
+      4 if params[i].get(1) == Some(&0) => off(style, Modifier::UNDERLINED),
+      4 | 21 =>
       {
         *style = style.add_modifier(Modifier::UNDERLINED);
       }
-      22 =>
+      5 =>
+      {
+        *style = style.add_modifier(Modifier::SLOW_BLINK);
+      }
+      6 =>
       {
-        *style = style.remove_modifier(Modifier::BOLD);
+        *style = style.add_modifier(Modifier::RAPID_BLINK);
       }
-      23 =>
+      7 =>
       {
-        *style = style.remove_modifier(Modifier::ITALIC);
+        *style = style.add_modifier(Modifier::REVERSED);
       }
-      24 =>
+      8 =>
       {
-        *style = style.remove_modifier(Modifier::UNDERLINED);
+        *style = style.add_modifier(Modifier::HIDDEN);
       }
+      9 =>
+      {
+        *style = style.add_modifier(Modifier::CROSSED_OUT);
+      }
+      22 => off(style, Modifier::BOLD | Modifier::DIM),
+      23 => off(style, Modifier::ITALIC),
+      24 => off(style, Modifier::UNDERLINED),
+      25 => off(style, Modifier::SLOW_BLINK | Modifier::RAPID_BLINK),
+      27 => off(style, Modifier::REVERSED),
+      28 => off(style, Modifier::HIDDEN),
+      29 => off(style, Modifier::CROSSED_OUT),
       30..=37 =>
       {
-        style.fg = Some(basic_color((nums[i] - 30) as u8, false));
+        style.fg = Some(basic_color((code - 30) as u8, false));
+      }
+      39 =>
+      {
+        style.fg = None;
       }
       90..=97 =>
       {
-        style.fg = Some(basic_color((nums[i] - 90) as u8, true));
+        style.fg = Some(basic_color((code - 90) as u8, true));
       }
       40..=47 =>
       {
-        style.bg = Some(basic_color((nums[i] - 40) as u8, false));
+        style.bg = Some(basic_color((code - 40) as u8, false));
       }
-      100..=107 =>
+      49 =>
       {
-        style.bg = Some(basic_color((nums[i] - 100) as u8, true));
+        style.bg = None;
       }
-      38 =>
+      100..=107 =>
       {
-        if i + 1 < nums.len()
-        {
-          match nums[i + 1]
-          {
-            5 =>
-            {
-              if i + 2 < nums.len()
-              {
-                style.fg = Some(Color::Indexed(nums[i + 2] as u8));
-                i += 2;
-              }
-            }
-            2 =>
-            {
-              if i + 4 < nums.len()
-              {
-                style.fg = Some(Color::Rgb(
-                  nums[i + 2] as u8,
-                  nums[i + 3] as u8,
-                  nums[i + 4] as u8,
-                ));
-                i += 4;
-              }
-            }
-            _ =>
-            {}
-          }
-        }
+        style.bg = Some(basic_color((code - 100) as u8, true));
       }
-      48 =>
+      38 | 48 | 58 =>
       {
-        if i + 1 < nums.len()
+        let (color, used) = extended_color(&params, i);
+        match (code, color)
         {
-          match nums[i + 1]
-          {
-            5 =>
-            {
-              if i + 2 < nums.len()
-              {
-                style.bg = Some(Color::Indexed(nums[i + 2] as u8));
-                i += 2;
-              }
-            }
-            2 =>
-            {
-              if i + 4 < nums.len()
-              {
-                style.bg = Some(Color::Rgb(
-                  nums[i + 2] as u8,
-                  nums[i + 3] as u8,
-                  nums[i + 4] as u8,
-                ));
-                i += 4;
-              }
-            }
-            _ =>
-            {}
-          }
+          (38, Some(c)) => style.fg = Some(c),
+          (48, Some(c)) => style.bg = Some(c),
+          // Underline colours are not drawn; the parameters are skipped
+          _ =>
+          {}
         }
+        i += used;
       }
       _ =>
       {}
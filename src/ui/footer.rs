@@ -0,0 +1,82 @@
+//! Optional status line below the panes (`ui.footer`).
+//!
+//! Each side is a template with the header's placeholders, or a Lua function
+//! called on every draw with a table of the same values (`selected_count`,
+//! `clipboard`, `jobs`, `filter`, `cwd`, `current_file`, …) that returns a
+//! template. Footer functions run on the UI thread, so keep them cheap.
+
+use mlua::Value;
+use ratatui::layout::Rect;
+
+use super::template;
+use crate::config::FooterSegment;
+
+/// Call the Lua footer function `idx`; errors are traced and show nothing.
+fn lua_segment(
+  app: &crate::App,
+  idx: usize,
+) -> String
+{
+  let Some(rt) = app.lua.as_ref()
+  else
+  {
+    return String::new();
+  };
+  let Some(key) = rt.actions.get(idx)
+  else
+  {
+    return String::new();
+  };
+  let lua = rt.engine.lua();
+  let run = || -> mlua::Result<String> {
+    let func = lua.registry_value::<mlua::Function>(key)?;
+    Ok(match func.call::<Value>(app.status_table(lua)?)?
+    {
+      Value::Nil => String::new(),
+      Value::String(s) => s.to_str()?.to_string(),
+      other => other.to_string()?,
+    })
+  };
+  run().unwrap_or_else(|e| {
+    crate::trace::log(format!("[footer] lua error: {}", e));
+    String::new()
+  })
+}
+
+fn render_segment(
+  app: &crate::App,
+  seg: Option<&FooterSegment>,
+) -> template::HeaderSide
+{
+  let tpl = match seg
+  {
+    None => String::new(),
+    Some(FooterSegment::Template(t)) => t.clone(),
+    Some(FooterSegment::Lua(idx)) => lua_segment(app, *idx),
+  };
+  template::format_header_side(app, Some(&tpl))
+}
+
+/// Draw the footer into `area` when one is configured.
+pub fn draw_footer(
+  f: &mut ratatui::Frame,
+  area: Rect,
+  app: &crate::App,
+)
+{
+  let Some(footer) = app.config.ui.footer.as_ref()
+  else
+  {
+    return;
+  };
+  let left = render_segment(app, footer.left.as_ref());
+  let right = render_segment(app, footer.right.as_ref());
+  super::draw_bar(
+    f,
+    area,
+    app,
+    (left, right),
+    footer.fg.as_ref(),
+    footer.bg.as_ref(),
+  );
+}
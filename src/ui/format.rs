@@ -20,6 +20,17 @@ pub fn human_size(bytes: u64) -> String
   }
 }
 
+/// Frame of a small spinner for work in progress, advancing with wall time.
+pub fn spinner_frame() -> char
+{
+  const FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+  let ms = SystemTime::now()
+    .duration_since(SystemTime::UNIX_EPOCH)
+    .map(|d| d.as_millis())
+    .unwrap_or(0);
+  FRAMES[(ms / 100) as usize % FRAMES.len()]
+}
+
 pub fn format_time_abs(
   t: SystemTime,
   fmt: &str,
@@ -33,6 +44,78 @@ pub fn format_time_abs(
   dt.format(fmt).to_string()
 }
 
+/// Like [`format_time_abs`], but `None` when `fmt` has an invalid
+/// specifier instead of panicking.
+pub fn try_format_time_abs(
+  t: SystemTime,
+  fmt: &str,
+) -> Option<String>
+{
+  use std::fmt::Write;
+  let dt: chrono::DateTime<chrono::Local> = chrono::DateTime::from(t);
+  let mut out = String::new();
+  write!(out, "{}", dt.format(fmt)).ok()?;
+  Some(out)
+}
+
+/// Pad or cut `text` by a field spec such as `<30`, `>8`, `^10` or `<20.20`:
+/// an optional alignment (`<` left, the default, `>` right, `^` centre), the
+/// width to pad to and, after `.`, the most columns to show, longer text
+/// ending in `~`. Widths count display columns. Returns `None` when `spec`
+/// isn't of that form.
+pub fn align_field(
+  text: &str,
+  spec: &str,
+) -> Option<String>
+{
+  let (align, rest) = match spec.chars().next()
+  {
+    Some(c @ ('<' | '>' | '^')) => (c, &spec[1..]),
+    _ => ('<', spec),
+  };
+  let (width, max) = match rest.split_once('.')
+  {
+    Some((w, m)) => (w, Some(m.parse::<usize>().ok()?)),
+    None => (rest, None),
+  };
+  let width = if width.is_empty() { 0 } else { width.parse::<usize>().ok()? };
+  if width == 0 && max.is_none()
+  {
+    return None;
+  }
+  let text = match max
+  {
+    Some(m) => crate::ui::row::truncate_with_tilde(text, m),
+    None => text.to_string(),
+  };
+  let pad =
+    width.saturating_sub(unicode_width::UnicodeWidthStr::width(text.as_str()));
+  Some(match align
+  {
+    '>' => format!("{}{}", " ".repeat(pad), text),
+    '^' =>
+    {
+      format!("{}{}{}", " ".repeat(pad / 2), text, " ".repeat(pad - pad / 2))
+    }
+    _ => format!("{}{}", text, " ".repeat(pad)),
+  })
+}
+
+/// `t` as "3h ago" while it is at most `within` seconds old, and with the
+/// strftime `fmt` after that (or when it lies in the future).
+pub fn format_time_within(
+  t: SystemTime,
+  fmt: &str,
+  within: u64,
+) -> String
+{
+  match SystemTime::now().duration_since(t)
+  {
+    Ok(age) if age.as_secs() <= within => format_time_ago(t),
+    _ => format_time_abs(t, fmt),
+  }
+}
+
 pub fn format_time_ago(t: SystemTime) -> String
 {
   let now = SystemTime::now();
@@ -0,0 +1,188 @@
+//! File icons shown before entry names.
+//!
+//! Icons come from `icons.extensions` / `icons.folders` in the config, then,
+//! with `preset = "nerdfont"` (what `ui.icons = true` selects), from a
+//! built-in table of Nerd Font glyphs with a colour per file type. Without
+//! the preset only the configured icons and defaults are used.
+
+use ratatui::style::Color;
+
+use crate::app::DirEntryInfo;
+
+/// Built-in glyphs and colours by lowercase extension.
+const NERD_EXTENSIONS: &[(&str, &str, &str)] = &[
+  ("rs", "\u{e7a8}", "#dea584"),
+  ("lua", "\u{e620}", "#51a0cf"),
+  ("py", "\u{e606}", "#ffbc03"),
+  ("js", "\u{e74e}", "#cbcb41"),
+  ("mjs", "\u{e74e}", "#cbcb41"),
+  ("ts", "\u{e628}", "#519aba"),
+  ("jsx", "\u{e7ba}", "#20c2e3"),
+  ("tsx", "\u{e7ba}", "#20c2e3"),
+  ("go", "\u{e627}", "#519aba"),
+  ("c", "\u{e61e}", "#599eff"),
+  ("h", "\u{e61e}", "#a074c4"),
+  ("cpp", "\u{e61d}", "#519aba"),
+  ("cc", "\u{e61d}", "#519aba"),
+  ("hpp", "\u{e61d}", "#a074c4"),
+  ("java", "\u{e738}", "#cc3e44"),
+  ("rb", "\u{e739}", "#701516"),
+  ("php", "\u{e73d}", "#a074c4"),
+  ("sh", "\u{e795}", "#89e051"),
+  ("bash", "\u{e795}", "#89e051"),
+  ("zsh", "\u{e795}", "#89e051"),
+  ("fish", "\u{e795}", "#89e051"),
+  ("vim", "\u{e62b}", "#019833"),
+  ("md", "\u{e73e}", "#dddddd"),
+  ("markdown", "\u{e73e}", "#dddddd"),
+  ("json", "\u{e60b}", "#cbcb41"),
+  ("toml", "\u{e615}", "#6d8086"),
+  ("yaml", "\u{e615}", "#6d8086"),
+  ("yml", "\u{e615}", "#6d8086"),
+  ("ini", "\u{e615}", "#6d8086"),
+  ("conf", "\u{e615}", "#6d8086"),
+  ("cfg", "\u{e615}", "#6d8086"),
+  ("html", "\u{e736}", "#e44d26"),
+  ("css", "\u{e749}", "#42a5f5"),
+  ("xml", "\u{f121}", "#e37933"),
+  ("txt", "\u{f0f6}", "#89e051"),
+  ("log", "\u{f0f6}", "#6d8086"),
+  ("pdf", "\u{f1c1}", "#b30b00"),
+  ("doc", "\u{f1c2}", "#185abd"),
+  ("docx", "\u{f1c2}", "#185abd"),
+  ("xls", "\u{f1c3}", "#207245"),
+  ("xlsx", "\u{f1c3}", "#207245"),
+  ("csv", "\u{f1c3}", "#89e051"),
+  ("ppt", "\u{f1c4}", "#cb4a32"),
+  ("pptx", "\u{f1c4}", "#cb4a32"),
+  ("png", "\u{f1c5}", "#a074c4"),
+  ("jpg", "\u{f1c5}", "#a074c4"),
+  ("jpeg", "\u{f1c5}", "#a074c4"),
+  ("gif", "\u{f1c5}", "#a074c4"),
+  ("webp", "\u{f1c5}", "#a074c4"),
+  ("svg", "\u{f1c5}", "#ffb13b"),
+  ("ico", "\u{f1c5}", "#cbcb41"),
+  ("mp3", "\u{f1c7}", "#d39ede"),
+  ("wav", "\u{f1c7}", "#d39ede"),
+  ("flac", "\u{f1c7}", "#d39ede"),
+  ("ogg", "\u{f1c7}", "#d39ede"),
+  ("mp4", "\u{f1c8}", "#fd971f"),
+  ("mkv", "\u{f1c8}", "#fd971f"),
+  ("mov", "\u{f1c8}", "#fd971f"),
+  ("webm", "\u{f1c8}", "#fd971f"),
+  ("zip", "\u{f1c6}", "#eca517"),
+  ("tar", "\u{f1c6}", "#eca517"),
+  ("gz", "\u{f1c6}", "#eca517"),
+  ("tgz", "\u{f1c6}", "#eca517"),
+  ("xz", "\u{f1c6}", "#eca517"),
+  ("bz2", "\u{f1c6}", "#eca517"),
+  ("zst", "\u{f1c6}", "#eca517"),
+  ("7z", "\u{f1c6}", "#eca517"),
+  ("rar", "\u{f1c6}", "#eca517"),
+  ("sql", "\u{e706}", "#dad8d8"),
+  ("db", "\u{e706}", "#dad8d8"),
+  ("sqlite", "\u{e706}", "#dad8d8"),
+  ("lock", "\u{f023}", "#bbbbbb"),
+];
+
+/// Built-in glyphs and colours for whole (lowercase) file names.
+const NERD_FILE_NAMES: &[(&str, &str, &str)] = &[
+  ("dockerfile", "\u{f308}", "#458ee6"),
+  ("makefile", "\u{e779}", "#6d8086"),
+  ("license", "\u{f02d}", "#d0bf41"),
+  (".gitignore", "\u{f1d3}", "#f54d27"),
+  (".gitmodules", "\u{f1d3}", "#f54d27"),
+];
+
+/// Built-in glyphs for (lowercase) folder names.
+const NERD_FOLDERS: &[(&str, &str)] = &[
+  (".git", "\u{e5fb}"),
+  (".github", "\u{e5fd}"),
+  (".config", "\u{e5fc}"),
+  ("node_modules", "\u{e5fa}"),
+];
+
+const NERD_DEFAULT_FILE: &str = "\u{f15b}";
+const NERD_DEFAULT_DIR: &str = "\u{f07b}";
+
+/// Presets that enable the built-in table.
+fn is_nerd_preset(preset: Option<&str>) -> bool
+{
+  matches!(
+    preset.map(str::to_ascii_lowercase).as_deref(),
+    Some("nerdfont" | "nerd" | "devicons")
+  )
+}
+
+/// The icon for `e` and the colour to draw it in, if any; empty when icons
+/// are off.
+pub fn icon_for(
+  icons: &crate::config::IconsConfig,
+  e: &DirEntryInfo,
+) -> (String, Option<Color>)
+{
+  if !icons.enabled
+  {
+    return (String::new(), None);
+  }
+  let nerd = is_nerd_preset(icons.preset.as_deref());
+  let name_lc = e
+    .path
+    .file_name()
+    .map(|s| s.to_string_lossy().to_lowercase())
+    .unwrap_or_else(|| {
+      e.name.rsplit('/').next().unwrap_or(&e.name).to_lowercase()
+    });
+  if e.is_dir
+  {
+    if let Some(sym) = icons.folders.get(&name_lc)
+    {
+      return (sym.clone(), None);
+    }
+    if nerd
+      && let Some((_, sym)) = NERD_FOLDERS.iter().find(|(n, _)| *n == name_lc)
+    {
+      return (sym.to_string(), None);
+    }
+    let fallback = if nerd { NERD_DEFAULT_DIR } else { "📁" };
+    return (
+      icons.default_dir.clone().unwrap_or_else(|| fallback.to_string()),
+      None,
+    );
+  }
+  let ext = e
+    .path
+    .extension()
+    .and_then(|s| s.to_str())
+    .map(|s| s.to_lowercase())
+    .unwrap_or_default();
+  let color = |builtin: Option<&str>| {
+    icons
+      .colors
+      .get(&ext)
+      .map(String::as_str)
+      .or(builtin)
+      .and_then(crate::ui::colors::parse_color)
+  };
+  let builtin = nerd
+    .then(|| {
+      NERD_FILE_NAMES.iter().find(|(n, ..)| *n == name_lc).or_else(|| {
+        NERD_EXTENSIONS.iter().find(|(x, ..)| !ext.is_empty() && *x == ext)
+      })
+    })
+    .flatten();
+  if !ext.is_empty()
+    && let Some(sym) = icons.extensions.get(&ext)
+  {
+    return (sym.clone(), color(builtin.map(|(.., c)| *c)));
+  }
+  if let Some((_, sym, c)) = builtin
+  {
+    return (sym.to_string(), color(Some(c)));
+  }
+  let fallback = if nerd { NERD_DEFAULT_FILE } else { "📄" };
+  (
+    icons.default_file.clone().unwrap_or_else(|| fallback.to_string()),
+    color(None),
+  )
+}
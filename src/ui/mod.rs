@@ -1,6 +1,8 @@
 pub mod ansi;
 pub mod colors;
+pub mod footer;
 pub mod format;
+pub mod icons;
 pub mod overlays;
 pub mod panes;
 pub mod preview;
@@ -31,14 +33,26 @@ pub fn draw(
   app: &mut crate::App,
 )
 {
-  // Split top header (1 row) and content
+  // Split the header (a row per template line), content and the optional
+  // footer (1 row)
   let full = f.area();
+  app.clear_template_fn_values();
+  let header = header_templates(app);
+  let footer_h = if app.config.ui.footer.is_some() { 1 } else { 0 };
   let vchunks = Layout::default()
     .direction(Direction::Vertical)
-    .constraints([Constraint::Length(1), Constraint::Min(1)])
+    .constraints([
+      Constraint::Length(header.len() as u16),
+      Constraint::Min(1),
+      Constraint::Length(footer_h),
+    ])
     .split(full);
 
-  draw_header(f, vchunks[0], app);
+  draw_header(f, vchunks[0], app, &header);
+  if footer_h > 0
+  {
+    footer::draw_footer(f, vchunks[2], app);
+  }
 
   let constraints = panes::pane_constraints(app);
   let chunks = Layout::default()
@@ -46,9 +60,23 @@ pub fn draw(
     .constraints(constraints)
     .split(vchunks[1]);
 
-  panes::draw_parent_panel(f, chunks[0], app);
+  app.pane_areas = crate::app::PaneAreas {
+    parent:  chunks[0],
+    current: chunks[1],
+    preview: chunks[2],
+  };
+  // Hidden panes get no width; skip them so no previewer runs for nothing
+  if chunks[0].width > 0
+  {
+    panes::draw_parent_panel(f, chunks[0], app);
+  }
   panes::draw_current_panel(f, chunks[1], app);
-  crate::ui::preview::draw_preview_panel(f, chunks[2], app);
+  if chunks[2].width > 0
+  {
+    crate::ui::preview::draw_preview_panel(f, chunks[2], app);
+  }
+  // Notifications sit above the panes, below any overlay
+  panes::draw_notifications(f, vchunks[1], app);
 
   // which-key overlay (draw last so it appears on top)
   match app.overlay
@@ -77,181 +105,175 @@ pub fn draw(
     {
       panes::draw_confirm_panel(f, f.area(), app);
     }
+    crate::app::Overlay::Select(_) =>
+    {
+      panes::draw_select_panel(f, f.area(), app);
+    }
     crate::app::Overlay::ThemePicker(_) =>
     {
       panes::draw_theme_picker_panel(f, f.area(), app);
     }
+    crate::app::Overlay::Trash(_) =>
+    {
+      panes::draw_trash_panel(f, f.area(), app);
+    }
+    crate::app::Overlay::Mounts(_) =>
+    {
+      panes::draw_mounts_panel(f, f.area(), app);
+    }
+    crate::app::Overlay::Finder(_) =>
+    {
+      panes::draw_finder_panel(f, f.area(), app);
+    }
+    crate::app::Overlay::Palette(_) =>
+    {
+      panes::draw_palette_panel(f, f.area(), app);
+    }
+    crate::app::Overlay::Help(_) =>
+    {
+      let area = f.area();
+      panes::draw_help_panel(f, area, app);
+    }
+    crate::app::Overlay::Grep(_) =>
+    {
+      panes::draw_grep_panel(f, f.area(), app);
+    }
+    crate::app::Overlay::Bookmarks { .. } =>
+    {
+      panes::draw_bookmarks_panel(f, f.area(), app);
+    }
+    crate::app::Overlay::Selections { .. } =>
+    {
+      panes::draw_selections_panel(f, f.area(), app);
+    }
+    crate::app::Overlay::Registers { .. } =>
+    {
+      panes::draw_registers_panel(f, f.area(), app);
+    }
+    crate::app::Overlay::Jobs { .. } =>
+    {
+      panes::draw_jobs_panel(f, f.area(), app);
+    }
     crate::app::Overlay::None =>
     {}
   }
 }
 
+/// Most header rows, so a stray template cannot crowd out the panes.
+const MAX_HEADER_ROWS: usize = 4;
+
+/// The header's (left, right) templates, one pair per row. Templates with
+/// several lines give the header as many rows as the longer side has.
+fn header_templates(app: &crate::App) -> Vec<(String, String)>
+{
+  let ui = &app.config.ui;
+  let left_tpl = ui
+    .header_left
+    .as_deref()
+    .unwrap_or(crate::config::defaults::DEFAULT_HEADER_LEFT);
+  let right_tpl = ui
+    .header_right
+    .as_deref()
+    .unwrap_or(crate::config::defaults::DEFAULT_HEADER_RIGHT);
+  let left: Vec<&str> = left_tpl.lines().collect();
+  let right: Vec<&str> = right_tpl.lines().collect();
+  let rows = left.len().max(right.len()).clamp(1, MAX_HEADER_ROWS);
+  let line = |v: &[&str], i: usize| v.get(i).copied().unwrap_or("").to_string();
+  (0..rows).map(|i| (line(&left, i), line(&right, i))).collect()
+}
+
 fn draw_header(
   f: &mut ratatui::Frame,
   area: Rect,
   app: &crate::App,
+  rows: &[(String, String)],
 )
 {
-  // Paint background row based on explicit header_bg or theme title_bg
-  if let Some(bg_s) =
-    app.config.ui.header_bg.as_ref().or_else(|| {
-      app.config.ui.theme.as_ref().and_then(|t| t.title_bg.as_ref())
-    })
-    && let Some(bg) = crate::ui::colors::parse_color(bg_s)
-  {
-    let blk = ratatui::widgets::Block::default()
-      .style(ratatui::style::Style::default().bg(bg));
-    f.render_widget(blk, area);
-  }
-  // helper removed; header rendering now lives in template::format_header_side
-  let _unused = (); // retain function body start for patching
-  #[allow(dead_code)]
-  fn render_header_side(
-    app: &crate::App,
-    tpl_opt: Option<&String>,
-  ) -> String
+  // Show an active filter, a count being typed and a macro being recorded
+  // unless the templates already place them
+  let placed =
+    rows.iter().any(|(l, r)| l.contains("{filter") || r.contains("{filter"));
+  let count_placed =
+    rows.iter().any(|(l, r)| l.contains("{count") || r.contains("{count"));
+  let recording_placed = rows
+    .iter()
+    .any(|(l, r)| l.contains("{recording") || r.contains("{recording"));
+  for (i, (left_tpl, right_tpl)) in rows.iter().enumerate()
   {
-    // Validate placeholders against allowed set; log unknowns
-    fn placeholders_in(s: &str) -> Vec<String>
+    let row = Rect { y: area.y + i as u16, height: 1, ..area };
+    if row.y >= area.bottom()
     {
-      let mut out = Vec::new();
-      let mut i = 0;
-      let b = s.as_bytes();
-      while i < b.len()
-      {
-        if b[i] == b'{'
-          && let Some(j) = s[i + 1..].find('}')
-        {
-          let end = i + 1 + j + 1;
-          let name = &s[i + 1..end - 1];
-          if !name.is_empty()
-          {
-            out.push(name.to_string());
-          }
-          i = end;
-          continue;
-        }
-        let ch = s[i..].chars().next().unwrap();
-        i += ch.len_utf8();
-      }
-      out
+      break;
     }
-
-    use chrono::Local;
-    let now = Local::now();
-    let date_s = now.format("%Y-%m-%d").to_string();
-    let time_s = now.format("%H:%M").to_string();
-    let username = whoami::username();
-    let hostname = whoami::fallible::hostname().unwrap_or_default();
-    let cwd_s = app.cwd.display().to_string();
-    let sel_opt = app.selected_entry();
-    let current_file = sel_opt
-      .as_ref()
-      .map(|e| e.path.display().to_string())
-      .unwrap_or_else(|| cwd_s.clone());
-    let owner = sel_opt
-      .as_ref()
-      .map(|e| owner_string(&e.path))
-      .unwrap_or_else(|| String::from("-"));
-    let perms = sel_opt
-      .as_ref()
-      .map(|e| crate::ui::panes::permissions_string(e))
-      .unwrap_or_else(|| String::from("---------"));
-    let size_s = sel_opt
-      .as_ref()
-      .map(|e| {
-        if e.is_dir
-        {
-          "-".to_string()
-        }
-        else
-        {
-          match app.display_mode
-          {
-            crate::app::DisplayMode::Friendly =>
-            {
-              crate::ui::panes::human_size(e.size)
-            }
-            crate::app::DisplayMode::Absolute => format!("{} B", e.size),
-          }
-        }
-      })
-      .unwrap_or_else(|| String::from("-"));
-    let ext = sel_opt
-      .as_ref()
-      .and_then(|e| {
-        e.path.extension().and_then(|s| s.to_str()).map(|s| s.to_string())
-      })
-      .unwrap_or_default();
-    let ctime_s = sel_opt
-      .as_ref()
-      .and_then(|e| e.ctime)
-      .map(|t| {
-        let fmt =
-          app.config.ui.date_format.as_deref().unwrap_or("%Y-%m-%d %H:%M");
-        crate::ui::panes::format_time_abs(t, fmt)
-      })
-      .unwrap_or_else(|| String::from("-"));
-    let mtime_s = sel_opt
-      .as_ref()
-      .and_then(|e| e.mtime)
-      .map(|t| {
-        let fmt =
-          app.config.ui.date_format.as_deref().unwrap_or("%Y-%m-%d %H:%M");
-        crate::ui::panes::format_time_abs(t, fmt)
-      })
-      .unwrap_or_else(|| String::from("-"));
-
-    let tpl = tpl_opt.cloned().unwrap_or_default();
-
-    // Allowed placeholders for header templates
-    let allowed = [
-      "date",
-      "time",
-      "cwd",
-      "current_file",
-      "username",
-      "hostname",
-      "current_file_permissions",
-      "current_file_size",
-      "current_file_ctime",
-      "current_file_mtime",
-      "current_file_extension",
-      "owner",
-    ];
-    for ph in placeholders_in(&tpl)
+    let mut left_side = template::format_header_side(app, Some(left_tpl));
+    let mut right_side = template::format_header_side(app, Some(right_tpl));
+    let mut tags = Vec::new();
+    if let Some(reg) = app.recording_macro()
+      && !recording_placed
     {
-      if !allowed.iter().any(|&a| a == ph)
-      {
-        crate::trace::log(format!("[header] unknown placeholder '{{{}}}'", ph));
-      }
+      tags.push(format!("recording @{}", reg));
+    }
+    let keys = template::pending_keys(app);
+    if !keys.is_empty() && !count_placed
+    {
+      tags.push(keys);
+    }
+    if !tags.is_empty() && i == 0
+    {
+      let tag = format!("{}  ", tags.join("  "));
+      right_side.text.insert_str(0, &tag);
+      right_side.spans.insert(
+        0,
+        ratatui::text::Span::styled(
+          tag,
+          ratatui::style::Style::default()
+            .add_modifier(ratatui::style::Modifier::BOLD),
+        ),
+      );
+    }
+    if let Some(pattern) = app.active_filter()
+      && i == 0
+      && !placed
+    {
+      let tag = format!("  [filter: {}]", pattern);
+      left_side.text.push_str(&tag);
+      left_side.spans.push(ratatui::text::Span::styled(
+        tag,
+        ratatui::style::Style::default()
+          .add_modifier(ratatui::style::Modifier::BOLD),
+      ));
     }
-    tpl
-      .replace("{date}", &date_s)
-      .replace("{time}", &time_s)
-      .replace("{cwd}", &cwd_s)
-      .replace("{current_file}", &current_file)
-      .replace("{username}", &username)
-      .replace("{hostname}", &hostname)
-      .replace("{current_file_permissions}", &perms)
-      .replace("{current_file_size}", &size_s)
-      .replace("{current_file_ctime}", &ctime_s)
-      .replace("{current_file_mtime}", &mtime_s)
-      .replace("{current_file_extension}", &ext)
-      .replace("{owner}", &owner)
+    draw_bar(
+      f,
+      row,
+      app,
+      (left_side, right_side),
+      app.config.ui.header_fg.as_ref(),
+      app.config.ui.header_bg.as_ref(),
+    );
   }
+}
 
-  // Prefer user-configured templates; fall back to a sensible default
-  let left_tpl =
-    app.config.ui.header_left.as_ref().cloned().or_else(|| {
-      Some(crate::config::defaults::DEFAULT_HEADER_LEFT.to_string())
-    });
-  let right_tpl = app.config.ui.header_right.as_ref().cloned().or_else(|| {
-    Some(crate::config::defaults::DEFAULT_HEADER_RIGHT.to_string())
-  });
-
-  let left_side = template::format_header_side(app, left_tpl.as_ref());
-  let right_side = template::format_header_side(app, right_tpl.as_ref());
+/// Draw a header-style line: `left` and `right` aligned to either end, with
+/// `fg`/`bg` (else the theme's title colours) where the template set none.
+fn draw_bar(
+  f: &mut ratatui::Frame,
+  area: Rect,
+  app: &crate::App,
+  (left_side, right_side): (template::HeaderSide, template::HeaderSide),
+  fg: Option<&String>,
+  bg: Option<&String>,
+)
+{
+  // Paint background row based on explicit bg or theme title_bg
+  if let Some(bg_s) = bg
+    .or_else(|| app.config.ui.theme.as_ref().and_then(|t| t.title_bg.as_ref()))
+    && let Some(bg) = crate::ui::colors::parse_color(bg_s)
+  {
+    let blk = ratatui::widgets::Block::default()
+      .style(ratatui::style::Style::default().bg(bg));
+    f.render_widget(blk, area);
+  }
 
   // Compute widths from plain text
   let total = area.width as usize;
@@ -310,14 +332,9 @@ fn draw_header(
   // Apply default fg/bg to spans where not explicitly set
   if let Some(th) = app.config.ui.theme.as_ref()
   {
-    // Prefer explicit ui.header_fg if provided, else theme title_fg
-    let fg_opt = app
-      .config
-      .ui
-      .header_fg
-      .as_ref()
-      .and_then(|s| crate::ui::colors::parse_color(s))
-      .or_else(|| {
+    // Prefer the explicit fg if provided, else theme title_fg
+    let fg_opt =
+      fg.and_then(|s| crate::ui::colors::parse_color(s)).or_else(|| {
         th.title_fg.as_ref().and_then(|s| crate::ui::colors::parse_color(s))
       });
     if let Some(fg) = fg_opt
@@ -337,14 +354,9 @@ fn draw_header(
         }
       }
     }
-    // Prefer explicit ui.header_bg if provided, else theme title_bg
-    let bg_opt = app
-      .config
-      .ui
-      .header_bg
-      .as_ref()
-      .and_then(|s| crate::ui::colors::parse_color(s))
-      .or_else(|| {
+    // Prefer the explicit bg if provided, else theme title_bg
+    let bg_opt =
+      bg.and_then(|s| crate::ui::colors::parse_color(s)).or_else(|| {
         th.title_bg.as_ref().and_then(|s| crate::ui::colors::parse_color(s))
       });
     if let Some(bg) = bg_opt
@@ -376,13 +388,14 @@ fn draw_header(
 }
 
 #[cfg(unix)]
-fn owner_string(path: &std::path::Path) -> String
+fn owner_string(
+  app: &crate::App,
+  e: &crate::app::DirEntryInfo,
+) -> String
 {
-  use std::os::unix::fs::MetadataExt;
-  if let Ok(meta) = std::fs::metadata(path)
+  if let Some(meta) = app.entry_meta(e)
   {
-    let uid = meta.uid();
-    let gid = meta.gid();
+    let (uid, gid) = (meta.uid, meta.gid);
     let user = lookup_user_name(uid).unwrap_or_else(|| uid.to_string());
     let group = lookup_group_name(gid).unwrap_or_else(|| gid.to_string());
     format!("{}:{}", user, group)
@@ -393,36 +406,50 @@ fn owner_string(path: &std::path::Path) -> String
   }
 }
 
-#[cfg(not(unix))]
-fn owner_string(_path: &std::path::Path) -> String
+#[cfg(windows)]
+fn owner_string(
+  _app: &crate::App,
+  e: &crate::app::DirEntryInfo,
+) -> String
+{
+  crate::core::winsec::owner_name(&e.path).unwrap_or_else(|| String::from("-"))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn owner_string(
+  _app: &crate::App,
+  _e: &crate::app::DirEntryInfo,
+) -> String
 {
   String::from("-")
 }
 
 #[cfg(unix)]
-static UID_CACHE: OnceLock<RwLock<HashMap<u32, String>>> = OnceLock::new();
+static UID_CACHE: OnceLock<RwLock<HashMap<u32, Option<String>>>> =
+  OnceLock::new();
 #[cfg(unix)]
-static GID_CACHE: OnceLock<RwLock<HashMap<u32, String>>> = OnceLock::new();
+static GID_CACHE: OnceLock<RwLock<HashMap<u32, Option<String>>>> =
+  OnceLock::new();
 
 #[cfg(unix)]
-fn uid_cache() -> &'static RwLock<HashMap<u32, String>>
+fn uid_cache() -> &'static RwLock<HashMap<u32, Option<String>>>
 {
   UID_CACHE.get_or_init(|| RwLock::new(HashMap::new()))
 }
 #[cfg(unix)]
-fn gid_cache() -> &'static RwLock<HashMap<u32, String>>
+fn gid_cache() -> &'static RwLock<HashMap<u32, Option<String>>>
 {
   GID_CACHE.get_or_init(|| RwLock::new(HashMap::new()))
 }
 
 #[cfg(unix)]
-fn lookup_user_name(uid: u32) -> Option<String>
+pub(crate) fn lookup_user_name(uid: u32) -> Option<String>
 {
-  // Fast path: check cache
+  // Fast path: check cache, which also remembers ids without a name
   if let Ok(map) = uid_cache().read()
     && let Some(v) = map.get(&uid)
   {
-    return Some(v.clone());
+    return v.clone();
   }
   // Parse /etc/passwd to resolve uid -> name
   let found = if let Ok(text) = std::fs::read_to_string("/etc/passwd")
@@ -465,21 +492,20 @@ fn lookup_user_name(uid: u32) -> Option<String>
       _ => None,
     }
   });
-  if let Some(ref name) = found
-    && let Ok(mut map) = uid_cache().write()
+  if let Ok(mut map) = uid_cache().write()
   {
-    map.insert(uid, name.clone());
+    map.insert(uid, found.clone());
   }
   found
 }
 
 #[cfg(unix)]
-fn lookup_group_name(gid: u32) -> Option<String>
+pub(crate) fn lookup_group_name(gid: u32) -> Option<String>
 {
   if let Ok(map) = gid_cache().read()
     && let Some(v) = map.get(&gid)
   {
-    return Some(v.clone());
+    return v.clone();
   }
   let found = if let Ok(text) = std::fs::read_to_string("/etc/group")
   {
@@ -519,10 +545,9 @@ fn lookup_group_name(gid: u32) -> Option<String>
       _ => None,
     }
   });
-  if let Some(ref name) = found
-    && let Ok(mut map) = gid_cache().write()
+  if let Ok(mut map) = gid_cache().write()
   {
-    map.insert(gid, name.clone());
+    map.insert(gid, found.clone());
   }
   found
 }
@@ -542,7 +567,13 @@ pub fn clear_owner_cache()
   }
 }
 
-#[cfg(not(unix))]
+#[cfg(windows)]
+pub fn clear_owner_cache()
+{
+  crate::core::winsec::clear_cache();
+}
+
+#[cfg(not(any(unix, windows)))]
 pub fn clear_owner_cache() {}
 
 // (unused)
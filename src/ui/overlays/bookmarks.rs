@@ -0,0 +1,132 @@
+use ratatui::{
+  layout::{
+    Constraint,
+    Direction,
+    Layout,
+    Rect,
+  },
+  style::{
+    Color,
+    Modifier,
+    Style,
+  },
+  text::{
+    Line,
+    Span,
+  },
+  widgets::{
+    Block,
+    Borders,
+    Clear,
+    List,
+    ListItem,
+    ListState,
+    Paragraph,
+  },
+};
+
+pub fn draw_bookmarks_panel(
+  f: &mut ratatui::Frame,
+  area: Rect,
+  app: &crate::App,
+)
+{
+  let selected = match app.overlay
+  {
+    crate::app::Overlay::Bookmarks { selected } => selected,
+    _ => return,
+  };
+  let names = app.mark_names();
+
+  let popup_width =
+    (area.width.saturating_mul(70) / 100).max(40).min(area.width);
+  let popup_height =
+    (area.height.saturating_mul(50) / 100).max(6).min(area.height);
+  let popup = Rect::new(
+    area.x + area.width.saturating_sub(popup_width) / 2,
+    area.y + area.height.saturating_sub(popup_height) / 2,
+    popup_width,
+    popup_height,
+  );
+  f.render_widget(Clear, popup);
+
+  let theme = app.config.ui.theme.as_ref();
+  let color =
+    |v: Option<&String>| v.and_then(|s| crate::ui::colors::parse_color(s));
+  let mut block = Block::default().borders(Borders::ALL);
+  if let Some(bg) = color(theme.and_then(|th| th.pane_bg.as_ref()))
+  {
+    block = block.style(Style::default().bg(bg));
+  }
+  if let Some(bfg) = color(theme.and_then(|th| th.border_fg.as_ref()))
+  {
+    block = block.border_style(Style::default().fg(bfg));
+  }
+  let title_fg =
+    color(theme.and_then(|th| th.title_fg.as_ref())).unwrap_or(Color::Yellow);
+  block = block.title(Span::styled(
+    " Bookmarks ",
+    Style::default().fg(title_fg).add_modifier(Modifier::BOLD),
+  ));
+
+  let inner = block.inner(popup);
+  f.render_widget(block, popup);
+  if inner.width == 0 || inner.height == 0
+  {
+    return;
+  }
+  let chunks = Layout::default()
+    .direction(Direction::Vertical)
+    .constraints([Constraint::Min(1), Constraint::Length(1)])
+    .split(inner);
+
+  let item_style = Style::default()
+    .fg(color(theme.and_then(|th| th.item_fg.as_ref())).unwrap_or(Color::Gray));
+  let info_style = Style::default().fg(
+    color(theme.and_then(|th| th.info_fg.as_ref())).unwrap_or(Color::DarkGray),
+  );
+  let mut highlight = Style::default().add_modifier(Modifier::BOLD);
+  if let Some(bg) = color(theme.and_then(|th| th.selected_item_bg.as_ref()))
+  {
+    highlight = highlight.bg(bg);
+  }
+
+  if names.is_empty()
+  {
+    f.render_widget(
+      Paragraph::new("No bookmarks; press a to mark the current directory")
+        .style(info_style),
+      chunks[0],
+    );
+  }
+  else
+  {
+    let width = names
+      .iter()
+      .map(|n| unicode_width::UnicodeWidthStr::width(n.as_str()))
+      .max()
+      .unwrap_or(0);
+    let items: Vec<ListItem> = names
+      .iter()
+      .map(|name| {
+        let path = app.marks.get(name).map(|p| p.display().to_string());
+        let pad = width - unicode_width::UnicodeWidthStr::width(name.as_str());
+        ListItem::new(Line::from(vec![
+          Span::styled(format!("{}{}  ", name, " ".repeat(pad)), item_style),
+          Span::styled(path.unwrap_or_default(), info_style),
+        ]))
+      })
+      .collect();
+    let mut list_state = ListState::default();
+    list_state.select(Some(selected.min(names.len() - 1)));
+    let list = List::new(items).highlight_style(highlight);
+    f.render_stateful_widget(list, chunks[0], &mut list_state);
+  }
+
+  let hint = Paragraph::new(
+    "↑/↓ select  Enter go  a add  r rename  d delete  Esc close",
+  )
+  .style(info_style)
+  .alignment(ratatui::layout::Alignment::Center);
+  f.render_widget(hint, chunks[1]);
+}
@@ -22,7 +22,10 @@ pub fn draw_command_pane(
   {
     crate::app::Overlay::CommandPane(ref st) =>
     {
-      (st.prompt == ":", st.show_suggestions && st.prompt == ":")
+      (
+        st.prompt == ":",
+        st.show_suggestions && st.prompt == ":" && st.candidates.is_empty(),
+      )
     }
     _ => (false, false),
   };
@@ -52,7 +55,11 @@ pub fn draw_command_pane(
   if let crate::app::Overlay::CommandPane(ref st_box) = app.overlay
   {
     let st = st_box.as_ref();
-    prompt = st.prompt.clone();
+    prompt = match st.history.search
+    {
+      Some(ref q) => format!("(history `{}`) {}", q, st.prompt),
+      None => st.prompt.clone(),
+    };
     input = st.input.clone();
     cursor_x = area.x + (prompt.len() as u16) + (st.cursor as u16);
   }
@@ -92,11 +99,11 @@ pub fn draw_command_pane(
       };
       // Build suggestions sorted alphabetically (case-insensitive)
       let prefix = input.trim();
-      let mut matches: Vec<&str> = crate::commands::all()
-        .iter()
-        .copied()
-        .filter(|c| prefix.is_empty() || c.starts_with(prefix))
-        .collect();
+      let mut matches: Vec<String> =
+        crate::commands::with_lua(&app.config.commands)
+          .into_iter()
+          .filter(|c| prefix.is_empty() || c.starts_with(prefix))
+          .collect();
       matches.sort_by_key(|a| a.to_lowercase());
       let line = if matches.is_empty() {
         String::from("<no matches>")
@@ -119,8 +126,59 @@ pub fn draw_command_pane(
     let para = Paragraph::new(text);
     f.render_widget(para, area);
   }
+  if let crate::app::Overlay::CommandPane(ref st) = app.overlay
+    && !st.candidates.is_empty()
+  {
+    draw_candidates(f, full, area, app, &st.candidates);
+  }
   f.set_cursor_position((
     cursor_x.min(area.x + area.width.saturating_sub(1)),
     cursor_y,
   ));
 }
+
+/// Most path completions listed in the popup at once.
+const MAX_CANDIDATES: usize = 12;
+
+/// Popup above the command pane listing path completions.
+fn draw_candidates(
+  f: &mut ratatui::Frame,
+  full: Rect,
+  pane: Rect,
+  app: &crate::App,
+  candidates: &[String],
+)
+{
+  let mut lines: Vec<String> =
+    candidates.iter().take(MAX_CANDIDATES).cloned().collect();
+  if candidates.len() > MAX_CANDIDATES
+  {
+    lines.push(format!("… {} more", candidates.len() - MAX_CANDIDATES));
+  }
+  let widest = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+  let width = (widest as u16 + 2).min(full.width);
+  let height = (lines.len() as u16 + 2).min(pane.y.saturating_sub(full.y));
+  if height < 3
+  {
+    return;
+  }
+  let area = Rect { x: full.x, y: pane.y - height, width, height };
+  let mut block = Block::default().borders(Borders::ALL);
+  let mut style = Style::default();
+  if let Some(th) = app.config.ui.theme.as_ref()
+  {
+    if let Some(bg) =
+      th.pane_bg.as_ref().and_then(|s| crate::ui::colors::parse_color(s))
+    {
+      style = style.bg(bg);
+    }
+    if let Some(bfg) =
+      th.border_fg.as_ref().and_then(|s| crate::ui::colors::parse_color(s))
+    {
+      block = block.border_style(Style::default().fg(bfg));
+    }
+  }
+  f.render_widget(Clear, area);
+  let para = Paragraph::new(lines.join("\n")).style(style).block(block);
+  f.render_widget(para, area);
+}
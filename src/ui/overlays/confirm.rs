@@ -18,23 +18,39 @@ use ratatui::{
   },
 };
 
+/// Draw the confirmation dialog. Takes the app mutably to record how many
+/// listed paths fit, for paging, and keep the scroll in range.
 pub fn draw_confirm_panel(
   f: &mut ratatui::Frame,
   area: Rect,
-  app: &crate::App,
+  app: &mut crate::App,
 )
 {
+  let theme = app.config.ui.theme.clone();
   let state = match app.overlay
   {
-    crate::app::Overlay::Confirm(ref s) => s.as_ref(),
+    crate::app::Overlay::Confirm(ref mut s) => s.as_mut(),
     _ => return,
   };
 
+  // Room for the question, a blank line and as many paths as fit
+  let question_rows = state.question.lines().count().max(1) as u16;
+  let (width, height) = if state.details.is_empty()
+  {
+    (60, 5)
+  }
+  else
+  {
+    let max_h = (area.height.saturating_mul(70) / 100).max(question_rows + 4);
+    let rows = (state.details.len() as u16).min(max_h - question_rows - 3);
+    ((area.width.saturating_mul(70) / 100).max(60), question_rows + 3 + rows)
+  };
+  let (width, height) = (width.min(area.width), height.min(area.height));
   let popup = Rect::new(
-    area.x + area.width.saturating_sub(60) / 2,
-    area.y + area.height.saturating_sub(5) / 2,
-    60,
-    5,
+    area.x + area.width.saturating_sub(width) / 2,
+    area.y + area.height.saturating_sub(height) / 2,
+    width,
+    height,
   );
   f.render_widget(Clear, popup);
 
@@ -42,7 +58,7 @@ pub fn draw_confirm_panel(
   let mut border_fg = None;
   let mut title_fg = Color::Yellow;
   let mut title_bg = None;
-  if let Some(th) = app.config.ui.theme.as_ref()
+  if let Some(th) = theme.as_ref()
   {
     pane_bg =
       th.pane_bg.as_ref().and_then(|s| crate::ui::colors::parse_color(s));
@@ -73,10 +89,66 @@ pub fn draw_confirm_panel(
     title_style = title_style.bg(tb);
   }
   block = block.title(Span::styled(state.title.clone(), title_style));
+  let info_style = Style::default().fg(Color::DarkGray);
+  if !state.details.is_empty()
+  {
+    let rows = popup.height.saturating_sub(question_rows + 3) as usize;
+    state.page_height = rows.max(1);
+    state.scroll =
+      state.scroll.min(state.details.len().saturating_sub(state.page_height));
+    let position = if state.details.len() > rows
+    {
+      format!(
+        " {}-{} of {} ",
+        state.scroll + 1,
+        (state.scroll + rows).min(state.details.len()),
+        state.details.len()
+      )
+    }
+    else
+    {
+      String::new()
+    };
+    block = block
+      .title_bottom(Span::styled(
+        " y confirm  n cancel  j/k scroll ",
+        info_style,
+      ))
+      .title_bottom(
+        Line::from(Span::styled(position, info_style)).right_aligned(),
+      );
+  }
   let inner = block.inner(popup);
   f.render_widget(block, popup);
-  let lines: Vec<Line> =
-    vec![Line::from(""), Line::from(Span::raw(state.question.clone()))];
+  if !state.details.is_empty()
+  {
+    let mut lines: Vec<Line> = state
+      .question
+      .lines()
+      .map(|l| Line::from(Span::raw(l.to_string())))
+      .collect();
+    lines.push(Line::from(""));
+    let width = inner.width as usize;
+    lines.extend(
+      state.details.iter().skip(state.scroll).take(state.page_height).map(
+        |d| {
+          Line::from(Span::raw(crate::ui::row::truncate_with_tilde(d, width)))
+        },
+      ),
+    );
+    f.render_widget(Paragraph::new(lines), inner);
+    return;
+  }
+  // One-line questions sit below a blank line; longer ones use it
+  let mut lines: Vec<Line> = state
+    .question
+    .lines()
+    .map(|l| Line::from(Span::raw(l.to_string())))
+    .collect();
+  if lines.len() < 2
+  {
+    lines.insert(0, Line::from(""));
+  }
   let para = Paragraph::new(lines).wrap(Wrap { trim: true });
   f.render_widget(para, inner);
 }
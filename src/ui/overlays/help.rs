@@ -0,0 +1,100 @@
+use ratatui::{
+  layout::Rect,
+  style::{
+    Color,
+    Modifier,
+    Style,
+  },
+  text::{
+    Line,
+    Span,
+  },
+  widgets::{
+    Block,
+    Borders,
+    Clear,
+    Paragraph,
+  },
+};
+
+/// Draw the `:help` text in a centered popup. Takes the app mutably to
+/// record the visible height for paging and keep the scroll in range.
+pub fn draw_help_panel(
+  f: &mut ratatui::Frame,
+  area: Rect,
+  app: &mut crate::App,
+)
+{
+  let theme = app.config.ui.theme.clone();
+  let crate::app::Overlay::Help(ref mut state) = app.overlay
+  else
+  {
+    return;
+  };
+
+  let popup_width =
+    (area.width.saturating_mul(80) / 100).max(40).min(area.width);
+  let popup_height =
+    (area.height.saturating_mul(80) / 100).max(6).min(area.height);
+  let popup = Rect::new(
+    area.x + area.width.saturating_sub(popup_width) / 2,
+    area.y + area.height.saturating_sub(popup_height) / 2,
+    popup_width,
+    popup_height,
+  );
+  f.render_widget(Clear, popup);
+
+  let theme = theme.as_ref();
+  let color =
+    |v: Option<&String>| v.and_then(|s| crate::ui::colors::parse_color(s));
+  let title_fg =
+    color(theme.and_then(|th| th.title_fg.as_ref())).unwrap_or(Color::Yellow);
+  let item_style = Style::default()
+    .fg(color(theme.and_then(|th| th.item_fg.as_ref())).unwrap_or(Color::Gray));
+  let info_style = Style::default().fg(
+    color(theme.and_then(|th| th.info_fg.as_ref())).unwrap_or(Color::DarkGray),
+  );
+  let heading = Style::default().fg(title_fg).add_modifier(Modifier::BOLD);
+
+  let rows = popup.height.saturating_sub(2) as usize;
+  state.page_height = rows.max(1);
+  state.scroll = state.scroll.min(state.lines.len().saturating_sub(rows));
+
+  let mut block = Block::default().borders(Borders::ALL);
+  if let Some(bg) = color(theme.and_then(|th| th.pane_bg.as_ref()))
+  {
+    block = block.style(Style::default().bg(bg));
+  }
+  if let Some(bfg) = color(theme.and_then(|th| th.border_fg.as_ref()))
+  {
+    block = block.border_style(Style::default().fg(bfg));
+  }
+  let position = format!(
+    " {}-{} of {} ",
+    (state.scroll + 1).min(state.lines.len()),
+    (state.scroll + rows).min(state.lines.len()),
+    state.lines.len()
+  );
+  block = block.title(Span::styled(" Help ", heading)).title_bottom(
+    Line::from(Span::styled(position, info_style)).right_aligned(),
+  );
+
+  // Section headings are the unindented lines
+  let lines: Vec<Line> = state
+    .lines
+    .iter()
+    .skip(state.scroll)
+    .take(rows)
+    .map(|l| {
+      if l.starts_with(' ')
+      {
+        Line::from(Span::styled(l.clone(), item_style))
+      }
+      else
+      {
+        Line::from(Span::styled(l.clone(), heading))
+      }
+    })
+    .collect();
+  f.render_widget(Paragraph::new(lines).block(block), popup);
+}
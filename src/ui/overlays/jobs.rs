@@ -0,0 +1,185 @@
+use ratatui::{
+  layout::{
+    Constraint,
+    Direction,
+    Layout,
+    Rect,
+  },
+  style::{
+    Color,
+    Modifier,
+    Style,
+  },
+  text::{
+    Line,
+    Span,
+  },
+  widgets::{
+    Block,
+    Borders,
+    Clear,
+    List,
+    ListItem,
+    ListState,
+    Paragraph,
+  },
+};
+
+use crate::{
+  app::{
+    Job,
+    JobStatus,
+  },
+  ui::format::human_size,
+};
+
+const BAR_WIDTH: usize = 20;
+
+fn progress_text(job: &Job) -> String
+{
+  let p = &job.progress;
+  let ratio = if p.bytes_total > 0
+  {
+    p.bytes_done as f64 / p.bytes_total as f64
+  }
+  else if p.files_total > 0
+  {
+    p.files_done as f64 / p.files_total as f64
+  }
+  else
+  {
+    0.0
+  }
+  .clamp(0.0, 1.0);
+  let filled = (ratio * BAR_WIDTH as f64).round() as usize;
+  let mut out = format!(
+    "[{}{}] {:>3}%  {}/{} files",
+    "#".repeat(filled),
+    "-".repeat(BAR_WIDTH - filled),
+    (ratio * 100.0).round() as u64,
+    p.files_done,
+    p.files_total
+  );
+  if p.bytes_total > 0
+  {
+    out.push_str(&format!(
+      "  {} / {}",
+      human_size(p.bytes_done),
+      human_size(p.bytes_total)
+    ));
+  }
+  out
+}
+
+pub fn draw_jobs_panel(
+  f: &mut ratatui::Frame,
+  area: Rect,
+  app: &crate::App,
+)
+{
+  let selected = match app.overlay
+  {
+    crate::app::Overlay::Jobs { selected } => selected,
+    _ => return,
+  };
+  let jobs = &app.jobs.jobs;
+
+  let popup_width =
+    (area.width.saturating_mul(80) / 100).max(40).min(area.width);
+  let popup_height =
+    (area.height.saturating_mul(50) / 100).max(6).min(area.height);
+  let popup = Rect::new(
+    area.x + area.width.saturating_sub(popup_width) / 2,
+    area.y + area.height.saturating_sub(popup_height) / 2,
+    popup_width,
+    popup_height,
+  );
+  f.render_widget(Clear, popup);
+
+  let theme = app.config.ui.theme.as_ref();
+  let color =
+    |v: Option<&String>| v.and_then(|s| crate::ui::colors::parse_color(s));
+  let mut block = Block::default().borders(Borders::ALL);
+  if let Some(bg) = color(theme.and_then(|th| th.pane_bg.as_ref()))
+  {
+    block = block.style(Style::default().bg(bg));
+  }
+  if let Some(bfg) = color(theme.and_then(|th| th.border_fg.as_ref()))
+  {
+    block = block.border_style(Style::default().fg(bfg));
+  }
+  let title_fg =
+    color(theme.and_then(|th| th.title_fg.as_ref())).unwrap_or(Color::Yellow);
+  block = block.title(Span::styled(
+    " Jobs ",
+    Style::default().fg(title_fg).add_modifier(Modifier::BOLD),
+  ));
+
+  let inner = block.inner(popup);
+  f.render_widget(block, popup);
+  if inner.width == 0 || inner.height == 0
+  {
+    return;
+  }
+  let chunks = Layout::default()
+    .direction(Direction::Vertical)
+    .constraints([Constraint::Min(1), Constraint::Length(1)])
+    .split(inner);
+
+  let item_style = Style::default()
+    .fg(color(theme.and_then(|th| th.item_fg.as_ref())).unwrap_or(Color::Gray));
+  let info_style = Style::default().fg(
+    color(theme.and_then(|th| th.info_fg.as_ref())).unwrap_or(Color::DarkGray),
+  );
+  let mut highlight = Style::default().add_modifier(Modifier::BOLD);
+  if let Some(bg) = color(theme.and_then(|th| th.selected_item_bg.as_ref()))
+  {
+    highlight = highlight.bg(bg);
+  }
+
+  if jobs.is_empty()
+  {
+    f.render_widget(Paragraph::new("No jobs").style(info_style), chunks[0]);
+  }
+  else
+  {
+    let items: Vec<ListItem> = jobs
+      .iter()
+      .map(|job| {
+        let (status, status_style) = match job.status
+        {
+          JobStatus::Queued => ("queued", info_style),
+          JobStatus::Running => ("running", Style::default().fg(Color::Cyan)),
+          JobStatus::Done => ("done", Style::default().fg(Color::Green)),
+          JobStatus::Failed => ("failed", Style::default().fg(Color::Red)),
+          JobStatus::Cancelled => ("cancelled", info_style),
+        };
+        let what = match job.items.len()
+        {
+          1 => job.items[0]
+            .file_name()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default(),
+          n => format!("{} items", n),
+        };
+        ListItem::new(Line::from(vec![
+          Span::styled(
+            format!("#{:<3} {:<6} {:<20} ", job.id, job.kind.label(), what),
+            item_style,
+          ),
+          Span::styled(format!("{:<10}", status), status_style),
+          Span::styled(progress_text(job), info_style),
+        ]))
+      })
+      .collect();
+    let mut list_state = ListState::default();
+    list_state.select(Some(selected.min(jobs.len() - 1)));
+    let list = List::new(items).highlight_style(highlight);
+    f.render_stateful_widget(list, chunks[0], &mut list_state);
+  }
+
+  let hint = Paragraph::new("↑/↓ select  c cancel  Esc close")
+    .style(info_style)
+    .alignment(ratatui::layout::Alignment::Center);
+  f.render_widget(hint, chunks[1]);
+}
@@ -10,7 +10,10 @@ use ratatui::{
     Modifier,
     Style,
   },
-  text::Span,
+  text::{
+    Line,
+    Span,
+  },
   widgets::{
     Block,
     Borders,
@@ -20,10 +23,12 @@ use ratatui::{
   },
 };
 
+use crate::app::MessageLevel;
+
 pub fn draw_messages_panel(
   f: &mut ratatui::Frame,
   area: Rect,
-  app: &crate::App,
+  app: &mut crate::App,
 )
 {
   let min_h = ((area.height as u32 * 20) / 100).max(3) as u16;
@@ -56,16 +61,34 @@ pub fn draw_messages_panel(
   f.render_widget(Clear, panel);
 
   let avail_rows = panel_h.saturating_sub(2) as usize;
-  let start = app.recent_messages.len().saturating_sub(avail_rows);
-  let slice = &app.recent_messages[start..];
-  let mut lines: Vec<ratatui::text::Line> = Vec::new();
-  for m in slice
+  app.text_view.page_height = avail_rows.max(1);
+  let start = app.text_view_offset();
+  let messages = &app.recent_messages;
+  if let Some(status) =
+    super::output::text_view_status(app, messages.len(), start, avail_rows)
   {
-    lines.push(ratatui::text::Line::from(Span::styled(
-      m.clone(),
-      Style::default().fg(Color::Gray),
-    )));
+    block = block.title_bottom(status.right_aligned());
   }
-  let para = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+  let body =
+    super::output::text_view_body(app, messages, start, avail_rows, |m| {
+      let color = match m.level
+      {
+        MessageLevel::Info => Color::Gray,
+        MessageLevel::Warn => Color::Yellow,
+        MessageLevel::Error => Color::Red,
+      };
+      Line::from(vec![
+        Span::styled(
+          format!("{} ", m.time.format("%H:%M:%S")),
+          Style::default().fg(Color::DarkGray),
+        ),
+        Span::styled(
+          format!("{:<5} ", m.level.as_str()),
+          Style::default().fg(color).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(m.text.as_str(), Style::default().fg(color)),
+      ])
+    });
+  let para = Paragraph::new(body).block(block).wrap(Wrap { trim: true });
   f.render_widget(para, panel);
 }
@@ -1,15 +1,39 @@
+pub mod bookmarks;
 pub mod command;
 pub mod confirm;
+pub mod finder;
+pub mod grep;
+pub mod help;
+pub mod jobs;
 pub mod messages;
+pub mod mounts;
+pub mod notify;
 pub mod output;
+pub mod palette;
 pub mod prompt;
+pub mod registers;
+pub mod select;
+pub mod selections;
 pub mod theme_picker;
+pub mod trash;
 pub mod whichkey;
 
+pub use bookmarks::draw_bookmarks_panel;
 pub use command::draw_command_pane;
 pub use confirm::draw_confirm_panel;
+pub use finder::draw_finder_panel;
+pub use grep::draw_grep_panel;
+pub use help::draw_help_panel;
+pub use jobs::draw_jobs_panel;
 pub use messages::draw_messages_panel;
+pub use mounts::draw_mounts_panel;
+pub use notify::draw_notifications;
 pub use output::draw_output_panel;
+pub use palette::draw_palette_panel;
 pub use prompt::draw_prompt_panel;
+pub use registers::draw_registers_panel;
+pub use select::draw_select_panel;
+pub use selections::draw_selections_panel;
 pub use theme_picker::draw_theme_picker_panel;
+pub use trash::draw_trash_panel;
 pub use whichkey::draw_whichkey_panel;
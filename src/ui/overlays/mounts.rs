@@ -0,0 +1,160 @@
+use ratatui::{
+  layout::{
+    Constraint,
+    Direction,
+    Layout,
+    Rect,
+  },
+  style::{
+    Color,
+    Modifier,
+    Style,
+  },
+  text::{
+    Line,
+    Span,
+  },
+  widgets::{
+    Block,
+    Borders,
+    Clear,
+    List,
+    ListItem,
+    ListState,
+    Paragraph,
+  },
+};
+
+use crate::core::mounts::MountPoint;
+
+/// Cells in a usage bar.
+const BAR_WIDTH: usize = 10;
+
+/// `[######----]  61%  12.3 GB free of 32 GB`, or nothing without figures.
+fn usage_text(m: &MountPoint) -> String
+{
+  let (Some(total), Some(avail), Some(used)) =
+    (m.total, m.avail, m.used_fraction())
+  else
+  {
+    return String::new();
+  };
+  let filled = ((used * BAR_WIDTH as f64).round() as usize).min(BAR_WIDTH);
+  format!(
+    "[{}{}] {:>3}%  {} free of {}",
+    "#".repeat(filled),
+    "-".repeat(BAR_WIDTH - filled),
+    (used * 100.0).round() as u64,
+    crate::ui::format::human_size(avail),
+    crate::ui::format::human_size(total),
+  )
+}
+
+pub fn draw_mounts_panel(
+  f: &mut ratatui::Frame,
+  area: Rect,
+  app: &crate::App,
+)
+{
+  let state = match app.overlay
+  {
+    crate::app::Overlay::Mounts(ref s) => s.as_ref(),
+    _ => return,
+  };
+
+  let popup_width =
+    (area.width.saturating_mul(80) / 100).max(30).min(area.width);
+  let popup_height =
+    (area.height.saturating_mul(60) / 100).max(6).min(area.height);
+  let popup = Rect::new(
+    area.x + area.width.saturating_sub(popup_width) / 2,
+    area.y + area.height.saturating_sub(popup_height) / 2,
+    popup_width,
+    popup_height,
+  );
+  f.render_widget(Clear, popup);
+
+  let theme = app.config.ui.theme.as_ref();
+  let color =
+    |v: Option<&String>| v.and_then(|s| crate::ui::colors::parse_color(s));
+  let mut block = Block::default().borders(Borders::ALL);
+  if let Some(bg) = color(theme.and_then(|th| th.pane_bg.as_ref()))
+  {
+    block = block.style(Style::default().bg(bg));
+  }
+  if let Some(bfg) = color(theme.and_then(|th| th.border_fg.as_ref()))
+  {
+    block = block.border_style(Style::default().fg(bfg));
+  }
+  let title_fg =
+    color(theme.and_then(|th| th.title_fg.as_ref())).unwrap_or(Color::Yellow);
+  let title = format!(" Mounts ({}) ", state.entries.len());
+  block = block.title(Span::styled(
+    title,
+    Style::default().fg(title_fg).add_modifier(Modifier::BOLD),
+  ));
+
+  let inner = block.inner(popup);
+  f.render_widget(block, popup);
+  if inner.width == 0 || inner.height == 0
+  {
+    return;
+  }
+
+  let chunks = Layout::default()
+    .direction(Direction::Vertical)
+    .constraints([Constraint::Min(1), Constraint::Length(1)])
+    .split(inner);
+
+  let item_style = Style::default()
+    .fg(color(theme.and_then(|th| th.item_fg.as_ref())).unwrap_or(Color::Gray));
+  let info_style = Style::default().fg(
+    color(theme.and_then(|th| th.info_fg.as_ref())).unwrap_or(Color::DarkGray),
+  );
+  let mut highlight = Style::default().add_modifier(Modifier::BOLD);
+  if let Some(fg) = color(theme.and_then(|th| th.selected_item_fg.as_ref()))
+  {
+    highlight = highlight.fg(fg);
+  }
+  if let Some(bg) = color(theme.and_then(|th| th.selected_item_bg.as_ref()))
+  {
+    highlight = highlight.bg(bg);
+  }
+
+  // Line the usage bars up after the longest mount point
+  let path_width = state
+    .entries
+    .iter()
+    .map(|m| m.path.display().to_string().chars().count())
+    .max()
+    .unwrap_or(0)
+    .min(inner.width as usize / 2);
+  let items: Vec<ListItem> = state
+    .entries
+    .iter()
+    .map(|m| {
+      let kind = match m.fs_type
+      {
+        Some(ref t) => format!("{} {}", t, m.device),
+        None => m.device.clone(),
+      };
+      ListItem::new(Line::from(vec![
+        Span::styled(
+          format!("{:<w$}  ", m.path.display(), w = path_width),
+          item_style,
+        ),
+        Span::styled(format!("{}  ", usage_text(m)), item_style),
+        Span::styled(kind, info_style),
+      ]))
+    })
+    .collect();
+  let mut list_state = ListState::default();
+  list_state.select(Some(state.selected));
+  let list = List::new(items).highlight_style(highlight);
+  f.render_stateful_widget(list, chunks[0], &mut list_state);
+
+  let hint = Paragraph::new("Enter go to mount point  Esc close")
+    .style(info_style)
+    .alignment(ratatui::layout::Alignment::Center);
+  f.render_widget(hint, chunks[1]);
+}
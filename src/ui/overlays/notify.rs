@@ -0,0 +1,72 @@
+use ratatui::{
+  layout::Rect,
+  style::{
+    Color,
+    Style,
+  },
+  text::Span,
+  widgets::{
+    Block,
+    Borders,
+    Clear,
+    Paragraph,
+  },
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::app::MessageLevel;
+
+/// Widest a note gets, borders included.
+const MAX_WIDTH: u16 = 48;
+
+/// Draw notifications stacked up from the bottom-right corner of `area`,
+/// the newest lowest.
+pub fn draw_notifications(
+  f: &mut ratatui::Frame,
+  area: Rect,
+  app: &crate::App,
+)
+{
+  let notes = app.notifications();
+  let theme = app.config.ui.theme.as_ref();
+  let bg = theme
+    .and_then(|th| th.pane_bg.as_ref())
+    .and_then(|s| crate::ui::colors::parse_color(s));
+  let mut bottom = area.bottom();
+  for note in notes.iter().rev()
+  {
+    if bottom < area.y + 3
+    {
+      break;
+    }
+    let color = match note.level
+    {
+      MessageLevel::Info => Color::Cyan,
+      MessageLevel::Warn => Color::Yellow,
+      MessageLevel::Error => Color::Red,
+    };
+    let width = (note.text.width() as u16 + 4).min(MAX_WIDTH).min(area.width);
+    let rect = Rect::new(area.right() - width, bottom - 3, width, 3);
+    bottom -= 3;
+    let text = crate::ui::row::truncate_with_tilde(
+      &note.text,
+      width.saturating_sub(4) as usize,
+    );
+    let mut block = Block::default()
+      .borders(Borders::ALL)
+      .border_style(Style::default().fg(color));
+    if let Some(bg) = bg
+    {
+      block = block.style(Style::default().bg(bg));
+    }
+    f.render_widget(Clear, rect);
+    f.render_widget(
+      Paragraph::new(Span::styled(
+        format!(" {}", text),
+        Style::default().fg(color),
+      ))
+      .block(block),
+      rect,
+    );
+  }
+}
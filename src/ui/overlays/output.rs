@@ -10,7 +10,10 @@ use ratatui::{
     Modifier,
     Style,
   },
-  text::Span,
+  text::{
+    Line,
+    Span,
+  },
   widgets::{
     Block,
     Borders,
@@ -20,10 +23,60 @@ use ratatui::{
   },
 };
 
+/// Bottom title of the messages and output panels: the search being typed,
+/// or which lines are in view when the text does not fit.
+pub(crate) fn text_view_status(
+  app: &crate::App,
+  total: usize,
+  start: usize,
+  rows: usize,
+) -> Option<Line<'static>>
+{
+  let text = match app.text_view.input
+  {
+    Some(ref input) => format!(" /{} ", input),
+    None if total > rows =>
+    {
+      format!(" {}-{} of {} ", start + 1, (start + rows).min(total), total)
+    }
+    None => return None,
+  };
+  Some(Line::from(Span::styled(text, Style::default().fg(Color::DarkGray))))
+}
+
+/// Lines `start..start + rows` of `lines`, with the current search match
+/// shown reversed.
+pub(crate) fn text_view_body<'a, T>(
+  app: &crate::App,
+  lines: &'a [T],
+  start: usize,
+  rows: usize,
+  render: impl Fn(&'a T) -> Line<'a>,
+) -> Vec<Line<'a>>
+{
+  lines
+    .iter()
+    .enumerate()
+    .skip(start)
+    .take(rows)
+    .map(|(i, l)| {
+      let line = render(l);
+      if app.text_view.current == Some(i)
+      {
+        line.patch_style(Style::default().add_modifier(Modifier::REVERSED))
+      }
+      else
+      {
+        line
+      }
+    })
+    .collect()
+}
+
 pub fn draw_output_panel(
   f: &mut ratatui::Frame,
   area: Rect,
-  app: &crate::App,
+  app: &mut crate::App,
 )
 {
   let (title, lines): (String, Vec<String>) = match app.overlay.clone()
@@ -61,13 +114,25 @@ pub fn draw_output_panel(
   f.render_widget(Clear, panel);
 
   let avail_rows = panel_h.saturating_sub(2) as usize;
-  let start = lines.len().saturating_sub(avail_rows);
-  let slice = &lines[start..];
-  let mut lines: Vec<ratatui::text::Line> = Vec::new();
-  for m in slice
+  app.text_view.page_height = avail_rows.max(1);
+  let start = app.text_view_offset();
+  if let Some(status) = app.command_run_status()
+  {
+    block = block.title(
+      Line::from(Span::styled(
+        format!(" {} ", status),
+        Style::default().fg(Color::DarkGray),
+      ))
+      .right_aligned(),
+    );
+  }
+  if let Some(status) = text_view_status(app, lines.len(), start, avail_rows)
   {
-    lines.push(ratatui::text::Line::from(crate::ui::ansi::ansi_spans(m)));
+    block = block.title_bottom(status.right_aligned());
   }
-  let para = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+  let body = text_view_body(app, &lines, start, avail_rows, |m| {
+    Line::from(crate::ui::ansi::ansi_spans(m))
+  });
+  let para = Paragraph::new(body).block(block).wrap(Wrap { trim: true });
   f.render_widget(para, panel);
 }
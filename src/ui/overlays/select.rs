@@ -0,0 +1,126 @@
+use ratatui::{
+  layout::{
+    Constraint,
+    Direction,
+    Layout,
+    Rect,
+  },
+  style::{
+    Color,
+    Modifier,
+    Style,
+  },
+  text::{
+    Line,
+    Span,
+  },
+  widgets::{
+    Block,
+    Borders,
+    Clear,
+    List,
+    ListItem,
+    ListState,
+    Paragraph,
+  },
+};
+use unicode_width::UnicodeWidthStr;
+
+/// Draw the list opened by `lsv.ui.select`: the query above the matching
+/// items.
+pub fn draw_select_panel(
+  f: &mut ratatui::Frame,
+  area: Rect,
+  app: &crate::App,
+)
+{
+  let state = match app.overlay
+  {
+    crate::app::Overlay::Select(ref s) => s.as_ref(),
+    _ => return,
+  };
+
+  let popup_width =
+    (area.width.saturating_mul(60) / 100).max(40).min(area.width);
+  let popup_height =
+    (area.height.saturating_mul(60) / 100).max(6).min(area.height);
+  let popup = Rect::new(
+    area.x + area.width.saturating_sub(popup_width) / 2,
+    area.y + area.height.saturating_sub(popup_height) / 2,
+    popup_width,
+    popup_height,
+  );
+  f.render_widget(Clear, popup);
+
+  let theme = app.config.ui.theme.as_ref();
+  let color =
+    |v: Option<&String>| v.and_then(|s| crate::ui::colors::parse_color(s));
+  let mut block = Block::default().borders(Borders::ALL);
+  if let Some(bg) = color(theme.and_then(|th| th.pane_bg.as_ref()))
+  {
+    block = block.style(Style::default().bg(bg));
+  }
+  if let Some(bfg) = color(theme.and_then(|th| th.border_fg.as_ref()))
+  {
+    block = block.border_style(Style::default().fg(bfg));
+  }
+  let title_fg =
+    color(theme.and_then(|th| th.title_fg.as_ref())).unwrap_or(Color::Yellow);
+  block = block.title(Span::styled(
+    format!(" {} ", state.title),
+    Style::default().fg(title_fg).add_modifier(Modifier::BOLD),
+  ));
+
+  let inner = block.inner(popup);
+  f.render_widget(block, popup);
+  if inner.width == 0 || inner.height < 2
+  {
+    return;
+  }
+  let chunks = Layout::default()
+    .direction(Direction::Vertical)
+    .constraints([Constraint::Length(1), Constraint::Min(1)])
+    .split(inner);
+
+  let item_style = Style::default()
+    .fg(color(theme.and_then(|th| th.item_fg.as_ref())).unwrap_or(Color::Gray));
+  let info_style = Style::default().fg(
+    color(theme.and_then(|th| th.info_fg.as_ref())).unwrap_or(Color::DarkGray),
+  );
+  let mut highlight = Style::default().add_modifier(Modifier::BOLD);
+  if let Some(fg) = color(theme.and_then(|th| th.selected_item_fg.as_ref()))
+  {
+    highlight = highlight.fg(fg);
+  }
+  if let Some(bg) = color(theme.and_then(|th| th.selected_item_bg.as_ref()))
+  {
+    highlight = highlight.bg(bg);
+  }
+
+  let counter = format!("  {}/{}", state.results.len(), state.items.len());
+  let query = Line::from(vec![
+    Span::styled("> ", Style::default().fg(title_fg)),
+    Span::styled(state.input.clone(), item_style),
+    Span::styled(counter, info_style),
+  ]);
+  f.render_widget(Paragraph::new(query), chunks[0]);
+  let cursor_x = chunks[0].x + 2 + state.input.width() as u16;
+  if cursor_x < chunks[0].x + chunks[0].width
+  {
+    f.set_cursor_position((cursor_x, chunks[0].y));
+  }
+
+  let items: Vec<ListItem> = state
+    .results
+    .iter()
+    .filter_map(|&i| state.items.get(i))
+    .map(|item| ListItem::new(Span::styled(item.clone(), item_style)))
+    .collect();
+  let mut list_state = ListState::default();
+  if !items.is_empty()
+  {
+    list_state.select(Some(state.selected));
+  }
+  let list = List::new(items).highlight_style(highlight);
+  f.render_stateful_widget(list, chunks[1], &mut list_state);
+}
@@ -0,0 +1,137 @@
+use std::path::Path;
+
+use ratatui::{
+  layout::{
+    Constraint,
+    Direction,
+    Layout,
+    Rect,
+  },
+  style::{
+    Color,
+    Modifier,
+    Style,
+  },
+  text::{
+    Line,
+    Span,
+  },
+  widgets::{
+    Block,
+    Borders,
+    Clear,
+    List,
+    ListItem,
+    ListState,
+    Paragraph,
+  },
+};
+
+pub fn draw_selections_panel(
+  f: &mut ratatui::Frame,
+  area: Rect,
+  app: &crate::App,
+)
+{
+  let selected = match app.overlay
+  {
+    crate::app::Overlay::Selections { selected } => selected,
+    _ => return,
+  };
+  let paths = app.get_selected_paths();
+
+  let popup_width =
+    (area.width.saturating_mul(70) / 100).max(40).min(area.width);
+  let popup_height =
+    (area.height.saturating_mul(60) / 100).max(6).min(area.height);
+  let popup = Rect::new(
+    area.x + area.width.saturating_sub(popup_width) / 2,
+    area.y + area.height.saturating_sub(popup_height) / 2,
+    popup_width,
+    popup_height,
+  );
+  f.render_widget(Clear, popup);
+
+  let theme = app.config.ui.theme.as_ref();
+  let color =
+    |v: Option<&String>| v.and_then(|s| crate::ui::colors::parse_color(s));
+  let mut block = Block::default().borders(Borders::ALL);
+  if let Some(bg) = color(theme.and_then(|th| th.pane_bg.as_ref()))
+  {
+    block = block.style(Style::default().bg(bg));
+  }
+  if let Some(bfg) = color(theme.and_then(|th| th.border_fg.as_ref()))
+  {
+    block = block.border_style(Style::default().fg(bfg));
+  }
+  let title_fg =
+    color(theme.and_then(|th| th.title_fg.as_ref())).unwrap_or(Color::Yellow);
+  block = block.title(Span::styled(
+    format!(" Selections ({}) ", paths.len()),
+    Style::default().fg(title_fg).add_modifier(Modifier::BOLD),
+  ));
+
+  let inner = block.inner(popup);
+  f.render_widget(block, popup);
+  if inner.width == 0 || inner.height == 0
+  {
+    return;
+  }
+  let chunks = Layout::default()
+    .direction(Direction::Vertical)
+    .constraints([Constraint::Min(1), Constraint::Length(1)])
+    .split(inner);
+
+  let item_style = Style::default()
+    .fg(color(theme.and_then(|th| th.item_fg.as_ref())).unwrap_or(Color::Gray));
+  let dir_style = Style::default()
+    .fg(color(theme.and_then(|th| th.dir_fg.as_ref())).unwrap_or(Color::Blue))
+    .add_modifier(Modifier::BOLD);
+  let info_style = Style::default().fg(
+    color(theme.and_then(|th| th.info_fg.as_ref())).unwrap_or(Color::DarkGray),
+  );
+  let mut highlight = Style::default().add_modifier(Modifier::BOLD);
+  if let Some(bg) = color(theme.and_then(|th| th.selected_item_bg.as_ref()))
+  {
+    highlight = highlight.bg(bg);
+  }
+
+  // A heading row per directory, then its selected entries by name
+  let mut items: Vec<ListItem> = Vec::new();
+  let mut cursor_row = 0;
+  let mut last_dir: Option<&Path> = None;
+  for (i, path) in paths.iter().enumerate()
+  {
+    let dir = path.parent();
+    if i == 0 || dir != last_dir
+    {
+      let heading = dir.map(|d| d.display().to_string()).unwrap_or_default();
+      items.push(ListItem::new(Span::styled(heading, dir_style)));
+      last_dir = dir;
+    }
+    if i == selected
+    {
+      cursor_row = items.len();
+    }
+    let name = path
+      .file_name()
+      .map(|s| s.to_string_lossy().into_owned())
+      .unwrap_or_else(|| path.display().to_string());
+    let mut spans = vec![Span::styled(format!("  {}", name), item_style)];
+    if path.is_dir()
+    {
+      spans.push(Span::styled("/", info_style));
+    }
+    items.push(ListItem::new(Line::from(spans)));
+  }
+  let mut list_state = ListState::default();
+  list_state.select(Some(cursor_row));
+  let list = List::new(items).highlight_style(highlight);
+  f.render_stateful_widget(list, chunks[0], &mut list_state);
+
+  let hint =
+    Paragraph::new("↑/↓ select  Enter go  d unselect  c clear all  Esc close")
+      .style(info_style)
+      .alignment(ratatui::layout::Alignment::Center);
+  f.render_widget(hint, chunks[1]);
+}
@@ -10,7 +10,10 @@ use ratatui::{
     Modifier,
     Style,
   },
-  text::Span,
+  text::{
+    Line,
+    Span,
+  },
   widgets::{
     Block,
     Borders,
@@ -23,6 +26,109 @@ use ratatui::{
 };
 use unicode_width::UnicodeWidthStr;
 
+/// Shown after themes that come with lsv rather than the config directory.
+const BUILTIN_TAG: &str = "  built-in";
+
+/// Columns added to the default popup width for the sample listing.
+const SAMPLE_WIDTH: u16 = 28;
+
+/// Entries of the sample listing: name, directory, executable. The third
+/// row is drawn as the selected one.
+const SAMPLE_ROWS: &[(&str, bool, bool)] = &[
+  ("docs", true, false),
+  ("src", true, false),
+  ("Cargo.toml", false, false),
+  ("README.md", false, false),
+  ("build.sh", false, true),
+  (".gitignore", false, false),
+];
+const SAMPLE_SELECTED: usize = 2;
+
+/// A miniature pane drawn with `th`: a header row, entries styled as the
+/// listing would style them with one selected, and an info line.
+fn draw_sample(
+  f: &mut ratatui::Frame,
+  area: Rect,
+  th: &crate::config::UiTheme,
+)
+{
+  let color = |s: &Option<String>| {
+    s.as_ref().and_then(|s| crate::ui::colors::parse_color(s))
+  };
+  let mut block = Block::default().borders(Borders::ALL);
+  if let Some(bg) = color(&th.pane_bg)
+  {
+    block = block.style(Style::default().bg(bg));
+  }
+  if let Some(fg) = color(&th.border_fg)
+  {
+    block = block.border_style(Style::default().fg(fg));
+  }
+  let inner = block.inner(area);
+  f.render_widget(block, area);
+  if inner.width == 0 || inner.height == 0
+  {
+    return;
+  }
+
+  let mut title = Style::default().add_modifier(Modifier::BOLD);
+  if let Some(fg) = color(&th.title_fg)
+  {
+    title = title.fg(fg);
+  }
+  if let Some(bg) = color(&th.title_bg)
+  {
+    title = title.bg(bg);
+  }
+  let width = inner.width as usize;
+  let mut lines =
+    vec![Line::from(Span::styled(format!("{:<width$}", " ~/project"), title))];
+
+  let mut item = Style::default();
+  if let Some(fg) = color(&th.item_fg)
+  {
+    item = item.fg(fg);
+  }
+  if let Some(bg) = color(&th.item_bg)
+  {
+    item = item.bg(bg);
+  }
+  let mut selected = Style::default().add_modifier(Modifier::BOLD);
+  if let Some(fg) = color(&th.selected_item_fg)
+  {
+    selected = selected.fg(fg);
+  }
+  if let Some(bg) = color(&th.selected_item_bg)
+  {
+    selected = selected.bg(bg);
+  }
+  for (i, (name, is_dir, is_exec)) in SAMPLE_ROWS.iter().enumerate()
+  {
+    let label =
+      if *is_dir { format!(" {}/", name) } else { format!(" {}", name) };
+    let hidden = name.starts_with('.');
+    let mut style = item.patch(crate::ui::row::theme_entry_style(
+      th, name, *is_dir, *is_exec, hidden,
+    ));
+    if i == SAMPLE_SELECTED
+    {
+      style = style.patch(selected);
+    }
+    lines.push(Line::from(Span::styled(format!("{:<width$}", label), style)));
+  }
+
+  let mut info = Style::default().fg(Color::DarkGray);
+  if let Some(fg) = color(&th.info_fg)
+  {
+    info = info.fg(fg);
+  }
+  lines.push(Line::from(Span::styled(
+    format!(" {} items", SAMPLE_ROWS.len()),
+    info,
+  )));
+  f.render_widget(Paragraph::new(lines), inner);
+}
+
 pub fn draw_theme_picker_panel(
   f: &mut ratatui::Frame,
   area: Rect,
@@ -42,7 +148,10 @@ pub fn draw_theme_picker_panel(
   let max_name_width = state
     .entries
     .iter()
-    .map(|e| UnicodeWidthStr::width(e.name.as_str()))
+    .map(|e| {
+      let tag = if e.path.is_none() { BUILTIN_TAG.len() } else { 0 };
+      UnicodeWidthStr::width(e.name.as_str()) + tag
+    })
     .max()
     .unwrap_or(0);
   let (popup_width, popup_height) = if let Some(m) =
@@ -57,13 +166,15 @@ pub fn draw_theme_picker_panel(
   }
   else
   {
-    let base_width = (max_name_width as u16).saturating_add(6);
+    let base_width =
+      (max_name_width as u16).saturating_add(6).saturating_add(SAMPLE_WIDTH);
     let desired_width = base_width.max(30);
     let w = desired_width
       .min(area.width.saturating_sub(4).max(20))
       .min(area.width)
       .max(10);
-    let entries_len = state.entries.len() as u16;
+    let entries_len =
+      (state.entries.len() as u16).max(SAMPLE_ROWS.len() as u16 + 4);
     let desired_height = entries_len.saturating_add(4);
     let h = desired_height
       .min(area.height.saturating_sub(4).max(6))
@@ -161,7 +272,17 @@ pub fn draw_theme_picker_panel(
   let items: Vec<ListItem> = state
     .entries
     .iter()
-    .map(|entry| ListItem::new(ratatui::text::Line::from(entry.name.clone())))
+    .map(|entry| {
+      let mut spans = vec![Span::raw(entry.name.clone())];
+      if entry.path.is_none()
+      {
+        spans.push(Span::styled(
+          BUILTIN_TAG,
+          Style::default().fg(Color::DarkGray),
+        ));
+      }
+      ListItem::new(ratatui::text::Line::from(spans))
+    })
     .collect();
 
   let constraints: Vec<Constraint> = if inner.height > 3
@@ -176,7 +297,20 @@ pub fn draw_theme_picker_panel(
     .direction(Direction::Vertical)
     .constraints(constraints)
     .split(inner);
-  let list_area = chunks[0];
+  let mut list_area = chunks[0];
+  let list_width = (max_name_width as u16).saturating_add(2);
+  if list_area.width >= list_width + SAMPLE_WIDTH / 2
+  {
+    let cols = Layout::default()
+      .direction(Direction::Horizontal)
+      .constraints([Constraint::Length(list_width), Constraint::Min(1)])
+      .split(list_area);
+    list_area = cols[0];
+    if let Some(entry) = state.entries.get(state.selected)
+    {
+      draw_sample(f, cols[1], &entry.theme);
+    }
+  }
 
   let mut list_state = ListState::default();
   list_state.select(Some(state.selected));
@@ -0,0 +1,129 @@
+use ratatui::{
+  layout::{
+    Constraint,
+    Direction,
+    Layout,
+    Rect,
+  },
+  style::{
+    Color,
+    Modifier,
+    Style,
+  },
+  text::{
+    Line,
+    Span,
+  },
+  widgets::{
+    Block,
+    Borders,
+    Clear,
+    List,
+    ListItem,
+    ListState,
+    Paragraph,
+  },
+};
+
+pub fn draw_trash_panel(
+  f: &mut ratatui::Frame,
+  area: Rect,
+  app: &crate::App,
+)
+{
+  let state = match app.overlay
+  {
+    crate::app::Overlay::Trash(ref s) => s.as_ref(),
+    _ => return,
+  };
+
+  let popup_width =
+    (area.width.saturating_mul(70) / 100).max(30).min(area.width);
+  let popup_height =
+    (area.height.saturating_mul(60) / 100).max(6).min(area.height);
+  let popup = Rect::new(
+    area.x + area.width.saturating_sub(popup_width) / 2,
+    area.y + area.height.saturating_sub(popup_height) / 2,
+    popup_width,
+    popup_height,
+  );
+  f.render_widget(Clear, popup);
+
+  let theme = app.config.ui.theme.as_ref();
+  let color =
+    |v: Option<&String>| v.and_then(|s| crate::ui::colors::parse_color(s));
+  let mut block = Block::default().borders(Borders::ALL);
+  if let Some(bg) = color(theme.and_then(|th| th.pane_bg.as_ref()))
+  {
+    block = block.style(Style::default().bg(bg));
+  }
+  if let Some(bfg) = color(theme.and_then(|th| th.border_fg.as_ref()))
+  {
+    block = block.border_style(Style::default().fg(bfg));
+  }
+  let title_fg =
+    color(theme.and_then(|th| th.title_fg.as_ref())).unwrap_or(Color::Yellow);
+  let title = format!(" Trash ({}) ", state.entries.len());
+  block = block.title(Span::styled(
+    title,
+    Style::default().fg(title_fg).add_modifier(Modifier::BOLD),
+  ));
+
+  let inner = block.inner(popup);
+  f.render_widget(block, popup);
+  if inner.width == 0 || inner.height == 0
+  {
+    return;
+  }
+
+  let chunks = Layout::default()
+    .direction(Direction::Vertical)
+    .constraints([Constraint::Min(1), Constraint::Length(1)])
+    .split(inner);
+
+  let item_style = Style::default()
+    .fg(color(theme.and_then(|th| th.item_fg.as_ref())).unwrap_or(Color::Gray));
+  let info_style = Style::default().fg(
+    color(theme.and_then(|th| th.info_fg.as_ref())).unwrap_or(Color::DarkGray),
+  );
+  let mut highlight = Style::default().add_modifier(Modifier::BOLD);
+  if let Some(fg) = color(theme.and_then(|th| th.selected_item_fg.as_ref()))
+  {
+    highlight = highlight.fg(fg);
+  }
+  if let Some(bg) = color(theme.and_then(|th| th.selected_item_bg.as_ref()))
+  {
+    highlight = highlight.bg(bg);
+  }
+
+  if state.entries.is_empty()
+  {
+    f.render_widget(
+      Paragraph::new("Trash is empty").style(info_style),
+      chunks[0],
+    );
+  }
+  else
+  {
+    let items: Vec<ListItem> = state
+      .entries
+      .iter()
+      .map(|e| {
+        let when = e.deleted_at.as_deref().unwrap_or("-").replace('T', " ");
+        ListItem::new(Line::from(vec![
+          Span::styled(format!("{}  ", when), info_style),
+          Span::styled(e.original_path.display().to_string(), item_style),
+        ]))
+      })
+      .collect();
+    let mut list_state = ListState::default();
+    list_state.select(Some(state.selected));
+    let list = List::new(items).highlight_style(highlight);
+    f.render_stateful_widget(list, chunks[0], &mut list_state);
+  }
+
+  let hint = Paragraph::new("Enter/r restore  D delete forever  Esc close")
+    .style(info_style)
+    .alignment(ratatui::layout::Alignment::Center);
+  f.render_widget(hint, chunks[1]);
+}
@@ -23,167 +23,109 @@ use ratatui::{
 };
 use unicode_width::UnicodeWidthStr;
 
-pub fn draw_whichkey_panel(
-  f: &mut ratatui::Frame,
-  area: Rect,
-  app: &crate::App,
-)
+fn tokenize_seq(s: &str) -> Vec<String>
 {
-  fn tokenize_seq(s: &str) -> Vec<String>
+  let mut out = Vec::new();
+  let mut i = 0;
+  let b = s.as_bytes();
+  while i < b.len()
   {
-    let mut out = Vec::new();
-    let mut i = 0;
-    let b = s.as_bytes();
-    while i < b.len()
+    if b[i] == b'<'
+      && let Some(j) = s[i + 1..].find('>')
     {
-      if b[i] == b'<'
-        && let Some(j) = s[i + 1..].find('>')
-      {
-        let end = i + 1 + j + 1;
-        out.push(s[i..end].to_string());
-        i = end;
-        continue;
-      }
-      let ch = s[i..].chars().next().unwrap();
-      out.push(ch.to_string());
-      i += ch.len_utf8();
+      let end = i + 1 + j + 1;
+      out.push(s[i..end].to_string());
+      i = end;
+      continue;
     }
-    out
+    let ch = s[i..].chars().next().unwrap();
+    out.push(ch.to_string());
+    i += ch.len_utf8();
   }
+  out
+}
 
-  fn format_token(tok: &str) -> String
+fn format_token(tok: &str) -> String
+{
+  match tok
   {
-    match tok
+    " " => "Space".to_string(),
+    "\t" => "Tab".to_string(),
+    "\n" => "Enter".to_string(),
+    "<Esc>" => "Escape".to_string(),
+    _ =>
     {
-      " " => "Space".to_string(),
-      "\t" => "Tab".to_string(),
-      "\n" => "Enter".to_string(),
-      "<Esc>" => "Escape".to_string(),
-      _ =>
+      if tok.starts_with('<') && tok.ends_with('>')
       {
-        if tok.starts_with('<') && tok.ends_with('>')
+        let inner = &tok[1..tok.len() - 1];
+        if let Some(rest) = inner.strip_prefix("C-")
+        {
+          return format!("Ctrl-{}", rest);
+        }
+        if let Some(rest) = inner.strip_prefix("M-")
         {
-          let inner = &tok[1..tok.len() - 1];
-          if let Some(rest) = inner.strip_prefix("C-")
-          {
-            return format!("Ctrl-{}", rest);
-          }
-          if let Some(rest) = inner.strip_prefix("M-")
-          {
-            return format!("Alt-{}", rest);
-          }
-          if let Some(rest) = inner.strip_prefix("S-")
-          {
-            return format!("Super-{}", rest);
-          }
-          if let Some(rest) = inner.strip_prefix("Sh-")
-          {
-            return format!("Shift-{}", rest);
-          }
+          return format!("Alt-{}", rest);
         }
-        tok.to_string()
+        if let Some(rest) = inner.strip_prefix("S-")
+        {
+          return format!("Super-{}", rest);
+        }
+        if let Some(rest) = inner.strip_prefix("Sh-")
+        {
+          return format!("Shift-{}", rest);
+        }
+        return match inner
+        {
+          "CR" => "Enter".to_string(),
+          "BS" => "Backspace".to_string(),
+          other => other.to_string(),
+        };
       }
+      tok.to_string()
     }
   }
+}
 
-  fn format_seq_for_display(s: &str) -> String
-  {
-    let toks = tokenize_seq(s);
-    let formatted: Vec<String> =
-      toks.into_iter().map(|t| format_token(&t)).collect();
-    formatted.join("")
-  }
-
-  use std::collections::HashMap;
-  let mut map: HashMap<&str, (&str, &str)> = HashMap::new();
-  for km in &app.keys.maps
-  {
-    let label = km.description.as_deref().unwrap_or(km.action.as_str());
-    map.insert(km.sequence.as_str(), (km.sequence.as_str(), label));
-  }
+/// Key sequence as shown to users: `<C-d>` reads as `Ctrl-d`, `<CR>` as
+/// `Enter`.
+pub(crate) fn format_seq_for_display(s: &str) -> String
+{
+  let toks = tokenize_seq(s);
+  let formatted: Vec<String> =
+    toks.into_iter().map(|t| format_token(&t)).collect();
+  formatted.join("")
+}
 
+pub fn draw_whichkey_panel(
+  f: &mut ratatui::Frame,
+  area: Rect,
+  app: &crate::App,
+)
+{
   let prefix = match app.overlay
   {
     crate::app::Overlay::WhichKey { ref prefix } => prefix.as_str(),
     _ => "",
   };
-  let mut buckets: HashMap<String, Vec<(&str, &str)>> = HashMap::new();
-  let prefix_toks = tokenize_seq(prefix);
-  for (seq, (_, label)) in map.into_iter()
-  {
-    let seq_toks = tokenize_seq(seq);
-    if seq_toks.len() > prefix_toks.len()
-    {
-      if seq_toks[..prefix_toks.len()] == prefix_toks[..]
-      {
-        let mut np_toks = prefix_toks.clone();
-        np_toks.push(seq_toks[prefix_toks.len()].clone());
-        let np = np_toks.concat();
-        buckets.entry(np).or_default().push((seq, label));
-      }
-    }
-    else if seq_toks.len() == prefix_toks.len() && seq_toks == prefix_toks
-    {
-      buckets.entry(seq.to_string()).or_default().push((seq, label));
-    }
-  }
-
-  #[derive(Clone)]
-  struct Entry
-  {
-    left:     String,
-    right:    String,
-    is_group: bool,
-  }
-  let mut entries: Vec<Entry> = Vec::new();
-  let mut keys: Vec<String> = buckets.keys().cloned().collect();
-  keys.sort();
-  for k in keys
-  {
-    let list = buckets.get(&k).unwrap();
-    let mut exact_only = false;
-    if list.len() == 1
-    {
-      let (seq, _) = list[0];
-      if seq == k
-      {
-        exact_only = true;
-      }
-    }
-    if exact_only
-    {
-      let (_seq, label) = list[0];
-      entries.push(Entry {
-        left:     format_seq_for_display(&k),
-        right:    label.to_string(),
-        is_group: false,
-      });
-    }
-    else
-    {
-      let n = list.len();
-      let label = if n == 1
-      {
-        "(1 binding)".to_string()
-      }
-      else
-      {
-        format!("({} bindings)", n)
-      };
-      entries.push(Entry {
-        left:     format_seq_for_display(&k),
-        right:    label,
-        is_group: true,
-      });
-    }
-  }
+  let entries: Vec<Entry> = whichkey_entries(app, prefix)
+    .into_iter()
+    .map(|e| Entry {
+      left: e
+        .keys
+        .iter()
+        .map(|k| format_seq_for_display(k))
+        .collect::<Vec<_>>()
+        .join(", "),
+      ..e
+    })
+    .collect();
 
   if entries.is_empty()
   {
     return;
   }
 
-  let title_str = if prefix.is_empty()
+  let mut title_str = if prefix.is_empty()
   {
     "Keys".to_string()
   }
@@ -191,6 +133,10 @@ pub fn draw_whichkey_panel(
   {
     format!("Keys: prefix '{}'", format_seq_for_display(prefix))
   };
+  if let Some(n) = app.pending_count
+  {
+    title_str.push_str(&format!(" (count {})", n));
+  }
   let mut block = Block::default().borders(Borders::ALL).title(Span::styled(
     title_str,
     Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
@@ -280,7 +226,7 @@ pub fn draw_whichkey_panel(
         Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD);
       let right_style = if e.is_group
       {
-        Style::default().fg(Color::DarkGray)
+        Style::default().fg(Color::Magenta)
       }
       else
       {
@@ -314,3 +260,143 @@ pub fn draw_whichkey_panel(
   let para = Paragraph::new(lines).block(block);
   f.render_widget(para, panel);
 }
+
+/// One line of the which-key overlay: the next keys to press and what they
+/// do, or the name of the group of sequences they start.
+struct Entry
+{
+  /// Keys after the prefix; several when they run the same action.
+  keys:     Vec<String>,
+  left:     String,
+  right:    String,
+  is_group: bool,
+}
+
+/// Label for an action without a description: `cmd:find_files` reads as
+/// "find files", Lua actions as "Lua action".
+pub(crate) fn action_label(action: &str) -> String
+{
+  if action.starts_with("run_lua:")
+  {
+    return String::from("Lua action");
+  }
+  if action.starts_with("shell:")
+  {
+    return String::from("Shell command");
+  }
+  action.strip_prefix("cmd:").unwrap_or(action).replace('_', " ")
+}
+
+/// Sort position of a key: letters in alphabetical order with lowercase
+/// before uppercase, then other characters, then named keys like `<C-d>`.
+fn key_order(key: &str) -> (u8, String, bool)
+{
+  let first = key.chars().next().unwrap_or(' ');
+  let class = if key.starts_with('<') && key.len() > 1
+  {
+    2
+  }
+  else if first.is_alphanumeric()
+  {
+    0
+  }
+  else
+  {
+    1
+  };
+  (class, key.to_lowercase(), first.is_uppercase())
+}
+
+/// Action and label bound to a key, if the key completes a sequence.
+type ExactBinding<'a> = Option<(&'a str, String)>;
+
+/// Entries for the keys that can follow `prefix`, sorted by key. A key that
+/// only starts longer sequences becomes a group named by `keys.groups`;
+/// keys bound to the same action share one entry.
+fn whichkey_entries(
+  app: &crate::App,
+  prefix: &str,
+) -> Vec<Entry>
+{
+  use std::collections::BTreeMap;
+
+  let prefix_toks = crate::keymap::tokenize_sequence(prefix);
+  // Next token after the prefix -> (exact action and label, longer bindings)
+  let mut next: BTreeMap<String, (ExactBinding, usize)> = BTreeMap::new();
+  for km in &app.keys.maps
+  {
+    let seq = crate::keymap::normalize_sequence(&km.sequence);
+    let toks = crate::keymap::tokenize_sequence(&seq);
+    if toks.len() <= prefix_toks.len()
+      || toks[..prefix_toks.len()] != prefix_toks
+    {
+      continue;
+    }
+    let slot = next.entry(toks[prefix_toks.len()].clone()).or_default();
+    if toks.len() == prefix_toks.len() + 1
+    {
+      let label =
+        km.description.clone().unwrap_or_else(|| action_label(&km.action));
+      // Later mappings override earlier ones, as in the lookup table
+      slot.0 = Some((km.action.as_str(), label));
+    }
+    else
+    {
+      slot.1 += 1;
+    }
+  }
+
+  let mut entries: Vec<Entry> = Vec::new();
+  let mut by_action: std::collections::HashMap<(&str, String), usize> =
+    std::collections::HashMap::new();
+  for (key, (exact, longer)) in next
+  {
+    match exact
+    {
+      // An exact binding wins over longer sequences when typed
+      Some((action, label)) =>
+      {
+        if let Some(&i) = by_action.get(&(action, label.clone()))
+        {
+          entries[i].keys.push(key);
+          continue;
+        }
+        by_action.insert((action, label.clone()), entries.len());
+        entries.push(Entry {
+          keys:     vec![key],
+          left:     String::new(),
+          right:    label,
+          is_group: false,
+        });
+      }
+      None =>
+      {
+        let full = format!("{}{}", prefix, key);
+        let name = app
+          .config
+          .keys
+          .groups
+          .iter()
+          .find(|(p, _)| crate::keymap::normalize_sequence(p) == full)
+          .map(|(_, name)| name.clone());
+        let right = match name
+        {
+          Some(name) => format!("+{}", name),
+          None => format!("+{} keys", longer),
+        };
+        entries.push(Entry {
+          keys: vec![key],
+          left: String::new(),
+          right,
+          is_group: true,
+        });
+      }
+    }
+  }
+  for e in &mut entries
+  {
+    e.keys.sort_by_key(|k| key_order(k));
+  }
+  entries.sort_by_key(|e| key_order(&e.keys[0]));
+  entries
+}
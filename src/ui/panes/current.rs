@@ -5,15 +5,38 @@ use ratatui::{
     Modifier,
     Style,
   },
+  text::Span,
   widgets::{
     Block,
     Borders,
     Clear,
     List,
     ListItem,
+    ListState,
   },
 };
 
+use crate::config::LineNumbers;
+
+/// First row shown of a `len`-row list `height` rows high, last scrolled to
+/// `offset`, once it has scrolled `selected` into view the way ratatui's
+/// `List` does.
+fn scroll_offset(
+  offset: usize,
+  selected: Option<usize>,
+  len: usize,
+  height: usize,
+) -> usize
+{
+  let offset = offset.min(len.saturating_sub(1));
+  match selected
+  {
+    Some(sel) if sel < offset => sel,
+    Some(sel) if height > 0 && sel >= offset + height => sel + 1 - height,
+    _ => offset,
+  }
+}
+
 pub fn draw_current_panel(
   f: &mut ratatui::Frame,
   area: Rect,
@@ -22,6 +45,33 @@ pub fn draw_current_panel(
 {
   f.render_widget(Clear, area);
   let mut block = Block::default().borders(Borders::ALL);
+  if app.current_load.is_some()
+  {
+    // Partial listing while a background read is still streaming
+    let mut st = Style::default().fg(Color::DarkGray);
+    if let Some(fg) = app
+      .config
+      .ui
+      .theme
+      .as_ref()
+      .and_then(|th| th.info_fg.as_ref())
+      .and_then(|s| crate::ui::colors::parse_color(s))
+    {
+      st = st.fg(fg);
+    }
+    block = block.title(Span::styled(
+      format!(" loading… ({}) ", app.current_entries.len()),
+      st,
+    ));
+  }
+  else if let Some(depth) = app.flatten_depth()
+  {
+    block = block.title(format!(
+      " flat: {} files, depth {} ",
+      app.current_entries.len(),
+      depth
+    ));
+  }
   if let Some(th) = app.config.ui.theme.as_ref()
   {
     if let Some(bg) =
@@ -38,11 +88,64 @@ pub fn draw_current_panel(
   f.render_widget(block.clone(), area);
   let inner = block.inner(area);
   let fmt = app.config.ui.row.clone().unwrap_or_default();
-  let items: Vec<ListItem> = app
-    .current_entries
+  let numbers = app.config.ui.line_numbers;
+  let cursor = app.list_state.selected().unwrap_or(0);
+  // Wide enough for the largest number, plus a space
+  let gutter_w = match numbers
+  {
+    LineNumbers::None => 0,
+    _ => app.current_entries.len().max(1).to_string().len() + 1,
+  };
+  let mut number_style = Style::default().fg(Color::DarkGray);
+  if let Some(fg) = app
+    .config
+    .ui
+    .theme
+    .as_ref()
+    .and_then(|th| th.info_fg.as_ref())
+    .and_then(|s| crate::ui::colors::parse_color(s))
+  {
+    number_style = number_style.fg(fg);
+  }
+  let row_width = inner.width.saturating_sub(gutter_w as u16);
+
+  // Only the rows in view are built, however long the listing
+  let len = app.current_entries.len();
+  if len == 0
+  {
+    app.list_state.select(None);
+  }
+  else if app.list_state.selected().is_some_and(|s| s >= len)
+  {
+    app.list_state.select(Some(len - 1));
+  }
+  let start = scroll_offset(
+    app.list_state.offset(),
+    app.list_state.selected(),
+    len,
+    inner.height as usize,
+  );
+  let end = (start + inner.height as usize).min(len);
+  let items: Vec<ListItem> = app.current_entries[start..end]
     .iter()
-    .map(|e| {
-      ListItem::new(crate::ui::row::build_row_line(app, &fmt, e, inner.width))
+    .enumerate()
+    .map(|(j, e)| {
+      let i = start + j;
+      let mut line = crate::ui::row::build_row_line(app, &fmt, e, row_width);
+      let number = match numbers
+      {
+        LineNumbers::None => None,
+        LineNumbers::Relative if i != cursor => Some(i.abs_diff(cursor)),
+        _ => Some(i + 1),
+      };
+      if let Some(n) = number
+      {
+        line.spans.insert(
+          0,
+          Span::styled(format!("{:>w$} ", n, w = gutter_w - 1), number_style),
+        );
+      }
+      ListItem::new(line)
     })
     .collect();
 
@@ -89,5 +192,8 @@ pub fn draw_current_panel(
     );
   }
 
-  f.render_stateful_widget(list, list_area, &mut app.list_state);
+  let mut state = ListState::default()
+    .with_selected(app.list_state.selected().map(|s| s.saturating_sub(start)));
+  f.render_stateful_widget(list, list_area, &mut state);
+  *app.list_state.offset_mut() = start;
 }
@@ -18,9 +18,27 @@ pub fn pane_constraints(app: &crate::App) -> [Constraint; 3]
       Constraint::Percentage(30),
     ];
   }
+  // Hidden panes give their width to the current pane; the tree view
+  // replaces the parent pane
+  if !app.config.ui.show_parent || app.tree_active()
+  {
+    c = c.saturating_add(p);
+    p = 0;
+  }
+  if !app.config.ui.show_preview
+  {
+    c = c.saturating_add(r);
+    r = 0;
+  }
   let p_norm = (p as u32 * 100 / total as u32) as u16;
-  let c_norm = (c as u32 * 100 / total as u32) as u16;
-  let r_norm = 100u16.saturating_sub(p_norm).saturating_sub(c_norm);
+  let mut c_norm = (c as u32 * 100 / total as u32) as u16;
+  let mut r_norm = 100u16.saturating_sub(p_norm).saturating_sub(c_norm);
+  // Rounding leftovers go to the current pane when the preview is hidden
+  if r == 0
+  {
+    c_norm += r_norm;
+    r_norm = 0;
+  }
   [
     Constraint::Percentage(p_norm),
     Constraint::Percentage(c_norm),
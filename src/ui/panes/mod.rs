@@ -4,12 +4,24 @@ pub use super::{
     human_size,
   },
   overlays::{
+    draw_bookmarks_panel,
     draw_command_pane,
     draw_confirm_panel,
+    draw_finder_panel,
+    draw_grep_panel,
+    draw_help_panel,
+    draw_jobs_panel,
     draw_messages_panel,
+    draw_mounts_panel,
+    draw_notifications,
     draw_output_panel,
+    draw_palette_panel,
     draw_prompt_panel,
+    draw_registers_panel,
+    draw_select_panel,
+    draw_selections_panel,
     draw_theme_picker_panel,
+    draw_trash_panel,
     draw_whichkey_panel,
   },
   row::{
@@ -41,9 +41,11 @@ pub fn draw_parent_panel(
     width:  inner.width,
     height: inner.height,
   };
+  // The parent pane is not scrolled: build only the rows that fit
   let items: Vec<ListItem> = app
     .parent_entries
     .iter()
+    .take(list_area.height as usize)
     .map(|e| {
       ListItem::new(crate::ui::row::build_row_line(app, &fmt, e, inner_width))
     })
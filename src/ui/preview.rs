@@ -1,6 +1,8 @@
 use std::{
+  io::Read,
   path::Path,
-  process::Command,
+  process::Stdio,
+  sync::mpsc,
 };
 
 use ratatui::{
@@ -22,10 +24,22 @@ use ratatui::{
   },
 };
 
-use crate::ui::ansi::ansi_spans;
+use crate::{
+  app::PreviewContent,
+  core::{
+    archive::ArchiveListing,
+    mime,
+  },
+  ui::{
+    ansi::ansi_spans,
+    format::human_size,
+  },
+};
 use mlua::Value as LuaValue;
 
 const PREVIEW_LINES_LIMIT: usize = 1000;
+/// How much of a remote file is fetched for its preview.
+const REMOTE_PREVIEW_BYTES: usize = 64 * 1024;
 
 pub fn draw_preview_panel(
   f: &mut ratatui::Frame,
@@ -35,21 +49,93 @@ pub fn draw_preview_panel(
 {
   f.render_widget(Clear, area);
   let mut dynamic_lines: Option<Vec<String>> = None;
-  if let Some(sel) = app.selected_entry()
+  let mut loading = false;
+  if let Some((path, is_dir)) =
+    app.selected_entry().map(|e| (e.path.clone(), e.is_dir))
   {
-    if !sel.is_dir
+    // Directories go through the same cache when Lua can preview them
+    let dir_hook = is_dir
+      && !app.config.dir_previewers.is_empty()
+      && !crate::core::remote::is_remote(&path);
+    if !is_dir || dir_hook
     {
-      let key = (sel.path.clone(), area.width, area.height);
+      let key = (path.clone(), area.width, area.height);
       if app.preview.cache_key.as_ref() == Some(&key)
       {
         dynamic_lines = app.preview.cache_lines.clone();
       }
-      else
+      else if crate::core::vfs::is_member(&path)
       {
-        dynamic_lines =
-          run_previewer(app, &sel.path, area, PREVIEW_LINES_LIMIT);
+        // Archive members are not on disk; previewer commands cannot read
+        // them
         app.preview.cache_key = Some(key);
-        app.preview.cache_lines = dynamic_lines.clone();
+        app.preview.cache_lines = None;
+      }
+      else if app.pending_preview.as_ref().is_some_and(|p| p.key == key)
+      {
+        loading = true;
+      }
+      else
+      {
+        let meta = std::fs::metadata(&path).ok();
+        let mtime = meta.as_ref().and_then(|m| m.modified().ok());
+        let size = meta.as_ref().map(|m| m.len()).unwrap_or(0);
+        if let Some(lines) = app.preview.recent.get(&(key.clone(), mtime, size))
+        {
+          // Seen recently at this size; no need to run the previewer again
+          dynamic_lines = lines.clone();
+          app.pending_preview = None;
+          app.preview.cache_key = Some(key);
+          app.preview.cache_lines = dynamic_lines.clone();
+        }
+        else if let Some(rp) = crate::core::remote::RemotePath::parse(&path)
+        {
+          app.pending_preview =
+            Some(spawn_preview(key, (mtime, size), move || {
+              Some(remote_preview(&rp, PREVIEW_LINES_LIMIT))
+            }));
+          loading = true;
+        }
+        else
+        {
+          let job = if is_dir
+          {
+            dir_previewer_command(app, &path, area)
+          }
+          else
+          {
+            previewer_command(app, &path, area)
+          };
+          match job
+          {
+            Some(job) =>
+            {
+              // Replacing an older pending preview drops its receiver
+              app.pending_preview =
+                Some(spawn_preview(key, (mtime, size), move || {
+                  run_previewer_command(&job, PREVIEW_LINES_LIMIT)
+                }));
+              loading = true;
+            }
+            None
+              if matches!(
+                app.preview.content,
+                PreviewContent::Thumbnail(_)
+              ) && app.preview.offset_path.as_ref() == Some(&path) =>
+            {
+              let job = thumbnail_job(app, &path, area);
+              app.pending_preview =
+                Some(spawn_preview(key, (mtime, size), job));
+              loading = true;
+            }
+            None =>
+            {
+              app.pending_preview = None;
+              app.preview.cache_key = Some(key);
+              app.preview.cache_lines = None;
+            }
+          }
+        }
       }
     }
     else
@@ -72,20 +158,52 @@ pub fn draw_preview_panel(
       block = block.border_style(Style::default().fg(bfg));
     }
   }
+  if app.preview.focused || app.preview.nowrap
+  {
+    // Say how lines are laid out, and where, when it isn't the default
+    let mut title =
+      String::from(if app.preview.nowrap { " nowrap" } else { " wrap" });
+    if app.preview.nowrap && app.preview.hscroll > 0
+    {
+      title.push_str(&format!(" +{}", app.preview.hscroll));
+    }
+    title.push(' ');
+    let title_fg = app
+      .config
+      .ui
+      .theme
+      .as_ref()
+      .and_then(|th| th.title_fg.as_ref())
+      .and_then(|s| crate::ui::colors::parse_color(s))
+      .unwrap_or(Color::Cyan);
+    block = block.title(Span::styled(title, Style::default().fg(title_fg)));
+    if app.preview.focused
+    {
+      block = block.border_style(Style::default().fg(title_fg));
+    }
+  }
 
   let text: Vec<Line> = if let Some(sel) = app.selected_entry()
   {
-    if sel.is_dir
+    if loading
+    {
+      vec![Line::from(Span::styled(
+        "loading preview…",
+        Style::default().fg(Color::DarkGray),
+      ))]
+    }
+    else if sel.is_dir && dynamic_lines.is_none()
     {
       let block_inner = block.inner(area);
       let inner_w = block_inner.width;
       let fmt = app.config.ui.row.clone().unwrap_or_default();
-      let list = app.read_dir_sorted(&sel.path).unwrap_or_default();
-      let limit = PREVIEW_LINES_LIMIT.min(list.len());
-      list
-        .into_iter()
-        .take(limit)
-        .map(|e| crate::ui::panes::build_row_line(app, &fmt, &e, inner_w))
+      // Only the first screenful is read and drawn
+      let limit = PREVIEW_LINES_LIMIT.min(block_inner.height as usize);
+      app
+        .read_dir_head(&sel.path, limit)
+        .unwrap_or_default()
+        .iter()
+        .map(|e| crate::ui::panes::build_row_line(app, &fmt, e, inner_w))
         .collect()
     }
     else if let Some(lines) = dynamic_lines.as_ref()
@@ -102,6 +220,11 @@ pub fn draw_preview_panel(
         lines.iter().map(|l| Line::from(ansi_spans(l))).collect()
       }
     }
+    else if let PreviewContent::ArchiveListing(ref listing) =
+      app.preview.content
+    {
+      archive_lines(app, listing)
+    }
     else if app.preview.static_lines.is_empty()
     {
       vec![Line::from(Span::styled(
@@ -131,7 +254,37 @@ pub fn draw_preview_panel(
     app.preview.static_lines.iter().map(|l| Line::from(ansi_spans(l))).collect()
   };
 
-  let mut para = Paragraph::new(text).block(block).wrap(Wrap { trim: true });
+  // Apply the scroll offset; remember the geometry for page steps. The
+  // placeholder shown while loading must not clamp the offset away.
+  let inner_h = area.height.saturating_sub(2) as usize;
+  app.preview.page_height = inner_h.max(1);
+  app.preview.page_width = area.width.saturating_sub(2) as usize;
+  let text: Vec<Line> = if loading
+  {
+    text
+  }
+  else
+  {
+    app.preview.total_lines = text.len();
+    app.preview.line_width = text.iter().map(Line::width).max().unwrap_or(0);
+    app.preview.offset = app.preview.offset.min(text.len().saturating_sub(1));
+    text.into_iter().skip(app.preview.offset).collect()
+  };
+
+  let mut para = Paragraph::new(text).block(block);
+  para = if app.preview.nowrap
+  {
+    let max = app.preview.line_width.saturating_sub(app.preview.page_width);
+    if !loading
+    {
+      app.preview.hscroll = app.preview.hscroll.min(max);
+    }
+    para.scroll((0, app.preview.hscroll.min(u16::MAX as usize) as u16))
+  }
+  else
+  {
+    para.wrap(Wrap { trim: true })
+  };
   if let Some(th) = app.config.ui.theme.as_ref()
   {
     let mut st = Style::default();
@@ -150,170 +303,352 @@ pub fn draw_preview_panel(
   f.render_widget(para, area);
 }
 
-fn run_previewer(
+/// Render an archive listing as a summary line followed by one row per
+/// member: size, modification date and path.
+fn archive_lines(
+  app: &crate::App,
+  listing: &ArchiveListing,
+) -> Vec<Line<'static>>
+{
+  let theme = app.config.ui.theme.as_ref();
+  let color =
+    |v: Option<&String>| v.and_then(|s| crate::ui::colors::parse_color(s));
+  let info_style = Style::default().fg(
+    color(theme.and_then(|t| t.info_fg.as_ref())).unwrap_or(Color::DarkGray),
+  );
+  let dir_style = Style::default()
+    .fg(color(theme.and_then(|t| t.dir_fg.as_ref())).unwrap_or(Color::Blue));
+  let date_fmt =
+    app.config.ui.date_format.as_deref().unwrap_or("%Y-%m-%d %H:%M");
+
+  let total: u64 = listing.entries.iter().map(|e| e.size).sum();
+  let mut lines = vec![Line::from(Span::styled(
+    format!(
+      "{} archive: {}{} entries, {}",
+      listing.kind.label(),
+      listing.entries.len(),
+      if listing.truncated { "+" } else { "" },
+      human_size(total)
+    ),
+    info_style,
+  ))];
+  for e in &listing.entries
+  {
+    let size = if e.is_dir { "-".to_string() } else { human_size(e.size) };
+    let date =
+      e.modified.map(|d| d.format(date_fmt).to_string()).unwrap_or_default();
+    let name = crate::util::sanitize_line(&e.name);
+    lines.push(Line::from(vec![
+      Span::styled(format!("{:>9}  {:<16}  ", size, date), info_style),
+      if e.is_dir { Span::styled(name, dir_style) } else { Span::raw(name) },
+    ]));
+  }
+  if listing.truncated
+  {
+    lines.push(Line::from(Span::styled("…", info_style)));
+  }
+  lines
+}
+
+/// Previewer command resolved on the UI thread, ready to run elsewhere.
+struct PreviewJob
+{
+  cmd:       String,
+  dir:       String,
+  path:      String,
+  shell:     crate::config::CommandShell,
+  /// The command is killed when it runs longer or prints more than this.
+  timeout:   std::time::Duration,
+  max_bytes: usize,
+}
+
+/// Run a preview job on a background thread; the result is picked up by
+/// [`crate::App::poll_preview`].
+fn spawn_preview(
+  key: (std::path::PathBuf, u16, u16),
+  (mtime, size): (Option<std::time::SystemTime>, u64),
+  job: impl FnOnce() -> Option<Vec<String>> + Send + 'static,
+) -> crate::app::PendingPreview
+{
+  let (tx, rx) = std::sync::mpsc::channel();
+  std::thread::spawn(move || {
+    // The receiver is gone when the selection moved on; nothing to do
+    let _ = tx.send(job());
+  });
+  crate::app::PendingPreview { key, mtime, size, rx }
+}
+
+/// Text preview of a remote file: its first bytes, read over `ssh`.
+fn remote_preview(
+  rp: &crate::core::remote::RemotePath,
+  limit: usize,
+) -> Vec<String>
+{
+  match crate::core::remote::head(rp, REMOTE_PREVIEW_BYTES)
+  {
+    Err(e) => vec![format!("<error reading remote file: {}>", e)],
+    Ok(bytes) if bytes.contains(&0) => vec![String::from("<binary file>")],
+    Ok(bytes) => String::from_utf8_lossy(&bytes)
+      .lines()
+      .take(limit)
+      .map(crate::util::sanitize_line)
+      .collect(),
+  }
+}
+
+/// Make (or reuse) the thumbnail of `path` and draw it to fit the pane.
+fn thumbnail_job(
   app: &crate::App,
   path: &Path,
   area: Rect,
-  limit: usize,
-) -> Option<Vec<String>>
+) -> impl FnOnce() -> Option<Vec<String>> + Send + 'static
 {
-  if let Some(lua) = app.lua.as_ref()
-    && let (engine, Some(key)) = (&lua.engine, lua.previewer.as_ref())
+  use crate::core::thumbnail;
+  let cfg = &app.config.ui.thumbnails;
+  let cmd = match app.preview.content
   {
-    let lua = engine.lua();
-    if let Ok(func) = lua.registry_value::<mlua::Function>(key)
+    PreviewContent::Thumbnail(kind) =>
     {
-      let path_str = path.to_string_lossy().to_string();
-      let dir_str = path
-        .parent()
-        .unwrap_or_else(|| Path::new("."))
-        .to_string_lossy()
-        .to_string();
-      let ext =
-        path.extension().and_then(|s| s.to_str()).unwrap_or("").to_string();
-      let is_binary = file_is_binary(path);
-      let name_now = path
-        .file_name()
-        .map(|s| s.to_string_lossy().to_string())
-        .unwrap_or_default();
-      if let Ok(ctx) = lua.create_table()
-      {
-        let _ = ctx.set("current_file", path_str.clone());
-        let _ = ctx.set("current_file_dir", dir_str.clone());
-        let _ = ctx.set("current_file_name", name_now.clone());
-        let _ = ctx.set("current_file_extension", ext.clone());
-        let _ = ctx.set("is_binary", is_binary);
-        let _ = ctx.set("preview_height", area.height as i64);
-        let _ = ctx.set("preview_width", area.width as i64);
-        let _ = ctx.set("preview_x", area.x as i64);
-        let _ = ctx.set("preview_y", area.y as i64);
+      thumbnail::command(cfg, kind).map(String::from)
+    }
+    _ => None,
+  };
+  let view = cfg.view.clone();
+  let sh = app.config.command_shell.clone();
+  let protocol = app.image_protocol.as_str();
+  let path = path.to_path_buf();
+  let (width, height) =
+    (area.width.saturating_sub(2), area.height.saturating_sub(2));
+  move || {
+    let dir = thumbnail::cache_dir()?;
+    let image = thumbnail::ensure_thumbnail(&sh, &cmd?, &path, &dir)?;
+    thumbnail::render(&sh, &view, &image, width, height, protocol)
+  }
+}
+
+impl PreviewJob
+{
+  fn new(
+    app: &crate::App,
+    cmd: String,
+    dir: String,
+    path: String,
+  ) -> Self
+  {
+    let ui = &app.config.ui;
+    Self {
+      cmd,
+      dir,
+      path,
+      shell: app.config.command_shell.clone(),
+      timeout: std::time::Duration::from_millis(ui.preview_timeout_ms),
+      max_bytes: ui.preview_max_bytes,
+    }
+  }
+}
+
+/// Command of the first `previewers` rule matching `path`, with `{file}`,
+/// `{width}` and `{height}` filled in for the inside of `area`.
+fn rule_command(
+  app: &crate::App,
+  path: &Path,
+  area: Rect,
+) -> Option<PreviewJob>
+{
+  let rule = crate::core::opener::find_previewer(&app.config.previewers, path)?;
+  let path_str = path.to_string_lossy().to_string();
+  let dir_str = path
+    .parent()
+    .unwrap_or_else(|| Path::new("."))
+    .to_string_lossy()
+    .to_string();
+  let mut ph = app.placeholders(&[path.to_path_buf()]);
+  ph.file = Some(path.to_path_buf());
+  ph.extra = vec![
+    ("width".into(), area.width.saturating_sub(2).to_string()),
+    ("height".into(), area.height.saturating_sub(2).to_string()),
+  ];
+  let cmd =
+    crate::core::shell::expand(&app.config.command_shell, &rule.cmd, &ph);
+  crate::trace::log(format!(
+    "[preview] rule cmd='{}' cwd='{}' file='{}'",
+    cmd, dir_str, path_str
+  ));
+  Some(PreviewJob::new(app, cmd, dir_str, path_str))
+}
+
+/// Command for `path` from the `previewers` rules or else the Lua
+/// previewers. Lua state is not `Send`, so this runs on the UI thread; only
+/// the command itself runs in the background.
+fn previewer_command(
+  app: &crate::App,
+  path: &Path,
+  area: Rect,
+) -> Option<PreviewJob>
+{
+  if let Some(job) = rule_command(app, path, area)
+  {
+    return Some(job);
+  }
+  let rt = app.lua.as_ref()?;
+  ask_previewers(app, rt.previewers.iter(), path, area, false)
+}
 
-        match func.call::<LuaValue>(ctx)
+/// Command for the directory `path` from the `lsv.add_dir_previewer`
+/// functions, if any returns one.
+fn dir_previewer_command(
+  app: &crate::App,
+  path: &Path,
+  area: Rect,
+) -> Option<PreviewJob>
+{
+  let rt = app.lua.as_ref()?;
+  let keys = app
+    .config
+    .dir_previewers
+    .iter()
+    .filter_map(|(_, idx)| rt.actions.get(*idx));
+  ask_previewers(app, keys, path, area, true)
+}
+
+/// Ask the Lua functions behind `keys` in turn for a command previewing
+/// `path`; each may return nil (or fail) to leave it to the next.
+fn ask_previewers<'a>(
+  app: &crate::App,
+  keys: impl Iterator<Item = &'a mlua::RegistryKey>,
+  path: &Path,
+  area: Rect,
+  is_dir: bool,
+) -> Option<PreviewJob>
+{
+  let mut keys = keys.peekable();
+  keys.peek()?;
+  let lua = app.lua.as_ref()?.engine.lua();
+  let path_str = path.to_string_lossy().to_string();
+  let dir_str = path
+    .parent()
+    .unwrap_or_else(|| Path::new("."))
+    .to_string_lossy()
+    .to_string();
+  let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("").to_string();
+  let head = if is_dir { Vec::new() } else { mime::read_head(path) };
+  let is_binary = !is_dir && mime::looks_binary(&head);
+  let mime = if is_dir
+  {
+    String::from("inode/directory")
+  }
+  else
+  {
+    mime::from_head(&head, &ext)
+  };
+  let name_now = path
+    .file_name()
+    .map(|s| s.to_string_lossy().to_string())
+    .unwrap_or_default();
+  let ctx = lua.create_table().ok()?;
+  let _ = ctx.set("current_file", path_str.clone());
+  let _ = ctx.set("current_file_dir", dir_str.clone());
+  let _ = ctx.set("current_file_name", name_now.clone());
+  let _ = ctx.set("current_file_extension", ext.clone());
+  let _ = ctx.set("is_binary", is_binary);
+  let _ = ctx.set("mime", mime);
+  let _ = ctx.set("is_dir", is_dir);
+  let _ = ctx.set("preview_height", area.height as i64);
+  let _ = ctx.set("preview_width", area.width as i64);
+  let _ = ctx.set("preview_x", area.x as i64);
+  let _ = ctx.set("preview_y", area.y as i64);
+  let _ = ctx.set("image_protocol", app.image_protocol.as_str());
+
+  for (i, key) in keys.enumerate()
+  {
+    let Ok(func) = lua.registry_value::<mlua::Function>(key)
+    else
+    {
+      continue;
+    };
+    match func.call::<LuaValue>(ctx.clone())
+    {
+      Ok(LuaValue::String(s)) => match s.to_str()
+      {
+        Ok(cmd) =>
         {
-          Ok(LuaValue::String(s)) => match s.to_str()
-          {
-            Ok(cmd) =>
-            {
-              let cmd = cmd.to_string();
-              crate::trace::log(format!(
-                "[preview] lua cmd='{}' cwd='{}' file='{}'",
-                cmd, dir_str, path_str
-              ));
-              return run_previewer_command(&cmd, &dir_str, &path_str, limit);
-            }
-            Err(e) =>
-            {
-              crate::trace::log(format!(
-                "[preview] lua previewer returned non-utf8 string: {}",
-                e
-              ));
-            }
-          },
-          Ok(LuaValue::Nil) =>
-          {
-            crate::trace::log(format!(
-              "[preview] lua previewer returned nil for file {} (ext: {})",
-              path_str, ext
-            ));
-          }
-          Ok(other) =>
-          {
-            crate::trace::log(format!(
-              "[preview] lua previewer returned unexpected type: {}",
-              other.type_name()
-            ));
-          }
-          Err(e) =>
-          {
-            let bt = std::backtrace::Backtrace::force_capture();
-            crate::trace::log(format!("[preview] lua error: {}", e));
-            crate::trace::log(format!("[preview] backtrace:\n{}", bt));
-          }
+          let cmd = cmd.to_string();
+          crate::trace::log(format!(
+            "[preview] lua previewer {} cmd='{}' cwd='{}' file='{}'",
+            i + 1,
+            cmd,
+            dir_str,
+            path_str
+          ));
+          return Some(PreviewJob::new(app, cmd, dir_str, path_str));
         }
+        Err(e) =>
+        {
+          crate::trace::log(format!(
+            "[preview] lua previewer {} returned non-utf8 string: {}",
+            i + 1,
+            e
+          ));
+        }
+      },
+      Ok(LuaValue::Nil) =>
+      {}
+      Ok(other) =>
+      {
+        crate::trace::log(format!(
+          "[preview] lua previewer {} returned unexpected type: {}",
+          i + 1,
+          other.type_name()
+        ));
+      }
+      Err(e) =>
+      {
+        let bt = std::backtrace::Backtrace::force_capture();
+        crate::trace::log(format!(
+          "[preview] lua previewer {} error: {}",
+          i + 1,
+          e
+        ));
+        crate::trace::log(format!("[preview] backtrace:\n{}", bt));
       }
     }
   }
+  crate::trace::log(format!(
+    "[preview] lua previewers returned nil for {} (ext: {})",
+    path_str, ext
+  ));
   None
 }
 
 fn run_previewer_command(
-  cmd: &str,
-  dir_str: &str,
-  path_str: &str,
+  job: &PreviewJob,
   limit: usize,
 ) -> Option<Vec<String>>
 {
   let started = std::time::Instant::now();
+  let shell = crate::core::shell::program(&job.shell);
   crate::trace::log(format!(
     "[preview] run: shell='{}' cwd='{}' cmd='{}' file='{}'",
-    if cfg!(windows) { "cmd" } else { "sh" },
-    dir_str,
-    cmd,
-    path_str
+    shell, job.dir, job.cmd, job.path
   ));
 
-  #[cfg(windows)]
-  let mut command = {
-    let mut c = Command::new("cmd");
-    c.arg("/C").arg(cmd);
-    c
-  };
-  #[cfg(not(windows))]
-  let mut command = {
-    let mut c = Command::new("sh");
-    c.arg("-lc").arg(cmd);
-    c
-  };
-
-  match command
-    .current_dir(dir_str)
+  let spawned = crate::core::shell::command(&job.shell, &job.cmd)
+    .current_dir(&job.dir)
     // No implicit LSV_* env; use placeholders or Lua ctx instead
     .env("FORCE_COLOR", "1")
     .env("CLICOLOR_FORCE", "1")
-    .output()
+    .stdin(Stdio::null())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn();
+  let mut child = match spawned
   {
-    Ok(out) =>
-    {
-      let elapsed = started.elapsed().as_millis();
-      let mut buf = Vec::new();
-      buf.extend_from_slice(&out.stdout);
-      if !out.stderr.is_empty()
-      {
-        buf.push(b'\n');
-        buf.extend_from_slice(&out.stderr);
-      }
-      let text = String::from_utf8_lossy(&buf).replace('\r', "");
-      crate::trace::log(format!(
-        "[preview] done: success={} exit_code={:?} bytes_out={} elapsed_ms={}",
-        out.status.success(),
-        out.status.code(),
-        text.len(),
-        elapsed
-      ));
-      if !out.status.success()
-      {
-        crate::trace::log(format!(
-          "[preview] non-zero status running '{}'",
-          cmd
-        ));
-      }
-      let mut lines: Vec<String> = Vec::new();
-      for l in text.lines()
-      {
-        lines.push(l.to_string());
-        if lines.len() >= limit
-        {
-          break;
-        }
-      }
-      Some(lines)
-    }
+    Ok(child) => child,
     Err(e) =>
     {
       crate::trace::log(format!(
         "[preview] error spawning via {}: {}",
-        if cfg!(windows) { "cmd" } else { "sh" },
-        e
+        shell, e
       ));
       #[cfg(windows)]
       {
@@ -322,28 +657,108 @@ fn run_previewer_command(
            adjust your previewer to use Windows-compatible tooling.",
         );
       }
-      None
+      return None;
     }
-  }
-}
+  };
 
-fn file_is_binary(path: &Path) -> bool
-{
-  if let Ok(mut f) = std::fs::File::open(path)
+  // Pipes are read on their own threads so a command that never closes
+  // them (`cat` on a FIFO) can't hold the preview up past the timeout
+  let (tx, rx) = mpsc::channel::<(bool, Vec<u8>)>();
+  if let Some(out) = child.stdout.take()
+  {
+    read_chunks(out, false, tx.clone());
+  }
+  if let Some(err) = child.stderr.take()
+  {
+    read_chunks(err, true, tx);
+  }
+  let (mut stdout, mut stderr) = (Vec::new(), Vec::new());
+  let mut timed_out = false;
+  loop
   {
-    let mut buf = [0u8; 4096];
-    if let Ok(n) = std::io::Read::read(&mut f, &mut buf)
+    let left = job.timeout.saturating_sub(started.elapsed());
+    match rx.recv_timeout(left)
     {
-      let slice = &buf[..n];
-      if slice.contains(&0)
+      Ok((is_err, chunk)) =>
       {
-        return true;
+        if is_err { &mut stderr } else { &mut stdout }.extend(chunk);
+        if stdout.len() + stderr.len() >= job.max_bytes
+        {
+          crate::trace::log(format!(
+            "[preview] output over {} bytes; stopping '{}'",
+            job.max_bytes, job.cmd
+          ));
+          break;
+        }
       }
-      if std::str::from_utf8(slice).is_err()
+      Err(mpsc::RecvTimeoutError::Disconnected) => break,
+      Err(mpsc::RecvTimeoutError::Timeout) =>
       {
-        return true;
+        timed_out = true;
+        break;
       }
     }
   }
-  false
+  // Still running only when it timed out or printed too much
+  let status = match child.try_wait()
+  {
+    Ok(Some(status)) => Some(status),
+    _ =>
+    {
+      let _ = child.kill();
+      let _ = child.wait();
+      None
+    }
+  };
+  let elapsed = started.elapsed().as_millis();
+  if timed_out
+  {
+    crate::trace::log(format!(
+      "[preview] timed out after {} ms running '{}'",
+      elapsed, job.cmd
+    ));
+    return Some(vec![String::from("<previewer timed out>")]);
+  }
+  let mut buf = stdout;
+  if !stderr.is_empty()
+  {
+    buf.push(b'\n');
+    buf.extend_from_slice(&stderr);
+  }
+  buf.truncate(job.max_bytes);
+  let text = String::from_utf8_lossy(&buf).replace('\r', "");
+  crate::trace::log(format!(
+    "[preview] done: success={:?} exit_code={:?} bytes_out={} elapsed_ms={}",
+    status.map(|s| s.success()),
+    status.and_then(|s| s.code()),
+    text.len(),
+    elapsed
+  ));
+  if status.is_some_and(|s| !s.success())
+  {
+    crate::trace::log(format!(
+      "[preview] non-zero status running '{}'",
+      job.cmd
+    ));
+  }
+  Some(text.lines().take(limit).map(str::to_string).collect())
+}
+
+/// Send what `pipe` yields, tagged with `is_err`, until it closes.
+fn read_chunks(
+  mut pipe: impl Read + Send + 'static,
+  is_err: bool,
+  tx: mpsc::Sender<(bool, Vec<u8>)>,
+)
+{
+  std::thread::spawn(move || {
+    let mut buf = [0u8; 8192];
+    while let Ok(n) = pipe.read(&mut buf)
+    {
+      if n == 0 || tx.send((is_err, buf[..n].to_vec())).is_err()
+      {
+        break;
+      }
+    }
+  });
 }
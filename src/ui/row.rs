@@ -1,6 +1,7 @@
 use ratatui::{
   style::{
     Color,
+    Modifier,
     Style,
   },
   text::{
@@ -10,9 +11,40 @@ use ratatui::{
 };
 use unicode_width::UnicodeWidthStr;
 
+/// Fields lsv fills in row templates; Lua columns can't take these names.
+pub const ROW_PLACEHOLDERS: [&str; 5] =
+  ["name", "info", "size", "mtime", "ctime"];
+
+/// Row fields read from the entry's metadata.
+const META_PLACEHOLDERS: [&str; 3] = ["size", "mtime", "ctime"];
+
+/// Split a row field into its name and the `:spec` after it, if any. The
+/// name of a `lua:fn` field keeps its prefix.
+fn split_field(field: &str) -> (&str, Option<&str>)
+{
+  let after = if field.starts_with("lua:") { 4 } else { 0 };
+  match field[after..].find(':')
+  {
+    Some(i) => (&field[..after + i], Some(&field[after + i + 1..])),
+    None => (field, None),
+  }
+}
+
+/// Whether the row templates show fields that need each entry's metadata,
+/// so listings must read it even when the info column is off.
+pub(crate) fn row_uses_meta(fmt: &crate::config::UiRowFormat) -> bool
+{
+  [&fmt.left, &fmt.middle, &fmt.right].iter().any(|tpl| {
+    tpl.split('{').skip(1).any(|s| {
+      let field = s.split('}').next().unwrap_or("").trim();
+      META_PLACEHOLDERS.contains(&split_field(field).0)
+    })
+  })
+}
+
 pub fn build_row_line(
   app: &crate::App,
-  _fmt: &crate::config::UiRowFormat,
+  fmt: &crate::config::UiRowFormat,
   e: &crate::app::DirEntryInfo,
   inner_width: u16,
 ) -> Line<'static>
@@ -38,8 +70,12 @@ pub fn build_row_line(
   }
 
   let marker = if e.is_dir { "/" } else { "" };
-  let name_val = format!("{}{}", e.name, marker);
-  let icon_val = compute_icon(app, e);
+  let name_val = match tree_label(app, e)
+  {
+    Some(label) => format!("{}{}", label, marker),
+    None => format!("{}{}", e.name, marker),
+  };
+  let (icon_val, icon_color) = super::icons::icon_for(&app.config.icons, e);
   let info_val = format_info(app, e).unwrap_or_default();
 
   let mut sel_style = bar_style;
@@ -85,15 +121,15 @@ pub fn build_row_line(
   spans.push(Span::styled(indicator.to_string(), sel_style));
   spans.push(Span::raw(" "));
 
-  let mut left_txt = String::new();
-  if !icon_val.is_empty()
-  {
-    left_txt.push_str(&icon_val);
-    left_txt.push(' ');
-  }
-  left_txt.push_str(&name_val);
+  let left_txt = expand_row_template(app, &fmt.left, e, &name_val, &info_val);
 
-  let right_txt = info_val;
+  // The middle segment sits just before the right one
+  let right_txt = [&fmt.middle, &fmt.right]
+    .iter()
+    .map(|tpl| expand_row_template(app, tpl, e, &name_val, &info_val))
+    .filter(|s| !s.is_empty())
+    .collect::<Vec<_>>()
+    .join(" ");
   let tw = inner_width as usize;
   let left_fixed = 2usize;
   let total_w = tw.saturating_sub(2);
@@ -102,7 +138,18 @@ pub fn build_row_line(
 
   let mut left_rest = left_txt;
   let right_w = UnicodeWidthStr::width(right_txt.as_str());
-  let left_allowed = total_w.saturating_sub(right_w);
+  let mut left_allowed = total_w.saturating_sub(right_w);
+
+  // The icon gets its file type's colour, the name the entry's style
+  if !icon_val.is_empty() && left_allowed > 0
+  {
+    let icon_txt = truncate_with_tilde(&format!("{} ", icon_val), left_allowed);
+    let icon_w = UnicodeWidthStr::width(icon_txt.as_str());
+    left_allowed -= icon_w;
+    rendered_left_w += icon_w;
+    let icon_style = icon_color.map_or(base_style, |c| base_style.fg(c));
+    spans.push(Span::styled(icon_txt, icon_style));
+  }
 
   if left_allowed > 0
   {
@@ -112,9 +159,19 @@ pub fn build_row_line(
       left_rest = truncate_with_tilde(&left_rest, left_allowed);
     }
     rendered_left_w += UnicodeWidthStr::width(left_rest.as_str());
-    if !left_rest.is_empty()
+    let hits = search_hits(app, e, &left_rest);
+    if hits.is_empty()
     {
-      spans.push(Span::styled(left_rest, base_style));
+      if !left_rest.is_empty()
+      {
+        spans.push(Span::styled(left_rest, base_style));
+      }
+    }
+    else
+    {
+      let hit_style =
+        base_style.add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+      spans.extend(split_highlighted(&left_rest, &hits, base_style, hit_style));
     }
   }
 
@@ -143,40 +200,82 @@ pub fn build_row_line(
   Line::from(spans)
 }
 
-fn compute_icon(
+/// Fill `{name}`, `{info}`, `{size}`, `{mime}`, `{mtime}`, `{ctime}`, Lua
+/// column and `{lua:fn}` placeholders in a row template. A field may end in
+/// `:spec`: `{name:<30}` aligns as
+/// [`align_field`](crate::ui::format::align_field) does, and the time fields
+/// also take a strftime format, `{mtime:%d %b}`. Unknown placeholders and specs
+/// are kept as written.
+fn expand_row_template(
   app: &crate::App,
+  tpl: &str,
   e: &crate::app::DirEntryInfo,
+  name_val: &str,
+  info_val: &str,
 ) -> String
 {
-  let ic = &app.config.icons;
-  if !ic.enabled
-  {
-    return String::new();
-  }
-  if e.is_dir
+  let mut out = String::new();
+  let mut rest = tpl;
+  while let Some(start) = rest.find('{')
   {
-    let name_lc = e.name.to_lowercase();
-    if let Some(sym) = ic.folders.get(&name_lc)
+    let Some(len) = rest[start + 1..].find('}')
+    else
     {
-      return sym.clone();
-    }
-    return ic.default_dir.clone().unwrap_or_else(|| "📁".to_string());
-  }
-  let ext = e
-    .path
-    .extension()
-    .and_then(|s| s.to_str())
-    .map(|s| s.to_lowercase())
-    .unwrap_or_default();
-  if !ext.is_empty()
-    && let Some(sym) = ic.extensions.get(&ext)
-  {
-    return sym.clone();
+      break;
+    };
+    out.push_str(&rest[..start]);
+    let literal = &rest[start..start + len + 2];
+    let (key, spec) = split_field(rest[start + 1..start + 1 + len].trim());
+    let time = match key
+    {
+      "mtime" => Some(e.mtime),
+      "ctime" => Some(e.ctime),
+      _ => None,
+    };
+    let text = match (time, spec)
+    {
+      // A date format rather than an alignment
+      (Some(t), Some(fmt)) if fmt.contains('%') => match t
+      {
+        Some(t) => crate::ui::format::try_format_time_abs(t, fmt),
+        None => Some(String::new()),
+      },
+      _ =>
+      {
+        let value = match key
+        {
+          "name" => Some(name_val.to_string()),
+          "info" => Some(info_val.to_string()),
+          "size" => Some(size_field(app, e).unwrap_or_default()),
+          "mime" => Some(app.entry_mime(e)),
+          _ if time.is_some() =>
+          {
+            Some(time_field(app, time.flatten()).unwrap_or_default())
+          }
+          other if other.starts_with("lua:") =>
+          {
+            app.template_fn_value(&other[4..], Some(e))
+          }
+          other => app.column_value(other, e),
+        };
+        match spec
+        {
+          Some(spec) =>
+          {
+            value.and_then(|v| crate::ui::format::align_field(&v, spec))
+          }
+          None => value,
+        }
+      }
+    };
+    out.push_str(text.as_deref().unwrap_or(literal));
+    rest = &rest[start + len + 2..];
   }
-  ic.default_file.clone().unwrap_or_else(|| "📄".to_string())
+  out.push_str(rest);
+  out
 }
 
-fn truncate_with_tilde(
+pub(crate) fn truncate_with_tilde(
   s: &str,
   max_w: usize,
 ) -> String
@@ -215,12 +314,47 @@ fn entry_style(
   e: &crate::app::DirEntryInfo,
 ) -> Style
 {
-  let mut st = Style::default();
-  let th = match app.config.ui.theme.as_ref()
+  match app.config.ui.theme.as_ref()
   {
-    Some(t) => t,
-    None => return st,
+    Some(th) => theme_entry_style(
+      th,
+      base_name(e),
+      e.is_dir,
+      !e.is_dir && is_executable(app, e),
+      is_hidden(app, e),
+    ),
+    None => Style::default(),
+  }
+}
+
+/// Whether `e` is drawn as hidden. Only Windows has to read attributes for
+/// this; elsewhere the name decides.
+fn is_hidden(
+  app: &crate::App,
+  e: &crate::app::DirEntryInfo,
+) -> bool
+{
+  let attributes = if cfg!(windows)
+  {
+    app.entry_meta(e).map(|m| m.attributes).unwrap_or(0)
+  }
+  else
+  {
+    0
   };
+  crate::core::listing::is_hidden(base_name(e), attributes)
+}
+
+/// Style `th` gives an entry called `name`.
+pub(crate) fn theme_entry_style(
+  th: &crate::config::UiTheme,
+  name: &str,
+  is_dir: bool,
+  is_exec: bool,
+  is_hidden: bool,
+) -> Style
+{
+  let mut st = Style::default();
   if let Some(fg) =
     th.item_fg.as_ref().and_then(|s| crate::ui::colors::parse_color(s))
   {
@@ -231,7 +365,7 @@ fn entry_style(
   {
     st = st.bg(bg);
   }
-  if e.is_dir
+  if is_dir
   {
     if let Some(fg) =
       th.dir_fg.as_ref().and_then(|s| crate::ui::colors::parse_color(s))
@@ -256,7 +390,7 @@ fn entry_style(
     {
       st = st.bg(bg);
     }
-    if is_executable(&e.path)
+    if is_exec
     {
       if let Some(fg) =
         th.exec_fg.as_ref().and_then(|s| crate::ui::colors::parse_color(s))
@@ -270,7 +404,7 @@ fn entry_style(
       }
     }
   }
-  if e.name.starts_with('.')
+  if is_hidden
   {
     if let Some(fg) =
       th.hidden_fg.as_ref().and_then(|s| crate::ui::colors::parse_color(s))
@@ -283,19 +417,48 @@ fn entry_style(
       st = st.bg(bg);
     }
   }
+  // Name rules are the most specific, so they come last
+  if let Some(rule) = th
+    .entry_colors
+    .iter()
+    .find(|r| crate::core::opener::glob_match(&r.pattern, name))
+  {
+    if let Some(fg) =
+      rule.fg.as_ref().and_then(|s| crate::ui::colors::parse_color(s))
+    {
+      st = st.fg(fg);
+    }
+    if let Some(bg) =
+      rule.bg.as_ref().and_then(|s| crate::ui::colors::parse_color(s))
+    {
+      st = st.bg(bg);
+    }
+    for (on, m) in [
+      (rule.bold, Modifier::BOLD),
+      (rule.italic, Modifier::ITALIC),
+      (rule.underline, Modifier::UNDERLINED),
+    ]
+    {
+      if on
+      {
+        st = st.add_modifier(m);
+      }
+    }
+  }
   st
 }
 
 #[cfg(unix)]
-pub fn permissions_string(e: &crate::app::DirEntryInfo) -> String
+pub fn permissions_string(
+  app: &crate::App,
+  e: &crate::app::DirEntryInfo,
+) -> String
 {
-  use std::os::unix::fs::PermissionsExt;
   let mut s = String::new();
-  let (type_ch, mode) = if let Ok(meta) = std::fs::metadata(&e.path)
+  let (type_ch, mode) = if let Some(meta) = app.entry_meta(e)
   {
-    let ft = meta.file_type();
-    let t = if e.is_dir || ft.is_dir() { 'd' } else { '-' };
-    (t, meta.permissions().mode())
+    let t = if e.is_dir || meta.is_dir { 'd' } else { '-' };
+    (t, meta.mode)
   }
   else
   {
@@ -307,6 +470,16 @@ pub fn permissions_string(e: &crate::app::DirEntryInfo) -> String
     s.push_str("?????????");
     return s;
   }
+  s.push_str(&mode_string(mode));
+  s
+}
+
+/// `rwxr-xr-x` for the permission bits of `mode`, with setuid, setgid and
+/// sticky bits shown as `s`/`S` and `t`/`T`.
+#[cfg(unix)]
+pub fn mode_string(mode: u32) -> String
+{
+  let mut s = String::with_capacity(9);
   s.push(if mode & 0o400 != 0 { 'r' } else { '-' });
   s.push(if mode & 0o200 != 0 { 'w' } else { '-' });
   s.push(match (mode & 0o100 != 0, mode & 0o4000 != 0)
@@ -337,79 +510,206 @@ pub fn permissions_string(e: &crate::app::DirEntryInfo) -> String
   s
 }
 
-#[cfg(not(unix))]
-pub fn permissions_string(_e: &crate::app::DirEntryInfo) -> String
+/// `darhsl` flags for Windows file `attributes`, as PowerShell shows them:
+/// directory, archive, read-only, hidden, system and reparse point (link).
+/// Built everywhere so it can be tested off Windows.
+#[cfg_attr(not(windows), allow(dead_code))]
+pub fn attributes_string(attributes: u32) -> String
 {
-  "---------".to_string()
+  [(0x10, 'd'), (0x20, 'a'), (0x1, 'r'), (0x2, 'h'), (0x4, 's'), (0x400, 'l')]
+    .iter()
+    .map(|&(bit, ch)| if attributes & bit != 0 { ch } else { '-' })
+    .collect()
 }
 
-#[cfg(unix)]
-fn is_executable(path: &std::path::Path) -> bool
+#[cfg(not(unix))]
+pub fn permissions_string(
+  app: &crate::App,
+  e: &crate::app::DirEntryInfo,
+) -> String
 {
-  use std::os::unix::fs::PermissionsExt;
-  if let Ok(meta) = std::fs::metadata(path)
+  match app.entry_meta(e)
   {
-    let mode = meta.permissions().mode();
-    return (mode & 0o111) != 0;
+    Some(meta) => attributes_string(meta.attributes),
+    None => "------".to_string(),
   }
-  false
+}
+
+#[cfg(unix)]
+fn is_executable(
+  app: &crate::App,
+  e: &crate::app::DirEntryInfo,
+) -> bool
+{
+  app.entry_meta(e).is_some_and(|meta| meta.mode & 0o111 != 0)
 }
 
 #[cfg(not(unix))]
-fn is_executable(_path: &std::path::Path) -> bool
+fn is_executable(
+  _app: &crate::App,
+  _e: &crate::app::DirEntryInfo,
+) -> bool
 {
   false
 }
 
-fn format_info(
+/// Character indices of `text` (a rendered current-pane row) matched by the
+/// active `/` search query.
+fn search_hits(
+  app: &crate::App,
+  e: &crate::app::DirEntryInfo,
+  text: &str,
+) -> Vec<usize>
+{
+  let Some(query) = app.search_highlight_query()
+  else
+  {
+    return Vec::new();
+  };
+  let is_current = e
+    .path
+    .strip_prefix(&app.cwd)
+    .is_ok_and(|rel| rel.to_string_lossy().replace('\\', "/") == e.name);
+  if !is_current
+  {
+    return Vec::new();
+  }
+  // Tree rows show only the last component
+  let shown = if app.tree_active() { base_name(e) } else { e.name.as_str() };
+  let Some(at) = text.find(shown)
+  else
+  {
+    return Vec::new();
+  };
+  let offset = text[..at].chars().count();
+  crate::core::fuzzy::fuzzy_positions(query, shown)
+    .map(|hits| hits.into_iter().map(|p| p + offset).collect())
+    .unwrap_or_default()
+}
+
+/// Split `text` into spans, using `hit` for the characters at `hits`.
+fn split_highlighted(
+  text: &str,
+  hits: &[usize],
+  base: Style,
+  hit: Style,
+) -> Vec<Span<'static>>
+{
+  let mut spans = Vec::new();
+  let mut run = String::new();
+  let mut run_hit = false;
+  for (i, ch) in text.chars().enumerate()
+  {
+    let is_hit = hits.contains(&i);
+    if is_hit != run_hit && !run.is_empty()
+    {
+      let style = if run_hit { hit } else { base };
+      spans.push(Span::styled(std::mem::take(&mut run), style));
+    }
+    run_hit = is_hit;
+    run.push(ch);
+  }
+  if !run.is_empty()
+  {
+    spans.push(Span::styled(run, if run_hit { hit } else { base }));
+  }
+  spans
+}
+
+/// Last component of an entry's name; flat and tree views name entries by
+/// their path relative to the current directory.
+fn base_name(e: &crate::app::DirEntryInfo) -> &str
+{
+  e.name.rsplit('/').next().unwrap_or(&e.name)
+}
+
+/// Indented name with an expand marker, when the current pane is a tree.
+fn tree_label(
   app: &crate::App,
   e: &crate::app::DirEntryInfo,
 ) -> Option<String>
 {
-  use crate::app::InfoMode;
-  let fmt = app.config.ui.date_format.as_deref().unwrap_or("%Y-%m-%d %H:%M");
-  match app.info_mode
+  // Only current-pane rows are named by their path below the cwd; parent
+  // and preview listings use the plain name
+  let rel = e.path.strip_prefix(&app.cwd).ok()?;
+  if !app.tree_active() || rel.to_string_lossy().replace('\\', "/") != e.name
   {
-    InfoMode::None => None,
-    InfoMode::Size =>
+    return None;
+  }
+  let depth = e.name.matches('/').count();
+  let marker = match (e.is_dir, app.is_tree_expanded(&e.path))
+  {
+    (true, true) => "▾ ",
+    (true, false) => "▸ ",
+    (false, _) => "  ",
+  };
+  Some(format!("{}{}{}", "  ".repeat(depth), marker, base_name(e)))
+}
+
+/// Size shown for `e`: a file's size, or a directory's computed size.
+fn size_field(
+  app: &crate::App,
+  e: &crate::app::DirEntryInfo,
+) -> Option<String>
+{
+  let size = |bytes: u64| match app.display_mode
+  {
+    crate::app::DisplayMode::Friendly => crate::ui::format::human_size(bytes),
+    crate::app::DisplayMode::Absolute => format!("{} B", bytes),
+  };
+  if !e.is_dir
+  {
+    return Some(size(e.size));
+  }
+  // Directories only show sizes computed by `compute_dir_sizes`
+  app.dir_size(&e.path).map(|d| {
+    if d.done
     {
-      if e.is_dir
-      {
-        None
-      }
-      else
-      {
-        Some(match app.display_mode
-        {
-          crate::app::DisplayMode::Friendly =>
-          {
-            crate::ui::format::human_size(e.size)
-          }
-          crate::app::DisplayMode::Absolute => format!("{} B", e.size),
-        })
-      }
+      size(d.bytes)
     }
-    InfoMode::Created => match app.display_mode
+    else
     {
-      crate::app::DisplayMode::Absolute =>
-      {
-        e.ctime.map(|t| crate::ui::format::format_time_abs(t, fmt))
-      }
-      crate::app::DisplayMode::Friendly =>
-      {
-        e.ctime.map(crate::ui::format::format_time_ago)
-      }
-    },
-    InfoMode::Modified => match app.display_mode
+      format!("{} {}", crate::ui::format::spinner_frame(), size(d.bytes))
+    }
+  })
+}
+
+/// A time as the display mode shows it: with `ui.date_format`, or relative.
+/// `ui.relative_dates` shows recent times relative in either mode.
+fn time_field(
+  app: &crate::App,
+  t: Option<std::time::SystemTime>,
+) -> Option<String>
+{
+  let fmt = app.config.ui.date_format.as_deref().unwrap_or("%Y-%m-%d %H:%M");
+  if let Some(within) = app.config.ui.relative_dates
+  {
+    return t.map(|t| crate::ui::format::format_time_within(t, fmt, within));
+  }
+  match app.display_mode
+  {
+    crate::app::DisplayMode::Absolute =>
     {
-      crate::app::DisplayMode::Absolute =>
-      {
-        e.mtime.map(|t| crate::ui::format::format_time_abs(t, fmt))
-      }
-      crate::app::DisplayMode::Friendly =>
-      {
-        e.mtime.map(crate::ui::format::format_time_ago)
-      }
-    },
+      t.map(|t| crate::ui::format::format_time_abs(t, fmt))
+    }
+    crate::app::DisplayMode::Friendly =>
+    {
+      t.map(crate::ui::format::format_time_ago)
+    }
+  }
+}
+
+fn format_info(
+  app: &crate::App,
+  e: &crate::app::DirEntryInfo,
+) -> Option<String>
+{
+  use crate::app::InfoMode;
+  match app.info_mode
+  {
+    InfoMode::None => None,
+    InfoMode::Size => size_field(app, e),
+    InfoMode::Created => time_field(app, e.ctime),
+    InfoMode::Modified => time_field(app, e.mtime),
   }
 }
@@ -7,6 +7,169 @@ use ratatui::{
   text::Span,
 };
 
+/// A byte count as the display mode shows sizes.
+fn size_text(
+  app: &App,
+  bytes: u64,
+) -> String
+{
+  match app.get_display_mode()
+  {
+    crate::app::DisplayMode::Friendly => super::panes::human_size(bytes),
+    crate::app::DisplayMode::Absolute => format!("{} B", bytes),
+  }
+}
+
+/// Combined size of the selected files. Entries of the current listing use
+/// their listed size when the listing read it; others are looked up on disk.
+/// Directories count with their computed size, if any.
+fn selection_size(app: &App) -> u64
+{
+  let listed_sizes =
+    app.listing_needs_meta() || app.in_archive() || app.in_remote();
+  let listed: std::collections::HashMap<_, _> = if listed_sizes
+  {
+    app.current_entries.iter().map(|e| (&e.path, e)).collect()
+  }
+  else
+  {
+    Default::default()
+  };
+  let dir_bytes =
+    |path: &std::path::Path| app.dir_size(path).map(|s| s.bytes).unwrap_or(0);
+  app
+    .selected
+    .iter()
+    .map(|path| match listed.get(path)
+    {
+      Some(e) if e.is_dir => dir_bytes(path),
+      Some(e) => e.size,
+      None => match std::fs::symlink_metadata(path)
+      {
+        Ok(m) if m.is_dir() => dir_bytes(path),
+        Ok(m) => m.len(),
+        Err(_) => 0,
+      },
+    })
+    .sum()
+}
+
+/// Selection, clipboard, job, search, position and disk space state shared
+/// by the header and footer templates and passed to Lua footer functions.
+pub fn status_values(app: &App) -> Vec<(&'static str, String)>
+{
+  let clipboard = app
+    .clipboard
+    .as_ref()
+    .filter(|cb| !cb.items.is_empty())
+    .map(|cb| match cb.op
+    {
+      crate::app::ClipboardOp::Copy => format!("copy {}", cb.items.len()),
+      crate::app::ClipboardOp::Move => format!("move {}", cb.items.len()),
+    })
+    .unwrap_or_default();
+  let jobs = app
+    .jobs
+    .jobs
+    .iter()
+    .filter(|j| {
+      matches!(
+        j.status,
+        crate::app::JobStatus::Queued | crate::app::JobStatus::Running
+      )
+    })
+    .count();
+  let entry_index = app
+    .get_list_selected_index()
+    .filter(|_| !app.current_entries.is_empty())
+    .map(|i| (i + 1).to_string())
+    .unwrap_or_else(|| String::from("0"));
+  let (total_space, free_space) = app
+    .disk_space()
+    .map(|(total, avail)| (size_text(app, total), size_text(app, avail)))
+    .unwrap_or_default();
+  let selection_size = if app.selected.is_empty()
+  {
+    String::new()
+  }
+  else
+  {
+    size_text(app, selection_size(app))
+  };
+  vec![
+    ("selected_count", app.selected.len().to_string()),
+    ("selection_count", app.selected.len().to_string()),
+    ("selection_size", selection_size),
+    ("entry_index", entry_index),
+    ("entry_total", app.current_entries.len().to_string()),
+    ("free_space", free_space),
+    ("total_space", total_space),
+    ("clipboard", clipboard),
+    ("jobs", jobs.to_string()),
+    ("filter", app.active_filter().unwrap_or_default().to_string()),
+    ("search", app.search_query.clone().unwrap_or_default()),
+    ("count", pending_keys(app)),
+    (
+      "recording",
+      app.recording_macro().map(|r| format!("@{}", r)).unwrap_or_default(),
+    ),
+  ]
+}
+
+/// A count being typed and the keys after it, like `3g`; empty without one.
+pub(crate) fn pending_keys(app: &crate::App) -> String
+{
+  app
+    .pending_count
+    .map(|n| format!("{}{}", n, app.keys.pending))
+    .unwrap_or_default()
+}
+
+/// Header and footer placeholders drawn from the current file's mode or
+/// owner.
+const META_PLACEHOLDERS: [&str; 2] = ["current_file_permissions", "owner"];
+
+/// Whether a header or footer template shows the current file's mode or
+/// owner, so listings should read them up front.
+pub(crate) fn status_uses_meta(ui: &crate::config::UiConfig) -> bool
+{
+  let footer = ui.footer.as_ref().map(|f| [&f.left, &f.right]);
+  let footer = footer.iter().flatten().filter_map(|seg| match seg
+  {
+    Some(crate::config::FooterSegment::Template(t)) => Some(t),
+    _ => None,
+  });
+  [&ui.header_left, &ui.header_right].into_iter().flatten().chain(footer).any(
+    |tpl| {
+      tpl.split('{').skip(1).any(|s| {
+        let field = s.split(['}', '|', ':']).next().unwrap_or("").trim();
+        META_PLACEHOLDERS.contains(&field)
+      })
+    },
+  )
+}
+
+/// Placeholders about the time, user and current file; the rest come from
+/// [`status_values`].
+const PLACEHOLDERS: &[&str] = &[
+  "date",
+  "time",
+  "cwd",
+  "current_file",
+  "current_file_dir",
+  "current_file_name",
+  "username",
+  "hostname",
+  "current_file_permissions",
+  "current_file_size",
+  "current_file_ctime",
+  "current_file_mtime",
+  "current_file_extension",
+  "mime",
+  "owner",
+  "tabs",
+];
+
 #[derive(Clone, Default)]
 pub struct HeaderSide
 {
@@ -71,11 +234,11 @@ pub fn format_header_side(
     .unwrap_or_else(|| cwd_s.clone());
   let owner = sel_opt
     .as_ref()
-    .map(|e| super::owner_string(&e.path))
+    .map(|e| super::owner_string(app, e))
     .unwrap_or_else(|| String::from("-"));
   let perms = sel_opt
     .as_ref()
-    .map(|e| super::panes::permissions_string(e))
+    .map(|e| super::panes::permissions_string(app, e))
     .unwrap_or_else(|| String::from("---------"));
   let size_s = sel_opt
     .as_ref()
@@ -108,39 +271,46 @@ pub fn format_header_side(
     .unwrap_or_default();
   let date_fmt_binding = app.get_date_format();
   let date_fmt = date_fmt_binding.as_deref().unwrap_or("%Y-%m-%d %H:%M");
+  let show_time = |t: std::time::SystemTime| match app.config.ui.relative_dates
+  {
+    Some(within) => super::format::format_time_within(t, date_fmt, within),
+    None => super::panes::format_time_abs(t, date_fmt),
+  };
   let ctime_s = sel_opt
     .as_ref()
     .and_then(|e| e.ctime)
-    .map(|t| super::panes::format_time_abs(t, date_fmt))
+    .map(show_time)
     .unwrap_or_else(|| String::from("-"));
   let mtime_s = sel_opt
     .as_ref()
     .and_then(|e| e.mtime)
-    .map(|t| super::panes::format_time_abs(t, date_fmt))
+    .map(show_time)
     .unwrap_or_else(|| String::from("-"));
+  // Only read the file when the template shows its type
+  let mime_s = std::cell::OnceCell::new();
+  let mime_of = || {
+    mime_s
+      .get_or_init(|| {
+        sel_opt.as_ref().map(|e| app.entry_mime(e)).unwrap_or_default()
+      })
+      .clone()
+  };
+  let tabs_s = app.tabs_label();
+  let status = status_values(app);
 
   let tpl = tpl_opt.cloned().unwrap_or_default();
 
-  let allowed = [
-    "date",
-    "time",
-    "cwd",
-    "current_file",
-    "current_file_dir",
-    "current_file_name",
-    "username",
-    "hostname",
-    "current_file_permissions",
-    "current_file_size",
-    "current_file_ctime",
-    "current_file_mtime",
-    "current_file_extension",
-    "owner",
-  ];
+  let known = |name: &str| {
+    PLACEHOLDERS.contains(&name)
+      || status.iter().any(|(n, _)| *n == name)
+      || name
+        .strip_prefix("lua:")
+        .is_some_and(|f| app.config.template_fns.iter().any(|t| t.name == f))
+  };
   for ph in placeholders_in(&tpl)
   {
     let base = ph.split('|').next().unwrap_or(ph.as_str());
-    if !allowed.contains(&base)
+    if !known(base)
     {
       crate::trace::log(format!("[header] unknown placeholder '{{{}}}'", ph));
     }
@@ -163,8 +333,18 @@ pub fn format_header_side(
       "current_file_ctime" => ctime_s.clone(),
       "current_file_mtime" => mtime_s.clone(),
       "current_file_extension" => ext.clone(),
+      "mime" => mime_of(),
       "owner" => owner.clone(),
-      _ => String::new(),
+      "tabs" => tabs_s.clone(),
+      _ if name.starts_with("lua:") =>
+      {
+        app.template_fn_value(&name[4..], sel_opt).unwrap_or_default()
+      }
+      _ => status
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, v)| v.clone())
+        .unwrap_or_default(),
     }
   };
 
@@ -247,23 +427,7 @@ pub fn format_header_side(
           Some((n, m)) => (n.trim(), Some(m.trim())),
           None => (token.trim(), None),
         };
-        let allowed = [
-          "date",
-          "time",
-          "cwd",
-          "current_file",
-          "current_file_dir",
-          "current_file_name",
-          "username",
-          "hostname",
-          "current_file_permissions",
-          "current_file_size",
-          "current_file_ctime",
-          "current_file_mtime",
-          "current_file_extension",
-          "owner",
-        ];
-        if allowed.contains(&name)
+        if known(name)
         {
           let val = value_for(name);
           out.text.push_str(&val);
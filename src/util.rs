@@ -4,11 +4,29 @@ use std::{
   fs::File,
   io::{
     self,
+    IsTerminal,
     Read,
+    Write,
   },
   path::Path,
 };
 
+/// Where the TUI draws: stdout, or the terminal itself when stdout is
+/// redirected (as with `--choosefile -`), so nothing but output meant for
+/// the caller ends up there.
+pub fn terminal_output() -> io::Result<Box<dyn Write + Send>>
+{
+  if io::stdout().is_terminal()
+  {
+    return Ok(Box::new(io::stdout()));
+  }
+  #[cfg(windows)]
+  let tty = std::fs::OpenOptions::new().read(true).write(true).open("CONOUT$");
+  #[cfg(not(windows))]
+  let tty = std::fs::OpenOptions::new().write(true).open("/dev/tty");
+  Ok(Box::new(tty?))
+}
+
 /// Read up to `max_bytes` from the start of `path` and split into at most
 /// `max_lines` lines. Uses lossy UTF-8 conversion to avoid panics and limits
 /// memory usage for binary or very long single-line files.
@@ -47,26 +65,10 @@ pub fn read_file_head_safe(
 }
 
 /// Heuristic binary detector: reads a small prefix and returns true if it
-/// contains a NUL byte or is not valid UTF-8.
+/// contains a NUL byte or is not valid UTF-8. See [`crate::core::mime`].
 pub fn is_binary(path: &Path) -> bool
 {
-  if let Ok(mut f) = File::open(path)
-  {
-    let mut buf = [0u8; 4096];
-    if let Ok(n) = Read::read(&mut f, &mut buf)
-    {
-      let slice = &buf[..n];
-      if slice.contains(&0)
-      {
-        return true;
-      }
-      if std::str::from_utf8(slice).is_err()
-      {
-        return true;
-      }
-    }
-  }
-  false
+  crate::core::mime::looks_binary(&crate::core::mime::read_head(path))
 }
 
 /// Expand tabs, strip carriage returns, and replace control characters with
@@ -87,3 +89,17 @@ pub fn sanitize_line(s: &str) -> String
   }
   out
 }
+
+/// Quote `s` as one argument for the shell previewers and actions run in
+/// (`sh` on POSIX, `cmd` on Windows).
+pub fn shell_quote(s: &str) -> String
+{
+  #[cfg(windows)]
+  {
+    format!("\"{}\"", s.replace('"', "\"\""))
+  }
+  #[cfg(not(windows))]
+  {
+    format!("'{}'", s.replace('\'', "'\\''"))
+  }
+}
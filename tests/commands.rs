@@ -20,3 +20,22 @@ fn commands_contains_expected_entries()
     assert!(cmds.iter().any(|c| c == &expected), "missing: {}", expected);
   }
 }
+
+#[test]
+fn split_args_honours_quotes_and_escapes()
+{
+  let split = lsv::commands::split_args;
+  assert_eq!(split("  a  b "), vec!["a", "b"]);
+  assert_eq!(split("'a b' \"c d\""), vec!["a b", "c d"]);
+  assert_eq!(split(r#"x\ y "q\"t" 'it\s'"#), vec!["x y", "q\"t", "it\\s"]);
+  assert_eq!(split("'' end"), vec!["", "end"]);
+  assert!(split("   ").is_empty());
+}
+
+#[test]
+fn builtin_names_drop_arguments()
+{
+  let names: Vec<&str> = lsv::commands::builtin_names().collect();
+  assert!(names.contains(&"sort"));
+  assert!(!names.iter().any(|n| n.contains(' ')));
+}
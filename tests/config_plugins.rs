@@ -0,0 +1,191 @@
+use std::fs;
+
+fn plugin(
+  root: &std::path::Path,
+  name: &str,
+  code: &str,
+)
+{
+  let dir = root.join("plugins").join(name);
+  fs::create_dir_all(&dir).unwrap();
+  fs::write(dir.join("init.lua"), code).unwrap();
+}
+
+fn deps(list: &[(&str, &[&str])]) -> Vec<(String, Vec<String>)>
+{
+  list
+    .iter()
+    .map(|(n, d)| (n.to_string(), d.iter().map(|s| s.to_string()).collect()))
+    .collect()
+}
+
+#[test]
+fn order_puts_dependencies_first()
+{
+  let plugins = deps(&[("a", &["c"]), ("b", &[]), ("c", &["b"])]);
+  let (order, failed) = lsv::config::order_plugins(&plugins);
+  assert_eq!(order, vec![1, 2, 0]);
+  assert!(failed.is_empty());
+}
+
+#[test]
+fn order_skips_missing_and_cyclic_dependencies()
+{
+  let plugins = deps(&[
+    ("a", &["b"]),
+    ("b", &["a"]),
+    ("c", &["zzz"]),
+    ("d", &["c"]),
+    ("e", &[]),
+  ]);
+  let (order, failed) = lsv::config::order_plugins(&plugins);
+  assert_eq!(order, vec![4]);
+  let mut failed_names: Vec<usize> = failed.iter().map(|(i, _)| *i).collect();
+  failed_names.sort();
+  assert_eq!(failed_names, vec![0, 1, 2, 3]);
+  assert!(failed.iter().any(|(i, r)| *i == 2 && r.contains("zzz")));
+}
+
+#[test]
+fn discover_finds_plugin_dirs_with_init()
+{
+  let tmp = tempfile::tempdir().unwrap();
+  plugin(tmp.path(), "b", "");
+  plugin(tmp.path(), "a", "");
+  fs::create_dir_all(tmp.path().join("plugins/empty")).unwrap();
+  let found: Vec<String> = lsv::config::discover_plugins(tmp.path())
+    .into_iter()
+    .map(|(n, _)| n)
+    .collect();
+  assert_eq!(found, vec!["a", "b"]);
+}
+
+#[test]
+fn plugins_load_in_dependency_order_with_own_globals()
+{
+  let tmp = tempfile::tempdir().unwrap();
+  plugin(
+    tmp.path(),
+    "a_uses_b",
+    r#"
+leaked = true
+return {
+  depends = { "b" },
+  setup = function(p)
+    local util = require("util")
+    p.register_command(util.name(p.name), function() end)
+  end,
+}
+"#,
+  );
+  fs::write(
+    tmp.path().join("plugins/a_uses_b/util.lua"),
+    "return { name = function(n) return n .. '_cmd' end }",
+  )
+  .unwrap();
+  plugin(
+    tmp.path(),
+    "b",
+    r#"
+lsv.plugin.define_column("b_col", function(e) return "b" end)
+return { setup = function(p) p.register_command("b_cmd", function() end) end }
+"#,
+  );
+  plugin(tmp.path(), "c", "return { depends = { 'missing' } }");
+  plugin(tmp.path(), "d", "error('boom')");
+
+  let code = "assert(leaked == nil, 'plugin globals leaked')";
+  let (cfg, _maps, _engine) =
+    lsv::config::load_config_from_code(code, Some(tmp.path())).expect("load");
+  let commands: Vec<&str> =
+    cfg.commands.iter().map(|c| c.name.as_str()).collect();
+  assert_eq!(commands, vec!["b_cmd", "a_uses_b_cmd"]);
+  assert_eq!(cfg.columns.len(), 1);
+
+  let status = |name: &str| {
+    cfg.plugins.iter().find(|p| p.name == name).expect(name).error.clone()
+  };
+  assert_eq!(status("a_uses_b"), None);
+  assert_eq!(status("b"), None);
+  assert!(status("c").unwrap().contains("missing"));
+  assert!(status("d").unwrap().contains("boom"));
+}
+
+#[test]
+fn plugin_previewers_follow_the_users()
+{
+  let tmp = tempfile::tempdir().unwrap();
+  plugin(
+    tmp.path(),
+    "md",
+    r#"
+lsv.plugin.add_previewer(function(ctx)
+  if ctx.current_file_extension == "md" then return "glow" end
+end)
+"#,
+  );
+  let code = r#"
+lsv.set_previewer(function(ctx)
+  if ctx.current_file_extension == "rs" then return "bat" end
+end)
+"#;
+  let (_cfg, _maps, engine) =
+    lsv::config::load_config_from_code(code, Some(tmp.path())).expect("load");
+  let (engine, previewers, _keys) = engine.expect("engine");
+  assert_eq!(previewers.len(), 2);
+  assert_eq!(run_chain(&engine, &previewers, "rs").as_deref(), Some("bat"));
+  assert_eq!(run_chain(&engine, &previewers, "md").as_deref(), Some("glow"));
+  assert_eq!(run_chain(&engine, &previewers, "txt"), None);
+}
+
+/// First command the previewers return for a file with extension `ext`.
+fn run_chain(
+  engine: &lsv::config::LuaEngine,
+  previewers: &[mlua::RegistryKey],
+  ext: &str,
+) -> Option<String>
+{
+  let lua = engine.lua();
+  let ctx = lua.create_table().unwrap();
+  ctx.set("current_file_extension", ext).unwrap();
+  previewers.iter().find_map(|key| {
+    let f: mlua::Function = lua.registry_value(key).unwrap();
+    f.call::<Option<String>>(ctx.clone()).unwrap()
+  })
+}
+
+#[test]
+fn previewers_are_asked_by_priority()
+{
+  let tmp = tempfile::tempdir().unwrap();
+  plugin(
+    tmp.path(),
+    "urgent",
+    r#"
+lsv.plugin.add_previewer(function(ctx)
+  if ctx.current_file_extension == "md" then return "plugin-md" end
+end, { priority = 10 })
+"#,
+  );
+  let code = r#"
+lsv.add_previewer(function(ctx) return "fallback" end, { priority = -1 })
+lsv.add_previewer(function(ctx)
+  if ctx.current_file_extension ~= "txt" then return "added" end
+end)
+lsv.set_previewer(function(ctx)
+  if ctx.current_file_extension == "rs" then return "main" end
+end)
+"#;
+  let (_cfg, _maps, engine) =
+    lsv::config::load_config_from_code(code, Some(tmp.path())).expect("load");
+  let (engine, previewers, _keys) = engine.expect("engine");
+  assert_eq!(previewers.len(), 4);
+  let run = |ext: &str| run_chain(&engine, &previewers, ext);
+  // Priority 10 beats the rest
+  assert_eq!(run("md").as_deref(), Some("plugin-md"));
+  // set_previewer comes before add_previewer at the same priority
+  assert_eq!(run("rs").as_deref(), Some("main"));
+  assert_eq!(run("py").as_deref(), Some("added"));
+  // Negative priorities are asked last
+  assert_eq!(run("txt").as_deref(), Some("fallback"));
+}
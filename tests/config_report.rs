@@ -0,0 +1,62 @@
+use std::path::Path;
+
+use lsv::config::ConfigError;
+
+#[test]
+fn parse_extracts_file_and_line_from_syntax_error()
+{
+  let err = ConfigError::parse(
+    "init.lua execution failed: syntax error: [string \"/cfg/init.lua\"]:4: \
+     unexpected symbol near <eof>",
+  );
+  assert_eq!(err.file.as_deref(), Some(Path::new("/cfg/init.lua")));
+  assert_eq!(err.line, Some(4));
+  assert_eq!(err.message, "syntax error: unexpected symbol near <eof>");
+  assert!(err.details.is_empty());
+  assert_eq!(err.location().as_deref(), Some("/cfg/init.lua:4"));
+}
+
+#[test]
+fn parse_takes_location_from_traceback()
+{
+  let err = ConfigError::parse(
+    "init.lua execution failed: runtime error: ui.sort: bad value\nstack \
+     traceback:\n\t[C]: in field 'config'\n\t[string \"/cfg/init.lua\"]:7: in \
+     main chunk",
+  );
+  assert_eq!(err.line, Some(7));
+  assert_eq!(err.message, "runtime error: ui.sort: bad value");
+  assert_eq!(err.details.len(), 3);
+  assert_eq!(err.details[1], "  [C]: in field 'config'");
+  assert_eq!(err.lines()[0], "/cfg/init.lua:7");
+}
+
+#[test]
+fn parse_keeps_message_without_location()
+{
+  let err = ConfigError::parse("read init.lua failed: permission denied");
+  assert_eq!(err.file, None);
+  assert_eq!(err.line, None);
+  assert_eq!(err.lines(), vec!["read init.lua failed: permission denied"]);
+}
+
+#[test]
+fn broken_config_at_startup_falls_back_to_defaults()
+{
+  let temp = tempfile::tempdir().expect("tempdir");
+  std::fs::write(temp.path().join("init.lua"), "local x = nil\nx.y = 1\n")
+    .unwrap();
+  // Only this test touches the environment in this binary
+  unsafe { std::env::set_var("LSV_CONFIG_DIR", temp.path()) };
+  let app = lsv::app::App::new().expect("app new");
+  unsafe { std::env::remove_var("LSV_CONFIG_DIR") };
+
+  assert!(app.get_show_output());
+  assert_eq!(app.get_output_title(), "Config error");
+  let err = app.config_error().expect("config error");
+  assert_eq!(err.line, Some(2));
+  assert!(err.message.contains("attempt to index a nil value"));
+  assert!(app.get_output_text().contains("default config"));
+  // Built-in keymaps still work
+  assert!(app.get_keymap_action("q").is_some());
+}
@@ -0,0 +1,204 @@
+use std::fs;
+
+use lsv::core::archive::{
+  ArchiveKind,
+  detect,
+  list,
+};
+
+// GNU tar (docs/, docs/readme.txt, data/<120 chars>.bin with a long-name
+// record) compressed with gzip -9; built with Python's tarfile module.
+const TAR_GZ: &[u8] = &[
+  0x1f, 0x8b, 0x08, 0x08, 0x00, 0x00, 0x00, 0x00, 0x02, 0xff, 0x66, 0x69, 0x78,
+  0x74, 0x75, 0x72, 0x65, 0x2e, 0x74, 0x61, 0x72, 0x00, 0x4b, 0xc9, 0x4f, 0x2e,
+  0xd6, 0x67, 0xa0, 0x2d, 0x30, 0x00, 0x02, 0x73, 0x53, 0x53, 0x30, 0x0d, 0x04,
+  0xe8, 0x34, 0x98, 0x6d, 0x68, 0x62, 0x6a, 0x64, 0x62, 0x6e, 0x6e, 0x60, 0x02,
+  0x16, 0x37, 0x33, 0x33, 0x37, 0x67, 0x50, 0x30, 0x65, 0xa0, 0x03, 0x28, 0x2d,
+  0x2e, 0x49, 0x2c, 0x52, 0x50, 0x60, 0x18, 0xa1, 0x20, 0x05, 0x14, 0xff, 0x45,
+  0xa9, 0x89, 0x29, 0xb9, 0xa9, 0x7a, 0x25, 0x15, 0x25, 0x34, 0x8b, 0x7f, 0x33,
+  0x13, 0x13, 0x5c, 0xf1, 0x6f, 0x68, 0x62, 0x82, 0x16, 0xff, 0x86, 0x06, 0x66,
+  0xe6, 0x26, 0x0c, 0x0a, 0x06, 0xa3, 0xf1, 0x4f, 0x73, 0x90, 0x53, 0x5c, 0xa6,
+  0x90, 0x58, 0x94, 0x9c, 0x91, 0x59, 0x96, 0xaa, 0x50, 0x50, 0x94, 0x5a, 0x96,
+  0x99, 0x5a, 0xce, 0x35, 0x2a, 0x36, 0x2a, 0x36, 0x2a, 0x06, 0x11, 0x1b, 0xee,
+  0xf9, 0x5f, 0x4f, 0x5f, 0x4f, 0xdf, 0xc1, 0x27, 0x3f, 0x2f, 0xdd, 0x27, 0x33,
+  0x2f, 0x9b, 0x86, 0xf5, 0x3f, 0x96, 0x72, 0x1f, 0x5e, 0xff, 0x1b, 0x19, 0x18,
+  0xa1, 0xb4, 0x05, 0x80, 0xad, 0x05, 0x73, 0x13, 0x60, 0xfd, 0xef, 0x33, 0x5a,
+  0xfe, 0xd3, 0xbe, 0xfe, 0x4f, 0x2c, 0x49, 0xd4, 0xcf, 0x1b, 0x20, 0xa0, 0x97,
+  0x94, 0x99, 0xc7, 0x30, 0x0a, 0x86, 0x7b, 0xfc, 0x13, 0x68, 0xff, 0x99, 0x61,
+  0xb4, 0xff, 0x8d, 0x8d, 0x8d, 0x80, 0xd4, 0x68, 0xfb, 0x8f, 0x1e, 0x80, 0x91,
+  0x89, 0x99, 0x85, 0x95, 0x8d, 0x9d, 0x83, 0x93, 0x8b, 0x9b, 0x87, 0x97, 0x8f,
+  0x5f, 0x40, 0x50, 0x48, 0x58, 0x44, 0x54, 0x4c, 0x5c, 0x42, 0x52, 0x4a, 0x5a,
+  0x46, 0x56, 0x4e, 0x5e, 0x41, 0x51, 0x49, 0x59, 0x45, 0x55, 0x4d, 0x5d, 0x43,
+  0x53, 0x4b, 0x5b, 0x47, 0x57, 0x4f, 0xdf, 0xc0, 0xd0, 0xc8, 0xd8, 0xc4, 0xd4,
+  0xcc, 0xdc, 0xc2, 0xd2, 0xca, 0xda, 0xc6, 0xd6, 0xce, 0xde, 0xc1, 0xd1, 0xc9,
+  0xd9, 0xc5, 0xd5, 0xcd, 0xdd, 0xc3, 0xd3, 0xcb, 0xdb, 0xc7, 0xd7, 0xcf, 0x3f,
+  0x20, 0x30, 0x28, 0x38, 0x24, 0x34, 0x2c, 0x3c, 0x22, 0x32, 0x2a, 0x3a, 0x26,
+  0x36, 0x2e, 0x3e, 0x21, 0x31, 0x29, 0x39, 0x25, 0x35, 0x2d, 0x3d, 0x23, 0x33,
+  0x2b, 0x3b, 0x27, 0x37, 0x2f, 0xbf, 0xa0, 0xb0, 0xa8, 0xb8, 0xa4, 0xb4, 0xac,
+  0xbc, 0xa2, 0xb2, 0xaa, 0xba, 0xa6, 0xb6, 0xae, 0xbe, 0xa1, 0xb1, 0xa9, 0xb9,
+  0xa5, 0xb5, 0xad, 0xbd, 0xa3, 0xb3, 0xab, 0xbb, 0xa7, 0xb7, 0xaf, 0x7f, 0xc2,
+  0xc4, 0x49, 0x93, 0xa7, 0x4c, 0x9d, 0x36, 0x7d, 0xc6, 0xcc, 0x59, 0xb3, 0xe7,
+  0xcc, 0x9d, 0x37, 0x7f, 0xc1, 0xc2, 0x45, 0x8b, 0x97, 0x2c, 0x5d, 0xb6, 0x7c,
+  0xc5, 0xca, 0x55, 0xab, 0xd7, 0xac, 0x5d, 0xb7, 0x7e, 0xc3, 0xc6, 0x4d, 0x9b,
+  0xb7, 0x6c, 0xdd, 0xb6, 0x7d, 0xc7, 0xce, 0x5d, 0xbb, 0xf7, 0xec, 0xdd, 0xb7,
+  0xff, 0xc0, 0xc1, 0x43, 0x87, 0x8f, 0x1c, 0x3d, 0x76, 0xfc, 0xc4, 0xc9, 0x53,
+  0xa7, 0xcf, 0x9c, 0x3d, 0x77, 0xfe, 0xc2, 0xc5, 0x4b, 0x97, 0xaf, 0x5c, 0xbd,
+  0x76, 0xfd, 0xc6, 0xcd, 0x5b, 0xb7, 0xef, 0xdc, 0xbd, 0x77, 0xff, 0xc1, 0xc3,
+  0x47, 0x8f, 0x9f, 0x3c, 0x7d, 0xf6, 0xfc, 0xc5, 0xcb, 0x57, 0xaf, 0xdf, 0xbc,
+  0x7d, 0xf7, 0xfe, 0xc3, 0xc7, 0x4f, 0x9f, 0xbf, 0x7c, 0xfd, 0xf6, 0xfd, 0xc7,
+  0xcf, 0x5f, 0xbf, 0xff, 0xfc, 0xfd, 0xf7, 0x7f, 0xd4, 0xff, 0xa3, 0xfe, 0x1f,
+  0xf5, 0xff, 0xa8, 0xff, 0x47, 0xfd, 0x3f, 0xea, 0xff, 0x51, 0xff, 0x8f, 0xfa,
+  0x7f, 0xd4, 0xff, 0xa3, 0xfe, 0x1f, 0x51, 0xfe, 0x1f, 0x05, 0xa3, 0x60, 0x14,
+  0x8c, 0x82, 0x51, 0x30, 0x0a, 0x46, 0xc1, 0x28, 0x18, 0x05, 0xa3, 0x60, 0x14,
+  0x8c, 0x82, 0x51, 0x30, 0x22, 0x00, 0x00, 0xe0, 0x2a, 0xd2, 0x03, 0x00, 0x28,
+  0x00, 0x00,
+];
+
+/// Build a stored (uncompressed) zip with the given members.
+fn build_zip(members: &[(&str, &[u8])]) -> Vec<u8>
+{
+  let mut out = Vec::new();
+  let mut central = Vec::new();
+  // 2024-03-05 14:30:20 in DOS format
+  let date: u16 = ((2024 - 1980) << 9) | (3 << 5) | 5;
+  let time: u16 = (14 << 11) | (30 << 5) | 10;
+  for (name, data) in members
+  {
+    let offset = out.len() as u32;
+    let mut header = Vec::new();
+    header.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+    header.extend_from_slice(&[20, 0, 0, 0, 0, 0]);
+    header.extend_from_slice(&time.to_le_bytes());
+    header.extend_from_slice(&date.to_le_bytes());
+    header.extend_from_slice(&0u32.to_le_bytes());
+    header.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    header.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    header.extend_from_slice(&0u16.to_le_bytes());
+    out.extend_from_slice(&header);
+    out.extend_from_slice(name.as_bytes());
+    out.extend_from_slice(data);
+
+    central.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+    central.extend_from_slice(&[20, 0]);
+    central.extend_from_slice(&header[4..]);
+    // comment length, disk number, internal attributes
+    central.extend_from_slice(&[0u8; 6]);
+    central.extend_from_slice(&0u32.to_le_bytes());
+    central.extend_from_slice(&offset.to_le_bytes());
+    central.extend_from_slice(name.as_bytes());
+  }
+  let cd_offset = out.len() as u32;
+  out.extend_from_slice(&central);
+  out.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+  out.extend_from_slice(&[0u8; 4]);
+  out.extend_from_slice(&(members.len() as u16).to_le_bytes());
+  out.extend_from_slice(&(members.len() as u16).to_le_bytes());
+  out.extend_from_slice(&(central.len() as u32).to_le_bytes());
+  out.extend_from_slice(&cd_offset.to_le_bytes());
+  out.extend_from_slice(&0u16.to_le_bytes());
+  out
+}
+
+#[test]
+fn detect_by_extension()
+{
+  let kind = |n: &str| detect(std::path::Path::new(n));
+  assert_eq!(kind("a.zip"), Some(ArchiveKind::Zip));
+  assert_eq!(kind("A.TAR.GZ"), Some(ArchiveKind::TarGz));
+  assert_eq!(kind("b.tgz"), Some(ArchiveKind::TarGz));
+  assert_eq!(kind("c.tar"), Some(ArchiveKind::Tar));
+  assert_eq!(kind("d.7z"), Some(ArchiveKind::SevenZip));
+  assert_eq!(kind("e.gz"), None);
+}
+
+#[test]
+fn lists_zip_central_directory()
+{
+  let temp = tempfile::tempdir().expect("tempdir");
+  let path = temp.path().join("t.zip");
+  fs::write(&path, build_zip(&[("dir/", b""), ("dir/a.txt", b"hello")]))
+    .unwrap();
+
+  let listing = list(&path, ArchiveKind::Zip, 100).expect("list zip");
+  assert!(!listing.truncated);
+  let names: Vec<_> = listing.entries.iter().map(|e| e.name.as_str()).collect();
+  assert_eq!(names, vec!["dir/", "dir/a.txt"]);
+  assert!(listing.entries[0].is_dir);
+  assert_eq!(listing.entries[1].size, 5);
+  assert_eq!(
+    listing.entries[1].modified.map(|d| d.to_string()).as_deref(),
+    Some("2024-03-05 14:30:20")
+  );
+
+  let capped = list(&path, ArchiveKind::Zip, 1).expect("list zip");
+  assert_eq!(capped.entries.len(), 1);
+  assert!(capped.truncated);
+}
+
+#[test]
+fn lists_gzipped_tar_with_long_names()
+{
+  let temp = tempfile::tempdir().expect("tempdir");
+  let path = temp.path().join("t.tar.gz");
+  fs::write(&path, TAR_GZ).unwrap();
+
+  let listing = list(&path, ArchiveKind::TarGz, 100).expect("list tar.gz");
+  let entries: Vec<_> = listing
+    .entries
+    .iter()
+    .map(|e| (e.name.clone(), e.size, e.is_dir))
+    .collect();
+  assert_eq!(
+    entries,
+    vec![
+      ("docs/".to_string(), 0, true),
+      ("docs/readme.txt".to_string(), 800, false),
+      (format!("data/{}.bin", "n".repeat(120)), 3072, false),
+    ]
+  );
+}
+
+#[test]
+fn gunzip_round_trips_tar_blocks()
+{
+  let mut out = Vec::new();
+  lsv::core::inflate::gunzip(TAR_GZ, |chunk| {
+    out.extend_from_slice(chunk);
+    true
+  })
+  .expect("gunzip");
+  assert_eq!(out.len(), 10240);
+  assert!(out.windows(19).any(|w| w == b"lsv archive preview"));
+}
+
+#[test]
+fn lists_plain_tar_by_seeking_over_members()
+{
+  let mut tar = Vec::new();
+  lsv::core::inflate::gunzip(TAR_GZ, |chunk| {
+    tar.extend_from_slice(chunk);
+    true
+  })
+  .expect("gunzip");
+  let temp = tempfile::tempdir().expect("tempdir");
+  let path = temp.path().join("t.tar");
+  fs::write(&path, &tar).unwrap();
+
+  let listing = list(&path, ArchiveKind::Tar, 100).expect("list tar");
+  let names: Vec<_> = listing.entries.iter().map(|e| e.name.clone()).collect();
+  assert_eq!(
+    names,
+    vec![
+      "docs/".to_string(),
+      "docs/readme.txt".to_string(),
+      format!("data/{}.bin", "n".repeat(120)),
+    ]
+  );
+  assert!(!listing.truncated);
+
+  // A tar cut off inside a member lists what precedes it
+  fs::write(&path, &tar[..2048]).unwrap();
+  let cut = list(&path, ArchiveKind::Tar, 100).expect("list cut tar");
+  assert_eq!(cut.entries.len(), 2);
+}
@@ -0,0 +1,98 @@
+use lsv::core::checksum::{
+  Algorithm,
+  Hasher,
+  ManifestEntry,
+  file_digest,
+  parse_manifest,
+};
+
+fn digest(
+  algo: Algorithm,
+  data: &[u8],
+) -> String
+{
+  let mut h = Hasher::new(algo);
+  h.update(data);
+  h.finish()
+}
+
+#[test]
+fn digests_match_known_vectors()
+{
+  let cases: [(Algorithm, &[u8], &str); 7] = [
+    (Algorithm::Md5, b"", "d41d8cd98f00b204e9800998ecf8427e"),
+    (Algorithm::Md5, b"abc", "900150983cd24fb0d6963f7d28e17f72"),
+    (Algorithm::Sha1, b"", "da39a3ee5e6b4b0d3255bfef95601890afd80709"),
+    (Algorithm::Sha1, b"abc", "a9993e364706816aba3e25717850c26c9cd0d89d"),
+    (
+      Algorithm::Sha256,
+      b"",
+      "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+    ),
+    (
+      Algorithm::Sha256,
+      b"abc",
+      "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+    ),
+    // Two blocks once padded
+    (
+      Algorithm::Sha256,
+      b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+      "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+    ),
+  ];
+  for (algo, data, want) in cases
+  {
+    assert_eq!(digest(algo, data), want, "{:?} of {:?}", algo, data);
+  }
+}
+
+#[test]
+fn file_digests_span_many_chunks()
+{
+  let tmp = tempfile::tempdir().expect("tmp");
+  let path = tmp.path().join("a.bin");
+  let data = vec![b'a'; 1_000_000];
+  std::fs::write(&path, &data).unwrap();
+  assert_eq!(
+    file_digest(Algorithm::Sha1, &path).unwrap(),
+    "34aa973cd4c4daa4f61eeb2bdbad27316534016f"
+  );
+  assert_eq!(
+    file_digest(Algorithm::Md5, &path).unwrap(),
+    "7707d6ae4e027c70eea2a935c2296f21"
+  );
+}
+
+#[test]
+fn manifests_in_gnu_and_bsd_format()
+{
+  let sha = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+  let text = format!(
+    "# comment\n{}  empty.txt\n{} *with space.bin\n\nMD5 (x.txt) = \
+     900150983CD24FB0D6963F7D28E17F72\nnot a line\n",
+    sha, sha
+  );
+  let (entries, bad) = parse_manifest(&text);
+  assert_eq!(bad, vec![6]);
+  assert_eq!(
+    entries,
+    vec![
+      ManifestEntry {
+        algo: Algorithm::Sha256,
+        hash: sha.to_string(),
+        name: "empty.txt".into(),
+      },
+      ManifestEntry {
+        algo: Algorithm::Sha256,
+        hash: sha.to_string(),
+        name: "with space.bin".into(),
+      },
+      ManifestEntry {
+        algo: Algorithm::Md5,
+        hash: "900150983cd24fb0d6963f7d28e17f72".into(),
+        name: "x.txt".into(),
+      },
+    ]
+  );
+}
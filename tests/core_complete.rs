@@ -0,0 +1,72 @@
+use std::fs;
+
+use lsv::core::complete::complete_path;
+#[cfg(unix)]
+use lsv::core::complete::{
+  expand_tilde,
+  resolve_typed_path,
+};
+
+#[test]
+fn completes_unique_names_and_common_prefixes()
+{
+  let temp = tempfile::tempdir().expect("tempdir");
+  fs::create_dir(temp.path().join("src")).unwrap();
+  fs::write(temp.path().join("src").join("main.rs"), "").unwrap();
+  fs::write(temp.path().join("report-2023.txt"), "").unwrap();
+  fs::write(temp.path().join("report-2024.txt"), "").unwrap();
+  fs::write(temp.path().join(".secret"), "").unwrap();
+
+  // A unique directory gains a slash, a unique file completes in full
+  let c = complete_path("sr", temp.path(), false);
+  assert_eq!(c.completed, "src/");
+  assert!(c.candidates.is_empty());
+  assert_eq!(
+    complete_path("src/m", temp.path(), false).completed,
+    "src/main.rs"
+  );
+
+  // Several matches extend to their common prefix and are listed
+  let c = complete_path("re", temp.path(), false);
+  assert_eq!(c.completed, "report-202");
+  assert_eq!(c.candidates, ["report-2023.txt", "report-2024.txt"]);
+
+  // Hidden entries need a leading dot unless hidden files are shown
+  assert_eq!(complete_path("", temp.path(), false).candidates.len(), 3);
+  assert_eq!(complete_path("", temp.path(), true).candidates.len(), 4);
+  assert_eq!(complete_path(".s", temp.path(), false).completed, ".secret");
+
+  // No match leaves the word alone
+  let c = complete_path("zzz", temp.path(), false);
+  assert_eq!(c.completed, "zzz");
+  assert!(c.candidates.is_empty());
+
+  // Absolute paths ignore the current directory
+  let abs = format!("{}/sr", temp.path().display());
+  assert_eq!(
+    complete_path(&abs, std::path::Path::new("/"), false).completed,
+    format!("{}/src/", temp.path().display())
+  );
+}
+
+#[cfg(unix)]
+#[test]
+fn tilde_expands_to_home()
+{
+  let Ok(home) = std::env::var("HOME")
+  else
+  {
+    return;
+  };
+  assert_eq!(expand_tilde("~"), std::path::PathBuf::from(&home));
+  assert_eq!(expand_tilde("~/x"), std::path::Path::new(&home).join("x"));
+  assert_eq!(expand_tilde("a/~"), std::path::PathBuf::from("a/~"));
+  assert_eq!(
+    resolve_typed_path("docs", std::path::Path::new("/base")),
+    std::path::PathBuf::from("/base/docs")
+  );
+  assert_eq!(
+    complete_path("~", std::path::Path::new("/"), false).completed,
+    "~/"
+  );
+}
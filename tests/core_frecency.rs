@@ -0,0 +1,96 @@
+use std::path::{
+  Path,
+  PathBuf,
+};
+
+use lsv::core::frecency::{
+  MAX_TOTAL_RANK,
+  Visit,
+  add_visit,
+  load_db,
+  matches,
+  parse_zoxide_scores,
+  save_db,
+  score,
+  scored_matches,
+};
+
+#[test]
+fn keywords_match_in_order_with_the_last_in_the_final_component()
+{
+  let p = Path::new("/home/me/src/Lsv/docs");
+  assert!(matches("", p));
+  assert!(matches("docs", p));
+  assert!(matches("lsv docs", p));
+  assert!(matches("SRC do", p));
+  assert!(!matches("docs lsv", p));
+  // "lsv" is only a parent here
+  assert!(!matches("lsv", p));
+}
+
+#[test]
+fn recent_visits_score_higher()
+{
+  let now = 1_000_000_000;
+  let v = |last| Visit { path: PathBuf::from("/a"), rank: 2.0, last };
+  assert_eq!(score(&v(now - 10), now), 8.0);
+  assert_eq!(score(&v(now - 2 * 60 * 60), now), 4.0);
+  assert_eq!(score(&v(now - 2 * 24 * 60 * 60), now), 1.0);
+  assert_eq!(score(&v(now - 30 * 24 * 60 * 60), now), 0.5);
+}
+
+#[test]
+fn visits_bump_the_rank_and_age_the_database()
+{
+  let mut db = Vec::new();
+  add_visit(&mut db, Path::new("/a"), 10);
+  add_visit(&mut db, Path::new("/a"), 20);
+  add_visit(&mut db, Path::new("/b"), 15);
+  assert_eq!(db[0], Visit { path: PathBuf::from("/a"), rank: 2.0, last: 20 });
+  assert_eq!(db[1].rank, 1.0);
+
+  db[0].rank = MAX_TOTAL_RANK;
+  add_visit(&mut db, Path::new("/a"), 30);
+  let total: f64 = db.iter().map(|v| v.rank).sum();
+  assert!(total <= MAX_TOTAL_RANK);
+  // "/b" decayed below a single visit and was dropped
+  assert_eq!(db.len(), 1);
+}
+
+#[test]
+fn scored_matches_filter_by_query()
+{
+  let db = vec![
+    Visit { path: PathBuf::from("/x/lsv"), rank: 1.0, last: 0 },
+    Visit { path: PathBuf::from("/x/other"), rank: 3.0, last: 0 },
+  ];
+  let hits = scored_matches(&db, "ls", 0);
+  assert_eq!(hits, vec![(4.0, PathBuf::from("/x/lsv"))]);
+}
+
+#[test]
+fn database_round_trips_paths_with_bars()
+{
+  let temp = tempfile::tempdir().expect("tempdir");
+  let file = temp.path().join("frecency");
+  let db = vec![
+    Visit { path: PathBuf::from("/a|b"), rank: 2.5, last: 7 },
+    Visit { path: PathBuf::from("/c"), rank: 1.0, last: 9 },
+  ];
+  save_db(&file, &db).unwrap();
+  assert_eq!(load_db(&file), db);
+  assert!(load_db(&temp.path().join("missing")).is_empty());
+}
+
+#[test]
+fn zoxide_scores_are_parsed()
+{
+  let out = "  12.5 /home/me/src\n   4.0 /tmp/with space\nbogus\n";
+  assert_eq!(
+    parse_zoxide_scores(out),
+    vec![
+      (12.5, PathBuf::from("/home/me/src")),
+      (4.0, PathBuf::from("/tmp/with space")),
+    ]
+  );
+}
@@ -32,3 +32,159 @@ fn copy_move_remove_paths()
   lsv::core::fs_ops::remove_path_all(&c).expect("remove");
   assert!(!c.exists());
 }
+
+#[test]
+fn copy_with_progress_reports_and_cancels()
+{
+  use lsv::core::fs_ops::{
+    CopyProgress,
+    copy_path_with_progress,
+    path_totals,
+  };
+
+  let tmp = tempfile::tempdir().expect("tmp");
+  let src = tmp.path().join("src");
+  fs::create_dir_all(src.join("sub")).unwrap();
+  fs::write(src.join("a.bin"), vec![1u8; 4096]).unwrap();
+  fs::write(src.join("sub").join("b.bin"), vec![2u8; 1000]).unwrap();
+  assert_eq!(path_totals(&src), (5096, 2));
+
+  let (mut bytes, mut files) = (0u64, 0u64);
+  let dst = tmp.path().join("dst");
+  copy_path_with_progress(&src, &dst, &mut |step| {
+    match step
+    {
+      CopyProgress::Bytes(n) => bytes += n,
+      CopyProgress::FileDone => files += 1,
+    }
+    Ok(())
+  })
+  .expect("copy");
+  assert_eq!((bytes, files), (5096, 2));
+  assert_eq!(fs::read(dst.join("sub").join("b.bin")).unwrap(), vec![2u8; 1000]);
+
+  // An error from the callback aborts the copy
+  let res =
+    copy_path_with_progress(&src, &tmp.path().join("dst2"), &mut |_| {
+      Err(std::io::Error::from(std::io::ErrorKind::Interrupted))
+    });
+  assert!(res.is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn copy_with_progress_keeps_symlinks()
+{
+  use lsv::core::fs_ops::{
+    CopyProgress,
+    copy_path_with_progress,
+    path_totals,
+  };
+
+  let tmp = tempfile::tempdir().expect("tmp");
+  let src = tmp.path().join("src");
+  fs::create_dir_all(&src).unwrap();
+  fs::write(src.join("a.txt"), b"data").unwrap();
+  std::os::unix::fs::symlink("a.txt", src.join("link")).unwrap();
+  // A link back up the tree would otherwise be copied forever
+  std::os::unix::fs::symlink("..", src.join("up")).unwrap();
+
+  let (mut bytes, mut files) = (0u64, 0u64);
+  let dst = tmp.path().join("dst");
+  copy_path_with_progress(&src, &dst, &mut |step| {
+    match step
+    {
+      CopyProgress::Bytes(n) => bytes += n,
+      CopyProgress::FileDone => files += 1,
+    }
+    Ok(())
+  })
+  .expect("copy");
+  assert_eq!((bytes, files), path_totals(&src));
+  assert_eq!(
+    fs::read_link(dst.join("link")).unwrap(),
+    std::path::Path::new("a.txt")
+  );
+  assert_eq!(
+    fs::read_link(dst.join("up")).unwrap(),
+    std::path::Path::new("..")
+  );
+  assert_eq!(fs::read(dst.join("link")).unwrap(), b"data");
+}
+
+#[test]
+fn relative_paths_between_directories()
+{
+  use std::path::{
+    Path,
+    PathBuf,
+  };
+
+  use lsv::core::fs_ops::relative_path;
+  let rel =
+    |dir: &str, target: &str| relative_path(Path::new(dir), Path::new(target));
+  assert_eq!(rel("/a/b", "/a/c/f.txt"), PathBuf::from("../c/f.txt"));
+  assert_eq!(rel("/a", "/a/b/f.txt"), PathBuf::from("b/f.txt"));
+  assert_eq!(rel("/a/b/c", "/x"), PathBuf::from("../../../x"));
+  assert_eq!(rel("/a/b", "/a/b"), PathBuf::from("."));
+}
+
+#[test]
+fn free_names_count_up_past_taken_ones()
+{
+  use lsv::core::fs_ops::free_name;
+  let tmp = tempfile::tempdir().expect("tmp");
+  let root = tmp.path();
+  assert_eq!(free_name(&root.join("a.txt")), root.join("a.txt"));
+  fs::write(root.join("a.txt"), b"").unwrap();
+  fs::write(root.join("a_1.txt"), b"").unwrap();
+  assert_eq!(free_name(&root.join("a.txt")), root.join("a_2.txt"));
+  fs::create_dir(root.join(".config")).unwrap();
+  assert_eq!(free_name(&root.join(".config")), root.join(".config_1"));
+}
+
+#[test]
+fn normalize_resolves_dots_lexically()
+{
+  use std::path::{
+    Path,
+    PathBuf,
+  };
+
+  use lsv::core::fs_ops::normalize;
+  assert_eq!(normalize(Path::new("/a/./b/../c")), PathBuf::from("/a/c"));
+  assert_eq!(normalize(Path::new("/a/b/../..")), PathBuf::from("/"));
+  assert_eq!(normalize(Path::new("../x")), PathBuf::from("../x"));
+}
+
+#[test]
+fn removals_and_transfers_have_safety_rails()
+{
+  use std::path::Path;
+
+  use lsv::core::fs_ops::{
+    check_remove,
+    check_transfer,
+  };
+  let tmp = tempfile::tempdir().expect("tmp");
+  let root = tmp.path();
+  let home = root.join("home");
+  let work = home.join("work");
+  fs::create_dir_all(work.join("sub")).unwrap();
+  fs::write(work.join("a.txt"), b"A").unwrap();
+
+  assert!(check_remove(Path::new("/"), &work, Some(&home)).is_err());
+  assert!(check_remove(&home, root, Some(&home)).is_err());
+  // The current directory and its ancestors
+  assert!(check_remove(&work, &work, None).is_err());
+  assert!(check_remove(&home, &work, None).is_err());
+  assert!(check_remove(&work.join("gone"), &work, None).is_err());
+  assert!(check_remove(&work.join("a.txt"), &work, Some(&home)).is_ok());
+
+  assert!(check_transfer(&work.join("a.txt"), &work.join("b.txt")).is_ok());
+  assert!(check_transfer(&work.join("gone"), &work.join("b.txt")).is_err());
+  // Taken destinations, missing parents, and a directory into itself
+  assert!(check_transfer(&work.join("a.txt"), &work.join("sub")).is_err());
+  assert!(check_transfer(&work.join("a.txt"), &work.join("x/a.txt")).is_err());
+  assert!(check_transfer(&work, &work.join("sub/work")).is_err());
+}
@@ -0,0 +1,44 @@
+use lsv::core::fuzzy::{
+  fuzzy_positions,
+  fuzzy_score,
+  rank,
+};
+
+#[test]
+fn fuzzy_score_requires_ordered_subsequence()
+{
+  assert!(fuzzy_score("mrs", "src/main.rs").is_some());
+  assert!(fuzzy_score("MAIN", "src/main.rs").is_some());
+  assert!(fuzzy_score("srm", "src/main.rs").is_some());
+  assert!(fuzzy_score("xyz", "src/main.rs").is_none());
+  assert!(fuzzy_score("sm.rx", "src/main.rs").is_none());
+  assert_eq!(fuzzy_score("", "anything"), Some(0));
+}
+
+#[test]
+fn rank_prefers_file_name_and_contiguous_matches()
+{
+  let files = [
+    "docs/configuration.md",
+    "src/config/mod.rs",
+    "src/app/config_loader.rs",
+    "tests/cfg.rs",
+  ];
+  let ranked = rank("config", &files, 10);
+  // "cfg.rs" is not a subsequence match for "config"
+  assert_eq!(ranked.len(), 3);
+  // Matches inside the file name beat a match on a directory component
+  assert_eq!(files[ranked[2].0], "src/config/mod.rs");
+  // Among equal matches the shorter path wins
+  assert_eq!(files[ranked[0].0], "docs/configuration.md");
+  assert_eq!(rank("config", &files, 1).len(), 1);
+}
+
+#[test]
+fn positions_follow_the_scored_match()
+{
+  assert_eq!(fuzzy_positions("abc", "a_b_c.txt"), Some(vec![0, 2, 4]));
+  assert_eq!(fuzzy_positions("RPT", "report"), Some(vec![0, 2, 5]));
+  assert_eq!(fuzzy_positions("", "x"), Some(vec![]));
+  assert_eq!(fuzzy_positions("xz", "zx"), None);
+}
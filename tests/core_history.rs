@@ -0,0 +1,30 @@
+use std::fs;
+
+use lsv::core::history::{
+  append_history,
+  load_history,
+  push_entry,
+};
+
+#[test]
+fn push_moves_duplicates_to_the_end_and_trims()
+{
+  let mut entries = vec![":a".to_string(), ":b".to_string(), ":c".to_string()];
+  push_entry(&mut entries, ":a", 3);
+  assert_eq!(entries, [":b", ":c", ":a"]);
+  push_entry(&mut entries, "/d", 3);
+  assert_eq!(entries, [":c", ":a", "/d"]);
+}
+
+#[test]
+fn append_merges_with_the_file()
+{
+  let temp = tempfile::tempdir().expect("tempdir");
+  let path = temp.path().join("history");
+  assert!(load_history(&path).is_empty());
+
+  fs::write(&path, ":cd /tmp\n/readme\n").unwrap();
+  append_history(&path, &[":sort size".to_string(), ":cd /tmp".to_string()])
+    .unwrap();
+  assert_eq!(load_history(&path), ["/readme", ":sort size", ":cd /tmp"]);
+}
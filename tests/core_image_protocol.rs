@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+use lsv::core::image_protocol::{
+  ImageProtocol,
+  Source,
+  detect,
+  is_valid_setting,
+  resolve,
+  terminal_name,
+};
+
+fn env_of(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String>
+{
+  let vars: HashMap<String, String> =
+    vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+  move |k: &str| vars.get(k).cloned()
+}
+
+#[test]
+fn terminals_are_recognised_from_the_environment()
+{
+  let cases = [
+    (vec![("TERM", "xterm-kitty")], ImageProtocol::Kitty),
+    (
+      vec![("TERM", "xterm-256color"), ("KITTY_WINDOW_ID", "1")],
+      ImageProtocol::Kitty,
+    ),
+    (vec![("TERM_PROGRAM", "iTerm.app")], ImageProtocol::Iterm2),
+    (vec![("TERM_PROGRAM", "WezTerm")], ImageProtocol::Iterm2),
+    (vec![("TERM", "foot")], ImageProtocol::Sixel),
+    (vec![("TERM", "xterm-256color")], ImageProtocol::Halfblocks),
+    (vec![], ImageProtocol::Halfblocks),
+  ];
+  for (vars, expected) in cases
+  {
+    assert_eq!(detect(&env_of(&vars)), expected, "{:?}", vars);
+  }
+}
+
+#[test]
+fn explicit_setting_beats_overrides_and_detection()
+{
+  let env = env_of(&[("TERM_PROGRAM", "WezTerm"), ("TERM", "xterm-256color")]);
+  let mut overrides = HashMap::new();
+  overrides.insert(String::from("wezterm"), String::from("kitty"));
+
+  assert_eq!(
+    resolve(Some("sixel"), &overrides, &env),
+    (ImageProtocol::Sixel, Source::Config)
+  );
+  assert_eq!(
+    resolve(Some("auto"), &overrides, &env),
+    (ImageProtocol::Kitty, Source::Override(String::from("wezterm")))
+  );
+  assert_eq!(
+    resolve(None, &HashMap::new(), &env),
+    (ImageProtocol::Iterm2, Source::Detected)
+  );
+}
+
+#[test]
+fn terminal_name_prefers_term_program()
+{
+  assert_eq!(
+    terminal_name(&env_of(&[("TERM_PROGRAM", "WezTerm"), ("TERM", "xterm")])),
+    Some(String::from("wezterm"))
+  );
+  assert_eq!(
+    terminal_name(&env_of(&[("TERM", "foot")])),
+    Some(String::from("foot"))
+  );
+  assert_eq!(terminal_name(&env_of(&[])), None);
+}
+
+#[test]
+fn settings_are_validated()
+{
+  for ok in ["auto", "Kitty", "iterm2", "sixel", "halfblocks"]
+  {
+    assert!(is_valid_setting(ok), "{}", ok);
+  }
+  assert!(!is_valid_setting("ascii"));
+  assert_eq!(ImageProtocol::parse("auto"), None);
+}
@@ -0,0 +1,87 @@
+use std::{
+  cmp::Ordering,
+  fs,
+};
+
+use lsv::core::listing::natural_cmp;
+
+#[test]
+fn natural_order_compares_numbers_by_value()
+{
+  let mut names =
+    vec!["file10", "File2", "file1", "file02", "file", "a10b2", "a10b10"];
+  names.sort_by(|a, b| natural_cmp(a, b));
+  assert_eq!(
+    names,
+    ["a10b2", "a10b10", "file", "file1", "File2", "file02", "file10"]
+  );
+}
+
+#[test]
+fn natural_order_ties_on_padding_and_case()
+{
+  assert_eq!(natural_cmp("v1.2", "v1.10"), Ordering::Less);
+  assert_eq!(natural_cmp("x2", "x02"), Ordering::Less);
+  assert_eq!(natural_cmp("b", "A"), Ordering::Greater);
+  assert_eq!(natural_cmp("same", "same"), Ordering::Equal);
+}
+
+fn sorted_names(sort: &str) -> Vec<String>
+{
+  let temp = tempfile::tempdir().expect("tempdir");
+  for n in ["img10.png", "img2.png", "notes.txt", "b.md", "Makefile"]
+  {
+    fs::write(temp.path().join(n), "x").unwrap();
+  }
+  fs::create_dir(temp.path().join("dir1.d")).unwrap();
+  fs::create_dir(temp.path().join("dir0.z")).unwrap();
+  let mut app = lsv::app::App::new().expect("app new");
+  app.set_cwd(temp.path());
+  app.finish_loading();
+  lsv::dispatch_action(&mut app, &format!("sort:{}", sort)).unwrap();
+  (0..10).filter_map(|i| app.get_current_entry_name(i)).collect()
+}
+
+#[test]
+fn natural_and_extension_keys_sort_listings()
+{
+  assert_eq!(
+    sorted_names("natural"),
+    [
+      "dir0.z",
+      "dir1.d",
+      "b.md",
+      "img2.png",
+      "img10.png",
+      "Makefile",
+      "notes.txt"
+    ]
+  );
+  // Files without an extension come first; directories stay by name
+  assert_eq!(
+    sorted_names("extension"),
+    [
+      "dir0.z",
+      "dir1.d",
+      "Makefile",
+      "b.md",
+      "img2.png",
+      "img10.png",
+      "notes.txt"
+    ]
+  );
+}
+
+#[test]
+fn dotfiles_and_the_hidden_attribute_are_hidden()
+{
+  use lsv::core::listing::{
+    FILE_ATTRIBUTE_HIDDEN,
+    is_hidden,
+  };
+  assert!(is_hidden(".env", 0));
+  assert!(!is_hidden("notes.txt", 0));
+  assert!(is_hidden("desktop.ini", FILE_ATTRIBUTE_HIDDEN | 0x4));
+  // Read-only and archive bits alone don't hide a file
+  assert!(!is_hidden("report.doc", 0x1 | 0x20));
+}
@@ -0,0 +1,31 @@
+use lsv::core::lru::{
+  DEFAULT_CAPACITY,
+  Lru,
+};
+
+#[test]
+fn least_recently_used_entry_is_evicted()
+{
+  let mut cache: Lru<usize, String> = Lru::default();
+  for i in 0..DEFAULT_CAPACITY
+  {
+    cache.insert(i, i.to_string());
+  }
+  // Touch the oldest entry so the next one becomes the eviction candidate
+  assert_eq!(cache.get(&0).map(String::as_str), Some("0"));
+  cache.insert(DEFAULT_CAPACITY, String::from("new"));
+  assert!(cache.get(&1).is_none());
+  assert!(cache.get(&0).is_some());
+  assert_eq!(cache.get(&DEFAULT_CAPACITY).map(String::as_str), Some("new"));
+}
+
+#[test]
+fn inserting_an_existing_key_replaces_it()
+{
+  let mut cache = Lru::default();
+  cache.insert("k", 1);
+  cache.insert("k", 2);
+  assert_eq!(cache.get(&"k"), Some(&2));
+  cache.clear();
+  assert!(cache.get(&"k").is_none());
+}
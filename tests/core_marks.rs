@@ -0,0 +1,47 @@
+use std::{
+  collections::HashMap,
+  path::PathBuf,
+};
+
+use lsv::core::marks::{
+  load_marks,
+  save_marks,
+  valid_name,
+};
+
+#[test]
+fn named_marks_round_trip()
+{
+  let temp = tempfile::tempdir().expect("tempdir");
+  let file = temp.path().join("marks");
+  let mut marks = HashMap::new();
+  marks.insert(String::from("a"), PathBuf::from("/tmp"));
+  marks.insert(String::from("Projects"), PathBuf::from("/home/me/src"));
+  save_marks(&file, &marks).unwrap();
+  assert_eq!(
+    std::fs::read_to_string(&file).unwrap(),
+    "Projects\t/home/me/src\na\t/tmp\n"
+  );
+  assert_eq!(load_marks(&file), marks);
+}
+
+#[test]
+fn single_character_marks_still_load()
+{
+  let temp = tempfile::tempdir().expect("tempdir");
+  let file = temp.path().join("marks");
+  std::fs::write(&file, "# marks\nx\t/srv\n\n").unwrap();
+  let marks = load_marks(&file);
+  assert_eq!(marks.get("x"), Some(&PathBuf::from("/srv")));
+  assert_eq!(marks.len(), 1);
+}
+
+#[test]
+fn mark_names_cannot_contain_whitespace()
+{
+  assert!(valid_name("work"));
+  assert!(valid_name("a"));
+  assert!(!valid_name(""));
+  assert!(!valid_name("my work"));
+  assert!(!valid_name("tab\there"));
+}
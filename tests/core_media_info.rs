@@ -0,0 +1,199 @@
+use std::path::Path;
+
+use lsv::core::media_info::{
+  MediaKind,
+  image_info,
+  media_kind,
+  parse_ffprobe,
+};
+
+const ASCII: u16 = 2;
+const SHORT: u16 = 3;
+const LONG: u16 = 4;
+const RATIONAL: u16 = 5;
+
+/// Tag, type, and the value's count and bytes.
+type Field = (u16, u16, (u32, Vec<u8>));
+
+fn text(s: &str) -> (u32, Vec<u8>)
+{
+  let mut v = s.as_bytes().to_vec();
+  v.push(0);
+  (v.len() as u32, v)
+}
+
+fn rationals(values: &[(u32, u32)]) -> (u32, Vec<u8>)
+{
+  let mut v = Vec::new();
+  for (n, d) in values
+  {
+    v.extend(n.to_le_bytes());
+    v.extend(d.to_le_bytes());
+  }
+  (values.len() as u32, v)
+}
+
+/// Append a little-endian IFD at the end of `out`, its out-of-line values
+/// right after it. Returns its offset.
+fn write_ifd(
+  out: &mut Vec<u8>,
+  entries: &[Field],
+) -> u32
+{
+  let start = out.len();
+  let data_start = start + 2 + entries.len() * 12 + 4;
+  let mut data: Vec<u8> = Vec::new();
+  out.extend((entries.len() as u16).to_le_bytes());
+  for (tag, kind, (count, value)) in entries
+  {
+    out.extend(tag.to_le_bytes());
+    out.extend(kind.to_le_bytes());
+    out.extend(count.to_le_bytes());
+    if value.len() <= 4
+    {
+      let mut inline = value.clone();
+      inline.resize(4, 0);
+      out.extend(inline);
+    }
+    else
+    {
+      out.extend(((data_start + data.len()) as u32).to_le_bytes());
+      data.extend(value);
+    }
+  }
+  out.extend(0u32.to_le_bytes());
+  out.extend(data);
+  start as u32
+}
+
+fn sample_tiff() -> Vec<u8>
+{
+  let mut tiff = b"II\x2a\x00\0\0\0\0".to_vec();
+  let exif = write_ifd(
+    &mut tiff,
+    &[
+      (0x829A, RATIONAL, rationals(&[(1, 250)])),
+      (0x829D, RATIONAL, rationals(&[(28, 10)])),
+      (0x8827, SHORT, (1, 200u16.to_le_bytes().to_vec())),
+      (0x9003, ASCII, text("2024:05:01 10:20:30")),
+      (0x920A, RATIONAL, rationals(&[(35, 1)])),
+      (0xA002, LONG, (1, 4000u32.to_le_bytes().to_vec())),
+      (0xA003, SHORT, (1, 3000u16.to_le_bytes().to_vec())),
+    ],
+  );
+  let gps = write_ifd(
+    &mut tiff,
+    &[
+      (1, ASCII, text("N")),
+      (2, RATIONAL, rationals(&[(52, 1), (30, 1), (0, 1)])),
+      (3, ASCII, text("W")),
+      (4, RATIONAL, rationals(&[(1, 1), (15, 1), (0, 1)])),
+    ],
+  );
+  let ifd0 = write_ifd(
+    &mut tiff,
+    &[
+      (0x010F, ASCII, text("Canon")),
+      (0x0110, ASCII, text("Canon EOS R6")),
+      (0x0112, SHORT, (1, 6u16.to_le_bytes().to_vec())),
+      (0x8769, LONG, (1, exif.to_le_bytes().to_vec())),
+      (0x8825, LONG, (1, gps.to_le_bytes().to_vec())),
+    ],
+  );
+  tiff[4..8].copy_from_slice(&ifd0.to_le_bytes());
+  tiff
+}
+
+fn field<'a>(
+  fields: &'a [(String, String)],
+  key: &str,
+) -> Option<&'a str>
+{
+  fields.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+}
+
+#[test]
+fn exif_is_read_from_jpegs()
+{
+  let tiff = sample_tiff();
+  let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE1];
+  jpeg.extend(((2 + 6 + tiff.len()) as u16).to_be_bytes());
+  jpeg.extend(b"Exif\0\0");
+  jpeg.extend(&tiff);
+  jpeg.extend([0xFF, 0xDA, 0x00, 0x02]);
+
+  let fields = image_info(&jpeg);
+  assert_eq!(field(&fields, "Dimensions"), Some("4000x3000"));
+  assert_eq!(field(&fields, "Camera"), Some("Canon EOS R6"));
+  assert_eq!(field(&fields, "Taken"), Some("2024:05:01 10:20:30"));
+  assert_eq!(field(&fields, "Exposure"), Some("1/250 s"));
+  assert_eq!(field(&fields, "Aperture"), Some("f/2.8"));
+  assert_eq!(field(&fields, "ISO"), Some("200"));
+  assert_eq!(field(&fields, "Focal length"), Some("35 mm"));
+  assert_eq!(field(&fields, "Orientation"), Some("rotated 90° clockwise"));
+  assert_eq!(field(&fields, "GPS"), Some("52.50000, -1.25000"));
+
+  // TIFF files carry the same structure without the JPEG wrapping
+  assert_eq!(field(&image_info(&tiff), "ISO"), Some("200"));
+}
+
+#[test]
+fn picture_size_comes_from_headers_without_exif()
+{
+  let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xC0, 0x00, 0x11, 8];
+  jpeg.extend(480u16.to_be_bytes());
+  jpeg.extend(640u16.to_be_bytes());
+  jpeg.extend([3; 10]);
+  assert_eq!(field(&image_info(&jpeg), "Dimensions"), Some("640x480"));
+
+  let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+  png.extend(32u32.to_be_bytes());
+  png.extend(16u32.to_be_bytes());
+  assert_eq!(
+    image_info(&png),
+    vec![("Dimensions".to_string(), "32x16".to_string())]
+  );
+
+  assert!(image_info(b"not an image").is_empty());
+}
+
+#[test]
+fn ffprobe_output_is_summarised()
+{
+  let out = "\
+[STREAM]
+codec_name=h264
+codec_type=video
+width=1920
+height=1080
+r_frame_rate=30000/1001
+sample_rate=N/A
+[/STREAM]
+[STREAM]
+codec_name=aac
+codec_type=audio
+sample_rate=48000
+channels=2
+[/STREAM]
+[FORMAT]
+format_name=mov,mp4,m4a,3gp,3g2,mj2
+duration=3725.400000
+bit_rate=1200000
+[/FORMAT]
+";
+  let fields = parse_ffprobe(out);
+  assert_eq!(field(&fields, "Duration"), Some("1:02:05"));
+  assert_eq!(field(&fields, "Container"), Some("mov,mp4,m4a,3gp,3g2,mj2"));
+  assert_eq!(field(&fields, "Bit rate"), Some("1200 kb/s"));
+  assert_eq!(field(&fields, "Video"), Some("h264, 1920x1080, 29.97 fps"));
+  assert_eq!(field(&fields, "Audio"), Some("aac, 48000 Hz, 2 ch"));
+}
+
+#[test]
+fn media_kinds_follow_extensions()
+{
+  assert_eq!(media_kind(Path::new("a.JPG")), Some(MediaKind::Image));
+  assert_eq!(media_kind(Path::new("song.flac")), Some(MediaKind::Audio));
+  assert_eq!(media_kind(Path::new("clip.mkv")), Some(MediaKind::Video));
+  assert_eq!(media_kind(Path::new("notes.md")), None);
+}
@@ -0,0 +1,80 @@
+use lsv::core::mime::{
+  detect,
+  from_bytes,
+  from_extension,
+  guess,
+  looks_binary,
+};
+
+#[test]
+fn signatures_are_recognised()
+{
+  assert_eq!(from_bytes(b"\x89PNG\r\n\x1a\n...."), Some("image/png"));
+  assert_eq!(from_bytes(b"%PDF-1.7\n"), Some("application/pdf"));
+  assert_eq!(from_bytes(b"PK\x03\x04rest"), Some("application/zip"));
+  assert_eq!(from_bytes(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+  assert_eq!(from_bytes(b"\0\0\0\x18ftypisom"), Some("video/mp4"));
+  assert_eq!(from_bytes(b"\0\0\0\x18ftypheic"), Some("image/heic"));
+  assert_eq!(from_bytes(b"BZh91AY&SY"), Some("application/x-bzip2"));
+  assert_eq!(from_bytes(b"\xFD7zXZ\0\0"), Some("application/x-xz"));
+  assert_eq!(
+    from_bytes(b"SQLite format 3\0...."),
+    Some("application/vnd.sqlite3")
+  );
+  let mut tar = vec![0u8; 512];
+  tar[257..262].copy_from_slice(b"ustar");
+  assert_eq!(from_bytes(&tar), Some("application/x-tar"));
+  assert_eq!(from_bytes(b"plain words"), None);
+}
+
+#[test]
+fn shebang_lines_name_the_script_type()
+{
+  assert_eq!(from_bytes(b"#!/bin/sh\necho hi\n"), Some("text/x-shellscript"));
+  assert_eq!(
+    from_bytes(b"#!/usr/bin/env -S python3.12 -u\n"),
+    Some("text/x-python")
+  );
+  assert_eq!(from_bytes(b"#!/usr/local/bin/lua5.4\n"), Some("text/x-lua"));
+  assert_eq!(from_bytes(b"#!/opt/custom-tool\n"), None);
+}
+
+#[test]
+fn binary_means_nul_bytes_or_invalid_utf8()
+{
+  assert!(!looks_binary(b"plain text\n"));
+  assert!(looks_binary(b"a\0b"));
+  assert!(looks_binary(b"\xFF\xFEtext"));
+  // A multi-byte character cut off by the sample size
+  assert!(!looks_binary("caf\u{e9}".as_bytes().split_last().unwrap().1));
+}
+
+#[test]
+fn extensions_cover_text_formats()
+{
+  assert_eq!(from_extension("RS"), Some("text/x-rust"));
+  assert_eq!(from_extension("json"), Some("application/json"));
+  assert_eq!(from_extension("unknown"), None);
+}
+
+#[test]
+fn guesses_fall_back_on_the_content()
+{
+  let tmp = tempfile::tempdir().unwrap();
+  let write = |name: &str, data: &[u8]| {
+    let p = tmp.path().join(name);
+    std::fs::write(&p, data).unwrap();
+    p
+  };
+  assert_eq!(guess(&write("notes", b"hello\n")), "text/plain");
+  assert_eq!(
+    guess(&write("blob", b"\x00\x01\x02")),
+    "application/octet-stream"
+  );
+  assert_eq!(guess(&write("empty", b"")), "inode/x-empty");
+  assert_eq!(guess(&write("main.rs", b"fn main() {}\n")), "text/x-rust");
+  // Content wins over a misleading extension
+  assert_eq!(guess(&write("photo.txt", b"\xFF\xD8\xFF\xE0")), "image/jpeg");
+  assert_eq!(guess(&write("clip.webm", b"\x1A\x45\xDF\xA3")), "video/webm");
+  assert_eq!(detect(tmp.path()), "inode/directory");
+}
@@ -0,0 +1,113 @@
+use std::path::{
+  Path,
+  PathBuf,
+};
+
+use lsv::core::mounts::{
+  DfRow,
+  MountPoint,
+  containing,
+  parse_df,
+  parse_net_use,
+  parse_proc_mounts,
+};
+
+fn mount(path: &str) -> MountPoint
+{
+  MountPoint {
+    device:  String::from("dev"),
+    path:    PathBuf::from(path),
+    fs_type: None,
+    total:   None,
+    avail:   None,
+  }
+}
+
+#[test]
+fn proc_mounts_skip_pseudo_filesystems_and_unescape_paths()
+{
+  let text = "\
+sysfs /sys sysfs rw,nosuid 0 0
+proc /proc proc rw 0 0
+/dev/sda2 / ext4 rw,relatime 0 0
+/dev/sdb1 /media/My\\040Disk vfat rw 0 0
+tmpfs /tmp tmpfs rw 0 0
+/dev/sdc1 /tmp xfs rw 0 0
+";
+  let mounts = parse_proc_mounts(text);
+  let paths: Vec<&Path> = mounts.iter().map(|m| m.path.as_path()).collect();
+  assert_eq!(
+    paths,
+    [Path::new("/"), Path::new("/media/My Disk"), Path::new("/tmp")]
+  );
+  // The later mount on /tmp hides the earlier one
+  assert_eq!(mounts[2].device, "/dev/sdc1");
+  assert_eq!(mounts[2].fs_type.as_deref(), Some("xfs"));
+}
+
+#[test]
+fn df_rows_keep_spaces_in_mount_points()
+{
+  let text = "\
+Filesystem     1024-blocks      Used Available Capacity Mounted on
+/dev/sda2         40000000  30000000  10000000      75% /
+/dev/sdb1             1000       250       750      25% /media/My Disk
+";
+  assert_eq!(
+    parse_df(text),
+    vec![
+      DfRow {
+        device: String::from("/dev/sda2"),
+        total:  40000000 * 1024,
+        avail:  10000000 * 1024,
+        path:   PathBuf::from("/"),
+      },
+      DfRow {
+        device: String::from("/dev/sdb1"),
+        total:  1000 * 1024,
+        avail:  750 * 1024,
+        path:   PathBuf::from("/media/My Disk"),
+      },
+    ]
+  );
+  let mut m = mount("/media/My Disk");
+  (m.total, m.avail) = (Some(1000), Some(750));
+  assert_eq!(m.used_fraction(), Some(0.25));
+}
+
+#[test]
+fn the_deepest_mount_contains_a_path()
+{
+  let mounts = [mount("/"), mount("/home"), mount("/home/me/data")];
+  assert_eq!(containing(&mounts, Path::new("/home/me/src")), Some(1));
+  assert_eq!(containing(&mounts, Path::new("/home/me/data/x")), Some(2));
+  assert_eq!(containing(&mounts, Path::new("/etc")), Some(0));
+  // Components, not string prefixes
+  assert_eq!(containing(&mounts, Path::new("/homework")), Some(0));
+}
+
+#[test]
+fn net_use_lists_shares_without_a_drive_letter()
+{
+  let text = "\
+New connections will be remembered.
+
+
+Status       Local     Remote                    Network
+
+-------------------------------------------------------------------------------
+OK           Z:        \\\\files\\home               Microsoft Windows Network
+OK                     \\\\nas\\media                Microsoft Windows Network
+Unavailable            \\\\nas\\media\\              Microsoft Windows Network
+                       \\\\backup-server.example.org\\archive
+                                                 Microsoft Windows Network
+The command completed successfully.
+";
+  assert_eq!(
+    parse_net_use(text),
+    vec![
+      PathBuf::from("\\\\nas\\media\\"),
+      PathBuf::from("\\\\backup-server.example.org\\archive\\"),
+    ]
+  );
+}
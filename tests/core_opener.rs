@@ -0,0 +1,73 @@
+use std::path::Path;
+
+use lsv::{
+  config::OpenerRule,
+  core::opener::{
+    find_opener,
+    glob_match,
+    mime_matches,
+    rule_matches,
+  },
+};
+
+fn rule(cmd: &str) -> OpenerRule
+{
+  OpenerRule { cmd: cmd.to_string(), ..Default::default() }
+}
+
+#[test]
+fn glob_supports_star_and_question_mark()
+{
+  assert!(glob_match("*.tar.gz", "backup.TAR.gz"));
+  assert!(glob_match("img_??.png", "img_01.png"));
+  assert!(!glob_match("img_??.png", "img_1.png"));
+  assert!(glob_match("*read*", "README.md"));
+  assert!(!glob_match("*.md", "notes.txt"));
+}
+
+#[test]
+fn mime_patterns_match_whole_types_or_families()
+{
+  assert!(mime_matches("image/*", "image/png"));
+  assert!(mime_matches("text/plain", "text/plain"));
+  assert!(!mime_matches("text/*", "application/json"));
+  assert!(!mime_matches("text/plain", "text/html"));
+}
+
+#[test]
+fn rules_check_extension_and_name()
+{
+  let mut by_ext = rule("view");
+  by_ext.ext = vec!["png".into(), "jpg".into()];
+  let mut no_mime = || -> Option<String> { panic!("mime not needed") };
+  assert!(rule_matches(&by_ext, Path::new("/a/b.PNG"), &mut no_mime));
+  assert!(!rule_matches(&by_ext, Path::new("/a/b.gif"), &mut no_mime));
+
+  let mut by_name = rule("make");
+  by_name.name = Some("Makefile".into());
+  assert!(rule_matches(&by_name, Path::new("/src/Makefile"), &mut no_mime));
+  assert!(!rule_matches(&by_name, Path::new("/src/main.c"), &mut no_mime));
+}
+
+#[test]
+fn mime_rules_use_the_detected_type()
+{
+  let mut by_mime = rule("view");
+  by_mime.mime = Some("image/*".into());
+  let p = Path::new("/a/photo");
+  assert!(rule_matches(&by_mime, p, &mut || Some("image/jpeg".into())));
+  assert!(!rule_matches(&by_mime, p, &mut || Some("text/plain".into())));
+  assert!(!rule_matches(&by_mime, p, &mut || None));
+}
+
+#[test]
+fn first_matching_rule_wins()
+{
+  let mut md = rule("glow");
+  md.ext = vec!["md".into()];
+  let rules = vec![md, rule("fallback")];
+  let found =
+    |p: &str| find_opener(&rules, Path::new(p)).map(|r| r.cmd.clone());
+  assert_eq!(found("README.md").as_deref(), Some("glow"));
+  assert_eq!(found("main.rs").as_deref(), Some("fallback"));
+}
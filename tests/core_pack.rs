@@ -0,0 +1,86 @@
+use std::fs;
+
+use lsv::core::{
+  archive::{
+    self,
+    ArchiveKind,
+  },
+  pack::{
+    self,
+    PackFormat,
+    PackSpec,
+  },
+};
+
+#[test]
+fn formats_follow_the_extension()
+{
+  assert_eq!(PackFormat::detect("a.zip"), Some(PackFormat::Zip));
+  assert_eq!(PackFormat::detect("a.tar"), Some(PackFormat::Tar));
+  assert_eq!(PackFormat::detect("a.TAR.GZ"), Some(PackFormat::TarGz));
+  assert_eq!(PackFormat::detect("a.tgz"), Some(PackFormat::TarGz));
+  assert_eq!(PackFormat::detect("a.tar.zst"), Some(PackFormat::TarZst));
+  assert_eq!(PackFormat::detect("a.rar"), None);
+}
+
+#[test]
+fn tarballs_read_back_with_long_names()
+{
+  let tmp = tempfile::tempdir().expect("tmp");
+  let root = tmp.path();
+  let deep = root.join("d").join("x".repeat(60)).join("y".repeat(60));
+  fs::create_dir_all(&deep).unwrap();
+  fs::write(deep.join("f.txt"), b"deep").unwrap();
+  fs::write(root.join("top.txt"), vec![b'z'; 1000]).unwrap();
+
+  let spec = PackSpec {
+    archive: root.join("out.tar"),
+    format:  PackFormat::Tar,
+    base:    root.to_path_buf(),
+    command: None,
+    shell:   Default::default(),
+  };
+  let items = vec![root.join("d"), root.join("top.txt")];
+  let mut files = 0;
+  pack::create(&spec, &items, &mut |step| {
+    if let lsv::core::fs_ops::CopyProgress::FileDone = step
+    {
+      files += 1;
+    }
+    Ok(())
+  })
+  .unwrap();
+  assert_eq!(files, 2);
+
+  let listing = archive::list(&spec.archive, ArchiveKind::Tar, 100).unwrap();
+  let names: Vec<(&str, u64)> =
+    listing.entries.iter().map(|e| (e.name.as_str(), e.size)).collect();
+  let long = format!("d/{}/{}/f.txt", "x".repeat(60), "y".repeat(60));
+  assert!(names.contains(&(long.as_str(), 4)), "{:?}", names);
+  assert!(names.contains(&("top.txt", 1000)), "{:?}", names);
+
+  let out = root.join("extracted");
+  archive::extract(&spec.archive, ArchiveKind::Tar, "", &out, &mut |_| Ok(()))
+    .unwrap();
+  assert_eq!(fs::read(out.join(&long)).unwrap(), b"deep");
+}
+
+#[test]
+fn failing_compressors_report_their_error()
+{
+  let tmp = tempfile::tempdir().expect("tmp");
+  let root = tmp.path();
+  fs::write(root.join("a.txt"), b"a").unwrap();
+  let spec = PackSpec {
+    archive: root.join("out.tar.gz"),
+    format:  PackFormat::TarGz,
+    base:    root.to_path_buf(),
+    command: Some(String::from(
+      "cat >/dev/null; echo 'no space left' >&2; exit 1",
+    )),
+    shell:   Default::default(),
+  };
+  let err =
+    pack::create(&spec, &[root.join("a.txt")], &mut |_| Ok(())).unwrap_err();
+  assert_eq!(err.to_string(), "no space left");
+}
@@ -0,0 +1,144 @@
+use lsv::core::permissions::{
+  ModeSpec,
+  parse_mode,
+  parse_request,
+};
+
+fn apply(
+  spec: &str,
+  mode: u32,
+  is_dir: bool,
+) -> u32
+{
+  parse_mode(spec).expect("valid mode").apply(mode, is_dir)
+}
+
+#[test]
+fn octal_modes_replace_the_bits()
+{
+  assert_eq!(parse_mode("755"), Some(ModeSpec::Octal(0o755)));
+  assert_eq!(apply("0640", 0o4777, false), 0o640);
+  assert_eq!(parse_mode("8"), None);
+  assert_eq!(parse_mode("12345"), None);
+}
+
+#[test]
+fn symbolic_modes_follow_chmod()
+{
+  assert_eq!(apply("u+x", 0o644, false), 0o744);
+  assert_eq!(apply("go-w", 0o666, false), 0o644);
+  assert_eq!(apply("+x", 0o644, false), 0o755);
+  assert_eq!(apply("a=r", 0o755, false), 0o444);
+  assert_eq!(apply("u=rwx,g=rx,o=", 0o777, false), 0o750);
+  assert_eq!(apply("u+s,g+s,+t", 0o755, true), 0o7755);
+  assert_eq!(apply("g=", 0o2775, true), 0o705);
+  // X only adds execute to directories and files already executable by
+  // someone
+  assert_eq!(apply("a+X", 0o644, false), 0o644);
+  assert_eq!(apply("a+X", 0o744, false), 0o755);
+  assert_eq!(apply("a+X", 0o644, true), 0o755);
+
+  for bad in ["u+q", "z+x", "u", "", "u+x,"]
+  {
+    assert_eq!(parse_mode(bad), None, "{}", bad);
+  }
+}
+
+#[test]
+fn requests_combine_mode_owner_and_recursion()
+{
+  let req = parse_request("-R go-w alice:staff").unwrap();
+  assert!(req.recursive);
+  assert_eq!(req.mode, parse_mode("go-w"));
+  assert_eq!(req.owner.as_deref(), Some("alice:staff"));
+
+  let req = parse_request("644").unwrap();
+  assert!(!req.recursive && req.owner.is_none());
+
+  let req = parse_request(":wheel").unwrap();
+  assert!(req.mode.is_none());
+  assert_eq!(req.owner.as_deref(), Some(":wheel"));
+
+  assert!(parse_request("").is_err());
+  assert!(parse_request("-R").is_err());
+  assert!(parse_request("644 755").is_err());
+  assert!(parse_request("u+q").is_err());
+  assert!(parse_request("bob carol").is_err());
+}
+
+#[test]
+fn numeric_owners_need_a_plus()
+{
+  let req = parse_request("+1000:+100").unwrap();
+  assert!(req.mode.is_none());
+  assert_eq!(req.owner.as_deref(), Some("1000:100"));
+  assert_eq!(
+    parse_request("1000:100").unwrap().owner.as_deref(),
+    Some("1000:100")
+  );
+
+  // Ids that are not modes, or would be modes no one sets, are refused
+  // rather than guessed at
+  for ambiguous in ["1000", "2001", "1009", "65534"]
+  {
+    let err = parse_request(ambiguous).unwrap_err();
+    assert!(err.contains(&format!("+{}", ambiguous)), "{}", err);
+  }
+  assert_eq!(
+    parse_request("01000").unwrap().mode,
+    Some(ModeSpec::Octal(0o1000))
+  );
+  assert_eq!(
+    parse_request("4755").unwrap().mode,
+    Some(ModeSpec::Octal(0o4755))
+  );
+  assert_eq!(
+    parse_request("1777").unwrap().mode,
+    Some(ModeSpec::Octal(0o1777))
+  );
+  assert!(parse_request("+bob").is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn recursive_changes_skip_symlinks()
+{
+  use std::os::unix::fs::PermissionsExt;
+
+  use lsv::core::permissions::{
+    chmod,
+    expand,
+  };
+
+  let tmp = tempfile::tempdir().unwrap();
+  let dir = tmp.path().join("d");
+  std::fs::create_dir_all(dir.join("sub")).unwrap();
+  std::fs::write(dir.join("a.txt"), "a").unwrap();
+  std::fs::write(dir.join("sub/b.txt"), "b").unwrap();
+  let outside = tmp.path().join("outside.txt");
+  std::fs::write(&outside, "o").unwrap();
+  std::fs::set_permissions(&outside, std::fs::Permissions::from_mode(0o644))
+    .unwrap();
+  std::os::unix::fs::symlink(&outside, dir.join("link")).unwrap();
+
+  let paths = expand(std::slice::from_ref(&dir), true);
+  assert_eq!(
+    paths,
+    vec![
+      dir.clone(),
+      dir.join("a.txt"),
+      dir.join("sub"),
+      dir.join("sub/b.txt"),
+    ]
+  );
+  assert_eq!(expand(std::slice::from_ref(&dir), false), vec![dir.clone()]);
+
+  let (done, errors) = chmod(&paths, &parse_mode("go-rwx").unwrap());
+  assert_eq!((done, errors.len()), (4, 0));
+  let mode = |p: &std::path::Path| {
+    std::fs::metadata(p).unwrap().permissions().mode() & 0o777
+  };
+  assert_eq!(mode(&dir.join("sub/b.txt")) & 0o077, 0);
+  assert_eq!(mode(&dir) & 0o077, 0);
+  assert_eq!(mode(&outside), 0o644);
+}
@@ -0,0 +1,79 @@
+use lsv::core::regex::Regex;
+
+fn matches(
+  pattern: &str,
+  text: &str,
+) -> bool
+{
+  Regex::new(pattern).expect("valid pattern").try_match(text) == Some(true)
+}
+
+#[test]
+fn literals_and_dots_match_anywhere()
+{
+  assert!(matches("log", "build.log.1"));
+  assert!(matches("a.c", "xxabcxx"));
+  assert!(!matches("a.c", "ac"));
+}
+
+#[test]
+fn anchors_pin_the_match()
+{
+  assert!(matches(r"^img_\d+\.png$", "img_042.png"));
+  assert!(!matches(r"^img_\d+\.png$", "old_img_042.png"));
+  assert!(!matches(r"\.png$", "a.png.bak"));
+}
+
+#[test]
+fn quantifiers_backtrack()
+{
+  assert!(matches("^a*ab$", "aaab"));
+  assert!(matches("^(ab)+$", "ababab"));
+  assert!(!matches("^(ab)+$", "abba"));
+  assert!(matches("^colou?r$", "color"));
+  assert!(matches(r"^\d{4}-\d{2}$", "2024-05"));
+  assert!(!matches(r"^\d{4}$", "123"));
+  assert!(matches("^x{2,}$", "xxxx"));
+  assert!(!matches("^x{2,3}$", "xxxx"));
+  // Repeating something that can match empty terminates
+  assert!(matches("^(a*)*b$", "aab"));
+}
+
+#[test]
+fn classes_and_alternation()
+{
+  assert!(matches(r"\.(jpe?g|png)$", "photo.jpeg"));
+  assert!(!matches(r"\.(jpe?g|png)$", "photo.gif"));
+  assert!(matches("^[a-c][^0-9]$", "bx"));
+  assert!(!matches("^[a-c][^0-9]$", "b7"));
+  assert!(matches(r"^[\w-]+$", "my-file_2"));
+  assert!(matches("[]]", "a]b"));
+}
+
+#[test]
+fn case_insensitive_prefix()
+{
+  assert!(!matches("readme", "README.md"));
+  assert!(matches("(?i)readme", "README.md"));
+  assert!(matches(r"(?i)^\D+$", "ABC"));
+}
+
+#[test]
+fn invalid_patterns_are_rejected()
+{
+  for bad in ["(ab", "ab)", "[ab", "*a", "a{3,1}", "a\\", "[z-a]"]
+  {
+    assert!(Regex::new(bad).is_err(), "{} should be rejected", bad);
+  }
+}
+
+#[test]
+fn pathological_patterns_give_up_instead_of_hanging()
+{
+  let re = Regex::new("^(a+)+$").expect("valid pattern");
+  let name = format!("{}!", "a".repeat(30));
+  let start = std::time::Instant::now();
+  assert_eq!(re.try_match(&name), None);
+  assert!(start.elapsed() < std::time::Duration::from_secs(2));
+  assert_eq!(re.try_match("aaaa"), Some(true));
+}
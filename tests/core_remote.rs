@@ -0,0 +1,127 @@
+use std::path::Path;
+
+use chrono::NaiveDate;
+use lsv::core::{
+  remote::{
+    RemoteEntry,
+    RemotePath,
+    batch_quote,
+    parse_listing,
+  },
+  vfs,
+};
+
+#[test]
+fn parses_and_formats_locations()
+{
+  let rp =
+    RemotePath::parse(Path::new("sftp://me@example.org:2222/srv/./www/"))
+      .expect("parse");
+  assert_eq!(rp.user.as_deref(), Some("me"));
+  assert_eq!(rp.host, "example.org");
+  assert_eq!(rp.port, Some(2222));
+  assert_eq!(rp.path, "/srv/www");
+  assert_eq!(rp.to_path(), Path::new("sftp://me@example.org:2222/srv/www"));
+
+  let root = RemotePath::parse(Path::new("sftp://host")).expect("parse");
+  assert_eq!(root.path, "/");
+  assert_eq!(root.user, None);
+  let v6 = RemotePath::parse(Path::new("sftp://[::1]:22/a/../b")).unwrap();
+  assert_eq!(
+    (v6.host.as_str(), v6.port, v6.path.as_str()),
+    ("::1", Some(22), "/b")
+  );
+  assert_eq!(v6.to_path(), Path::new("sftp://[::1]:22/b"));
+
+  // Hosts that ssh would take for options are refused too
+  for bad in [
+    "/tmp",
+    "sftp:///x",
+    "sftp://host:port/x",
+    "sftp://@host/",
+    "sftp://-oProxyCommand=touch%20x/tmp",
+    "sftp://me@-oProxyCommand=x/",
+    "sftp://[-oProxyCommand=x]:22/",
+  ]
+  {
+    assert_eq!(RemotePath::parse(Path::new(bad)), None, "{}", bad);
+  }
+}
+
+#[test]
+fn parents_stop_at_the_remote_root()
+{
+  let dir = Path::new("sftp://host/srv/www");
+  assert_eq!(vfs::parent(dir).as_deref(), Some(Path::new("sftp://host/srv")));
+  assert_eq!(
+    vfs::parent(Path::new("sftp://host/srv")).as_deref(),
+    Some(Path::new("sftp://host/"))
+  );
+  assert_eq!(vfs::parent(Path::new("sftp://host/")), None);
+  // Joined child paths parse back to the same host
+  let child = RemotePath::parse(&dir.join("index.html")).unwrap();
+  assert_eq!(child.path, "/srv/www/index.html");
+  assert_eq!(child.name(), "index.html");
+}
+
+#[test]
+fn batch_arguments_escape_spaces_quotes_and_globs()
+{
+  assert_eq!(batch_quote("/a b/it's", false).unwrap(), r"/a\ b/it\'s");
+  assert_eq!(batch_quote("/x/*[1]?.txt", true).unwrap(), r"/x/\*\[1]\?.txt");
+  assert_eq!(batch_quote("/x/*.txt", false).unwrap(), "/x/*.txt");
+}
+
+#[test]
+fn batch_arguments_refuse_control_characters()
+{
+  // A newline would end the command and start one of the name's choosing
+  assert!(batch_quote("/tmp/a\nrm -r /", false).is_err());
+  assert!(batch_quote("/tmp/a\rb", true).is_err());
+  assert!(batch_quote("/tmp/a\tb", false).is_err());
+}
+
+#[test]
+fn listings_are_parsed_from_long_format()
+{
+  let now = NaiveDate::from_ymd_opt(2026, 3, 10)
+    .unwrap()
+    .and_hms_opt(12, 0, 0)
+    .unwrap();
+  let text = "\
+sftp> ls -lan /srv
+drwxr-xr-x    5 0        0            4096 Mar  9 08:15 .
+drwxr-xr-x   19 0        0            4096 Jan  2  2024 ..
+drwxr-xr-x    2 1000     1000         4096 Mar  9 08:15 www
+-rw-r--r--    1 1000     1000          123 Dec 24 18:30 notes  with spaces.txt
+lrwxrwxrwx    1 1000     1000            7 Feb 29  2024 link
+";
+  let entries = parse_listing(text, now);
+  let at = |y, m, d, h, min| {
+    NaiveDate::from_ymd_opt(y, m, d).and_then(|d| d.and_hms_opt(h, min, 0))
+  };
+  assert_eq!(
+    entries,
+    vec![
+      RemoteEntry {
+        name:     String::from("www"),
+        is_dir:   true,
+        size:     4096,
+        modified: at(2026, 3, 9, 8, 15),
+      },
+      // A date later than now belongs to last year
+      RemoteEntry {
+        name:     String::from("notes  with spaces.txt"),
+        is_dir:   false,
+        size:     123,
+        modified: at(2025, 12, 24, 18, 30),
+      },
+      RemoteEntry {
+        name:     String::from("link"),
+        is_dir:   false,
+        size:     7,
+        modified: at(2024, 2, 29, 0, 0),
+      },
+    ]
+  );
+}
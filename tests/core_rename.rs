@@ -0,0 +1,108 @@
+use std::{
+  fs,
+  path::PathBuf,
+};
+
+use lsv::core::rename::{
+  apply_renames,
+  names_text,
+  plan_renames,
+};
+
+fn touch(
+  dir: &std::path::Path,
+  names: &[&str],
+) -> Vec<PathBuf>
+{
+  names
+    .iter()
+    .map(|n| {
+      let p = dir.join(n);
+      fs::write(&p, n.as_bytes()).unwrap();
+      p
+    })
+    .collect()
+}
+
+#[test]
+fn plan_keeps_only_changed_names()
+{
+  let temp = tempfile::tempdir().expect("tempdir");
+  let items = touch(temp.path(), &["a.txt", "b.txt"]);
+  assert_eq!(names_text(&items), "a.txt\nb.txt\n");
+  let plan = plan_renames(&items, "a.txt\nc.txt\n").unwrap();
+  assert_eq!(plan, vec![(items[1].clone(), temp.path().join("c.txt"))]);
+}
+
+#[test]
+fn plan_rejects_collisions_and_bad_lists()
+{
+  let temp = tempfile::tempdir().expect("tempdir");
+  let items = touch(temp.path(), &["a.txt", "b.txt"]);
+  touch(temp.path(), &["taken.txt"]);
+  // Line count must match
+  assert!(plan_renames(&items, "a.txt\n").is_err());
+  // Two items to one name
+  assert!(plan_renames(&items, "x.txt\nx.txt\n").is_err());
+  // Existing file that is not being renamed
+  assert!(plan_renames(&items, "taken.txt\nb.txt\n").is_err());
+  // Unchanged item keeps its name, so another item can't take it
+  assert!(plan_renames(&items, "a.txt\na.txt\n").is_err());
+  // Names must stay in the same directory
+  assert!(plan_renames(&items, "sub/a.txt\nb.txt\n").is_err());
+  assert!(plan_renames(&items, "\nb.txt\n").is_err());
+}
+
+#[test]
+fn apply_handles_swaps()
+{
+  let temp = tempfile::tempdir().expect("tempdir");
+  let items = touch(temp.path(), &["a.txt", "b.txt", "c.txt"]);
+  let plan = plan_renames(&items, "b.txt\nc.txt\na.txt\n").unwrap();
+  let (done, errors) = apply_renames(&plan);
+  assert!(errors.is_empty(), "{errors:?}");
+  assert_eq!(done.len(), 3);
+  assert_eq!(fs::read(temp.path().join("b.txt")).unwrap(), b"a.txt");
+  assert_eq!(fs::read(temp.path().join("c.txt")).unwrap(), b"b.txt");
+  assert_eq!(fs::read(temp.path().join("a.txt")).unwrap(), b"c.txt");
+  assert_eq!(fs::read_dir(temp.path()).unwrap().count(), 3);
+}
+
+#[test]
+fn apply_handles_chains()
+{
+  let temp = tempfile::tempdir().expect("tempdir");
+  let items = touch(temp.path(), &["a.txt", "b.txt"]);
+  let plan = plan_renames(&items, "b.txt\nc.txt\n").unwrap();
+  let (done, errors) = apply_renames(&plan);
+  assert!(errors.is_empty(), "{errors:?}");
+  assert_eq!(done.len(), 2);
+  assert_eq!(fs::read(temp.path().join("b.txt")).unwrap(), b"a.txt");
+  assert_eq!(fs::read(temp.path().join("c.txt")).unwrap(), b"b.txt");
+  assert_eq!(fs::read_dir(temp.path()).unwrap().count(), 2);
+}
+
+#[test]
+fn apply_refuses_targets_that_appeared()
+{
+  let temp = tempfile::tempdir().expect("tempdir");
+  let items = touch(temp.path(), &["a.txt"]);
+  let plan = plan_renames(&items, "z.txt\n").unwrap();
+  fs::write(temp.path().join("z.txt"), b"new").unwrap();
+  let (done, errors) = apply_renames(&plan);
+  assert!(done.is_empty());
+  assert_eq!(errors.len(), 1);
+  assert_eq!(fs::read(temp.path().join("z.txt")).unwrap(), b"new");
+  assert_eq!(fs::read(temp.path().join("a.txt")).unwrap(), b"a.txt");
+}
+
+#[test]
+fn list_files_are_new_and_never_followed()
+{
+  let temp = tempfile::tempdir().expect("tempdir");
+  let a = lsv::core::rename::create_list_file(temp.path(), "x\n").unwrap();
+  let b = lsv::core::rename::create_list_file(temp.path(), "y\n").unwrap();
+  assert_ne!(a, b);
+  assert_eq!(fs::read_to_string(&a).unwrap(), "x\n");
+  assert_eq!(fs::read_to_string(&b).unwrap(), "y\n");
+}
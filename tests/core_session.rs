@@ -0,0 +1,53 @@
+use std::{
+  fs,
+  path::PathBuf,
+};
+
+use lsv::{
+  actions::internal::SortKey,
+  app::{
+    DisplayMode,
+    InfoMode,
+  },
+  core::session::{
+    Session,
+    load_session,
+    save_session,
+  },
+};
+
+#[test]
+fn session_round_trips()
+{
+  let temp = tempfile::tempdir().expect("tempdir");
+  let path = temp.path().join("state").join("session");
+  let s = Session {
+    cwd:          Some(PathBuf::from("/some/where")),
+    selected:     Some("a name=with equals.txt".to_string()),
+    sort_key:     Some(SortKey::MTime),
+    sort_reverse: Some(true),
+    info_mode:    Some(InfoMode::None),
+    display_mode: Some(DisplayMode::Friendly),
+    show_hidden:  Some(true),
+  };
+  save_session(&path, &s).expect("save");
+  assert_eq!(load_session(&path), s);
+}
+
+#[test]
+fn missing_or_garbled_values_are_ignored()
+{
+  let temp = tempfile::tempdir().expect("tempdir");
+  let path = temp.path().join("session");
+  assert_eq!(load_session(&path), Session::default());
+
+  fs::write(
+    &path,
+    "# comment\nsort=bogus\nshow_hidden=yes\nnonsense\nshow=size\n",
+  )
+  .unwrap();
+  let s = load_session(&path);
+  assert_eq!(s.sort_key, None);
+  assert_eq!(s.show_hidden, None);
+  assert_eq!(s.info_mode, Some(InfoMode::Size));
+}
@@ -0,0 +1,104 @@
+use std::{
+  ffi::OsStr,
+  path::PathBuf,
+};
+
+use lsv::{
+  config::CommandShell,
+  core::shell::{
+    self,
+    Placeholders,
+  },
+};
+
+fn args(c: &std::process::Command) -> Vec<&str>
+{
+  c.get_args().map(|a| a.to_str().unwrap()).collect()
+}
+
+fn shell(
+  program: &str,
+  login: bool,
+) -> CommandShell
+{
+  CommandShell { program: Some(program.into()), login, env: None }
+}
+
+#[test]
+fn posix_shells_get_the_files_as_positional_parameters()
+{
+  let c = shell::with_args(&shell("bash", false), "cat \"$1\"", ["a", "b c"]);
+  assert_eq!(c.get_program(), OsStr::new("bash"));
+  assert_eq!(args(&c), ["-c", "cat \"$1\"", "lsv", "a", "b c"]);
+  let c = shell::command(&shell("/usr/bin/zsh", true), "ls");
+  assert_eq!(args(&c), ["-l", "-c", "ls", "lsv"]);
+}
+
+#[test]
+fn cmd_and_powershell_get_their_own_flags()
+{
+  let c = shell::with_args(&shell("cmd.exe", true), "type", ["a.txt"]);
+  assert_eq!(args(&c), ["/C", "type", "a.txt"]);
+  let c = shell::command(&shell("pwsh", false), "Get-ChildItem");
+  assert_eq!(args(&c), ["-NoProfile", "-Command", "Get-ChildItem"]);
+  let c = shell::command(&shell("powershell", true), "gci");
+  assert_eq!(args(&c), ["-Command", "gci"]);
+}
+
+#[test]
+fn default_shell_is_sh_or_cmd()
+{
+  let expected = if cfg!(windows) { "cmd" } else { "sh" };
+  assert_eq!(shell::program(&CommandShell::default()), expected);
+}
+
+#[test]
+fn env_list_passes_only_the_named_variables()
+{
+  let cfg = CommandShell {
+    env: Some(vec!["PATH".into(), "LSV_SURELY_UNSET_VAR".into()]),
+    ..shell("sh", false)
+  };
+  let c = shell::command(&cfg, "true");
+  let names: Vec<&OsStr> = c.get_envs().map(|(k, _)| k).collect();
+  assert_eq!(names, [OsStr::new("PATH")]);
+}
+
+#[test]
+fn placeholders_are_quoted_for_the_shell_and_not_rescanned()
+{
+  let ph = Placeholders {
+    file:      Some(PathBuf::from("/tmp/it's {dir}.tar.gz")),
+    files:     vec![PathBuf::from("/a b"), PathBuf::from("/c")],
+    dir:       PathBuf::from("/tmp"),
+    clipboard: Vec::new(),
+    extra:     vec![("width".into(), "80".into())],
+  };
+  let sh = shell("sh", false);
+  assert_eq!(
+    shell::expand(&sh, "x {file} {basename} {ext} {width}", &ph),
+    r"x '/tmp/it'\''s {dir}.tar.gz' 'it'\''s {dir}.tar.gz' 'gz' 80"
+  );
+  assert_eq!(
+    shell::expand(&sh, "{files}|{clipboard}|{dir}", &ph),
+    "'/a b' '/c'||'/tmp'"
+  );
+  // Unknown names and unclosed braces stay as written
+  assert_eq!(
+    shell::expand(&sh, "awk '{print $1}' {nope} {file", &ph),
+    "awk '{print $1}' {nope} {file"
+  );
+  let cmd = shell("cmd", false);
+  assert_eq!(shell::expand(&cmd, "type {files}", &ph), r#"type "/a b" "/c""#);
+  let pwsh = shell("pwsh", false);
+  assert_eq!(shell::quote(&pwsh, "it's"), "'it''s'");
+}
+
+#[test]
+fn nested_shells_count_up_from_lsv_level()
+{
+  assert_eq!(shell::next_level(None), 1);
+  assert_eq!(shell::next_level(Some("1")), 2);
+  assert_eq!(shell::next_level(Some(" 3 ")), 4);
+  assert_eq!(shell::next_level(Some("junk")), 1);
+}
@@ -0,0 +1,118 @@
+use std::path::Path;
+
+#[cfg(unix)]
+use lsv::{
+  config::CommandShell,
+  core::thumbnail::{
+    ensure_thumbnail,
+    render,
+  },
+};
+use lsv::{
+  config::UiThumbnails,
+  core::thumbnail::{
+    ThumbnailKind,
+    kind_for,
+    thumbnail_path,
+  },
+};
+
+#[test]
+fn videos_documents_and_images_get_thumbnails()
+{
+  let cfg = UiThumbnails::default();
+  assert_eq!(
+    kind_for(Path::new("a/clip.MP4"), &cfg),
+    Some(ThumbnailKind::Video)
+  );
+  assert_eq!(kind_for(Path::new("film.mkv"), &cfg), Some(ThumbnailKind::Video));
+  assert_eq!(kind_for(Path::new("paper.pdf"), &cfg), Some(ThumbnailKind::Pdf));
+  assert_eq!(kind_for(Path::new("logo.svg"), &cfg), Some(ThumbnailKind::Svg));
+  assert_eq!(
+    kind_for(Path::new("IMG_1.HEIC"), &cfg),
+    Some(ThumbnailKind::Heif)
+  );
+  assert_eq!(
+    kind_for(Path::new("photo.avif"), &cfg),
+    Some(ThumbnailKind::Heif)
+  );
+  assert_eq!(kind_for(Path::new("notes.txt"), &cfg), None);
+  assert_eq!(kind_for(Path::new("Makefile"), &cfg), None);
+
+  let no_video = UiThumbnails { video: None, ..UiThumbnails::default() };
+  assert_eq!(kind_for(Path::new("clip.mp4"), &no_video), None);
+  let no_svg = UiThumbnails { svg: None, ..UiThumbnails::default() };
+  assert_eq!(kind_for(Path::new("logo.svg"), &no_svg), None);
+  let off = UiThumbnails { enabled: false, ..UiThumbnails::default() };
+  assert_eq!(kind_for(Path::new("paper.pdf"), &off), None);
+}
+
+#[test]
+fn thumbnail_name_follows_the_file()
+{
+  let tmp = tempfile::tempdir().unwrap();
+  let file = tmp.path().join("clip.mp4");
+  std::fs::write(&file, b"one").unwrap();
+  let cache = tmp.path().join("cache");
+  let first = thumbnail_path(&cache, &file);
+  assert_eq!(first, thumbnail_path(&cache, &file));
+  assert!(first.starts_with(&cache));
+  assert_eq!(first.extension().unwrap(), "png");
+
+  std::fs::write(&file, b"longer").unwrap();
+  assert_ne!(first, thumbnail_path(&cache, &file));
+}
+
+#[cfg(unix)]
+#[test]
+fn thumbnails_are_made_once_and_rendered()
+{
+  let tmp = tempfile::tempdir().unwrap();
+  let file = tmp.path().join("clip.mp4");
+  std::fs::write(&file, b"video").unwrap();
+  let cache = tmp.path().join("cache");
+  let sh = CommandShell::default();
+  let log = tmp.path().join("runs");
+  let cmd = format!(r#"cp "$1" "$2" && echo x >> "{}""#, log.display());
+
+  let png = ensure_thumbnail(&sh, &cmd, &file, &cache).expect("thumbnail");
+  assert_eq!(std::fs::read(&png).unwrap(), b"video");
+  assert_eq!(ensure_thumbnail(&sh, &cmd, &file, &cache), Some(png.clone()));
+  assert_eq!(std::fs::read_to_string(&log).unwrap().lines().count(), 1);
+
+  let lines =
+    render(&sh, r#"echo "$2x$3 $4"; cat "$1""#, &png, 40, 12, "kitty");
+  assert_eq!(lines, Some(vec!["40x12 kitty".to_string(), "video".to_string()]));
+}
+
+#[cfg(unix)]
+#[test]
+fn failing_commands_give_no_thumbnail()
+{
+  let tmp = tempfile::tempdir().unwrap();
+  let file = tmp.path().join("paper.pdf");
+  std::fs::write(&file, b"pdf").unwrap();
+  let cache = tmp.path().join("cache");
+  let sh = CommandShell::default();
+  assert_eq!(ensure_thumbnail(&sh, "exit 1", &file, &cache), None);
+  // Succeeding without writing the image is a failure too
+  assert_eq!(ensure_thumbnail(&sh, "true", &file, &cache), None);
+  assert_eq!(render(&sh, "exit 3", &file, 10, 10, "halfblocks"), None);
+}
+
+#[cfg(unix)]
+#[test]
+fn thumbnailers_run_in_the_command_shell()
+{
+  let tmp = tempfile::tempdir().unwrap();
+  let file = tmp.path().join("clip.mp4");
+  std::fs::write(&file, b"video").unwrap();
+  let cache = tmp.path().join("cache");
+  // Only the variables `command_shell` lets through reach the command
+  let sh = CommandShell {
+    env: Some(vec![String::from("PATH")]),
+    ..Default::default()
+  };
+  let cmd = r#"[ -z "$HOME" ] && cp "$1" "$2""#;
+  assert!(ensure_thumbnail(&sh, cmd, &file, &cache).is_some());
+}
@@ -0,0 +1,45 @@
+use std::fs;
+
+use lsv::core::trash;
+
+#[test]
+fn trash_list_restore_and_purge()
+{
+  let tmp = tempfile::tempdir().expect("tmp");
+  let root = tmp.path();
+  let loc = trash::TrashLocation::at(&root.join("Trash"));
+
+  let work = root.join("work dir");
+  fs::create_dir_all(work.join("sub")).unwrap();
+  let file = work.join("notes.txt");
+  fs::write(&file, b"hello").unwrap();
+  fs::write(work.join("sub").join("inner.txt"), b"x").unwrap();
+
+  let item =
+    trash::trash_path(&loc, &file, &mut |_| Ok(())).expect("trash file");
+  assert!(!file.exists());
+  assert_eq!(item.original_path, file);
+  let dir_item = trash::trash_path(&loc, &work.join("sub"), &mut |_| Ok(()))
+    .expect("trash dir");
+
+  // Same name again gets a distinct id
+  fs::write(&file, b"second").unwrap();
+  let again =
+    trash::trash_path(&loc, &file, &mut |_| Ok(())).expect("trash again");
+  assert_ne!(again.id, item.id);
+
+  let items = trash::list_items(&loc).expect("list");
+  assert_eq!(items.len(), 3);
+  // Original paths survive the percent-encoding round trip
+  assert!(items.iter().all(|i| i.original_path.starts_with(&work)));
+
+  // Restore refuses to overwrite, then succeeds once the path is free
+  trash::restore_item(&loc, &item).expect("restore");
+  assert_eq!(fs::read(&file).unwrap(), b"hello");
+  assert!(trash::restore_item(&loc, &again).is_err());
+
+  trash::purge_item(&loc, &dir_item).expect("purge");
+  let left = trash::list_items(&loc).expect("list");
+  assert_eq!(left.len(), 1);
+  assert_eq!(left[0].id, again.id);
+}
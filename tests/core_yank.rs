@@ -0,0 +1,30 @@
+use lsv::core::yank::{
+  base64_encode,
+  osc52_sequence,
+};
+
+#[test]
+fn base64_pads_partial_groups()
+{
+  assert_eq!(base64_encode(b""), "");
+  assert_eq!(base64_encode(b"f"), "Zg==");
+  assert_eq!(base64_encode(b"fo"), "Zm8=");
+  assert_eq!(base64_encode(b"foo"), "Zm9v");
+  assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+  assert_eq!(base64_encode("/tmp/é".as_bytes()), "L3RtcC/DqQ==");
+}
+
+#[test]
+fn osc52_sets_the_clipboard_selection()
+{
+  assert_eq!(osc52_sequence("foo", false), "\x1b]52;c;Zm9v\x07");
+}
+
+#[test]
+fn osc52_is_wrapped_for_tmux()
+{
+  assert_eq!(
+    osc52_sequence("foo", true),
+    "\x1bPtmux;\x1b\x1b]52;c;Zm9v\x07\x1b\\"
+  );
+}
@@ -1,3 +1,97 @@
+/// An app in a fresh temporary directory holding the files `names`.
+fn app_with(names: &[&str]) -> (tempfile::TempDir, lsv::app::App)
+{
+  let temp = tempfile::tempdir().expect("tempdir");
+  for n in names
+  {
+    std::fs::write(temp.path().join(n), "x").unwrap();
+  }
+  let mut app = lsv::app::App::new().expect("app new");
+  app.set_cwd(temp.path());
+  app.finish_loading();
+  (temp, app)
+}
+
+/// An app with the Lua config `code` loaded, keymaps included.
+fn app_with_config(code: &str) -> lsv::app::App
+{
+  let (cfg, maps, engine_opt) =
+    lsv::config::load_config_from_code(code, None).expect("load");
+  let mut app = lsv::app::App::new().expect("app new");
+  app.set_config(cfg);
+  if let Some((engine, _prev, keys)) = engine_opt
+  {
+    app.inject_lua_engine_for_tests(engine, keys);
+  }
+  app.set_keymaps(maps);
+  app
+}
+
+/// [`app_with_config`] listing `dir`.
+fn app_with_config_in(
+  code: &str,
+  dir: &std::path::Path,
+) -> lsv::app::App
+{
+  let mut app = app_with_config(code);
+  app.set_cwd(dir);
+  app.finish_loading();
+  app
+}
+
+/// Everything on the screen of `term`, row after row.
+#[cfg(unix)]
+fn screen_text(
+  term: &ratatui::Terminal<ratatui::backend::TestBackend>
+) -> String
+{
+  term.backend().buffer().content().iter().map(|c| c.symbol()).collect()
+}
+
+/// Draw `app` on a `width` by `height` screen and return its rows, one per
+/// line.
+fn screen(
+  app: &mut lsv::app::App,
+  width: u16,
+  height: u16,
+) -> String
+{
+  let mut term =
+    ratatui::Terminal::new(ratatui::backend::TestBackend::new(width, height))
+      .unwrap();
+  term.draw(|f| lsv::ui::draw(f, app)).unwrap();
+  let buf = term.backend().buffer();
+  (0..buf.area.height)
+    .map(|y| {
+      (0..buf.area.width)
+        .map(|x| buf[(x, y)].symbol().to_string())
+        .collect::<String>()
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+/// Send `code` to the app as a key press.
+fn press(
+  app: &mut lsv::app::App,
+  code: crossterm::event::KeyCode,
+)
+{
+  lsv::input::handle_key(app, crossterm::event::KeyEvent::from(code)).unwrap();
+}
+
+/// Type `text` one character key at a time.
+fn keys(
+  app: &mut lsv::app::App,
+  text: &str,
+)
+{
+  for ch in text.chars()
+  {
+    press(app, crossterm::event::KeyCode::Char(ch));
+  }
+}
+
 mod config_tests
 {
   #[test]
@@ -117,6 +211,47 @@ lsv.config({ ui = { date_format = fmt } })
         || msg.contains("inline init.lua execution failed")
     );
   }
+
+  #[test]
+  fn theme_modules_fall_back_to_builtin_themes()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let root = temp.path().to_path_buf();
+    let themes_dir = root.join("lua").join("themes");
+    std::fs::create_dir_all(&themes_dir).expect("mkdir themes");
+    std::fs::write(themes_dir.join("gruvbox.lua"), "return { dir_fg = 'red' }")
+      .expect("write theme");
+
+    let code = "lsv.config({ ui = { theme = 'themes.nord' } })";
+    let (cfg, _maps, _eng) =
+      lsv::config::load_config_from_code(code, Some(&root))
+        .expect("load config");
+    let theme = cfg.ui.theme.expect("theme");
+    assert_eq!(theme.pane_bg.as_deref(), Some("#2e3440"));
+
+    // A user file of the same name wins
+    let code = "lsv.config({ ui = { theme = 'themes.gruvbox' } })";
+    let (cfg, _maps, _eng) =
+      lsv::config::load_config_from_code(code, Some(&root))
+        .expect("load config");
+    let theme = cfg.ui.theme.expect("theme");
+    assert_eq!(theme.dir_fg.as_deref(), Some("red"));
+    assert_eq!(theme.pane_bg, None);
+  }
+
+  #[test]
+  fn builtin_themes_all_load()
+  {
+    assert!(lsv::config::builtin_themes::THEMES.len() >= 4);
+    for (name, _) in lsv::config::builtin_themes::THEMES
+    {
+      let theme = lsv::config::builtin_themes::load(name)
+        .unwrap_or_else(|e| panic!("{}: {}", name, e));
+      assert!(theme.item_fg.is_some(), "{} has no item_fg", name);
+    }
+    assert!(lsv::config::builtin_themes::load("Nord").is_ok());
+    assert!(lsv::config::builtin_themes::load("missing").is_err());
+  }
 }
 
 mod effects_tests
@@ -1201,292 +1336,5800 @@ mod app_rs_tests
   }
 }
 
-mod runtime_rs_tests
+mod nav_tests
 {
-  use crossterm::event::{
-    Event,
-    KeyCode,
-    KeyEvent,
-    KeyModifiers,
-  };
+  use std::fs;
 
   #[test]
-  fn process_event_quit_returns_true()
+  fn streamed_listing_completes_sorted()
   {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let dir = temp.path();
+    // More than one loader batch worth of entries
+    for i in 0..2500
+    {
+      fs::write(dir.join(format!("f{:05}", i)), b"").unwrap();
+    }
+    fs::create_dir(dir.join("zdir")).unwrap();
     let mut app = lsv::app::App::new().expect("app new");
-    let quit = lsv::runtime_util::process_event(
-      &mut app,
-      Event::Key(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE)),
-    )
-    .unwrap();
-    assert!(quit);
+    app.set_cwd(dir);
+    app.finish_loading();
+    assert!(!app.is_loading());
+    assert!(!app.poll_dir_loads());
+    // Directories first, then files by name
+    assert_eq!(app.get_current_entry_name(0).as_deref(), Some("zdir"));
+    assert_eq!(app.get_current_entry_name(1).as_deref(), Some("f00000"));
+    assert_eq!(app.get_current_entry_name(2500).as_deref(), Some("f02499"));
+    assert_eq!(app.get_list_selected_index(), Some(0));
   }
+}
+
+mod tabs_tests
+{
+  use std::fs;
 
   #[test]
-  fn process_event_resize_returns_false()
+  fn tabs_keep_independent_directories()
   {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let a = temp.path().join("a");
+    let b = temp.path().join("b");
+    fs::create_dir(&a).unwrap();
+    fs::create_dir(&b).unwrap();
+    fs::write(a.join("one"), b"").unwrap();
+    fs::write(a.join("two"), b"").unwrap();
+    fs::write(b.join("other"), b"").unwrap();
+
     let mut app = lsv::app::App::new().expect("app new");
-    let cont =
-      lsv::runtime_util::process_event(&mut app, Event::Resize(80, 24))
-        .unwrap();
-    assert!(!cont);
+    app.set_cwd(&a);
+    app.select_index(1);
+    app.tab_new();
+    assert_eq!(app.tab_count(), 2);
+    assert_eq!(app.active_tab_index(), 1);
+    app.set_cwd(&b);
+
+    app.tab_prev();
+    assert_eq!(app.active_tab_index(), 0);
+    assert_eq!(app.get_cwd_path(), a);
+    assert_eq!(app.get_list_selected_index(), Some(1));
+
+    app.tab_next();
+    assert_eq!(app.get_cwd_path(), b);
+    assert_eq!(app.get_current_entry_name(0).as_deref(), Some("other"));
+
+    app.tab_close();
+    assert_eq!(app.tab_count(), 1);
+    assert_eq!(app.get_cwd_path(), a);
+    // The last tab stays open
+    app.tab_close();
+    assert_eq!(app.tab_count(), 1);
   }
-}
 
-mod util_rs_tests
-{
   #[test]
-  fn sanitize_line_expands_tabs_and_strips_cr_and_controls()
+  fn selection_copies_and_moves_to_the_other_tab()
   {
-    let input = "a\tb\rc\x07d"; // tab, CR, bell
-    let out = lsv::util::sanitize_line(input);
-    // tab -> 4 spaces, CR removed, control -> space
-    assert_eq!(out, "a    bc d");
+    let temp = tempfile::tempdir().expect("tempdir");
+    let a = temp.path().join("a");
+    let b = temp.path().join("b");
+    fs::create_dir(&a).unwrap();
+    fs::create_dir(&b).unwrap();
+    fs::write(a.join("one"), b"1").unwrap();
+    fs::write(a.join("two"), b"2").unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(&a);
+    app.finish_loading();
+    // A single tab has nowhere to send things
+    let before = app.recent_messages_len();
+    lsv::actions::dispatch_action(&mut app, "cmd:copy_to_other").unwrap();
+    assert!(app.recent_messages_len() > before);
+    assert!(!app.has_active_jobs());
+
+    app.tab_new();
+    app.set_cwd(&b);
+    app.tab_prev();
+    app.finish_loading();
+    app.select_index(0);
+    lsv::actions::dispatch_action(&mut app, "cmd:copy_to_other").unwrap();
+    app.finish_jobs();
+    assert_eq!(fs::read(b.join("one")).unwrap(), b"1");
+    assert!(a.join("one").exists());
+
+    app.select_index(1);
+    lsv::actions::dispatch_action(&mut app, "cmd:move_to_other").unwrap();
+    app.finish_jobs();
+    assert_eq!(fs::read(b.join("two")).unwrap(), b"2");
+    assert!(!a.join("two").exists());
   }
 }
-mod partial_return_tests
+
+mod jobs_tests
 {
+  use std::fs;
+
   #[test]
-  fn partial_overlay_return_is_merged_safely()
+  fn paste_and_delete_run_as_queued_jobs()
   {
-    // Action returns only a tiny table; validator should still see a full
-    // config
-    let code = r#"
-lsv.map_action('x', 'Partial', function(lsv, config)
-  return { ui = { display_mode = 'friendly' } }
-end)
-"#;
-    let (_cfg, maps, engine_opt) =
-      lsv::config::load_config_from_code(code, None).expect("load with action");
-    let (engine, _prev, keys) = engine_opt.expect("engine present");
+    let temp = tempfile::tempdir().expect("tempdir");
+    let src = temp.path().join("src");
+    let dst = temp.path().join("dst");
+    fs::create_dir_all(&src).unwrap();
+    fs::create_dir_all(&dst).unwrap();
+    fs::write(src.join("a.txt"), b"A").unwrap();
+    fs::write(src.join("b.txt"), b"B").unwrap();
+
     let mut app = lsv::app::App::new().expect("app new");
-    app.inject_lua_engine_for_tests(engine, keys);
-    app.set_keymaps(maps);
-    // Call the action via its mapped command (covers merge path too)
-    let action = app.get_keymap_action("x").expect("binding for x");
-    let ran =
-      lsv::actions::dispatch_action(&mut app, &action).expect("dispatch lua");
-    assert!(ran);
-    // Display mode should be Friendly after overlay is applied
-    assert!(matches!(app.get_display_mode(), lsv::app::DisplayMode::Friendly));
+    app.set_cwd(&src);
+    lsv::actions::dispatch_action(&mut app, "cmd:select_toggle").unwrap();
+    app.select_index(1);
+    lsv::actions::dispatch_action(&mut app, "cmd:select_toggle").unwrap();
+    lsv::actions::dispatch_action(&mut app, "clipboard:copy").unwrap();
+
+    app.set_cwd(&dst);
+    lsv::actions::dispatch_action(&mut app, "clipboard:paste").unwrap();
+    app.finish_jobs();
+    assert!(!app.has_active_jobs());
+    assert_eq!(fs::read(dst.join("a.txt")).unwrap(), b"A");
+    assert_eq!(fs::read(dst.join("b.txt")).unwrap(), b"B");
+    assert_eq!(app.get_current_entry_name(0).as_deref(), Some("a.txt"));
+  }
+
+  #[test]
+  fn trash_runs_as_a_queued_job()
+  {
+    let (temp, mut app) = super::app_with(&["a.txt", "b.txt"]);
+    let trash = temp.path().join(".Trash");
+    // No other test trashes anything, so sharing the variable is safe
+    unsafe { std::env::set_var("LSV_TRASH_DIR", &trash) };
+    lsv::actions::dispatch_action(&mut app, "cmd:select_toggle").unwrap();
+    lsv::actions::dispatch_action(&mut app, "cmd:delete_to_trash").unwrap();
+    super::press(&mut app, crossterm::event::KeyCode::Char('y'));
+    assert!(app.has_active_jobs());
+    app.finish_jobs();
+    assert!(!temp.path().join("a.txt").exists());
+    assert_eq!(fs::read(trash.join("files").join("a.txt")).unwrap(), b"x");
+    assert!(trash.join("info").join("a.txt.trashinfo").is_file());
+    assert_eq!(app.get_current_entry_name(0).as_deref(), Some("b.txt"));
   }
 }
 
-mod lua_glue_tests
+mod watch_tests
 {
-  use std::fs;
+  use std::{
+    fs,
+    time::{
+      Duration,
+      SystemTime,
+    },
+  };
 
-  fn make_app_with_actions(
-    lua_src: &str,
-    _seq: &str,
-  ) -> lsv::app::App
+  // Directory mtimes can have coarse granularity; push it forward explicitly
+  fn bump_mtime(
+    dir: &std::path::Path,
+    secs: u64,
+  )
   {
-    let (_cfg, maps, engine_opt) =
-      lsv::config::load_config_from_code(lua_src, None).expect("load lua");
-    let (engine, _prev, keys) = engine_opt.expect("engine");
-    let mut app = lsv::app::App::new().expect("app new");
-    app.inject_lua_engine_for_tests(engine, keys);
-    app.set_keymaps(maps);
-    app
+    let later = SystemTime::now() + Duration::from_secs(secs);
+    fs::File::open(dir).unwrap().set_modified(later).unwrap();
   }
 
   #[test]
-  fn lsv_select_item_sets_selection_effect()
+  fn external_changes_refresh_listing()
   {
-    // Two files to exercise selection reliably
     let temp = tempfile::tempdir().expect("tempdir");
     let dir = temp.path();
-    fs::write(dir.join("x.txt"), b"x").unwrap();
-    fs::write(dir.join("y.txt"), b"y").unwrap();
-    let code = r#"
-lsv.map_action('sel', 'Select first', function(lsv, config)
-  lsv.select_item(0)
-end)
-"#;
-    let mut app = make_app_with_actions(code, "sel");
+    fs::write(dir.join("a.txt"), b"").unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
     app.set_cwd(dir);
-    let action = app.get_keymap_action("sel").expect("binding");
-    let ran =
-      lsv::actions::dispatch_action(&mut app, &action).expect("dispatch");
-    assert!(ran);
-    assert_eq!(app.get_list_selected_index(), Some(0));
-  }
+    // First check only records the watched directories
+    assert!(!app.check_fs_changes());
+    assert!(!app.check_fs_changes());
 
-  #[test]
-  fn lsv_quit_sets_quit_effect()
-  {
-    let code = r#"
-lsv.map_action('q', 'Quit', function(lsv, config)
-  lsv.quit()
-end)
-"#;
-    let mut app = make_app_with_actions(code, "q");
-    let action = app.get_keymap_action("q").expect("binding");
-    let ran =
-      lsv::actions::dispatch_action(&mut app, &action).expect("dispatch");
-    assert!(ran);
-    assert!(app.get_quit());
+    fs::write(dir.join("b.txt"), b"").unwrap();
+    bump_mtime(dir, 5);
+    assert!(app.check_fs_changes());
+    assert_eq!(app.get_current_entry_name(1).as_deref(), Some("b.txt"));
+
+    fs::remove_file(dir.join("a.txt")).unwrap();
+    bump_mtime(dir, 10);
+    assert!(app.check_fs_changes());
+    assert_eq!(app.get_current_entry_name(0).as_deref(), Some("b.txt"));
+    assert_eq!(app.get_current_entry_name(1), None);
   }
 
   #[test]
-  fn lsv_display_output_sets_output_overlay()
+  fn saving_theme_file_reapplies_theme()
   {
-    let code = r#"
-lsv.map_action('o', 'Output', function(lsv, config)
-  lsv.display_output('Body', 'Title')
-end)
-"#;
-    let mut app = make_app_with_actions(code, "o");
-    let action = app.get_keymap_action("o").expect("binding");
-    let ran =
-      lsv::actions::dispatch_action(&mut app, &action).expect("dispatch");
-    assert!(ran);
-    assert!(app.get_show_output());
-    assert_eq!(app.get_output_title(), "Title");
-    assert!(app.get_output_text().contains("Body"));
+    let temp = tempfile::tempdir().expect("tempdir");
+    let theme_file = temp.path().join("theme.lua");
+    fs::write(&theme_file, "return { item_fg = 'white' }\n").unwrap();
+    let code = "lsv.config({ ui = { theme_path = 'theme.lua' } })";
+    let (cfg, _maps, _eng) =
+      lsv::config::load_config_from_code(code, Some(temp.path()))
+        .expect("load config");
+
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    app.set_config(cfg);
+    app.check_fs_changes();
+
+    fs::write(&theme_file, "return { item_fg = 'yellow' }\n").unwrap();
+    bump_mtime(&theme_file, 5);
+    assert!(app.check_fs_changes());
+    let item_fg = |app: &mut lsv::app::App| {
+      app.get_config().ui.theme.and_then(|t| t.item_fg)
+    };
+    assert_eq!(item_fg(&mut app).as_deref(), Some("yellow"));
+
+    // A half-written file keeps the last good theme
+    let before = app.recent_messages_len();
+    fs::write(&theme_file, "return {").unwrap();
+    bump_mtime(&theme_file, 10);
+    assert!(!app.check_fs_changes());
+    assert_eq!(item_fg(&mut app).as_deref(), Some("yellow"));
+    assert_eq!(app.recent_messages_len(), before + 1);
   }
+}
+
+mod preview_scroll_tests
+{
+  use std::fs;
+
+  use ratatui::{
+    Terminal,
+    backend::TestBackend,
+  };
 
   #[test]
-  fn lsv_os_run_captures_env_name()
+  fn preview_scrolls_and_resets_on_selection_change()
   {
-    // Prepare a known selection name
     let temp = tempfile::tempdir().expect("tempdir");
-    let dir = temp.path();
-    fs::write(dir.join("hello.txt"), b"hi").unwrap();
-    let code = r#"
-lsv.map_action('r', 'Run', function(lsv, config)
-  -- Compose the command from ctx; no placeholder expansion
-  local name = (config.context and config.context.current_file_name) or ''
-  lsv.os_run('echo ' .. lsv.quote(name))
-end)
-"#;
-    let mut app = make_app_with_actions(code, "r");
-    app.set_cwd(dir);
-    // Ensure selection is the file we just wrote
-    let pos = (0..100)
-      .find(|&i| app.get_current_entry_name(i).as_deref() == Some("hello.txt"))
-      .expect("find hello");
-    app.select_index(pos);
-    let action = app.get_keymap_action("r").expect("binding");
-    let ran =
-      lsv::actions::dispatch_action(&mut app, &action).expect("dispatch");
-    assert!(ran);
-    assert!(app.get_output_text().contains("hello.txt"));
+    let body: String = (1..=100).map(|i| format!("line {}\n", i)).collect();
+    fs::write(temp.path().join("a.txt"), &body).unwrap();
+    fs::write(temp.path().join("b.txt"), &body).unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    app.select_index(0);
+    let mut term = Terminal::new(TestBackend::new(80, 24)).unwrap();
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+
+    lsv::actions::dispatch_action(&mut app, "preview:scroll_down").unwrap();
+    lsv::actions::dispatch_action(&mut app, "preview:scroll_down").unwrap();
+    assert_eq!(app.preview_offset(), 2);
+    lsv::actions::dispatch_action(&mut app, "preview:scroll_up").unwrap();
+    assert_eq!(app.preview_offset(), 1);
+    lsv::actions::dispatch_action(&mut app, "preview:page_down").unwrap();
+    assert!(app.preview_offset() > 10);
+    lsv::actions::dispatch_action(&mut app, "preview:page_up").unwrap();
+    lsv::actions::dispatch_action(&mut app, "preview:page_up").unwrap();
+    assert_eq!(app.preview_offset(), 0);
+
+    // Scrolling stops at the last line of content
+    for _ in 0..50
+    {
+      lsv::actions::dispatch_action(&mut app, "preview:page_down").unwrap();
+    }
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+    assert!(app.preview_offset() < 100);
+
+    app.select_index(1);
+    assert_eq!(app.preview_offset(), 0);
   }
 
   #[test]
-  fn lsv_select_last_item_goes_to_end()
+  fn focus_mode_scrolls_unwrapped_preview_sideways()
   {
+    use crossterm::event::{
+      KeyCode,
+      KeyEvent,
+      KeyModifiers,
+    };
+    let key = |ch| KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE);
+    let screen = |term: &Terminal<TestBackend>| -> String {
+      term.backend().buffer().content().iter().map(|c| c.symbol()).collect()
+    };
+
     let temp = tempfile::tempdir().expect("tempdir");
-    let dir = temp.path();
-    fs::write(dir.join("a"), b"a").unwrap();
-    fs::write(dir.join("b"), b"b").unwrap();
-    let code = r#"
-lsv.map_action('last', 'Last', function(lsv, config)
-  lsv.select_last_item()
-end)
-"#;
-    let mut app = make_app_with_actions(code, "last");
-    app.set_cwd(dir);
-    let action = app.get_keymap_action("last").expect("binding");
-    let ran =
-      lsv::actions::dispatch_action(&mut app, &action).expect("dispatch");
-    assert!(ran);
-    if app.current_has_entries()
+    let long = format!("START{}END\nsecond\nthird\n", "x".repeat(200));
+    fs::write(temp.path().join("a.txt"), &long).unwrap();
+    fs::write(temp.path().join("b.txt"), &long).unwrap();
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    app.select_index(0);
+    let mut term = Terminal::new(TestBackend::new(80, 24)).unwrap();
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+    // Wrapped by default: the whole line shows over several rows
+    assert!(app.preview_wraps());
+    assert!(screen(&term).contains("END"));
+
+    lsv::actions::dispatch_action(&mut app, "preview:toggle_wrap").unwrap();
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+    let text = screen(&term);
+    assert!(!app.preview_wraps());
+    assert!(text.contains("START") && !text.contains("END"), "{}", text);
+    assert!(text.contains("nowrap"), "{}", text);
+
+    lsv::input::handle_key(&mut app, key('g')).unwrap();
+    lsv::input::handle_key(&mut app, key('p')).unwrap();
+    assert!(app.is_preview_focused());
+    for _ in 0..100
+    {
+      lsv::input::handle_key(&mut app, key('l')).unwrap();
+    }
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+    let text = screen(&term);
+    assert!(text.contains("END") && !text.contains("START"), "{}", text);
+    // Clamped so the end of the widest line stays in view
+    assert!(app.preview_hscroll() < 205);
+
+    lsv::input::handle_key(&mut app, key('0')).unwrap();
+    assert_eq!(app.preview_hscroll(), 0);
+    lsv::input::handle_key(&mut app, key('l')).unwrap();
+    // j scrolls the preview rather than moving the cursor
+    lsv::input::handle_key(&mut app, key('j')).unwrap();
+    assert_eq!(app.preview_offset(), 1);
+    assert_eq!(app.get_list_selected_index(), Some(0));
+
+    lsv::input::handle_key(&mut app, key('q')).unwrap();
+    assert!(!app.is_preview_focused());
+    lsv::input::handle_key(&mut app, key('j')).unwrap();
+    assert_eq!(app.get_list_selected_index(), Some(1));
+    assert_eq!(app.preview_hscroll(), 0);
+  }
+}
+
+#[cfg(unix)]
+mod async_preview_tests
+{
+  use std::{
+    fs,
+    thread::sleep,
+    time::{
+      Duration,
+      Instant,
+    },
+  };
+
+  use ratatui::{
+    Terminal,
+    backend::TestBackend,
+  };
+
+  use super::screen_text;
+
+  #[test]
+  fn archives_are_listed_in_background()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    fs::write(
+      temp.path().join("bundle.tar"),
+      super::archive_vfs_tests::build_tar(&[("listed-member.txt", b"hi")]),
+    )
+    .unwrap();
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    app.select_index(0);
+    assert!(app.is_preview_loading());
+
+    for _ in 0..300
+    {
+      if app.poll_preview()
+      {
+        break;
+      }
+      sleep(Duration::from_millis(10));
+    }
+    assert!(!app.is_preview_loading());
+    let mut term = Terminal::new(TestBackend::new(100, 20)).unwrap();
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+    assert!(screen_text(&term).contains("listed-member.txt"));
+  }
+
+  #[test]
+  fn slow_previewer_runs_in_background()
+  {
+    let code = r#"
+lsv.set_previewer(function(ctx) return "sleep 0.5; echo slow-preview-done" end)
+"#;
+    let (_cfg, _maps, engine_opt) =
+      lsv::config::load_config_from_code(code, None).expect("load config");
+    let (engine, previewer, _keys) = engine_opt.expect("engine present");
+
+    let temp = tempfile::tempdir().expect("tempdir");
+    fs::write(temp.path().join("doc.txt"), b"plain head").unwrap();
+    let mut app = lsv::app::App::new().expect("app new");
+    app.inject_lua_previewer_for_tests(engine, previewer);
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    app.select_index(0);
+
+    let mut term = Terminal::new(TestBackend::new(100, 20)).unwrap();
+    let started = Instant::now();
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+    assert!(started.elapsed() < Duration::from_millis(400));
+    assert!(app.is_preview_loading());
+    assert!(screen_text(&term).contains("loading preview"));
+
+    for _ in 0..300
+    {
+      if app.poll_preview()
+      {
+        break;
+      }
+      sleep(Duration::from_millis(10));
+    }
+    assert!(!app.is_preview_loading());
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+    assert!(screen_text(&term).contains("slow-preview-done"));
+  }
+
+  #[test]
+  fn recent_previews_are_reused()
+  {
+    let code = r#"
+lsv.set_previewer(function(ctx) return "echo preview-of-" .. ctx.current_file_name end)
+"#;
+    let (_cfg, _maps, engine_opt) =
+      lsv::config::load_config_from_code(code, None).expect("load config");
+    let (engine, previewer, _keys) = engine_opt.expect("engine present");
+
+    let temp = tempfile::tempdir().expect("tempdir");
+    fs::write(temp.path().join("a.txt"), b"a").unwrap();
+    fs::write(temp.path().join("b.txt"), b"b").unwrap();
+    let mut app = lsv::app::App::new().expect("app new");
+    app.inject_lua_previewer_for_tests(engine, previewer);
+    app.set_cwd(temp.path());
+    app.finish_loading();
+
+    let mut term = Terminal::new(TestBackend::new(100, 20)).unwrap();
+    let mut show = |app: &mut lsv::app::App, idx: usize| {
+      app.select_index(idx);
+      term.draw(|f| lsv::ui::draw(f, app)).unwrap();
+      let loading = app.is_preview_loading();
+      while app.is_preview_loading()
+      {
+        app.poll_preview();
+        sleep(Duration::from_millis(5));
+      }
+      term.draw(|f| lsv::ui::draw(f, app)).unwrap();
+      (loading, screen_text(&term))
+    };
+    let (loading, text) = show(&mut app, 0);
+    assert!(loading);
+    assert!(text.contains("preview-of-a.txt"));
+    assert!(show(&mut app, 1).0);
+
+    // Back on the first file, the earlier output is shown straight away
+    let (loading, text) = show(&mut app, 0);
+    assert!(!loading);
+    assert!(text.contains("preview-of-a.txt"));
+  }
+
+  /// App in a directory with `a.txt` and `b.txt`, previewed by a command.
+  fn previewed_app(
+    temp: &std::path::Path,
+    ui: &str,
+  ) -> lsv::app::App
+  {
+    let code = format!(
+      r#"
+lsv.config({{ ui = {{ {} }} }})
+lsv.set_previewer(function(ctx) return "echo preview-of-" .. ctx.current_file_name end)
+"#,
+      ui
+    );
+    let (cfg, _maps, engine_opt) =
+      lsv::config::load_config_from_code(&code, None).expect("load config");
+    let (engine, previewer, _keys) = engine_opt.expect("engine present");
+    fs::write(temp.join("a.txt"), b"a").unwrap();
+    fs::write(temp.join("b.txt"), b"b").unwrap();
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.inject_lua_previewer_for_tests(engine, previewer);
+    app.set_cwd(temp);
+    app.finish_loading();
+    app
+  }
+
+  /// Show entry `idx`; true when its previewer had to run.
+  fn preview_ran(
+    app: &mut lsv::app::App,
+    term: &mut Terminal<TestBackend>,
+    idx: usize,
+  ) -> bool
+  {
+    app.select_index(idx);
+    term.draw(|f| lsv::ui::draw(f, app)).unwrap();
+    let loading = app.is_preview_loading();
+    while app.is_preview_loading()
+    {
+      app.poll_preview();
+      sleep(Duration::from_millis(5));
+    }
+    loading
+  }
+
+  #[test]
+  fn preview_cache_size_and_ttl_follow_config()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let mut term = Terminal::new(TestBackend::new(100, 20)).unwrap();
+
+    // One kept output: b.txt pushes out a.txt
+    let mut app = previewed_app(temp.path(), "preview_cache = 1");
+    assert!(preview_ran(&mut app, &mut term, 0));
+    assert!(preview_ran(&mut app, &mut term, 1));
+    assert!(preview_ran(&mut app, &mut term, 0));
+    assert!(!preview_ran(&mut app, &mut term, 0));
+
+    // Outputs older than the TTL are not reused
+    let mut app = previewed_app(temp.path(), "preview_ttl = 1");
+    assert!(preview_ran(&mut app, &mut term, 0));
+    assert!(preview_ran(&mut app, &mut term, 1));
+    assert!(!preview_ran(&mut app, &mut term, 0));
+    sleep(Duration::from_millis(1100));
+    assert!(preview_ran(&mut app, &mut term, 1));
+  }
+
+  #[test]
+  fn preview_cache_notices_size_changes()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let mut term = Terminal::new(TestBackend::new(100, 20)).unwrap();
+    let mut app = previewed_app(temp.path(), "");
+    assert!(preview_ran(&mut app, &mut term, 0));
+    assert!(preview_ran(&mut app, &mut term, 1));
+
+    // Same modification time, different contents
+    let path = temp.path().join("a.txt");
+    let mtime = fs::metadata(&path).unwrap().modified().unwrap();
+    fs::write(&path, b"longer contents").unwrap();
+    fs::File::options()
+      .write(true)
+      .open(&path)
+      .unwrap()
+      .set_modified(mtime)
+      .unwrap();
+    assert!(preview_ran(&mut app, &mut term, 0));
+  }
+
+  fn preview_with(
+    ui: &str,
+    previewer: &str,
+  ) -> (lsv::app::App, Terminal<TestBackend>, tempfile::TempDir)
+  {
+    let code = format!(
+      "lsv.config({{ ui = {{ {} }} }})\nlsv.set_previewer(function(ctx) \
+       return {:?} end)",
+      ui, previewer
+    );
+    let (cfg, _maps, engine_opt) =
+      lsv::config::load_config_from_code(&code, None).expect("load config");
+    let (engine, previewer, _keys) = engine_opt.expect("engine present");
+    let temp = tempfile::tempdir().expect("tempdir");
+    fs::write(temp.path().join("doc.txt"), b"plain head").unwrap();
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.inject_lua_previewer_for_tests(engine, previewer);
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    app.select_index(0);
+    let mut term = Terminal::new(TestBackend::new(100, 20)).unwrap();
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+    while app.is_preview_loading()
+    {
+      app.poll_preview();
+      sleep(Duration::from_millis(5));
+    }
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+    (app, term, temp)
+  }
+
+  #[test]
+  fn slow_previewers_are_killed_after_the_timeout()
+  {
+    let started = Instant::now();
+    let (_app, term, _temp) =
+      preview_with("preview_timeout_ms = 200", "sleep 5; echo too-late");
+    assert!(started.elapsed() < Duration::from_secs(3));
+    let text = screen_text(&term);
+    assert!(text.contains("<previewer timed out>"), "{}", text);
+    assert!(!text.contains("too-late"));
+  }
+
+  #[test]
+  fn previewer_output_is_cut_at_max_bytes()
+  {
+    let started = Instant::now();
+    let (_app, term, _temp) =
+      preview_with("preview_max_bytes = 6", "echo first; yes second");
+    assert!(started.elapsed() < Duration::from_secs(3));
+    let text = screen_text(&term);
+    assert!(text.contains("first"), "{}", text);
+    assert!(!text.contains("second"), "{}", text);
+  }
+
+  fn show_preview(
+    app: &mut lsv::app::App,
+    idx: usize,
+  ) -> String
+  {
+    let mut term = Terminal::new(TestBackend::new(100, 20)).unwrap();
+    app.select_index(idx);
+    term.draw(|f| lsv::ui::draw(f, app)).unwrap();
+    while app.is_preview_loading()
+    {
+      app.poll_preview();
+      sleep(Duration::from_millis(5));
+    }
+    term.draw(|f| lsv::ui::draw(f, app)).unwrap();
+    screen_text(&term)
+  }
+
+  #[test]
+  fn lua_previewers_get_the_detected_mime_type()
+  {
+    let code = r#"
+lsv.set_previewer(function(ctx) return "echo mime=" .. ctx.mime end)
+"#;
+    let (_cfg, _maps, engine_opt) =
+      lsv::config::load_config_from_code(code, None).expect("load config");
+    let (engine, previewer, _keys) = engine_opt.expect("engine present");
+    let temp = tempfile::tempdir().expect("tempdir");
+    fs::write(temp.path().join("doc.txt"), b"%PDF-1.7\n").unwrap();
+    let mut app = lsv::app::App::new().expect("app new");
+    app.inject_lua_previewer_for_tests(engine, previewer);
+    app.set_cwd(temp.path());
+    app.finish_loading();
+
+    let text = show_preview(&mut app, 0);
+    assert!(text.contains("mime=application/pdf"), "{}", text);
+  }
+
+  #[test]
+  fn previewer_rules_need_no_lua()
+  {
+    let code = r#"
+lsv.config({
+  previewers = {
+    { ext = { "md" }, cmd = "echo rule {width}x{height}; cat {file}" },
+  },
+})
+"#;
+    let (cfg, _maps, _engine) =
+      lsv::config::load_config_from_code(code, None).expect("load config");
+    let temp = tempfile::tempdir().expect("tempdir");
+    fs::write(temp.path().join("it's.md"), b"markdown body").unwrap();
+    fs::write(temp.path().join("plain.txt"), b"plain head").unwrap();
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.set_cwd(temp.path());
+    app.finish_loading();
+
+    let text = show_preview(&mut app, 0);
+    assert!(text.contains("rule 68x17"), "{}", text);
+    assert!(text.contains("markdown body"), "{}", text);
+    // No rule matches: the built-in preview
+    let text = show_preview(&mut app, 1);
+    assert!(text.contains("plain head"), "{}", text);
+    assert!(!text.contains("rule"), "{}", text);
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn previewers_run_under_the_command_shell()
+  {
+    let code = r#"
+lsv.config({
+  command_shell = { program = "bash", env = { "PATH" } },
+  previewers = {
+    { ext = { "md" }, cmd = "echo ${BASH_VERSION:+bash} home=${HOME:-unset}" },
+  },
+})
+"#;
+    let (cfg, _maps, _engine) =
+      lsv::config::load_config_from_code(code, None).expect("load config");
+    assert!(!cfg.command_shell.login);
+    let temp = tempfile::tempdir().expect("tempdir");
+    fs::write(temp.path().join("a.md"), b"a").unwrap();
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.set_cwd(temp.path());
+    app.finish_loading();
+
+    let text = show_preview(&mut app, 0);
+    assert!(text.contains("bash home=unset"), "{}", text);
+    let bad = "lsv.config({ command_shell = 3 })";
+    assert!(lsv::config::load_config_from_code(bad, None).is_err());
+  }
+
+  #[test]
+  fn previewer_rules_come_before_the_lua_previewer()
+  {
+    let code = r#"
+lsv.config({ previewers = { { name = "*.md", cmd = "echo from-rule" } } })
+lsv.set_previewer(function(ctx) return "echo from-lua" end)
+"#;
+    let (cfg, _maps, engine_opt) =
+      lsv::config::load_config_from_code(code, None).expect("load config");
+    let (engine, previewer, _keys) = engine_opt.expect("engine present");
+    let temp = tempfile::tempdir().expect("tempdir");
+    fs::write(temp.path().join("a.md"), b"a").unwrap();
+    fs::write(temp.path().join("b.txt"), b"b").unwrap();
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.inject_lua_previewer_for_tests(engine, previewer);
+    app.set_cwd(temp.path());
+    app.finish_loading();
+
+    assert!(show_preview(&mut app, 0).contains("from-rule"));
+    assert!(show_preview(&mut app, 1).contains("from-lua"));
+    let bad = "lsv.config({ previewers = { { ext = 'md' } } })";
+    assert!(lsv::config::load_config_from_code(bad, None).is_err());
+  }
+
+  #[test]
+  fn failing_previewers_leave_the_file_to_the_next()
+  {
+    let code = r#"
+lsv.add_previewer(function(ctx) error("boom") end, { priority = 1 })
+lsv.add_previewer(function(ctx) return nil end)
+lsv.add_previewer(function(ctx)
+  if ctx.current_file_extension == "md" then return "echo from-third" end
+end)
+"#;
+    let (cfg, _maps, engine_opt) =
+      lsv::config::load_config_from_code(code, None).expect("load config");
+    let (engine, previewers, _keys) = engine_opt.expect("engine present");
+    let temp = tempfile::tempdir().expect("tempdir");
+    fs::write(temp.path().join("a.md"), b"a").unwrap();
+    fs::write(temp.path().join("b.txt"), b"plain head").unwrap();
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.inject_lua_previewer_for_tests(engine, previewers);
+    app.set_cwd(temp.path());
+    app.finish_loading();
+
+    assert!(show_preview(&mut app, 0).contains("from-third"));
+    // Nobody takes it: the built-in preview
+    assert!(show_preview(&mut app, 1).contains("plain head"));
+  }
+
+  #[test]
+  fn dir_previewers_are_asked_by_priority()
+  {
+    let code = r#"
+lsv.add_dir_previewer(function(ctx)
+  if ctx.is_dir and ctx.current_file_name == "repo" then
+    return "echo tree-of-" .. ctx.current_file_name
+  end
+end)
+lsv.add_dir_previewer(function(ctx) return "echo urgent" end, { priority = 5 })
+lsv.add_dir_previewer(function(ctx)
+  if ctx.current_file_name == "repo" then return "echo too-late" end
+end)
+"#;
+    let (cfg, _maps, engine_opt) =
+      lsv::config::load_config_from_code(code, None).expect("load config");
+    let (engine, _previewers, keys) = engine_opt.expect("engine present");
+    let temp = tempfile::tempdir().expect("tempdir");
+    for dir in ["plain", "repo"]
+    {
+      fs::create_dir(temp.path().join(dir)).unwrap();
+      fs::write(temp.path().join(dir).join("inside.txt"), b"x").unwrap();
+    }
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.inject_lua_engine_for_tests(engine, keys);
+    app.set_cwd(temp.path());
+    app.finish_loading();
+
+    // Highest priority first
+    assert!(show_preview(&mut app, 0).contains("urgent"));
+    assert!(show_preview(&mut app, 1).contains("urgent"));
+  }
+
+  #[test]
+  fn dir_previewers_fall_back_to_the_listing()
+  {
+    let code = r#"
+lsv.add_dir_previewer(function(ctx)
+  if ctx.is_dir and ctx.current_file_name == "repo" then
+    return "echo tree-of-" .. ctx.current_file_name
+  end
+end)
+lsv.add_dir_previewer(function(ctx)
+  if ctx.current_file_name == "repo" then return "echo too-late" end
+end)
+"#;
+    let (cfg, _maps, engine_opt) =
+      lsv::config::load_config_from_code(code, None).expect("load config");
+    let (engine, _previewers, keys) = engine_opt.expect("engine present");
+    let temp = tempfile::tempdir().expect("tempdir");
+    for dir in ["plain", "repo"]
+    {
+      fs::create_dir(temp.path().join(dir)).unwrap();
+      fs::write(temp.path().join(dir).join("inside.txt"), b"x").unwrap();
+    }
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.inject_lua_engine_for_tests(engine, keys);
+    app.set_cwd(temp.path());
+    app.finish_loading();
+
+    let text = show_preview(&mut app, 1);
+    assert!(text.contains("tree-of-repo"), "{}", text);
+    assert!(!text.contains("too-late"), "{}", text);
+    // Nothing for this one: the usual listing
+    let text = show_preview(&mut app, 0);
+    assert!(text.contains("inside.txt"), "{}", text);
+  }
+
+  #[test]
+  fn previewers_are_told_the_image_protocol()
+  {
+    let code = r#"
+lsv.config({ ui = { image_protocol = "sixel" } })
+lsv.set_previewer(function(ctx) return "echo protocol=" .. ctx.image_protocol end)
+"#;
+    let (cfg, _maps, engine_opt) =
+      lsv::config::load_config_from_code(code, None).expect("load config");
+    let (engine, previewer, _keys) = engine_opt.expect("engine present");
+
+    let temp = tempfile::tempdir().expect("tempdir");
+    fs::write(temp.path().join("photo.png"), b"png").unwrap();
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.inject_lua_previewer_for_tests(engine, previewer);
+    app.set_cwd(temp.path());
+    app.finish_loading();
+
+    let mut term = Terminal::new(TestBackend::new(100, 20)).unwrap();
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+    while app.is_preview_loading()
+    {
+      app.poll_preview();
+      sleep(Duration::from_millis(5));
+    }
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+    assert!(screen_text(&term).contains("protocol=sixel"));
+
+    let bad = r#"lsv.config({ ui = { image_protocol = "ascii" } })"#;
+    assert!(lsv::config::load_config_from_code(bad, None).is_err());
+  }
+
+  #[test]
+  fn failed_thumbnails_fall_back_to_a_notice()
+  {
+    let code = r#"
+lsv.config({ ui = { thumbnails = { video = "exit 1", pdf = false } } })
+"#;
+    let (cfg, _maps, _engine) =
+      lsv::config::load_config_from_code(code, None).expect("load config");
+    assert_eq!(cfg.ui.thumbnails.video.as_deref(), Some("exit 1"));
+    assert!(cfg.ui.thumbnails.pdf.is_none());
+
+    let temp = tempfile::tempdir().expect("tempdir");
+    fs::write(temp.path().join("clip.mp4"), b"\0\0\0 ftypisom").unwrap();
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.set_cwd(temp.path());
+    app.finish_loading();
+
+    let mut term = Terminal::new(TestBackend::new(100, 20)).unwrap();
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+    assert!(app.is_preview_loading());
+    while app.is_preview_loading()
+    {
+      app.poll_preview();
+      sleep(Duration::from_millis(5));
+    }
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+    assert!(screen_text(&term).contains("<no thumbnail>"));
+
+    let bad = r#"lsv.config({ ui = { thumbnails = { video = 3 } } })"#;
+    assert!(lsv::config::load_config_from_code(bad, None).is_err());
+  }
+
+  #[test]
+  fn svgs_without_a_thumbnail_show_their_source()
+  {
+    let code = r#"lsv.config({ ui = { thumbnails = { svg = "exit 1" } } })"#;
+    let (cfg, _maps, _engine) =
+      lsv::config::load_config_from_code(code, None).expect("load config");
+
+    let temp = tempfile::tempdir().expect("tempdir");
+    fs::write(temp.path().join("logo.svg"), "<svg id=\"logo\"></svg>\n")
+      .unwrap();
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.set_cwd(temp.path());
+    app.finish_loading();
+
+    let mut term = Terminal::new(TestBackend::new(100, 20)).unwrap();
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+    while app.is_preview_loading()
+    {
+      app.poll_preview();
+      sleep(Duration::from_millis(5));
+    }
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+    assert!(screen_text(&term).contains("<svg id=\"logo\"></svg>"));
+  }
+}
+
+mod archive_vfs_tests
+{
+  use std::fs;
+
+  use crossterm::event::{
+    KeyCode,
+    KeyEvent,
+    KeyModifiers,
+  };
+
+  /// Build a ustar archive from `(name, data)` pairs; names ending in `/`
+  /// become directories.
+  pub(super) fn build_tar(members: &[(&str, &[u8])]) -> Vec<u8>
+  {
+    let mut out = Vec::new();
+    for (name, data) in members
+    {
+      let mut hdr = [0u8; 512];
+      hdr[..name.len()].copy_from_slice(name.as_bytes());
+      hdr[100..107].copy_from_slice(b"0000644");
+      hdr[124..135].copy_from_slice(format!("{:011o}", data.len()).as_bytes());
+      hdr[136..147].copy_from_slice(b"00000000000");
+      hdr[156] = if name.ends_with('/') { b'5' } else { b'0' };
+      hdr[257..263].copy_from_slice(b"ustar\0");
+      hdr[263..265].copy_from_slice(b"00");
+      hdr[148..156].fill(b' ');
+      let sum: u32 = hdr.iter().map(|&b| b as u32).sum();
+      hdr[148..155].copy_from_slice(format!("{sum:06o}\0").as_bytes());
+      out.extend_from_slice(&hdr);
+      out.extend_from_slice(data);
+      out.resize(out.len().div_ceil(512) * 512, 0);
+    }
+    out.resize(out.len() + 1024, 0);
+    out
+  }
+
+  #[test]
+  fn enter_tar_and_extract_member()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let tar = temp.path().join("bundle.tar");
+    fs::write(
+      &tar,
+      build_tar(&[("inner/", b""), ("inner/hello.txt", b"hi there")]),
+    )
+    .unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    app.select_index(0);
+    let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+    lsv::input::handle_key(&mut app, enter).unwrap();
+    app.finish_loading();
+    assert_eq!(app.get_cwd_path(), tar);
+    assert!(app.in_archive());
+    assert_eq!(app.get_current_entry_name(0).as_deref(), Some("inner"));
+
+    lsv::input::handle_key(&mut app, enter).unwrap();
+    app.finish_loading();
+    assert_eq!(app.get_current_entry_name(0).as_deref(), Some("hello.txt"));
+
+    lsv::actions::dispatch_action(&mut app, "cmd:extract_here").unwrap();
+    app.finish_jobs();
+    assert_eq!(fs::read(temp.path().join("hello.txt")).unwrap(), b"hi there");
+  }
+}
+
+mod dir_sizes_tests
+{
+  use std::fs;
+
+  #[test]
+  fn compute_dir_sizes_sums_nested_files()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let sub = temp.path().join("sub");
+    fs::create_dir_all(sub.join("deep")).unwrap();
+    fs::create_dir_all(temp.path().join("empty")).unwrap();
+    fs::write(sub.join("a.txt"), b"abc").unwrap();
+    fs::write(sub.join("deep").join("b.txt"), b"12345").unwrap();
+    fs::write(temp.path().join("top.txt"), b"x").unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    lsv::actions::dispatch_action(&mut app, "cmd:compute_dir_sizes").unwrap();
+    app.finish_dir_sizes();
+    assert!(matches!(app.get_info_mode(), lsv::app::InfoMode::Size));
+    let size = app.dir_size(&sub).expect("sub computed");
+    assert!(size.done);
+    assert_eq!(size.bytes, 8);
+    let empty = app.dir_size(&temp.path().join("empty")).expect("empty");
+    assert_eq!(empty.bytes, 0);
+    assert!(app.dir_size(&temp.path().join("top.txt")).is_none());
+  }
+
+  #[test]
+  fn compute_dir_sizes_limits_to_selected_directories()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    for d in ["one", "two"]
+    {
+      fs::create_dir_all(temp.path().join(d)).unwrap();
+      fs::write(temp.path().join(d).join("f"), b"data").unwrap();
+    }
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    app.select_index(1);
+    lsv::actions::dispatch_action(&mut app, "cmd:select_toggle").unwrap();
+    lsv::actions::dispatch_action(&mut app, "cmd:compute_dir_sizes").unwrap();
+    app.finish_dir_sizes();
+    assert!(app.dir_size(&temp.path().join("one")).is_none());
+    assert_eq!(
+      app.dir_size(&temp.path().join("two")).map(|s| s.bytes),
+      Some(4)
+    );
+  }
+}
+
+mod mouse_tests
+{
+  use std::fs;
+
+  use crossterm::event::{
+    KeyModifiers,
+    MouseButton,
+    MouseEvent,
+    MouseEventKind,
+  };
+  use ratatui::{
+    Terminal,
+    backend::TestBackend,
+  };
+
+  fn mouse(
+    kind: MouseEventKind,
+    column: u16,
+    row: u16,
+  ) -> MouseEvent
+  {
+    MouseEvent { kind, column, row, modifiers: KeyModifiers::NONE }
+  }
+
+  fn click(
+    column: u16,
+    row: u16,
+  ) -> MouseEvent
+  {
+    mouse(MouseEventKind::Down(MouseButton::Left), column, row)
+  }
+
+  #[test]
+  fn click_selects_and_double_click_enters()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    fs::create_dir_all(temp.path().join("alpha")).unwrap();
+    fs::create_dir_all(temp.path().join("beta").join("inner")).unwrap();
+    fs::write(temp.path().join("gamma.txt"), b"g").unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    app.select_index(0);
+    // 100 columns: parent 0..10, current 10..30, preview 30..100; rows start
+    // below the header and the pane border
+    let mut term = Terminal::new(TestBackend::new(100, 20)).unwrap();
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+
+    lsv::input::handle_mouse(&mut app, click(15, 3)).unwrap();
+    assert_eq!(app.get_list_selected_index(), Some(1));
+    assert_eq!(app.get_cwd_path(), temp.path());
+
+    lsv::input::handle_mouse(&mut app, click(15, 3)).unwrap();
+    app.finish_loading();
+    assert_eq!(app.get_cwd_path(), temp.path().join("beta"));
+    assert_eq!(app.get_current_entry_name(0).as_deref(), Some("inner"));
+
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+    lsv::input::handle_mouse(&mut app, click(3, 5)).unwrap();
+    app.finish_loading();
+    assert_eq!(app.get_cwd_path(), temp.path());
+  }
+
+  #[test]
+  fn wheel_moves_cursor_and_ignores_rows_past_the_end()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    for name in ["a.txt", "b.txt", "c.txt"]
+    {
+      fs::write(temp.path().join(name), name).unwrap();
+    }
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    app.select_index(0);
+    let mut term = Terminal::new(TestBackend::new(100, 20)).unwrap();
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+
+    let down = mouse(MouseEventKind::ScrollDown, 15, 4);
+    for _ in 0..5
+    {
+      lsv::input::handle_mouse(&mut app, down).unwrap();
+    }
+    assert_eq!(app.get_list_selected_index(), Some(2));
+    lsv::input::handle_mouse(&mut app, mouse(MouseEventKind::ScrollUp, 15, 4))
+      .unwrap();
+    assert_eq!(app.get_list_selected_index(), Some(1));
+
+    lsv::input::handle_mouse(&mut app, click(15, 12)).unwrap();
+    assert_eq!(app.get_list_selected_index(), Some(1));
+  }
+}
+
+mod rename_editor_tests
+{
+  use std::fs;
+
+  #[test]
+  fn rename_from_list_applies_edited_names()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    for name in ["one.txt", "two.txt"]
+    {
+      fs::write(temp.path().join(name), name).unwrap();
+    }
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    app.select_index(0);
+    lsv::actions::dispatch_action(&mut app, "cmd:select_toggle").unwrap();
+    let items = vec![temp.path().join("one.txt"), temp.path().join("two.txt")];
+
+    // A collision renames nothing
+    app.rename_from_list(&items, "same.txt\nsame.txt\n");
+    assert!(temp.path().join("one.txt").exists());
+
+    app.rename_from_list(&items, "first.txt\ntwo.txt\n");
+    app.finish_loading();
+    assert_eq!(fs::read(temp.path().join("first.txt")).unwrap(), b"one.txt");
+    assert!(!temp.path().join("one.txt").exists());
+    assert_eq!(app.get_current_entry_name(0).as_deref(), Some("first.txt"));
+  }
+}
+
+mod flatten_tests
+{
+  use std::fs;
+
+  #[test]
+  fn toggle_flatten_lists_nested_files_by_relative_path()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let deep = temp.path().join("a").join("b").join("c");
+    fs::create_dir_all(&deep).unwrap();
+    fs::write(temp.path().join("top.txt"), b"1").unwrap();
+    fs::write(temp.path().join("a").join("mid.txt"), b"22").unwrap();
+    fs::write(deep.join("low.txt"), b"333").unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    lsv::actions::dispatch_action(&mut app, "cmd:toggle_flatten 2").unwrap();
+    app.finish_loading();
+    assert_eq!(app.flatten_depth(), Some(2));
+    let names: Vec<String> =
+      (0..4).filter_map(|i| app.get_current_entry_name(i)).collect();
+    assert_eq!(names, vec!["a/mid.txt", "top.txt"]);
+
+    lsv::actions::dispatch_action(&mut app, "cmd:toggle_flatten 5").unwrap();
+    app.finish_loading();
+    lsv::actions::dispatch_action(&mut app, "cmd:sort size").unwrap();
+    app.finish_loading();
+    let names: Vec<String> =
+      (0..4).filter_map(|i| app.get_current_entry_name(i)).collect();
+    assert_eq!(names.len(), 3);
+    assert!(names.contains(&"a/b/c/low.txt".to_string()));
+
+    lsv::actions::dispatch_action(&mut app, "cmd:toggle_flatten").unwrap();
+    app.finish_loading();
+    assert_eq!(app.flatten_depth(), None);
+    assert_eq!(app.get_current_entry_name(0).as_deref(), Some("a"));
+  }
+
+  #[test]
+  fn flat_view_ends_when_leaving_the_directory()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let sub = temp.path().join("sub");
+    fs::create_dir_all(sub.join("inner")).unwrap();
+    fs::write(sub.join("inner").join("f.txt"), b"f").unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(&sub);
+    app.finish_loading();
+    lsv::actions::dispatch_action(&mut app, "cmd:toggle_flatten").unwrap();
+    app.finish_loading();
+    assert_eq!(app.get_current_entry_name(0).as_deref(), Some("inner/f.txt"));
+
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    app.set_cwd(&sub);
+    app.finish_loading();
+    assert_eq!(app.flatten_depth(), None);
+    assert_eq!(app.get_current_entry_name(0).as_deref(), Some("inner"));
+  }
+}
+
+mod tree_view_tests
+{
+  use std::fs;
+
+  use crossterm::event::KeyCode;
+
+  use super::{
+    press,
+    screen,
+  };
+
+  fn names(app: &lsv::app::App) -> Vec<String>
+  {
+    (0..16).map_while(|i| app.get_current_entry_name(i)).collect()
+  }
+
+  #[test]
+  fn expand_and_collapse_in_place()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    fs::create_dir_all(temp.path().join("alpha").join("nested")).unwrap();
+    fs::create_dir_all(temp.path().join("beta")).unwrap();
+    fs::write(temp.path().join("alpha").join("x.txt"), b"x").unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    lsv::actions::dispatch_action(&mut app, "cmd:toggle_tree").unwrap();
+    app.finish_loading();
+    assert!(app.tree_active());
+    assert_eq!(names(&app), vec!["alpha", "beta"]);
+
+    app.select_index(0);
+    press(&mut app, KeyCode::Char('l'));
+    app.finish_loading();
+    assert_eq!(app.get_cwd_path(), temp.path());
+    assert_eq!(
+      names(&app),
+      vec!["alpha", "alpha/nested", "alpha/x.txt", "beta"]
+    );
+    assert_eq!(app.get_list_selected_index(), Some(0));
+
+    let screen = screen(&mut app, 100, 12);
+    assert!(screen.contains("▾ alpha/"));
+    assert!(screen.contains("  ▸ nested/"));
+
+    // Already expanded: l steps onto the first child, h collapses back
+    press(&mut app, KeyCode::Char('l'));
+    app.finish_loading();
+    assert_eq!(app.get_list_selected_index(), Some(1));
+    press(&mut app, KeyCode::Char('h'));
+    app.finish_loading();
+    assert_eq!(names(&app), vec!["alpha", "beta"]);
+    assert_eq!(app.get_list_selected_index(), Some(0));
+    assert_eq!(app.get_cwd_path(), temp.path());
+  }
+
+  #[test]
+  fn expanded_directories_persist_while_navigating()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let root = temp.path().join("root");
+    fs::create_dir_all(root.join("open")).unwrap();
+    fs::write(root.join("open").join("f.txt"), b"f").unwrap();
+    fs::write(root.join("top.txt"), b"t").unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(&root);
+    app.finish_loading();
+    lsv::actions::dispatch_action(&mut app, "cmd:toggle_tree").unwrap();
+    app.finish_loading();
+    app.select_index(0);
+    press(&mut app, KeyCode::Char('l'));
+    app.finish_loading();
+    assert_eq!(names(&app), vec!["open", "open/f.txt", "top.txt"]);
+
+    // h on a top-level file goes up as usual
+    app.select_index(2);
+    press(&mut app, KeyCode::Char('h'));
+    app.finish_loading();
+    assert_eq!(app.get_cwd_path(), temp.path());
+    app.set_cwd(&root);
+    app.finish_loading();
+    assert_eq!(names(&app), vec!["open", "open/f.txt", "top.txt"]);
+
+    lsv::actions::dispatch_action(&mut app, "cmd:toggle_tree").unwrap();
+    app.finish_loading();
+    assert_eq!(names(&app), vec!["open", "top.txt"]);
+  }
+}
+
+mod pane_layout_tests
+{
+  use std::fs;
+
+  use super::screen;
+
+  const LONG: &str = "a_rather_long_file_name_to_truncate.txt";
+
+  #[test]
+  fn pane_ratios_shorthand_sets_panes()
+  {
+    let code =
+      "lsv.config({ ui = { pane_ratios = { 1, 2, 3 }, show_parent = false } })";
+    let (cfg, _, _) =
+      lsv::config::load_config_from_code(code, None).expect("load config");
+    assert_eq!(
+      cfg.ui.panes.as_ref().map(|p| (p.parent, p.current, p.preview)),
+      Some((1, 2, 3))
+    );
+    assert!(!cfg.ui.show_parent);
+    assert!(cfg.ui.show_preview);
+
+    let bad = "lsv.config({ ui = { pane_ratios = { 1, 2 } } })";
+    let err = lsv::config::load_config_from_code(bad, None)
+      .err()
+      .expect("two ratios should be rejected");
+    assert!(err.to_string().contains("pane_ratios"));
+  }
+
+  #[test]
+  fn toggling_panes_hides_them()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let dir = temp.path().join("cwd");
+    fs::create_dir(&dir).unwrap();
+    fs::create_dir(temp.path().join("s@b")).unwrap();
+    fs::write(dir.join(LONG), "PREVIEWMARK").unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(&dir);
+    app.finish_loading();
+    app.select_index(0);
+    let s = screen(&mut app, 100, 12);
+    assert!(s.contains("s@b"));
+    assert!(s.contains("PREVIEWMARK"));
+    assert!(!s.lines().nth(2).unwrap().contains(LONG));
+
+    lsv::actions::dispatch_action(&mut app, "cmd:toggle_preview_pane").unwrap();
+    let s = screen(&mut app, 100, 12);
+    assert!(!s.contains("PREVIEWMARK"));
+    // The current pane takes the preview's width
+    assert!(s.lines().nth(2).unwrap().contains(LONG));
+
+    lsv::actions::dispatch_action(&mut app, "cmd:toggle_parent_pane").unwrap();
+    let s = screen(&mut app, 100, 12);
+    assert!(!s.contains("s@b"));
+    assert!(s.lines().nth(2).unwrap().contains(LONG));
+
+    lsv::actions::dispatch_action(&mut app, "cmd:toggle_preview_pane").unwrap();
+    lsv::actions::dispatch_action(&mut app, "cmd:toggle_parent_pane").unwrap();
+    let s = screen(&mut app, 100, 12);
+    assert!(s.contains("s@b"));
+    assert!(s.contains("PREVIEWMARK"));
+  }
+}
+
+mod session_tests
+{
+  use std::fs;
+
+  #[test]
+  fn session_restores_directory_view_and_selection()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let dir = temp.path().join("work");
+    fs::create_dir(&dir).unwrap();
+    fs::write(dir.join("a.txt"), "a").unwrap();
+    fs::write(dir.join("b.txt"), "bb").unwrap();
+    fs::write(dir.join(".hidden"), "").unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(&dir);
+    app.finish_loading();
+    lsv::actions::dispatch_action(&mut app, "cmd:sort size").unwrap();
+    lsv::actions::dispatch_action(&mut app, "cmd:show_hidden_toggle").unwrap();
+    app.finish_loading();
+    let idx = (0..8)
+      .find(|&i| app.get_current_entry_name(i).as_deref() == Some("a.txt"))
+      .expect("a.txt listed");
+    app.select_index(idx);
+    let saved = app.session_state();
+
+    let mut fresh = lsv::app::App::new().expect("app new");
+    fresh.apply_session(&saved, true);
+    fresh.finish_loading();
+    assert_eq!(fresh.get_cwd_path(), dir);
+    assert_eq!(fresh.session_state(), saved);
+    assert!(fresh.show_hidden());
+    assert_eq!(fresh.get_list_selected_index(), Some(idx));
+
+    // Without restore_cwd only the view settings carry over
+    let mut other = lsv::app::App::new().expect("app new");
+    let start = other.get_cwd_path();
+    other.apply_session(&saved, false);
+    assert_eq!(other.get_cwd_path(), start);
+    assert_eq!(other.session_state().sort_key, saved.sort_key);
+  }
+}
+
+mod picker_tests
+{
+  use std::fs;
+
+  use crossterm::event::KeyCode;
+  use lsv::app::PickMode;
+
+  use super::press;
+
+  #[test]
+  fn enter_picks_a_file_and_quits()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    fs::create_dir(temp.path().join("sub")).unwrap();
+    fs::write(temp.path().join("sub").join("inner.txt"), "x").unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    app.set_pick_mode(PickMode::One);
+
+    // Directories are entered, not picked
+    press(&mut app, KeyCode::Enter);
+    app.finish_loading();
+    assert!(!app.get_quit());
+    assert_eq!(app.get_cwd_path(), temp.path().join("sub"));
+    assert!(app.picked().is_empty());
+
+    press(&mut app, KeyCode::Enter);
+    assert!(app.get_quit());
+    assert_eq!(app.picked(), [temp.path().join("sub").join("inner.txt")]);
+
+    let out = temp.path().join("choice");
+    lsv::app::write_picked(out.to_str().unwrap(), app.picked()).unwrap();
+    assert_eq!(
+      fs::read_to_string(&out).unwrap(),
+      format!("{}\n", temp.path().join("sub").join("inner.txt").display())
+    );
+  }
+
+  #[test]
+  fn many_mode_picks_the_selection()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    for n in ["a.txt", "b.txt", "c.txt"]
+    {
+      fs::write(temp.path().join(n), n).unwrap();
+    }
+
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    app.set_pick_mode(PickMode::Many);
+    app.select_index(0);
+    press(&mut app, KeyCode::Char(' '));
+    app.select_index(2);
+    press(&mut app, KeyCode::Char(' '));
+    app.select_index(1);
+
+    press(&mut app, KeyCode::Enter);
+    assert!(app.get_quit());
+    assert_eq!(
+      app.picked(),
+      [temp.path().join("a.txt"), temp.path().join("c.txt")]
+    );
+  }
+
+  #[test]
+  fn enter_does_not_pick_outside_picker_mode()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    fs::write(temp.path().join("a.txt"), "a").unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    press(&mut app, KeyCode::Enter);
+    assert!(!app.get_quit());
+    assert!(app.picked().is_empty());
+  }
+}
+
+mod command_history_tests
+{
+  use crossterm::event::{
+    KeyCode,
+    KeyEvent,
+    KeyModifiers,
+  };
+
+  use super::press;
+
+  fn type_str(
+    app: &mut lsv::app::App,
+    s: &str,
+  )
+  {
+    for ch in s.chars()
+    {
+      press(app, KeyCode::Char(ch));
+    }
+  }
+
+  fn run(
+    app: &mut lsv::app::App,
+    line: &str,
+  )
+  {
+    press(app, KeyCode::Char(':'));
+    type_str(app, line);
+    press(app, KeyCode::Enter);
+    app.finish_loading();
+  }
+
+  #[test]
+  fn up_and_down_recall_commands()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    run(&mut app, "sort size");
+    run(&mut app, "sort name");
+
+    press(&mut app, KeyCode::Char(':'));
+    press(&mut app, KeyCode::Up);
+    assert_eq!(app.command_input(), Some("sort name"));
+    press(&mut app, KeyCode::Up);
+    assert_eq!(app.command_input(), Some("sort size"));
+    press(&mut app, KeyCode::Down);
+    assert_eq!(app.command_input(), Some("sort name"));
+    press(&mut app, KeyCode::Down);
+    assert_eq!(app.command_input(), Some(""));
+
+    // Typed text narrows the recall to entries starting with it
+    type_str(&mut app, "sort s");
+    press(&mut app, KeyCode::Up);
+    assert_eq!(app.command_input(), Some("sort size"));
+  }
+
+  #[test]
+  fn ctrl_r_searches_history()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    run(&mut app, "sort size");
+    run(&mut app, "sort name");
+
+    press(&mut app, KeyCode::Char(':'));
+    let ctrl_r = KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL);
+    lsv::input::handle_key(&mut app, ctrl_r).unwrap();
+    type_str(&mut app, "siz");
+    assert_eq!(app.command_input(), Some("sort size"));
+    press(&mut app, KeyCode::Backspace);
+    press(&mut app, KeyCode::Backspace);
+    press(&mut app, KeyCode::Backspace);
+    type_str(&mut app, "sort");
+    assert_eq!(app.command_input(), Some("sort name"));
+    lsv::input::handle_key(&mut app, ctrl_r).unwrap();
+    assert_eq!(app.command_input(), Some("sort size"));
+
+    // Esc leaves the search and restores what was typed
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.command_input(), Some(""));
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.command_input(), None);
+  }
+}
+
+mod path_completion_tests
+{
+  use std::fs;
+
+  use crossterm::event::KeyCode;
+  use ratatui::{
+    Terminal,
+    backend::TestBackend,
+  };
+
+  use super::press;
+
+  fn type_str(
+    app: &mut lsv::app::App,
+    s: &str,
+  )
+  {
+    for ch in s.chars()
+    {
+      press(app, KeyCode::Char(ch));
+    }
+  }
+
+  #[test]
+  fn tab_completes_argument_paths()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    fs::create_dir(temp.path().join("projects")).unwrap();
+    fs::create_dir(temp.path().join("projects").join("alpha")).unwrap();
+    fs::create_dir(temp.path().join("projects").join("beta")).unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+
+    press(&mut app, KeyCode::Char(':'));
+    type_str(&mut app, "cd pro");
+    press(&mut app, KeyCode::Tab);
+    assert_eq!(app.command_input(), Some("cd projects/"));
+
+    // Ambiguous: nothing to add, the candidates are listed in a popup
+    press(&mut app, KeyCode::Tab);
+    assert_eq!(app.command_input(), Some("cd projects/"));
+    let mut term = Terminal::new(TestBackend::new(60, 16)).unwrap();
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+    let screen: String =
+      term.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+    assert!(screen.contains("alpha/"));
+    assert!(screen.contains("beta/"));
+
+    type_str(&mut app, "b");
+    press(&mut app, KeyCode::Tab);
+    press(&mut app, KeyCode::Enter);
+    app.finish_loading();
+    assert_eq!(app.get_cwd_path(), temp.path().join("projects").join("beta"));
+  }
+
+  #[test]
+  fn tab_still_completes_command_names()
+  {
+    let mut app = lsv::app::App::new().expect("app new");
+    press(&mut app, KeyCode::Char(':'));
+    type_str(&mut app, "sort s");
+    press(&mut app, KeyCode::Tab);
+    assert_eq!(app.command_input(), Some("sort size"));
+  }
+}
+
+mod event_hook_tests
+{
+  use std::fs;
+
+  use lsv::app::AppEvent;
+
+  const HOOKS: &str = r#"
+seen = {}
+local function note(lsv, config)
+  local c = config.context
+  local now = c.event == "cd" and c.cwd or c.current_file_name
+  table.insert(seen, c.event .. ":" .. (c.previous or "") .. ">" .. now)
+  lsv.display_output(table.concat(seen, "\n"), "events")
+end
+lsv.on_event("on_startup", note)
+lsv.on_event("cd", note)
+lsv.on_event("select", note)
+"#;
+
+  #[test]
+  fn hooks_run_on_startup_cd_and_select()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let sub = temp.path().join("sub");
+    fs::create_dir(&sub).unwrap();
+    fs::write(temp.path().join("z.txt"), "z").unwrap();
+    fs::write(sub.join("inner.txt"), "i").unwrap();
+
+    let (cfg, _maps, engine_opt) =
+      lsv::config::load_config_from_code(HOOKS, None).expect("load hooks");
+    let (engine, _prev, keys) = engine_opt.expect("engine present");
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.inject_lua_engine_for_tests(engine, keys);
+    app.set_cwd(temp.path());
+    app.finish_loading();
+
+    app.emit_event(AppEvent::Startup);
+    app.poll_event_hooks();
+    assert_eq!(app.get_output_text(), "startup:>sub");
+
+    app.select_index(1);
+    app.poll_event_hooks();
+    // Nothing changed since the last poll: nothing fires
+    app.poll_event_hooks();
+    let lines: Vec<String> =
+      app.get_output_text().lines().map(String::from).collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[1], format!("select:{}>z.txt", sub.display()));
+
+    app.set_cwd(&sub);
+    app.finish_loading();
+    app.poll_event_hooks();
+    let lines: Vec<String> =
+      app.get_output_text().lines().map(String::from).collect();
+    assert_eq!(
+      lines[2..],
+      [
+        format!("cd:{}>{}", temp.path().display(), sub.display()),
+        format!("select:{}>inner.txt", temp.path().join("z.txt").display()),
+      ]
+    );
+  }
+
+  #[test]
+  fn unknown_event_names_are_rejected()
+  {
+    let err = lsv::config::load_config_from_code(
+      "lsv.on_event('on_blink', function() end)",
+      None,
+    )
+    .err()
+    .expect("unknown event should fail");
+    assert!(err.to_string().contains("unknown event 'on_blink'"));
+  }
+}
+
+mod lua_command_tests
+{
+  use crossterm::event::KeyCode;
+
+  use super::press;
+
+  const COMMANDS: &str = r#"
+lsv.register_command("greet", function(lsv, config)
+  local c = config.context
+  lsv.display_output(c.command .. "|" .. table.concat(c.args, ",") .. "|" .. c.args_line, "greet")
+end, { complete = { "world", "moon" } })
+"#;
+
+  fn app_with_commands() -> lsv::app::App
+  {
+    let (cfg, _maps, engine_opt) =
+      lsv::config::load_config_from_code(COMMANDS, None).expect("load");
+    let (engine, _prev, keys) = engine_opt.expect("engine present");
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.inject_lua_engine_for_tests(engine, keys);
+    app
+  }
+
+  fn type_line(
+    app: &mut lsv::app::App,
+    s: &str,
+  )
+  {
+    press(app, KeyCode::Char(':'));
+    for ch in s.chars()
+    {
+      press(app, KeyCode::Char(ch));
+    }
+  }
+
+  #[test]
+  fn registered_command_gets_parsed_arguments()
+  {
+    let mut app = app_with_commands();
+    type_line(&mut app, "greet Big 'two words' x\\ y");
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(
+      app.get_output_text(),
+      "greet|Big,two words,x y|Big 'two words' x\\ y"
+    );
+  }
+
+  #[test]
+  fn registered_command_completes_name_and_values()
+  {
+    let mut app = app_with_commands();
+    // "gre" alone also matches "grep"
+    type_line(&mut app, "gree");
+    press(&mut app, KeyCode::Tab);
+    assert_eq!(app.command_input(), Some("greet"));
+    for ch in " m".chars()
+    {
+      press(&mut app, KeyCode::Char(ch));
+    }
+    press(&mut app, KeyCode::Tab);
+    assert_eq!(app.command_input(), Some("greet moon"));
+  }
+
+  #[test]
+  fn builtin_and_invalid_names_are_rejected()
+  {
+    for code in [
+      "lsv.register_command('cd', function() end)",
+      "lsv.register_command('Bad Name', function() end)",
+    ]
+    {
+      assert!(
+        lsv::config::load_config_from_code(code, None).is_err(),
+        "{}",
+        code
+      );
+    }
+  }
+}
+
+mod lua_column_tests
+{
+  use std::fs;
+
+  use super::screen;
+
+  const COLUMNS: &str = r#"
+local counts = {}
+lsv.define_column("tag", function(e)
+  counts[e.path] = (counts[e.path] or 0) + 1
+  if e.is_dir then return nil end
+  return e.extension:upper() .. counts[e.path]
+end)
+lsv.map_action("zr", "Refresh tags", function(lsv, config)
+  lsv.invalidate_column("tag")
+end)
+lsv.config({ ui = { row = { left = "{name} [{nope}]", right = "{tag}" } } })
+"#;
+
+  #[test]
+  fn row_templates_show_cached_lua_columns()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    fs::write(temp.path().join("notes.md"), "x").unwrap();
+
+    let (cfg, _maps, engine_opt) =
+      lsv::config::load_config_from_code(COLUMNS, None).expect("load");
+    let (engine, _prev, keys) = engine_opt.expect("engine present");
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.inject_lua_engine_for_tests(engine, keys);
+    app.set_cwd(temp.path());
+    app.finish_loading();
+
+    let s = screen(&mut app, 200, 8);
+    assert!(s.contains("MD1"), "column value missing: {}", s);
+    // Unknown placeholders are kept as written
+    assert!(s.contains("notes.md [{nope}]"));
+    assert!(screen(&mut app, 200, 8).contains("MD1"), "value should be cached");
+
+    lsv::dispatch_action(&mut app, "run_lua:1").unwrap();
+    assert!(
+      screen(&mut app, 200, 8).contains("MD2"),
+      "value should be recomputed"
+    );
+  }
+
+  #[test]
+  fn template_functions_run_once_per_redraw()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    fs::write(temp.path().join("notes.md"), "x").unwrap();
+    let code = r##"
+local calls = {}
+lsv.define_template_fn("stamp", function(e, s)
+  calls[e.path] = (calls[e.path] or 0) + 1
+  return e.name .. "#" .. calls[e.path] .. "/" .. s.entry_total
+end)
+lsv.config({ ui = {
+  header = { left = "<{lua:stamp}> <{lua:missing}>", right = "" },
+  row = { left = "{name}", right = "{lua:stamp}" },
+} })
+"##;
+    let (cfg, _maps, engine_opt) =
+      lsv::config::load_config_from_code(code, None).expect("load");
+    let (engine, _prev, keys) = engine_opt.expect("engine present");
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.inject_lua_engine_for_tests(engine, keys);
+    app.set_cwd(temp.path());
+    app.finish_loading();
+
+    // Header and row share the value computed for the entry this frame
+    let s = screen(&mut app, 200, 8);
+    assert!(s.contains("<notes.md#1/1>"), "{}", s);
+    assert!(!s.contains("notes.md#2"), "{}", s);
+    // Unknown functions are kept as written
+    assert!(s.contains("<{lua:missing}>"), "{}", s);
+    // Each redraw calls again
+    let s = screen(&mut app, 200, 8);
+    assert!(s.contains("<notes.md#2/1>"), "{}", s);
+  }
+
+  #[test]
+  fn builtin_field_names_are_rejected()
+  {
+    let code = "lsv.define_column('name', function() end)";
+    assert!(lsv::config::load_config_from_code(code, None).is_err());
+  }
+}
+
+mod row_format_tests
+{
+  use std::{
+    fs,
+    time::{
+      Duration,
+      SystemTime,
+    },
+  };
+
+  use ratatui::{
+    Terminal,
+    backend::TestBackend,
+  };
+
+  #[test]
+  fn row_fields_take_width_alignment_and_date_specs()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let path = temp.path().join("a.txt");
+    fs::write(&path, "12345").unwrap();
+    // 2020-06-15, far enough from a year boundary for any timezone
+    let when = SystemTime::UNIX_EPOCH + Duration::from_secs(1_592_222_400);
+    fs::File::options()
+      .write(true)
+      .open(&path)
+      .unwrap()
+      .set_modified(when)
+      .unwrap();
+
+    let code = r#"
+lsv.config({ ui = {
+  show = "none",
+  display_mode = "absolute",
+  row = { left = "{name:<8}|{size:>6}|{mtime:%Y}|{name:.3}|{size:?}", right = "" },
+} })
+"#;
+    let (cfg, _maps, _eng) =
+      lsv::config::load_config_from_code(code, None).expect("load");
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.set_cwd(temp.path());
+    app.finish_loading();
+
+    let mut term = Terminal::new(TestBackend::new(200, 6)).unwrap();
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+    let screen: String = term
+      .backend()
+      .buffer()
+      .content()
+      .iter()
+      .map(|c| c.symbol().to_string())
+      .collect();
+    assert!(
+      screen.contains("a.txt   |   5 B|2020|a.~|{size:?}"),
+      "row not formatted: {}",
+      screen.trim()
+    );
+  }
+
+  #[test]
+  fn mime_field_comes_from_the_contents()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    fs::write(temp.path().join("picture.txt"), b"\x89PNG\r\n\x1a\n....")
+      .unwrap();
+    fs::write(temp.path().join("script"), b"#!/usr/bin/env python3\n").unwrap();
+    let code = r#"
+lsv.config({ ui = {
+  show = "none",
+  header = { left = "head:{mime}" },
+  row = { left = "{name:<12}[{mime}]", right = "" },
+} })
+"#;
+    let (cfg, _maps, _eng) =
+      lsv::config::load_config_from_code(code, None).expect("load");
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    app.select_index(0);
+
+    let mut term = Terminal::new(TestBackend::new(200, 6)).unwrap();
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+    let screen: String =
+      term.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+    assert!(screen.contains("head:image/png"), "{}", screen.trim());
+    assert!(screen.contains("picture.txt [image/png]"), "{}", screen.trim());
+    assert!(
+      screen.contains("script      [text/x-python]"),
+      "{}",
+      screen.trim()
+    );
+  }
+
+  #[test]
+  fn relative_dates_show_recent_times_as_ago()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let now = SystemTime::now();
+    for (name, when) in [
+      ("new.txt", now - Duration::from_secs(3 * 3600 + 60)),
+      ("old.txt", SystemTime::UNIX_EPOCH + Duration::from_secs(1_592_222_400)),
+    ]
+    {
+      let path = temp.path().join(name);
+      fs::write(&path, "x").unwrap();
+      fs::File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_modified(when)
+        .unwrap();
+    }
+
+    let code = r#"
+lsv.config({ ui = {
+  show = "none",
+  display_mode = "absolute",
+  date_format = "%Y",
+  relative_dates = 2,
+  row = { left = "{name}|{mtime}|", right = "" },
+  header = { left = "[{current_file_mtime}]", right = "" },
+} })
+"#;
+    let (cfg, _maps, _eng) =
+      lsv::config::load_config_from_code(code, None).expect("load");
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.set_cwd(temp.path());
+    app.finish_loading();
+
+    let mut term = Terminal::new(TestBackend::new(200, 6)).unwrap();
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+    let screen: String = term
+      .backend()
+      .buffer()
+      .content()
+      .iter()
+      .map(|c| c.symbol().to_string())
+      .collect();
+    assert!(screen.contains("new.txt|3h ago|"), "row: {}", screen.trim());
+    assert!(screen.contains("old.txt|2020|"), "row: {}", screen.trim());
+    assert!(screen.contains("[3h ago]"), "header: {}", screen.trim());
+  }
+
+  #[test]
+  fn relative_dates_rejects_other_values()
+  {
+    let code = r#"lsv.config({ ui = { relative_dates = "soon" } })"#;
+    assert!(lsv::config::load_config_from_code(code, None).is_err());
+  }
+}
+
+mod icon_tests
+{
+  use std::fs;
+
+  use ratatui::{
+    Terminal,
+    backend::TestBackend,
+    style::Color,
+  };
+
+  /// Foreground of the first cell showing `symbol`.
+  fn fg_of(
+    app: &mut lsv::app::App,
+    symbol: &str,
+  ) -> Option<Option<Color>>
+  {
+    let mut term = Terminal::new(TestBackend::new(120, 8)).unwrap();
+    term.draw(|f| lsv::ui::draw(f, app)).unwrap();
+    let buf = term.backend().buffer().clone();
+    buf.content().iter().find(|c| c.symbol() == symbol).map(|c| Some(c.fg))
+  }
+
+  #[test]
+  fn ui_icons_uses_builtin_glyphs_with_overridable_colours()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    fs::write(temp.path().join("main.rs"), "").unwrap();
+    fs::write(temp.path().join("notes.md"), "").unwrap();
+    fs::write(temp.path().join("data.bin"), "").unwrap();
+    let code = r##"
+lsv.config({ ui = { icons = true } })
+lsv.icons({ md = { color = "#ff0000" }, bin = "B" })
+"##;
+    let (cfg, _maps, _engine) =
+      lsv::config::load_config_from_code(code, None).expect("load");
+    assert!(cfg.icons.enabled);
+    assert_eq!(cfg.icons.preset.as_deref(), Some("nerdfont"));
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.set_cwd(temp.path());
+    app.finish_loading();
+
+    // Built-in glyph and colour for Rust sources
+    assert_eq!(
+      fg_of(&mut app, "\u{e7a8}"),
+      Some(Some(Color::Rgb(0xde, 0xa5, 0x84)))
+    );
+    // Built-in glyph, overridden colour
+    assert_eq!(fg_of(&mut app, "\u{e73e}"), Some(Some(Color::Rgb(255, 0, 0))));
+    // Overridden glyph
+    let mut term = Terminal::new(TestBackend::new(120, 8)).unwrap();
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+    let screen: String =
+      term.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+    assert!(screen.contains("B data.bin"), "{}", screen);
+  }
+}
+
+mod entry_color_tests
+{
+  use std::fs;
+
+  use ratatui::{
+    Terminal,
+    backend::TestBackend,
+    style::{
+      Color,
+      Modifier,
+    },
+  };
+
+  const THEME: &str = r#"
+lsv.config({ ui = { theme = {
+  file_fg = "white",
+  entry_colors = {
+    ["*.md"] = "cyan",
+    ["*"] = { fg = "gray" },
+    ["readme.md"] = { fg = "magenta", italic = true },
+    Makefile = "yellow bold on blue",
+  },
+} } })
+"#;
+
+  #[test]
+  fn rules_are_parsed_most_specific_first()
+  {
+    let (cfg, _maps, _eng) =
+      lsv::config::load_config_from_code(THEME, None).expect("load");
+    let rules = &cfg.ui.theme.as_ref().unwrap().entry_colors;
+    let order: Vec<&str> = rules.iter().map(|r| r.pattern.as_str()).collect();
+    assert_eq!(order, ["readme.md", "Makefile", "*.md", "*"]);
+    let make = &rules[1];
+    assert_eq!(make.fg.as_deref(), Some("yellow"));
+    assert_eq!(make.bg.as_deref(), Some("blue"));
+    assert!(make.bold && !make.italic);
+    assert!(rules[0].italic);
+  }
+
+  #[test]
+  fn matching_entries_are_drawn_with_the_rule_style()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    for name in ["Makefile", "notes.md", "README.md", "zz.txt"]
+    {
+      fs::write(temp.path().join(name), "").unwrap();
+    }
+    let (cfg, _maps, _eng) =
+      lsv::config::load_config_from_code(THEME, None).expect("load");
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    // Keep the cursor off the rows checked below
+    app.select_index(3);
+
+    let mut term = Terminal::new(TestBackend::new(120, 8)).unwrap();
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+    let buf = term.backend().buffer().clone();
+    let area = *buf.area();
+    let style_of = |name: &str| {
+      (area.y..area.bottom())
+        .find_map(|y| {
+          let row: String = (area.x..area.right())
+            .map(|x| buf[(x, y)].symbol().to_string())
+            .collect();
+          row.find(name).map(|col| {
+            let x = area.x + row[..col].chars().count() as u16;
+            buf[(x, y)].style()
+          })
+        })
+        .unwrap_or_else(|| panic!("{} not drawn", name))
+    };
+    let make = style_of("Makefile");
+    assert_eq!((make.fg, make.bg), (Some(Color::Yellow), Some(Color::Blue)));
+    assert!(make.add_modifier.contains(Modifier::BOLD));
+    assert_eq!(style_of("notes.md").fg, Some(Color::Cyan));
+    let readme = style_of("README.md");
+    assert_eq!(readme.fg, Some(Color::Magenta));
+    assert!(readme.add_modifier.contains(Modifier::ITALIC));
+  }
+}
+
+mod theme_picker_tests
+{
+  use crossterm::event::{
+    KeyCode,
+    KeyEvent,
+    KeyModifiers,
+  };
+  use ratatui::{
+    Terminal,
+    backend::TestBackend,
+  };
+
+  #[test]
+  fn picker_previews_highlighted_theme_without_applying_it()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    let before = app.get_config().ui.theme;
+
+    lsv::dispatch_action(&mut app, "cmd:theme").unwrap();
+    let down = KeyEvent::new(KeyCode::Down, KeyModifiers::NONE);
+    lsv::input::handle_key(&mut app, down).unwrap();
+    assert_eq!(app.get_config().ui.theme, before);
+
+    let mut term = Terminal::new(TestBackend::new(100, 30)).unwrap();
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+    let buf = term.backend().buffer().clone();
+    let symbols: Vec<&str> = buf.content.iter().map(|c| c.symbol()).collect();
+    let name: Vec<String> = "Cargo.toml".chars().map(String::from).collect();
+    let at = (0..symbols.len() - name.len())
+      .find(|&i| (0..name.len()).all(|j| symbols[i + j] == name[j]))
+      .expect("sample listing drawn");
+    let cell = &buf.content[at];
+
+    let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+    lsv::input::handle_key(&mut app, enter).unwrap();
+    let applied = app.get_config().ui.theme.expect("theme applied");
+    assert_ne!(Some(&applied), before.as_ref());
+    let selected_bg = applied
+      .selected_item_bg
+      .as_deref()
+      .and_then(lsv::ui::colors::parse_color)
+      .expect("selected_item_bg");
+    assert_eq!(cell.bg, selected_bg);
+  }
+}
+
+mod reload_config_tests
+{
+  use std::fs;
+
+  fn paths(root: &std::path::Path) -> lsv::config::ConfigPaths
+  {
+    lsv::config::ConfigPaths {
+      root:   root.to_path_buf(),
+      entry:  root.join("init.lua"),
+      exists: true,
+    }
+  }
+
+  #[test]
+  fn reload_applies_new_config_and_keeps_it_on_error()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let root = temp.path();
+    let init = root.join("init.lua");
+    fs::write(&init, "lsv.config({ ui = { date_format = '%d' } })\n").unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
+    assert!(app.reload_config_from(paths(root)));
+    assert_eq!(app.get_date_format().as_deref(), Some("%d"));
+
+    fs::write(
+      &init,
+      "lsv.config({ ui = { date_format = '%m' } })\nlsv.map_action('zq', \
+       'Quiet', function(lsv, config) end)\n",
+    )
+    .unwrap();
+    lsv::dispatch_action(&mut app, "cmd:reload_config").unwrap();
+    assert_eq!(app.get_date_format().as_deref(), Some("%m"));
+    assert!(app.get_keymap_action("zq").is_some());
+
+    fs::write(&init, "lsv.config({").unwrap();
+    assert!(!app.reload_config_from(paths(root)));
+    assert_eq!(app.get_output_title(), "Config error");
+    assert!(app.get_output_text().contains("init.lua"));
+    assert_eq!(app.get_date_format().as_deref(), Some("%m"));
+    assert!(app.get_keymap_action("zq").is_some());
+  }
+}
+
+mod footer_tests
+{
+  use super::{
+    app_with,
+    app_with_config_in,
+    screen,
+  };
+
+  const FILES: [&str; 2] = ["a.txt", "b.txt"];
+
+  #[test]
+  fn no_footer_by_default()
+  {
+    let (_temp, mut app) = app_with(&FILES);
+    let shown = screen(&mut app, 80, 6);
+    let last = shown.lines().last().unwrap();
+    assert!(last.contains('─') || last.contains('└'), "{}", last);
+  }
+
+  #[test]
+  fn template_footer_shows_selection_count()
+  {
+    let code = r#"lsv.config({ ui = { footer = {
+      left = "sel:{selected_count} jobs:{jobs}", right = "[{filter}]" } } })"#;
+    let (temp, _) = app_with(&FILES);
+    let mut app = app_with_config_in(code, temp.path());
+    lsv::dispatch_action(&mut app, "cmd:select_toggle").unwrap();
+    let shown = screen(&mut app, 80, 6);
+    let last = shown.lines().last().unwrap();
+    assert!(last.starts_with("sel:1 jobs:0"), "{}", last);
+    assert!(last.trim_end().ends_with("[]"), "{}", last);
+  }
+
+  #[test]
+  fn position_selection_and_disk_space_placeholders()
+  {
+    let code = r#"lsv.config({ ui = { footer = {
+      left = "{entry_index}/{entry_total} sel:{selection_count} {selection_size}",
+      right = "free:{free_space}" } } })"#;
+    let (temp, _) = app_with(&FILES);
+    let mut app = app_with_config_in(code, temp.path());
+    lsv::dispatch_action(&mut app, "cmd:select_all").unwrap();
+    app.select_index(1);
+    app.finish_disk_space();
+    let shown = screen(&mut app, 80, 6);
+    let last = shown.lines().last().unwrap();
+    assert!(last.starts_with("2/2 sel:2 2 B"), "{}", last);
+    let (_total, avail) = app.disk_space().expect("df answered");
+    let free = format!("free:{} B", avail);
+    assert!(last.trim_end().ends_with(&free), "{}", last);
+  }
+
+  #[test]
+  fn lua_footer_gets_status_values()
+  {
+    let code = r#"lsv.config({ ui = { footer = {
+      left = function(s) return "n=" .. s.current_len .. " clip=" .. s.clipboard end,
+    } } })"#;
+    let (temp, _) = app_with(&FILES);
+    let mut app = app_with_config_in(code, temp.path());
+    lsv::dispatch_action(&mut app, "cmd:select_toggle; clipboard:copy")
+      .unwrap();
+    let shown = screen(&mut app, 80, 6);
+    let last = shown.lines().last().unwrap();
+    assert!(last.starts_with("n=2 clip=copy 1"), "{}", last);
+  }
+
+  #[test]
+  fn multi_line_header_templates_add_rows()
+  {
+    let code = r#"lsv.config({ ui = { header = {
+      left = "top-left\nsecond {current_file_name}", right = "top-right" } } })"#;
+    let (temp, _) = app_with(&FILES);
+    let mut app = app_with_config_in(code, temp.path());
+    let shown = screen(&mut app, 80, 6);
+    let rows: Vec<&str> = shown.lines().collect();
+    assert!(rows[0].starts_with("top-left"), "{}", rows[0]);
+    assert!(rows[0].trim_end().ends_with("top-right"), "{}", rows[0]);
+    assert!(rows[1].starts_with("second a.txt"), "{}", rows[1]);
+    // The panes start below the second row
+    assert!(rows[2].contains('─') || rows[2].contains('┌'), "{}", rows[2]);
+  }
+
+  #[test]
+  fn footer_rejects_other_types()
+  {
+    let code = "lsv.config({ ui = { footer = { left = 3 } } })";
+    let err = lsv::config::load_config_from_code(code, None).err();
+    assert!(err.is_some());
+  }
+}
+
+mod opener_tests
+{
+  use std::fs;
+
+  use crossterm::event::{
+    KeyCode,
+    KeyEvent,
+  };
+
+  const OPENERS: &str = r#"
+lsv.config({
+  openers = {
+    { ext = { "txt" }, cmd = "printf 'opened %s' \"$1\" > ../out.log" },
+    { name = "*.fail", cmd = "echo broken; exit 3" },
+  },
+})
+"#;
+
+  fn app_in(dir: &std::path::Path) -> lsv::app::App
+  {
+    let (cfg, _maps, _engine) =
+      lsv::config::load_config_from_code(OPENERS, None).expect("load");
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.set_cwd(dir);
+    app.finish_loading();
+    app
+  }
+
+  #[test]
+  fn enter_runs_the_matching_opener_in_cwd()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let dir = temp.path().join("docs");
+    fs::create_dir(&dir).unwrap();
+    fs::write(dir.join("a.txt"), "x").unwrap();
+    let mut app = app_in(&dir);
+
+    lsv::input::handle_key(&mut app, KeyEvent::from(KeyCode::Enter)).unwrap();
+    let out = fs::read_to_string(temp.path().join("out.log")).expect("ran");
+    assert_eq!(out, format!("opened {}", dir.join("a.txt").display()));
+    assert_eq!(
+      app.get_cwd_path(),
+      dir,
+      "Enter on a file should not change directory"
+    );
+  }
+
+  #[test]
+  fn failing_opener_shows_its_output()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    fs::write(temp.path().join("x.fail"), "x").unwrap();
+    let mut app = app_in(temp.path());
+
+    lsv::dispatch_action(&mut app, "cmd:open").unwrap();
+    let text = app.get_output_text();
+    assert!(text.contains("broken"));
+    assert!(text.contains("exit"), "{}", text);
+  }
+
+  #[test]
+  fn open_without_a_rule_reports_it()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    fs::write(temp.path().join("x.bin"), "x").unwrap();
+    let mut app = app_in(temp.path());
+
+    let before = app.recent_messages_len();
+    lsv::dispatch_action(&mut app, "cmd:open").unwrap();
+    assert!(app.get_output_text().is_empty());
+    assert_eq!(app.recent_messages_len(), before + 1);
+  }
+
+  #[test]
+  fn rules_without_cmd_are_rejected()
+  {
+    let code = "lsv.config({ openers = { { ext = 'txt' } } })";
+    assert!(lsv::config::load_config_from_code(code, None).is_err());
+  }
+
+  const LINE_OPENERS: &str = r#"
+lsv.config({
+  openers = {
+    { ext = { "txt" }, cmd = "echo \"$@\" > ../out.log", line = "+{line}" },
+    { ext = { "rs" }, cmd = "echo \"$@\" > ../out.log", line = "-g {file}:{line}" },
+  },
+})
+"#;
+
+  fn line_app_in(dir: &std::path::Path) -> lsv::app::App
+  {
+    let (cfg, _maps, _engine) =
+      lsv::config::load_config_from_code(LINE_OPENERS, None).expect("load");
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.set_cwd(dir);
+    app.finish_loading();
+    app
+  }
+
+  #[test]
+  fn open_at_line_fills_the_line_template()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let dir = temp.path().join("src");
+    fs::create_dir(&dir).unwrap();
+    fs::write(dir.join("a.txt"), "x").unwrap();
+    fs::write(dir.join("b.rs"), "x").unwrap();
+    let mut app = line_app_in(&dir);
+    let out = || fs::read_to_string(temp.path().join("out.log")).expect("ran");
+
+    lsv::dispatch_action(&mut app, "cmd:open_at_line 7").unwrap();
+    assert_eq!(out(), format!("+7 {}\n", dir.join("a.txt").display()));
+    app.select_index(1);
+    lsv::dispatch_action(&mut app, "cmd:open_at_line 12").unwrap();
+    assert_eq!(out(), format!("-g {}:12\n", dir.join("b.rs").display()));
+  }
+
+  #[test]
+  fn open_at_line_defaults_to_the_preview_line()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let dir = temp.path().join("src");
+    fs::create_dir(&dir).unwrap();
+    let body: String = (1..=50).map(|i| format!("line {}\n", i)).collect();
+    fs::write(dir.join("a.txt"), body).unwrap();
+    let mut app = line_app_in(&dir);
+    let mut term =
+      ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, 24))
+        .unwrap();
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+    lsv::dispatch_action(&mut app, "preview:scroll_down").unwrap();
+    lsv::dispatch_action(&mut app, "preview:scroll_down").unwrap();
+
+    lsv::input::handle_key(&mut app, KeyEvent::from(KeyCode::Char('g')))
+      .unwrap();
+    lsv::input::handle_key(&mut app, KeyEvent::from(KeyCode::Char('l')))
+      .unwrap();
+    let out = fs::read_to_string(temp.path().join("out.log")).expect("ran");
+    assert_eq!(out, format!("+3 {}\n", dir.join("a.txt").display()));
+  }
+}
+
+mod interactive_tests
+{
+  use std::fs;
+
+  #[test]
+  fn run_interactive_passes_files_and_keeps_case()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    fs::write(temp.path().join("Notes.TXT"), "x").unwrap();
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+
+    lsv::dispatch_action(
+      &mut app,
+      r#"cmd:run_interactive printf 'Got %s' "$(basename "$1")" > Out.log"#,
+    )
+    .unwrap();
+    let out = fs::read_to_string(temp.path().join("Out.log")).expect("ran");
+    assert_eq!(out, "Got Notes.TXT");
+  }
+
+  #[test]
+  fn failing_command_is_reported()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+
+    let before = app.recent_messages_len();
+    lsv::dispatch_action(&mut app, "cmd:run_interactive exit 4").unwrap();
+    assert_eq!(app.recent_messages_len(), before + 1);
+  }
+
+  #[test]
+  fn lua_spawn_interactive_runs_after_the_action()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let code = r#"
+lsv.map_action("zi", "Touch", function(lsv, config)
+  lsv.spawn_interactive("touch spawned")
+end)
+"#;
+    let (cfg, _maps, engine_opt) =
+      lsv::config::load_config_from_code(code, None).expect("load");
+    let (engine, _prev, keys) = engine_opt.expect("engine present");
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.inject_lua_engine_for_tests(engine, keys);
+    app.set_cwd(temp.path());
+    app.finish_loading();
+
+    lsv::dispatch_action(&mut app, "run_lua:0").unwrap();
+    assert!(temp.path().join("spawned").exists());
+  }
+}
+
+mod yank_tests
+{
+  use std::fs;
+
+  #[test]
+  fn yank_contents_refuses_binary_files()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    fs::write(temp.path().join("blob.bin"), [0u8, 1, 2]).unwrap();
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+
+    let before = app.recent_messages_len();
+    lsv::dispatch_action(&mut app, "cmd:yank_contents").unwrap();
+    assert_eq!(app.recent_messages_len(), before + 1);
+  }
+
+  #[test]
+  fn yank_keys_are_mapped_by_default()
+  {
+    let app = lsv::app::App::new().expect("app new");
+    for (seq, action) in [
+      ("yp", "cmd:yank_path"),
+      ("yn", "cmd:yank_name"),
+      ("yd", "cmd:yank_dir"),
+      ("yc", "cmd:yank_contents"),
+    ]
+    {
+      assert_eq!(app.get_keymap_action(seq).as_deref(), Some(action));
+    }
+  }
+}
+
+mod select_pattern_tests
+{
+  use super::app_with;
+
+  fn selected_names(app: &lsv::app::App) -> Vec<String>
+  {
+    app
+      .get_selected_paths()
+      .iter()
+      .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+      .collect()
+  }
+
+  #[test]
+  fn glob_select_and_unselect()
+  {
+    let (_temp, mut app) = app_with(&["a.rs", "b.RS", "c.md", "main.rs"]);
+    lsv::dispatch_action(&mut app, "cmd:select *.rs").unwrap();
+    assert_eq!(selected_names(&app), ["a.rs", "b.RS", "main.rs"]);
+    lsv::dispatch_action(&mut app, "cmd:unselect ?.*").unwrap();
+    assert_eq!(selected_names(&app), ["main.rs"]);
+  }
+
+  #[test]
+  fn regex_select_keeps_pattern_case()
+  {
+    let (_temp, mut app) = app_with(&["IMG_1.png", "img_2.png", "IMG_x.png"]);
+    lsv::dispatch_action(&mut app, r"cmd:select! ^IMG_\d").unwrap();
+    assert_eq!(selected_names(&app), ["IMG_1.png"]);
+
+    let before = app.recent_messages_len();
+    lsv::dispatch_action(&mut app, "cmd:select! (oops").unwrap();
+    assert_eq!(app.recent_messages_len(), before + 1);
+    assert_eq!(selected_names(&app), ["IMG_1.png"]);
+  }
+
+  #[test]
+  fn regex_select_gives_up_on_runaway_patterns()
+  {
+    let long = format!("{}!", "a".repeat(30));
+    let (_temp, mut app) = app_with(&["aaa", &long]);
+    let before = app.recent_messages_len();
+    lsv::dispatch_action(&mut app, "cmd:select! ^(a+)+$").unwrap();
+    // Nothing is selected, not even the names that did match
+    assert!(selected_names(&app).is_empty());
+    assert_eq!(app.recent_messages_len(), before + 1);
+  }
+
+  #[test]
+  fn select_all_and_invert()
+  {
+    let (_temp, mut app) = app_with(&["a", "b", "c"]);
+    lsv::dispatch_action(&mut app, "cmd:select a").unwrap();
+    lsv::dispatch_action(&mut app, "cmd:invert_selection").unwrap();
+    assert_eq!(selected_names(&app), ["b", "c"]);
+    lsv::dispatch_action(&mut app, "cmd:select_all").unwrap();
+    assert_eq!(selected_names(&app), ["a", "b", "c"]);
+    lsv::dispatch_action(&mut app, "cmd:invert_selection").unwrap();
+    assert!(selected_names(&app).is_empty());
+  }
+}
+
+mod filter_tests
+{
+  use std::fs;
+
+  use ratatui::{
+    Terminal,
+    backend::TestBackend,
+  };
+
+  use super::app_with;
+
+  fn names(app: &lsv::app::App) -> Vec<String>
+  {
+    (0..10).filter_map(|i| app.get_current_entry_name(i)).collect()
+  }
+
+  #[test]
+  fn filter_hides_non_matching_entries_until_cleared()
+  {
+    let (temp, mut app) = app_with(&["main.rs", "lib.RS", "notes.md"]);
+    lsv::dispatch_action(&mut app, "cmd:filter .rs").unwrap();
+    assert_eq!(names(&app), ["lib.RS", "main.rs"]);
+
+    // New files are filtered on refresh too
+    fs::write(temp.path().join("todo.txt"), "x").unwrap();
+    fs::write(temp.path().join("build.rs"), "x").unwrap();
+    lsv::dispatch_action(&mut app, "sort:name").unwrap();
+    assert_eq!(names(&app), ["build.rs", "lib.RS", "main.rs"]);
+
+    lsv::dispatch_action(&mut app, "cmd:filter_clear").unwrap();
+    assert_eq!(names(&app).len(), 5);
+  }
+
+  #[test]
+  fn filter_is_kept_per_directory()
+  {
+    let (temp, mut app) = app_with(&["a.md", "b.txt"]);
+    fs::create_dir(temp.path().join("sub")).unwrap();
+    fs::write(temp.path().join("sub").join("c.txt"), "x").unwrap();
+    lsv::dispatch_action(&mut app, "cmd:filter *.md").unwrap();
+    assert_eq!(names(&app), ["a.md"]);
+
+    app.set_cwd(&temp.path().join("sub"));
+    assert_eq!(names(&app), ["c.txt"]);
+    app.set_cwd(temp.path());
+    assert_eq!(names(&app), ["a.md"]);
+  }
+
+  #[test]
+  fn header_shows_active_filter()
+  {
+    let (_temp, mut app) = app_with(&["a.md"]);
+    lsv::dispatch_action(&mut app, "cmd:filter md").unwrap();
+    let mut term = Terminal::new(TestBackend::new(120, 6)).unwrap();
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+    let header: String = term.backend().buffer().content()[..120]
+      .iter()
+      .map(|c| c.symbol().to_string())
+      .collect();
+    assert!(header.contains("[filter: md]"), "{}", header);
+  }
+
+  #[test]
+  fn filter_prompt_prefills_the_command_line()
+  {
+    let (_temp, mut app) = app_with(&["a.md"]);
+    lsv::dispatch_action(&mut app, "cmd:filter md").unwrap();
+    lsv::dispatch_action(&mut app, "cmd:filter_prompt").unwrap();
+    assert_eq!(app.command_input(), Some("filter md"));
+  }
+}
+
+mod fuzzy_search_tests
+{
+  use crossterm::event::{
+    KeyCode,
+    KeyEvent,
+  };
+  use ratatui::{
+    Terminal,
+    backend::TestBackend,
+    style::Modifier,
+  };
+
+  use super::app_with;
+
+  fn type_search(
+    app: &mut lsv::app::App,
+    query: &str,
+  )
+  {
+    lsv::input::handle_key(app, KeyEvent::from(KeyCode::Char('/'))).unwrap();
+    for ch in query.chars()
+    {
+      lsv::input::handle_key(app, KeyEvent::from(KeyCode::Char(ch))).unwrap();
+    }
+  }
+
+  #[test]
+  fn typing_jumps_to_the_best_match_and_n_steps_by_rank()
+  {
+    let (_temp, mut app) = app_with(&["a_b_c.txt", "abc_report.txt", "zzz"]);
+    type_search(&mut app, "abc");
+    // The consecutive match ranks above the earlier, scattered one
+    assert_eq!(app.get_list_selected_index(), Some(1));
+    lsv::input::handle_key(&mut app, KeyEvent::from(KeyCode::Enter)).unwrap();
+
+    lsv::dispatch_action(&mut app, "cmd:next").unwrap();
+    assert_eq!(app.get_list_selected_index(), Some(0));
+    lsv::dispatch_action(&mut app, "cmd:next").unwrap();
+    assert_eq!(app.get_list_selected_index(), Some(1));
+    lsv::input::handle_key(&mut app, KeyEvent::from(KeyCode::Char('N')))
+      .unwrap();
+    assert_eq!(app.get_list_selected_index(), Some(0));
+  }
+
+  #[test]
+  fn matched_characters_are_highlighted()
+  {
+    let (_temp, mut app) = app_with(&["a_b_c.txt"]);
+    type_search(&mut app, "abc");
+    let mut term = Terminal::new(TestBackend::new(90, 6)).unwrap();
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+    let buf = term.backend().buffer();
+    let row: Vec<_> = (0..90).map(|x| &buf[(x, 2)]).collect();
+    let text: String = row.iter().map(|c| c.symbol()).collect();
+    let start = text.find("a_b_c").expect("row drawn");
+    let start = text[..start].chars().count();
+    let bold: Vec<bool> = (start..start + 5)
+      .map(|x| row[x].modifier.contains(Modifier::UNDERLINED))
+      .collect();
+    assert_eq!(bold, [true, false, true, false, true], "{}", text);
+  }
+}
+
+mod cursor_memory_tests
+{
+  use std::fs;
+
+  use crossterm::event::KeyCode;
+
+  use super::press;
+
+  fn current_name(app: &lsv::app::App) -> Option<String>
+  {
+    app.get_list_selected_index().and_then(|i| app.get_current_entry_name(i))
+  }
+
+  #[test]
+  fn entering_a_directory_again_restores_its_cursor()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let sub = temp.path().join("sub");
+    fs::create_dir(&sub).unwrap();
+    for n in ["a.txt", "b.txt", "c.txt"]
+    {
+      fs::write(sub.join(n), "x").unwrap();
+    }
+    fs::write(temp.path().join("z.txt"), "x").unwrap();
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    assert_eq!(current_name(&app).as_deref(), Some("sub"));
+
+    press(&mut app, KeyCode::Enter);
+    app.finish_loading();
+    assert_eq!(current_name(&app).as_deref(), Some("a.txt"));
+    app.select_index(2);
+    press(&mut app, KeyCode::Left);
+    app.finish_loading();
+    assert_eq!(app.get_cwd_path(), temp.path());
+    assert_eq!(current_name(&app).as_deref(), Some("sub"));
+
+    press(&mut app, KeyCode::Enter);
+    app.finish_loading();
+    assert_eq!(current_name(&app).as_deref(), Some("c.txt"));
+  }
+
+  #[test]
+  fn going_up_focuses_the_directory_just_left()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    for d in ["one", "two"]
+    {
+      fs::create_dir(temp.path().join(d)).unwrap();
+    }
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    // The parent remembers "one", but leaving "two" focuses "two"
+    app.set_cwd(&temp.path().join("two"));
+    press(&mut app, KeyCode::Left);
+    app.finish_loading();
+    assert_eq!(current_name(&app).as_deref(), Some("two"));
+  }
+}
+
+mod jump_tests
+{
+  use std::fs;
+
+  #[test]
+  fn z_jumps_to_a_visited_directory()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let alpha = temp.path().join("src").join("lsvzalpha");
+    let beta = temp.path().join("lsvzbeta");
+    fs::create_dir_all(&alpha).unwrap();
+    fs::create_dir_all(&beta).unwrap();
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(&alpha);
+    app.set_cwd(&beta);
+    app.set_cwd(temp.path());
+    app.finish_loading();
+
+    lsv::dispatch_action(&mut app, "cmd:z src lsvzalp").unwrap();
+    assert_eq!(app.get_cwd_path(), alpha.as_path());
+
+    lsv::dispatch_action(&mut app, "cmd:z lsvznothing").unwrap();
+    assert_eq!(app.get_cwd_path(), alpha.as_path());
+    assert!(app.recent_messages_len() > 0);
+  }
+
+  #[test]
+  fn z_without_a_query_lists_visited_directories()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let dir = temp.path().join("lsvzgamma");
+    fs::create_dir_all(&dir).unwrap();
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(&dir);
+    app.set_cwd(temp.path());
+    app.finish_loading();
+
+    lsv::dispatch_action(&mut app, "cmd:z").unwrap();
+    app.set_finder_query("lsvzgam");
+    assert_eq!(app.finder_results(), [dir.to_string_lossy().to_string()]);
+    app.finder_accept();
+    assert_eq!(app.get_cwd_path(), dir.as_path());
+  }
+}
+
+mod file_info_tests
+{
+  use std::fs;
+
+  #[test]
+  fn file_info_shows_size_and_image_metadata()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+    png.extend(64u32.to_be_bytes());
+    png.extend(48u32.to_be_bytes());
+    fs::write(temp.path().join("a.png"), &png).unwrap();
+    fs::write(temp.path().join("b.txt"), "hello").unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    assert_eq!(app.get_current_entry_name(0).as_deref(), Some("a.png"));
+
+    lsv::dispatch_action(&mut app, "cmd:show_file_info").unwrap();
+    assert!(app.get_show_output());
+    assert_eq!(app.get_output_title(), "Info: a.png");
+    let text = app.get_output_text();
+    assert!(text.contains(&format!("({} bytes)", png.len())), "{}", text);
+    assert!(text.contains("Dimensions:") && text.contains("64x48"), "{}", text);
+
+    app.select_index(1);
+    lsv::dispatch_action(&mut app, "cmd:show_file_info").unwrap();
+    let text = app.get_output_text();
+    assert!(text.contains("(5 bytes)"), "{}", text);
+    assert!(!text.contains("Dimensions:"));
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn file_info_shows_stat_details()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let target = temp.path().join("b.json");
+    fs::write(&target, "{}").unwrap();
+    std::os::unix::fs::symlink(&target, temp.path().join("a-link")).unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    assert_eq!(app.get_current_entry_name(0).as_deref(), Some("a-link"));
+
+    lsv::dispatch_action(&mut app, "cmd:file_info").unwrap();
+    let text = app.get_output_text();
+    let line = |key: &str| {
+      text
+        .lines()
+        .find(|l| l.starts_with(key))
+        .map(|l| l[key.len()..].trim().to_string())
+        .unwrap_or_default()
+    };
+    assert_eq!(
+      line("Type:"),
+      format!("symlink to file -> {}", target.display())
+    );
+    assert_eq!(line("MIME:"), "application/json");
+    assert_eq!(line("Size:"), "2 B (2 bytes)");
+    assert!(line("Mode:").ends_with("(rwxrwxrwx)"), "{}", text);
+    for key in ["Owner:", "Group:", "Inode:", "Links:", "Device:"]
+    {
+      assert!(!line(key).is_empty(), "{} missing in {}", key, text);
+    }
+    for key in ["Accessed:", "Modified:", "Changed:"]
+    {
+      assert!(!line(key).is_empty(), "{} missing in {}", key, text);
+    }
+  }
+}
+
+#[cfg(unix)]
+mod permissions_tests
+{
+  use std::{
+    fs,
+    os::unix::fs::PermissionsExt,
+    path::Path,
+  };
+
+  use crossterm::event::{
+    KeyCode,
+    KeyEvent,
+  };
+
+  fn mode(p: &Path) -> u32
+  {
+    fs::metadata(p).unwrap().permissions().mode() & 0o7777
+  }
+
+  fn set_mode(
+    p: &Path,
+    m: u32,
+  )
+  {
+    fs::set_permissions(p, fs::Permissions::from_mode(m)).unwrap();
+  }
+
+  #[test]
+  fn prompt_starts_from_the_current_mode()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let file = temp.path().join("a.sh");
+    fs::write(&file, "echo").unwrap();
+    set_mode(&file, 0o644);
+
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    lsv::input::handle_key(&mut app, KeyEvent::from(KeyCode::Char('=')))
+      .unwrap();
+    // Replace the pre-filled "644"
+    for _ in 0..3
+    {
+      lsv::input::handle_key(&mut app, KeyEvent::from(KeyCode::Backspace))
+        .unwrap();
+    }
+    for ch in "u+x".chars()
+    {
+      lsv::input::handle_key(&mut app, KeyEvent::from(KeyCode::Char(ch)))
+        .unwrap();
+    }
+    lsv::input::handle_key(&mut app, KeyEvent::from(KeyCode::Enter)).unwrap();
+    assert_eq!(mode(&file), 0o744);
+  }
+
+  #[test]
+  fn selected_directories_change_recursively()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let dir = temp.path().join("d");
+    fs::create_dir(&dir).unwrap();
+    let inner = dir.join("f.txt");
+    fs::write(&inner, "f").unwrap();
+    set_mode(&inner, 0o666);
+    let other = temp.path().join("other.txt");
+    fs::write(&other, "o").unwrap();
+    set_mode(&other, 0o666);
+
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    assert_eq!(app.get_current_entry_name(0).as_deref(), Some("d"));
+    lsv::dispatch_action(&mut app, "cmd:set_permissions -R go-w").unwrap();
+    // Recursive changes run as a job
+    assert!(app.has_active_jobs());
+    app.finish_jobs();
+    assert_eq!(mode(&inner), 0o644);
+    assert_eq!(mode(&other), 0o666);
+
+    let before = app.recent_messages_len();
+    lsv::dispatch_action(&mut app, "cmd:set_permissions u+q").unwrap();
+    assert!(app.recent_messages_len() > before);
+    assert_eq!(mode(&inner), 0o644);
+  }
+}
+
+#[cfg(unix)]
+mod paste_link_tests
+{
+  use std::{
+    fs,
+    path::PathBuf,
+  };
+
+  fn copy_both(src: &std::path::Path) -> lsv::app::App
+  {
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(src);
+    app.finish_loading();
+    lsv::dispatch_action(&mut app, "cmd:select_all; clipboard:copy").unwrap();
+    app
+  }
+
+  #[test]
+  fn symlinks_are_absolute_or_relative()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let src = temp.path().join("src");
+    let dst = temp.path().join("dst");
+    fs::create_dir_all(src.join("dir")).unwrap();
+    fs::create_dir_all(&dst).unwrap();
+    fs::write(src.join("a.txt"), "A").unwrap();
+
+    let mut app = copy_both(&src);
+    app.set_cwd(&dst);
+    lsv::dispatch_action(&mut app, "clipboard:paste_symlink").unwrap();
+    assert_eq!(fs::read_link(dst.join("a.txt")).unwrap(), src.join("a.txt"));
+    assert_eq!(fs::read_link(dst.join("dir")).unwrap(), src.join("dir"));
+    assert_eq!(fs::read_to_string(dst.join("a.txt")).unwrap(), "A");
+
+    let rel = temp.path().join("rel");
+    fs::create_dir_all(&rel).unwrap();
+    let mut app = copy_both(&src);
+    app.set_cwd(&rel);
+    lsv::dispatch_action(&mut app, "cmd:paste_symlink relative").unwrap();
+    assert_eq!(
+      fs::read_link(rel.join("a.txt")).unwrap(),
+      PathBuf::from("../src/a.txt")
+    );
+    assert_eq!(fs::read_to_string(rel.join("a.txt")).unwrap(), "A");
+  }
+
+  #[test]
+  fn relative_links_can_be_the_default()
+  {
+    let code = r#"lsv.config({ ui = { symlinks = "relative" } })"#;
+    let (cfg, _maps, _engine) =
+      lsv::config::load_config_from_code(code, None).expect("load config");
+    let temp = tempfile::tempdir().expect("tempdir");
+    let src = temp.path().join("src");
+    fs::create_dir_all(&src).unwrap();
+    fs::write(src.join("a.txt"), "A").unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.set_cwd(&src);
+    app.finish_loading();
+    lsv::dispatch_action(&mut app, "cmd:select_all; clipboard:copy").unwrap();
+    app.set_cwd(temp.path());
+    lsv::dispatch_action(&mut app, "clipboard:paste_symlink").unwrap();
+    assert_eq!(
+      fs::read_link(temp.path().join("a.txt")).unwrap(),
+      PathBuf::from("src/a.txt")
+    );
+
+    let bad = r#"lsv.config({ ui = { symlinks = "sideways" } })"#;
+    assert!(lsv::config::load_config_from_code(bad, None).is_err());
+  }
+
+  #[test]
+  fn hard_links_share_the_file_and_skip_directories()
+  {
+    use std::os::unix::fs::MetadataExt;
+
+    let temp = tempfile::tempdir().expect("tempdir");
+    let src = temp.path().join("src");
+    let dst = temp.path().join("dst");
+    fs::create_dir_all(src.join("dir")).unwrap();
+    fs::create_dir_all(&dst).unwrap();
+    fs::write(src.join("a.txt"), "A").unwrap();
+
+    let mut app = copy_both(&src);
+    app.set_cwd(&dst);
+    lsv::dispatch_action(&mut app, "clipboard:paste_hardlink").unwrap();
+    let (orig, link) = (src.join("a.txt"), dst.join("a.txt"));
+    assert!(!fs::symlink_metadata(&link).unwrap().is_symlink());
+    assert_eq!(
+      fs::metadata(&orig).unwrap().ino(),
+      fs::metadata(&link).unwrap().ino()
+    );
+    assert!(!dst.join("dir").exists());
+  }
+
+  #[test]
+  fn moved_items_are_not_linked()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    fs::write(temp.path().join("a.txt"), "A").unwrap();
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    lsv::dispatch_action(&mut app, "cmd:select_all; clipboard:move").unwrap();
+    let sub = temp.path().join("sub");
+    fs::create_dir(&sub).unwrap();
+    app.set_cwd(&sub);
+    lsv::dispatch_action(&mut app, "clipboard:paste_symlink").unwrap();
+    assert!(fs::read_dir(&sub).unwrap().next().is_none());
+  }
+}
+
+mod paste_conflict_tests
+{
+  use std::{
+    fs,
+    path::Path,
+  };
+
+  use crossterm::event::{
+    KeyCode,
+    KeyEvent,
+  };
+
+  use super::press;
+
+  /// Copy every entry of `src` and paste into `dst`.
+  fn copy_all_into(
+    app: &mut lsv::app::App,
+    src: &Path,
+    dst: &Path,
+  )
+  {
+    app.set_cwd(src);
+    app.finish_loading();
+    lsv::actions::dispatch_action(app, "cmd:select_all").unwrap();
+    lsv::actions::dispatch_action(app, "clipboard:copy").unwrap();
+    app.set_cwd(dst);
+    app.finish_loading();
+    lsv::actions::dispatch_action(app, "clipboard:paste").unwrap();
+  }
+
+  fn dirs(root: &Path) -> (std::path::PathBuf, std::path::PathBuf)
+  {
+    let src = root.join("src");
+    let dst = root.join("dst");
+    fs::create_dir_all(&src).unwrap();
+    fs::create_dir_all(&dst).unwrap();
+    (src, dst)
+  }
+
+  #[test]
+  fn rename_pastes_beside_the_existing_file()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let (src, dst) = dirs(temp.path());
+    fs::write(src.join("a.txt"), b"new").unwrap();
+    fs::write(dst.join("a.txt"), b"old").unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
+    copy_all_into(&mut app, &src, &dst);
+    // Nothing happens until the clash is answered
+    assert!(!app.has_active_jobs());
+    press(&mut app, KeyCode::Char('r'));
+    app.finish_jobs();
+    assert_eq!(fs::read(dst.join("a.txt")).unwrap(), b"old");
+    assert_eq!(fs::read(dst.join("a_1.txt")).unwrap(), b"new");
+  }
+
+  #[test]
+  fn rename_skips_names_taken_by_the_same_paste()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let (src, dst) = dirs(temp.path());
+    fs::write(src.join("a.txt"), b"new").unwrap();
+    fs::write(src.join("a_1.txt"), b"other").unwrap();
+    fs::write(dst.join("a.txt"), b"old").unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
+    copy_all_into(&mut app, &src, &dst);
+    press(&mut app, KeyCode::Char('r'));
+    app.finish_jobs();
+    assert_eq!(fs::read(dst.join("a.txt")).unwrap(), b"old");
+    assert_eq!(fs::read(dst.join("a_1.txt")).unwrap(), b"other");
+    assert_eq!(fs::read(dst.join("a_2.txt")).unwrap(), b"new");
+  }
+
+  #[test]
+  fn overwrite_all_replaces_every_clash()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let (src, dst) = dirs(temp.path());
+    fs::write(src.join("a.txt"), b"new a").unwrap();
+    fs::create_dir(src.join("d")).unwrap();
+    fs::write(src.join("d").join("f.txt"), b"new f").unwrap();
+    fs::write(src.join("free.txt"), b"free").unwrap();
+    fs::write(dst.join("a.txt"), b"old a").unwrap();
+    fs::create_dir(dst.join("d")).unwrap();
+    fs::write(dst.join("d").join("stale.txt"), b"").unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
+    copy_all_into(&mut app, &src, &dst);
+    press(&mut app, KeyCode::Char('O'));
+    // Replacing more than one item asks once more, listing them
+    let replaced: Vec<String> = [dst.join("a.txt"), dst.join("d")]
+      .iter()
+      .map(|p| p.display().to_string())
+      .collect();
+    assert_eq!(app.confirm_details(), Some(&replaced[..]));
+    press(&mut app, KeyCode::Char('y'));
+    app.finish_jobs();
+    assert_eq!(fs::read(dst.join("a.txt")).unwrap(), b"new a");
+    assert_eq!(fs::read(dst.join("d").join("f.txt")).unwrap(), b"new f");
+    assert!(!dst.join("d").join("stale.txt").exists());
+    assert_eq!(fs::read(dst.join("free.txt")).unwrap(), b"free");
+  }
+
+  #[test]
+  fn skip_and_cancel_leave_existing_files_alone()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let (src, dst) = dirs(temp.path());
+    fs::write(src.join("a.txt"), b"new a").unwrap();
+    fs::write(src.join("b.txt"), b"new b").unwrap();
+    fs::write(src.join("free.txt"), b"free").unwrap();
+    fs::write(dst.join("a.txt"), b"old a").unwrap();
+    fs::write(dst.join("b.txt"), b"old b").unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
+    copy_all_into(&mut app, &src, &dst);
+    press(&mut app, KeyCode::Char('s'));
+    press(&mut app, KeyCode::Char('o'));
+    app.finish_jobs();
+    assert_eq!(fs::read(dst.join("a.txt")).unwrap(), b"old a");
+    assert_eq!(fs::read(dst.join("b.txt")).unwrap(), b"new b");
+    assert!(dst.join("free.txt").exists());
+
+    // Esc drops the whole paste, items without a clash included
+    fs::remove_file(dst.join("free.txt")).unwrap();
+    copy_all_into(&mut app, &src, &dst);
+    lsv::input::handle_key(&mut app, KeyEvent::from(KeyCode::Esc)).unwrap();
+    app.finish_jobs();
+    assert!(!dst.join("free.txt").exists());
+    assert_eq!(fs::read(dst.join("a.txt")).unwrap(), b"old a");
+  }
+
+  #[test]
+  fn pasting_onto_itself_is_never_overwritten()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let (src, _) = dirs(temp.path());
+    fs::write(src.join("a.txt"), b"keep").unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
+    copy_all_into(&mut app, &src, &src);
+    press(&mut app, KeyCode::Char('o'));
+    app.finish_jobs();
+    assert_eq!(fs::read(src.join("a.txt")).unwrap(), b"keep");
+
+    copy_all_into(&mut app, &src, &src);
+    press(&mut app, KeyCode::Char('r'));
+    app.finish_jobs();
+    assert_eq!(fs::read(src.join("a_1.txt")).unwrap(), b"keep");
+  }
+}
+
+mod checksum_tests
+{
+  use std::fs;
+
+  #[test]
+  fn checksums_of_selected_files_are_listed()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    fs::write(temp.path().join("a.txt"), b"abc").unwrap();
+    fs::write(temp.path().join("b.txt"), b"").unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    lsv::actions::dispatch_action(&mut app, "cmd:checksum md5").unwrap();
+    app.finish_checksums();
+    assert_eq!(app.get_output_title(), "md5sum");
+    assert_eq!(
+      app.get_output_text(),
+      "900150983cd24fb0d6963f7d28e17f72  a.txt"
+    );
+
+    lsv::actions::dispatch_action(&mut app, "cmd:select_all").unwrap();
+    lsv::actions::dispatch_action(&mut app, "cmd:checksum").unwrap();
+    app.finish_checksums();
+    assert_eq!(app.get_output_title(), "sha256sum");
+    let text = app.get_output_text();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(
+      lines[0].starts_with("ba7816bf8f01cfea") && lines[0].ends_with("  a.txt")
+    );
+    assert!(
+      lines[1].starts_with("e3b0c44298fc1c14") && lines[1].ends_with("  b.txt")
+    );
+  }
+
+  #[test]
+  fn verify_reports_each_listed_file()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let sub = temp.path().join("sub");
+    fs::create_dir(&sub).unwrap();
+    fs::write(sub.join("good.txt"), b"abc").unwrap();
+    fs::write(sub.join("bad.txt"), b"changed").unwrap();
+    fs::write(
+      sub.join("SUMS.sha256"),
+      "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad  good.txt\n\
+       ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad  bad.txt\n\
+       ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad  gone.txt\n",
+    )
+    .unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    // Listed names are relative to the manifest, not the current directory
+    lsv::actions::dispatch_action(&mut app, "cmd:verify sub/SUMS.sha256")
+      .unwrap();
+    app.finish_checksums();
+    assert_eq!(app.get_output_title(), "Verify: SUMS.sha256");
+    let text = app.get_output_text();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], "good.txt: OK");
+    assert_eq!(lines[1], "bad.txt: FAILED");
+    assert!(lines[2].starts_with("gone.txt: FAILED open or read"));
+  }
+}
+
+mod compress_tests
+{
+  use std::fs;
+
+  use crossterm::event::{
+    KeyCode,
+    KeyEvent,
+  };
+  use lsv::core::archive::{
+    self,
+    ArchiveKind,
+  };
+
+  fn names(path: &std::path::Path) -> Vec<String>
+  {
+    let listing = archive::list(path, ArchiveKind::Tar, 100).unwrap();
+    listing.entries.into_iter().map(|e| e.name).collect()
+  }
+
+  #[test]
+  fn selected_entries_pack_into_a_tarball_job()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let dir = temp.path();
+    fs::create_dir(dir.join("d")).unwrap();
+    fs::write(dir.join("d").join("inner.txt"), b"in").unwrap();
+    fs::write(dir.join("a.txt"), b"a").unwrap();
+    fs::write(dir.join("skip.txt"), b"s").unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(dir);
+    app.finish_loading();
+    // Entries sort directories first: d, a.txt, skip.txt
+    lsv::actions::dispatch_action(&mut app, "cmd:select_toggle").unwrap();
+    app.select_index(1);
+    lsv::actions::dispatch_action(&mut app, "cmd:select_toggle").unwrap();
+    lsv::actions::dispatch_action(&mut app, "cmd:compress Out.tar").unwrap();
+    app.finish_jobs();
+    let got = names(&dir.join("Out.tar"));
+    assert_eq!(got, vec!["a.txt", "d/", "d/inner.txt"]);
+  }
+
+  #[test]
+  fn prompt_suggests_a_name_and_command_filters_the_tar()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let dir = temp.path();
+    fs::write(dir.join("notes.txt"), b"n").unwrap();
+
+    // A stand-in compressor, so the result is still a plain tar
+    let code = r#"lsv.config({ ui = { compress = { gzip = "cat" } } })"#;
+    let (cfg, _maps, _engine) =
+      lsv::config::load_config_from_code(code, None).expect("load config");
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.set_cwd(dir);
+    app.finish_loading();
+    lsv::actions::dispatch_action(&mut app, "cmd:compress").unwrap();
+    lsv::input::handle_key(&mut app, KeyEvent::from(KeyCode::Enter)).unwrap();
+    app.finish_jobs();
+    assert_eq!(names(&dir.join("notes.tar.gz")), vec!["notes.txt"]);
+
+    // Existing archives are not replaced
+    let before = app.recent_messages_len();
+    lsv::actions::dispatch_action(&mut app, "cmd:compress notes.tar.gz")
+      .unwrap();
+    assert!(app.recent_messages_len() > before);
+    assert!(!app.has_active_jobs());
+  }
+
+  #[test]
+  fn failed_commands_leave_no_archive_behind()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let dir = temp.path();
+    fs::write(dir.join("a.txt"), b"a").unwrap();
+
+    let code = r#"
+lsv.config({ ui = { compress = { zstd = "cat >/dev/null; exit 3" } } })
+"#;
+    let (cfg, _maps, _engine) =
+      lsv::config::load_config_from_code(code, None).expect("load config");
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.set_cwd(dir);
+    app.finish_loading();
+    lsv::actions::dispatch_action(&mut app, "cmd:compress a.tar.zst").unwrap();
+    app.finish_jobs();
+    assert!(!dir.join("a.tar.zst").exists());
+
+    let bad = r#"lsv.config({ ui = { compress = { zip = true } } })"#;
+    assert!(lsv::config::load_config_from_code(bad, None).is_err());
+  }
+}
+
+mod extract_tests
+{
+  use std::{
+    fs,
+    path::Path,
+  };
+
+  /// Pack `items` (relative to `base`) into the tarball `out`.
+  fn tar(
+    base: &Path,
+    items: &[&str],
+    out: &Path,
+  )
+  {
+    let items: Vec<_> = items.iter().map(|i| base.join(i)).collect();
+    let mut file = fs::File::create(out).unwrap();
+    lsv::core::pack::write_tar(&mut file, base, &items, out, &mut |_| Ok(()))
+      .unwrap();
+  }
+
+  fn extract(dir: &Path) -> lsv::app::App
+  {
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(dir);
+    app.finish_loading();
+    lsv::actions::dispatch_action(&mut app, "cmd:extract").unwrap();
+    app.finish_jobs();
+    app.finish_loading();
+    app
+  }
+
+  fn highlighted(app: &lsv::app::App) -> Option<String>
+  {
+    app.get_list_selected_index().and_then(|i| app.get_current_entry_name(i))
+  }
+
+  #[test]
+  fn loose_members_go_into_a_folder_named_after_the_archive()
+  {
+    let src = tempfile::tempdir().expect("tempdir");
+    fs::write(src.path().join("a.txt"), b"a").unwrap();
+    fs::write(src.path().join("b.txt"), b"b").unwrap();
+    let temp = tempfile::tempdir().expect("tempdir");
+    let dir = temp.path();
+    tar(src.path(), &["a.txt", "b.txt"], &dir.join("bundle.tar"));
+
+    let app = extract(dir);
+    assert_eq!(fs::read(dir.join("bundle").join("a.txt")).unwrap(), b"a");
+    assert_eq!(fs::read(dir.join("bundle").join("b.txt")).unwrap(), b"b");
+    assert!(!dir.join("a.txt").exists());
+    assert_eq!(highlighted(&app).as_deref(), Some("bundle"));
+
+    // Taken names get a suffix instead of merging into the existing folder
+    let mut app = app;
+    app.select_index(1);
+    lsv::actions::dispatch_action(&mut app, "cmd:extract").unwrap();
+    app.finish_jobs();
+    assert!(dir.join("bundle_1").join("a.txt").exists());
+  }
+
+  #[test]
+  fn a_single_top_level_folder_is_extracted_as_is()
+  {
+    let src = tempfile::tempdir().expect("tempdir");
+    fs::create_dir(src.path().join("site-1.0")).unwrap();
+    fs::write(src.path().join("site-1.0").join("index.html"), b"hi").unwrap();
+    let temp = tempfile::tempdir().expect("tempdir");
+    let dir = temp.path();
+    tar(src.path(), &["site-1.0"], &dir.join("site.tar"));
+
+    let app = extract(dir);
+    assert_eq!(
+      fs::read(dir.join("site-1.0").join("index.html")).unwrap(),
+      b"hi"
+    );
+    assert!(!dir.join("site").exists());
+    assert_eq!(highlighted(&app).as_deref(), Some("site-1.0"));
+  }
+
+  #[test]
+  fn plain_files_are_refused()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    fs::write(temp.path().join("notes.txt"), b"n").unwrap();
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    let before = app.recent_messages_len();
+    lsv::actions::dispatch_action(&mut app, "cmd:extract").unwrap();
+    assert!(app.recent_messages_len() > before);
+    assert!(!app.has_active_jobs());
+  }
+}
+
+#[cfg(target_os = "linux")]
+mod mounts_tests
+{
+  use crossterm::event::{
+    KeyCode,
+    KeyEvent,
+    KeyModifiers,
+  };
+
+  #[test]
+  fn enter_jumps_to_the_mount_holding_the_current_directory()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let dir = temp.path().canonicalize().unwrap();
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(&dir);
+    app.finish_loading();
+    lsv::actions::dispatch_action(&mut app, "cmd:mounts").unwrap();
+    lsv::input::handle_key(
+      &mut app,
+      KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE),
+    )
+    .unwrap();
+    app.finish_loading();
+    let mounts = lsv::core::mounts::list();
+    let expected = lsv::core::mounts::containing(&mounts, &dir)
+      .map(|i| mounts[i].path.clone());
+    assert_eq!(Some(app.get_cwd_path()), expected);
+    assert!(dir.starts_with(app.get_cwd_path()));
+  }
+
+  #[test]
+  fn drive_list_shows_mount_points_and_refuses_changes()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let dir = temp.path().canonicalize().unwrap();
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(&dir);
+    app.finish_loading();
+    lsv::actions::dispatch_action(&mut app, "cmd:drives").unwrap();
+    app.finish_loading();
+    assert!(app.in_drives());
+    let names: Vec<String> =
+      (0..).map_while(|i| app.get_current_entry_name(i)).collect();
+    let mounts: Vec<String> = lsv::core::mounts::list()
+      .iter()
+      .map(|m| m.path.to_string_lossy().into_owned())
+      .collect();
+    assert_eq!(names, mounts);
+    // Nothing above the drive list
+    assert_eq!(lsv::core::vfs::parent(&app.get_cwd_path()), None);
+    lsv::actions::dispatch_action(&mut app, "cmd:delete").unwrap();
+    let last = app.messages().last().map(|m| m.text.clone());
+    assert_eq!(last.as_deref(), Some("Delete: the drive list is read-only"));
+    assert!(app.confirm_question().is_none());
+  }
+}
+
+mod finder_tests
+{
+  use std::{
+    fs,
+    thread::sleep,
+    time::Duration,
+  };
+
+  #[test]
+  fn finder_indexes_tree_and_jumps_to_selection()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let root = temp.path();
+    fs::create_dir_all(root.join("deep").join("er")).unwrap();
+    fs::create_dir_all(root.join(".hidden")).unwrap();
+    fs::write(root.join("top.txt"), b"").unwrap();
+    fs::write(root.join("deep").join("er").join("target.rs"), b"").unwrap();
+    fs::write(root.join(".hidden").join("target_secret.rs"), b"").unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(root);
+    lsv::actions::dispatch_action(&mut app, "cmd:find_files").unwrap();
+    for _ in 0..200
+    {
+      app.poll_finder();
+      if !app.is_finder_indexing()
+      {
+        break;
+      }
+      sleep(Duration::from_millis(10));
+    }
+    app.set_finder_query("targ");
+    assert_eq!(app.finder_results(), vec!["deep/er/target.rs".to_string()]);
+
+    app.finder_accept();
+    assert_eq!(app.get_cwd_path(), root.join("deep").join("er"));
+    let idx = app.get_list_selected_index().unwrap();
+    assert_eq!(app.get_current_entry_name(idx).as_deref(), Some("target.rs"));
+  }
+}
+
+mod grep_tests
+{
+  use crossterm::event::{
+    KeyCode,
+    KeyEvent,
+    KeyModifiers,
+  };
+  use std::{
+    fs,
+    thread::sleep,
+    time::Duration,
+  };
+
+  #[test]
+  fn grep_streams_matches_and_jumps_to_file()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let root = temp.path();
+    fs::create_dir_all(root.join("sub")).unwrap();
+    fs::write(root.join("a.txt"), "nothing here\n").unwrap();
+    fs::write(root.join("sub").join("b.txt"), "one\nneedle two\n").unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(root);
+    lsv::actions::dispatch_action(&mut app, "cmd:grep needle").unwrap();
+    for _ in 0..200
+    {
+      app.poll_grep();
+      if !app.is_grep_running()
+      {
+        break;
+      }
+      sleep(Duration::from_millis(10));
+    }
+    assert_eq!(app.grep_match_count(), 1);
+
+    let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+    lsv::input::handle_key(&mut app, enter).unwrap();
+    assert_eq!(app.get_cwd_path(), root.join("sub"));
+    let idx = app.get_list_selected_index().unwrap();
+    assert_eq!(app.get_current_entry_name(idx).as_deref(), Some("b.txt"));
+  }
+
+  #[test]
+  fn grep_match_opens_at_its_line()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let root = temp.path().join("work");
+    fs::create_dir_all(&root).unwrap();
+    fs::write(root.join("b.txt"), "one\ntwo\nneedle three\n").unwrap();
+    let code = r#"lsv.config({ openers = {
+      { ext = "txt", cmd = "echo \"$@\" > ../out.log", line = "+{line}" },
+    } })"#;
+    let (cfg, _maps, _engine) =
+      lsv::config::load_config_from_code(code, None).expect("load");
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.set_cwd(&root);
+    lsv::actions::dispatch_action(&mut app, "cmd:grep needle").unwrap();
+    for _ in 0..200
+    {
+      app.poll_grep();
+      if !app.is_grep_running()
+      {
+        break;
+      }
+      sleep(Duration::from_millis(10));
+    }
+    assert_eq!(app.grep_match_count(), 1);
+
+    let o = KeyEvent::new(KeyCode::Char('o'), KeyModifiers::NONE);
+    lsv::input::handle_key(&mut app, o).unwrap();
+    let out = fs::read_to_string(temp.path().join("out.log")).expect("ran");
+    assert_eq!(out, format!("+3 {}\n", root.join("b.txt").display()));
+    // The results stay open for the next match
+    assert_eq!(app.grep_match_count(), 1);
+  }
+}
+
+mod runtime_rs_tests
+{
+  use crossterm::event::{
+    Event,
+    KeyCode,
+    KeyEvent,
+    KeyModifiers,
+  };
+
+  #[test]
+  fn process_event_quit_returns_true()
+  {
+    let mut app = lsv::app::App::new().expect("app new");
+    let quit = lsv::runtime_util::process_event(
+      &mut app,
+      Event::Key(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE)),
+    )
+    .unwrap();
+    assert!(quit);
+  }
+
+  #[test]
+  fn process_event_resize_returns_false()
+  {
+    let mut app = lsv::app::App::new().expect("app new");
+    let cont =
+      lsv::runtime_util::process_event(&mut app, Event::Resize(80, 24))
+        .unwrap();
+    assert!(!cont);
+  }
+}
+
+mod util_rs_tests
+{
+  #[test]
+  fn sanitize_line_expands_tabs_and_strips_cr_and_controls()
+  {
+    let input = "a\tb\rc\x07d"; // tab, CR, bell
+    let out = lsv::util::sanitize_line(input);
+    // tab -> 4 spaces, CR removed, control -> space
+    assert_eq!(out, "a    bc d");
+  }
+}
+mod partial_return_tests
+{
+  #[test]
+  fn partial_overlay_return_is_merged_safely()
+  {
+    // Action returns only a tiny table; validator should still see a full
+    // config
+    let code = r#"
+lsv.map_action('x', 'Partial', function(lsv, config)
+  return { ui = { display_mode = 'friendly' } }
+end)
+"#;
+    let (_cfg, maps, engine_opt) =
+      lsv::config::load_config_from_code(code, None).expect("load with action");
+    let (engine, _prev, keys) = engine_opt.expect("engine present");
+    let mut app = lsv::app::App::new().expect("app new");
+    app.inject_lua_engine_for_tests(engine, keys);
+    app.set_keymaps(maps);
+    // Call the action via its mapped command (covers merge path too)
+    let action = app.get_keymap_action("x").expect("binding for x");
+    let ran =
+      lsv::actions::dispatch_action(&mut app, &action).expect("dispatch lua");
+    assert!(ran);
+    // Display mode should be Friendly after overlay is applied
+    assert!(matches!(app.get_display_mode(), lsv::app::DisplayMode::Friendly));
+  }
+}
+
+mod lua_glue_tests
+{
+  use std::fs;
+
+  use super::{
+    keys,
+    press,
+  };
+
+  fn make_app_with_actions(
+    lua_src: &str,
+    _seq: &str,
+  ) -> lsv::app::App
+  {
+    let (_cfg, maps, engine_opt) =
+      lsv::config::load_config_from_code(lua_src, None).expect("load lua");
+    let (engine, _prev, keys) = engine_opt.expect("engine");
+    let mut app = lsv::app::App::new().expect("app new");
+    app.inject_lua_engine_for_tests(engine, keys);
+    app.set_keymaps(maps);
+    app
+  }
+
+  #[test]
+  fn lsv_select_item_sets_selection_effect()
+  {
+    // Two files to exercise selection reliably
+    let temp = tempfile::tempdir().expect("tempdir");
+    let dir = temp.path();
+    fs::write(dir.join("x.txt"), b"x").unwrap();
+    fs::write(dir.join("y.txt"), b"y").unwrap();
+    let code = r#"
+lsv.map_action('sel', 'Select first', function(lsv, config)
+  lsv.select_item(0)
+end)
+"#;
+    let mut app = make_app_with_actions(code, "sel");
+    app.set_cwd(dir);
+    let action = app.get_keymap_action("sel").expect("binding");
+    let ran =
+      lsv::actions::dispatch_action(&mut app, &action).expect("dispatch");
+    assert!(ran);
+    assert_eq!(app.get_list_selected_index(), Some(0));
+  }
+
+  #[test]
+  fn lsv_quit_sets_quit_effect()
+  {
+    let code = r#"
+lsv.map_action('q', 'Quit', function(lsv, config)
+  lsv.quit()
+end)
+"#;
+    let mut app = make_app_with_actions(code, "q");
+    let action = app.get_keymap_action("q").expect("binding");
+    let ran =
+      lsv::actions::dispatch_action(&mut app, &action).expect("dispatch");
+    assert!(ran);
+    assert!(app.get_quit());
+  }
+
+  #[test]
+  fn lsv_display_output_sets_output_overlay()
+  {
+    let code = r#"
+lsv.map_action('o', 'Output', function(lsv, config)
+  lsv.display_output('Body', 'Title')
+end)
+"#;
+    let mut app = make_app_with_actions(code, "o");
+    let action = app.get_keymap_action("o").expect("binding");
+    let ran =
+      lsv::actions::dispatch_action(&mut app, &action).expect("dispatch");
+    assert!(ran);
+    assert!(app.get_show_output());
+    assert_eq!(app.get_output_title(), "Title");
+    assert!(app.get_output_text().contains("Body"));
+  }
+
+  #[test]
+  fn lsv_os_run_captures_env_name()
+  {
+    // Prepare a known selection name
+    let temp = tempfile::tempdir().expect("tempdir");
+    let dir = temp.path();
+    fs::write(dir.join("hello.txt"), b"hi").unwrap();
+    let code = r#"
+lsv.map_action('r', 'Run', function(lsv, config)
+  -- Compose the command from ctx; no placeholder expansion
+  local name = (config.context and config.context.current_file_name) or ''
+  lsv.os_run('echo ' .. lsv.quote(name))
+end)
+"#;
+    let mut app = make_app_with_actions(code, "r");
+    app.set_cwd(dir);
+    // Ensure selection is the file we just wrote
+    let pos = (0..100)
+      .find(|&i| app.get_current_entry_name(i).as_deref() == Some("hello.txt"))
+      .expect("find hello");
+    app.select_index(pos);
+    let action = app.get_keymap_action("r").expect("binding");
+    let ran =
+      lsv::actions::dispatch_action(&mut app, &action).expect("dispatch");
+    assert!(ran);
+    assert!(app.get_output_text().contains("hello.txt"));
+  }
+
+  #[test]
+  fn lsv_select_last_item_goes_to_end()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let dir = temp.path();
+    fs::write(dir.join("a"), b"a").unwrap();
+    fs::write(dir.join("b"), b"b").unwrap();
+    let code = r#"
+lsv.map_action('last', 'Last', function(lsv, config)
+  lsv.select_last_item()
+end)
+"#;
+    let mut app = make_app_with_actions(code, "last");
+    app.set_cwd(dir);
+    let action = app.get_keymap_action("last").expect("binding");
+    let ran =
+      lsv::actions::dispatch_action(&mut app, &action).expect("dispatch");
+    assert!(ran);
+    if app.current_has_entries()
+    {
+      // last index should be selected
+      let last = (0..100)
+        .position(|i| app.get_entry(i).is_none())
+        .unwrap_or(0)
+        .saturating_sub(1);
+      assert_eq!(app.get_list_selected_index(), Some(last));
+    }
+  }
+
+  #[test]
+  fn mutate_config_and_return_nil_still_applies_overlay()
+  {
+    let code = r#"
+lsv.map_action('friendly', 'Friendly', function(lsv, config)
+  config.ui = config.ui or {}
+  config.ui.display_mode = 'friendly'
+  return nil  -- rely on mutation
+end)
+"#;
+    let mut app = make_app_with_actions(code, "friendly");
+    let action = app.get_keymap_action("friendly").expect("binding");
+    let ran =
+      lsv::actions::dispatch_action(&mut app, &action).expect("dispatch");
+    assert!(ran);
+    assert!(matches!(app.get_display_mode(), lsv::app::DisplayMode::Friendly));
+  }
+
+  #[test]
+  fn return_effects_only_toggle_messages_and_output()
+  {
+    let code = r#"
+lsv.map_action('ov', 'Overlays', function(lsv, config)
+  return { messages = 'toggle', output = 'show' }
+end)
+"#;
+    let mut app = make_app_with_actions(code, "ov");
+    let action = app.get_keymap_action("ov").expect("binding");
+    let ran =
+      lsv::actions::dispatch_action(&mut app, &action).expect("dispatch");
+    assert!(ran);
+    assert!(app.get_show_output());
+    assert!(!app.get_show_messages());
+  }
+
+  #[test]
+  fn display_output_default_title_when_missing()
+  {
+    let code = r#"
+lsv.map_action('od', 'Output default', function(lsv, config)
+  lsv.display_output('Body')
+end)
+"#;
+    let mut app = make_app_with_actions(code, "od");
+    let action = app.get_keymap_action("od").expect("binding");
+    let ran =
+      lsv::actions::dispatch_action(&mut app, &action).expect("dispatch");
+    assert!(ran);
+    assert_eq!(app.get_output_title(), "Output");
+  }
+
+  #[test]
+  fn e_mapping_passes_path_to_command()
+  {
+    use std::fs;
+    let temp = tempfile::tempdir().expect("tempdir");
+    let dir = temp.path();
+    // Use a path with space to verify quoting
+    let fname = "hello world.txt";
+    let fpath = dir.join(fname);
+    fs::write(&fpath, b"content").unwrap();
+    let code = r#"
+local function shquote(s)
+  return "'" .. tostring(s):gsub("'", "'\\''") .. "'"
+end
+lsv.map_action('e', 'Edit', function(lsv, config)
+  local path = (config.context and config.context.current_file) or "."
+  -- Simulate editor by printing the argument we pass
+  lsv.os_run("printf 'EDIT:%s' " .. shquote(path))
+end)
+"#;
+    let mut app = make_app_with_actions(code, "e");
+    app.set_cwd(dir);
+    // Select our file
+    let pos = (0..100)
+      .find(|&i| app.get_current_entry_name(i).as_deref() == Some(fname))
+      .expect("find file");
+    app.select_index(pos);
+    let action = app.get_keymap_action("e").expect("binding");
+    let ran =
+      lsv::actions::dispatch_action(&mut app, &action).expect("dispatch");
+    assert!(ran);
+    let abs = fpath.to_string_lossy();
+    assert!(app.get_show_output());
+    assert!(app.get_output_text().contains(&*abs));
+  }
+
+  #[test]
+  fn lsv_fs_reads_at_once_and_queues_changes_as_jobs()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let dir = temp.path();
+    fs::write(dir.join("a.txt"), b"A").unwrap();
+    fs::write(dir.join("b.txt"), b"BB").unwrap();
+    let code = r#"
+lsv.map_action('f', 'Files', function(lsv, config)
+  local names = {}
+  for _, e in ipairs(lsv.fs.read_dir()) do
+    names[#names + 1] = e.name
+  end
+  local st = lsv.fs.stat("b.txt")
+  assert(lsv.fs.mkdir("out/deep"))
+  assert(lsv.fs.copy({ "a.txt", "b.txt" }, "out/deep"))
+  assert(lsv.fs.move("a.txt", "moved.txt"))
+  lsv.display_output(table.concat(names, ",") .. " " .. st.size .. " " .. tostring(st.is_file))
+end)
+"#;
+    let mut app = make_app_with_actions(code, "f");
+    app.set_cwd(dir);
+    let action = app.get_keymap_action("f").expect("binding");
+    lsv::actions::dispatch_action(&mut app, &action).expect("dispatch");
+    assert_eq!(app.get_output_text(), "a.txt,b.txt 2 true");
+    app.finish_jobs();
+    assert_eq!(fs::read(dir.join("out/deep/a.txt")).unwrap(), b"A");
+    assert_eq!(fs::read(dir.join("out/deep/b.txt")).unwrap(), b"BB");
+    assert!(!dir.join("a.txt").exists());
+    assert_eq!(fs::read(dir.join("moved.txt")).unwrap(), b"A");
+  }
+
+  #[test]
+  fn lsv_fs_refuses_unsafe_changes_and_reports_them()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let dir = temp.path();
+    fs::create_dir(dir.join("sub")).unwrap();
+    fs::write(dir.join("keep.txt"), b"K").unwrap();
+    let code = r#"
+lsv.map_action('r', 'Remove', function(lsv, config)
+  local ok, err = lsv.fs.remove("..")
+  assert(ok == nil and err:find("current directory"))
+  assert(lsv.fs.copy("sub", "sub/inner") == nil)
+  assert(lsv.fs.stat("missing") == nil)
+  assert(lsv.fs.remove("keep.txt"))
+end)
+"#;
+    let mut app = make_app_with_actions(code, "r");
+    let action = app.get_keymap_action("r").expect("binding");
+    app.set_cwd(dir);
+    lsv::actions::dispatch_action(&mut app, &action).expect("dispatch");
+    app.finish_jobs();
+    assert!(dir.exists() && dir.join("sub").is_dir());
+    assert!(!dir.join("sub/inner").exists());
+    assert!(!dir.join("keep.txt").exists());
+    let errors: Vec<&str> = app
+      .messages()
+      .iter()
+      .filter(|m| m.level == lsv::app::MessageLevel::Error)
+      .map(|m| m.text.as_str())
+      .collect();
+    assert_eq!(errors.len(), 2, "{errors:?}");
+    assert!(errors[0].starts_with("lsv.fs.remove:"));
+    assert!(errors[1].starts_with("lsv.fs.copy:"));
+  }
+
+  #[test]
+  fn lsv_fs_jobs_cannot_be_forged_from_lua()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let dir = temp.path();
+    fs::write(dir.join("keep.txt"), b"K").unwrap();
+    let code = r#"
+lsv.map_action('x', 'Forge', function(lsv, config)
+  local job = { op = "remove", items = { config.context.cwd } }
+  config.fs_jobs = { job }
+  return { fs_jobs = { job } }
+end)
+"#;
+    let mut app = make_app_with_actions(code, "x");
+    app.set_cwd(dir);
+    let action = app.get_keymap_action("x").expect("binding");
+    lsv::actions::dispatch_action(&mut app, &action).expect("dispatch");
+    app.finish_jobs();
+    assert!(dir.join("keep.txt").exists());
+  }
+
+  #[test]
+  fn lsv_ui_prompt_answers_its_callback()
+  {
+    use crossterm::event::KeyCode;
+    let temp = tempfile::tempdir().expect("tempdir");
+    let dir = temp.path();
+    let code = r#"
+lsv.map_action('n', 'New dir', function(lsv, config)
+  lsv.ui.prompt("Directory name:", "new", function(name)
+    if name then
+      lsv.fs.mkdir(name)
+      lsv.show_message("made " .. name)
+    else
+      lsv.show_message("cancelled")
+    end
+  end)
+end)
+"#;
+    let mut app = make_app_with_actions(code, "n");
+    app.set_cwd(dir);
+    let action = app.get_keymap_action("n").expect("binding");
+    lsv::actions::dispatch_action(&mut app, &action).expect("dispatch");
+    keys(&mut app, "_one");
+    press(&mut app, KeyCode::Enter);
+    assert!(dir.join("new_one").is_dir());
+
+    lsv::actions::dispatch_action(&mut app, &action).expect("dispatch");
+    press(&mut app, KeyCode::Esc);
+    let texts: Vec<&str> =
+      app.messages().iter().map(|m| m.text.as_str()).collect();
+    assert!(texts.ends_with(&["made new_one", "cancelled"]), "{texts:?}");
+  }
+
+  #[test]
+  fn lsv_ui_confirm_and_select_chain_callbacks()
+  {
+    use crossterm::event::KeyCode;
+    let code = r#"
+lsv.map_action('p', 'Pick', function(lsv, config)
+  lsv.ui.confirm("Pick a fruit?", function(yes)
+    if not yes then
+      lsv.display_output("no", "Pick")
+      return
+    end
+    lsv.ui.select({ "apple", "banana", "cherry" }, function(item, index)
+      lsv.display_output(tostring(item) .. "@" .. tostring(index), "Pick")
+    end, { title = "Fruit" })
+  end)
+end)
+"#;
+    let mut app = make_app_with_actions(code, "p");
+    let action = app.get_keymap_action("p").expect("binding");
+    lsv::actions::dispatch_action(&mut app, &action).expect("dispatch");
+    assert_eq!(app.confirm_question(), Some("Pick a fruit?"));
+    press(&mut app, KeyCode::Char('y'));
+    assert_eq!(app.select_results(), ["apple", "banana", "cherry"]);
+    keys(&mut app, "nan");
+    assert_eq!(app.select_results(), ["banana"]);
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.get_output_text(), "banana@2");
+
+    lsv::actions::dispatch_action(&mut app, &action).expect("dispatch");
+    press(&mut app, KeyCode::Char('y'));
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.get_output_text(), "nil@nil");
+
+    lsv::actions::dispatch_action(&mut app, &action).expect("dispatch");
+    press(&mut app, KeyCode::Char('n'));
+    assert_eq!(app.get_output_text(), "no");
+  }
+}
+
+mod line_number_tests
+{
+  use crossterm::event::KeyCode;
+
+  use super::{
+    app_with,
+    app_with_config_in,
+    keys,
+    press,
+    screen,
+  };
+
+  const FILES: [&str; 12] = [
+    "f01", "f02", "f03", "f04", "f05", "f06", "f07", "f08", "f09", "f10",
+    "f11", "f12",
+  ];
+
+  const CONFIG: &str = r#"lsv.config({ ui = {
+  show_parent = false, show_preview = false, line_numbers = "%s",
+} })"#;
+
+  #[test]
+  fn absolute_numbers_prefix_rows()
+  {
+    let (temp, _) = app_with(&FILES);
+    let mut app =
+      app_with_config_in(&CONFIG.replace("%s", "absolute"), temp.path());
+    let screen = screen(&mut app, 60, 20);
+    assert!(screen.contains(" 1   f01"), "{}", screen);
+    assert!(screen.contains("12   f12"), "{}", screen);
+  }
+
+  #[test]
+  fn relative_numbers_count_from_cursor()
+  {
+    let (temp, _) = app_with(&FILES);
+    let mut app =
+      app_with_config_in(&CONFIG.replace("%s", "relative"), temp.path());
+    app.select_index(4);
+    let screen = screen(&mut app, 60, 20);
+    assert!(screen.contains(" 5   f05"), "{}", screen);
+    assert!(screen.contains(" 1   f04"));
+    assert!(screen.contains(" 4   f01"));
+    assert!(screen.contains(" 7   f12"));
+  }
+
+  #[test]
+  fn count_prefix_repeats_motions()
+  {
+    let (_temp, mut app) = app_with(&FILES);
+    app.select_index(0);
+    keys(&mut app, "5j");
+    assert_eq!(app.get_list_selected_index(), Some(5));
+    keys(&mut app, "2k");
+    assert_eq!(app.get_list_selected_index(), Some(3));
+    keys(&mut app, "j");
+    assert_eq!(app.get_list_selected_index(), Some(4));
+    keys(&mut app, "40j");
+    assert_eq!(app.get_list_selected_index(), Some(11));
+  }
+
+  #[test]
+  fn count_prefix_picks_row_for_g_and_gg()
+  {
+    let (_temp, mut app) = app_with(&FILES);
+    keys(&mut app, "10G");
+    assert_eq!(app.get_list_selected_index(), Some(9));
+    keys(&mut app, "3gg");
+    assert_eq!(app.get_list_selected_index(), Some(2));
+    keys(&mut app, "G");
+    assert_eq!(app.get_list_selected_index(), Some(11));
+  }
+
+  #[test]
+  fn escape_drops_pending_count()
+  {
+    let (_temp, mut app) = app_with(&FILES);
+    app.select_index(0);
+    keys(&mut app, "4");
+    press(&mut app, KeyCode::Esc);
+    keys(&mut app, "j");
+    assert_eq!(app.get_list_selected_index(), Some(1));
+  }
+}
+
+mod count_prefix_tests
+{
+  use super::{
+    app_with,
+    app_with_config_in,
+    keys,
+    screen,
+  };
+
+  const ACTIONS: &str = r#"
+lsv.map_action("X", "Show count", function(lsv, config)
+  lsv.display_output(tostring(config.context.count), "count")
+end)
+"#;
+
+  const FILES: [&str; 5] = ["a", "b", "c", "d", "e"];
+
+  #[test]
+  fn count_toggles_that_many_rows()
+  {
+    let (temp, _) = app_with(&FILES);
+    let mut app = app_with_config_in(ACTIONS, temp.path());
+    app.select_index(1);
+    keys(&mut app, "3 ");
+    let mut names: Vec<String> = app
+      .get_selected_paths()
+      .iter()
+      .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+      .collect();
+    names.sort();
+    assert_eq!(names, ["b", "c", "d"]);
+    assert_eq!(app.get_list_selected_index(), Some(1));
+  }
+
+  #[test]
+  fn lua_actions_see_the_count()
+  {
+    let (temp, _) = app_with(&FILES);
+    let mut app = app_with_config_in(ACTIONS, temp.path());
+    app.select_index(1);
+    keys(&mut app, "12X");
+    assert_eq!(app.get_output_text(), "12");
+    keys(&mut app, "X");
+    assert_eq!(app.get_output_text(), "nil");
+  }
+
+  #[test]
+  fn count_repeats_tab_switches()
+  {
+    let (temp, _) = app_with(&FILES);
+    let mut app = app_with_config_in(ACTIONS, temp.path());
+    app.select_index(1);
+    keys(&mut app, "tntn");
+    assert_eq!(app.active_tab_index(), 2);
+    keys(&mut app, "2gT");
+    assert_eq!(app.active_tab_index(), 0);
+  }
+
+  #[test]
+  fn pending_count_shows_in_header_and_which_key()
+  {
+    let (temp, _) = app_with(&FILES);
+    let mut app = app_with_config_in(ACTIONS, temp.path());
+    app.select_index(1);
+    keys(&mut app, "42g");
+    let shown = screen(&mut app, 100, 30);
+    let header = shown.lines().next().unwrap();
+    assert!(header.contains("42g"), "header: {}", header);
+    assert!(shown.contains("(count 42)"), "{}", shown);
+    // The count applies to the finished sequence, then clears
+    keys(&mut app, "g");
+    assert_eq!(app.get_list_selected_index(), Some(4));
+    let shown = screen(&mut app, 100, 30);
+    let header = shown.lines().next().unwrap();
+    assert!(!header.contains("42g"), "header: {}", header);
+  }
+}
+
+mod macro_tests
+{
+  use crossterm::event::KeyCode;
+
+  use super::{
+    app_with,
+    keys,
+    press,
+    screen,
+  };
+
+  fn selected(app: &lsv::app::App) -> Vec<String>
+  {
+    let mut names: Vec<String> = app
+      .get_selected_paths()
+      .iter()
+      .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+      .collect();
+    names.sort();
+    names
+  }
+
+  #[test]
+  fn recorded_actions_replay_with_count()
+  {
+    let (_temp, mut app) = app_with(&["a", "b", "c", "d", "e", "f"]);
+    app.select_index(0);
+    keys(&mut app, "Qa");
+    assert!(screen(&mut app, 100, 10).contains("recording @a"));
+    keys(&mut app, " j");
+    keys(&mut app, "Q");
+    assert!(!screen(&mut app, 100, 10).contains("recording"));
+    assert_eq!(selected(&app), ["a"]);
+    assert_eq!(app.get_list_selected_index(), Some(1));
+
+    keys(&mut app, "2@a");
+    assert_eq!(selected(&app), ["a", "b", "c"]);
+    assert_eq!(app.get_list_selected_index(), Some(3));
+    keys(&mut app, "@@");
+    assert_eq!(selected(&app), ["a", "b", "c", "d"]);
+    assert_eq!(app.get_list_selected_index(), Some(4));
+  }
+
+  #[test]
+  fn counts_and_command_lines_are_recorded()
+  {
+    let (_temp, mut app) = app_with(&["a", "b", "c", "d", "e", "f"]);
+    app.select_index(0);
+    keys(&mut app, "Qb2j:select_toggle");
+    press(&mut app, KeyCode::Enter);
+    keys(&mut app, "Q");
+    assert_eq!(selected(&app), ["c"]);
+    keys(&mut app, "@b");
+    assert_eq!(selected(&app), ["c", "e"]);
+    assert_eq!(app.get_list_selected_index(), Some(4));
+  }
+
+  #[test]
+  fn empty_register_reports_and_does_nothing()
+  {
+    let (_temp, mut app) = app_with(&["a", "b", "c", "d", "e", "f"]);
+    app.select_index(0);
+    keys(&mut app, "@z");
+    assert!(selected(&app).is_empty());
+    assert_eq!(app.get_list_selected_index(), Some(0));
+  }
+}
+
+mod whichkey_tests
+{
+  use crossterm::event::KeyCode;
+
+  use super::{
+    app_with_config,
+    press,
+    screen,
+  };
+
+  #[test]
+  fn overlay_names_groups_and_merges_keys_for_one_action()
+  {
+    let mut app = app_with_config(
+      r#"
+lsv.config({ keys = { groups = { ["<leader>"] = "Mine" } } })
+lsv.mapkey("<leader>a", "cmd:find_files")
+"#,
+    );
+    press(&mut app, KeyCode::Char('?'));
+    let screen = screen(&mut app, 160, 40);
+    assert!(screen.contains("s  +Sort"), "{}", screen);
+    assert!(screen.contains("z  +View"), "{}", screen);
+    assert!(screen.contains("\\  +Mine"), "{}", screen);
+    assert!(screen.contains("j, Down  Move down"), "{}", screen);
+    assert!(screen.contains("Enter  Open or enter"), "{}", screen);
+  }
+
+  #[test]
+  fn prefix_lists_next_keys_with_fallback_labels()
+  {
+    let mut app = app_with_config(r#"lsv.mapkey("zq", "cmd:find_files")"#);
+    press(&mut app, KeyCode::Char('z'));
+    let screen = screen(&mut app, 160, 40);
+    assert!(screen.contains("Keys: prefix 'z'"), "{}", screen);
+    assert!(screen.contains("q  find files"), "{}", screen);
+    assert!(screen.contains("h  Toggle Show Hidden"), "{}", screen);
+    // Lowercase keys sort ahead of their uppercase forms
+    let lower = screen.find("a  Display: absolute").expect("za");
+    let upper = screen.find("A  Compress into an archive").expect("zA");
+    assert!(lower < upper, "{}", screen);
+  }
+}
+
+mod palette_tests
+{
+  use crossterm::event::{
+    KeyCode,
+    KeyEvent,
+    KeyModifiers,
+  };
+
+  use super::{
+    app_with_config,
+    press,
+  };
+
+  const CONFIG: &str = r#"
+lsv.map_action("<leader>h", "Say hello", function(lsv, config)
+  lsv.display_output("hello", "Greeting")
+end)
+lsv.register_command("greet", function(lsv, config) end, { complete = { "world" } })
+"#;
+
+  fn open_palette(app: &mut lsv::app::App)
+  {
+    lsv::input::handle_key(
+      app,
+      KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL),
+    )
+    .unwrap();
+  }
+
+  #[test]
+  fn palette_lists_actions_and_runs_the_selected_one()
+  {
+    let mut app = app_with_config(CONFIG);
+    open_palette(&mut app);
+    let all = app.palette_results();
+    assert!(all.contains(&"Say hello".to_string()));
+    // Unmapped commands are listed too, but not the palette itself
+    assert!(all.contains(&"reload config".to_string()), "{:?}", all);
+    // One entry per action, whether spelled sort:name or :sort name
+    assert!(!all.contains(&"sort name".to_string()));
+    assert!(all.contains(&"greet".to_string()));
+    assert!(!all.iter().any(|l| l == "Command palette" || l == "palette"));
+
+    for ch in "sayhel".chars()
+    {
+      press(&mut app, KeyCode::Char(ch));
+    }
+    assert_eq!(
+      app.palette_results().first().map(String::as_str),
+      Some("Say hello")
+    );
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.get_output_text(), "hello");
+  }
+
+  #[test]
+  fn commands_needing_arguments_open_the_command_pane()
+  {
+    let mut app = app_with_config(CONFIG);
+    for (query, line) in [("greet", "greet "), ("grep", "grep ")]
+    {
+      open_palette(&mut app);
+      app.set_palette_query(query);
+      assert_eq!(
+        app.palette_results().first().map(String::as_str),
+        Some(query)
+      );
+      press(&mut app, KeyCode::Enter);
+      assert_eq!(app.command_input(), Some(line));
+      press(&mut app, KeyCode::Esc);
+    }
+  }
+
+  #[test]
+  fn escape_closes_without_running()
+  {
+    let mut app = app_with_config(CONFIG);
+    open_palette(&mut app);
+    app.set_palette_query("say hello");
+    press(&mut app, KeyCode::Esc);
+    assert!(app.palette_results().is_empty());
+    assert_eq!(app.get_output_text(), "");
+  }
+}
+
+mod help_tests
+{
+  use crossterm::event::KeyCode;
+  use ratatui::{
+    Terminal,
+    backend::TestBackend,
+  };
+
+  use super::{
+    app_with_config,
+    press,
+  };
+
+  const CONFIG: &str = r#"
+lsv.map_action("<leader>x", "Do the thing", function(lsv, config) end)
+lsv.register_command("greet", function(lsv, config) end, { complete = { "world" } })
+"#;
+
+  #[test]
+  fn help_lists_bindings_actions_and_commands()
+  {
+    let mut app = app_with_config(CONFIG);
+    lsv::actions::dispatch_action(&mut app, "cmd:help").unwrap();
+    let lines = app.help_lines().expect("help open").to_vec();
+    for heading in ["Keys", "Actions", "Commands"]
+    {
+      assert!(lines.iter().any(|l| l == heading), "{}", heading);
+    }
+    let has = |needle: &str| lines.iter().any(|l| l.contains(needle));
+    assert!(has("Do the thing (Lua)"), "{:#?}", lines);
+    assert!(has("Ctrl-p") && has("Command palette (cmd:palette)"));
+    assert!(has(":sort <name|size|mtime|created|natural|extension>"));
+    assert!(has(":checksum [md5|sha1|sha256]"));
+    assert!(has(":greet [world] (Lua)"));
+    for (name, _) in lsv::actions::internal::ACTIONS
+    {
+      assert!(has(name), "{}", name);
+    }
+  }
+
+  #[test]
+  fn overridden_binding_is_listed_once()
+  {
+    let mut app = app_with_config(CONFIG);
+    let (_cfg, maps, _eng) = lsv::config::load_config_from_code(
+      r#"lsv.mapkey("gf", "cmd:trash", "Mine")"#,
+      None,
+    )
+    .expect("load");
+    app.set_keymaps(maps);
+    lsv::actions::dispatch_action(&mut app, "cmd:help").unwrap();
+    let lines = app.help_lines().unwrap();
+    let gf: Vec<&String> =
+      lines.iter().filter(|l| l.trim_start().starts_with("gf ")).collect();
+    assert_eq!(gf.len(), 1, "{:?}", gf);
+    assert!(gf[0].contains("Mine (cmd:trash)"));
+  }
+
+  #[test]
+  fn every_listed_action_runs()
+  {
+    for (name, _) in lsv::actions::internal::ACTIONS
+    {
+      if name.starts_with("cmd:")
+      {
+        continue;
+      }
+      let mut app = lsv::app::App::new().expect("app new");
+      let ran = lsv::actions::dispatch_action(&mut app, name).unwrap();
+      assert!(ran, "{} did not run", name);
+    }
+  }
+
+  #[test]
+  fn help_scrolls_and_closes()
+  {
+    let mut app = app_with_config(CONFIG);
+    press(&mut app, KeyCode::F(1));
+    let total = app.help_lines().expect("help open").len();
+    let mut term = Terminal::new(TestBackend::new(100, 30)).unwrap();
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+
+    press(&mut app, KeyCode::Char('j'));
+    assert_eq!(app.help_offset(), Some(1));
+    press(&mut app, KeyCode::Char('G'));
+    // 80% of 30 rows less the border
+    assert_eq!(app.help_offset(), Some(total - 22));
+    press(&mut app, KeyCode::Char('g'));
+    assert_eq!(app.help_offset(), Some(0));
+    press(&mut app, KeyCode::PageDown);
+    assert_eq!(app.help_offset(), Some(22));
+    term.draw(|f| lsv::ui::draw(f, &mut app)).unwrap();
+    let buf = term.backend().buffer();
+    let screen: String = (0..buf.area.height)
+      .flat_map(|y| (0..buf.area.width).map(move |x| (x, y)))
+      .map(|(x, y)| buf[(x, y)].symbol().to_string())
+      .collect();
+    assert!(screen.contains(&format!("23-44 of {}", total)), "{}", screen);
+
+    press(&mut app, KeyCode::Char('q'));
+    assert!(app.help_lines().is_none());
+  }
+}
+
+mod text_view_tests
+{
+  use crossterm::event::KeyCode;
+
+  use super::{
+    keys,
+    press,
+    screen,
+  };
+
+  fn app_with_output() -> lsv::app::App
+  {
+    let mut app = lsv::app::App::new().expect("app new");
+    let text: Vec<String> = (0..100).map(|i| format!("line {}", i)).collect();
+    app.display_output("Long", &text.join("\n"));
+    // 60% of 30 rows less the border: 16 lines in view
+    screen(&mut app, 80, 30);
+    app
+  }
+
+  #[test]
+  fn output_opens_at_the_end_and_scrolls()
+  {
+    let mut app = app_with_output();
+    assert_eq!(app.text_view_offset(), 84);
+    keys(&mut app, "gg");
+    assert_eq!(app.text_view_offset(), 0);
+    assert!(screen(&mut app, 80, 30).contains("1-16 of 100"));
+    keys(&mut app, "j");
+    assert_eq!(app.text_view_offset(), 1);
+    press(&mut app, KeyCode::PageDown);
+    assert_eq!(app.text_view_offset(), 17);
+    press(&mut app, KeyCode::PageUp);
+    assert_eq!(app.text_view_offset(), 1);
+    keys(&mut app, "Gk");
+    assert_eq!(app.text_view_offset(), 83);
+    // Scroll keys do not reach the file list behind
+    assert!(app.get_show_output());
+  }
+
+  #[test]
+  fn search_jumps_between_matching_lines()
+  {
+    let mut app = app_with_output();
+    keys(&mut app, "gg/LINE 4");
+    assert!(screen(&mut app, 80, 30).contains("/LINE 4"));
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.text_view_match(), Some(4));
+    keys(&mut app, "n");
+    assert_eq!(app.text_view_match(), Some(40));
+    assert_eq!(app.text_view_offset(), 38);
+    keys(&mut app, "NN");
+    assert_eq!(app.text_view_match(), Some(49));
+    assert!(app.text_view_offset() <= 49 && 49 < app.text_view_offset() + 16);
+
+    let before = app.recent_messages_len();
+    keys(&mut app, "/nothing");
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.text_view_match(), None);
+    assert_eq!(app.recent_messages_len(), before + 1);
+  }
+
+  #[test]
+  fn other_keys_reach_the_keymap_and_q_closes()
+  {
+    let mut app = lsv::app::App::new().expect("app new");
+    for i in 0..40
+    {
+      app.add_message(&format!("message {}", i));
+    }
+    lsv::actions::dispatch_action(&mut app, "cmd:messages").unwrap();
+    screen(&mut app, 80, 30);
+    keys(&mut app, "gg");
+    assert_eq!(app.text_view_offset(), 0);
+    // `zm` goes through the keymap and opens the messages afresh, at the end
+    keys(&mut app, "zm");
+    assert!(app.get_show_messages());
+    assert!(app.text_view_offset() > 0);
+    keys(&mut app, "q");
+    assert!(!app.get_show_messages());
+    assert!(!app.get_quit());
+  }
+
+  #[test]
+  fn yank_reports_the_copy()
+  {
+    let mut app = app_with_output();
+    let before = app.recent_messages_len();
+    keys(&mut app, "y");
+    assert_eq!(app.recent_messages_len(), before + 1);
+  }
+}
+
+mod message_log_tests
+{
+  use lsv::app::MessageLevel;
+
+  use super::app_with_config;
+
+  #[test]
+  fn lua_helpers_record_levels()
+  {
+    let code = r#"
+lsv.map_action("zw", "Warn", function(lsv, config)
+  lsv.show_message("hello")
+  lsv.show_warning("careful")
+end)
+lsv.map_action("ze", "Fail", function(lsv, config)
+  lsv.show_error("broken")
+end)
+"#;
+    let mut app = app_with_config(code);
+    lsv::dispatch_action(&mut app, "run_lua:0").unwrap();
+    lsv::dispatch_action(&mut app, "run_lua:1").unwrap();
+    let got: Vec<(MessageLevel, &str)> =
+      app.messages().iter().map(|m| (m.level, m.text.as_str())).collect();
+    assert_eq!(
+      got,
+      vec![
+        (MessageLevel::Info, "hello"),
+        (MessageLevel::Warn, "careful"),
+        (MessageLevel::Error, "broken"),
+      ]
+    );
+    // The panel line carries the time and the level
+    let line = app.messages()[2].line();
+    assert!(line.ends_with(" ERROR broken"), "line: {}", line);
+    assert_eq!(line.as_bytes()[2], b':');
+  }
+
+  #[test]
+  fn log_keeps_the_newest_max_messages()
+  {
+    let (cfg, _maps, _engine) = lsv::config::load_config_from_code(
+      "lsv.config({ ui = { max_messages = 3 } })",
+      None,
+    )
+    .expect("load");
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.clear_recent_messages();
+    for i in 0..5
+    {
+      app.add_message(&format!("message {}", i));
+    }
+    let texts: Vec<&str> =
+      app.messages().iter().map(|m| m.text.as_str()).collect();
+    assert_eq!(texts, vec!["message 2", "message 3", "message 4"]);
+  }
+
+  #[test]
+  fn messages_clear_and_save()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    app.clear_recent_messages();
+    app.add_message("first");
+    app.add_error("second");
+
+    lsv::dispatch_action(&mut app, "cmd:messages save log.txt").unwrap();
+    let saved = std::fs::read_to_string(temp.path().join("log.txt")).unwrap();
+    let lines: Vec<&str> = saved.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with(" INFO  first"), "saved: {}", saved);
+    assert!(lines[1].ends_with(" ERROR second"), "saved: {}", saved);
+    // Full date first: YYYY-MM-DD HH:MM:SS
+    assert_eq!(lines[0].as_bytes()[4], b'-');
+    assert_eq!(
+      app.messages().last().map(|m| m.text.starts_with("Saved 2 message(s)")),
+      Some(true)
+    );
+
+    lsv::dispatch_action(&mut app, "cmd:messages clear").unwrap();
+    assert_eq!(app.recent_messages_len(), 0);
+
+    lsv::dispatch_action(&mut app, "cmd:messages bogus").unwrap();
+    assert_eq!(app.messages()[0].level, MessageLevel::Warn);
+  }
+}
+
+mod notify_tests
+{
+  use std::fs;
+
+  use lsv::app::MessageLevel;
+
+  use super::{
+    app_with_config,
+    screen,
+  };
+
+  #[test]
+  fn lua_notify_shows_a_note_and_logs_it()
+  {
+    let code = r#"
+lsv.map_action("zn", "Notify", function(lsv, config)
+  lsv.notify("disk almost full", "warn")
+end)
+"#;
+    let mut app = app_with_config(code);
+    lsv::dispatch_action(&mut app, "run_lua:0").unwrap();
+    let notes = app.notifications();
+    assert_eq!(notes.len(), 1);
+    assert_eq!(notes[0].level, MessageLevel::Warn);
+    assert_eq!(notes[0].text, "disk almost full");
+    let last = app.messages().last().unwrap();
+    assert_eq!(
+      (last.level, last.text.as_str()),
+      (MessageLevel::Warn, "disk almost full")
+    );
+
+    // The note sits in the bottom-right corner
+    let shown = screen(&mut app, 80, 20);
+    let row = shown.lines().nth(18).unwrap();
+    assert!(row.trim_end().ends_with("disk almost full │"), "row: {}", row);
+  }
+
+  #[test]
+  fn notes_expire_and_can_be_turned_off()
+  {
+    let mut app = app_with_config("lsv.config({ ui = { notify_ms = 1 } })");
+    app.notify(MessageLevel::Info, "short");
+    assert_eq!(app.notifications().len(), 1);
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    assert!(app.poll_notifications());
+    assert!(app.notifications().is_empty());
+
+    let mut app = app_with_config("lsv.config({ ui = { notify_ms = 0 } })");
+    let before = app.recent_messages_len();
+    app.notify(MessageLevel::Error, "logged only");
+    assert!(app.notifications().is_empty());
+    assert_eq!(app.recent_messages_len(), before + 1);
+  }
+
+  #[test]
+  fn finished_jobs_post_a_note()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let src = temp.path().join("src");
+    let dst = temp.path().join("dst");
+    fs::create_dir_all(&src).unwrap();
+    fs::create_dir_all(&dst).unwrap();
+    fs::write(src.join("a.txt"), b"A").unwrap();
+
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(&src);
+    lsv::actions::dispatch_action(&mut app, "cmd:select_toggle").unwrap();
+    lsv::actions::dispatch_action(&mut app, "clipboard:copy").unwrap();
+    app.set_cwd(&dst);
+    lsv::actions::dispatch_action(&mut app, "clipboard:paste").unwrap();
+    app.finish_jobs();
+    let notes = app.notifications();
+    assert_eq!(notes.len(), 1);
+    assert_eq!(notes[0].level, MessageLevel::Info);
+    assert!(notes[0].text.starts_with("Copy done"), "note: {}", notes[0].text);
+  }
+}
+
+#[cfg(unix)]
+mod run_tests
+{
+  use crossterm::event::{
+    KeyCode,
+    KeyEvent,
+    KeyModifiers,
+  };
+
+  fn app_in_temp() -> (tempfile::TempDir, lsv::app::App)
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    (temp, app)
+  }
+
+  #[test]
+  fn run_streams_output_and_reports_the_exit()
+  {
+    let (temp, mut app) = app_in_temp();
+    lsv::dispatch_action(
+      &mut app,
+      "cmd:run echo out && echo err >&2 && touch made && exit 3",
+    )
+    .unwrap();
+    assert!(app.get_show_output());
+    assert_eq!(
+      app.get_output_title(),
+      "$ echo out && echo err >&2 && touch made && exit 3"
+    );
+    app.finish_command_run();
+    let text = app.get_output_text();
+    assert!(text.contains("out") && text.contains("err"), "text: {}", text);
+    assert_eq!(app.command_run_status().as_deref(), Some("exit 3"));
+    assert!(temp.path().join("made").exists());
+
+    // :output closes the panel and brings the last run back
+    lsv::dispatch_action(&mut app, "cmd:output").unwrap();
+    assert!(!app.get_show_output());
+    lsv::dispatch_action(&mut app, "cmd:output").unwrap();
+    assert!(app.get_output_text().contains("out"));
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn run_fills_in_placeholders_quoted()
+  {
+    let (temp, mut app) = app_in_temp();
+    let dir = temp.path().canonicalize().unwrap();
+    std::fs::write(dir.join("it's b.md"), b"x").unwrap();
+    app.set_cwd(&dir);
+    app.finish_loading();
+    lsv::dispatch_action(&mut app, "cmd:select_toggle").unwrap();
+    lsv::dispatch_action(&mut app, "clipboard:copy").unwrap();
+    lsv::dispatch_action(
+      &mut app,
+      "cmd:run printf '%s|' {file} {files} {basename} {ext} {dir} {clipboard} \
+       {env:HOME} '{x}' {file",
+    )
+    .unwrap();
+    app.finish_command_run();
+    let file = dir.join("it's b.md");
+    let home = std::env::var("HOME").unwrap_or_default();
+    assert_eq!(
+      app.get_output_text(),
+      format!(
+        "{f}|{f}|it's b.md|md|{d}|{f}|{h}|{{x}}|{{file|",
+        f = file.display(),
+        d = dir.display(),
+        h = home
+      )
+    );
+  }
+
+  fn wait_for(cond: impl Fn() -> bool) -> bool
+  {
+    let start = std::time::Instant::now();
+    while !cond() && start.elapsed().as_secs() < 5
+    {
+      std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    cond()
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn mapped_commands_run_where_their_mode_says()
+  {
+    let code = r#"
+lsv.map_command("go", "echo out {basename}; echo second")
+lsv.map_command({ "gb", "gB" }, "touch bg-made", { mode = "background" })
+lsv.map_command("gp", "echo one; echo two", { mode = "preview", description = "Two lines" })
+lsv.config({
+  actions = {
+    { keymap = "gt", cmd = "echo \"$1\" > term-made", mode = "terminal" },
+  },
+})
+"#;
+    let (cfg, maps, _engine) =
+      lsv::config::load_config_from_code(code, None).expect("load");
+    let (temp, mut app) = app_in_temp();
+    let dir = temp.path();
+    std::fs::write(dir.join("a.txt"), b"a").unwrap();
+    app.set_config(cfg);
+    app.set_keymaps(maps);
+    app.set_cwd(dir);
+    app.finish_loading();
+    let run = |app: &mut lsv::app::App, keys: &str| {
+      let action = app.get_keymap_action(keys).expect("binding");
+      assert!(lsv::dispatch_action(app, &action).unwrap(), "{}", keys);
+    };
+
+    run(&mut app, "go");
+    app.finish_command_run();
+    assert_eq!(app.get_output_text(), "out a.txt\nsecond");
+
+    run(&mut app, "gB");
+    assert!(wait_for(|| dir.join("bg-made").exists()));
+
+    app.select_index(0);
+    run(&mut app, "gp");
+    assert!(app.is_preview_process_running() || app.preview_line_count() > 0);
+    let start = std::time::Instant::now();
+    while app.preview_line_count() < 2 && start.elapsed().as_secs() < 5
+    {
+      app.poll_running_preview();
+      std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+    assert_eq!(app.preview_line_count(), 2);
+
+    run(&mut app, "gt");
+    let written = std::fs::read_to_string(dir.join("term-made")).unwrap();
+    assert_eq!(written.trim(), dir.join("a.txt").display().to_string());
+
+    let bad = r#"lsv.map_command("gz", "true", { mode = "sideways" })"#;
+    assert!(lsv::config::load_config_from_code(bad, None).is_err());
+  }
+
+  #[test]
+  fn ctrl_c_cancels_the_running_command()
+  {
+    let (_temp, mut app) = app_in_temp();
+    lsv::dispatch_action(&mut app, "cmd:run echo started && sleep 30").unwrap();
+    assert!(app.is_command_running());
+    assert_eq!(
+      app.command_run_status().as_deref(),
+      Some("running, Ctrl-c cancels")
+    );
+    let start = std::time::Instant::now();
+    while !app.get_output_text().contains("started")
+      && start.elapsed().as_secs() < 5
+    {
+      app.poll_command_run();
+      std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    assert_eq!(app.get_output_text(), "started");
+
+    lsv::input::handle_key(
+      &mut app,
+      KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL),
+    )
+    .unwrap();
+    assert!(!app.is_command_running());
+    assert_eq!(app.command_run_status().as_deref(), Some("cancelled"));
+    assert!(app.get_show_output());
+  }
+}
+
+mod batch_confirm_tests
+{
+  use std::fs;
+
+  use crossterm::event::KeyCode;
+
+  use super::{
+    press,
+    screen,
+  };
+
+  #[test]
+  fn delete_lists_paths_counts_and_size()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    fs::create_dir(temp.path().join("dir")).unwrap();
+    fs::write(temp.path().join("dir").join("inner.txt"), vec![0u8; 1000])
+      .unwrap();
+    fs::write(temp.path().join("a.txt"), vec![0u8; 24]).unwrap();
+    fs::write(temp.path().join("b.txt"), b"").unwrap();
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    lsv::actions::dispatch_action(&mut app, "cmd:select_all").unwrap();
+
+    lsv::actions::dispatch_action(&mut app, "cmd:delete").unwrap();
+    let question = app.confirm_question().unwrap().to_string();
+    assert!(question.starts_with("Delete 3 selected items?"), "{}", question);
+    assert!(
+      question.contains("1 directory and 2 files, 3 files and 1.0 KB in total"),
+      "{}",
+      question
+    );
+    let paths: Vec<String> = ["a.txt", "b.txt", "dir"]
+      .iter()
+      .map(|n| temp.path().join(n).display().to_string())
+      .collect();
+    assert_eq!(app.confirm_details(), Some(&paths[..]));
+    assert!(screen(&mut app, 100, 20).contains("b.txt"));
+
+    // Scrolling keeps the dialog open; `n` cancels
+    press(&mut app, KeyCode::Char('j'));
+    assert!(app.confirm_details().is_some());
+    press(&mut app, KeyCode::Char('n'));
+    assert!(app.confirm_details().is_none());
+    assert!(temp.path().join("dir").exists());
+
+    lsv::actions::dispatch_action(&mut app, "cmd:delete").unwrap();
+    press(&mut app, KeyCode::Char('y'));
+    app.finish_jobs();
+    assert!(!temp.path().join("dir").exists());
+    assert!(!temp.path().join("a.txt").exists());
+  }
+
+  #[test]
+  fn bulk_renames_are_listed_before_they_run()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    for name in ["one.txt", "two.txt"]
+    {
+      fs::write(temp.path().join(name), name).unwrap();
+    }
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    let items = vec![temp.path().join("one.txt"), temp.path().join("two.txt")];
+
+    app.confirm_rename_list(&items, "1.txt\n2.txt\n");
+    let lines = ["one.txt -> 1.txt".to_string(), "two.txt -> 2.txt".into()];
+    assert_eq!(app.confirm_details(), Some(&lines[..]));
+    assert!(temp.path().join("one.txt").exists());
+    press(&mut app, KeyCode::Char('y'));
+    assert!(temp.path().join("1.txt").exists());
+    assert!(temp.path().join("2.txt").exists());
+
+    // A single rename needs no confirmation
+    let items = vec![temp.path().join("1.txt"), temp.path().join("2.txt")];
+    app.confirm_rename_list(&items, "first.txt\n2.txt\n");
+    assert!(app.confirm_details().is_none());
+    assert!(temp.path().join("first.txt").exists());
+  }
+}
+
+mod selections_tests
+{
+  use std::fs;
+
+  use crossterm::event::KeyCode;
+
+  use super::{
+    press,
+    screen,
+  };
+
+  /// An app in `root/b` with `a/one.txt`, `a/two.txt` and `b/three.txt`
+  /// selected.
+  fn setup(root: &std::path::Path) -> lsv::app::App
+  {
+    for (dir, name) in [("a", "one.txt"), ("a", "two.txt"), ("b", "three.txt")]
     {
-      // last index should be selected
-      let last = (0..100)
-        .position(|i| app.get_entry(i).is_none())
-        .unwrap_or(0)
-        .saturating_sub(1);
-      assert_eq!(app.get_list_selected_index(), Some(last));
+      fs::create_dir_all(root.join(dir)).unwrap();
+      fs::write(root.join(dir).join(name), name).unwrap();
     }
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(&root.join("a"));
+    app.finish_loading();
+    lsv::actions::dispatch_action(&mut app, "cmd:select_all").unwrap();
+    app.set_cwd(&root.join("b"));
+    app.finish_loading();
+    lsv::actions::dispatch_action(&mut app, "cmd:select_all").unwrap();
+    app
   }
 
   #[test]
-  fn mutate_config_and_return_nil_still_applies_overlay()
+  fn lists_selections_from_every_directory()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let mut app = setup(temp.path());
+    lsv::actions::dispatch_action(&mut app, "cmd:selections").unwrap();
+    let text = screen(&mut app, 100, 24);
+    assert!(text.contains("Selections (3)"), "{}", text);
+    assert!(text.contains(&temp.path().join("a").display().to_string()));
+    assert!(text.contains(&temp.path().join("b").display().to_string()));
+    for name in ["one.txt", "two.txt", "three.txt"]
+    {
+      assert!(text.contains(name), "{}", text);
+    }
+    assert_eq!(
+      app.selections_current(),
+      Some(temp.path().join("a").join("one.txt"))
+    );
+  }
+
+  #[test]
+  fn enter_jumps_to_the_selected_path()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let mut app = setup(temp.path());
+    lsv::actions::dispatch_action(&mut app, "cmd:selections").unwrap();
+    press(&mut app, KeyCode::Char('j'));
+    press(&mut app, KeyCode::Enter);
+    app.finish_loading();
+    assert_eq!(app.get_cwd_path(), temp.path().join("a"));
+    let idx = app.get_list_selected_index().unwrap();
+    assert_eq!(app.get_current_entry_name(idx).as_deref(), Some("two.txt"));
+    assert_eq!(app.selections_current(), None);
+    // Jumping keeps the selection
+    assert_eq!(app.get_selected_paths().len(), 3);
+  }
+
+  #[test]
+  fn unselect_and_clear()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let mut app = setup(temp.path());
+    lsv::actions::dispatch_action(&mut app, "cmd:selections").unwrap();
+    press(&mut app, KeyCode::Char('d'));
+    assert_eq!(
+      app.get_selected_paths(),
+      vec![
+        temp.path().join("a").join("two.txt"),
+        temp.path().join("b").join("three.txt")
+      ]
+    );
+    assert_eq!(
+      app.selections_current(),
+      Some(temp.path().join("a").join("two.txt"))
+    );
+    press(&mut app, KeyCode::Char('c'));
+    assert!(app.get_selected_paths().is_empty());
+    assert_eq!(app.selections_current(), None);
+
+    // Nothing selected: the overlay does not open
+    lsv::actions::dispatch_action(&mut app, "cmd:selections").unwrap();
+    assert_eq!(app.selections_current(), None);
+  }
+}
+
+mod registers_tests
+{
+  use std::fs;
+
+  use crossterm::event::KeyCode;
+
+  use super::{
+    keys,
+    press,
+    screen,
+  };
+
+  /// `src` with `one.txt` and `two.txt`, and empty `dst1` and `dst2`.
+  fn setup(root: &std::path::Path) -> lsv::app::App
+  {
+    fs::create_dir(root.join("src")).unwrap();
+    for dir in ["dst1", "dst2"]
+    {
+      fs::create_dir(root.join(dir)).unwrap();
+    }
+    fs::write(root.join("src").join("one.txt"), "1").unwrap();
+    fs::write(root.join("src").join("two.txt"), "2").unwrap();
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_cwd(&root.join("src"));
+    app.finish_loading();
+    app
+  }
+
+  #[test]
+  fn named_registers_stage_separate_sets()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let root = temp.path();
+    let mut app = setup(root);
+
+    // one.txt into "a, two.txt into the unnamed clipboard
+    app.select_index(0);
+    lsv::dispatch_action(&mut app, "cmd:select_toggle").unwrap();
+    keys(&mut app, "\"ac");
+    lsv::dispatch_action(&mut app, "cmd:select_clear").unwrap();
+    app.select_index(1);
+    lsv::dispatch_action(&mut app, "cmd:select_toggle").unwrap();
+    keys(&mut app, "c");
+    let a = app.register(Some('a')).unwrap();
+    assert_eq!(a.items, vec![root.join("src").join("one.txt")]);
+    assert_eq!(
+      app.register(None).unwrap().items,
+      vec![root.join("src").join("two.txt")]
+    );
+
+    // A copied register can be pasted more than once
+    for dir in ["dst1", "dst2"]
+    {
+      app.set_cwd(&root.join(dir));
+      app.finish_loading();
+      keys(&mut app, "\"av");
+      app.finish_jobs();
+      assert!(root.join(dir).join("one.txt").exists());
+      assert!(!root.join(dir).join("two.txt").exists());
+    }
+    assert!(app.register(Some('a')).is_some());
+
+    keys(&mut app, "v");
+    app.finish_jobs();
+    assert!(root.join("dst2").join("two.txt").exists());
+    assert!(app.register(None).is_none());
+  }
+
+  #[test]
+  fn move_register_empties_after_paste()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let root = temp.path();
+    let mut app = setup(root);
+    lsv::dispatch_action(&mut app, "cmd:select_all").unwrap();
+    keys(&mut app, "\"bx");
+    assert_eq!(app.active_register(), None);
+    app.set_cwd(&root.join("dst1"));
+    app.finish_loading();
+    keys(&mut app, "\"bv");
+    app.finish_jobs();
+    assert!(root.join("dst1").join("one.txt").exists());
+    assert!(!root.join("src").join("one.txt").exists());
+    assert!(app.register(Some('b')).is_none());
+
+    keys(&mut app, "\"bv");
+    let last = app.messages().last().unwrap().text.clone();
+    assert_eq!(last, "Paste: register \"b is empty");
+  }
+
+  #[test]
+  fn overlay_lists_pastes_and_clears_registers()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let root = temp.path();
+    let mut app = setup(root);
+    lsv::dispatch_action(&mut app, "cmd:select_all").unwrap();
+    keys(&mut app, "\"zc");
+    lsv::dispatch_action(&mut app, "cmd:registers").unwrap();
+    let text = screen(&mut app, 100, 24);
+    assert!(text.contains("Registers"), "{}", text);
+    assert!(text.contains("\"z  copy   2  one.txt, two.txt"), "{}", text);
+    assert_eq!(app.registers_current(), Some('z'));
+
+    app.set_cwd(&root.join("dst1"));
+    app.finish_loading();
+    lsv::dispatch_action(&mut app, "cmd:registers").unwrap();
+    press(&mut app, KeyCode::Enter);
+    app.finish_jobs();
+    assert!(root.join("dst1").join("two.txt").exists());
+
+    lsv::dispatch_action(&mut app, "cmd:registers").unwrap();
+    press(&mut app, KeyCode::Char('d'));
+    assert!(app.register(Some('z')).is_none());
+    assert!(app.register_list().is_empty());
+  }
+}
+
+mod lua_filter_tests
+{
+  use std::fs;
+
+  use super::{
+    app_with_config_in,
+    screen,
+  };
+
+  fn names(app: &lsv::app::App) -> Vec<String>
+  {
+    (0..).map_while(|i| app.get_current_entry_name(i)).collect::<Vec<_>>()
+  }
+
+  #[test]
+  fn filter_hides_entries_everywhere()
   {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let root = temp.path();
+    fs::create_dir_all(root.join("proj").join("build")).unwrap();
+    fs::create_dir_all(root.join("proj").join("src")).unwrap();
+    fs::write(root.join("proj").join("main.o"), "").unwrap();
+    fs::write(root.join("proj").join("main.c"), "").unwrap();
+    fs::write(root.join("proj").join("src").join("lib.c"), "").unwrap();
+    fs::write(root.join("proj").join("build").join("out.bin"), "").unwrap();
     let code = r#"
-lsv.map_action('friendly', 'Friendly', function(lsv, config)
-  config.ui = config.ui or {}
-  config.ui.display_mode = 'friendly'
-  return nil  -- rely on mutation
+lsv.filter(function(e, dir)
+  if e.extension == "o" then return false end
+  return not (e.is_dir and e.name == "build")
 end)
 "#;
-    let mut app = make_app_with_actions(code, "friendly");
-    let action = app.get_keymap_action("friendly").expect("binding");
-    let ran =
-      lsv::actions::dispatch_action(&mut app, &action).expect("dispatch");
-    assert!(ran);
-    assert!(matches!(app.get_display_mode(), lsv::app::DisplayMode::Friendly));
+    let mut app = app_with_config_in(code, &root.join("proj"));
+    assert_eq!(names(&app), vec!["src", "main.c"]);
+
+    // The flat view leaves out what is below a hidden directory
+    lsv::dispatch_action(&mut app, "cmd:toggle_flatten").unwrap();
+    app.finish_loading();
+    let flat = names(&app);
+    assert!(flat.iter().any(|n| n.ends_with("lib.c")), "{:?}", flat);
+    assert!(!flat.iter().any(|n| n.contains("out.bin")), "{:?}", flat);
+    assert!(!flat.iter().any(|n| n.contains("main.o")), "{:?}", flat);
+    lsv::dispatch_action(&mut app, "cmd:toggle_flatten").unwrap();
+
+    // Directory previews are filtered too
+    app.set_cwd(root);
+    app.finish_loading();
+    app.select_index(0);
+    let text = screen(&mut app, 100, 20);
+    assert!(text.contains("main.c"), "{}", text);
+    assert!(!text.contains("main.o"), "{}", text);
+    assert!(!text.contains("build"), "{}", text);
   }
 
   #[test]
-  fn return_effects_only_toggle_messages_and_output()
+  fn filter_can_depend_on_the_directory()
   {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let root = temp.path();
+    for dir in ["keep", "tidy"]
+    {
+      fs::create_dir(root.join(dir)).unwrap();
+      fs::write(root.join(dir).join("a.log"), "").unwrap();
+      fs::write(root.join(dir).join("b.txt"), "").unwrap();
+    }
     let code = r#"
-lsv.map_action('ov', 'Overlays', function(lsv, config)
-  return { messages = 'toggle', output = 'show' }
+lsv.filter(function(e, dir)
+  if dir:match("tidy$") and e.extension == "log" then return false end
 end)
 "#;
-    let mut app = make_app_with_actions(code, "ov");
-    let action = app.get_keymap_action("ov").expect("binding");
-    let ran =
-      lsv::actions::dispatch_action(&mut app, &action).expect("dispatch");
-    assert!(ran);
-    assert!(app.get_show_output());
-    assert!(!app.get_show_messages());
+    let mut app = app_with_config_in(code, &root.join("tidy"));
+    assert_eq!(names(&app), vec!["b.txt"]);
+    app.set_cwd(&root.join("keep"));
+    app.finish_loading();
+    assert_eq!(names(&app), vec!["a.log", "b.txt"]);
   }
+}
+
+#[cfg(unix)]
+mod meta_cache_tests
+{
+  use std::{
+    fs,
+    os::unix::fs::PermissionsExt,
+  };
+
+  use super::{
+    app_with_config_in,
+    screen,
+  };
 
   #[test]
-  fn display_output_default_title_when_missing()
+  fn metadata_is_read_once_until_refresh()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    for (name, mode) in [("a.sh", 0o755), ("b.txt", 0o644)]
+    {
+      let path = temp.path().join(name);
+      fs::write(&path, "x").unwrap();
+      fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+    }
+    let code = r#"
+lsv.config({ ui = {
+  header = { left = "[{current_file_permissions}] [{owner}]", right = "" },
+} })
+"#;
+    let (cfg, _maps, _eng) =
+      lsv::config::load_config_from_code(code, None).expect("load");
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.set_cwd(temp.path());
+    app.finish_loading();
+
+    // The listing read the mode and owner, so drawing stats nothing
+    let s = screen(&mut app, 120, 8);
+    assert!(s.contains("[-rwxr-xr-x]"), "{}", s);
+    assert!(!s.contains("[-:-]"), "{}", s);
+    assert_eq!(app.meta_cache_len(), 0);
+
+    // A refresh reads the metadata again
+    let path = temp.path().join("a.sh");
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    let s = screen(&mut app, 120, 8);
+    assert!(s.contains("[-rw-------]"), "{}", s);
+  }
+
+  #[test]
+  fn entries_listed_without_metadata_are_read_once()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    for name in ["a.sh", "b.txt"]
+    {
+      fs::write(temp.path().join(name), "x").unwrap();
+    }
+    let code = r#"
+lsv.config({ ui = {
+  header = { left = "{cwd}", right = "" },
+  theme = { exec_fg = "green" },
+} })
+"#;
+    let mut app = app_with_config_in(code, temp.path());
+
+    // Rows check the executable bit of each entry once
+    screen(&mut app, 120, 8);
+    let cached = app.meta_cache_len();
+    assert!(cached >= 2, "{}", cached);
+    screen(&mut app, 120, 8);
+    assert_eq!(app.meta_cache_len(), cached);
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    assert_eq!(app.meta_cache_len(), 0);
+  }
+
+  #[test]
+  fn lua_entries_carry_mode_and_links()
   {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let path = temp.path().join("f.txt");
+    fs::write(&path, "x").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+    fs::hard_link(&path, temp.path().join("g.txt")).unwrap();
     let code = r#"
-lsv.map_action('od', 'Output default', function(lsv, config)
-  lsv.display_output('Body')
+lsv.define_column("stat", function(e)
+  if not e.mode then return "none" end
+  return string.format("%o/%d", e.mode % 4096, e.nlink)
 end)
+lsv.config({ ui = { show = "size", row = { left = "{name} <{stat}>", right = "" } } })
 "#;
-    let mut app = make_app_with_actions(code, "od");
-    let action = app.get_keymap_action("od").expect("binding");
-    let ran =
-      lsv::actions::dispatch_action(&mut app, &action).expect("dispatch");
-    assert!(ran);
-    assert_eq!(app.get_output_title(), "Output");
+    let (cfg, _maps, engine_opt) =
+      lsv::config::load_config_from_code(code, None).expect("load");
+    let (engine, _prev, keys) = engine_opt.expect("engine present");
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.inject_lua_engine_for_tests(engine, keys);
+    app.set_cwd(temp.path());
+    app.finish_loading();
+    let s = screen(&mut app, 120, 8);
+    assert!(s.contains("f.txt <640/2>"), "{}", s);
+  }
+}
+
+mod virtual_rows_tests
+{
+  use std::fs;
+
+  use super::screen;
+
+  /// An app in `root/big` (500 files) next to `root/other` (500 files),
+  /// whose rows count in Lua how many entries of each were drawn.
+  fn setup(root: &std::path::Path) -> lsv::app::App
+  {
+    for dir in ["big", "other"]
+    {
+      fs::create_dir(root.join(dir)).unwrap();
+      for i in 0..500
+      {
+        fs::write(root.join(dir).join(format!("f{:03}", i)), "").unwrap();
+      }
+    }
+    let code = r#"
+drawn = {}
+lsv.define_column("count", function(e)
+  drawn[e.dir] = (drawn[e.dir] or 0) + 1
+  return ""
+end)
+lsv.register_command("drawn", function(lsv, config)
+  lsv.show_message(tostring(drawn[config.context.args_line] or 0))
+end)
+lsv.config({ ui = { row = { left = "{name}{count}", right = "" } } })
+"#;
+    let (cfg, _maps, engine_opt) =
+      lsv::config::load_config_from_code(code, None).expect("load");
+    let (engine, _prev, keys) = engine_opt.expect("engine present");
+    let mut app = lsv::app::App::new().expect("app new");
+    app.set_config(cfg);
+    app.inject_lua_engine_for_tests(engine, keys);
+    app.set_cwd(&root.join("big"));
+    app.finish_loading();
+    app
+  }
+
+  fn drawn(
+    app: &mut lsv::app::App,
+    dir: &std::path::Path,
+  ) -> usize
+  {
+    let cmd = format!("cmd:drawn {}", dir.display());
+    lsv::dispatch_action(app, &cmd).unwrap();
+    app.messages().last().unwrap().text.parse().unwrap()
   }
 
   #[test]
-  fn e_mapping_passes_path_to_command()
+  fn only_visible_rows_are_built()
   {
-    use std::fs;
     let temp = tempfile::tempdir().expect("tempdir");
-    let dir = temp.path();
-    // Use a path with space to verify quoting
-    let fname = "hello world.txt";
-    let fpath = dir.join(fname);
-    fs::write(&fpath, b"content").unwrap();
+    let root = temp.path();
+    let mut app = setup(root);
+    let s = screen(&mut app, 120, 20);
+    assert!(s.contains("f000"), "{}", s);
+    let n = drawn(&mut app, &root.join("big"));
+    assert!(n > 0 && n <= 20, "{}", n);
+
+    // Jumping to the end scrolls the window there
+    lsv::dispatch_action(&mut app, "nav:bottom").unwrap();
+    let s = screen(&mut app, 120, 20);
+    assert!(s.contains("f499"), "{}", s);
+    assert!(!s.contains("f000"), "{}", s);
+    assert_eq!(app.get_list_selected_index(), Some(499));
+    let n = drawn(&mut app, &root.join("big"));
+    assert!(n <= 40, "{}", n);
+  }
+
+  #[test]
+  fn directory_preview_reads_a_screenful()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let root = temp.path();
+    let mut app = setup(root);
+    app.set_cwd(root);
+    app.finish_loading();
+    let idx = (0..)
+      .find(|i| app.get_current_entry_name(*i).as_deref() == Some("other"))
+      .unwrap();
+    app.select_index(idx);
+    let s = screen(&mut app, 120, 20);
+    assert!(s.contains("f000"), "{}", s);
+    let n = drawn(&mut app, &root.join("other"));
+    assert!(n > 0 && n <= 20, "{}", n);
+  }
+}
+
+#[cfg(unix)]
+mod running_preview_tests
+{
+  use std::{
+    fs,
+    thread::sleep,
+    time::{
+      Duration,
+      Instant,
+    },
+  };
+
+  fn app_with_stream(temp: &std::path::Path) -> lsv::app::App
+  {
     let code = r#"
-local function shquote(s)
-  return "'" .. tostring(s):gsub("'", "'\\''") .. "'"
-end
-lsv.map_action('e', 'Edit', function(lsv, config)
-  local path = (config.context and config.context.current_file) or "."
-  -- Simulate editor by printing the argument we pass
-  lsv.os_run("printf 'EDIT:%s' " .. shquote(path))
+lsv.map_action('tp', 'Stream into preview', function(lsv, config)
+  config.preview_run_cmd = "echo one; echo two; sleep 5; echo late"
+  return config
 end)
 "#;
-    let mut app = make_app_with_actions(code, "e");
-    app.set_cwd(dir);
-    // Select our file
-    let pos = (0..100)
-      .find(|&i| app.get_current_entry_name(i).as_deref() == Some(fname))
-      .expect("find file");
-    app.select_index(pos);
-    let action = app.get_keymap_action("e").expect("binding");
-    let ran =
-      lsv::actions::dispatch_action(&mut app, &action).expect("dispatch");
-    assert!(ran);
-    let abs = fpath.to_string_lossy();
-    assert!(app.get_show_output());
-    assert!(app.get_output_text().contains(&*abs));
+    let (_cfg, maps, engine_opt) =
+      lsv::config::load_config_from_code(code, None).expect("load");
+    let (engine, _prev, keys) = engine_opt.expect("engine present");
+    fs::write(temp.join("a.txt"), b"a").unwrap();
+    fs::write(temp.join("b.txt"), b"b").unwrap();
+    let mut app = lsv::app::App::new().expect("app new");
+    app.inject_lua_engine_for_tests(engine, keys);
+    app.set_keymaps(maps);
+    app.set_cwd(temp);
+    app.finish_loading();
+    app.select_index(0);
+    let action = app.get_keymap_action("tp").expect("binding for tp");
+    lsv::actions::dispatch_action(&mut app, &action).expect("dispatch");
+    let started = Instant::now();
+    while app.preview_line_count() < 2
+      && started.elapsed() < Duration::from_secs(3)
+    {
+      app.poll_running_preview();
+      sleep(Duration::from_millis(5));
+    }
+    app
+  }
+
+  #[test]
+  fn output_streams_while_the_selection_stays()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let mut app = app_with_stream(temp.path());
+    assert!(app.is_preview_process_running());
+    assert_eq!(app.preview_line_count(), 2);
+
+    // Redrawing or refreshing for the same entry leaves it running
+    app.select_index(0);
+    assert!(app.is_preview_process_running());
+    assert_eq!(app.preview_line_count(), 2);
+  }
+
+  #[test]
+  fn moving_on_stops_the_command()
+  {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let mut app = app_with_stream(temp.path());
+    assert!(app.is_preview_process_running());
+
+    app.select_index(1);
+    assert!(!app.is_preview_process_running());
+    // b.txt's own preview, with nothing from the stopped command
+    assert!(!app.poll_running_preview());
+    assert_eq!(app.preview_line_count(), 1);
   }
 }
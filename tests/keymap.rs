@@ -0,0 +1,162 @@
+use crossterm::event::{
+  KeyCode,
+  KeyEvent,
+  KeyModifiers,
+};
+use lsv::keymap::{
+  key_token,
+  normalize_sequence,
+};
+
+#[test]
+fn key_token_names_keys_without_characters()
+{
+  assert_eq!(
+    key_token(KeyCode::F(5), KeyModifiers::NONE).as_deref(),
+    Some("<F5>")
+  );
+  assert_eq!(
+    key_token(KeyCode::Tab, KeyModifiers::NONE).as_deref(),
+    Some("<Tab>")
+  );
+  assert_eq!(
+    key_token(KeyCode::BackTab, KeyModifiers::SHIFT).as_deref(),
+    Some("<Sh-Tab>")
+  );
+  assert_eq!(
+    key_token(KeyCode::Down, KeyModifiers::CONTROL).as_deref(),
+    Some("<C-Down>")
+  );
+  assert_eq!(
+    key_token(KeyCode::Enter, KeyModifiers::NONE).as_deref(),
+    Some("<CR>")
+  );
+  assert_eq!(
+    key_token(KeyCode::Backspace, KeyModifiers::ALT).as_deref(),
+    Some("<M-BS>")
+  );
+  assert_eq!(
+    key_token(KeyCode::Char(' '), KeyModifiers::NONE).as_deref(),
+    Some(" ")
+  );
+  assert_eq!(key_token(KeyCode::Esc, KeyModifiers::NONE), None);
+}
+
+#[test]
+fn normalize_sequence_accepts_aliases_and_any_case()
+{
+  assert_eq!(normalize_sequence("<Space>ff"), " ff");
+  assert_eq!(normalize_sequence("<c-space>"), "<C- >");
+  assert_eq!(normalize_sequence("<Enter>"), "<CR>");
+  assert_eq!(normalize_sequence("<return>"), "<CR>");
+  assert_eq!(normalize_sequence("<Backspace>"), "<BS>");
+  assert_eq!(normalize_sequence("<c-down>"), "<C-Down>");
+  assert_eq!(normalize_sequence("<f12>"), "<F12>");
+  assert_eq!(normalize_sequence("g<S-Tab>"), "g<S-Tab>");
+  assert_eq!(normalize_sequence("<Sh-Tab>"), "<Sh-Tab>");
+  // Existing spellings are kept
+  assert_eq!(normalize_sequence("<C-e>"), "<C-e>");
+  assert_eq!(normalize_sequence("<Esc>"), "<Esc>");
+  assert_eq!(normalize_sequence("<C-->"), "<C-->");
+  assert_eq!(normalize_sequence("<leader>x"), "<leader>x");
+}
+
+fn app_with_keys(code: &str) -> lsv::app::App
+{
+  let temp = tempfile::tempdir().expect("tempdir");
+  for name in ["a", "b", "c"]
+  {
+    std::fs::write(temp.path().join(name), "x").unwrap();
+  }
+  let (cfg, maps, _eng) =
+    lsv::config::load_config_from_code(code, None).expect("load");
+  let mut app = lsv::app::App::new().expect("app new");
+  app.set_config(cfg);
+  app.set_keymaps(maps);
+  app.set_cwd(temp.path());
+  app.finish_loading();
+  app
+}
+
+#[test]
+fn named_keys_trigger_their_mappings()
+{
+  let mut app = app_with_keys(
+    r#"
+lsv.mapkey("<F2>", "cmd:messages")
+lsv.mapkey("<Down>", "nav:bottom")
+lsv.mapkey("<Space>s", "sort:size")
+"#,
+  );
+  app.select_index(0);
+  lsv::input::handle_key(&mut app, KeyEvent::from(KeyCode::Down)).unwrap();
+  assert_eq!(app.get_list_selected_index(), Some(2));
+  // Unbound named keys keep their built-in behaviour
+  lsv::input::handle_key(&mut app, KeyEvent::from(KeyCode::Up)).unwrap();
+  assert_eq!(app.get_list_selected_index(), Some(1));
+  assert!(app.has_prefix(" "));
+  assert_eq!(app.get_keymap_action("<space>s").as_deref(), Some("sort:size"));
+  assert_eq!(app.get_keymap_action("<f2>").as_deref(), Some("cmd:messages"));
+}
+
+fn press(
+  app: &mut lsv::app::App,
+  code: KeyCode,
+) -> bool
+{
+  lsv::input::handle_key(app, KeyEvent::from(code)).unwrap()
+}
+
+#[test]
+fn navigation_keys_can_be_rebound()
+{
+  let mut app = app_with_keys(r#"lsv.mapkey("j", "nav:bottom")"#);
+  app.select_index(0);
+  press(&mut app, KeyCode::Char('j'));
+  assert_eq!(app.get_list_selected_index(), Some(2));
+  press(&mut app, KeyCode::Char('k'));
+  assert_eq!(app.get_list_selected_index(), Some(1));
+}
+
+#[test]
+fn defaults_off_leaves_only_user_keys()
+{
+  let mut app = app_with_keys(
+    r#"
+lsv.config({ keys = { defaults = false } })
+lsv.mapkey("<C-q>", "quit")
+lsv.mapkey("n", "nav:down")
+"#,
+  );
+  app.select_index(0);
+  assert!(app.get_keymap_action("gg").is_none());
+  assert!(!press(&mut app, KeyCode::Char('q')));
+  press(&mut app, KeyCode::Char('j'));
+  press(&mut app, KeyCode::Down);
+  assert_eq!(app.get_list_selected_index(), Some(0));
+  press(&mut app, KeyCode::Char('n'));
+  assert_eq!(app.get_list_selected_index(), Some(1));
+  let quit = lsv::input::handle_key(
+    &mut app,
+    KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL),
+  )
+  .unwrap();
+  assert!(quit);
+}
+
+#[test]
+fn leader_expands_at_load_time()
+{
+  let app = app_with_keys(
+    r#"
+lsv.config({ keys = { leader = "<Space>" } })
+lsv.mapkey("<leader>s", "sort:size")
+lsv.mapkey("<Leader><Leader>", "nav:top")
+"#,
+  );
+  assert_eq!(app.get_keymap_action(" s").as_deref(), Some("sort:size"));
+  assert_eq!(app.get_keymap_action("  ").as_deref(), Some("nav:top"));
+
+  let app = app_with_keys(r#"lsv.mapkey("<leader>x", "quit")"#);
+  assert_eq!(app.get_keymap_action("\\x").as_deref(), Some("quit"));
+}
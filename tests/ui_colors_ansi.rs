@@ -50,3 +50,54 @@ fn ansi_spans_bold_and_blue_then_unbold()
   let s1_bold = spans[1].style.add_modifier(Modifier::BOLD);
   assert_eq!(s1_bold, spans[0].style);
 }
+
+#[test]
+fn ansi_spans_256_and_truecolor_in_both_forms()
+{
+  use lsv::ui::ansi::ansi_spans;
+  let s = "\x1b[38;5;208mA\x1b[48;2;10;20;30mB\x1b[38:2::1:2:3;48:5:17mC";
+  let spans = ansi_spans(s);
+  assert_eq!(spans.len(), 3);
+  assert_eq!(spans[0].style.fg, Some(Color::Indexed(208)));
+  assert_eq!(spans[1].style.fg, Some(Color::Indexed(208)));
+  assert_eq!(spans[1].style.bg, Some(Color::Rgb(10, 20, 30)));
+  assert_eq!(spans[2].style.fg, Some(Color::Rgb(1, 2, 3)));
+  assert_eq!(spans[2].style.bg, Some(Color::Indexed(17)));
+}
+
+#[test]
+fn ansi_spans_attributes_and_per_attribute_resets()
+{
+  use lsv::ui::ansi::ansi_spans;
+  let s = "\x1b[2;3;4;7;9mA\x1b[22;27mB\x1b[23;24;29mC";
+  let spans = ansi_spans(s);
+  assert_eq!(spans.len(), 3);
+  let all = Modifier::DIM
+    | Modifier::ITALIC
+    | Modifier::UNDERLINED
+    | Modifier::REVERSED
+    | Modifier::CROSSED_OUT;
+  assert_eq!(spans[0].style.add_modifier, all);
+  assert_eq!(
+    spans[1].style.add_modifier,
+    Modifier::ITALIC | Modifier::UNDERLINED | Modifier::CROSSED_OUT
+  );
+  assert_eq!(spans[2].style, Style::default());
+}
+
+#[test]
+fn ansi_spans_default_colors_and_underline_color_are_skipped()
+{
+  use lsv::ui::ansi::ansi_spans;
+  // 58;5;n sets an underline colour, which must not leak into fg/bg
+  let s = "\x1b[31;44mA\x1b[39;58;5;196mB\x1b[49;4:0mC\x1b[1;;32mD";
+  let spans = ansi_spans(s);
+  assert_eq!(spans.len(), 4);
+  assert_eq!(spans[0].style.fg, Some(Color::Red));
+  assert_eq!(spans[0].style.bg, Some(Color::Blue));
+  assert_eq!(spans[1].style.fg, None);
+  assert_eq!(spans[1].style.bg, Some(Color::Blue));
+  assert_eq!(spans[2].style, Style::default());
+  // An empty parameter is a reset, so bold is dropped before green
+  assert_eq!(spans[3].style, Style::default().fg(Color::Green));
+}
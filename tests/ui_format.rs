@@ -49,3 +49,45 @@ fn format_time_ago_buckets()
   let s = format_time_ago(now - Duration::from_secs(86_400 * 800));
   assert!(s.ends_with("y ago"), "got: {}", s);
 }
+
+#[test]
+fn format_time_within_switches_to_absolute_past_threshold()
+{
+  use lsv::ui::format::format_time_within;
+  let now = SystemTime::now();
+  let s = format_time_within(now - Duration::from_secs(10_800), "%Y", 86_400);
+  assert_eq!(s, "3h ago");
+  let old = SystemTime::UNIX_EPOCH + Duration::from_secs(1_592_222_400);
+  assert_eq!(format_time_within(old, "%Y", 86_400), "2020");
+  assert!(format_time_within(old, "%Y", u64::MAX).ends_with("y ago"));
+}
+
+#[test]
+fn align_field_pads_and_truncates_by_display_width()
+{
+  use lsv::ui::format::align_field;
+  assert_eq!(align_field("abc", "<6").as_deref(), Some("abc   "));
+  assert_eq!(align_field("abc", "6").as_deref(), Some("abc   "));
+  assert_eq!(align_field("abc", ">6").as_deref(), Some("   abc"));
+  assert_eq!(align_field("abc", "^6").as_deref(), Some(" abc  "));
+  // Wide characters count as two columns
+  assert_eq!(align_field("日本", ">6").as_deref(), Some("  日本"));
+  // Width alone never cuts; `.N` does
+  assert_eq!(align_field("abcdef", "<4").as_deref(), Some("abcdef"));
+  assert_eq!(align_field("abcdef", "<6.4").as_deref(), Some("abc~  "));
+  assert_eq!(align_field("abcdef", ".3").as_deref(), Some("ab~"));
+  assert_eq!(align_field("abc", "x"), None);
+  assert_eq!(align_field("abc", "<"), None);
+  assert_eq!(align_field("abc", "<4.x"), None);
+}
+
+#[test]
+fn windows_attributes_read_like_powershell_modes()
+{
+  use lsv::ui::row::attributes_string;
+  assert_eq!(attributes_string(0x10), "d-----");
+  assert_eq!(attributes_string(0x20 | 0x1), "-ar---");
+  assert_eq!(attributes_string(0x20 | 0x2 | 0x4), "-a-hs-");
+  assert_eq!(attributes_string(0x10 | 0x400), "d----l");
+  assert_eq!(attributes_string(0), "------");
+}